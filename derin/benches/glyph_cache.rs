@@ -0,0 +1,56 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#![feature(test)]
+extern crate test;
+extern crate derin;
+extern crate derin_core;
+
+use derin::layout::{Margins, LayoutVertical};
+use derin::widgets::*;
+use derin::geometry::rect::DimsBox;
+use derin_core::Root;
+use derin_core::render::headless::{HeadlessRenderer, TextMetrics};
+use test::Bencher;
+
+const LABELS: usize = 256;
+
+fn labels_root() -> Root<Group<Vec<Label>, LayoutVertical>, HeadlessRenderer> {
+    let labels = Group::new(
+        (0..LABELS).map(|i| Label::new(Contents::Text(format!("The quick brown fox {}", i)))).collect(),
+        LayoutVertical::new(Margins::new(8, 8, 8, 8), Default::default())
+    );
+
+    let mut root = Root::new(labels, (), HeadlessRenderer::new(TextMetrics::default()), DimsBox::new2(512, 8192));
+    root.set_retained_rendering(false);
+    root.relayout();
+    root.redraw();
+    root
+}
+
+/// Redraw a window full of labels that share most of their glyphs. Only the first frame should
+/// rasterize anything, and only once per distinct glyph.
+#[bench]
+fn redraw_shared_glyphs(b: &mut Bencher) {
+    let mut root = labels_root();
+    let rasterized = root.renderer.glyphs_rasterized();
+
+    b.iter(|| root.render_to_image());
+
+    assert!(rasterized <= 64);
+    assert_eq!(rasterized, root.renderer.glyphs_rasterized());
+}
+
+/// The same as `redraw_shared_glyphs`, but with a cache too small to hold every glyph, so glyphs
+/// have to be rasterized again every frame.
+#[bench]
+fn redraw_shared_glyphs_evicting(b: &mut Bencher) {
+    let mut root = labels_root();
+    root.renderer.set_glyph_cache_capacity(4);
+    let rasterized = root.renderer.glyphs_rasterized();
+
+    b.iter(|| root.render_to_image());
+
+    assert!(root.renderer.glyphs_rasterized() > rasterized);
+}
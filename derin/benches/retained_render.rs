@@ -0,0 +1,92 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#![feature(test)]
+extern crate test;
+extern crate derin;
+extern crate derin_core;
+#[macro_use]
+extern crate derin_macros;
+
+use derin::layout::{Margins, LayoutVertical};
+use derin::widgets::*;
+use derin::geometry::rect::DimsBox;
+use derin_core::Root;
+use derin_core::render::headless::{HeadlessRenderer, TextMetrics};
+use test::Bencher;
+
+const ROWS: usize = 64;
+
+struct NoOp;
+impl ButtonHandler for NoOp {
+    fn on_click(&mut self) {}
+}
+impl CheckToggleHandler for NoOp {
+    fn change_state(&mut self, _: bool) {}
+}
+
+#[derive(WidgetContainer)]
+struct GalleryContainer {
+    progress_bar: ProgressBar,
+    #[derin(collection = "Label")]
+    labels: Vec<Label>,
+    #[derin(collection = "Button<NoOp>")]
+    buttons: Vec<Button<NoOp>>,
+    #[derin(collection = "CheckBox<NoOp>")]
+    check_boxes: Vec<CheckBox<NoOp>>,
+}
+
+type Gallery = Group<GalleryContainer, LayoutVertical>;
+
+fn gallery_root() -> Root<Gallery, HeadlessRenderer> {
+    let gallery = Group::new(
+        GalleryContainer {
+            progress_bar: ProgressBar::new(0.0, 0.0, 100.0),
+            labels: (0..ROWS).map(|i| Label::new(Contents::Text(format!("Label {}", i)))).collect(),
+            buttons: (0..ROWS).map(|i| Button::new(Contents::Text(format!("Button {}", i)), NoOp)).collect(),
+            check_boxes: (0..ROWS).map(|i| CheckBox::new(i % 2 == 0, Contents::Text(format!("Check {}", i)), NoOp)).collect(),
+        },
+        LayoutVertical::new(Margins::new(8, 8, 8, 8), Default::default())
+    );
+
+    let mut root = Root::new(gallery, (), HeadlessRenderer::new(TextMetrics::default()), DimsBox::new2(1024, 8192));
+    root.relayout();
+    root.redraw();
+    root
+}
+
+/// Redraw the gallery with one widget changing every frame, replaying every other widget.
+#[bench]
+fn redraw_one_dirty_retained(b: &mut Bencher) {
+    let mut root = gallery_root();
+
+    let mut value = 0.0;
+    b.iter(|| {
+        value = (value + 1.0) % 100.0;
+        *root.root_widget.container_mut().progress_bar.value_mut() = value;
+        root.relayout();
+        root.redraw();
+    });
+
+    #[cfg(feature = "frame-stats")]
+    assert!(root.frame_stats().widgets_replayed > 0);
+}
+
+/// The same as `redraw_one_dirty_retained`, but with every widget getting rendered again.
+#[bench]
+fn redraw_one_dirty_immediate(b: &mut Bencher) {
+    let mut root = gallery_root();
+    root.set_retained_rendering(false);
+
+    let mut value = 0.0;
+    b.iter(|| {
+        value = (value + 1.0) % 100.0;
+        *root.root_widget.container_mut().progress_bar.value_mut() = value;
+        root.relayout();
+        root.redraw();
+    });
+
+    #[cfg(feature = "frame-stats")]
+    assert_eq!(0, root.frame_stats().widgets_replayed);
+}
@@ -5,7 +5,12 @@
 //! Types used to specify children of container widgets.
 //!
 //! This module's primary functionality is in the `WidgetContainer` trait, and an implementation
-//! which contains a single widget is provided with the `SingleContainer` struct.
+//! which contains a single widget is provided with the `SingleContainer` struct. Tuples and arrays
+//! of widgets are also containers, which covers simple fixed layouts without needing a derived
+//! struct. Containers which allow their children to be swapped out in-place implement
+//! `ReplaceChild`, and `Chain` joins two containers into one.
+
+use std::mem;
 
 use crate::{
     core::LoopFlow,
@@ -52,36 +57,77 @@ pub trait WidgetContainer<S: ?Sized>: 'static {
         where G: FnMut(WidgetInfoMut<'a, R, S>) -> LoopFlow,
               R: Renderer;
 
-    /// Get the child with the specified name.
-    fn framed_child<R>(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, R, S>>
-        where R: Renderer
+    /// Call `f` on each child until it returns `Some`, and return that value.
+    ///
+    /// This is the value-carrying counterpart to breaking out of `framed_children`, for
+    /// search-style traversals that want to hand back whatever they found.
+    fn framed_find_child_map<'a, R, T, G>(&'a self, mut f: G) -> Option<T>
+        where G: FnMut(WidgetInfo<'a, R, S>) -> Option<T>,
+              R: Renderer
     {
-        let mut summary_opt = None;
+        let mut found = None;
         self.framed_children(|summary| {
-            if summary.ident == widget_ident {
-                summary_opt = Some(summary);
-                LoopFlow::Break
-            } else {
-                LoopFlow::Continue
+            found = f(summary);
+            match found {
+                Some(_) => LoopFlow::Break,
+                None => LoopFlow::Continue
             }
         });
-        summary_opt
+        found
     }
 
-    /// Mutably get the child with the specified name.
-    fn framed_child_mut<R>(&mut self, widget_ident: WidgetIdent) -> Option<WidgetInfoMut<'_, R, S>>
-        where R: Renderer
+    /// Call `f` on each child until it returns `Some`, and return that value.
+    ///
+    /// Mutable version of `framed_find_child_map`.
+    fn framed_find_child_map_mut<'a, R, T, G>(&'a mut self, mut f: G) -> Option<T>
+        where G: FnMut(WidgetInfoMut<'a, R, S>) -> Option<T>,
+              R: Renderer
     {
-        let mut summary_opt = None;
+        let mut found = None;
         self.framed_children_mut(|summary| {
-            if summary.ident == widget_ident {
-                summary_opt = Some(summary);
-                LoopFlow::Break
-            } else {
-                LoopFlow::Continue
+            found = f(summary);
+            match found {
+                Some(_) => LoopFlow::Break,
+                None => LoopFlow::Continue
             }
         });
-        summary_opt
+        found
+    }
+
+    /// Get the first child for which `predicate` returns `true`.
+    fn framed_find_child<'a, R, G>(&'a self, mut predicate: G) -> Option<WidgetInfo<'a, R, S>>
+        where G: FnMut(&WidgetInfo<'a, R, S>) -> bool,
+              R: Renderer
+    {
+        self.framed_find_child_map(|summary| match predicate(&summary) {
+            true => Some(summary),
+            false => None
+        })
+    }
+
+    /// Mutably get the first child for which `predicate` returns `true`.
+    fn framed_find_child_mut<'a, R, G>(&'a mut self, mut predicate: G) -> Option<WidgetInfoMut<'a, R, S>>
+        where G: FnMut(&WidgetInfoMut<'a, R, S>) -> bool,
+              R: Renderer
+    {
+        self.framed_find_child_map_mut(|summary| match predicate(&summary) {
+            true => Some(summary),
+            false => None
+        })
+    }
+
+    /// Get the child with the specified name.
+    fn framed_child<R>(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, R, S>>
+        where R: Renderer
+    {
+        self.framed_find_child(|summary| summary.ident == widget_ident)
+    }
+
+    /// Mutably get the child with the specified name.
+    fn framed_child_mut<R>(&mut self, widget_ident: WidgetIdent) -> Option<WidgetInfoMut<'_, R, S>>
+        where R: Renderer
+    {
+        self.framed_find_child_mut(|summary| summary.ident == widget_ident)
     }
 
     /// Get the child at the specified index.
@@ -91,17 +137,10 @@ pub trait WidgetContainer<S: ?Sized>: 'static {
     fn framed_child_by_index<R>(&self, mut index: usize) -> Option<WidgetInfo<'_, R, S>>
         where R: Renderer
     {
-        let mut summary_opt = None;
-        self.framed_children(|summary| {
-            if index == 0 {
-                summary_opt = Some(summary);
-                LoopFlow::Break
-            } else {
-                index -= 1;
-                LoopFlow::Continue
-            }
-        });
-        summary_opt
+        self.framed_find_child(|_| match index {
+            0 => true,
+            _ => {index -= 1; false}
+        })
     }
     /// Mutably get the child at the specified index.
     ///
@@ -110,17 +149,30 @@ pub trait WidgetContainer<S: ?Sized>: 'static {
     fn framed_child_by_index_mut<R>(&mut self, mut index: usize) -> Option<WidgetInfoMut<'_, R, S>>
         where R: Renderer
     {
-        let mut summary_opt = None;
-        self.framed_children_mut(|summary| {
-            if index == 0 {
-                summary_opt = Some(summary);
-                LoopFlow::Break
-            } else {
-                index -= 1;
-                LoopFlow::Continue
-            }
+        self.framed_find_child_mut(|_| match index {
+            0 => true,
+            _ => {index -= 1; false}
+        })
+    }
+
+    /// Thread an accumulator through each child widget stored within the container, returning the
+    /// final value of the accumulator.
+    ///
+    /// `f` is called with the current accumulator and the child, and returns the new accumulator
+    /// along with whether or not to continue iterating.
+    fn framed_fold_children<'a, R, B, G>(&'a self, init: B, mut f: G) -> B
+        where G: FnMut(B, WidgetInfo<'a, R, S>) -> (B, LoopFlow),
+              R: Renderer
+    {
+        // `framed_children` only takes an `FnMut`, so the accumulator has to be moved out of and
+        // back into the closure's environment on each call.
+        let mut acc = Some(init);
+        self.framed_children(|summary| {
+            let (new_acc, flow) = f(acc.take().unwrap(), summary);
+            acc = Some(new_acc);
+            flow
         });
-        summary_opt
+        acc.unwrap()
     }
 
     fn children<'a, G>(&'a self, for_each_child: G)
@@ -128,11 +180,36 @@ pub trait WidgetContainer<S: ?Sized>: 'static {
     {
         self.framed_children::<!, G>(for_each_child)
     }
+    fn fold_children<'a, B, G>(&'a self, init: B, f: G) -> B
+        where G: FnMut(B, WidgetInfo<'a, !, S>) -> (B, LoopFlow)
+    {
+        self.framed_fold_children::<!, B, G>(init, f)
+    }
     fn children_mut<'a, G>(&'a mut self, for_each_child: G)
         where G: FnMut(WidgetInfoMut<'a, !, S>) -> LoopFlow
     {
         self.framed_children_mut::<!, G>(for_each_child)
     }
+    fn find_child_map<'a, T, G>(&'a self, f: G) -> Option<T>
+        where G: FnMut(WidgetInfo<'a, !, S>) -> Option<T>
+    {
+        self.framed_find_child_map::<!, T, G>(f)
+    }
+    fn find_child_map_mut<'a, T, G>(&'a mut self, f: G) -> Option<T>
+        where G: FnMut(WidgetInfoMut<'a, !, S>) -> Option<T>
+    {
+        self.framed_find_child_map_mut::<!, T, G>(f)
+    }
+    fn find_child<'a, G>(&'a self, predicate: G) -> Option<WidgetInfo<'a, !, S>>
+        where G: FnMut(&WidgetInfo<'a, !, S>) -> bool
+    {
+        self.framed_find_child::<!, G>(predicate)
+    }
+    fn find_child_mut<'a, G>(&'a mut self, predicate: G) -> Option<WidgetInfoMut<'a, !, S>>
+        where G: FnMut(&WidgetInfoMut<'a, !, S>) -> bool
+    {
+        self.framed_find_child_mut::<!, G>(predicate)
+    }
     fn child(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, !, S>> {
         self.framed_child::<!>(widget_ident)
     }
@@ -147,6 +224,20 @@ pub trait WidgetContainer<S: ?Sized>: 'static {
     }
 }
 
+/// A container whose children can be swapped out without rebuilding the container.
+///
+/// The new child takes the old child's index, and therefore its slot in the parent's layout. The
+/// old child is returned to the caller, and is removed from the widget tree once it's dropped. To
+/// replace a child with a widget of a different type, store the children as `Box<dyn Widget>`.
+///
+/// This isn't implemented for containers generated with `#[derive(WidgetContainer)]`; just assign
+/// the new widget to the relevant field instead.
+pub trait ReplaceChild<W: Widget> {
+    /// Replace the child at `index` with `widget`, returning the old child. If there's no child at
+    /// `index`, `widget` is handed back in the `Err` variant.
+    fn replace_child(&mut self, index: usize, widget: W) -> Result<W, W>;
+}
+
 /// A container that contains a single widget.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct SingleContainer<W: Widget> {
@@ -184,6 +275,187 @@ impl<S, W> WidgetContainer<S> for SingleContainer<W>
     }
 }
 
+/// A container that presents the children of two containers as a single list.
+///
+/// `first`'s children come before `second`'s. The indices and numeric idents of `second`'s children
+/// are offset by the number of children in `first`, so that they don't collide with `first`'s.
+/// String idents aren't changed.
+///
+/// This is useful for following a fixed set of widgets with a collection:
+///
+/// ```ignore
+/// let children = Chain::new(
+///     (Label::new(Contents::Text("Files".to_string())), add_button),
+///     Vec::<Button<_>>::new()
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Chain<A, B> {
+    /// The container whose children come first.
+    pub first: A,
+    /// The container whose children come second.
+    pub second: B,
+}
+
+/// Which of a `Chain`'s containers a child ident refers to.
+enum ChainIdent {
+    First(WidgetIdent),
+    Second(WidgetIdent),
+    /// String idents aren't offset, so they could belong to either container.
+    Either(WidgetIdent),
+}
+
+impl<A, B> Chain<A, B> {
+    /// Creates a new container with `first`'s children followed by `second`'s.
+    #[inline(always)]
+    pub fn new(first: A, second: B) -> Chain<A, B> {
+        Chain{ first, second }
+    }
+}
+
+impl ChainIdent {
+    fn new(ident: WidgetIdent, offset: usize) -> ChainIdent {
+        let offset = offset as u32;
+        match ident {
+            WidgetIdent::Num(n) if n < offset => ChainIdent::First(ident),
+            WidgetIdent::Num(n) => ChainIdent::Second(WidgetIdent::Num(n - offset)),
+            WidgetIdent::NumCollection(n, _) if n < offset => ChainIdent::First(ident),
+            WidgetIdent::NumCollection(n, i) => ChainIdent::Second(WidgetIdent::NumCollection(n - offset, i)),
+            WidgetIdent::Str(_) |
+            WidgetIdent::StrCollection(..) => ChainIdent::Either(ident)
+        }
+    }
+}
+
+/// Convert the ident of one of `Chain::second`'s children into the ident the `Chain` exposes.
+fn offset_ident(ident: WidgetIdent, offset: usize) -> WidgetIdent {
+    let offset = offset as u32;
+    match ident {
+        WidgetIdent::Num(n) => WidgetIdent::Num(n + offset),
+        WidgetIdent::NumCollection(n, i) => WidgetIdent::NumCollection(n + offset, i),
+        ident => ident
+    }
+}
+
+fn offset_info<R: Renderer, S: ?Sized>(mut summary: WidgetInfo<'_, R, S>, offset: usize) -> WidgetInfo<'_, R, S> {
+    summary.ident = offset_ident(summary.ident, offset);
+    summary.index += offset;
+    summary
+}
+
+fn offset_info_mut<R: Renderer, S: ?Sized>(mut summary: WidgetInfoMut<'_, R, S>, offset: usize) -> WidgetInfoMut<'_, R, S> {
+    summary.ident = offset_ident(summary.ident, offset);
+    summary.index += offset;
+    summary
+}
+
+impl<S, A, B> WidgetContainer<S> for Chain<A, B>
+    where S: ?Sized,
+          A: WidgetContainer<S>,
+          B: WidgetContainer<S>
+{
+    #[inline]
+    fn num_children(&self) -> usize {
+        self.first.num_children() + self.second.num_children()
+    }
+
+    fn framed_children<'a, R, G>(&'a self, mut for_each_child: G)
+            where G: FnMut(WidgetInfo<'a, R, S>) -> LoopFlow,
+                  R: Renderer
+    {
+        let offset = self.first.num_children();
+        let mut flow = LoopFlow::Continue;
+        self.first.framed_children(|summary| {
+            flow = for_each_child(summary);
+            flow
+        });
+        if flow == LoopFlow::Continue {
+            self.second.framed_children(|summary| for_each_child(offset_info(summary, offset)));
+        }
+    }
+
+    fn framed_children_mut<'a, R, G>(&'a mut self, mut for_each_child: G)
+            where G: FnMut(WidgetInfoMut<'a, R, S>) -> LoopFlow,
+                  R: Renderer
+    {
+        let offset = self.first.num_children();
+        let mut flow = LoopFlow::Continue;
+        self.first.framed_children_mut(|summary| {
+            flow = for_each_child(summary);
+            flow
+        });
+        if flow == LoopFlow::Continue {
+            self.second.framed_children_mut(|summary| for_each_child(offset_info_mut(summary, offset)));
+        }
+    }
+
+    fn framed_child<R>(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, R, S>>
+        where R: Renderer
+    {
+        let offset = self.first.num_children();
+        match ChainIdent::new(widget_ident, offset) {
+            ChainIdent::First(ident) => self.first.framed_child(ident),
+            ChainIdent::Second(ident) => self.second.framed_child(ident).map(|s| offset_info(s, offset)),
+            ChainIdent::Either(ident) => match self.first.framed_child(ident.clone()) {
+                Some(summary) => Some(summary),
+                None => self.second.framed_child(ident).map(|s| offset_info(s, offset))
+            }
+        }
+    }
+
+    fn framed_child_mut<R>(&mut self, widget_ident: WidgetIdent) -> Option<WidgetInfoMut<'_, R, S>>
+        where R: Renderer
+    {
+        let offset = self.first.num_children();
+        match ChainIdent::new(widget_ident, offset) {
+            ChainIdent::First(ident) => self.first.framed_child_mut(ident),
+            ChainIdent::Second(ident) => self.second.framed_child_mut(ident).map(|s| offset_info_mut(s, offset)),
+            ChainIdent::Either(ident) => match self.first.framed_child_mut(ident.clone()) {
+                Some(summary) => Some(summary),
+                None => self.second.framed_child_mut(ident).map(|s| offset_info_mut(s, offset))
+            }
+        }
+    }
+
+    fn framed_child_by_index<R>(&self, index: usize) -> Option<WidgetInfo<'_, R, S>>
+        where R: Renderer
+    {
+        let offset = self.first.num_children();
+        match index.checked_sub(offset) {
+            None => self.first.framed_child_by_index(index),
+            Some(index) => self.second.framed_child_by_index(index).map(|s| offset_info(s, offset))
+        }
+    }
+
+    fn framed_child_by_index_mut<R>(&mut self, index: usize) -> Option<WidgetInfoMut<'_, R, S>>
+        where R: Renderer
+    {
+        let offset = self.first.num_children();
+        match index.checked_sub(offset) {
+            None => self.first.framed_child_by_index_mut(index),
+            Some(index) => self.second.framed_child_by_index_mut(index).map(|s| offset_info_mut(s, offset))
+        }
+    }
+}
+
+impl<W: Widget> ReplaceChild<W> for SingleContainer<W> {
+    fn replace_child(&mut self, index: usize, widget: W) -> Result<W, W> {
+        match index {
+            0 => Ok(mem::replace(&mut self.widget, widget)),
+            _ => Err(widget)
+        }
+    }
+}
+
+impl<W: Widget> ReplaceChild<W> for Vec<W> {
+    fn replace_child(&mut self, index: usize, widget: W) -> Result<W, W> {
+        match self.get_mut(index) {
+            Some(child) => Ok(mem::replace(child, widget)),
+            None => Err(widget)
+        }
+    }
+}
+
 impl<S, W> WidgetContainer<S> for Vec<W>
     where S: WidgetSubtype<W>,
           W: Widget
@@ -217,3 +489,358 @@ impl<S, W> WidgetContainer<S> for Vec<W>
         }
     }
 }
+
+macro_rules! impl_widget_container_tuple {
+    ($len:expr; $($index:tt: $W:ident),+) => {
+        /// Each element of the tuple is a child, identified by its index with `WidgetIdent::Num`.
+        impl<S, $($W),+> WidgetContainer<S> for ($($W,)+)
+            where $(S: WidgetSubtype<$W>,)+
+                  $($W: Widget),+
+        {
+            #[inline(always)]
+            fn num_children(&self) -> usize {$len}
+
+            fn framed_children<'a, R, G>(&'a self, mut for_each_child: G)
+                    where G: FnMut(WidgetInfo<'a, R, S>) -> LoopFlow,
+                          R: Renderer
+            {
+                $(
+                    match for_each_child(WidgetInfo::new(WidgetIdent::Num($index), $index, &self.$index)) {
+                        LoopFlow::Continue => (),
+                        LoopFlow::Break => return
+                    }
+                )+
+            }
+
+            fn framed_children_mut<'a, R, G>(&'a mut self, mut for_each_child: G)
+                    where G: FnMut(WidgetInfoMut<'a, R, S>) -> LoopFlow,
+                          R: Renderer
+            {
+                $(
+                    match for_each_child(WidgetInfoMut::new(WidgetIdent::Num($index), $index, &mut self.$index)) {
+                        LoopFlow::Continue => (),
+                        LoopFlow::Break => return
+                    }
+                )+
+            }
+        }
+    }
+}
+
+impl_widget_container_tuple!(1; 0: W0);
+impl_widget_container_tuple!(2; 0: W0, 1: W1);
+impl_widget_container_tuple!(3; 0: W0, 1: W1, 2: W2);
+impl_widget_container_tuple!(4; 0: W0, 1: W1, 2: W2, 3: W3);
+impl_widget_container_tuple!(5; 0: W0, 1: W1, 2: W2, 3: W3, 4: W4);
+impl_widget_container_tuple!(6; 0: W0, 1: W1, 2: W2, 3: W3, 4: W4, 5: W5);
+impl_widget_container_tuple!(7; 0: W0, 1: W1, 2: W2, 3: W3, 4: W4, 5: W5, 6: W6);
+impl_widget_container_tuple!(8; 0: W0, 1: W1, 2: W2, 3: W3, 4: W4, 5: W5, 6: W6, 7: W7);
+impl_widget_container_tuple!(9; 0: W0, 1: W1, 2: W2, 3: W3, 4: W4, 5: W5, 6: W6, 7: W7, 8: W8);
+impl_widget_container_tuple!(10; 0: W0, 1: W1, 2: W2, 3: W3, 4: W4, 5: W5, 6: W6, 7: W7, 8: W8, 9: W9);
+impl_widget_container_tuple!(11; 0: W0, 1: W1, 2: W2, 3: W3, 4: W4, 5: W5, 6: W6, 7: W7, 8: W8, 9: W9, 10: W10);
+impl_widget_container_tuple!(12; 0: W0, 1: W1, 2: W2, 3: W3, 4: W4, 5: W5, 6: W6, 7: W7, 8: W8, 9: W9, 10: W10, 11: W11);
+
+macro_rules! impl_widget_container_array {
+    ($($len:expr),+) => {$(
+        /// Each element of the array is a child, identified by its index with `WidgetIdent::Num`.
+        impl<S, W> WidgetContainer<S> for [W; $len]
+            where S: WidgetSubtype<W>,
+                  W: Widget
+        {
+            #[inline(always)]
+            fn num_children(&self) -> usize {$len}
+
+            fn framed_children<'a, R, G>(&'a self, mut for_each_child: G)
+                    where G: FnMut(WidgetInfo<'a, R, S>) -> LoopFlow,
+                          R: Renderer
+            {
+                for (index, widget) in self.iter().enumerate() {
+                    match for_each_child(WidgetInfo::new(WidgetIdent::Num(index as u32), index, widget)) {
+                        LoopFlow::Continue => (),
+                        LoopFlow::Break => return
+                    }
+                }
+            }
+
+            fn framed_children_mut<'a, R, G>(&'a mut self, mut for_each_child: G)
+                    where G: FnMut(WidgetInfoMut<'a, R, S>) -> LoopFlow,
+                          R: Renderer
+            {
+                for (index, widget) in self.iter_mut().enumerate() {
+                    match for_each_child(WidgetInfoMut::new(WidgetIdent::Num(index as u32), index, widget)) {
+                        LoopFlow::Continue => (),
+                        LoopFlow::Break => return
+                    }
+                }
+            }
+        }
+
+        impl<W: Widget> ReplaceChild<W> for [W; $len] {
+            fn replace_child(&mut self, index: usize, widget: W) -> Result<W, W> {
+                match self.get_mut(index) {
+                    Some(child) => Ok(mem::replace(child, widget)),
+                    None => Err(widget)
+                }
+            }
+        }
+    )+}
+}
+
+impl_widget_container_array!(
+    1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16,
+    17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cgmath::Point2,
+        core::{render::headless::HeadlessRenderer, widget::{Parent, WidgetId}},
+        layout::{LayoutHorizontal, Margins},
+        test_helpers::{click, headless_root},
+        widgets::{Button, ButtonHandler, Contents, Group, Spacer},
+    };
+    use cgmath_geometry::rect::{DimsBox, GeoBox};
+    use std::{cell::Cell, rc::Rc};
+
+    type SpacerChain = Chain<Vec<Spacer>, Vec<Spacer>>;
+
+    fn spacer_chain() -> SpacerChain {
+        Chain::new(
+            (0..2).map(|_| Spacer::flex()).collect(),
+            (0..3).map(|_| Spacer::flex()).collect(),
+        )
+    }
+
+    fn ids(spacers: &[Spacer]) -> Vec<WidgetId> {
+        spacers.iter().map(|s| s.widget_id()).collect()
+    }
+
+    fn child(chain: &SpacerChain, ident: WidgetIdent) -> Option<(WidgetIdent, usize, WidgetId)> {
+        WidgetContainer::<dyn Widget>::framed_child::<HeadlessRenderer>(chain, ident)
+            .map(|c| (c.ident.clone(), c.index, c.widget().widget_id()))
+    }
+
+    fn child_by_index(chain: &SpacerChain, index: usize) -> Option<(WidgetIdent, usize, WidgetId)> {
+        WidgetContainer::<dyn Widget>::framed_child_by_index::<HeadlessRenderer>(chain, index)
+            .map(|c| (c.ident.clone(), c.index, c.widget().widget_id()))
+    }
+
+    #[test]
+    fn chain_children() {
+        let chain = spacer_chain();
+        let (first, second) = (ids(&chain.first), ids(&chain.second));
+        assert_eq!(5, WidgetContainer::<dyn Widget>::num_children(&chain));
+
+        let mut children = Vec::new();
+        WidgetContainer::<dyn Widget>::framed_children::<HeadlessRenderer, _>(&chain, |c| {
+            children.push((c.ident.clone(), c.index, c.widget().widget_id()));
+            LoopFlow::Continue
+        });
+        let expected = first.iter().chain(&second).enumerate()
+            .map(|(i, &id)| (WidgetIdent::Num(i as u32), i, id))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, children);
+
+        // Breaking out of the first container's children doesn't visit the second's.
+        let mut visited = 0;
+        WidgetContainer::<dyn Widget>::framed_children::<HeadlessRenderer, _>(&chain, |_| {
+            visited += 1;
+            LoopFlow::Break
+        });
+        assert_eq!(1, visited);
+    }
+
+    #[test]
+    fn chain_routing() {
+        let mut chain = spacer_chain();
+        let (first, second) = (ids(&chain.first), ids(&chain.second));
+
+        assert_eq!(Some((WidgetIdent::Num(1), 1, first[1])), child(&chain, WidgetIdent::Num(1)));
+        assert_eq!(Some((WidgetIdent::Num(3), 3, second[1])), child(&chain, WidgetIdent::Num(3)));
+        assert_eq!(None, child(&chain, WidgetIdent::Num(5)));
+        assert_eq!(None, child(&chain, WidgetIdent::new_str("spacer")));
+
+        assert_eq!(Some((WidgetIdent::Num(0), 0, first[0])), child_by_index(&chain, 0));
+        assert_eq!(Some((WidgetIdent::Num(4), 4, second[2])), child_by_index(&chain, 4));
+        assert_eq!(None, child_by_index(&chain, 5));
+
+        let child_mut = WidgetContainer::<dyn Widget>::framed_child_mut::<HeadlessRenderer>(&mut chain, WidgetIdent::Num(2))
+            .map(|c| (c.index, c.widget().widget_id()));
+        assert_eq!(Some((2, second[0])), child_mut);
+        let child_mut = WidgetContainer::<dyn Widget>::framed_child_by_index_mut::<HeadlessRenderer>(&mut chain, 1)
+            .map(|c| (c.ident.clone(), c.widget().widget_id()));
+        assert_eq!(Some((WidgetIdent::Num(1), first[1])), child_mut);
+    }
+
+    struct Counter(Rc<Cell<u32>>);
+
+    impl ButtonHandler for Counter {
+        fn on_click(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn chain_event_routing() {
+        let counts = (0..3).map(|_| Rc::new(Cell::new(0))).collect::<Vec<_>>();
+        let button = |i: usize| Button::new(Contents::Text("A".to_string()), Counter(counts[i].clone()));
+        let group = Group::new(
+            Chain::new(vec![button(0)], vec![button(1), button(2)]),
+            LayoutHorizontal::new(Margins::new(0, 0, 0, 0), Default::default()),
+        );
+        let mut root = headless_root(group, DimsBox::new2(300, 24));
+
+        // Clicks on the second container's children reach the right widget.
+        click(&mut root, Point2::new(250, 12));
+        assert_eq!(vec![0, 0, 1], counts.iter().map(|c| c.get()).collect::<Vec<_>>());
+        click(&mut root, Point2::new(150, 12));
+        click(&mut root, Point2::new(50, 12));
+        assert_eq!(vec![1, 1, 1], counts.iter().map(|c| c.get()).collect::<Vec<_>>());
+    }
+
+    fn child_list<P: Parent>(parent: &P) -> Vec<(WidgetIdent, usize, WidgetId)> {
+        let mut children = Vec::new();
+        parent.children(|c| {
+            children.push((c.ident.clone(), c.index, c.widget().widget_id()));
+            LoopFlow::Continue
+        });
+        children
+    }
+
+    #[test]
+    fn tuple_children() {
+        let clicks = Rc::new(Cell::new(0));
+        let group = Group::new(
+            (Spacer::flex(), Button::new(Contents::Text("A".to_string()), Counter(clicks.clone()))),
+            LayoutHorizontal::new(Margins::new(0, 0, 0, 0), Default::default()),
+        );
+        let mut root = headless_root(group, DimsBox::new2(200, 24));
+
+        let (spacer, button) = root.root_widget.container();
+        let expected = vec![
+            (WidgetIdent::Num(0), 0, spacer.widget_id()),
+            (WidgetIdent::Num(1), 1, button.widget_id()),
+        ];
+        assert_eq!(expected, child_list(&root.root_widget));
+        assert_eq!(Some(1), root.root_widget.child(WidgetIdent::Num(1)).map(|c| c.index));
+        assert_eq!(Some(WidgetIdent::Num(0)), root.root_widget.child_by_index(0).map(|c| c.ident.clone()));
+        assert!(root.root_widget.child(WidgetIdent::Num(2)).is_none());
+
+        // Events reach the tuple's children, whatever their types.
+        let button_center = root.root_widget.container().1.rect().center();
+        click(&mut root, button_center);
+        assert_eq!(1, clicks.get());
+    }
+
+    #[test]
+    fn array_children() {
+        let group = Group::new(
+            [Spacer::flex(), Spacer::flex(), Spacer::flex()],
+            LayoutHorizontal::new(Margins::new(0, 0, 0, 0), Default::default()),
+        );
+        let root = headless_root(group, DimsBox::new2(300, 24));
+
+        let expected = root.root_widget.container().iter().enumerate()
+            .map(|(i, s)| (WidgetIdent::Num(i as u32), i, s.widget_id()))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, child_list(&root.root_widget));
+        assert_eq!(3, root.root_widget.num_children());
+        assert_eq!(Some(expected[2].2), root.root_widget.child(WidgetIdent::Num(2)).map(|c| c.widget().widget_id()));
+        assert!(root.root_widget.child_by_index(3).is_none());
+    }
+
+    #[test]
+    fn fold_children() {
+        let spacers = (0..4).map(|_| Spacer::flex()).collect::<Vec<_>>();
+        let spacer_ids = ids(&spacers);
+
+        // The accumulator returned for each child gets passed to the next one.
+        let visited = WidgetContainer::<dyn Widget>::framed_fold_children::<HeadlessRenderer, _, _>(
+            &spacers,
+            Vec::new(),
+            |mut visited, c| {
+                visited.push((c.index, c.widget().widget_id()));
+                (visited, LoopFlow::Continue)
+            }
+        );
+        assert_eq!(spacer_ids.iter().cloned().enumerate().collect::<Vec<_>>(), visited);
+
+        // Breaking stops the fold, and returns the accumulator from the child that broke.
+        let sum = WidgetContainer::<dyn Widget>::framed_fold_children::<HeadlessRenderer, _, _>(
+            &spacers,
+            0,
+            |sum, c| match c.index {
+                2 => (sum + 100, LoopFlow::Break),
+                i => (sum + i, LoopFlow::Continue)
+            }
+        );
+        assert_eq!(101, sum);
+    }
+
+    #[test]
+    fn find_child_map() {
+        let mut spacers = (0..4).map(|_| Spacer::flex()).collect::<Vec<_>>();
+        let spacer_ids = ids(&spacers);
+
+        // The search stops at the first child `f` returns `Some` for.
+        let mut visited = 0;
+        let found = WidgetContainer::<dyn Widget>::framed_find_child_map::<HeadlessRenderer, _, _>(&spacers, |c| {
+            visited += 1;
+            match c.index {
+                1 | 2 => Some(c.widget().widget_id()),
+                _ => None
+            }
+        });
+        assert_eq!(Some(spacer_ids[1]), found);
+        assert_eq!(2, visited);
+
+        let mut visited = 0;
+        let found = WidgetContainer::<dyn Widget>::framed_find_child_map::<HeadlessRenderer, (), _>(&spacers, |_| {
+            visited += 1;
+            None
+        });
+        assert_eq!(None, found);
+        assert_eq!(4, visited);
+
+        let found = WidgetContainer::<dyn Widget>::framed_find_child_map_mut::<HeadlessRenderer, _, _>(&mut spacers, |mut c| match c.index {
+            3 => Some(c.widget_mut().widget_id()),
+            _ => None
+        });
+        assert_eq!(Some(spacer_ids[3]), found);
+
+        let found = WidgetContainer::<dyn Widget>::framed_find_child::<HeadlessRenderer, _>(&spacers, |c| c.widget().widget_id() == spacer_ids[2])
+            .map(|c| (c.ident.clone(), c.index));
+        assert_eq!(Some((WidgetIdent::Num(2), 2)), found);
+        let found = WidgetContainer::<dyn Widget>::framed_find_child_mut::<HeadlessRenderer, _>(&mut spacers, |c| c.index > 3)
+            .map(|c| c.index);
+        assert_eq!(None, found);
+    }
+
+    #[test]
+    fn child_lookups() {
+        let mut spacers = (0..3).map(|_| Spacer::flex()).collect::<Vec<_>>();
+        let spacer_ids = ids(&spacers);
+        let by_index = |spacers: &Vec<Spacer>, index| WidgetContainer::<dyn Widget>::framed_child_by_index::<HeadlessRenderer>(spacers, index)
+            .map(|c| (c.ident.clone(), c.index, c.widget().widget_id()));
+
+        assert_eq!(Some((WidgetIdent::Num(0), 0, spacer_ids[0])), by_index(&spacers, 0));
+        assert_eq!(Some((WidgetIdent::Num(2), 2, spacer_ids[2])), by_index(&spacers, 2));
+        assert_eq!(None, by_index(&spacers, 3));
+
+        let child_mut = WidgetContainer::<dyn Widget>::framed_child_by_index_mut::<HeadlessRenderer>(&mut spacers, 1)
+            .map(|c| (c.ident.clone(), c.widget().widget_id()));
+        assert_eq!(Some((WidgetIdent::Num(1), spacer_ids[1])), child_mut);
+        let child_mut = WidgetContainer::<dyn Widget>::framed_child_by_index_mut::<HeadlessRenderer>(&mut spacers, 3)
+            .map(|c| c.index);
+        assert_eq!(None, child_mut);
+
+        let child = WidgetContainer::<dyn Widget>::framed_child::<HeadlessRenderer>(&spacers, WidgetIdent::Num(1))
+            .map(|c| c.widget().widget_id());
+        assert_eq!(Some(spacer_ids[1]), child);
+        let child = WidgetContainer::<dyn Widget>::framed_child_mut::<HeadlessRenderer>(&mut spacers, WidgetIdent::new_str("spacer"))
+            .map(|c| c.index);
+        assert_eq!(None, child);
+    }
+}
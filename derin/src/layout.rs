@@ -3,13 +3,86 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 //! Utilities for specifying the layout of widgets.
-pub use derin_common_types::layout::{Align, Align2, GridSize, Margins, SizeBounds, TrRange, TrackHints, WidgetPos, WidgetSpan};
+pub use derin_common_types::layout::{Align, Align2, GridSize, Margins, SizeBounds, Track, TrRange, TrackHints, WidgetPos, WidgetSpan};
 use crate::core::widget::WidgetIdent;
+use cgmath_geometry::{D2, rect::{BoundBox, DimsBox, GeoBox}};
+use derin_common_types::layout::Tr;
+use derin_layout_engine::GridEngine;
 
 /// Places widgets in a resizable grid-based layout.
 pub trait GridLayout: 'static {
     fn positions(&self, widget_ident: WidgetIdent, widget_index: usize, num_widgets: usize) -> Option<WidgetPos>;
     fn grid_size(&self, num_widgets: usize) -> GridSize;
+
+    /// The sizing rule for the given column. Defaults to `Track::Fraction(1.0)`, which splits the
+    /// free space evenly between the columns.
+    fn col_track(&self, _col: Tr, _num_widgets: usize) -> Track {
+        Track::default()
+    }
+
+    /// The sizing rule for the given row. Defaults to `Track::Fraction(1.0)`, which splits the
+    /// free space evenly between the rows.
+    fn row_track(&self, _row: Tr, _num_widgets: usize) -> Track {
+        Track::default()
+    }
+}
+
+/// Set the engine's track hints, resolving percentage tracks against `container_dims`. The engine's
+/// grid size must already be set.
+///
+/// Returns the total size of the percentage tracks along each axis. Resolved percentages are
+/// rigid, so this should be removed from the container's minimum size with
+/// `without_percent_tracks` - otherwise the tracks would keep the container from ever shrinking.
+pub(crate) fn set_track_hints(
+    engine: &mut GridEngine,
+    col_track: impl Fn(Tr) -> Track,
+    row_track: impl Fn(Tr) -> Track,
+    container_dims: DimsBox<D2, i32>,
+) -> DimsBox<D2, i32> {
+    let grid_size = engine.grid_size();
+    let mut percent_dims = DimsBox::new2(0, 0);
+
+    for col in 0..grid_size.x {
+        let track = col_track(col);
+        let hints = track.track_hints(container_dims.width());
+        if let Track::Percent(_) = track {
+            percent_dims.dims.x += hints.min_size;
+        }
+        engine.set_col_hints(col, hints);
+    }
+    for row in 0..grid_size.y {
+        let track = row_track(row);
+        let hints = track.track_hints(container_dims.height());
+        if let Track::Percent(_) = track {
+            percent_dims.dims.y += hints.min_size;
+        }
+        engine.set_row_hints(row, hints);
+    }
+
+    percent_dims
+}
+
+/// Remove the size of the percentage tracks returned by `set_track_hints` from `size_bounds`.
+pub(crate) fn without_percent_tracks(mut size_bounds: SizeBounds, percent_dims: DimsBox<D2, i32>) -> SizeBounds {
+    size_bounds.min.dims.x = i32::max(0, size_bounds.min.width() - percent_dims.width());
+    size_bounds.min.dims.y = i32::max(0, size_bounds.min.height() - percent_dims.height());
+    size_bounds
+}
+
+/// Places widgets at absolute positions on a [`Canvas`](../widgets/struct.Canvas.html).
+pub trait CanvasLayout: 'static {
+    /// The rectangle the widget occupies, in the canvas's content coordinates.
+    ///
+    /// Widgets without a rectangle get placed at the content origin, at their minimum size.
+    fn rect(&self, widget_ident: WidgetIdent, widget_index: usize) -> Option<BoundBox<D2, i32>>;
+}
+
+/// Places each widget at the rectangle with the same index as the widget.
+impl CanvasLayout for Vec<BoundBox<D2, i32>> {
+    #[inline]
+    fn rect(&self, _: WidgetIdent, widget_index: usize) -> Option<BoundBox<D2, i32>> {
+        self.get(widget_index).cloned()
+    }
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
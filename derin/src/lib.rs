@@ -48,6 +48,11 @@ extern crate parking_lot;
 #[macro_use]
 extern crate lazy_static;
 
+#[cfg(test)]
+mod test_helpers;
+#[cfg(test)]
+mod tests;
+
 pub mod container;
 // pub mod gl_render;
 // mod glutin_window;
@@ -65,6 +70,11 @@ pub mod event {
     pub use derin_common_types::buttons::{ModifierKeys, Key, MouseButton};
 }
 
+/// Semantic descriptions of widgets, for screen readers and other assistive technologies.
+pub mod accessibility {
+    pub use crate::core::accessibility::{AccessibilityRole, AccessibilityState, AccessibilityNode, AccessibilityTreeNode};
+}
+
 /// Types used to assemble widget geometry.
 ///
 /// The types within this module are all re-exported, either from `cgmath` or `cgmath-geometry`.
@@ -0,0 +1,181 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Helpers for driving widgets through a headless `Root` in tests.
+
+use crate::{
+    cgmath::Point2,
+    container::{SingleContainer, WidgetContainer},
+    core::{
+        LoopFlow, Root, WindowEvent,
+        event::{EventOps, InputState, WidgetEventSourced},
+        render::{Renderer, SubFrame, WidgetTheme, headless::{HeadlessRenderer, TextMetrics}},
+        widget::{Parent, Widget, WidgetIdent, WidgetInfo, WidgetInfoMut, WidgetRenderable, WidgetTag},
+    },
+    event::{Key, ModifierKeys, MouseButton},
+};
+use cgmath_geometry::{D2, rect::{BoundBox, DimsBox, GeoBox}};
+
+/// Create a root drawn with the headless renderer, and lay it out.
+///
+/// Widgets only get added to the tree once they've been visited, so the tree gets drawn once and
+/// then laid out again, which lets containers see the size bounds their children calculated.
+pub(crate) fn headless_root<W: Widget>(widget: W, dims: DimsBox<D2, u32>) -> Root<W, HeadlessRenderer> {
+    let mut root = Root::new(widget, (), HeadlessRenderer::new(TextMetrics::default()), dims);
+    root.relayout();
+    root.redraw();
+    root.relayout();
+    root
+}
+
+/// Deliver `events` to the tree in a single frame, then lay the tree out again so that messages
+/// and layout changes made by the events take effect.
+pub(crate) fn send_events<W: Widget>(root: &mut Root<W, HeadlessRenderer>, events: &[WindowEvent]) {
+    let mut frame = root.start_frame();
+    for event in events {
+        frame.process_event(*event);
+    }
+    let _ = frame.finish();
+    root.relayout();
+}
+
+/// Move the mouse to `pos` and click the left mouse button there.
+pub(crate) fn click<W: Widget>(root: &mut Root<W, HeadlessRenderer>, pos: Point2<i32>) {
+    send_events(root, &[
+        WindowEvent::MouseMove(pos),
+        WindowEvent::MouseDown(MouseButton::Left),
+        WindowEvent::MouseUp(MouseButton::Left),
+    ]);
+}
+
+/// Press and release `key`.
+pub(crate) fn press_key<W: Widget>(root: &mut Root<W, HeadlessRenderer>, key: Key) {
+    send_events(root, &[WindowEvent::KeyDown(key), WindowEvent::KeyUp(key)]);
+}
+
+/// Press and release `key` while holding `Alt`, activating the widget with that access key.
+pub(crate) fn press_access_key<W: Widget>(root: &mut Root<W, HeadlessRenderer>, key: Key) {
+    let mut frame = root.start_frame();
+    frame.set_modifiers(ModifierKeys::ALT);
+    frame.process_event(WindowEvent::KeyDown(key));
+    frame.process_event(WindowEvent::KeyUp(key));
+    frame.set_modifiers(ModifierKeys::empty());
+    let _ = frame.finish();
+    root.relayout();
+}
+
+/// A parent with a single child, which records every message of type `M` sent to it. The child
+/// gets stretched to fill the recorder.
+pub(crate) struct MessageRecorder<W: Widget, M: 'static + Clone> {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    pub child: SingleContainer<W>,
+    pub messages: Vec<M>,
+}
+
+pub(crate) struct MessageRecorderTheme;
+
+impl<W: Widget, M: 'static + Clone> MessageRecorder<W, M> {
+    pub fn new(child: W) -> MessageRecorder<W, M> {
+        let mut widget_tag = WidgetTag::new();
+        widget_tag.register_message(|recorder: &mut MessageRecorder<W, M>, message: &M| recorder.messages.push(message.clone()));
+        MessageRecorder {
+            widget_tag,
+            rect: BoundBox::new2(0, 0, 0, 0),
+            child: SingleContainer::new(child),
+            messages: Vec::new(),
+        }
+    }
+
+    fn container(&self) -> &impl WidgetContainer<dyn Widget> {
+        &self.child
+    }
+
+    fn container_mut(&mut self) -> &mut impl WidgetContainer<dyn Widget> {
+        &mut self.child
+    }
+}
+
+impl<W: Widget, M: 'static + Clone> Widget for MessageRecorder<W, M> {
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        self.widget_tag.request_relayout();
+        &mut self.rect
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        EventOps {
+            focus: None,
+            bubble: event.default_bubble(),
+            handled: false,
+        }
+    }
+}
+
+impl<W: Widget, M: 'static + Clone> Parent for MessageRecorder<W, M> {
+    fn num_children(&self) -> usize {
+        1
+    }
+
+    fn framed_child<R: Renderer>(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, R>> {
+        self.container().framed_child(widget_ident).map(WidgetInfo::erase_subtype)
+    }
+    fn framed_child_mut<R: Renderer>(&mut self, widget_ident: WidgetIdent) -> Option<WidgetInfoMut<'_, R>> {
+        self.container_mut().framed_child_mut(widget_ident).map(WidgetInfoMut::erase_subtype)
+    }
+
+    fn framed_children<'a, R, G>(&'a self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfo<'a, R>) -> LoopFlow
+    {
+        self.container().framed_children(|summary| for_each(WidgetInfo::erase_subtype(summary)))
+    }
+
+    fn framed_children_mut<'a, R, G>(&'a mut self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfoMut<'a, R>) -> LoopFlow
+    {
+        self.container_mut().framed_children_mut(|summary| for_each(WidgetInfoMut::erase_subtype(summary)))
+    }
+
+    fn framed_child_by_index<R: Renderer>(&self, index: usize) -> Option<WidgetInfo<'_, R>> {
+        self.container().framed_child_by_index(index).map(WidgetInfo::erase_subtype)
+    }
+    fn framed_child_by_index_mut<R: Renderer>(&mut self, index: usize) -> Option<WidgetInfoMut<'_, R>> {
+        self.container_mut().framed_child_by_index_mut(index).map(WidgetInfoMut::erase_subtype)
+    }
+}
+
+impl<R: Renderer, W: Widget, M: 'static + Clone> WidgetRenderable<R> for MessageRecorder<W, M> {
+    type Theme = MessageRecorderTheme;
+
+    fn theme(&self) -> MessageRecorderTheme {
+        MessageRecorderTheme
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, _: &mut R::Layout) {
+        let rect = BoundBox::new2(0, 0, self.rect.width(), self.rect.height());
+        if self.child.widget.rect() != rect {
+            *self.child.widget.rect_mut() = rect;
+        }
+    }
+}
+
+impl WidgetTheme for MessageRecorderTheme {
+    type Fallback = !;
+    fn fallback(self) -> Option<!> {
+        None
+    }
+}
@@ -0,0 +1,650 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Tests for how a `Root` handles window-level events, driven through the headless renderer.
+
+use crate::{
+    cgmath::Point2,
+    container::WidgetContainer,
+    core::{
+        CloseAction, EventLoopResult, LoopFlow, Root, WindowEvent,
+        event::{EventOps, InputState, WidgetEvent, WidgetEventSourced},
+        render::{ReadPixels, Renderer, RendererLayout, SubFrame, WidgetTheme, headless::HeadlessRenderer},
+        timer::{Timer, TimerId},
+        widget::{Parent, Widget, WidgetIdent, WidgetInfo, WidgetInfoMut, WidgetRenderable, WidgetTag},
+    },
+    event::MouseButton,
+    test_helpers::{headless_root, send_events},
+};
+use cgmath_geometry::{D2, rect::{BoundBox, DimsBox}};
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+/// A widget that records the events it receives. When a mouse button is pressed over it, it
+/// requests a redraw, sets the window title to `title`, sends a `"mouse down"` action, and
+/// broadcasts `Ping`. It asks for the window to be closed when it's clicked.
+struct WindowControl {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    events: Vec<WidgetEvent>,
+    title: Option<&'static str>,
+}
+
+struct WindowControlTheme;
+
+/// Broadcast by `WindowControl` when a mouse button is pressed over it.
+struct Ping;
+
+impl WindowControl {
+    fn new() -> WindowControl {
+        WindowControl {
+            widget_tag: WidgetTag::new(),
+            rect: BoundBox::new2(0, 0, 0, 0),
+            events: Vec::new(),
+            title: None,
+        }
+    }
+}
+
+impl Widget for WindowControl {
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        &mut self.rect
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        let event = event.unwrap();
+        self.events.push(event.clone());
+
+        if let WidgetEvent::MouseDown{..} = event {
+            self.widget_tag.request_redraw();
+            if let Some(title) = self.title {
+                self.widget_tag.set_window_title(title).unwrap();
+            }
+            self.widget_tag.send_action("mouse down");
+            self.widget_tag.broadcast_message(Ping);
+        }
+        if let WidgetEvent::Click{..} = event {
+            self.widget_tag.request_close_window().unwrap();
+        }
+
+        EventOps {
+            focus: None,
+            bubble: event.default_bubble(),
+            handled: true,
+        }
+    }
+}
+
+impl<R: Renderer> WidgetRenderable<R> for WindowControl {
+    type Theme = WindowControlTheme;
+
+    fn theme(&self) -> WindowControlTheme {
+        WindowControlTheme
+    }
+
+    fn render(&mut self, _: &mut R::SubFrame) {}
+}
+
+impl WidgetTheme for WindowControlTheme {
+    type Fallback = !;
+    fn fallback(self) -> Option<!> {
+        None
+    }
+}
+
+/// A widget that draws `text`, which only gets read when the widget's laid out.
+struct LaidOutText {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    text: String,
+}
+
+impl LaidOutText {
+    fn new(text: &str) -> LaidOutText {
+        LaidOutText {
+            widget_tag: WidgetTag::new(),
+            rect: BoundBox::new2(0, 0, 0, 0),
+            text: text.to_string(),
+        }
+    }
+}
+
+impl Widget for LaidOutText {
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        &mut self.rect
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        EventOps {
+            focus: None,
+            bubble: event.default_bubble(),
+            handled: false,
+        }
+    }
+}
+
+impl<R: Renderer> WidgetRenderable<R> for LaidOutText {
+    type Theme = WindowControlTheme;
+
+    fn theme(&self) -> WindowControlTheme {
+        WindowControlTheme
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, layout: &mut R::Layout) {
+        layout.prepare_string(&self.text);
+        layout.finish();
+    }
+}
+
+/// A widget that records the rect it had every time `on_update_layout` gets called.
+struct LayoutRecorder {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    old_rects: Vec<BoundBox<D2, i32>>,
+}
+
+/// Stacks its children vertically, ten pixels apart. When `reverse` is set, the children get
+/// reversed the next time the stack's laid out.
+struct Stack {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    old_rects: Vec<BoundBox<D2, i32>>,
+    children: Vec<LayoutRecorder>,
+    reverse: bool,
+}
+
+impl LayoutRecorder {
+    fn new() -> LayoutRecorder {
+        LayoutRecorder {
+            widget_tag: WidgetTag::new(),
+            rect: BoundBox::new2(0, 0, 0, 0),
+            old_rects: Vec::new(),
+        }
+    }
+}
+
+impl Widget for LayoutRecorder {
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        &mut self.rect
+    }
+
+    fn on_update_layout(&mut self, old_rect: BoundBox<D2, i32>) {
+        self.old_rects.push(old_rect);
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        EventOps {
+            focus: None,
+            bubble: event.default_bubble(),
+            handled: false,
+        }
+    }
+}
+
+impl<R: Renderer> WidgetRenderable<R> for LayoutRecorder {
+    type Theme = WindowControlTheme;
+
+    fn theme(&self) -> WindowControlTheme {
+        WindowControlTheme
+    }
+
+    fn render(&mut self, _: &mut R::SubFrame) {}
+}
+
+impl Stack {
+    fn container(&self) -> &impl WidgetContainer<dyn Widget> {
+        &self.children
+    }
+
+    fn container_mut(&mut self) -> &mut impl WidgetContainer<dyn Widget> {
+        &mut self.children
+    }
+}
+
+impl Widget for Stack {
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        &mut self.rect
+    }
+
+    fn on_update_layout(&mut self, old_rect: BoundBox<D2, i32>) {
+        self.old_rects.push(old_rect);
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        EventOps {
+            focus: None,
+            bubble: event.default_bubble(),
+            handled: false,
+        }
+    }
+}
+
+impl Parent for Stack {
+    fn num_children(&self) -> usize {
+        self.container().num_children()
+    }
+
+    fn framed_child<R: Renderer>(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, R>> {
+        self.container().framed_child(widget_ident).map(WidgetInfo::erase_subtype)
+    }
+    fn framed_child_mut<R: Renderer>(&mut self, widget_ident: WidgetIdent) -> Option<WidgetInfoMut<'_, R>> {
+        self.container_mut().framed_child_mut(widget_ident).map(WidgetInfoMut::erase_subtype)
+    }
+
+    fn framed_children<'a, R, G>(&'a self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfo<'a, R>) -> LoopFlow
+    {
+        self.container().framed_children(|summary| for_each(WidgetInfo::erase_subtype(summary)))
+    }
+
+    fn framed_children_mut<'a, R, G>(&'a mut self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfoMut<'a, R>) -> LoopFlow
+    {
+        self.container_mut().framed_children_mut(|summary| for_each(WidgetInfoMut::erase_subtype(summary)))
+    }
+
+    fn framed_child_by_index<R: Renderer>(&self, index: usize) -> Option<WidgetInfo<'_, R>> {
+        self.container().framed_child_by_index(index).map(WidgetInfo::erase_subtype)
+    }
+    fn framed_child_by_index_mut<R: Renderer>(&mut self, index: usize) -> Option<WidgetInfoMut<'_, R>> {
+        self.container_mut().framed_child_by_index_mut(index).map(WidgetInfoMut::erase_subtype)
+    }
+}
+
+impl<R: Renderer> WidgetRenderable<R> for Stack {
+    type Theme = WindowControlTheme;
+
+    fn theme(&self) -> WindowControlTheme {
+        WindowControlTheme
+    }
+
+    fn render(&mut self, _: &mut R::SubFrame) {}
+
+    fn update_layout(&mut self, _: &mut R::Layout) {
+        if self.reverse {
+            self.reverse = false;
+            self.children.reverse();
+        }
+        let width = self.rect.width();
+        for (i, child) in self.children.iter_mut().enumerate() {
+            let top = i as i32 * 10;
+            child.rect = BoundBox::new2(0, top, width, top + 10);
+        }
+    }
+}
+
+/// Deliver `events` to the tree in a single frame, returning what the frame asks of the window.
+fn finish_frame<W: Widget>(root: &mut Root<W, HeadlessRenderer>, events: &[WindowEvent]) -> EventLoopResult {
+    let mut frame = root.start_frame();
+    for event in events {
+        frame.process_event(*event);
+    }
+    frame.finish()
+}
+
+#[test]
+fn window_focus_loss_releases_mouse() {
+    let mut root = headless_root(WindowControl::new(), DimsBox::new2(32, 32));
+
+    send_events(&mut root, &[
+        WindowEvent::MouseMove(Point2::new(4, 4)),
+        WindowEvent::MouseDown(MouseButton::Left),
+    ]);
+    root.root_widget.events.clear();
+
+    send_events(&mut root, &[WindowEvent::WindowFocus(false)]);
+    let events = &root.root_widget.events;
+    let release = events.iter().position(|e| match e {
+        WidgetEvent::MouseUp{button: MouseButton::Left, ..} => true,
+        _ => false
+    });
+    let focus_lost = events.iter().position(|e| *e == WidgetEvent::WindowFocusChanged(false));
+    assert!(release.is_some(), "{:?}", events);
+    assert!(release < focus_lost, "{:?}", events);
+
+    // The button's already been released, so the window regaining focus doesn't release it again.
+    root.root_widget.events.clear();
+    send_events(&mut root, &[WindowEvent::WindowFocus(true)]);
+    assert_eq!(vec![WidgetEvent::WindowFocusChanged(true)], root.root_widget.events);
+}
+
+#[test]
+fn needs_redraw() {
+    let mut root = headless_root(WindowControl::new(), DimsBox::new2(32, 32));
+    root.redraw();
+    assert!(!root.needs_redraw());
+
+    send_events(&mut root, &[WindowEvent::MouseMove(Point2::new(4, 4))]);
+    assert!(!root.needs_redraw());
+
+    send_events(&mut root, &[WindowEvent::MouseDown(MouseButton::Left)]);
+    assert!(root.needs_redraw());
+
+    root.redraw();
+    assert!(!root.needs_redraw());
+
+    send_events(&mut root, &[WindowEvent::Redraw]);
+    assert!(root.needs_redraw());
+}
+
+#[test]
+fn close_request() {
+    let mut root = headless_root(WindowControl::new(), DimsBox::new2(32, 32));
+    assert!(!finish_frame(&mut root, &[WindowEvent::MouseMove(Point2::new(4, 4))]).close);
+    assert!(finish_frame(&mut root, &[WindowEvent::CloseRequested]).close);
+
+    let handler_calls = Rc::new(Cell::new(0));
+    let calls = handler_calls.clone();
+    root.set_close_handler(Box::new(move || {
+        calls.set(calls.get() + 1);
+        CloseAction::Cancel
+    }));
+    assert!(!finish_frame(&mut root, &[WindowEvent::CloseRequested, WindowEvent::CloseRequested]).close);
+    assert_eq!(1, handler_calls.get());
+
+    // Requests made by widgets go through the handler too.
+    let click = [WindowEvent::MouseDown(MouseButton::Left), WindowEvent::MouseUp(MouseButton::Left)];
+    assert!(!finish_frame(&mut root, &click).close);
+    assert_eq!(2, handler_calls.get());
+
+    root.remove_close_handler();
+    assert!(finish_frame(&mut root, &click).close);
+}
+
+#[test]
+fn widget_timer() {
+    let mut root = headless_root(WindowControl::new(), DimsBox::new2(32, 32));
+    let timer_id = TimerId::new();
+    root.root_widget.widget_tag.timers_mut().insert(timer_id, Timer::new(Duration::from_secs(60)));
+    let start_time = root.root_widget.widget_tag.timers()[&timer_id].start_time();
+
+    // Timers fire as soon as they're started, and then once per period.
+    let result = finish_frame(&mut root, &[]);
+    assert_eq!(Some(start_time + Duration::from_secs(60)), result.next_timer);
+    let fired = root.root_widget.events.iter().filter(|e| match e {
+        WidgetEvent::Timer{timer_id: id, times_triggered: 0, ..} => *id == timer_id,
+        _ => false
+    }).count();
+    assert_eq!(1, fired);
+
+    root.root_widget.events.clear();
+    finish_frame(&mut root, &[]);
+    assert_eq!(Vec::<WidgetEvent>::new(), root.root_widget.events);
+}
+
+#[test]
+fn clear_timers() {
+    let mut root = headless_root(WindowControl::new(), DimsBox::new2(32, 32));
+    root.root_widget.widget_tag.timers_mut().insert(TimerId::new(), Timer::new(Duration::from_secs(60)));
+    assert!(finish_frame(&mut root, &[]).next_timer.is_some());
+
+    // Clearing the timers drops their queued triggers, so the event loop stops waking up for them.
+    root.root_widget.widget_tag.clear_timers();
+    root.root_widget.events.clear();
+    assert_eq!(None, finish_frame(&mut root, &[]).next_timer);
+    assert_eq!(Vec::<WidgetEvent>::new(), root.root_widget.events);
+}
+
+#[test]
+fn scale_factor_change() {
+    let mut root = headless_root(WindowControl::new(), DimsBox::new2(32, 32));
+    root.redraw();
+    assert_eq!(1.0, root.scale_factor());
+
+    send_events(&mut root, &[WindowEvent::ScaleFactorChanged(2.0)]);
+    assert_eq!(2.0, root.scale_factor());
+    assert_eq!(2.0, root.renderer.scale_factor());
+    assert!(root.needs_redraw());
+
+    // Reporting the same scale factor again doesn't redraw anything.
+    root.redraw();
+    send_events(&mut root, &[WindowEvent::ScaleFactorChanged(2.0)]);
+    assert!(!root.needs_redraw());
+}
+
+#[test]
+fn set_window_title() {
+    let mut root = headless_root(WindowControl::new(), DimsBox::new2(32, 32));
+    let press = [
+        WindowEvent::MouseMove(Point2::new(4, 4)),
+        WindowEvent::MouseDown(MouseButton::Left),
+        WindowEvent::MouseUp(MouseButton::Left),
+    ];
+    assert_eq!(None, finish_frame(&mut root, &press).set_window_title);
+
+    root.root_widget.title = Some("Title");
+    assert_eq!(Some("Title".to_string()), finish_frame(&mut root, &press).set_window_title);
+    // The request only gets reported once.
+    assert_eq!(None, finish_frame(&mut root, &[WindowEvent::MouseMove(Point2::new(5, 5))]).set_window_title);
+}
+
+#[test]
+fn defer() {
+    use crate::{core::widget::UpdateError, widgets::Spacer};
+
+    let mut outside_tree = WindowControl::new();
+    assert_eq!(Err(UpdateError::NoRootWidget), outside_tree.widget_tag.defer(|_: &mut WindowControl| ()));
+
+    let mut root = headless_root(WindowControl::new(), DimsBox::new2(32, 32));
+    let widget_tag = &mut root.root_widget.widget_tag;
+    assert_eq!(Err(UpdateError::WrongWidgetType), widget_tag.defer(|_: &mut Spacer| ()));
+    widget_tag.defer(|control: &mut WindowControl| control.title = Some("first")).unwrap();
+    widget_tag.defer(|control: &mut WindowControl| {
+        assert_eq!(Some("first"), control.title);
+        control.title = Some("second");
+    }).unwrap();
+    assert_eq!(None, root.root_widget.title);
+
+    // Deferred mutations run in order once the frame's events have been dispatched.
+    finish_frame(&mut root, &[]);
+    assert_eq!(Some("second"), root.root_widget.title);
+}
+
+#[test]
+fn take_actions() {
+    let mut root = headless_root(WindowControl::new(), DimsBox::new2(32, 32));
+    root.root_widget.widget_tag.register_message(|control: &mut WindowControl, _: &Ping| {
+        control.widget_tag.send_action("message");
+    });
+    assert_eq!(0, root.take_actions().count());
+
+    // Actions sent while handling events come before the ones sent from message handlers.
+    send_events(&mut root, &[
+        WindowEvent::MouseMove(Point2::new(4, 4)),
+        WindowEvent::MouseDown(MouseButton::Left),
+    ]);
+    let actions = root.take_actions()
+        .map(|action| *action.downcast::<&'static str>().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["mouse down", "message"], actions);
+
+    // Taking the actions drains the queue.
+    assert_eq!(0, root.take_actions().count());
+}
+
+#[test]
+fn ui_state_round_trip() {
+    use crate::{
+        cgmath::Vector2,
+        core::{ui_state::UiStateValue, widget::{WidgetIdent, WidgetPathKey}},
+        layout::{LayoutHorizontal, Margins},
+        widgets::{Canvas, Group, Spacer},
+    };
+
+    let new_root = || {
+        let canvases = (0..2).map(|_| Canvas::new(Vec::<Spacer>::new(), Vec::new())).collect::<Vec<_>>();
+        let group = Group::new(canvases, LayoutHorizontal::new(Margins::new(0, 0, 0, 0), Default::default()));
+        headless_root(group, DimsBox::new2(64, 32))
+    };
+
+    let mut root = new_root();
+    root.root_widget.container_mut()[1].set_pan(Vector2::new(4.0, -2.0));
+    root.root_widget.container_mut()[1].set_zoom(2.0);
+    let ui_state = root.save_ui_state();
+    let path: WidgetPathKey = vec![WidgetIdent::Num(1)].into_iter().collect();
+    assert_eq!(2, ui_state.len());
+    assert_eq!(Some(2.0), ui_state.get(&path).and_then(UiStateValue::as_list).and_then(|l| l[2].as_float()));
+
+    // Restoring the state into a freshly-built tree puts every widget back where it was.
+    let mut restored = new_root();
+    restored.restore_ui_state(ui_state);
+    let canvases = restored.root_widget.container();
+    assert_eq!(Vector2::new(0.0, 0.0), canvases[0].pan());
+    assert_eq!(Vector2::new(4.0, -2.0), canvases[1].pan());
+    assert_eq!(2.0, canvases[1].zoom());
+}
+
+#[test]
+fn on_update_layout() {
+    let stack = Stack {
+        widget_tag: WidgetTag::new(),
+        rect: BoundBox::new2(0, 0, 0, 0),
+        old_rects: Vec::new(),
+        children: (0..3).map(|_| LayoutRecorder::new()).collect(),
+        reverse: false,
+    };
+    let mut root = headless_root(stack, DimsBox::new2(20, 30));
+    let ids = root.root_widget.children.iter().map(|c| c.widget_id()).collect::<Vec<_>>();
+    for child in &mut root.root_widget.children {
+        child.old_rects.clear();
+    }
+
+    // Children that get moved are told where they were, even though the stack reordered them.
+    // The middle child stays in place, so it isn't told anything.
+    root.root_widget.reverse = true;
+    root.root_widget.widget_tag.request_relayout();
+    root.relayout();
+    let children = &root.root_widget.children;
+    assert_eq!(vec![ids[2], ids[1], ids[0]], children.iter().map(|c| c.widget_id()).collect::<Vec<_>>());
+    assert_eq!(vec![BoundBox::new2(0, 20, 20, 30)], children[0].old_rects);
+    assert!(children[1].old_rects.is_empty());
+    assert_eq!(vec![BoundBox::new2(0, 0, 20, 10)], children[2].old_rects);
+
+    // Laying the stack out again without moving anything doesn't tell anyone anything.
+    root.root_widget.widget_tag.request_relayout();
+    root.relayout();
+    assert_eq!(1, root.root_widget.children[0].old_rects.len());
+    assert!(root.root_widget.children[1].old_rects.is_empty());
+
+    // Resizing the window resizes the root, and the children along with it.
+    assert!(root.root_widget.old_rects.is_empty());
+    send_events(&mut root, &[WindowEvent::WindowResize(DimsBox::new2(40, 30))]);
+    assert_eq!(vec![BoundBox::new2(0, 0, 20, 30)], root.root_widget.old_rects);
+    assert_eq!(vec![BoundBox::new2(0, 10, 20, 20)], root.root_widget.children[1].old_rects);
+}
+
+#[test]
+fn retained_rendering_relayout() {
+    use crate::{
+        layout::{LayoutHorizontal, Margins},
+        widgets::Group,
+    };
+
+    let group = Group::new(
+        vec![LaidOutText::new("a"), LaidOutText::new("b")],
+        LayoutHorizontal::new(Margins::new(0, 0, 0, 0), Default::default()),
+    );
+    let mut root = headless_root(group, DimsBox::new2(32, 16));
+    assert!(!root.retained_rendering());
+    root.set_retained_rendering(true);
+    let before = root.render_to_image();
+
+    // Only the second widget asks to be redrawn. The first one's content changes when it's laid
+    // out again, so it can't be replayed either.
+    root.root_widget.container_mut()[0].text = " ".to_string();
+    root.root_widget.container_mut()[0].widget_tag.request_relayout();
+    root.root_widget.container_mut()[1].widget_tag.request_redraw();
+    root.relayout();
+    root.redraw();
+    let after = root.renderer.read_pixels();
+    assert_ne!(before, after);
+    assert_eq!(root.render_to_image(), after);
+
+    // Laying a widget out is enough for it to be drawn again.
+    root.root_widget.container_mut()[0].text = "a".to_string();
+    root.root_widget.container_mut()[0].widget_tag.request_relayout();
+    root.relayout();
+    assert!(root.needs_redraw());
+    root.redraw();
+    assert_eq!(before, root.renderer.read_pixels());
+}
+
+#[cfg(feature = "frame-stats")]
+#[test]
+fn frame_stats() {
+    use crate::{
+        layout::{LayoutHorizontal, Margins},
+        widgets::Group,
+    };
+
+    let group = Group::new(
+        vec![WindowControl::new(), WindowControl::new()],
+        LayoutHorizontal::new(Margins::new(0, 0, 0, 0), Default::default()),
+    );
+    let mut root = headless_root(group, DimsBox::new2(32, 32));
+    root.set_retained_rendering(true);
+    let _ = root.render_to_image();
+    let stats = *root.frame_stats();
+    assert!(stats.widgets_laid_out >= 3);
+    // The whole tree was drawn, and every widget got outlined.
+    assert_eq!(3, stats.widgets_rendered);
+    assert_eq!(0, stats.widgets_replayed);
+    assert_eq!(None, stats.dirty_widgets);
+    assert_eq!(Some(3), stats.draw_calls);
+
+    send_events(&mut root, &[
+        WindowEvent::MouseMove(Point2::new(4, 4)),
+        WindowEvent::MouseDown(MouseButton::Left),
+    ]);
+    root.redraw();
+    let stats = *root.frame_stats();
+    assert_eq!(3, stats.widgets_rendered);
+    assert_eq!(2, stats.widgets_replayed);
+    assert_eq!(Some(1), stats.dirty_widgets);
+    assert_eq!(Some(3), stats.draw_calls);
+    assert!(stats.frame_time() >= stats.render_time);
+
+    // Nothing needs to be drawn, so the render stats are cleared.
+    root.redraw();
+    let stats = *root.frame_stats();
+    assert_eq!(0, stats.widgets_rendered);
+    assert_eq!(None, stats.draw_calls);
+}
@@ -7,34 +7,60 @@
 #[macro_use]
 pub mod assistants;
 mod button;
+mod canvas;
 mod check_box;
 mod clip;
+mod color_picker;
 // mod direct_render;
 mod edit_box;
+mod either;
+mod grid;
 mod group;
 mod label;
+mod menu_bar;
+mod number_input;
 mod progress_bar;
 mod radio_buttons;
-// mod scroll_box;
+mod scroll_box;
+mod segmented_control;
 mod slider;
-// mod tabs;
+mod spacer;
+mod text_area;
+mod tabs;
+mod toggle_button;
+mod toolbar;
+mod tree_view;
 
 pub use self::button::*;
+pub use self::canvas::*;
 pub use self::check_box::*;
 pub use self::clip::*;
+pub use self::color_picker::*;
 // pub use self::direct_render::*;
 pub use self::edit_box::*;
+pub use self::either::*;
+pub use self::grid::*;
 pub use self::group::*;
 pub use self::label::*;
+pub use self::menu_bar::*;
+pub use self::number_input::*;
 pub use self::progress_bar::*;
 pub use self::radio_buttons::*;
-// pub use self::scroll_box::*;
+pub use self::scroll_box::*;
+pub use self::segmented_control::*;
 pub use self::slider::*;
-// pub use self::tabs::*;
+pub use self::spacer::*;
+pub use self::text_area::*;
+pub use self::tabs::*;
+pub use self::toggle_button::*;
+pub use self::toolbar::*;
+pub use self::tree_view::*;
+pub use crate::core::widget::WidgetPathKey;
+pub use crate::core::render::{TextRun, TextStyle, WrapMode};
 
 /// The `Widget` trait, as well as associated types used to create custom widgets.
 pub mod custom {
-    pub use crate::core::widget::{WidgetTag, Widget, Parent, WidgetSubtype, WidgetInfo, WidgetInfoMut, WidgetIdent};
+    pub use crate::core::widget::{WidgetTag, Widget, Parent, WidgetSubtype, WidgetInfo, WidgetInfoMut, WidgetIdent, ChildPathError};
 }
 
 /// What should be drawn inside of a label, or other widgets that contains a label.
@@ -44,6 +70,8 @@ pub enum Contents {
     Text(String),
     /// Draw the theme icon with the given name.
     Icon(String),
+    /// Draw the runs of text as a single paragraph, with each run drawn in its own style.
+    Rich(Vec<TextRun>),
 }
 
 impl Contents {
@@ -88,4 +116,34 @@ impl Contents {
             _ => None
         }
     }
+
+    pub fn as_rich(self) -> Option<Vec<TextRun>> {
+        match self {
+            Contents::Rich(c) => Some(c),
+            _ => None
+        }
+    }
+
+    pub fn as_rich_ref(&self) -> Option<&[TextRun]> {
+        match self {
+            Contents::Rich(c) => Some(c),
+            _ => None
+        }
+    }
+
+    pub fn as_rich_mut(&mut self) -> Option<&mut Vec<TextRun>> {
+        match self {
+            Contents::Rich(c) => Some(c),
+            _ => None
+        }
+    }
+
+    /// The text that gets displayed, with rich text runs joined together. `None` for icons.
+    pub fn plain_text(&self) -> Option<String> {
+        match self {
+            Contents::Text(c) => Some(c.clone()),
+            Contents::Icon(_) => None,
+            Contents::Rich(c) => Some(c.iter().map(|r| &r.text[..]).collect()),
+        }
+    }
 }
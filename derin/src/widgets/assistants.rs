@@ -2,10 +2,15 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+pub mod clipboard;
+mod debounce;
+pub mod mnemonic;
 mod slider;
 pub mod text_edit;
 pub mod toggle_button;
 
+pub use derin_core::render::text_nav;
+pub use self::debounce::*;
 pub use self::slider::*;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
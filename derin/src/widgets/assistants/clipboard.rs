@@ -0,0 +1,71 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Access to the system clipboards.
+
+use clipboard::{ClipboardContext, ClipboardProvider};
+
+/// Whether the platform has a primary selection. True on X11-style platforms.
+pub const PRIMARY_SELECTION_SUPPORTED: bool = cfg!(all(
+    unix,
+    not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+));
+
+/// A system clipboard that text can be copied into and pasted from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Clipboard {
+    /// The regular clipboard, used by cut, copy, and paste.
+    Standard,
+    /// The primary selection, which holds the most recently selected text and gets pasted by
+    /// clicking the middle mouse button.
+    ///
+    /// This only exists on X11-style platforms (see `PRIMARY_SELECTION_SUPPORTED`). Elsewhere, it's
+    /// always empty and setting its contents does nothing.
+    Primary,
+}
+
+impl Clipboard {
+    /// Retrieves the text on the clipboard, or `None` if the clipboard is empty or can't be read.
+    pub fn contents(self) -> Option<String> {
+        match self {
+            Clipboard::Standard => ClipboardContext::new().and_then(|mut c| c.get_contents()).ok(),
+            Clipboard::Primary => primary::contents(),
+        }
+    }
+
+    /// Replace the text on the clipboard, returning whether or not it could be replaced.
+    pub fn set_contents(self, contents: String) -> bool {
+        match self {
+            Clipboard::Standard => ClipboardContext::new().and_then(|mut c| c.set_contents(contents)).is_ok(),
+            Clipboard::Primary => primary::set_contents(contents),
+        }
+    }
+}
+
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))))]
+mod primary {
+    use clipboard::{
+        ClipboardProvider,
+        x11_clipboard::{X11ClipboardContext, Primary},
+    };
+
+    pub fn contents() -> Option<String> {
+        X11ClipboardContext::<Primary>::new().and_then(|mut c| c.get_contents()).ok()
+    }
+
+    pub fn set_contents(contents: String) -> bool {
+        X11ClipboardContext::<Primary>::new().and_then(|mut c| c.set_contents(contents)).is_ok()
+    }
+}
+
+#[cfg(not(all(unix, not(any(target_os = "macos", target_os = "android", target_os = "emscripten")))))]
+mod primary {
+    pub fn contents() -> Option<String> {
+        None
+    }
+
+    pub fn set_contents(_: String) -> bool {
+        false
+    }
+}
@@ -0,0 +1,126 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use derin_core::{
+    event::WidgetEvent,
+    timer::{Timer, TimerId},
+    widget::WidgetTag,
+};
+use std::time::{Duration, Instant};
+
+/// Timer assistant that only fires after a quiet period.
+///
+/// Each call to [`trigger`] (re)arms the timer, pushing the firing point back by `delay`. The
+/// widget gets told about the debounced change through [`fired`] only once `delay` has passed
+/// without another call to `trigger`.
+///
+/// The timer is stored in the widget's `WidgetTag`, so dropping the widget cancels any pending
+/// timer.
+///
+/// [`trigger`]: ./struct.Debounce.html#method.trigger
+/// [`fired`]: ./struct.Debounce.html#method.fired
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Debounce {
+    pub delay: Duration,
+    timer_id: Option<TimerId>,
+}
+
+impl Debounce {
+    pub fn new(delay: Duration) -> Debounce {
+        Debounce {
+            delay,
+            timer_id: None,
+        }
+    }
+
+    /// Arm the timer, resetting it if it's already armed.
+    pub fn trigger(&mut self, widget_tag: &mut WidgetTag) {
+        let timers = widget_tag.timers_mut();
+        if let Some(old_id) = self.timer_id.take() {
+            timers.remove(&old_id);
+        }
+
+        let timer_id = TimerId::new();
+        timers.insert(timer_id, Timer::new_delayed(self.delay, Instant::now() + self.delay));
+        self.timer_id = Some(timer_id);
+    }
+
+    /// Disarm the timer without firing it.
+    pub fn cancel(&mut self, widget_tag: &mut WidgetTag) {
+        if let Some(timer_id) = self.timer_id.take() {
+            widget_tag.timers_mut().remove(&timer_id);
+        }
+    }
+
+    /// Whether or not the timer is currently armed.
+    #[inline]
+    pub fn is_pending(&self) -> bool {
+        self.timer_id.is_some()
+    }
+
+    /// Returns `true` if `event` is this debouncer's timer firing, disarming the timer in the
+    /// process.
+    pub fn fired(&mut self, event: &WidgetEvent, widget_tag: &mut WidgetTag) -> bool {
+        match *event {
+            WidgetEvent::Timer{timer_id, ..} if Some(timer_id) == self.timer_id => {
+                self.cancel(widget_tag);
+                true
+            },
+            _ => false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn trigger_postpones() {
+        let mut widget_tag = WidgetTag::new();
+        let mut debounce = Debounce::new(Duration::from_secs(60));
+        debounce.trigger(&mut widget_tag);
+        let first_id = debounce.timer_id.unwrap();
+        let first_trigger = widget_tag.timers()[&first_id].next_trigger();
+
+        // Triggering again replaces the timer with one that fires later.
+        thread::sleep(Duration::from_millis(2));
+        debounce.trigger(&mut widget_tag);
+        let second_id = debounce.timer_id.unwrap();
+        assert_ne!(first_id, second_id);
+        assert_eq!(1, widget_tag.timers().len());
+        assert!(widget_tag.timers()[&second_id].next_trigger() > first_trigger);
+    }
+
+    #[test]
+    fn fired_and_cancel() {
+        let mut widget_tag = WidgetTag::new();
+        let mut debounce = Debounce::new(Duration::from_secs(60));
+        debounce.trigger(&mut widget_tag);
+        let timer_id = debounce.timer_id.unwrap();
+        let timer_event = |timer_id| WidgetEvent::Timer {
+            timer_id,
+            start_time: Instant::now(),
+            last_triggered: None,
+            frequency: Duration::from_secs(60),
+            times_triggered: 0,
+        };
+
+        // Other events, including other timers, are left alone.
+        assert!(!debounce.fired(&WidgetEvent::LoseFocus, &mut widget_tag));
+        assert!(!debounce.fired(&timer_event(TimerId::new()), &mut widget_tag));
+        assert!(debounce.is_pending());
+
+        assert!(debounce.fired(&timer_event(timer_id), &mut widget_tag));
+        assert!(!debounce.is_pending());
+        assert!(widget_tag.timers().is_empty());
+        assert!(!debounce.fired(&timer_event(timer_id), &mut widget_tag));
+
+        debounce.trigger(&mut widget_tag);
+        debounce.cancel(&mut widget_tag);
+        assert!(!debounce.is_pending());
+        assert!(widget_tag.timers().is_empty());
+    }
+}
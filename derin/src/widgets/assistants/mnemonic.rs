@@ -0,0 +1,64 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Parsing for mnemonics: characters in a caption that are underlined, and that can be typed to
+//! activate the widget showing the caption.
+//!
+//! Mnemonics are marked by placing `&` before a character, such as `"&File"`. `"&&"` inserts a
+//! literal `&`. Only the first marked character is used as the mnemonic.
+
+/// Strip the `&` mnemonic markers out of `text`, returning the displayed text along with the byte
+/// index of the mnemonic character.
+pub fn parse_mnemonic(text: &str) -> (String, Option<usize>) {
+    let mut display = String::with_capacity(text.len());
+    let mut mnemonic = None;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '&') {
+            (_, false) => display.push(c),
+            (_, true) => match chars.next() {
+                Some('&') => display.push('&'),
+                Some(c) => {
+                    if mnemonic.is_none() {
+                        mnemonic = Some(display.len());
+                    }
+                    display.push(c);
+                },
+                None => ()
+            }
+        }
+    }
+    (display, mnemonic)
+}
+
+/// Get the lowercase mnemonic character out of text returned by `parse_mnemonic`.
+pub fn mnemonic_char(text: &str, mnemonic: Option<usize>) -> Option<char> {
+    mnemonic
+        .and_then(|i| text[i..].chars().next())
+        .and_then(|c| c.to_lowercase().next())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(("Save".to_string(), Some(0)), parse_mnemonic("&Save"));
+        assert_eq!(("Save As".to_string(), Some(5)), parse_mnemonic("Save &As"));
+        assert_eq!(("Save".to_string(), None), parse_mnemonic("Save"));
+        assert_eq!(("Salt & Pepper".to_string(), Some(7)), parse_mnemonic("Salt && &Pepper"));
+        // Only the first marker counts.
+        assert_eq!(("Save".to_string(), Some(0)), parse_mnemonic("&S&ave"));
+        // A trailing marker is dropped.
+        assert_eq!(("Save".to_string(), None), parse_mnemonic("Save&"));
+    }
+
+    #[test]
+    fn char() {
+        let (text, mnemonic) = parse_mnemonic("Save &As");
+        assert_eq!(Some('a'), mnemonic_char(&text, mnemonic));
+        assert_eq!(None, mnemonic_char("Save", None));
+    }
+}
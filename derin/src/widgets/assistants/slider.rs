@@ -21,26 +21,41 @@ pub struct SliderAssist {
 impl SliderAssist {
     #[inline]
     pub fn round_to_step(&mut self) {
-        self.value = ((self.value - self.min) / self.step).round() * self.step + self.min;
+        if self.step > 0.0 {
+            self.value = ((self.value - self.min) / self.step).round() * self.step + self.min;
+        }
         self.value = self.value.min(self.max).max(self.min);
     }
 
+    /// The value's position within `min..=max`, from `0.0` to `1.0`. An empty or inverted range
+    /// pins the value at `min`.
+    fn value_fraction(&self) -> f32 {
+        match self.max > self.min {
+            true => ((self.value - self.min) / (self.max - self.min)).min(1.0).max(0.0),
+            false => 0.0
+        }
+    }
+
     pub fn head_rect(&self) -> BoundBox<D2, i32> {
         let (bar_size, bar_min) = match self.horizontal {
             true => (self.bar_rect.width(), self.bar_rect.min.x),
             false => (self.bar_rect.height(), self.bar_rect.min.y)
         };
 
-        let head_start = (((self.value - self.min) / (self.max - self.min)) * (bar_size - self.head_size) as f32) as i32 + bar_min;
+        let bar_size = bar_size.max(0);
+
+        // If the bar is too small to fit the head, the head fills the bar.
+        let head_size = self.head_size.min(bar_size);
+        let head_start = (self.value_fraction() * (bar_size - head_size) as f32) as i32 + bar_min;
 
         match self.horizontal {
             true => BoundBox::new2(
                 head_start, self.bar_rect.min.y,
-                head_start + self.head_size, self.bar_rect.max.y
+                head_start + head_size, self.bar_rect.max.y
             ),
             false => BoundBox::new2(
                 self.bar_rect.min.x, head_start,
-                self.bar_rect.max.x, head_start + self.head_size
+                self.bar_rect.max.x, head_start + head_size
             )
         }
     }
@@ -65,6 +80,34 @@ impl SliderAssist {
         }
     }
 
+    /// The direction, along the bar's axis, from the head to `pos`. `None` if `pos` is outside
+    /// the bar or on the head itself; otherwise `-1.0` if `pos` is before the head and `1.0` if
+    /// it's after it.
+    pub fn trough_direction(&self, pos: Point2<i32>) -> Option<f32> {
+        let head_rect = self.head_rect();
+        if !self.bar_rect.contains(pos) || head_rect.contains(pos) {
+            return None;
+        }
+
+        let (pos_axis, head_min) = match self.horizontal {
+            true => (pos.x, head_rect.min.x),
+            false => (pos.y, head_rect.min.y)
+        };
+        match pos_axis < head_min {
+            true => Some(-1.0),
+            false => Some(1.0)
+        }
+    }
+
+    /// Moves the value by `page` in `direction`, clamping it to `min..=max`. Returns whether the
+    /// value changed.
+    pub fn page(&mut self, direction: f32, page: f32) -> bool {
+        let old_value = self.value;
+        self.value += direction * page;
+        self.round_to_step();
+        self.value != old_value
+    }
+
     pub fn move_head(&mut self, pos_px: i32) {
         if let Some(head_click_pos) = self.head_click_pos {
             let head_offset = self.head_size / 2;
@@ -79,6 +122,12 @@ impl SliderAssist {
                 )
             };
 
+            // A degenerate bar or an empty value range leaves nowhere to move the head.
+            if bar_range_max <= bar_range_min || self.max <= self.min {
+                self.value = self.min;
+                return;
+            }
+
             self.value = (pos_px - head_click_pos - (bar_range_min - head_offset)) as f32
                 / (bar_range_max - bar_range_min) as f32
                 * (self.max - self.min);
@@ -86,3 +135,61 @@ impl SliderAssist {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assist(min: f32, max: f32, bar_rect: BoundBox<D2, i32>) -> SliderAssist {
+        SliderAssist {
+            value: min,
+            step: 1.0,
+            min, max,
+
+            head_size: 10,
+            bar_rect,
+            head_click_pos: None,
+            horizontal: true
+        }
+    }
+
+    #[test]
+    fn empty_range() {
+        let mut slider = assist(5.0, 5.0, BoundBox::new2(0, 0, 100, 10));
+        assert_eq!(BoundBox::new2(0, 0, 10, 10), slider.head_rect());
+
+        assert!(slider.click_head(Point2::new(50, 5)));
+        slider.move_head(80);
+        assert_eq!(5.0, slider.value);
+        assert_eq!(BoundBox::new2(0, 0, 10, 10), slider.head_rect());
+    }
+
+    #[test]
+    fn zero_width_bar() {
+        let mut slider = assist(0.0, 10.0, BoundBox::new2(20, 0, 20, 10));
+        slider.value = 5.0;
+        assert_eq!(BoundBox::new2(20, 0, 20, 10), slider.head_rect());
+
+        slider.head_click_pos = Some(0);
+        slider.move_head(40);
+        assert_eq!(0.0, slider.value);
+        assert_eq!(BoundBox::new2(20, 0, 20, 10), slider.head_rect());
+    }
+
+    #[test]
+    fn trough_paging() {
+        let mut slider = assist(0.0, 90.0, BoundBox::new2(0, 0, 100, 10));
+        assert_eq!(None, slider.trough_direction(Point2::new(5, 5)));
+        assert_eq!(Some(1.0), slider.trough_direction(Point2::new(50, 5)));
+        assert_eq!(None, slider.trough_direction(Point2::new(50, 20)));
+
+        assert!(slider.page(1.0, 40.0));
+        assert_eq!(40.0, slider.value);
+        assert_eq!(Some(-1.0), slider.trough_direction(Point2::new(5, 5)));
+
+        assert!(slider.page(1.0, 80.0));
+        assert_eq!(90.0, slider.value);
+        assert!(!slider.page(1.0, 80.0));
+        assert_eq!(None, slider.trough_direction(Point2::new(95, 5)));
+    }
+}
@@ -3,12 +3,14 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use crate::{
+    cgmath::Point2,
     event::{Key, ModifierKeys, WidgetEvent, FocusChange, InputState, MouseHoverChange},
     theme::CursorIcon,
+    widgets::assistants::clipboard::{self, Clipboard},
 };
-use clipboard::{ClipboardContext, ClipboardProvider};
 use cgmath_geometry::line::Segment;
-use derin_core::render::{CursorData, CursorOp};
+use derin_core::render::{CaretShape, CursorData, CursorOp, TextLayoutResult};
+use std::ops::Range;
 
 pub trait CharFilter {
     fn char_allowed(&mut self, c: char) -> bool;
@@ -42,6 +44,27 @@ impl CharFilter for LineCharFilter {
     }
 }
 
+/// Filter for multi-line text that's edited with the keyboard, where `Enter` inserts a line break.
+///
+/// Carriage returns get rejected, since pressing `Enter` also produces a `'\r'` character. The line
+/// break is inserted by the widget handling the `Enter` key instead.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AreaCharFilter;
+impl CharFilter for AreaCharFilter {
+    #[inline(always)]
+    fn char_allowed(&mut self, c: char) -> bool {
+        match c {
+            '\r' => false,
+            _ => DefaultCharFilter.char_allowed(c)
+        }
+    }
+}
+
+/// The default distance, in pixels along either axis, the cursor has to move while the left mouse
+/// button is held before a selection drag starts. Matches how far the cursor can move for a press
+/// to still count as a `Click`.
+pub const DEFAULT_DRAG_THRESHOLD: i32 = 4;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CursorFlashOp {
     Start,
@@ -52,6 +75,8 @@ pub enum CursorFlashOp {
 pub struct TextEditOps {
     pub allow_bubble: bool,
     pub redraw: bool,
+    /// Whether or not the event queued an operation that modifies the string.
+    pub text_changed: bool,
     pub focus: Option<FocusChange>,
     pub cursor_flash: Option<CursorFlashOp>,
     pub cursor_icon: Option<CursorIcon>,
@@ -65,11 +90,120 @@ pub struct TextEditAssist<C = DefaultCharFilter>
     pub cursor_data: CursorData,
     pub cursor_ops: Vec<CursorOp>,
     pub filter: C,
+    /// If `true`, the text can be selected and copied but not modified, and no cursor is drawn.
+    pub read_only: bool,
+    /// The glyph positions from the last time the text was laid out, if the renderer provided them.
+    pub text_layout: Option<TextLayoutResult>,
+    /// If `true`, typed characters replace the grapheme after the cursor instead of being inserted.
+    /// Toggled with the `Insert` key.
+    pub overwrite: bool,
+    /// How far, in pixels along either axis, the cursor has to move while the left mouse button is
+    /// held before the movement starts selecting text. Smaller movements get ignored, so a press
+    /// with a bit of jitter just places the caret. `None` uses `DEFAULT_DRAG_THRESHOLD`.
+    pub drag_threshold: Option<i32>,
+    /// Whether the cursor has moved past the drag threshold since the left mouse button was
+    /// pressed.
+    dragging: bool,
+    /// If `true`, middle-clicking doesn't paste the primary selection, and selecting text doesn't
+    /// copy it into the primary selection. Only matters on platforms with a primary selection (see
+    /// `clipboard::PRIMARY_SELECTION_SUPPORTED`).
+    pub disable_primary_selection: bool,
+    /// The selection that was last copied into the primary selection.
+    primary_range: Range<usize>,
+    /// The horizontal position the caret tries to stay at while it's moved up and down between
+    /// lines, so that passing through a short line doesn't pull it towards the start of the text.
+    /// Cleared whenever the caret gets moved some other way.
+    desired_x: Option<i32>,
+}
+
+impl<C> TextEditAssist<C>
+    where C: CharFilter + Default
+{
+    /// Create an assist editing `string`.
+    pub fn new(string: String) -> TextEditAssist<C> {
+        TextEditAssist {
+            string,
+            ..TextEditAssist::default()
+        }
+    }
 }
 
 impl<C> TextEditAssist<C>
     where C: CharFilter
 {
+    /// Switch between overwriting and inserting typed characters, updating the caret's shape to
+    /// match.
+    pub fn set_overwrite(&mut self, overwrite: bool) {
+        self.overwrite = overwrite;
+        self.cursor_data.caret_shape = match overwrite {
+            true => CaretShape::Block,
+            false => CaretShape::Bar,
+        };
+    }
+
+    /// Whether middle-clicking pastes the primary selection, and selecting text copies into it.
+    #[inline]
+    pub fn uses_primary_selection(&self) -> bool {
+        clipboard::PRIMARY_SELECTION_SUPPORTED && !self.disable_primary_selection
+    }
+
+    /// Copy the selected text into the primary selection, if it's changed since it was last
+    /// copied. Queued cursor operations only get applied when the text is laid out, so this
+    /// should be called after that.
+    pub fn update_primary_selection(&mut self) {
+        let highlight_range = self.cursor_data.highlight_range.clone();
+        if !self.uses_primary_selection() || highlight_range == self.primary_range {
+            return;
+        }
+
+        self.primary_range = highlight_range.clone();
+        if highlight_range.start != highlight_range.end {
+            if let Some(selected) = self.string.get(highlight_range) {
+                Clipboard::Primary.set_contents(selected.to_string());
+            }
+        }
+    }
+
+    /// Forget the column the caret was being kept at while moving between lines. Widgets that move
+    /// the caret themselves, instead of through `adapt_event`, should call this.
+    #[inline]
+    pub fn clear_desired_x(&mut self) {
+        self.desired_x = None;
+    }
+
+    /// Queue moving the caret `delta` lines up or down, keeping it as close as possible to the
+    /// column it started out in.
+    ///
+    /// The target line is found with the glyph positions from the last layout. If those aren't
+    /// available, or if other operations have been queued since the layout that could make them
+    /// stale, the movement is left up to the renderer with `CursorOp::MoveVertical`.
+    pub fn move_vertical(&mut self, delta: isize, expand_selection: bool) {
+        let op = match self.cursor_ops.is_empty() {
+            true => self.vertical_selection(delta, expand_selection),
+            false => None
+        };
+        self.cursor_ops.push(op.unwrap_or(CursorOp::MoveVertical{ delta, expand_selection }));
+    }
+
+    fn vertical_selection(&mut self, delta: isize, expand_selection: bool) -> Option<CursorOp> {
+        let text_layout = self.text_layout.as_ref()?;
+        let CursorData{ cursor_pos, ref highlight_range, .. } = self.cursor_data;
+
+        let caret = text_layout.point_at_index(cursor_pos)?;
+        let lines = text_layout.lines();
+        let line = lines.iter().position(|line| line.top == caret.y)?;
+        let target_line = (line as isize + delta).max(0).min(lines.len() as isize - 1) as usize;
+        let target = Point2::new(*self.desired_x.get_or_insert(caret.x), lines[target_line].top);
+
+        // The end of the selection that isn't at the caret stays put.
+        let anchor = match (expand_selection, highlight_range.start == cursor_pos) {
+            (false, _) => target,
+            (true, true) => text_layout.point_at_index(highlight_range.end)?,
+            (true, false) => text_layout.point_at_index(highlight_range.start)?,
+        };
+        Some(CursorOp::SelectOnSegment(Segment::new(anchor, target)))
+    }
+
     pub fn adapt_event(&mut self, event: &WidgetEvent, input_state: InputState) -> TextEditOps {
         use self::WidgetEvent::*;
         use derin_common_types::buttons::MouseButton;
@@ -78,7 +212,9 @@ impl<C> TextEditAssist<C>
         let mut cursor_icon = None;
         let mut allow_bubble = true;
         let mut redraw = false;
+        let mut text_changed = false;
         let mut cursor_flash = None;
+        let mut keep_desired_x = false;
 
         match *event {
             KeyDown(key, modifiers) => loop {
@@ -95,14 +231,14 @@ impl<C> TextEditAssist<C>
                         expand_selection: modifiers.contains(ModifierKeys::SHIFT),
                         jump_to_word_boundaries,
                     }),
-                    (Key::UArrow, _) => self.cursor_ops.push(CursorOp::MoveVertical {
-                        delta: -1,
-                        expand_selection: modifiers.contains(ModifierKeys::SHIFT),
-                    }),
-                    (Key::DArrow, _) => self.cursor_ops.push(CursorOp::MoveVertical {
-                        delta: 1,
-                        expand_selection: modifiers.contains(ModifierKeys::SHIFT),
-                    }),
+                    (Key::UArrow, _) => {
+                        self.move_vertical(-1, modifiers.contains(ModifierKeys::SHIFT));
+                        keep_desired_x = true;
+                    },
+                    (Key::DArrow, _) => {
+                        self.move_vertical(1, modifiers.contains(ModifierKeys::SHIFT));
+                        keep_desired_x = true;
+                    },
                     (Key::A, ModifierKeys::CTRL) => self.cursor_ops.push(CursorOp::SelectAll),
 
                     // This implementation has a bug - if any `CursorOp`s has been submitted earlier in
@@ -111,31 +247,44 @@ impl<C> TextEditAssist<C>
                     // way to fix that is to add `Cut`/`Copy`/`Paste` events to `CursorOp`, which I'm
                     // presently against.
                     (Key::C, ModifierKeys::CTRL) => {
-                        if let Ok(mut clipboard) = ClipboardContext::new() {
-                            let new_contents = self.string[self.cursor_data.highlight_range.clone()].to_string();
-                            clipboard.set_contents(new_contents).ok();
-                        }
+                        let new_contents = self.string[self.cursor_data.highlight_range.clone()].to_string();
+                        Clipboard::Standard.set_contents(new_contents);
                     },
+                    (Key::V, ModifierKeys::CTRL) |
+                    (Key::X, ModifierKeys::CTRL) |
+                    (Key::Back, _) |
+                    (Key::Delete, _) if self.read_only => break,
                     (Key::V, ModifierKeys::CTRL) => {
-                        if let Ok(clipboard_contents) = ClipboardContext::new().and_then(|mut c| c.get_contents()) {
+                        if let Some(clipboard_contents) = Clipboard::Standard.contents() {
                             self.cursor_ops.push(CursorOp::InsertString(clipboard_contents));
+                            text_changed = true;
                         }
                     },
                     (Key::X, ModifierKeys::CTRL) => {
-                        if let Ok(mut clipboard) = ClipboardContext::new() {
-                            let new_contents = self.string[self.cursor_data.highlight_range.clone()].to_string();
-                            clipboard.set_contents(new_contents).ok();
+                        let new_contents = self.string[self.cursor_data.highlight_range.clone()].to_string();
+                        if Clipboard::Standard.set_contents(new_contents) {
                             self.cursor_ops.push(CursorOp::DeleteSelection);
+                            text_changed = true;
                         }
                     },
-                    (Key::Back, _) => self.cursor_ops.push(CursorOp::DeleteChars {
-                        dist: -1,
-                        jump_to_word_boundaries,
-                    }),
-                    (Key::Delete, _) => self.cursor_ops.push(CursorOp::DeleteChars {
-                        dist: 1,
-                        jump_to_word_boundaries,
-                    }),
+                    (Key::Back, _) => {
+                        self.cursor_ops.push(CursorOp::DeleteChars {
+                            dist: -1,
+                            jump_to_word_boundaries,
+                        });
+                        text_changed = true;
+                    },
+                    (Key::Delete, _) => {
+                        self.cursor_ops.push(CursorOp::DeleteChars {
+                            dist: 1,
+                            jump_to_word_boundaries,
+                        });
+                        text_changed = true;
+                    },
+                    (Key::Insert, _) if modifiers.is_empty() && !self.read_only => {
+                        let overwrite = !self.overwrite;
+                        self.set_overwrite(overwrite);
+                    },
                     _ => break
                 }
                 redraw = true;
@@ -143,20 +292,36 @@ impl<C> TextEditAssist<C>
                 break;
             },
             KeyUp(..) => allow_bubble = false,
-            Char(c) => if self.filter.char_allowed(c) {
+            Char(c) => if !self.read_only && self.filter.char_allowed(c) {
                 allow_bubble = false;
-                self.cursor_ops.push(CursorOp::InsertChar(c));
+                self.cursor_ops.push(match self.overwrite {
+                    true => CursorOp::OverwriteChar(c),
+                    false => CursorOp::InsertChar(c),
+                });
                 redraw = true;
+                text_changed = true;
                 cursor_flash = Some(CursorFlashOp::Start);
             }
             MouseDown{in_widget: true, button, pos} => {
                 focus = Some(FocusChange::Take);
                 if button == MouseButton::Left {
+                    self.dragging = false;
                     self.cursor_ops.push(CursorOp::SelectOnSegment(Segment::new(pos, pos)));
                     redraw = true;
                     cursor_flash = Some(CursorFlashOp::Start);
                 }
             },
+            MouseUp{button: MouseButton::Left, ..} => self.dragging = false,
+            Click{pos, button: MouseButton::Middle} if !self.read_only && self.uses_primary_selection() => {
+                if let Some(primary_contents) = Clipboard::Primary.contents() {
+                    allow_bubble = false;
+                    self.cursor_ops.push(CursorOp::SelectOnSegment(Segment::new(pos, pos)));
+                    self.cursor_ops.push(CursorOp::InsertString(primary_contents));
+                    redraw = true;
+                    text_changed = true;
+                    cursor_flash = Some(CursorFlashOp::Start);
+                }
+            },
             MouseDown{in_widget: false, ..} => {
                 focus = Some(FocusChange::Remove);
                 self.cursor_data.draw_cursor = false;
@@ -170,8 +335,13 @@ impl<C> TextEditAssist<C>
                     _ => ()
                 }
                 if let Some(down) = input_state.mouse_buttons_down_in_widget.iter().find(|d| d.button == MouseButton::Left) {
-                    self.cursor_ops.push(CursorOp::SelectOnSegment(Segment::new(down.down_pos, new_pos)));
-                    redraw = true;
+                    let threshold = self.drag_threshold.unwrap_or(DEFAULT_DRAG_THRESHOLD);
+                    let drag = new_pos - down.down_pos;
+                    self.dragging |= drag.x.abs() > threshold || drag.y.abs() > threshold;
+                    if self.dragging {
+                        self.cursor_ops.push(CursorOp::SelectOnSegment(Segment::new(down.down_pos, new_pos)));
+                        redraw = true;
+                    }
                 }
             },
             GainFocus(_, _) => {
@@ -185,12 +355,151 @@ impl<C> TextEditAssist<C>
             },
             _ => ()
         };
+        if redraw && !keep_desired_x {
+            self.desired_x = None;
+        }
+        if self.read_only {
+            self.cursor_data.draw_cursor = false;
+            cursor_flash = None;
+        }
         TextEditOps {
             allow_bubble,
             redraw,
+            text_changed,
             cursor_flash,
             cursor_icon,
             focus
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cgmath::Point2,
+        event::MouseDown,
+    };
+    use cgmath_geometry::D2;
+    use derin_common_types::buttons::MouseButton;
+
+    fn input_state(buttons_down: &[MouseDown]) -> InputState<'_> {
+        InputState {
+            mouse_buttons_down: buttons_down,
+            mouse_buttons_down_in_widget: buttons_down,
+            mouse_pos: None,
+            mouse_in_window: true,
+            modifiers: ModifierKeys::empty(),
+            keys_down: &[],
+        }
+    }
+
+    fn selections(edit: &TextEditAssist) -> Vec<Segment<D2, i32>> {
+        edit.cursor_ops.iter().filter_map(|op| match *op {
+            CursorOp::SelectOnSegment(ref segment) => Some(segment.clone()),
+            _ => None
+        }).collect()
+    }
+
+    #[test]
+    fn click_jitter_doesnt_select() {
+        let mut edit = TextEditAssist::default();
+        let down_pos = Point2::new(10, 5);
+        let down = [MouseDown::new(MouseButton::Left, down_pos)];
+
+        edit.adapt_event(&WidgetEvent::MouseDown{ pos: down_pos, in_widget: true, button: MouseButton::Left }, input_state(&[]));
+        edit.adapt_event(
+            &WidgetEvent::MouseMove{ old_pos: down_pos, new_pos: Point2::new(11, 5), in_widget: true, hover_change: None },
+            input_state(&down),
+        );
+        edit.adapt_event(
+            &WidgetEvent::MouseUp{ pos: Point2::new(11, 5), down_pos, in_widget: true, pressed_in_widget: true, button: MouseButton::Left },
+            input_state(&down),
+        );
+
+        // Only the caret placement from the press should have been queued.
+        assert_eq!(vec![Segment::new(down_pos, down_pos)], selections(&edit));
+    }
+
+    #[test]
+    fn drag_past_threshold_selects() {
+        let mut edit = TextEditAssist::default();
+        let down_pos = Point2::new(10, 5);
+        let down = [MouseDown::new(MouseButton::Left, down_pos)];
+
+        edit.adapt_event(&WidgetEvent::MouseDown{ pos: down_pos, in_widget: true, button: MouseButton::Left }, input_state(&[]));
+        edit.adapt_event(
+            &WidgetEvent::MouseMove{ old_pos: down_pos, new_pos: Point2::new(30, 5), in_widget: true, hover_change: None },
+            input_state(&down),
+        );
+        // Once the drag has started, moving back within the threshold keeps adjusting the selection.
+        edit.adapt_event(
+            &WidgetEvent::MouseMove{ old_pos: Point2::new(30, 5), new_pos: Point2::new(12, 5), in_widget: true, hover_change: None },
+            input_state(&down),
+        );
+
+        assert_eq!(
+            vec![
+                Segment::new(down_pos, down_pos),
+                Segment::new(down_pos, Point2::new(30, 5)),
+                Segment::new(down_pos, Point2::new(12, 5)),
+            ],
+            selections(&edit)
+        );
+    }
+
+    #[test]
+    fn vertical_movement_keeps_column() {
+        use derin_core::render::{CaretPosition, TextLine};
+
+        // Three lines, "abcd\nab\nabcd", with every grapheme 10 pixels wide.
+        let line = |start: usize, len: usize, top: i32| TextLine {
+            range: start..start + len,
+            top,
+            height: 10,
+            carets: (0..=len).map(|i| CaretPosition{ index: start + i, x: i as i32 * 10 }).collect(),
+        };
+        let text_layout = TextLayoutResult::new(vec![line(0, 4, 0), line(5, 2, 10), line(8, 4, 20)]);
+
+        let mut edit = TextEditAssist::default();
+        edit.string = "abcd\nab\nabcd".to_string();
+        edit.text_layout = Some(text_layout);
+        edit.cursor_data.cursor_pos = 3;
+        edit.cursor_data.highlight_range = 3..3;
+
+        let down = WidgetEvent::KeyDown(Key::DArrow, ModifierKeys::empty());
+        edit.adapt_event(&down, input_state(&[]));
+        assert_eq!(vec![Segment::new(Point2::new(30, 10), Point2::new(30, 10))], selections(&edit));
+        assert_eq!(Some(30), edit.desired_x);
+
+        // The renderer would have placed the caret at the end of the short line.
+        edit.cursor_ops.clear();
+        edit.cursor_data.cursor_pos = 7;
+        edit.cursor_data.highlight_range = 7..7;
+        edit.adapt_event(&down, input_state(&[]));
+        assert_eq!(vec![Segment::new(Point2::new(30, 20), Point2::new(30, 20))], selections(&edit));
+
+        // Moving horizontally forgets the column.
+        edit.cursor_ops.clear();
+        edit.adapt_event(&WidgetEvent::KeyDown(Key::LArrow, ModifierKeys::empty()), input_state(&[]));
+        assert_eq!(None, edit.desired_x);
+
+        // Without a fresh layout, the movement falls back to the renderer.
+        edit.adapt_event(&down, input_state(&[]));
+        assert_eq!(Some(&CursorOp::MoveVertical{ delta: 1, expand_selection: false }), edit.cursor_ops.last());
+    }
+
+    #[test]
+    fn middle_click_paste_disabled() {
+        let mut edit = TextEditAssist {
+            disable_primary_selection: true,
+            ..TextEditAssist::default()
+        };
+
+        let ops = edit.adapt_event(&WidgetEvent::Click{ pos: Point2::new(10, 5), button: MouseButton::Middle }, input_state(&[]));
+
+        assert!(!edit.uses_primary_selection());
+        assert!(!ops.text_changed);
+        assert!(edit.cursor_ops.is_empty());
+    }
+}
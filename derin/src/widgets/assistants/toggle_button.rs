@@ -145,6 +145,7 @@ impl<H, T> Widget for Toggle<H, T>
         let event = event.unwrap();
 
         let (mut new_selected, mut new_state) = (self.tbox.selected, self.tbox.button_state);
+        let mut handled = true;
         match event {
             MouseMove{hover_change: Some(ref change), ..} => match change {
                 MouseHoverChange::Enter => new_state = ButtonState::Hover,
@@ -152,14 +153,13 @@ impl<H, T> Widget for Toggle<H, T>
                 _ => ()
             },
             MouseDown{..} => new_state = ButtonState::Pressed,
-            MouseUp{in_widget: true, pressed_in_widget: true, ..} => {
-                self.handler.on_click(&mut new_selected);
-                new_state = ButtonState::Hover;
-            },
+            MouseUp{in_widget: true, pressed_in_widget: true, ..} => new_state = ButtonState::Hover,
+            Click{..} => self.handler.on_click(&mut new_selected),
             MouseUp{in_widget: false, ..} => new_state = ButtonState::Normal,
+            MouseLeaveWindow if new_state == ButtonState::Hover => new_state = ButtonState::Normal,
             GainFocus(_, _) => new_state = ButtonState::Hover,
             LoseFocus => new_state = ButtonState::Normal,
-            _ => ()
+            _ => handled = false
         };
 
         if new_selected != self.tbox.selected || new_state != self.tbox.button_state {
@@ -172,6 +172,7 @@ impl<H, T> Widget for Toggle<H, T>
         EventOps {
             focus: None,
             bubble: event.default_bubble(),
+            handled,
         }
     }
 }
@@ -255,6 +256,7 @@ impl Widget for ToggleBox {
         EventOps {
             focus: None,
             bubble: true,
+            handled: false,
         }
     }
 }
@@ -302,7 +304,8 @@ impl<R> WidgetRenderable<R> for ToggleBox
     }
 
     fn update_layout(&mut self, layout: &mut R::Layout) {
+        let indicator_size = layout.control_metrics().indicator_size;
         let result = layout.finish();
-        self.size_bounds = result.size_bounds;
+        self.size_bounds = result.size_bounds.expand_min(DimsBox::new2(indicator_size, indicator_size));
     }
 }
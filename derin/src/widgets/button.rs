@@ -9,17 +9,25 @@ use derin_core::{
 };
 use crate::widgets::{
     Contents,
-    assistants::ButtonState,
+    assistants::{
+        ButtonState,
+        mnemonic::{parse_mnemonic, mnemonic_char},
+    },
 };
 
-use cgmath_geometry::{D2, rect::BoundBox};
+use cgmath_geometry::{D2, rect::{BoundBox, DimsBox}};
 use derin_common_types::layout::SizeBounds;
 
 /// A simple push-button.
 ///
 /// When pressed, calls the [`on_click`] function in the associated handler passed in by the `new`
-/// function.
+/// function. Buttons created with [`with_mnemonic`] can also be pressed by holding `Alt` and typing
+/// the mnemonic character.
 ///
+/// A press only counts if the mouse button gets released over the button without the cursor
+/// having been dragged more than 4 pixels, as with `WidgetEvent::Click`.
+///
+/// [`with_mnemonic`]: ./struct.Button.html#method.with_mnemonic
 /// [`on_click`]: ./trait.ButtonHandler.html#tymethod.on_click
 #[derive(Debug, Clone)]
 pub struct Button<H> {
@@ -28,6 +36,8 @@ pub struct Button<H> {
     state: ButtonState,
     pub handler: H,
     contents: Contents,
+    /// The byte index of the mnemonic character within the text contents.
+    mnemonic: Option<usize>,
     size_bounds: SizeBounds
 }
 
@@ -39,6 +49,9 @@ pub trait ButtonHandler: 'static {
 #[derive(Debug, Clone, Copy)]
 pub struct ButtonTheme {
     pub state: ButtonState,
+    /// The byte index of the mnemonic character within the button's text, if the mnemonic should
+    /// be underlined.
+    pub mnemonic: Option<usize>,
 }
 
 impl<H> Button<H> {
@@ -50,18 +63,36 @@ impl<H> Button<H> {
             state: ButtonState::Normal,
             handler,
             contents,
+            mnemonic: None,
             size_bounds: SizeBounds::default()
         }
     }
 
+    /// Creates a new button displaying `text`, with the mnemonic marked by `&` (e.g. `"&Save"`).
+    /// `"&&"` displays a literal `&`.
+    ///
+    /// The mnemonic character gets underlined, and becomes the button's access key.
+    pub fn with_mnemonic(text: &str, handler: H) -> Button<H> {
+        let (text, mnemonic) = parse_mnemonic(text);
+        let mut button = Button::new(Contents::Text(text), handler);
+        button.mnemonic = mnemonic;
+        button.widget_tag.set_access_key(mnemonic_char(button.contents.as_text_ref().unwrap(), mnemonic));
+        button
+    }
+
     pub fn contents(&self) -> &Contents {
         &self.contents
     }
 
+    /// Retrieves the contents of the button, for mutation.
+    ///
+    /// This removes the button's mnemonic, if it has one.
     pub fn contents_mut(&mut self) -> &mut Contents {
         self.widget_tag
             .request_redraw()
-            .request_relayout();
+            .request_relayout()
+            .set_access_key(None);
+        self.mnemonic = None;
         &mut self.contents
     }
 }
@@ -88,10 +119,16 @@ impl<H> Widget for Button<H>
         self.size_bounds
     }
 
+    #[inline]
+    fn accepts_focus(&self) -> bool {
+        true
+    }
+
     fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
         use self::WidgetEvent::*;
         let event = event.unwrap();
 
+        let mut handled = true;
         let new_state = match event {
             MouseMove{hover_change: Some(ref change), ..} => match change {
                 MouseHoverChange::Enter => ButtonState::Hover,
@@ -99,14 +136,23 @@ impl<H> Widget for Button<H>
                 _ => self.state
             },
             MouseDown{..} => ButtonState::Pressed,
-            MouseUp{in_widget: true, pressed_in_widget: true, ..} => {
+            MouseUp{in_widget: true, pressed_in_widget: true, ..} => ButtonState::Hover,
+            Click{..} => {
                 self.handler.on_click();
-                ButtonState::Hover
+                self.state
             },
             MouseUp{in_widget: false, ..} => ButtonState::Normal,
+            MouseLeaveWindow if self.state == ButtonState::Hover => ButtonState::Normal,
+            AccessKey => {
+                self.handler.on_click();
+                self.state
+            },
             GainFocus(_, _) => ButtonState::Hover,
             LoseFocus => ButtonState::Normal,
-            _ => self.state
+            _ => {
+                handled = false;
+                self.state
+            }
         };
 
         if new_state != self.state {
@@ -118,6 +164,7 @@ impl<H> Widget for Button<H>
         EventOps {
             focus: None,
             bubble: event.default_bubble(),
+            handled,
         }
     }
 }
@@ -131,6 +178,7 @@ impl<R, H> WidgetRenderable<R> for Button<H>
     fn theme(&self) -> ButtonTheme {
         ButtonTheme {
             state: self.state,
+            mnemonic: self.mnemonic,
         }
     }
 
@@ -142,10 +190,12 @@ impl<R, H> WidgetRenderable<R> for Button<H>
         match self.contents {
             Contents::Text(ref s) => layout.prepare_string(s),
             Contents::Icon(ref i) => layout.prepare_icon(i),
+            Contents::Rich(ref r) => layout.prepare_rich_string(r),
         }
 
+        let control_height = layout.control_metrics().control_height;
         let result = layout.finish();
-        self.size_bounds = result.size_bounds;
+        self.size_bounds = result.size_bounds.expand_min(DimsBox::new2(0, control_height));
     }
 }
 
@@ -155,3 +205,52 @@ impl WidgetTheme for ButtonTheme {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cgmath::Point2,
+        core::{Root, WindowEvent, render::headless::HeadlessRenderer},
+        event::MouseButton,
+        test_helpers::{headless_root, send_events},
+    };
+    use std::{cell::Cell, rc::Rc};
+
+    struct Counter(Rc<Cell<u32>>);
+
+    impl ButtonHandler for Counter {
+        fn on_click(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    fn drag(root: &mut Root<Button<Counter>, HeadlessRenderer>, from: Point2<i32>, to: Point2<i32>) {
+        send_events(root, &[
+            WindowEvent::MouseMove(from),
+            WindowEvent::MouseDown(MouseButton::Left),
+            WindowEvent::MouseMove(to),
+            WindowEvent::MouseUp(MouseButton::Left),
+        ]);
+    }
+
+    #[test]
+    fn drag_threshold() {
+        let clicks = Rc::new(Cell::new(0));
+        let button = Button::new(Contents::Text("Button".to_string()), Counter(clicks.clone()));
+        let mut root = headless_root(button, DimsBox::new2(100, 40));
+
+        // Moving up to 4 pixels along each axis still counts as a click.
+        drag(&mut root, Point2::new(20, 20), Point2::new(24, 16));
+        assert_eq!(1, clicks.get());
+
+        // Moving any further doesn't, even if the button's released inside of the widget.
+        drag(&mut root, Point2::new(20, 20), Point2::new(25, 20));
+        assert_eq!(1, clicks.get());
+        drag(&mut root, Point2::new(20, 20), Point2::new(20, 15));
+        assert_eq!(1, clicks.get());
+
+        // The button goes back to its hover state either way.
+        assert_eq!(ButtonState::Hover, root.root_widget.state);
+    }
+}
@@ -0,0 +1,899 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use derin_core::{
+    LoopFlow,
+    ui_state::UiStateValue,
+    widget::{WidgetIdent, WidgetRenderable, WidgetTag, WidgetInfo, WidgetInfoMut, Widget, Parent},
+    render::{Renderer, SubFrame, WidgetTheme},
+};
+use crate::{
+    cgmath::{Point2, Vector2},
+    container::WidgetContainer,
+    event::{EventOps, WidgetEvent, WidgetEventSourced, InputState, ModifierKeys, MouseButton},
+    layout::CanvasLayout,
+    widgets::GroupTheme,
+};
+
+use cgmath_geometry::{D2, rect::BoundBox, line::Segment};
+use derin_common_types::layout::SizeBounds;
+
+use std::sync::Arc;
+
+/// The factor the zoom gets multiplied or divided by for every line scrolled.
+const ZOOM_STEP: f32 = 1.1;
+/// The default lower bound on the zoom factor.
+const DEFAULT_MIN_ZOOM: f32 = 0.1;
+/// The default upper bound on the zoom factor.
+const DEFAULT_MAX_ZOOM: f32 = 10.0;
+
+/// A pannable, zoomable surface that places its children at absolute positions.
+///
+/// Each child is given a rectangle in *content* coordinates by the canvas's [`CanvasLayout`].
+/// Before the children are rendered and hit-tested, those rectangles are scaled by the canvas's
+/// zoom factor and then moved by its pan offset, giving the rectangles in *screen* coordinates
+/// (relative to the canvas's origin, like any other child rectangle). Since only the rectangles
+/// get transformed, zooming changes the size children are laid out at rather than scaling what
+/// they draw.
+///
+/// Dragging the canvas's background with the middle mouse button pans the canvas, and scrolling
+/// the mouse wheel over it zooms the canvas toward the cursor, keeping the content point under the
+/// cursor in place. The zoom factor is kept within the limits set by
+/// [`set_zoom_limits`](#method.set_zoom_limits).
+///
+/// Code that moves the canvas's children around can snap them into alignment with a grid or with
+/// each other through [`snap_rect`](#method.snap_rect). Guide lines get drawn along whichever
+/// edges or centers the rectangle snapped to, until the snap is cleared.
+///
+/// [`CanvasLayout`]: ../layout/trait.CanvasLayout.html
+#[derive(Debug, Clone)]
+pub struct Canvas<C, L>
+    where L: CanvasLayout
+{
+    widget_tag: WidgetTag,
+    bounds: BoundBox<D2, i32>,
+    container: C,
+    layout: L,
+    pan: Vector2<f32>,
+    zoom: f32,
+    min_zoom: f32,
+    max_zoom: f32,
+    pan_drag_pos: Option<Point2<i32>>,
+    snap: Option<CanvasSnap>,
+    guides: Vec<SnapGuide>,
+}
+
+/// How rectangles get snapped into place by `Canvas::snap_rect`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CanvasSnap {
+    /// Snap edges and centers to the lines of a grid with cells of this size, in content units.
+    pub grid_size: Option<i32>,
+    /// Snap edges and centers to the edges and centers of the canvas's other children.
+    pub siblings: bool,
+    /// How close, in screen pixels, an edge or center has to be to a snap target to snap to it.
+    pub threshold: i32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CanvasTheme(());
+
+/// The theme of the guide lines drawn when a rectangle is snapped.
+#[derive(Debug, Clone, Default)]
+pub struct CanvasGuideTheme(());
+
+/// A guide line, drawn as a pixel-wide widget.
+#[derive(Debug, Clone)]
+struct SnapGuide {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    /// The guide line, in content coordinates.
+    line: Segment<D2, i32>,
+}
+
+impl<C, L> Canvas<C, L>
+    where L: CanvasLayout
+{
+    /// Create a new `Canvas` containing the widgets specified in `container`, placed at the
+    /// positions specified in `layout`. The canvas starts out unpanned and unzoomed.
+    pub fn new(container: C, layout: L) -> Canvas<C, L> {
+        Canvas {
+            widget_tag: WidgetTag::new(),
+            bounds: BoundBox::new2(0, 0, 0, 0),
+            container, layout,
+            pan: Vector2::new(0.0, 0.0),
+            zoom: 1.0,
+            min_zoom: DEFAULT_MIN_ZOOM,
+            max_zoom: DEFAULT_MAX_ZOOM,
+            pan_drag_pos: None,
+            snap: None,
+            guides: Vec::new(),
+        }
+    }
+
+    /// Retrieve the widgets contained within the canvas.
+    pub fn container(&self) -> &C {
+        &self.container
+    }
+
+    /// Retrieve the widgets contained within the canvas, for mutation.
+    pub fn container_mut(&mut self) -> &mut C {
+        &mut self.container
+    }
+
+    /// Retrieve the positions of the canvas's children.
+    pub fn layout(&self) -> &L {
+        &self.layout
+    }
+
+    /// Retrieve the positions of the canvas's children, for mutation.
+    ///
+    /// Calling this function forces the canvas to be re-laid out, so you're discouraged from
+    /// calling it unless you're actually moving the children.
+    pub fn layout_mut(&mut self) -> &mut L {
+        self.widget_tag.request_relayout();
+        &mut self.layout
+    }
+
+    /// The screen-space position of the content origin.
+    #[inline]
+    pub fn pan(&self) -> Vector2<f32> {
+        self.pan
+    }
+
+    /// Move the content so that its origin lies at `pan` in screen space.
+    pub fn set_pan(&mut self, pan: Vector2<f32>) {
+        if pan != self.pan {
+            self.pan = pan;
+            self.widget_tag.request_relayout();
+        }
+    }
+
+    /// The number of screen pixels a single unit of content takes up.
+    #[inline]
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Set the zoom factor. Values greater than `1.0` make the content larger.
+    ///
+    /// The zoom is scaled around the content origin, and gets clamped to the canvas's zoom limits.
+    ///
+    /// Panics if `zoom` isn't positive.
+    pub fn set_zoom(&mut self, zoom: f32) {
+        assert!(zoom > 0.0, "canvas zoom must be positive");
+        let zoom = zoom.max(self.min_zoom).min(self.max_zoom);
+        if zoom != self.zoom {
+            self.zoom = zoom;
+            self.widget_tag.request_relayout();
+        }
+    }
+
+    /// Set the zoom factor, adjusting the pan so that the content under the screen-space point
+    /// `focus` stays under `focus`.
+    ///
+    /// Panics if `zoom` isn't positive.
+    pub fn zoom_at(&mut self, zoom: f32, focus: Point2<f32>) {
+        let content_focus = self.screen_to_content(focus);
+        self.set_zoom(zoom);
+        let pan = focus - content_focus * self.zoom;
+        self.set_pan(pan);
+    }
+
+    /// The minimum and maximum zoom factors, in that order.
+    #[inline]
+    pub fn zoom_limits(&self) -> (f32, f32) {
+        (self.min_zoom, self.max_zoom)
+    }
+
+    /// Set the minimum and maximum zoom factors, clamping the current zoom to fit in them.
+    ///
+    /// Panics if `min` isn't positive or is greater than `max`.
+    pub fn set_zoom_limits(&mut self, min: f32, max: f32) {
+        assert!(0.0 < min && min <= max, "invalid canvas zoom limits");
+        self.min_zoom = min;
+        self.max_zoom = max;
+        let zoom = self.zoom;
+        self.set_zoom(zoom);
+    }
+
+    /// Convert a point in content space into the corresponding point in screen space.
+    #[inline]
+    pub fn content_to_screen(&self, point: Point2<f32>) -> Point2<f32> {
+        point * self.zoom + self.pan
+    }
+
+    /// Convert a point in screen space, such as a cursor position from a mouse event, into the
+    /// corresponding point in content space.
+    #[inline]
+    pub fn screen_to_content(&self, point: Point2<f32>) -> Point2<f32> {
+        (point - self.pan) / self.zoom
+    }
+
+    /// How `snap_rect` snaps rectangles, or `None` if snapping is disabled.
+    #[inline]
+    pub fn snap(&self) -> Option<CanvasSnap> {
+        self.snap
+    }
+
+    /// Set how `snap_rect` snaps rectangles. `None` disables snapping.
+    pub fn set_snap(&mut self, snap: Option<CanvasSnap>) {
+        self.snap = snap;
+        if snap.is_none() {
+            self.clear_snap_guides();
+        }
+    }
+
+    /// The guide lines shown for the last snap, in content coordinates.
+    pub fn snap_guides(&self) -> impl '_ + Iterator<Item=Segment<D2, i32>> {
+        self.guides.iter().map(|g| g.line)
+    }
+
+    /// Stop showing the guide lines from the last snap. This should be called once the drag that
+    /// was being snapped ends.
+    pub fn clear_snap_guides(&mut self) {
+        if !self.guides.is_empty() {
+            self.guides.clear();
+            self.widget_tag.request_relayout().request_redraw();
+        }
+    }
+}
+
+impl<C, L> Canvas<C, L>
+    where C: WidgetContainer<dyn Widget>,
+          L: CanvasLayout
+{
+    /// Snap `rect`, the content-space rectangle the child at `index` is being moved to, and show
+    /// guide lines for whatever it snapped to.
+    ///
+    /// The rectangle's left edge, center, and right edge get moved horizontally onto the nearest
+    /// snap target within the snap threshold, and likewise for the vertical axis. The rectangle's
+    /// size never changes. Holding `Alt` (as reported by `modifiers`) disables snapping, as does
+    /// not having a snap configuration set.
+    pub fn snap_rect(&mut self, index: usize, rect: BoundBox<D2, i32>, modifiers: ModifierKeys) -> BoundBox<D2, i32> {
+        self.clear_snap_guides();
+        let snap = match self.snap {
+            Some(snap) if !modifiers.contains(ModifierKeys::ALT) => snap,
+            _ => return rect
+        };
+        let threshold = snap.threshold as f32 / self.zoom;
+
+        let mut siblings = Vec::new();
+        if snap.siblings {
+            let layout = &self.layout;
+            self.container.children::<_>(|summary| {
+                if summary.index != index {
+                    let size_bounds = summary.widget().size_bounds();
+                    siblings.push(content_rect(layout, summary.ident.clone(), summary.index, size_bounds));
+                }
+                LoopFlow::Continue
+            });
+        }
+
+        let snap_x = snap_axis(
+            [rect.min.x, (rect.min.x + rect.max.x) / 2, rect.max.x],
+            snap.grid_size,
+            siblings.iter().map(|s| (s, [s.min.x, (s.min.x + s.max.x) / 2, s.max.x])),
+            threshold,
+        );
+        let snap_y = snap_axis(
+            [rect.min.y, (rect.min.y + rect.max.y) / 2, rect.max.y],
+            snap.grid_size,
+            siblings.iter().map(|s| (s, [s.min.y, (s.min.y + s.max.y) / 2, s.max.y])),
+            threshold,
+        );
+
+        let offset = Vector2::new(
+            snap_x.as_ref().map(|s| s.offset).unwrap_or(0),
+            snap_y.as_ref().map(|s| s.offset).unwrap_or(0),
+        );
+        let snapped = rect + offset;
+
+        // Guides span both the snapped rectangle and the sibling it was aligned with.
+        if let Some(snap_x) = snap_x {
+            let (mut y0, mut y1) = (snapped.min.y, snapped.max.y);
+            if let Some(sibling) = snap_x.sibling {
+                y0 = i32::min(y0, sibling.min.y);
+                y1 = i32::max(y1, sibling.max.y);
+            }
+            self.guides.push(SnapGuide::new(Segment::new(
+                Point2::new(snap_x.target, y0),
+                Point2::new(snap_x.target, y1),
+            )));
+        }
+        if let Some(snap_y) = snap_y {
+            let (mut x0, mut x1) = (snapped.min.x, snapped.max.x);
+            if let Some(sibling) = snap_y.sibling {
+                x0 = i32::min(x0, sibling.min.x);
+                x1 = i32::max(x1, sibling.max.x);
+            }
+            self.guides.push(SnapGuide::new(Segment::new(
+                Point2::new(x0, snap_y.target),
+                Point2::new(x1, snap_y.target),
+            )));
+        }
+        if !self.guides.is_empty() {
+            self.widget_tag.request_relayout().request_redraw();
+        }
+
+        snapped
+    }
+}
+
+impl Default for CanvasSnap {
+    fn default() -> CanvasSnap {
+        CanvasSnap {
+            grid_size: None,
+            siblings: true,
+            threshold: 6,
+        }
+    }
+}
+
+/// The snap found along a single axis.
+struct AxisSnap<'a> {
+    /// How far the rectangle needs to move to snap.
+    offset: i32,
+    /// The coordinate that the rectangle snapped to.
+    target: i32,
+    /// The sibling the target belongs to, if it wasn't a grid line.
+    sibling: Option<&'a BoundBox<D2, i32>>,
+}
+
+/// Find the closest snap target within `threshold` of any of the anchors (the near edge, center,
+/// and far edge of the rectangle being snapped).
+fn snap_axis<'a>(
+    anchors: [i32; 3],
+    grid_size: Option<i32>,
+    siblings: impl Iterator<Item=(&'a BoundBox<D2, i32>, [i32; 3])>,
+    threshold: f32
+) -> Option<AxisSnap<'a>> {
+    let mut best: Option<AxisSnap<'a>> = None;
+    let mut try_target = |anchor: i32, target: i32, sibling: Option<&'a BoundBox<D2, i32>>| {
+        let offset = target - anchor;
+        let closer = match best {
+            Some(ref best) => offset.abs() < best.offset.abs(),
+            None => true
+        };
+        if offset.abs() as f32 <= threshold && closer {
+            best = Some(AxisSnap{ offset, target, sibling });
+        }
+    };
+
+    if let Some(grid_size) = grid_size.filter(|g| *g > 0) {
+        for &anchor in &anchors {
+            let line = (anchor as f32 / grid_size as f32).round() as i32 * grid_size;
+            try_target(anchor, line, None);
+        }
+    }
+    for (sibling, targets) in siblings {
+        for &anchor in &anchors {
+            for &target in &targets {
+                try_target(anchor, target, Some(sibling));
+            }
+        }
+    }
+
+    best
+}
+
+/// The content-space rectangle of the child with the given ident and index.
+fn content_rect<L: CanvasLayout>(layout: &L, ident: WidgetIdent, index: usize, size_bounds: SizeBounds) -> BoundBox<D2, i32> {
+    // Children without a position get placed at the content origin.
+    match layout.rect(ident, index) {
+        Some(rect) => size_bounds.clamp_rect(rect),
+        None => BoundBox::from(size_bounds.min),
+    }
+}
+
+impl SnapGuide {
+    fn new(line: Segment<D2, i32>) -> SnapGuide {
+        let mut widget_tag = WidgetTag::new();
+        widget_tag.set_pointer_transparent(true);
+        SnapGuide {
+            widget_tag,
+            rect: BoundBox::new2(0, 0, 0, 0),
+            line,
+        }
+    }
+
+    fn ident(index: usize) -> WidgetIdent {
+        WidgetIdent::StrCollection(Arc::from("__canvas_snap_guide"), index as u32)
+    }
+}
+
+/// Transform a content-space rectangle into screen space.
+fn rect_to_screen(rect: BoundBox<D2, i32>, pan: Vector2<f32>, zoom: f32) -> BoundBox<D2, i32> {
+    let transform = |x: i32, pan: f32| (x as f32 * zoom + pan).round() as i32;
+    BoundBox::new2(
+        transform(rect.min.x, pan.x),
+        transform(rect.min.y, pan.y),
+        transform(rect.max.x, pan.x),
+        transform(rect.max.y, pan.y),
+    )
+}
+
+impl<C, L> Widget for Canvas<C, L>
+    where C: WidgetContainer<dyn Widget>,
+          L: CanvasLayout
+{
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.bounds
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        self.widget_tag.request_relayout();
+        &mut self.bounds
+    }
+
+    fn size_bounds(&self) -> SizeBounds {
+        SizeBounds::default()
+    }
+
+    /// Saves the pan and zoom, as a list containing the pan's `x` and `y` followed by the zoom.
+    fn save_state(&self) -> Option<UiStateValue> {
+        Some(UiStateValue::List(vec![
+            UiStateValue::Float(self.pan.x as f64),
+            UiStateValue::Float(self.pan.y as f64),
+            UiStateValue::Float(self.zoom as f64),
+        ]))
+    }
+
+    fn restore_state(&mut self, state: &UiStateValue) {
+        let state = match state.as_list() {
+            Some(state) => state.iter().map(|v| v.as_float()).collect::<Option<Vec<_>>>(),
+            None => return
+        };
+        if let Some(&[x, y, zoom]) = state.as_ref().map(|s| &s[..]) {
+            if zoom > 0.0 {
+                self.set_zoom(zoom as f32);
+            }
+            self.set_pan(Vector2::new(x as f32, y as f32));
+        }
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, input_state: InputState) -> EventOps {
+        let mut bubble = event.default_bubble();
+        let mut handled = false;
+
+        if let WidgetEventSourced::This(event) = event {
+            handled = true;
+            match event {
+                WidgetEvent::MouseDown{pos, in_widget: true, button: MouseButton::Middle} => {
+                    self.pan_drag_pos = Some(pos);
+                },
+                WidgetEvent::MouseMove{new_pos, ..} => match self.pan_drag_pos {
+                    Some(old_pos) => {
+                        let delta = new_pos - old_pos;
+                        let pan = self.pan + Vector2::new(delta.x as f32, delta.y as f32);
+                        self.pan_drag_pos = Some(new_pos);
+                        self.set_pan(pan);
+                    },
+                    // Moving the mouse without panning is left unhandled.
+                    None => handled = false
+                },
+                WidgetEvent::MouseUp{button: MouseButton::Middle, ..} => {
+                    self.pan_drag_pos = None;
+                },
+                WidgetEvent::MouseScrollLines{dir, in_widget: true} => {
+                    let zoom = self.zoom * ZOOM_STEP.powi(dir.y);
+                    match input_state.mouse_pos {
+                        Some(pos) => self.zoom_at(zoom, Point2::new(pos.x as f32, pos.y as f32)),
+                        None => self.set_zoom(zoom)
+                    }
+                    bubble = false;
+                },
+                _ => handled = false
+            }
+        }
+
+        EventOps {
+            focus: None,
+            bubble,
+            handled,
+        }
+    }
+}
+
+impl<C, L> Parent for Canvas<C, L>
+    where C: WidgetContainer<dyn Widget>,
+          L: CanvasLayout
+{
+    fn num_children(&self) -> usize {
+        self.container.num_children() + self.guides.len()
+    }
+
+    fn framed_child<R: Renderer>(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, R>> {
+        let guide_index = (0..self.guides.len()).find(|&i| SnapGuide::ident(i) == widget_ident);
+        match guide_index {
+            Some(i) => self.framed_child_by_index(self.container.num_children() + i),
+            None => self.container.framed_child(widget_ident).map(WidgetInfo::erase_subtype)
+        }
+    }
+    fn framed_child_mut<R: Renderer>(&mut self, widget_ident: WidgetIdent) -> Option<WidgetInfoMut<'_, R>> {
+        let guide_index = (0..self.guides.len()).find(|&i| SnapGuide::ident(i) == widget_ident);
+        match guide_index {
+            Some(i) => {
+                let index = self.container.num_children() + i;
+                self.framed_child_by_index_mut(index)
+            },
+            None => self.container.framed_child_mut(widget_ident).map(WidgetInfoMut::erase_subtype)
+        }
+    }
+
+    fn framed_children<'a, R, G>(&'a self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfo<'a, R>) -> LoopFlow
+    {
+        let mut flow = LoopFlow::Continue;
+        self.container.framed_children(|summary| {
+            flow = for_each(WidgetInfo::erase_subtype(summary));
+            flow
+        });
+        if let LoopFlow::Break = flow {
+            return;
+        }
+
+        // Guides come after the children, so that they get drawn on top of them.
+        let num_children = self.container.num_children();
+        for (i, guide) in self.guides.iter().enumerate() {
+            if let LoopFlow::Break = for_each(WidgetInfo::new(SnapGuide::ident(i), num_children + i, guide)) {
+                return;
+            }
+        }
+    }
+
+    fn framed_children_mut<'a, R, G>(&'a mut self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfoMut<'a, R>) -> LoopFlow
+    {
+        let num_children = self.container.num_children();
+        let mut flow = LoopFlow::Continue;
+        self.container.framed_children_mut(|summary| {
+            flow = for_each(WidgetInfoMut::erase_subtype(summary));
+            flow
+        });
+        if let LoopFlow::Break = flow {
+            return;
+        }
+
+        for (i, guide) in self.guides.iter_mut().enumerate() {
+            if let LoopFlow::Break = for_each(WidgetInfoMut::new(SnapGuide::ident(i), num_children + i, guide)) {
+                return;
+            }
+        }
+    }
+
+    fn framed_child_by_index<R: Renderer>(&self, index: usize) -> Option<WidgetInfo<'_, R>> {
+        let num_children = self.container.num_children();
+        match index.checked_sub(num_children) {
+            Some(i) => self.guides.get(i).map(|guide| WidgetInfo::new(SnapGuide::ident(i), index, guide)),
+            None => self.container.framed_child_by_index(index).map(WidgetInfo::erase_subtype)
+        }
+    }
+    fn framed_child_by_index_mut<R: Renderer>(&mut self, index: usize) -> Option<WidgetInfoMut<'_, R>> {
+        let num_children = self.container.num_children();
+        match index.checked_sub(num_children) {
+            Some(i) => self.guides.get_mut(i).map(|guide| WidgetInfoMut::new(SnapGuide::ident(i), index, guide)),
+            None => self.container.framed_child_by_index_mut(index).map(WidgetInfoMut::erase_subtype)
+        }
+    }
+}
+
+impl<R, C, L> WidgetRenderable<R> for Canvas<C, L>
+    where R: Renderer,
+          C: WidgetContainer<dyn Widget>,
+          L: CanvasLayout
+{
+    type Theme = CanvasTheme;
+
+    fn theme(&self) -> CanvasTheme {
+        CanvasTheme(())
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, _: &mut R::Layout) {
+        let Canvas {
+            ref mut container,
+            ref layout,
+            ref mut guides,
+            pan,
+            zoom,
+            ..
+        } = *self;
+
+        container.children_mut::<_>(|mut summary| {
+            let ident = summary.ident.clone();
+            let index = summary.index;
+            let widget = summary.widget_mut();
+
+            let size_bounds = widget.size_bounds();
+            *widget.rect_mut() = rect_to_screen(content_rect(layout, ident, index, size_bounds), pan, zoom);
+
+            LoopFlow::Continue
+        });
+
+        for guide in guides {
+            let line = BoundBox::new2(
+                guide.line.start.x,
+                guide.line.start.y,
+                guide.line.end.x,
+                guide.line.end.y,
+            );
+            let mut rect = rect_to_screen(line, pan, zoom);
+            // Give the line a thickness of one pixel.
+            rect.max.x = i32::max(rect.max.x, rect.min.x + 1);
+            rect.max.y = i32::max(rect.max.y, rect.min.y + 1);
+            guide.rect = rect;
+        }
+    }
+}
+
+impl Widget for SnapGuide {
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        &mut self.rect
+    }
+
+    #[inline]
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        EventOps {
+            focus: None,
+            bubble: event.default_bubble(),
+            handled: false,
+        }
+    }
+}
+
+impl<R: Renderer> WidgetRenderable<R> for SnapGuide {
+    type Theme = CanvasGuideTheme;
+
+    fn theme(&self) -> CanvasGuideTheme {
+        CanvasGuideTheme(())
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+}
+
+impl WidgetTheme for CanvasTheme {
+    type Fallback = GroupTheme;
+    fn fallback(self) -> Option<GroupTheme> {
+        Some(GroupTheme::default())
+    }
+}
+
+impl WidgetTheme for CanvasGuideTheme {
+    type Fallback = !;
+    fn fallback(self) -> Option<!> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::WindowEvent,
+        test_helpers::{headless_root, send_events},
+        widgets::Spacer,
+    };
+    use cgmath_geometry::rect::DimsBox;
+
+    type TestCanvas = Canvas<Vec<Spacer>, Vec<BoundBox<D2, i32>>>;
+
+    fn canvas(rects: Vec<BoundBox<D2, i32>>) -> TestCanvas {
+        Canvas::new(rects.iter().map(|_| Spacer::flex()).collect(), rects)
+    }
+
+    #[test]
+    fn coordinate_conversion() {
+        let mut canvas = canvas(Vec::new());
+        canvas.set_pan(Vector2::new(10.0, 20.0));
+        canvas.set_zoom(2.0);
+        assert_eq!(Point2::new(20.0, 30.0), canvas.content_to_screen(Point2::new(5.0, 5.0)));
+        assert_eq!(Point2::new(5.0, 5.0), canvas.screen_to_content(Point2::new(20.0, 30.0)));
+
+        canvas.set_zoom(1.5);
+        let point = Point2::new(7.0, -3.0);
+        assert_eq!(Point2::new(20.5, 15.5), canvas.content_to_screen(point));
+        assert_eq!(point, canvas.screen_to_content(canvas.content_to_screen(point)));
+    }
+
+    #[test]
+    fn child_placement() {
+        let canvas = canvas(vec![BoundBox::new2(10, 10, 30, 20), BoundBox::new2(0, 0, 5, 5)]);
+        let mut root = headless_root(canvas, DimsBox::new2(200, 200));
+        assert_eq!(BoundBox::new2(10, 10, 30, 20), root.root_widget.container()[0].rect());
+
+        // Child rectangles get scaled by the zoom, then moved by the pan.
+        root.root_widget.set_pan(Vector2::new(10.0, 20.0));
+        root.root_widget.set_zoom(2.0);
+        root.relayout();
+        assert_eq!(BoundBox::new2(30, 40, 70, 60), root.root_widget.container()[0].rect());
+        assert_eq!(BoundBox::new2(10, 20, 20, 30), root.root_widget.container()[1].rect());
+    }
+
+    #[test]
+    fn middle_drag_pans() {
+        let mut root = headless_root(canvas(Vec::new()), DimsBox::new2(200, 200));
+        send_events(&mut root, &[
+            WindowEvent::MouseMove(Point2::new(100, 100)),
+            WindowEvent::MouseDown(MouseButton::Middle),
+            WindowEvent::MouseMove(Point2::new(110, 95)),
+            WindowEvent::MouseUp(MouseButton::Middle),
+        ]);
+        assert_eq!(Vector2::new(10.0, -5.0), root.root_widget.pan());
+
+        // Moving the mouse once the button's released leaves the pan alone.
+        send_events(&mut root, &[WindowEvent::MouseMove(Point2::new(50, 50))]);
+        assert_eq!(Vector2::new(10.0, -5.0), root.root_widget.pan());
+
+        // So does dragging with any other button.
+        send_events(&mut root, &[
+            WindowEvent::MouseDown(MouseButton::Left),
+            WindowEvent::MouseMove(Point2::new(60, 60)),
+            WindowEvent::MouseUp(MouseButton::Left),
+        ]);
+        assert_eq!(Vector2::new(10.0, -5.0), root.root_widget.pan());
+    }
+
+    fn guides(canvas: &TestCanvas) -> Vec<(Point2<i32>, Point2<i32>)> {
+        canvas.snap_guides().map(|g| (g.start, g.end)).collect()
+    }
+
+    #[test]
+    fn snap_to_grid() {
+        let mut canvas = canvas(vec![BoundBox::new2(0, 0, 40, 20)]);
+        canvas.set_snap(Some(CanvasSnap{ grid_size: Some(10), siblings: false, threshold: 6 }));
+        let snapped = canvas.snap_rect(0, BoundBox::new2(53, 47, 93, 67), ModifierKeys::empty());
+        assert_eq!(BoundBox::new2(50, 50, 90, 70), snapped);
+        assert_eq!(
+            vec![
+                (Point2::new(50, 50), Point2::new(50, 70)),
+                (Point2::new(50, 50), Point2::new(90, 50)),
+            ],
+            guides(&canvas)
+        );
+
+        // Axes outside of the threshold are left alone.
+        canvas.set_snap(Some(CanvasSnap{ grid_size: Some(20), siblings: false, threshold: 3 }));
+        let snapped = canvas.snap_rect(0, BoundBox::new2(45, 41, 85, 61), ModifierKeys::empty());
+        assert_eq!(BoundBox::new2(45, 40, 85, 60), snapped);
+        assert_eq!(vec![(Point2::new(45, 40), Point2::new(85, 40))], guides(&canvas));
+
+        // The threshold is measured in screen pixels, so it shrinks in content space as the
+        // canvas zooms in.
+        canvas.set_zoom(4.0);
+        let snapped = canvas.snap_rect(0, BoundBox::new2(45, 42, 85, 62), ModifierKeys::empty());
+        assert_eq!(BoundBox::new2(45, 42, 85, 62), snapped);
+        assert_eq!(0, guides(&canvas).len());
+    }
+
+    #[test]
+    fn snap_to_siblings() {
+        let canvas = canvas(vec![BoundBox::new2(0, 0, 40, 20), BoundBox::new2(100, 100, 140, 120)]);
+        let mut root = headless_root(canvas, DimsBox::new2(200, 200));
+        root.root_widget.set_snap(Some(CanvasSnap::default()));
+
+        // The left edge snaps to the sibling's right edge, and the guide spans both rectangles.
+        let snapped = root.root_widget.snap_rect(1, BoundBox::new2(43, 30, 83, 50), ModifierKeys::empty());
+        assert_eq!(BoundBox::new2(40, 30, 80, 50), snapped);
+        assert_eq!(vec![(Point2::new(40, 0), Point2::new(40, 50))], guides(&root.root_widget));
+
+        // Guides get added to the tree after the canvas's children.
+        assert_eq!(3, root.root_widget.num_children());
+        root.relayout();
+        assert_eq!(BoundBox::new2(40, 0, 41, 50), root.root_widget.guides[0].rect);
+
+        // Holding Alt disables snapping, and clears the old guides.
+        let snapped = root.root_widget.snap_rect(1, BoundBox::new2(43, 30, 83, 50), ModifierKeys::ALT);
+        assert_eq!(BoundBox::new2(43, 30, 83, 50), snapped);
+        assert_eq!(0, guides(&root.root_widget).len());
+        assert_eq!(2, root.root_widget.num_children());
+
+        root.root_widget.snap_rect(1, BoundBox::new2(43, 30, 83, 50), ModifierKeys::empty());
+        root.root_widget.clear_snap_guides();
+        assert_eq!(0, guides(&root.root_widget).len());
+
+        // As does disabling snapping entirely.
+        root.root_widget.snap_rect(1, BoundBox::new2(43, 30, 83, 50), ModifierKeys::empty());
+        root.root_widget.set_snap(None);
+        assert_eq!(0, guides(&root.root_widget).len());
+        let snapped = root.root_widget.snap_rect(1, BoundBox::new2(43, 30, 83, 50), ModifierKeys::empty());
+        assert_eq!(BoundBox::new2(43, 30, 83, 50), snapped);
+    }
+
+    #[test]
+    fn zoom_at_focus() {
+        let mut canvas = canvas(Vec::new());
+        canvas.set_pan(Vector2::new(10.0, 10.0));
+        canvas.zoom_at(2.0, Point2::new(30.0, 30.0));
+        assert_eq!(2.0, canvas.zoom());
+        assert_eq!(Vector2::new(-10.0, -10.0), canvas.pan());
+        assert_eq!(Point2::new(20.0, 20.0), canvas.screen_to_content(Point2::new(30.0, 30.0)));
+
+        // The focus stays fixed even when the zoom gets clamped.
+        canvas.set_zoom_limits(0.5, 4.0);
+        canvas.zoom_at(8.0, Point2::new(30.0, 30.0));
+        assert_eq!(4.0, canvas.zoom());
+        assert_eq!(Point2::new(20.0, 20.0), canvas.screen_to_content(Point2::new(30.0, 30.0)));
+    }
+
+    #[test]
+    fn zoom_limits() {
+        let mut canvas = canvas(Vec::new());
+        canvas.set_zoom(100.0);
+        assert_eq!(DEFAULT_MAX_ZOOM, canvas.zoom());
+        canvas.set_zoom(0.001);
+        assert_eq!(DEFAULT_MIN_ZOOM, canvas.zoom());
+
+        canvas.set_zoom_limits(0.5, 2.0);
+        assert_eq!((0.5, 2.0), canvas.zoom_limits());
+        assert_eq!(0.5, canvas.zoom());
+        canvas.set_zoom(3.0);
+        assert_eq!(2.0, canvas.zoom());
+
+        // Narrowing the limits clamps the current zoom.
+        canvas.set_zoom_limits(0.5, 1.5);
+        assert_eq!(1.5, canvas.zoom());
+    }
+
+    #[test]
+    fn scroll_zooms_toward_cursor() {
+        let mut root = headless_root(canvas(Vec::new()), DimsBox::new2(200, 200));
+        send_events(&mut root, &[
+            WindowEvent::MouseMove(Point2::new(50, 80)),
+            WindowEvent::MouseScrollLines(Vector2::new(0, 2)),
+        ]);
+        let canvas = &root.root_widget;
+        assert!((canvas.zoom() - ZOOM_STEP * ZOOM_STEP).abs() < 1e-4);
+        let focus = canvas.screen_to_content(Point2::new(50.0, 80.0));
+        assert!((focus.x - 50.0).abs() < 1e-3 && (focus.y - 80.0).abs() < 1e-3);
+
+        // Scrolling the other way zooms back out, and the wheel can't zoom past the limits.
+        send_events(&mut root, &[WindowEvent::MouseScrollLines(Vector2::new(0, -2))]);
+        assert!((root.root_widget.zoom() - 1.0).abs() < 1e-4);
+        send_events(&mut root, &[WindowEvent::MouseScrollLines(Vector2::new(0, -100))]);
+        assert_eq!(DEFAULT_MIN_ZOOM, root.root_widget.zoom());
+        let focus = root.root_widget.screen_to_content(Point2::new(50.0, 80.0));
+        assert!((focus.x - 50.0).abs() < 1e-2 && (focus.y - 80.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn process_event_handled() {
+        let mut root = headless_root(canvas(Vec::new()), DimsBox::new2(200, 200));
+        let mut frame = root.start_frame();
+        assert!(!frame.process_event(WindowEvent::MouseMove(Point2::new(100, 100))));
+        assert!(!frame.process_event(WindowEvent::MouseDown(MouseButton::Left)));
+        assert!(!frame.process_event(WindowEvent::MouseUp(MouseButton::Left)));
+        assert!(!frame.process_event(WindowEvent::Char('a')));
+
+        assert!(frame.process_event(WindowEvent::MouseDown(MouseButton::Middle)));
+        assert!(frame.process_event(WindowEvent::MouseMove(Point2::new(110, 100))));
+        assert!(frame.process_event(WindowEvent::MouseUp(MouseButton::Middle)));
+        assert!(frame.process_event(WindowEvent::MouseScrollLines(Vector2::new(0, 1))));
+        let _ = frame.finish();
+    }
+}
@@ -3,6 +3,7 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use derin_core::{
+    accessibility::{AccessibilityNode, AccessibilityRole, AccessibilityState},
     event::{EventOps, InputState, WidgetEventSourced},
     widget::{WidgetTag, WidgetRenderable, Widget},
     render::{Renderer, WidgetTheme},
@@ -93,6 +94,22 @@ impl<H> Widget for CheckBox<H>
         self.toggle.size_bounds()
     }
 
+    #[inline]
+    fn accepts_focus(&self) -> bool {
+        true
+    }
+
+    fn accessibility_info(&self) -> Option<AccessibilityNode> {
+        Some(AccessibilityNode {
+            label: self.contents().plain_text(),
+            state: AccessibilityState {
+                checked: Some(self.checked()),
+                ..AccessibilityState::default()
+            },
+            ..AccessibilityNode::new(AccessibilityRole::CheckBox)
+        })
+    }
+
     fn on_widget_event(&mut self, event: WidgetEventSourced, state: InputState) -> EventOps {
         self.toggle.on_widget_event(event, state)
     }
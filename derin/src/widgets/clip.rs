@@ -9,8 +9,7 @@ use derin_core::{
     render::{Renderer, SubFrame, WidgetTheme},
 };
 
-use crate::cgmath::EuclideanSpace;
-use cgmath_geometry::{D2, rect::{BoundBox, GeoBox}};
+use cgmath_geometry::{D2, rect::BoundBox};
 
 /// Assistant widget that is used to clip another widget
 ///
@@ -71,6 +70,7 @@ impl<W> Widget for Clip<W>
         EventOps {
             focus: None,
             bubble: true,
+            handled: false,
         }
     }
 }
@@ -141,9 +141,9 @@ impl<W, R> WidgetRenderable<R> for Clip<W>
         let widget_rect = self.widget.rect();
         let size_bounds = self.widget.size_bounds();
 
-        let dims_clipped = size_bounds.bound_rect(widget_rect.dims());
-        if dims_clipped.dims() != widget_rect.dims() {
-            *self.widget.rect_mut() = BoundBox::from(dims_clipped) + widget_rect.min().to_vec();
+        let rect_clamped = size_bounds.clamp_rect(widget_rect);
+        if rect_clamped != widget_rect {
+            *self.widget.rect_mut() = rect_clamped;
         }
     }
 }
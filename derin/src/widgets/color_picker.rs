@@ -0,0 +1,753 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use derin_core::{
+    LoopFlow,
+    widget::{Parent, Widget, WidgetInfo, WidgetInfoMut, WidgetIdent, WidgetId, WidgetTag, WidgetRenderable, MessageTarget},
+    render::{Renderer, RendererLayout, SubFrame, WidgetTheme},
+};
+use crate::{
+    cgmath::Point2,
+    event::{EventOps, WidgetEvent, WidgetEventSourced, InputState, MouseButton},
+    widgets::{GroupTheme, NumberInput, Slider, SliderHandler, SliderOrientation, ValueChanged},
+};
+
+use cgmath_geometry::{D2, rect::{BoundBox, DimsBox, GeoBox}};
+use derin_common_types::layout::SizeBounds;
+
+/// A color, in hue/saturation/value form.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsv {
+    /// The hue, in degrees. Ranges from `0.0` up to (but not including) `360.0`.
+    pub hue: f32,
+    /// Ranges from `0.0` to `1.0`.
+    pub saturation: f32,
+    /// Ranges from `0.0` to `1.0`.
+    pub value: f32,
+}
+
+/// A widget for picking a color.
+///
+/// The picker is made up of a saturation/value field, with saturation increasing to the right and
+/// value increasing upwards, and a vertical hue [`Slider`] to the right of the field. Pressing or
+/// dragging in the field, or dragging the slider, changes the color live. Below those, the picker can show a row of red,
+/// green, and blue [`NumberInput`]s for entering exact values; see
+/// [`set_show_inputs`](#method.set_show_inputs).
+///
+/// Colors are RGBA, in the same `[u8; 4]` form used elsewhere in Derin. The picker doesn't edit
+/// the alpha channel, so whatever alpha the color was set with gets passed through.
+///
+/// Whenever the user changes the color, a [`ColorChanged`] message is sent to the picker's
+/// parent.
+///
+/// [`Slider`]: ./struct.Slider.html
+/// [`NumberInput`]: ./struct.NumberInput.html
+/// [`ColorChanged`]: ./struct.ColorChanged.html
+#[derive(Debug, Clone)]
+pub struct ColorPicker {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    size_bounds: SizeBounds,
+
+    hsv: Hsv,
+    alpha: u8,
+
+    field: ColorField,
+    hue_slider: Slider<HueHandler>,
+    /// The red, green, and blue inputs, or nothing if the inputs are hidden.
+    inputs: Vec<NumberInput<u8>>,
+}
+
+/// Message sent to the parent of a [`ColorPicker`] whenever the user changes its color.
+///
+/// [`ColorPicker`]: ./struct.ColorPicker.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorChanged {
+    /// The ID of the picker whose color changed.
+    pub widget_id: WidgetId,
+    /// The new color.
+    pub color: [u8; 4],
+}
+
+/// The saturation/value field.
+#[derive(Debug, Clone)]
+struct ColorField {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    size_bounds: SizeBounds,
+    hsv: Hsv,
+    dragging: bool,
+}
+
+/// Turns hue slider moves into `HuePicked` messages for the picker that owns the slider.
+#[derive(Debug, Clone, Copy)]
+struct HueHandler {
+    picker: WidgetId,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FieldPicked {
+    saturation: f32,
+    value: f32,
+}
+
+/// Broadcast by the hue slider, so it carries the ID of the picker it's meant for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct HuePicked {
+    picker: WidgetId,
+    hue: f32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ColorPickerTheme(());
+
+/// The theme of the saturation/value field.
+///
+/// The field draws the gradient of `hsv.hue` and the marker at the current saturation and value
+/// itself, in between the theme's background and foreground.
+#[derive(Debug, Clone, Copy)]
+pub struct ColorPickerFieldTheme {
+    pub hsv: Hsv,
+}
+
+/// The size of the squares the saturation/value gradient gets drawn with, in pixels.
+const GRADIENT_CELL: i32 = 4;
+/// The size of the marker drawn at the picked saturation and value, in pixels.
+const MARKER_SIZE: i32 = 5;
+/// The largest hue the hue slider can pick. The slider stops short of `360.0` so that the bottom
+/// of the slider doesn't wrap around to the same hue as the top.
+const MAX_HUE: f32 = 359.0;
+
+impl Hsv {
+    /// Converts an RGB color to HSV. Grays get a hue of `0.0`.
+    pub fn from_rgb([r, g, b]: [u8; 3]) -> Hsv {
+        let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+
+        let hue = if delta == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / delta)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+
+        Hsv {
+            hue: hue.rem_euclid(360.0),
+            saturation: match max == 0.0 {
+                true => 0.0,
+                false => delta / max
+            },
+            value: max,
+        }
+    }
+
+    /// Converts the color to RGB.
+    pub fn to_rgb(self) -> [u8; 3] {
+        let Hsv{ hue, saturation, value } = self.clamped();
+        let chroma = value * saturation;
+        let hue_sector = hue / 60.0;
+        let x = chroma * (1.0 - (hue_sector % 2.0 - 1.0).abs());
+        let (r, g, b) = match hue_sector as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = value - chroma;
+        let to_u8 = |c: f32| ((c + m) * 255.0).round() as u8;
+        [to_u8(r), to_u8(g), to_u8(b)]
+    }
+
+    /// Wraps the hue into `0.0..360.0` and clamps the saturation and value to `0.0..=1.0`.
+    fn clamped(self) -> Hsv {
+        Hsv {
+            hue: self.hue.rem_euclid(360.0),
+            saturation: self.saturation.min(1.0).max(0.0),
+            value: self.value.min(1.0).max(0.0),
+        }
+    }
+}
+
+impl ColorPicker {
+    /// Creates a new color picker set to `color`. The RGB inputs are shown by default.
+    pub fn new(color: [u8; 4]) -> ColorPicker {
+        let mut widget_tag = WidgetTag::new();
+        let picker_id = widget_tag.widget_id();
+        widget_tag.register_message(Self::on_field_picked);
+        widget_tag.register_message(Self::on_hue_picked);
+        widget_tag.register_message(Self::on_input_changed);
+
+        let hsv = Hsv::from_rgb([color[0], color[1], color[2]]);
+        let mut picker = ColorPicker {
+            widget_tag,
+            rect: BoundBox::new2(0, 0, 0, 0),
+            size_bounds: SizeBounds::default(),
+
+            hsv,
+            alpha: color[3],
+
+            field: ColorField {
+                widget_tag: WidgetTag::new(),
+                rect: BoundBox::new2(0, 0, 0, 0),
+                size_bounds: SizeBounds::default(),
+                hsv,
+                dragging: false,
+            },
+            hue_slider: Slider::new(hsv.hue.min(MAX_HUE), 1.0, 0.0..=MAX_HUE, HueHandler{ picker: picker_id }),
+            inputs: Vec::new(),
+        };
+        picker.hue_slider.set_orientation(SliderOrientation::Vertical);
+        picker.set_show_inputs(true);
+        picker
+    }
+
+    /// Retrieves the picked color.
+    pub fn color(&self) -> [u8; 4] {
+        let [r, g, b] = self.hsv.to_rgb();
+        [r, g, b, self.alpha]
+    }
+
+    /// Retrieves the picked color, in HSV form.
+    #[inline]
+    pub fn hsv(&self) -> Hsv {
+        self.hsv
+    }
+
+    /// Set the picked color.
+    ///
+    /// This doesn't send a [`ColorChanged`] message.
+    ///
+    /// [`ColorChanged`]: ./struct.ColorChanged.html
+    pub fn set_color(&mut self, color: [u8; 4]) {
+        self.alpha = color[3];
+        self.set_rgb([color[0], color[1], color[2]]);
+    }
+
+    /// Set the picked color, in HSV form. The hue gets wrapped into range, and the saturation and
+    /// value get clamped.
+    ///
+    /// This doesn't send a [`ColorChanged`] message.
+    ///
+    /// [`ColorChanged`]: ./struct.ColorChanged.html
+    pub fn set_hsv(&mut self, hsv: Hsv) {
+        self.hsv = hsv.clamped();
+        self.sync_children();
+    }
+
+    /// Whether or not the red, green, and blue inputs are shown.
+    #[inline]
+    pub fn show_inputs(&self) -> bool {
+        !self.inputs.is_empty()
+    }
+
+    /// Show or hide the red, green, and blue inputs.
+    pub fn set_show_inputs(&mut self, show_inputs: bool) {
+        if show_inputs == self.show_inputs() {
+            return;
+        }
+
+        self.inputs = match show_inputs {
+            true => self.hsv.to_rgb().iter().map(|&c| NumberInput::new(c, 1, 0, 255)).collect(),
+            false => Vec::new()
+        };
+        self.widget_tag.request_relayout().request_redraw();
+    }
+
+    /// Set the color from RGB, keeping the current hue if the new color is a gray (which doesn't
+    /// have a hue) so that the hue slider doesn't jump back to red.
+    fn set_rgb(&mut self, rgb: [u8; 3]) {
+        let mut hsv = Hsv::from_rgb(rgb);
+        if hsv.saturation == 0.0 || hsv.value == 0.0 {
+            hsv.hue = self.hsv.hue;
+        }
+        self.hsv = hsv;
+        self.sync_children();
+    }
+
+    fn on_field_picked(&mut self, picked: &FieldPicked) {
+        let hsv = Hsv {
+            saturation: picked.saturation,
+            value: picked.value,
+            ..self.hsv
+        };
+        self.change_hsv(hsv);
+    }
+
+    fn on_hue_picked(&mut self, picked: &HuePicked) {
+        if picked.picker != self.widget_tag.widget_id() {
+            return;
+        }
+        let hsv = Hsv {
+            hue: picked.hue,
+            ..self.hsv
+        };
+        self.change_hsv(hsv);
+    }
+
+    fn on_input_changed(&mut self, changed: &ValueChanged<u8>) {
+        let channel = self.inputs.iter().position(|i| i.widget_tag().widget_id() == changed.widget_id);
+        if let Some(channel) = channel {
+            let start_color = self.color();
+            let mut rgb = self.hsv.to_rgb();
+            rgb[channel] = changed.value;
+            self.set_rgb(rgb);
+            self.notify_if_changed(start_color);
+        }
+    }
+
+    /// Set the color, notifying the parent if it changed.
+    fn change_hsv(&mut self, hsv: Hsv) {
+        let start_color = self.color();
+        self.set_hsv(hsv);
+        self.notify_if_changed(start_color);
+    }
+
+    fn notify_if_changed(&mut self, start_color: [u8; 4]) {
+        let color = self.color();
+        if color != start_color {
+            let widget_id = self.widget_tag.widget_id();
+            self.widget_tag.send_message_to(ColorChanged{ widget_id, color }, MessageTarget::ParentOf(widget_id));
+        }
+    }
+
+    /// Push the current color to the field, hue slider, and inputs.
+    fn sync_children(&mut self) {
+        if self.field.hsv != self.hsv {
+            self.field.hsv = self.hsv;
+            self.field.widget_tag.request_redraw();
+        }
+        let hue = self.hsv.hue.min(MAX_HUE);
+        if self.hue_slider.value() != hue {
+            *self.hue_slider.value_mut() = hue;
+        }
+
+        let rgb = self.hsv.to_rgb();
+        for (input, &channel) in self.inputs.iter_mut().zip(rgb.iter()) {
+            if input.value() != channel {
+                input.set_value(channel);
+            }
+        }
+    }
+
+    fn child_info<R: Renderer>(&self, index: usize) -> Option<WidgetInfo<'_, R>> {
+        let ident = WidgetIdent::Num(index as u32);
+        match index {
+            0 => Some(WidgetInfo::new(ident, index, &self.field)),
+            1 => Some(WidgetInfo::new(ident, index, &self.hue_slider)),
+            _ => self.inputs.get(index - 2).map(|input| WidgetInfo::new(ident, index, input))
+        }
+    }
+
+    fn child_info_mut<R: Renderer>(&mut self, index: usize) -> Option<WidgetInfoMut<'_, R>> {
+        let ident = WidgetIdent::Num(index as u32);
+        match index {
+            0 => Some(WidgetInfoMut::new(ident, index, &mut self.field)),
+            1 => Some(WidgetInfoMut::new(ident, index, &mut self.hue_slider)),
+            _ => self.inputs.get_mut(index - 2).map(|input| WidgetInfoMut::new(ident, index, input))
+        }
+    }
+}
+
+/// Track a left-button drag, returning the cursor position whenever the pick should move.
+fn drag_pos(event: &WidgetEvent, dragging: &mut bool) -> Option<Point2<i32>> {
+    match *event {
+        WidgetEvent::MouseDown{pos, in_widget: true, button: MouseButton::Left} => {
+            *dragging = true;
+            Some(pos)
+        },
+        WidgetEvent::MouseMove{new_pos, ..} if *dragging => Some(new_pos),
+        WidgetEvent::MouseUp{button: MouseButton::Left, ..} => {
+            *dragging = false;
+            None
+        },
+        _ => None
+    }
+}
+
+/// The fraction of the way `pos` is along `len`, clamped to `0.0..=1.0`.
+fn fraction(pos: i32, len: i32) -> f32 {
+    match len {
+        0 => 0.0,
+        _ => (pos as f32 / len as f32).min(1.0).max(0.0)
+    }
+}
+
+impl Widget for ColorPicker {
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        self.widget_tag.request_relayout();
+        &mut self.rect
+    }
+
+    #[inline]
+    fn size_bounds(&self) -> SizeBounds {
+        self.size_bounds
+    }
+
+    #[inline]
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        EventOps {
+            focus: None,
+            bubble: event.unwrap().default_bubble(),
+            handled: false,
+        }
+    }
+}
+
+impl Widget for ColorField {
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        &mut self.rect
+    }
+
+    #[inline]
+    fn size_bounds(&self) -> SizeBounds {
+        self.size_bounds
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        let event = event.unwrap();
+        let was_dragging = self.dragging;
+        let pos = drag_pos(&event, &mut self.dragging);
+
+        if let Some(pos) = pos {
+            let picked = FieldPicked {
+                saturation: fraction(pos.x, self.rect.width()),
+                value: 1.0 - fraction(pos.y, self.rect.height()),
+            };
+            let widget_id = self.widget_tag.widget_id();
+            self.widget_tag.send_message_to(picked, MessageTarget::ParentOf(widget_id));
+        }
+
+        EventOps {
+            focus: None,
+            bubble: event.default_bubble(),
+            handled: pos.is_some() || was_dragging != self.dragging,
+        }
+    }
+}
+
+impl SliderHandler for HueHandler {
+    type Action = HuePicked;
+
+    fn on_move(&mut self, _: f32, new_value: f32) -> Option<HuePicked> {
+        Some(HuePicked {
+            picker: self.picker,
+            hue: new_value,
+        })
+    }
+}
+
+impl Parent for ColorPicker {
+    fn num_children(&self) -> usize {
+        2 + self.inputs.len()
+    }
+
+    fn framed_child<R: Renderer>(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, R>> {
+        match widget_ident {
+            WidgetIdent::Num(index) => self.child_info(index as usize),
+            _ => None
+        }
+    }
+    fn framed_child_mut<R: Renderer>(&mut self, widget_ident: WidgetIdent) -> Option<WidgetInfoMut<'_, R>> {
+        match widget_ident {
+            WidgetIdent::Num(index) => self.child_info_mut(index as usize),
+            _ => None
+        }
+    }
+
+    fn framed_children<'a, R, G>(&'a self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfo<'a, R>) -> LoopFlow
+    {
+        if let LoopFlow::Break = for_each(WidgetInfo::new(WidgetIdent::Num(0), 0, &self.field)) {
+            return;
+        }
+        if let LoopFlow::Break = for_each(WidgetInfo::new(WidgetIdent::Num(1), 1, &self.hue_slider)) {
+            return;
+        }
+        for (i, input) in self.inputs.iter().enumerate() {
+            let index = i + 2;
+            if let LoopFlow::Break = for_each(WidgetInfo::new(WidgetIdent::Num(index as u32), index, input)) {
+                return;
+            }
+        }
+    }
+
+    fn framed_children_mut<'a, R, G>(&'a mut self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfoMut<'a, R>) -> LoopFlow
+    {
+        if let LoopFlow::Break = for_each(WidgetInfoMut::new(WidgetIdent::Num(0), 0, &mut self.field)) {
+            return;
+        }
+        if let LoopFlow::Break = for_each(WidgetInfoMut::new(WidgetIdent::Num(1), 1, &mut self.hue_slider)) {
+            return;
+        }
+        for (i, input) in self.inputs.iter_mut().enumerate() {
+            let index = i + 2;
+            if let LoopFlow::Break = for_each(WidgetInfoMut::new(WidgetIdent::Num(index as u32), index, input)) {
+                return;
+            }
+        }
+    }
+
+    fn framed_child_by_index<R: Renderer>(&self, index: usize) -> Option<WidgetInfo<'_, R>> {
+        self.child_info(index)
+    }
+    fn framed_child_by_index_mut<R: Renderer>(&mut self, index: usize) -> Option<WidgetInfoMut<'_, R>> {
+        self.child_info_mut(index)
+    }
+}
+
+impl<R> WidgetRenderable<R> for ColorPicker
+    where R: Renderer
+{
+    type Theme = ColorPickerTheme;
+    fn theme(&self) -> ColorPickerTheme {
+        ColorPickerTheme(())
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, layout: &mut R::Layout) {
+        let metrics = layout.control_metrics();
+        let result = layout.finish();
+
+        let slider_width = self.hue_slider.size_bounds().min.width().max(metrics.slider_thickness);
+        let inputs_height = self.inputs.iter().map(|i| i.size_bounds().min.height()).max().unwrap_or(0);
+        let inputs_width: i32 = self.inputs.iter().map(|i| i.size_bounds().min.width()).sum();
+        let field_min = self.field.size_bounds.min.width()
+            .max(self.field.size_bounds.min.height())
+            .max(metrics.slider_length / 2);
+
+        self.size_bounds = result.size_bounds.expand_min(DimsBox::new2(
+            (field_min + slider_width).max(inputs_width),
+            field_min + inputs_height,
+        ));
+
+        // The field fills the space the hue slider and inputs leave over, with the slider to its
+        // right and the inputs splitting a row along the bottom.
+        let (width, height) = (self.rect.width(), self.rect.height());
+        let pick_height = (height - inputs_height).max(0);
+        let field_width = (width - slider_width).max(0);
+        self.field.rect = BoundBox::new2(0, 0, field_width, pick_height);
+        let slider_rect = BoundBox::new2(field_width, 0, width, pick_height);
+        if self.hue_slider.rect() != slider_rect {
+            *self.hue_slider.rect_mut() = slider_rect;
+        }
+
+        let num_inputs = self.inputs.len() as i32;
+        for (i, input) in self.inputs.iter_mut().enumerate() {
+            let i = i as i32;
+            let rect = BoundBox::new2(
+                width * i / num_inputs,
+                pick_height,
+                width * (i + 1) / num_inputs,
+                height,
+            );
+            if input.rect() != rect {
+                *input.rect_mut() = rect;
+            }
+        }
+    }
+}
+
+impl<R> WidgetRenderable<R> for ColorField
+    where R: Renderer
+{
+    type Theme = ColorPickerFieldTheme;
+    fn theme(&self) -> ColorPickerFieldTheme {
+        ColorPickerFieldTheme {
+            hsv: self.hsv,
+        }
+    }
+
+    /// Draw the gradient of every saturation and value of the picked hue, with a marker over the
+    /// picked saturation and value.
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        let (width, height) = (self.rect.width(), self.rect.height());
+        frame.render_background();
+        for y in (0..height).step_by(GRADIENT_CELL as usize) {
+            for x in (0..width).step_by(GRADIENT_CELL as usize) {
+                let cell = Hsv {
+                    saturation: fraction(x, width),
+                    value: 1.0 - fraction(y, height),
+                    ..self.hsv
+                };
+                let [r, g, b] = cell.to_rgb();
+                let rect = BoundBox::new2(x, y, (x + GRADIENT_CELL).min(width), (y + GRADIENT_CELL).min(height));
+                frame.fill_rect(rect, [r, g, b, 255]);
+            }
+        }
+
+        // A light border around the picked color, so the marker stands out against dark colors.
+        let marker = Point2::new(
+            (self.hsv.saturation * width as f32) as i32,
+            ((1.0 - self.hsv.value) * height as f32) as i32,
+        );
+        let [r, g, b] = self.hsv.to_rgb();
+        let half = MARKER_SIZE / 2;
+        frame.fill_rect(BoundBox::new2(marker.x - half, marker.y - half, marker.x + half + 1, marker.y + half + 1), [255; 4]);
+        frame.fill_rect(BoundBox::new2(marker.x - half + 1, marker.y - half + 1, marker.x + half, marker.y + half), [r, g, b, 255]);
+
+        frame.render_laid_out_content();
+        frame.render_foreground();
+    }
+
+    fn update_layout(&mut self, layout: &mut R::Layout) {
+        let result = layout.finish();
+        self.size_bounds = result.size_bounds;
+    }
+}
+
+impl WidgetTheme for ColorPickerTheme {
+    type Fallback = GroupTheme;
+    fn fallback(self) -> Option<GroupTheme> {
+        Some(GroupTheme::default())
+    }
+}
+
+impl WidgetTheme for ColorPickerFieldTheme {
+    type Fallback = !;
+    fn fallback(self) -> Option<!> {
+        None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::WindowEvent,
+        test_helpers::{click, headless_root, send_events},
+    };
+
+    #[test]
+    fn hsv_round_trip() {
+        assert_eq!(Hsv{ hue: 0.0, saturation: 1.0, value: 1.0 }, Hsv::from_rgb([255, 0, 0]));
+        assert_eq!(Hsv{ hue: 240.0, saturation: 1.0, value: 1.0 }, Hsv::from_rgb([0, 0, 255]));
+        assert_eq!(Hsv{ hue: 0.0, saturation: 0.0, value: 0.0 }, Hsv::from_rgb([0, 0, 0]));
+
+        for r in (0..=255).step_by(15) {
+            for g in (0..=255).step_by(15) {
+                for b in (0..=255).step_by(15) {
+                    let rgb = [r as u8, g as u8, b as u8];
+                    assert_eq!(rgb, Hsv::from_rgb(rgb).to_rgb());
+                }
+            }
+        }
+
+        // Out-of-range components get wrapped and clamped.
+        assert_eq!([0, 255, 255], Hsv{ hue: 540.0, saturation: 2.0, value: 1.5 }.to_rgb());
+    }
+
+    #[test]
+    fn pick_from_field() {
+        let mut picker = ColorPicker::new([255, 0, 0, 128]);
+        picker.set_show_inputs(false);
+        let mut root = headless_root(picker, DimsBox::new2(128, 128));
+        assert_eq!(BoundBox::new2(0, 0, 112, 128), root.root_widget.field.rect);
+
+        // The top-left corner of the field has no saturation and full value.
+        click(&mut root, Point2::new(0, 0));
+        assert_eq!([255, 255, 255, 128], root.root_widget.color());
+
+        // Dragging down the left edge darkens the color.
+        send_events(&mut root, &[
+            WindowEvent::MouseMove(Point2::new(0, 64)),
+            WindowEvent::MouseDown(MouseButton::Left),
+            WindowEvent::MouseMove(Point2::new(0, 128)),
+            WindowEvent::MouseUp(MouseButton::Left),
+        ]);
+        assert_eq!([0, 0, 0, 128], root.root_widget.color());
+        // Grays keep their hue.
+        assert_eq!(0.0, root.root_widget.hsv().hue);
+    }
+
+    #[test]
+    fn pick_from_hue_slider() {
+        let mut picker = ColorPicker::new([255, 0, 0, 255]);
+        picker.set_show_inputs(false);
+        let mut root = headless_root(picker, DimsBox::new2(128, 128));
+        assert_eq!(BoundBox::new2(112, 0, 128, 128), root.root_widget.hue_slider.rect());
+
+        // The slider's head is 16 pixels long, leaving 112 pixels for it to travel down.
+        send_events(&mut root, &[
+            WindowEvent::MouseMove(Point2::new(120, 8)),
+            WindowEvent::MouseDown(MouseButton::Left),
+            WindowEvent::MouseMove(Point2::new(120, 64)),
+            WindowEvent::MouseUp(MouseButton::Left),
+        ]);
+        assert_eq!(180.0, root.root_widget.hsv().hue);
+        assert_eq!([0, 255, 255, 255], root.root_widget.color());
+
+        // Setting the color moves the slider.
+        root.root_widget.set_color([0, 0, 255, 255]);
+        assert_eq!(240.0, root.root_widget.hue_slider.value());
+    }
+
+    #[test]
+    fn inputs() {
+        let mut root = headless_root(ColorPicker::new([255, 0, 0, 255]), DimsBox::new2(128, 128));
+        assert!(root.root_widget.show_inputs());
+        let values = root.root_widget.inputs.iter().map(|i| i.value()).collect::<Vec<_>>();
+        assert_eq!(vec![255, 0, 0], values);
+
+        root.root_widget.set_hsv(Hsv{ hue: 120.0, saturation: 1.0, value: 1.0 });
+        let values = root.root_widget.inputs.iter().map(|i| i.value()).collect::<Vec<_>>();
+        assert_eq!(vec![0, 255, 0], values);
+
+        root.root_widget.set_show_inputs(false);
+        assert!(!root.root_widget.show_inputs());
+        assert_eq!(2, root.root_widget.num_children());
+    }
+
+    #[test]
+    fn render_field_gradient() {
+        let mut picker = ColorPicker::new([255, 0, 0, 255]);
+        picker.set_show_inputs(false);
+        let mut root = headless_root(picker, DimsBox::new2(128, 128));
+        let image = root.render_to_image();
+
+        // The top-left of the field is white, the top-right is nearly the pure hue, and the
+        // bottom is nearly black.
+        assert_eq!(Some([255, 255, 255, 255]), image.pixel(2, 2));
+        let top_right = image.pixel(109, 2).unwrap();
+        assert_eq!(255, top_right[0]);
+        assert!(top_right[1] < 16 && top_right[2] < 16, "{:?}", top_right);
+        let bottom = image.pixel(56, 125).unwrap();
+        assert!(bottom[..3].iter().all(|&c| c < 16), "{:?}", bottom);
+    }
+}
@@ -34,6 +34,7 @@ pub trait DirectRenderState: 'static {
         EventOps {
             focus: None,
             bubble: true,
+            handled: false,
         }
     }
 }
@@ -3,15 +3,23 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use derin_core::{
+    accessibility::{AccessibilityNode, AccessibilityRole},
     event::{EventOps, WidgetEvent, WidgetEventSourced, InputState},
     timer::{Timer, TimerId},
-    widget::{WidgetTag, WidgetRenderable, Widget},
-    render::{Renderer, RendererLayout, SubFrame, WidgetTheme},
+    widget::{WidgetTag, WidgetRenderable, WidgetId, Widget, MessageTarget},
+    render::{Renderer, RendererLayout, SubFrame, CaretShape, CursorData, TextLayoutResult, TextLine, WidgetTheme},
 };
-use crate::widgets::assistants::text_edit::{TextEditAssist, TextEditOps, CursorFlashOp, LineCharFilter};
-use cgmath_geometry::{D2, rect::BoundBox};
+use crate::widgets::assistants::{
+    Debounce,
+    text_edit::{CharFilter, TextEditAssist, TextEditOps, CursorFlashOp, LineCharFilter},
+};
+use cgmath_geometry::{D2, rect::{BoundBox, DimsBox}};
 use derin_common_types::layout::SizeBounds;
-use std::time::Duration;
+use std::{
+    fmt,
+    rc::Rc,
+    time::Duration,
+};
 
 /// Multi-line editable text widget.
 #[derive(Debug, Clone)]
@@ -21,6 +29,9 @@ pub struct EditBox {
     edit: TextEditAssist,
     size_bounds: SizeBounds,
     flash_timer: Option<TimerId>,
+    change_debounce: Option<Debounce>,
+    validation: Option<Validation>,
+    colors: EditColors,
 }
 
 /// Single-line editable text widget.
@@ -31,12 +42,116 @@ pub struct LineBox {
     edit: TextEditAssist<LineCharFilter>,
     size_bounds: SizeBounds,
     flash_timer: Option<TimerId>,
+    change_debounce: Option<Debounce>,
+    validation: Option<Validation>,
+    colors: EditColors,
+}
+
+/// Message sent to the parent of an edit box once the user has stopped changing its text.
+///
+/// Only sent if a delay has been set with `set_change_debounce`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextChangedDebounced {
+    /// The ID of the edit box whose text changed.
+    pub widget_id: WidgetId,
+}
+
+/// Message sent to the parent of an edit box when its contents go from valid to invalid, or from
+/// invalid to valid.
+///
+/// Only sent if a validator has been set with `set_validator`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationChanged {
+    /// The ID of the edit box whose validity changed.
+    pub widget_id: WidgetId,
+    /// The error returned by the validator, or `None` if the contents are now valid.
+    pub error: Option<String>,
+}
+
+/// When an edit box runs its validator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValidateOn {
+    /// Validate whenever the text changes.
+    Change,
+    /// Validate when the edit box loses keyboard focus.
+    Blur,
+}
+
+#[derive(Clone)]
+struct Validation {
+    validator: Rc<Fn(&str) -> Result<(), String>>,
+    validate_on: ValidateOn,
+    error: Option<String>,
+    /// Set when the string needs to be validated at the next layout, which is when queued edits
+    /// actually get applied to the string.
+    pending: bool,
+}
+
+/// The colors an edit box's caret and selection get drawn with.
+///
+/// `None` uses the default color.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EditColors {
+    pub caret: Option<[u8; 4]>,
+    /// The background color of selected text.
+    pub selection: Option<[u8; 4]>,
 }
 
 #[derive(Debug, Clone, Default)]
-pub struct EditBoxTheme(());
+pub struct EditBoxTheme {
+    /// The error returned by the box's validator, if the contents are invalid.
+    pub error: Option<String>,
+    pub colors: EditColors,
+}
 #[derive(Debug, Clone, Default)]
-pub struct LineBoxTheme(());
+pub struct LineBoxTheme {
+    /// The error returned by the box's validator, if the contents are invalid.
+    pub error: Option<String>,
+    pub colors: EditColors,
+}
+
+impl EditColors {
+    /// The color the caret gets drawn in if `caret` isn't set.
+    pub const DEFAULT_CARET: [u8; 4] = [0, 0, 0, 255];
+    /// The color selected text gets highlighted with if `selection` isn't set.
+    pub const DEFAULT_SELECTION: [u8; 4] = [0, 120, 215, 255];
+}
+
+impl Validation {
+    fn new(validate_on: ValidateOn, validator: impl 'static + Fn(&str) -> Result<(), String>) -> Validation {
+        Validation {
+            validator: Rc::new(validator),
+            validate_on,
+            error: None,
+            pending: true,
+        }
+    }
+
+    fn validate(&mut self, string: &str, widget_tag: &mut WidgetTag) {
+        let error = (self.validator)(string).err();
+        if error != self.error {
+            widget_tag.request_redraw();
+            if error.is_some() != self.error.is_some() {
+                let widget_id = widget_tag.widget_id();
+                widget_tag.send_message_to(
+                    ValidationChanged{ widget_id, error: error.clone() },
+                    MessageTarget::ParentOf(widget_id)
+                );
+            }
+        }
+        self.error = error;
+    }
+}
+
+impl fmt::Debug for Validation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("Validation")
+            .field("validate_on", &self.validate_on)
+            .field("error", &self.error)
+            .field("pending", &self.pending)
+            .finish()
+    }
+}
 
 impl EditBox {
     /// Create a new `EditBox`, containing the included `String` by default.
@@ -44,12 +159,12 @@ impl EditBox {
         EditBox {
             widget_tag: WidgetTag::new(),
             bounds: BoundBox::new2(0, 0, 0, 0),
-            edit: TextEditAssist {
-                string,
-                ..TextEditAssist::default()
-            },
+            edit: TextEditAssist::new(string),
             size_bounds: SizeBounds::default(),
             flash_timer: None,
+            change_debounce: None,
+            validation: None,
+            colors: EditColors::default(),
         }
     }
 
@@ -58,14 +173,118 @@ impl EditBox {
         &self.edit.string
     }
 
+    /// Retrieves the positions of the `EditBox`'s glyphs from the last time it was laid out, for
+    /// mapping points to string indices and back.
+    ///
+    /// Returns `None` if the box hasn't been laid out yet, or if the renderer doesn't track glyph
+    /// positions.
+    pub fn text_layout(&self) -> Option<&TextLayoutResult> {
+        self.edit.text_layout.as_ref()
+    }
+
     /// Retrieves the `String` stored in the `EditBox`, for mutation.
     ///
     /// Calling this function forces the box to be re-drawn, so you're discouraged from calling
-    /// it unless you're actually changing the contents.
+    /// it unless you're actually changing the contents. If the box validates its contents with
+    /// `ValidateOn::Change`, they get validated again when the box is next laid out.
     pub fn string_mut(&mut self) -> &mut String {
         self.widget_tag.request_redraw().request_relayout();
+        if let Some(ref mut validation) = self.validation {
+            if validation.validate_on == ValidateOn::Change {
+                validation.pending = true;
+            }
+        }
         &mut self.edit.string
     }
+
+    /// Set how long the user has to stop typing before the `EditBox` sends a [`TextChangedDebounced`]
+    /// message to its parent. Passing `None` disables the message.
+    ///
+    /// [`TextChangedDebounced`]: ./struct.TextChangedDebounced.html
+    pub fn set_change_debounce(&mut self, delay: Option<Duration>) {
+        if let Some(mut debounce) = self.change_debounce.take() {
+            debounce.cancel(&mut self.widget_tag);
+        }
+        self.change_debounce = delay.map(Debounce::new);
+    }
+
+    /// Set a function that checks whether the `EditBox`'s contents are valid, replacing any previous
+    /// validator.
+    ///
+    /// Invalid contents aren't rejected, so the user can keep editing them. Instead, the error is
+    /// passed to the theme so the box can be drawn in an error style, and a [`ValidationChanged`]
+    /// message is sent to the box's parent whenever the contents become valid or invalid.
+    ///
+    /// [`ValidationChanged`]: ./struct.ValidationChanged.html
+    pub fn set_validator(&mut self, validate_on: ValidateOn, validator: impl 'static + Fn(&str) -> Result<(), String>) {
+        self.validation = Some(Validation::new(validate_on, validator));
+        self.widget_tag.request_relayout();
+    }
+
+    /// Remove the validator, clearing any validation error.
+    pub fn clear_validator(&mut self) {
+        if self.validation.take().is_some() {
+            self.widget_tag.request_redraw();
+        }
+    }
+
+    /// Retrieves the error returned by the validator the last time it ran.
+    pub fn validation_error(&self) -> Option<&str> {
+        self.validation.as_ref().and_then(|v| v.error.as_ref()).map(|e| &**e)
+    }
+
+    /// Whether typed characters replace the text after the cursor instead of being inserted.
+    pub fn overwrite(&self) -> bool {
+        self.edit.overwrite
+    }
+
+    /// Switch between overwrite and insert mode. The user can also toggle this by pressing the
+    /// `Insert` key. The caret is drawn as a block in overwrite mode, and as a bar in insert mode.
+    pub fn set_overwrite(&mut self, overwrite: bool) {
+        if self.edit.overwrite != overwrite {
+            self.edit.set_overwrite(overwrite);
+            self.widget_tag.request_redraw();
+        }
+    }
+
+    /// How far, in pixels, the cursor has to move while pressed before it starts selecting text.
+    /// `None` means the default threshold is used.
+    pub fn drag_threshold(&self) -> Option<i32> {
+        self.edit.drag_threshold
+    }
+
+    /// Set how far, in pixels along either axis, the cursor has to move while pressed before it
+    /// starts selecting text, so that a click with a bit of jitter just places the caret. Pass
+    /// `None` to use the default threshold.
+    pub fn set_drag_threshold(&mut self, drag_threshold: Option<i32>) {
+        self.edit.drag_threshold = drag_threshold;
+    }
+
+    /// Whether middle-clicking pastes the primary selection, and selecting text copies it into the
+    /// primary selection. Always `false` on platforms without a primary selection.
+    pub fn uses_primary_selection(&self) -> bool {
+        self.edit.uses_primary_selection()
+    }
+
+    /// Enable or disable pasting the primary selection with the middle mouse button, and copying
+    /// selected text into it. Enabled by default, but only has an effect on X11-style platforms,
+    /// so disabling it makes the box behave the same everywhere.
+    pub fn set_primary_selection_enabled(&mut self, enabled: bool) {
+        self.edit.disable_primary_selection = !enabled;
+    }
+
+    /// Retrieves the colors the `EditBox`'s caret and selection are drawn with.
+    pub fn colors(&self) -> EditColors {
+        self.colors
+    }
+
+    /// Override the theme's colors for the `EditBox`'s caret and selection.
+    pub fn set_colors(&mut self, colors: EditColors) {
+        if self.colors != colors {
+            self.colors = colors;
+            self.widget_tag.request_redraw();
+        }
+    }
 }
 
 impl LineBox {
@@ -74,12 +293,12 @@ impl LineBox {
         LineBox {
             widget_tag: WidgetTag::new(),
             bounds: BoundBox::new2(0, 0, 0, 0),
-            edit: TextEditAssist {
-                string,
-                ..TextEditAssist::default()
-            },
+            edit: TextEditAssist::new(string),
             size_bounds: SizeBounds::default(),
             flash_timer: None,
+            change_debounce: None,
+            validation: None,
+            colors: EditColors::default(),
         }
     }
 
@@ -88,14 +307,181 @@ impl LineBox {
         &self.edit.string
     }
 
+    /// Retrieves the positions of the `LineBox`'s glyphs from the last time it was laid out, for
+    /// mapping points to string indices and back.
+    ///
+    /// Returns `None` if the box hasn't been laid out yet, or if the renderer doesn't track glyph
+    /// positions.
+    pub fn text_layout(&self) -> Option<&TextLayoutResult> {
+        self.edit.text_layout.as_ref()
+    }
+
     /// Retrieves the `String` stored in the `LineBox`, for mutation.
     ///
     /// Calling this function forces the box to be re-drawn, so you're discouraged from calling
-    /// it unless you're actually changing the contents.
+    /// it unless you're actually changing the contents. If the box validates its contents with
+    /// `ValidateOn::Change`, they get validated again when the box is next laid out.
     pub fn string_mut(&mut self) -> &mut String {
         self.widget_tag.request_redraw().request_relayout();
+        if let Some(ref mut validation) = self.validation {
+            if validation.validate_on == ValidateOn::Change {
+                validation.pending = true;
+            }
+        }
         &mut self.edit.string
     }
+
+    /// Set how long the user has to stop typing before the `LineBox` sends a [`TextChangedDebounced`]
+    /// message to its parent. Passing `None` disables the message.
+    ///
+    /// [`TextChangedDebounced`]: ./struct.TextChangedDebounced.html
+    pub fn set_change_debounce(&mut self, delay: Option<Duration>) {
+        if let Some(mut debounce) = self.change_debounce.take() {
+            debounce.cancel(&mut self.widget_tag);
+        }
+        self.change_debounce = delay.map(Debounce::new);
+    }
+
+    /// Set a function that checks whether the `LineBox`'s contents are valid, replacing any previous
+    /// validator.
+    ///
+    /// Invalid contents aren't rejected, so the user can keep editing them. Instead, the error is
+    /// passed to the theme so the box can be drawn in an error style, and a [`ValidationChanged`]
+    /// message is sent to the box's parent whenever the contents become valid or invalid.
+    ///
+    /// [`ValidationChanged`]: ./struct.ValidationChanged.html
+    pub fn set_validator(&mut self, validate_on: ValidateOn, validator: impl 'static + Fn(&str) -> Result<(), String>) {
+        self.validation = Some(Validation::new(validate_on, validator));
+        self.widget_tag.request_relayout();
+    }
+
+    /// Remove the validator, clearing any validation error.
+    pub fn clear_validator(&mut self) {
+        if self.validation.take().is_some() {
+            self.widget_tag.request_redraw();
+        }
+    }
+
+    /// Retrieves the error returned by the validator the last time it ran.
+    pub fn validation_error(&self) -> Option<&str> {
+        self.validation.as_ref().and_then(|v| v.error.as_ref()).map(|e| &**e)
+    }
+
+    /// Whether typed characters replace the text after the cursor instead of being inserted.
+    pub fn overwrite(&self) -> bool {
+        self.edit.overwrite
+    }
+
+    /// Switch between overwrite and insert mode. The user can also toggle this by pressing the
+    /// `Insert` key. The caret is drawn as a block in overwrite mode, and as a bar in insert mode.
+    pub fn set_overwrite(&mut self, overwrite: bool) {
+        if self.edit.overwrite != overwrite {
+            self.edit.set_overwrite(overwrite);
+            self.widget_tag.request_redraw();
+        }
+    }
+
+    /// How far, in pixels, the cursor has to move while pressed before it starts selecting text.
+    /// `None` means the default threshold is used.
+    pub fn drag_threshold(&self) -> Option<i32> {
+        self.edit.drag_threshold
+    }
+
+    /// Set how far, in pixels along either axis, the cursor has to move while pressed before it
+    /// starts selecting text, so that a click with a bit of jitter just places the caret. Pass
+    /// `None` to use the default threshold.
+    pub fn set_drag_threshold(&mut self, drag_threshold: Option<i32>) {
+        self.edit.drag_threshold = drag_threshold;
+    }
+
+    /// Whether middle-clicking pastes the primary selection, and selecting text copies it into the
+    /// primary selection. Always `false` on platforms without a primary selection.
+    pub fn uses_primary_selection(&self) -> bool {
+        self.edit.uses_primary_selection()
+    }
+
+    /// Enable or disable pasting the primary selection with the middle mouse button, and copying
+    /// selected text into it. Enabled by default, but only has an effect on X11-style platforms,
+    /// so disabling it makes the box behave the same everywhere.
+    pub fn set_primary_selection_enabled(&mut self, enabled: bool) {
+        self.edit.disable_primary_selection = !enabled;
+    }
+
+    /// Retrieves the colors the `LineBox`'s caret and selection are drawn with.
+    pub fn colors(&self) -> EditColors {
+        self.colors
+    }
+
+    /// Override the theme's colors for the `LineBox`'s caret and selection.
+    pub fn set_colors(&mut self, colors: EditColors) {
+        if self.colors != colors {
+            self.colors = colors;
+            self.widget_tag.request_redraw();
+        }
+    }
+}
+
+/// Draw the box's text, with the selection highlighted behind it and the caret drawn in the shape
+/// given by the cursor data.
+fn render_edit<C: CharFilter>(frame: &mut impl SubFrame, edit: &TextEditAssist<C>, colors: EditColors) {
+    let CursorData{ draw_cursor, cursor_pos, ref highlight_range, caret_shape } = edit.cursor_data;
+    let text_layout = match edit.text_layout {
+        Some(ref text_layout) => text_layout,
+        None => {
+            frame.render_laid_out_content();
+            return;
+        }
+    };
+
+    frame.render_background();
+    let selection_color = colors.selection.unwrap_or(EditColors::DEFAULT_SELECTION);
+    for line in text_layout.lines() {
+        let start = highlight_range.start.max(line.range.start);
+        let end = highlight_range.end.min(line.range.end);
+        if start < end {
+            let rect = BoundBox::new2(caret_x(line, start), line.top, caret_x(line, end), line.top + line.height);
+            frame.fill_rect(rect, selection_color);
+        }
+    }
+
+    // Block carets sit behind the grapheme they cover, the same way the selection does. Bar carets
+    // lie between graphemes, so they get drawn over the text.
+    let caret = match draw_cursor && highlight_range.len() == 0 {
+        true => caret_rect(text_layout, cursor_pos, caret_shape),
+        false => None
+    };
+    let caret_color = colors.caret.unwrap_or(EditColors::DEFAULT_CARET);
+    if let (Some(rect), CaretShape::Block) = (caret, caret_shape) {
+        frame.fill_rect(rect, caret_color);
+    }
+    frame.render_laid_out_content();
+    if let (Some(rect), CaretShape::Bar) = (caret, caret_shape) {
+        frame.fill_rect(rect, caret_color);
+    }
+    frame.render_foreground();
+}
+
+/// The horizontal position of the caret placed at `index` on `line`.
+fn caret_x(line: &TextLine, index: usize) -> i32 {
+    line.carets.iter().rev()
+        .find(|caret| caret.index <= index)
+        .map(|caret| caret.x)
+        .unwrap_or(0)
+}
+
+/// The rect covered by the caret placed at `index`.
+fn caret_rect(text_layout: &TextLayoutResult, index: usize, shape: CaretShape) -> Option<BoundBox<D2, i32>> {
+    let point = text_layout.point_at_index(index)?;
+    let line = text_layout.lines().iter().find(|line| line.top == point.y)?;
+    let width = match shape {
+        CaretShape::Bar => 1,
+        // At the end of a line there's no grapheme to cover, so the block gets a width of its own.
+        CaretShape::Block => line.carets.iter()
+            .find(|caret| caret.index > index)
+            .map(|caret| caret.x - point.x)
+            .unwrap_or(line.height / 2),
+    };
+    Some(BoundBox::new2(point.x, point.y, point.x + width, point.y + line.height))
 }
 
 macro_rules! render {
@@ -104,11 +490,14 @@ macro_rules! render {
             type Theme = $theme;
 
             fn theme(&self) -> $theme {
-                $theme(())
+                $theme {
+                    error: self.validation.as_ref().and_then(|v| v.error.clone()),
+                    colors: self.colors,
+                }
             }
 
             fn render(&mut self, frame: &mut R::SubFrame) {
-                frame.render_laid_out_content();
+                render_edit(frame, &self.edit, self.colors);
             }
 
             fn update_layout(&mut self, layout: &mut R::Layout) {
@@ -117,9 +506,19 @@ macro_rules! render {
                     &mut self.edit.cursor_data,
                     self.edit.cursor_ops.drain(..),
                 );
+                self.edit.update_primary_selection();
+
+                if let Some(ref mut validation) = self.validation {
+                    if validation.pending {
+                        validation.pending = false;
+                        validation.validate(&self.edit.string, &mut self.widget_tag);
+                    }
+                }
 
+                let control_height = layout.control_metrics().control_height;
                 let result = layout.finish();
-                self.size_bounds = result.size_bounds;
+                self.size_bounds = result.size_bounds.expand_min(DimsBox::new2(0, control_height));
+                self.edit.text_layout = result.text_layout;
             }
         }
     }
@@ -133,11 +532,35 @@ macro_rules! event {
             let TextEditOps {
                 allow_bubble,
                 redraw,
+                text_changed,
                 cursor_flash,
                 cursor_icon,
                 focus,
             } = self.edit.adapt_event(&event, input_state);
 
+            if let Some(ref mut debounce) = self.change_debounce {
+                if text_changed {
+                    debounce.trigger(&mut self.widget_tag);
+                } else if debounce.fired(&event, &mut self.widget_tag) {
+                    let widget_id = self.widget_tag.widget_id();
+                    self.widget_tag.send_message_to(TextChangedDebounced{ widget_id }, MessageTarget::ParentOf(widget_id));
+                }
+            }
+
+            if let Some(ref mut validation) = self.validation {
+                let validate = match (validation.validate_on, &event) {
+                    (ValidateOn::Change, _) => text_changed,
+                    (ValidateOn::Blur, WidgetEvent::LoseFocus) => true,
+                    _ => false
+                };
+                // Queued edits only get applied to the string during layout, so that's where the
+                // validator actually runs.
+                if validate {
+                    validation.pending = true;
+                    self.widget_tag.request_relayout();
+                }
+            }
+
             match (cursor_flash, self.flash_timer) {
                 (Some(CursorFlashOp::Start), None) => {
                     let timer_id = TimerId::new();
@@ -170,6 +593,7 @@ macro_rules! event {
             EventOps {
                 focus,
                 bubble: allow_bubble && event.default_bubble(),
+                handled: redraw || text_changed || !allow_bubble,
             }
         }
     }
@@ -196,6 +620,18 @@ impl Widget for EditBox {
         self.size_bounds
     }
 
+    #[inline]
+    fn accepts_focus(&self) -> bool {
+        true
+    }
+
+    fn accessibility_info(&self) -> Option<AccessibilityNode> {
+        Some(AccessibilityNode {
+            value: Some(self.string().to_owned()),
+            ..AccessibilityNode::new(AccessibilityRole::TextInput)
+        })
+    }
+
     event!();
 }
 
@@ -220,6 +656,18 @@ impl Widget for LineBox {
         self.size_bounds
     }
 
+    #[inline]
+    fn accepts_focus(&self) -> bool {
+        true
+    }
+
+    fn accessibility_info(&self) -> Option<AccessibilityNode> {
+        Some(AccessibilityNode {
+            value: Some(self.string().to_owned()),
+            ..AccessibilityNode::new(AccessibilityRole::TextInput)
+        })
+    }
+
     event!();
 }
 
@@ -234,8 +682,194 @@ impl WidgetTheme for EditBoxTheme {
 }
 
 impl WidgetTheme for LineBoxTheme {
-    type Fallback = !;
-    fn fallback(self) -> Option<!> {
-        None
+    type Fallback = EditBoxTheme;
+    fn fallback(self) -> Option<EditBoxTheme> {
+        Some(EditBoxTheme{ error: self.error, colors: self.colors })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cgmath::Point2,
+        core::{WindowEvent, render::headless::HeadlessRenderer},
+        event::Key,
+        test_helpers::{MessageRecorder, click, headless_root, press_key, send_events},
+    };
+    use std::thread;
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+    const WHITE: [u8; 4] = [255; 4];
+
+    #[test]
+    fn overwrite_mode() {
+        let mut root = headless_root(LineBox::new("ab".to_string()), DimsBox::new2(64, 32));
+        click(&mut root, Point2::new(10, 4));
+        press_key(&mut root, Key::Insert);
+        assert!(root.root_widget.overwrite());
+
+        send_events(&mut root, &[WindowEvent::Char('x')]);
+        assert_eq!("ax", root.root_widget.string());
+
+        press_key(&mut root, Key::Insert);
+        send_events(&mut root, &[WindowEvent::Char('y')]);
+        assert_eq!("axy", root.root_widget.string());
+    }
+
+    #[test]
+    fn caret_and_selection() {
+        let mut line_box = LineBox::new("ab".to_string());
+        line_box.set_colors(EditColors {
+            caret: Some(RED),
+            selection: Some(BLUE),
+        });
+        let mut root = headless_root(line_box, DimsBox::new2(64, 32));
+
+        // Every glyph is 8 pixels wide, so the caret after `a` lies at x = 8.
+        let cursor_data = &mut root.root_widget.edit.cursor_data;
+        cursor_data.draw_cursor = true;
+        cursor_data.cursor_pos = 1;
+        cursor_data.highlight_range = 1..1;
+        let image = root.render_to_image();
+        assert_eq!(Some(RED), image.pixel(8, 8));
+        assert_eq!(Some([0; 4]), image.pixel(15, 8));
+
+        // Block carets cover the grapheme after the cursor, and get drawn behind it.
+        root.root_widget.set_overwrite(true);
+        let image = root.render_to_image();
+        assert_eq!(Some(RED), image.pixel(8, 8));
+        assert_eq!(Some(WHITE), image.pixel(10, 8));
+        assert_eq!(Some(RED), image.pixel(15, 8));
+
+        // The caret isn't drawn while text is selected.
+        let cursor_data = &mut root.root_widget.edit.cursor_data;
+        cursor_data.cursor_pos = 2;
+        cursor_data.highlight_range = 0..2;
+        let image = root.render_to_image();
+        assert_eq!(Some(BLUE), image.pixel(7, 8));
+        assert_eq!(Some(WHITE), image.pixel(10, 8));
+        assert_eq!(Some(BLUE), image.pixel(15, 8));
+        assert_eq!(Some([0; 4]), image.pixel(20, 8));
+    }
+
+    #[test]
+    fn change_debounce() {
+        let mut line_box = LineBox::new(String::new());
+        line_box.set_change_debounce(Some(Duration::from_millis(50)));
+        let recorder = MessageRecorder::<_, TextChangedDebounced>::new(line_box);
+        let mut root = headless_root(recorder, DimsBox::new2(64, 32));
+        let widget_id = root.root_widget.child.widget.widget_tag.widget_id();
+        click(&mut root, Point2::new(10, 4));
+
+        // Nothing gets sent while the user's still typing.
+        send_events(&mut root, &[WindowEvent::Char('a'), WindowEvent::Char('b')]);
+        send_events(&mut root, &[]);
+        assert!(root.root_widget.messages.is_empty());
+
+        // Once the box has been quiet for long enough, the message gets sent exactly once.
+        thread::sleep(Duration::from_millis(60));
+        send_events(&mut root, &[]);
+        send_events(&mut root, &[]);
+        assert_eq!(vec![TextChangedDebounced{ widget_id }], root.root_widget.messages);
+        thread::sleep(Duration::from_millis(60));
+        send_events(&mut root, &[]);
+        send_events(&mut root, &[]);
+        assert_eq!(1, root.root_widget.messages.len());
+    }
+
+    #[test]
+    fn disable_change_debounce() {
+        let mut line_box = LineBox::new(String::new());
+        line_box.set_change_debounce(Some(Duration::from_secs(60)));
+        let mut root = headless_root(line_box, DimsBox::new2(64, 32));
+        click(&mut root, Point2::new(10, 4));
+        let num_timers = root.root_widget.widget_tag.timers().len();
+
+        send_events(&mut root, &[WindowEvent::Char('a')]);
+        assert_eq!(num_timers + 1, root.root_widget.widget_tag.timers().len());
+
+        // Turning the debounce off removes its pending timer.
+        root.root_widget.set_change_debounce(None);
+        assert_eq!(num_timers, root.root_widget.widget_tag.timers().len());
+        send_events(&mut root, &[WindowEvent::Char('b')]);
+        assert_eq!(num_timers, root.root_widget.widget_tag.timers().len());
+    }
+
+    fn digits(string: &str) -> Result<(), String> {
+        match string.chars().all(|c| c.is_ascii_digit()) {
+            true => Ok(()),
+            false => Err(format!("{:?} isn't a number", string))
+        }
+    }
+
+    #[test]
+    fn validation_changed() {
+        let mut line_box = LineBox::new("1".to_string());
+        line_box.set_validator(ValidateOn::Change, digits);
+        let recorder = MessageRecorder::<_, ValidationChanged>::new(line_box);
+        let mut root = headless_root(recorder, DimsBox::new2(64, 32));
+        let widget_id = root.root_widget.child.widget.widget_tag.widget_id();
+        assert_eq!(None, root.root_widget.child.widget.validation_error());
+        click(&mut root, Point2::new(10, 4));
+
+        send_events(&mut root, &[WindowEvent::Char('a')]);
+        send_events(&mut root, &[]);
+        assert_eq!(Some("\"1a\" isn't a number"), root.root_widget.child.widget.validation_error());
+
+        // The error changes, but the contents were already invalid, so no message gets sent.
+        send_events(&mut root, &[WindowEvent::Char('b')]);
+        send_events(&mut root, &[]);
+        assert_eq!(Some("\"1ab\" isn't a number"), root.root_widget.child.widget.validation_error());
+
+        press_key(&mut root, Key::Back);
+        press_key(&mut root, Key::Back);
+        send_events(&mut root, &[]);
+        assert_eq!(None, root.root_widget.child.widget.validation_error());
+        assert_eq!(
+            vec![
+                ValidationChanged{ widget_id, error: Some("\"1a\" isn't a number".to_string()) },
+                ValidationChanged{ widget_id, error: None },
+            ],
+            root.root_widget.messages
+        );
+    }
+
+    #[test]
+    fn validate_string_mut() {
+        let mut line_box = LineBox::new("1".to_string());
+        line_box.set_validator(ValidateOn::Change, digits);
+        let mut root = headless_root(line_box, DimsBox::new2(64, 32));
+        root.root_widget.string_mut().push('a');
+        root.relayout();
+        assert_eq!(Some("\"1a\" isn't a number"), root.root_widget.validation_error());
+
+        // Boxes that validate on blur leave programmatic changes alone until they lose focus.
+        root.root_widget.set_validator(ValidateOn::Blur, digits);
+        root.relayout();
+        assert_eq!(Some("\"1a\" isn't a number"), root.root_widget.validation_error());
+        root.root_widget.string_mut().pop();
+        root.relayout();
+        assert_eq!(Some("\"1a\" isn't a number"), root.root_widget.validation_error());
+    }
+
+    #[test]
+    fn theme_error() {
+        let mut line_box = LineBox::new("a".to_string());
+        let theme = <LineBox as WidgetRenderable<HeadlessRenderer>>::theme(&line_box);
+        assert_eq!(None, theme.error);
+
+        line_box.set_validator(ValidateOn::Change, digits);
+        let mut root = headless_root(line_box, DimsBox::new2(64, 32));
+        let theme = <LineBox as WidgetRenderable<HeadlessRenderer>>::theme(&root.root_widget);
+        assert_eq!(Some("\"a\" isn't a number".to_string()), theme.error);
+        // Themes that don't style line boxes separately still get the error.
+        let error = theme.error.clone();
+        assert_eq!(error, theme.fallback().unwrap().error);
+
+        root.root_widget.clear_validator();
+        let theme = <LineBox as WidgetRenderable<HeadlessRenderer>>::theme(&root.root_widget);
+        assert_eq!(None, theme.error);
     }
 }
@@ -0,0 +1,347 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use derin_core::{
+    LoopFlow,
+    event::{EventOps, WidgetEventSourced, InputState},
+    widget::{WidgetIdent, WidgetRenderable, WidgetTag, WidgetInfo, WidgetInfoMut, Widget, Parent},
+    render::{Renderer, SubFrame, WidgetTheme},
+};
+
+use cgmath_geometry::{D2, rect::{BoundBox, DimsBox, GeoBox}};
+use derin_common_types::layout::SizeBounds;
+
+/// A widget that displays one of two widgets, such as a spinner while content is loading and the
+/// content once it's done.
+///
+/// The active widget fills the `Either`, which takes on the active widget's size bounds. Only the
+/// active widget is part of the widget tree, so the inactive side doesn't get events, layout, or
+/// rendering. Switching sides drops the previously-active widget.
+#[derive(Debug, Clone)]
+pub struct Either<A, B> {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    active: Side<A, B>,
+}
+
+#[derive(Debug, Clone)]
+enum Side<A, B> {
+    Left(A),
+    Right(B),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EitherTheme(());
+
+impl<A, B> Either<A, B>
+    where A: Widget,
+          B: Widget
+{
+    /// Creates an `Either` displaying the left widget.
+    pub fn new_left(left: A) -> Either<A, B> {
+        Either::new(Side::Left(left))
+    }
+
+    /// Creates an `Either` displaying the right widget.
+    pub fn new_right(right: B) -> Either<A, B> {
+        Either::new(Side::Right(right))
+    }
+
+    fn new(active: Side<A, B>) -> Either<A, B> {
+        Either {
+            widget_tag: WidgetTag::new(),
+            rect: BoundBox::new2(0, 0, 0, 0),
+            active,
+        }
+    }
+
+    /// Display `left`, replacing the active widget.
+    pub fn set_left(&mut self, left: A) {
+        self.widget_tag.request_relayout().request_redraw();
+        self.active = Side::Left(left);
+    }
+
+    /// Display `right`, replacing the active widget.
+    pub fn set_right(&mut self, right: B) {
+        self.widget_tag.request_relayout().request_redraw();
+        self.active = Side::Right(right);
+    }
+
+    /// Whether the left widget is being displayed.
+    #[inline]
+    pub fn is_left(&self) -> bool {
+        match self.active {
+            Side::Left(_) => true,
+            Side::Right(_) => false
+        }
+    }
+
+    /// Whether the right widget is being displayed.
+    #[inline]
+    pub fn is_right(&self) -> bool {
+        !self.is_left()
+    }
+
+    /// Retrieves the left widget, if it's being displayed.
+    pub fn left(&self) -> Option<&A> {
+        match self.active {
+            Side::Left(ref left) => Some(left),
+            Side::Right(_) => None
+        }
+    }
+
+    /// Retrieves the left widget for mutation, if it's being displayed.
+    pub fn left_mut(&mut self) -> Option<&mut A> {
+        match self.active {
+            Side::Left(ref mut left) => Some(left),
+            Side::Right(_) => None
+        }
+    }
+
+    /// Retrieves the right widget, if it's being displayed.
+    pub fn right(&self) -> Option<&B> {
+        match self.active {
+            Side::Left(_) => None,
+            Side::Right(ref right) => Some(right)
+        }
+    }
+
+    /// Retrieves the right widget for mutation, if it's being displayed.
+    pub fn right_mut(&mut self) -> Option<&mut B> {
+        match self.active {
+            Side::Left(_) => None,
+            Side::Right(ref mut right) => Some(right)
+        }
+    }
+
+    /// The left and right widgets get different idents, so that switching sides gets treated as
+    /// replacing the child rather than changing it.
+    fn active_ident(&self) -> WidgetIdent {
+        WidgetIdent::Num(self.is_right() as u32)
+    }
+
+    fn active(&self) -> &dyn Widget {
+        match self.active {
+            Side::Left(ref left) => left,
+            Side::Right(ref right) => right
+        }
+    }
+
+    fn active_info<R: Renderer>(&self) -> WidgetInfo<'_, R> {
+        let ident = self.active_ident();
+        match self.active {
+            Side::Left(ref left) => WidgetInfo::new(ident, 0, left),
+            Side::Right(ref right) => WidgetInfo::new(ident, 0, right),
+        }
+    }
+
+    fn active_info_mut<R: Renderer>(&mut self) -> WidgetInfoMut<'_, R> {
+        let ident = self.active_ident();
+        match self.active {
+            Side::Left(ref mut left) => WidgetInfoMut::new(ident, 0, left),
+            Side::Right(ref mut right) => WidgetInfoMut::new(ident, 0, right),
+        }
+    }
+}
+
+impl<A, B> Widget for Either<A, B>
+    where A: Widget,
+          B: Widget
+{
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        self.widget_tag.request_relayout();
+        &mut self.rect
+    }
+
+    #[inline]
+    fn size_bounds(&self) -> SizeBounds {
+        self.active().size_bounds()
+    }
+
+    #[inline]
+    fn min_intrinsic_size(&self) -> DimsBox<D2, i32> {
+        self.active().min_intrinsic_size()
+    }
+
+    #[inline]
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        EventOps {
+            focus: None,
+            bubble: event.unwrap().default_bubble(),
+            handled: false,
+        }
+    }
+}
+
+impl<A, B> Parent for Either<A, B>
+    where A: Widget,
+          B: Widget
+{
+    fn num_children(&self) -> usize {
+        1
+    }
+
+    fn framed_child<R: Renderer>(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, R>> {
+        match widget_ident == self.active_ident() {
+            true => Some(self.active_info()),
+            false => None
+        }
+    }
+    fn framed_child_mut<R: Renderer>(&mut self, widget_ident: WidgetIdent) -> Option<WidgetInfoMut<'_, R>> {
+        match widget_ident == self.active_ident() {
+            true => Some(self.active_info_mut()),
+            false => None
+        }
+    }
+
+    fn framed_children<'a, R, G>(&'a self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfo<'a, R>) -> LoopFlow
+    {
+        let _ = for_each(self.active_info());
+    }
+
+    fn framed_children_mut<'a, R, G>(&'a mut self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfoMut<'a, R>) -> LoopFlow
+    {
+        let _ = for_each(self.active_info_mut());
+    }
+
+    fn framed_child_by_index<R: Renderer>(&self, index: usize) -> Option<WidgetInfo<'_, R>> {
+        match index {
+            0 => Some(self.active_info()),
+            _ => None
+        }
+    }
+    fn framed_child_by_index_mut<R: Renderer>(&mut self, index: usize) -> Option<WidgetInfoMut<'_, R>> {
+        match index {
+            0 => Some(self.active_info_mut()),
+            _ => None
+        }
+    }
+}
+
+impl<A, B, R> WidgetRenderable<R> for Either<A, B>
+    where A: Widget,
+          B: Widget,
+          R: Renderer
+{
+    type Theme = EitherTheme;
+
+    fn theme(&self) -> EitherTheme {
+        EitherTheme(())
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, _: &mut R::Layout) {
+        let child_rect = BoundBox::from(self.rect.dims());
+        let active: &mut dyn Widget = match self.active {
+            Side::Left(ref mut left) => left,
+            Side::Right(ref mut right) => right
+        };
+        if active.rect() != child_rect {
+            *active.rect_mut() = child_rect;
+        }
+    }
+}
+
+impl WidgetTheme for EitherTheme {
+    type Fallback = !;
+    fn fallback(self) -> Option<!> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cgmath::Point2,
+        core::render::headless::HeadlessRenderer,
+        test_helpers::{click, headless_root},
+        widgets::{Button, ButtonHandler, Contents, Spacer},
+    };
+    use std::{cell::Cell, rc::Rc};
+
+    struct Counter(Rc<Cell<u32>>);
+
+    impl ButtonHandler for Counter {
+        fn on_click(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    fn spacer(width: i32, height: i32) -> Spacer {
+        Spacer::new(SizeBounds::new_min(DimsBox::new2(width, height)))
+    }
+
+    fn active_ident(either: &Either<Spacer, Spacer>) -> WidgetIdent {
+        either.framed_child_by_index::<HeadlessRenderer>(0).unwrap().ident
+    }
+
+    #[test]
+    fn layout() {
+        let root = headless_root(Either::<_, Spacer>::new_left(spacer(10, 10)), DimsBox::new2(100, 50));
+        let either = &root.root_widget;
+
+        assert!(either.is_left());
+        assert_eq!(BoundBox::new2(0, 0, 100, 50), either.left().unwrap().rect());
+        assert_eq!(DimsBox::new2(10, 10), either.size_bounds().min);
+        assert_eq!(1, either.num_children());
+    }
+
+    #[test]
+    fn switch_sides() {
+        let mut root = headless_root(Either::new_left(spacer(10, 10)), DimsBox::new2(100, 50));
+        assert_eq!(WidgetIdent::Num(0), active_ident(&root.root_widget));
+
+        root.root_widget.set_right(spacer(30, 20));
+        root.relayout();
+        let either = &root.root_widget;
+
+        // The new side gets a different ident, and gets laid out to fill the `Either`.
+        assert!(either.is_right());
+        assert!(either.left().is_none());
+        assert_eq!(WidgetIdent::Num(1), active_ident(either));
+        assert!(either.framed_child::<HeadlessRenderer>(WidgetIdent::Num(0)).is_none());
+        assert_eq!(BoundBox::new2(0, 0, 100, 50), either.right().unwrap().rect());
+        assert_eq!(DimsBox::new2(30, 20), either.size_bounds().min);
+
+        root.root_widget.set_left(spacer(10, 10));
+        root.relayout();
+        assert_eq!(WidgetIdent::Num(0), active_ident(&root.root_widget));
+        assert_eq!(BoundBox::new2(0, 0, 100, 50), root.root_widget.left().unwrap().rect());
+    }
+
+    #[test]
+    fn events_reach_active_side() {
+        let (left_clicks, right_clicks) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(0)));
+        let button = |clicks: &Rc<Cell<u32>>| Button::new(Contents::Text("Button".to_string()), Counter(clicks.clone()));
+
+        let mut root = headless_root(Either::new_left(button(&left_clicks)), DimsBox::new2(100, 50));
+        click(&mut root, Point2::new(10, 10));
+        assert_eq!((1, 0), (left_clicks.get(), right_clicks.get()));
+
+        root.root_widget.set_right(button(&right_clicks));
+        root.relayout();
+        root.redraw();
+        click(&mut root, Point2::new(10, 10));
+        assert_eq!((1, 1), (left_clicks.get(), right_clicks.get()));
+    }
+}
@@ -0,0 +1,578 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use derin_core::{
+    LoopFlow,
+    event::{EventOps, WidgetEventSourced, InputState},
+    widget::{WidgetIdent, WidgetRenderable, WidgetTag, WidgetInfo, WidgetInfoMut, WidgetSubtype, Widget, Parent},
+    render::{Renderer, SubFrame, WidgetTheme},
+};
+use crate::{
+    container::WidgetContainer,
+    layout::{self, Align2, GridSize, Margins, Track, WidgetPos, WidgetSpan},
+    widgets::GroupTheme,
+};
+
+use cgmath_geometry::{D2, rect::{BoundBox, DimsBox, GeoBox}};
+use derin_common_types::layout::{SizeBounds, Tr};
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use derin_layout_engine::{GridEngine, UpdateHeapCache, SolveError};
+
+/// A single widget placed within a [`Grid`], along with the cells it covers.
+///
+/// [`Grid`]: ./struct.Grid.html
+#[derive(Debug, Clone)]
+pub struct GridCell<W> {
+    pub widget: W,
+    pub col_span: Tr,
+    pub row_span: Tr,
+    pub place_in_cell: Align2,
+    pub margins: Margins<i32>,
+}
+
+/// The children of a [`Grid`], keyed by the `(column, row)` of their top-left cell.
+///
+/// Each child is identified with `WidgetIdent::NumCollection(column, row)`.
+///
+/// [`Grid`]: ./struct.Grid.html
+#[derive(Debug, Clone)]
+pub struct GridChildren<W> {
+    cells: BTreeMap<(Tr, Tr), GridCell<W>>,
+}
+
+/// A grid of widgets, with explicitly sized rows and columns.
+///
+/// Unlike [`Group`], which requires a [`GridLayout`] implementation to place its children, `Grid`
+/// places each child at the position it was inserted at. All children must be the same type; use
+/// an `enum` or a `Box` to place different kinds of widgets within the same grid.
+///
+/// The number of rows and columns is set by the row and column definitions. Children placed
+/// outside of those bounds aren't displayed.
+///
+/// [`Group`]: ./struct.Group.html
+/// [`GridLayout`]: ../layout/trait.GridLayout.html
+#[derive(Debug, Clone)]
+pub struct Grid<W> {
+    widget_tag: WidgetTag,
+    bounds: BoundBox<D2, i32>,
+    layout_engine: GridEngine,
+    /// The size taken up by percentage tracks in the last layout.
+    percent_dims: DimsBox<D2, i32>,
+    columns: Vec<Track>,
+    rows: Vec<Track>,
+    children: GridChildren<W>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GridTheme(());
+
+impl<W> GridCell<W> {
+    /// Create a cell covering `col_span` columns and `row_span` rows. The widget is stretched to
+    /// fill the cell, with no margins.
+    pub fn new(widget: W, col_span: Tr, row_span: Tr) -> GridCell<W> {
+        GridCell {
+            widget,
+            col_span,
+            row_span,
+            place_in_cell: Align2::default(),
+            margins: Margins::default(),
+        }
+    }
+}
+
+impl<W> GridChildren<W> {
+    fn new() -> GridChildren<W> {
+        GridChildren {
+            cells: BTreeMap::new(),
+        }
+    }
+
+    /// Iterate over the positions and cells of the children, ordered by row and then by column.
+    pub fn iter(&self) -> impl Iterator<Item=((Tr, Tr), &GridCell<W>)> {
+        self.cells.iter().map(|(&(row, col), cell)| ((col, row), cell))
+    }
+}
+
+impl<W> Grid<W> {
+    /// Create an empty grid with no rows or columns.
+    pub fn new() -> Grid<W> {
+        Grid {
+            widget_tag: WidgetTag::new(),
+            bounds: BoundBox::new2(0, 0, 0, 0),
+            layout_engine: GridEngine::new(),
+            percent_dims: DimsBox::new2(0, 0),
+            columns: Vec::new(),
+            rows: Vec::new(),
+            children: GridChildren::new(),
+        }
+    }
+
+    /// Add a column to the right side of the grid.
+    pub fn column(mut self, size: Track) -> Grid<W> {
+        self.columns.push(size);
+        self
+    }
+
+    /// Add a row to the bottom of the grid.
+    pub fn row(mut self, size: Track) -> Grid<W> {
+        self.rows.push(size);
+        self
+    }
+
+    /// Place a widget in the grid, covering the given span of cells.
+    pub fn child(mut self, col: Tr, row: Tr, col_span: Tr, row_span: Tr, widget: W) -> Grid<W> {
+        self.insert(col, row, GridCell::new(widget, col_span, row_span));
+        self
+    }
+
+    /// Retrieves the column definitions.
+    pub fn columns(&self) -> &[Track] {
+        &self.columns
+    }
+
+    /// Retrieves the column definitions, for mutation.
+    ///
+    /// Calling this function forces the grid to be re-laid out, so you're encouraged to only call
+    /// it if you're actually changing the columns.
+    pub fn columns_mut(&mut self) -> &mut Vec<Track> {
+        self.widget_tag.request_relayout();
+        &mut self.columns
+    }
+
+    /// Retrieves the row definitions.
+    pub fn rows(&self) -> &[Track] {
+        &self.rows
+    }
+
+    /// Retrieves the row definitions, for mutation.
+    ///
+    /// Calling this function forces the grid to be re-laid out, so you're encouraged to only call
+    /// it if you're actually changing the rows.
+    pub fn rows_mut(&mut self) -> &mut Vec<Track> {
+        self.widget_tag.request_relayout();
+        &mut self.rows
+    }
+
+    /// Retrieves the children of the grid.
+    pub fn children(&self) -> &GridChildren<W> {
+        &self.children
+    }
+
+    /// Place a cell in the grid with its top-left corner at `(col, row)`, returning the cell that
+    /// was previously at that position.
+    pub fn insert(&mut self, col: Tr, row: Tr, cell: GridCell<W>) -> Option<GridCell<W>> {
+        self.widget_tag.request_relayout();
+        self.children.cells.insert((row, col), cell)
+    }
+
+    /// Remove the cell with its top-left corner at `(col, row)`.
+    pub fn remove(&mut self, col: Tr, row: Tr) -> Option<GridCell<W>> {
+        let cell = self.children.cells.remove(&(row, col));
+        if cell.is_some() {
+            self.widget_tag.request_relayout();
+        }
+        cell
+    }
+
+    /// Retrieves the cell with its top-left corner at `(col, row)`.
+    pub fn get(&self, col: Tr, row: Tr) -> Option<&GridCell<W>> {
+        self.children.cells.get(&(row, col))
+    }
+
+    /// Retrieves the cell with its top-left corner at `(col, row)`, for mutation.
+    ///
+    /// Calling this function forces the grid to be re-laid out, so you're encouraged to only call
+    /// it if you're actually changing the cell.
+    pub fn get_mut(&mut self, col: Tr, row: Tr) -> Option<&mut GridCell<W>> {
+        let cell = self.children.cells.get_mut(&(row, col));
+        if cell.is_some() {
+            self.widget_tag.request_relayout();
+        }
+        cell
+    }
+}
+
+impl<W> Default for Grid<W> {
+    fn default() -> Grid<W> {
+        Grid::new()
+    }
+}
+
+impl<S, W> WidgetContainer<S> for GridChildren<W>
+    where S: WidgetSubtype<W> + ?Sized,
+          W: Widget
+{
+    #[inline(always)]
+    fn num_children(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn framed_children<'a, R, G>(&'a self, mut for_each_child: G)
+            where G: FnMut(WidgetInfo<'a, R, S>) -> LoopFlow,
+                  R: Renderer
+    {
+        for (index, (&(row, col), cell)) in self.cells.iter().enumerate() {
+            match for_each_child(WidgetInfo::new(WidgetIdent::NumCollection(col, row), index, &cell.widget)) {
+                LoopFlow::Continue => (),
+                LoopFlow::Break => return
+            }
+        }
+    }
+
+    fn framed_children_mut<'a, R, G>(&'a mut self, mut for_each_child: G)
+            where G: FnMut(WidgetInfoMut<'a, R, S>) -> LoopFlow,
+                  R: Renderer
+    {
+        for (index, (&(row, col), cell)) in self.cells.iter_mut().enumerate() {
+            match for_each_child(WidgetInfoMut::new(WidgetIdent::NumCollection(col, row), index, &mut cell.widget)) {
+                LoopFlow::Continue => (),
+                LoopFlow::Break => return
+            }
+        }
+    }
+
+    fn framed_child<R>(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, R, S>>
+        where R: Renderer
+    {
+        match widget_ident {
+            WidgetIdent::NumCollection(col, row) => {
+                let index = self.cells.range(..(row, col)).count();
+                self.cells.get(&(row, col))
+                    .map(|cell| WidgetInfo::new(widget_ident, index, &cell.widget))
+            },
+            _ => None
+        }
+    }
+
+    fn framed_child_mut<R>(&mut self, widget_ident: WidgetIdent) -> Option<WidgetInfoMut<'_, R, S>>
+        where R: Renderer
+    {
+        match widget_ident {
+            WidgetIdent::NumCollection(col, row) => {
+                let index = self.cells.range(..(row, col)).count();
+                self.cells.get_mut(&(row, col))
+                    .map(|cell| WidgetInfoMut::new(widget_ident, index, &mut cell.widget))
+            },
+            _ => None
+        }
+    }
+}
+
+impl<W> Widget for Grid<W>
+    where W: Widget
+{
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.bounds
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        self.widget_tag.request_relayout();
+        &mut self.bounds
+    }
+    fn size_bounds(&self) -> SizeBounds {
+        layout::without_percent_tracks(self.layout_engine.actual_size_bounds(), self.percent_dims)
+    }
+
+    #[inline]
+    fn on_widget_event(&mut self, _: WidgetEventSourced, _: InputState) -> EventOps {
+        EventOps {
+            focus: None,
+            bubble: true,
+            handled: false,
+        }
+    }
+}
+
+impl<W> Parent for Grid<W>
+    where W: Widget
+{
+    fn num_children(&self) -> usize {
+        WidgetContainer::<dyn Widget>::num_children(&self.children)
+    }
+
+    fn framed_child<R: Renderer>(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, R>> {
+        WidgetContainer::<dyn Widget>::framed_child(&self.children, widget_ident)
+    }
+    fn framed_child_mut<R: Renderer>(&mut self, widget_ident: WidgetIdent) -> Option<WidgetInfoMut<'_, R>> {
+        WidgetContainer::<dyn Widget>::framed_child_mut(&mut self.children, widget_ident)
+    }
+
+    fn framed_children<'a, R, G>(&'a self, for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfo<'a, R>) -> LoopFlow
+    {
+        WidgetContainer::<dyn Widget>::framed_children(&self.children, for_each)
+    }
+
+    fn framed_children_mut<'a, R, G>(&'a mut self, for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfoMut<'a, R>) -> LoopFlow
+    {
+        WidgetContainer::<dyn Widget>::framed_children_mut(&mut self.children, for_each)
+    }
+
+    fn framed_child_by_index<R: Renderer>(&self, index: usize) -> Option<WidgetInfo<'_, R>> {
+        WidgetContainer::<dyn Widget>::framed_child_by_index(&self.children, index)
+    }
+    fn framed_child_by_index_mut<R: Renderer>(&mut self, index: usize) -> Option<WidgetInfoMut<'_, R>> {
+        WidgetContainer::<dyn Widget>::framed_child_by_index_mut(&mut self.children, index)
+    }
+}
+
+impl<R, W> WidgetRenderable<R> for Grid<W>
+    where R: Renderer,
+          W: Widget
+{
+    type Theme = GridTheme;
+
+    fn theme(&self) -> GridTheme {
+        GridTheme(())
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, _: &mut R::Layout) {
+        #[derive(Default)]
+        struct HeapCache {
+            update_heap_cache: UpdateHeapCache,
+            hints_vec: Vec<WidgetPos>,
+            rects_vec: Vec<Result<BoundBox<D2, i32>, SolveError>>
+        }
+        thread_local! {
+            static HEAP_CACHE: RefCell<HeapCache> = RefCell::new(HeapCache::default());
+        }
+
+        HEAP_CACHE.with(|hc| {
+            let mut hc = hc.borrow_mut();
+
+            let HeapCache {
+                ref mut update_heap_cache,
+                ref mut hints_vec,
+                ref mut rects_vec
+            } = *hc;
+
+            let grid_size = GridSize::new(self.columns.len() as Tr, self.rows.len() as Tr);
+            self.layout_engine.set_grid_size(grid_size);
+            let (columns, rows) = (&self.columns, &self.rows);
+            self.percent_dims = layout::set_track_hints(
+                &mut self.layout_engine,
+                |col| columns[col as usize],
+                |row| rows[row as usize],
+                DimsBox::new2(self.bounds.width(), self.bounds.height()),
+            );
+
+            for (&(row, col), cell) in self.children.cells.iter() {
+                let widget_size_bounds = cell.widget.size_bounds();
+                let layout_hints = WidgetPos {
+                    size_bounds: widget_size_bounds,
+                    preferred_size: Some(cell.widget.min_intrinsic_size())
+                        .filter(|size| *size != widget_size_bounds.min)
+                        .map(|size| widget_size_bounds.clamp_dims(size)),
+                    widget_span: WidgetSpan::new(col..col + cell.col_span, row..row + cell.row_span),
+                    place_in_cell: cell.place_in_cell,
+                    margins: cell.margins,
+                };
+
+                hints_vec.push(layout_hints);
+                rects_vec.push(Ok(BoundBox::new2(0, 0, 0, 0)));
+            }
+
+            self.layout_engine.desired_size = DimsBox::new2(self.bounds.width(), self.bounds.height());
+            self.layout_engine.update_engine(hints_vec, rects_vec, update_heap_cache);
+
+            let mut rects_iter = rects_vec.drain(..);
+            for cell in self.children.cells.values_mut() {
+                match rects_iter.next() {
+                    Some(rect) => *cell.widget.rect_mut() = rect.unwrap_or(BoundBox::new2(0xDEDBEEF, 0xDEDBEEF, 0xDEDBEEF, 0xDEDBEEF)),
+                    None => break
+                }
+            }
+
+            hints_vec.clear();
+        })
+    }
+}
+
+impl WidgetTheme for GridTheme {
+    type Fallback = GroupTheme;
+    fn fallback(self) -> Option<GroupTheme> {
+        Some(GroupTheme::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::render::headless::HeadlessRenderer,
+        test_helpers::headless_root,
+        widgets::{Spacer, SpacerTheme},
+    };
+
+    /// A spacer that would like to be `intrinsic` large.
+    struct Intrinsic {
+        spacer: Spacer,
+        intrinsic: DimsBox<D2, i32>,
+    }
+
+    impl Intrinsic {
+        fn new(size_bounds: SizeBounds, intrinsic: DimsBox<D2, i32>) -> Intrinsic {
+            Intrinsic {
+                spacer: Spacer::new(size_bounds),
+                intrinsic,
+            }
+        }
+
+        fn flex() -> Intrinsic {
+            Intrinsic::new(SizeBounds::default(), DimsBox::new2(0, 0))
+        }
+    }
+
+    impl Widget for Intrinsic {
+        fn widget_tag(&self) -> &WidgetTag {
+            self.spacer.widget_tag()
+        }
+
+        fn rect(&self) -> BoundBox<D2, i32> {
+            self.spacer.rect()
+        }
+
+        fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+            self.spacer.rect_mut()
+        }
+
+        fn size_bounds(&self) -> SizeBounds {
+            self.spacer.size_bounds()
+        }
+
+        fn min_intrinsic_size(&self) -> DimsBox<D2, i32> {
+            self.intrinsic
+        }
+
+        fn on_widget_event(&mut self, event: WidgetEventSourced, input_state: InputState) -> EventOps {
+            self.spacer.on_widget_event(event, input_state)
+        }
+    }
+
+    impl<R: Renderer> WidgetRenderable<R> for Intrinsic {
+        type Theme = SpacerTheme;
+
+        fn theme(&self) -> SpacerTheme {
+            SpacerTheme::default()
+        }
+
+        fn render(&mut self, _: &mut R::SubFrame) {}
+    }
+
+    fn cell_rect(grid: &Grid<Intrinsic>, col: Tr, row: Tr) -> BoundBox<D2, i32> {
+        grid.get(col, row).unwrap().widget.rect()
+    }
+
+    #[test]
+    fn fixed_auto_fraction() {
+        let min_width = Intrinsic::new(SizeBounds::new_min(DimsBox::new2(30, 10)), DimsBox::new2(30, 10));
+        let grid = Grid::new()
+            .column(Track::Pixels(50))
+            .column(Track::Auto)
+            .column(Track::Fraction(1.0))
+            .row(Track::Fraction(1.0))
+            .child(0, 0, 1, 1, Intrinsic::flex())
+            .child(1, 0, 1, 1, min_width)
+            .child(2, 0, 1, 1, Intrinsic::flex());
+        let root = headless_root(grid, DimsBox::new2(200, 100));
+        let grid = &root.root_widget;
+
+        assert_eq!(BoundBox::new2(0, 0, 50, 100), cell_rect(grid, 0, 0));
+        assert_eq!(BoundBox::new2(50, 0, 80, 100), cell_rect(grid, 1, 0));
+        assert_eq!(BoundBox::new2(80, 0, 200, 100), cell_rect(grid, 2, 0));
+        assert_eq!(DimsBox::new2(80, 10), grid.size_bounds().min);
+    }
+
+    #[test]
+    fn percent_tracks() {
+        let grid = Grid::new()
+            .column(Track::Percent(25.0))
+            .column(Track::Fraction(1.0))
+            .row(Track::Fraction(1.0))
+            .child(0, 0, 1, 1, Intrinsic::new(SizeBounds::new_min(DimsBox::new2(20, 0)), DimsBox::new2(20, 0)))
+            .child(1, 0, 1, 1, Intrinsic::flex());
+        let mut root = headless_root(grid, DimsBox::new2(200, 40));
+        assert_eq!(BoundBox::new2(0, 0, 50, 40), cell_rect(&root.root_widget, 0, 0));
+        assert_eq!(BoundBox::new2(50, 0, 200, 40), cell_rect(&root.root_widget, 1, 0));
+        // Percentage tracks are rigid once resolved, but they shouldn't keep the grid from shrinking.
+        assert_eq!(DimsBox::new2(0, 0), root.root_widget.size_bounds().min);
+
+        // Percentages get resolved against the grid's new size when it's resized.
+        *root.root_widget.rect_mut() = BoundBox::new2(0, 0, 400, 40);
+        root.relayout();
+        assert_eq!(BoundBox::new2(0, 0, 100, 40), cell_rect(&root.root_widget, 0, 0));
+        assert_eq!(BoundBox::new2(100, 0, 400, 40), cell_rect(&root.root_widget, 1, 0));
+    }
+
+    #[test]
+    fn preferred_size() {
+        let intrinsic = |max_width| Intrinsic::new(
+            SizeBounds::new(DimsBox::new2(0, 0), DimsBox::new2(max_width, i32::max_value())),
+            DimsBox::new2(150, 0),
+        );
+        let new_grid = |widget| Grid::new()
+            .column(Track::Fraction(1.0))
+            .column(Track::Fraction(1.0))
+            .row(Track::Fraction(1.0))
+            .child(0, 0, 1, 1, widget)
+            .child(1, 0, 1, 1, Intrinsic::flex());
+
+        // There's room for the widget's preferred size, so the first column grows to fit it.
+        let root = headless_root(new_grid(intrinsic(160)), DimsBox::new2(200, 50));
+        assert_eq!(BoundBox::new2(0, 0, 150, 50), cell_rect(&root.root_widget, 0, 0));
+        assert_eq!(BoundBox::new2(150, 0, 200, 50), cell_rect(&root.root_widget, 1, 0));
+
+        // The preferred size is larger than the widget can get, so it's clamped to the maximum.
+        let root = headless_root(new_grid(intrinsic(120)), DimsBox::new2(200, 50));
+        assert_eq!(BoundBox::new2(0, 0, 120, 50), cell_rect(&root.root_widget, 0, 0));
+        assert_eq!(BoundBox::new2(120, 0, 200, 50), cell_rect(&root.root_widget, 1, 0));
+
+        // Preferred sizes don't raise the grid's minimum size.
+        assert_eq!(DimsBox::new2(0, 0), root.root_widget.size_bounds().min);
+    }
+
+    #[test]
+    fn insert_remove() {
+        let grid = Grid::new()
+            .column(Track::Fraction(1.0))
+            .column(Track::Fraction(1.0))
+            .row(Track::Fraction(1.0))
+            .child(0, 0, 1, 1, Intrinsic::flex());
+        let mut root = headless_root(grid, DimsBox::new2(100, 40));
+        assert_eq!(BoundBox::new2(0, 0, 50, 40), cell_rect(&root.root_widget, 0, 0));
+
+        assert!(root.root_widget.insert(1, 0, GridCell::new(Intrinsic::flex(), 1, 1)).is_none());
+        root.redraw();
+        root.relayout();
+        let ident = WidgetIdent::NumCollection(1, 0);
+        assert_eq!(2, root.root_widget.num_children());
+        assert_eq!(Some(1), root.root_widget.framed_child::<HeadlessRenderer>(ident.clone()).map(|c| c.index));
+        assert_eq!(BoundBox::new2(50, 0, 100, 40), cell_rect(&root.root_widget, 1, 0));
+
+        assert!(root.root_widget.remove(0, 0).is_some());
+        assert!(root.root_widget.remove(0, 0).is_none());
+        root.redraw();
+        root.relayout();
+        assert_eq!(1, root.root_widget.num_children());
+        assert_eq!(Some(0), root.root_widget.framed_child::<HeadlessRenderer>(ident).map(|c| c.index));
+        assert_eq!(BoundBox::new2(50, 0, 100, 40), cell_rect(&root.root_widget, 1, 0));
+    }
+}
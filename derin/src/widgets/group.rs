@@ -6,11 +6,11 @@ use derin_core::{
     LoopFlow,
     event::{EventOps, WidgetEventSourced, InputState},
     widget::{WidgetIdent, WidgetRenderable, WidgetTag, WidgetInfo, WidgetInfoMut, Widget, Parent},
-    render::{Renderer, SubFrame, WidgetTheme},
+    render::{ClipMask, Renderer, SubFrame, WidgetTheme},
 };
 use crate::{
-    container::WidgetContainer,
-    layout::GridLayout,
+    container::{ReplaceChild, WidgetContainer},
+    layout::{self, GridLayout},
 };
 
 use cgmath_geometry::{D2, rect::{BoundBox, DimsBox, GeoBox}};
@@ -24,6 +24,9 @@ use derin_layout_engine::{GridEngine, UpdateHeapCache, SolveError};
 ///
 /// Children of the group are specified by creating structs which implement [`WidgetContainer`].
 /// You're encouraged to use the `derive` macro in `derin_macros` to do so.
+///
+/// Setting a [corner radius](#method.set_corner_radius) rounds off the group's corners, clipping
+/// both the theme's background and the group's children to the rounded shape.
 #[derive(Debug, Clone)]
 pub struct Group<C, L>
     where L: GridLayout
@@ -31,6 +34,9 @@ pub struct Group<C, L>
     widget_tag: WidgetTag,
     bounds: BoundBox<D2, i32>,
     layout_engine: GridEngine,
+    /// The size taken up by percentage tracks in the last layout.
+    percent_dims: DimsBox<D2, i32>,
+    corner_radius: i32,
     container: C,
     layout: L
 }
@@ -48,6 +54,8 @@ impl<C, L> Group<C, L>
             widget_tag: WidgetTag::new(),
             bounds: BoundBox::new2(0, 0, 0, 0),
             layout_engine: GridEngine::new(),
+            percent_dims: DimsBox::new2(0, 0),
+            corner_radius: 0,
             container, layout
         }
     }
@@ -61,6 +69,43 @@ impl<C, L> Group<C, L>
     pub fn container_mut(&mut self) -> &mut C {
         &mut self.container
     }
+
+    /// Retrieve the radius the group's corners are rounded by, in pixels.
+    #[inline]
+    pub fn corner_radius(&self) -> i32 {
+        self.corner_radius
+    }
+
+    /// Round the group's corners by `corner_radius` pixels. `0`, the default, leaves the corners
+    /// square.
+    ///
+    /// The group's background and children get clipped to the rounded rectangle, so children
+    /// placed in the corners don't poke out from behind the rounded background. If the renderer
+    /// doesn't support rounded clipping, the group falls back to being clipped to its rectangle.
+    pub fn set_corner_radius(&mut self, corner_radius: i32) {
+        let corner_radius = corner_radius.max(0);
+        if corner_radius != self.corner_radius {
+            self.corner_radius = corner_radius;
+            self.widget_tag.request_redraw();
+        }
+    }
+
+    /// Replace the child at `index` with `widget`, keeping its place in the layout. The group is
+    /// re-laid out if the child was replaced.
+    ///
+    /// See [`ReplaceChild`] for details.
+    ///
+    /// [`ReplaceChild`]: ../container/trait.ReplaceChild.html
+    pub fn replace_child<W>(&mut self, index: usize, widget: W) -> Result<W, W>
+        where C: ReplaceChild<W>,
+              W: Widget
+    {
+        let result = self.container.replace_child(index, widget);
+        if result.is_ok() {
+            self.widget_tag.request_relayout();
+        }
+        result
+    }
 }
 
 impl<C, L> Widget for Group<C, L>
@@ -83,7 +128,7 @@ impl<C, L> Widget for Group<C, L>
         &mut self.bounds
     }
     fn size_bounds(&self) -> SizeBounds {
-        self.layout_engine.actual_size_bounds()
+        layout::without_percent_tracks(self.layout_engine.actual_size_bounds(), self.percent_dims)
     }
 
     #[inline]
@@ -92,6 +137,7 @@ impl<C, L> Widget for Group<C, L>
         EventOps {
             focus: None,
             bubble: true,
+            handled: false,
         }
     }
 }
@@ -145,6 +191,16 @@ impl<R, C, L> WidgetRenderable<R> for Group<C, L>
     }
 
     fn render(&mut self, frame: &mut R::SubFrame) {
+        if self.corner_radius > 0 {
+            // The mask is pushed before the background gets drawn, so that the background gets
+            // rounded along with the children. The mask stays pushed until the children have
+            // been rendered. If the renderer can't do rounded masks, the normal rectangular
+            // clipping is all we get.
+            let _ = frame.push_clip_mask(ClipMask::RoundedRect {
+                rect: BoundBox::new2(0, 0, self.bounds.width(), self.bounds.height()),
+                radius: self.corner_radius,
+            });
+        }
         frame.render_laid_out_content();
     }
 
@@ -174,9 +230,13 @@ impl<R, C, L> WidgetRenderable<R> for Group<C, L>
                 let mut layout_hints = self.layout.positions(summary.ident, summary.index, num_children).unwrap_or(WidgetPos::default());
 
                 layout_hints.size_bounds = SizeBounds {
-                    min: layout_hints.size_bounds.bound_rect(widget_size_bounds.min),
-                    max: layout_hints.size_bounds.bound_rect(widget_size_bounds.max),
+                    min: layout_hints.size_bounds.clamp_dims(widget_size_bounds.min),
+                    max: layout_hints.size_bounds.clamp_dims(widget_size_bounds.max),
                 };
+                let min_intrinsic_size = summary.widget().min_intrinsic_size();
+                if min_intrinsic_size != widget_size_bounds.min {
+                    layout_hints.preferred_size = Some(layout_hints.size_bounds.clamp_dims(min_intrinsic_size));
+                }
                 hints_vec.push(layout_hints);
                 rects_vec.push(Ok(BoundBox::new2(0, 0, 0, 0)));
                 LoopFlow::Continue
@@ -184,6 +244,13 @@ impl<R, C, L> WidgetRenderable<R> for Group<C, L>
 
             self.layout_engine.desired_size = DimsBox::new2(self.bounds.width(), self.bounds.height());
             self.layout_engine.set_grid_size(self.layout.grid_size(num_children));
+            let layout = &self.layout;
+            self.percent_dims = layout::set_track_hints(
+                &mut self.layout_engine,
+                |col| layout.col_track(col, num_children),
+                |row| layout.row_track(row, num_children),
+                DimsBox::new2(self.bounds.width(), self.bounds.height()),
+            );
             self.layout_engine.update_engine(hints_vec, rects_vec, update_heap_cache);
 
             let mut rects_iter = rects_vec.drain(..);
@@ -206,3 +273,112 @@ impl WidgetTheme for GroupTheme {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cgmath::Point2,
+        layout::{GridSize, Margins, LayoutHorizontal, Track, WidgetSpan},
+        test_helpers::{click, headless_root},
+        widgets::{Button, ButtonHandler, Contents, Spacer},
+    };
+    use derin_common_types::layout::Tr;
+    use std::{cell::Cell, rc::Rc};
+
+    struct Counter(Rc<Cell<u32>>);
+
+    impl ButtonHandler for Counter {
+        fn on_click(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    fn button(text: &str, clicks: &Rc<Cell<u32>>) -> Button<Counter> {
+        Button::new(Contents::Text(text.to_string()), Counter(clicks.clone()))
+    }
+
+    /// Lays widgets out in a row, with the first taking up a quarter of the group's width.
+    struct Sidebar;
+
+    impl GridLayout for Sidebar {
+        fn positions(&self, _: WidgetIdent, widget_index: usize, _: usize) -> Option<WidgetPos> {
+            Some(WidgetPos {
+                widget_span: WidgetSpan::new(widget_index as Tr, 0),
+                ..WidgetPos::default()
+            })
+        }
+
+        fn grid_size(&self, num_widgets: usize) -> GridSize {
+            GridSize::new(num_widgets as Tr, 1)
+        }
+
+        fn col_track(&self, col: Tr, _: usize) -> Track {
+            match col {
+                0 => Track::Percent(25.0),
+                _ => Track::Fraction(1.0),
+            }
+        }
+    }
+
+    #[test]
+    fn layout_tracks() {
+        let group = Group::new(vec![Spacer::flex(), Spacer::flex(), Spacer::flex()], Sidebar);
+        let root = headless_root(group, DimsBox::new2(200, 40));
+        let rects = root.root_widget.container().iter().map(|s| s.rect()).collect::<Vec<_>>();
+        assert_eq!(
+            vec![BoundBox::new2(0, 0, 50, 40), BoundBox::new2(50, 0, 125, 40), BoundBox::new2(125, 0, 200, 40)],
+            rects
+        );
+    }
+
+    #[test]
+    fn corner_radius_clips_children() {
+        let group = Group::new(vec![Spacer::flex()], LayoutHorizontal::new(Margins::new(0, 0, 0, 0), Default::default()));
+        let mut root = headless_root(group, DimsBox::new2(32, 32));
+
+        // The spacer fills the group, so its outline gets drawn along the group's edges.
+        let image = root.render_to_image();
+        assert_eq!(Some([255; 4]), image.pixel(0, 0));
+        assert_eq!(Some([255; 4]), image.pixel(31, 31));
+
+        root.root_widget.set_corner_radius(8);
+        let image = root.render_to_image();
+        assert_eq!(Some([0; 4]), image.pixel(0, 0));
+        assert_eq!(Some([0; 4]), image.pixel(1, 1));
+        assert_eq!(Some([0; 4]), image.pixel(31, 31));
+        assert_eq!(Some([255; 4]), image.pixel(0, 16));
+        assert_eq!(Some([255; 4]), image.pixel(16, 0));
+        assert_eq!(Some([0; 4]), image.pixel(16, 16));
+    }
+
+    #[test]
+    fn replace_child() {
+        let (old_clicks, new_clicks, other_clicks) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(0)), Rc::new(Cell::new(0)));
+        let group = Group::new(
+            vec![button("Old", &old_clicks), button("Other", &other_clicks)],
+            LayoutHorizontal::new(Margins::new(0, 0, 0, 0), Default::default())
+        );
+        let mut root = headless_root(group, DimsBox::new2(200, 24));
+        let old_id = root.root_widget.container()[0].widget_id();
+        let old_rect = root.root_widget.container()[0].rect();
+        assert!(root.widget_path_key(old_id).is_some());
+
+        let old = root.root_widget.replace_child(0, button("New", &new_clicks)).ok().unwrap();
+        drop(old);
+        root.relayout();
+
+        // The old button's gone from the tree, and the new one's taken its place.
+        let new_id = root.root_widget.container()[0].widget_id();
+        assert!(root.widget_path_key(old_id).is_none());
+        assert!(root.widget_path_key(new_id).is_some());
+        assert_eq!(old_rect, root.root_widget.container()[0].rect());
+
+        click(&mut root, old_rect.center());
+        assert_eq!((0, 1, 0), (old_clicks.get(), new_clicks.get(), other_clicks.get()));
+
+        // Replacing a child that doesn't exist hands the widget back.
+        assert!(root.root_widget.replace_child(2, button("Missing", &new_clicks)).is_err());
+        assert_eq!(2, root.root_widget.container().len());
+    }
+}
@@ -3,29 +3,63 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use derin_core::{
-    event::{EventOps, WidgetEventSourced, InputState},
+    accessibility::{AccessibilityNode, AccessibilityRole},
+    event::{EventOps, FocusChange, WidgetEvent, WidgetEventSourced, InputState},
     widget::{WidgetTag, WidgetRenderable, Widget},
-    render::{Renderer, RendererLayout, SubFrame, WidgetTheme},
+    render::{HitMask, Renderer, RendererLayout, SubFrame, TextLayoutResult, WidgetTheme, WrapMode},
+};
+use crate::widgets::{
+    Contents,
+    assistants::{
+        mnemonic::{parse_mnemonic, mnemonic_char},
+        text_edit::{TextEditAssist, TextEditOps},
+    },
 };
-use crate::widgets::Contents;
 
-use cgmath_geometry::{D2, rect::BoundBox};
+use cgmath_geometry::{D2, cgmath::Point2, rect::{BoundBox, DimsBox, GeoBox}};
 use derin_common_types::layout::SizeBounds;
 
+use std::mem;
+
 
 /// A simple, non-interactive label.
 ///
-/// Can display text or an image, depending on what's in `contents`.
+/// Can display text or an image, depending on what's in `contents`. Text labels can be made
+/// selectable with [`set_selectable`], which lets the user highlight and copy the text without
+/// being able to edit it.
+///
+/// Labels created with [`with_mnemonic`] move keyboard focus to the widget after them when `Alt`
+/// and the mnemonic character are pressed, so they can provide access keys for unlabeled controls
+/// such as edit boxes.
+///
+/// Text only gets broken at explicit line breaks by default. Use [`set_wrap_mode`] to wrap long
+/// lines at the edge of the label instead.
+///
+/// [`set_selectable`]: ./struct.Label.html#method.set_selectable
+/// [`with_mnemonic`]: ./struct.Label.html#method.with_mnemonic
+/// [`set_wrap_mode`]: ./struct.Label.html#method.set_wrap_mode
 #[derive(Debug, Clone)]
 pub struct Label {
     widget_tag: WidgetTag,
     bounds: BoundBox<D2, i32>,
     contents: Contents,
+    /// The byte index of the mnemonic character within the text contents.
+    mnemonic: Option<usize>,
     size_bounds: SizeBounds,
+    selectable: bool,
+    wrap_mode: WrapMode,
+    hit_mask: Option<HitMask>,
+    /// Selection state for selectable labels. The string itself stays in `contents`, and is only
+    /// swapped in while the assist is handling an event.
+    edit: TextEditAssist,
 }
 
 #[derive(Debug, Clone, Default)]
-pub struct LabelTheme(());
+pub struct LabelTheme {
+    /// The byte index of the mnemonic character within the label's text, if the mnemonic should
+    /// be underlined.
+    pub mnemonic: Option<usize>,
+}
 
 impl WidgetTheme for LabelTheme {
     type Fallback = !;
@@ -39,10 +73,31 @@ impl Label {
             widget_tag: WidgetTag::new(),
             bounds: BoundBox::new2(0, 0, 0, 0),
             contents,
+            mnemonic: None,
             size_bounds: SizeBounds::default(),
+            selectable: false,
+            wrap_mode: WrapMode::None,
+            hit_mask: None,
+            edit: {
+                let mut edit = TextEditAssist::new(String::new());
+                edit.read_only = true;
+                edit
+            },
         }
     }
 
+    /// Create a new label displaying `text`, with the mnemonic marked by `&` (e.g. `"&Name:"`).
+    /// `"&&"` displays a literal `&`.
+    ///
+    /// The mnemonic character gets underlined, and becomes the label's access key.
+    pub fn with_mnemonic(text: &str) -> Label {
+        let (text, mnemonic) = parse_mnemonic(text);
+        let mut label = Label::new(Contents::Text(text));
+        label.mnemonic = mnemonic;
+        label.widget_tag.set_access_key(mnemonic_char(label.contents.as_text_ref().unwrap(), mnemonic));
+        label
+    }
+
     /// Retrieves the contents of the label.
     pub fn contents(&self) -> &Contents {
         &self.contents
@@ -51,14 +106,90 @@ impl Label {
     /// Retrieves the contents of the label, for mutation.
     ///
     /// Calling this function forces the label to be re-drawn, so you're discouraged from calling
-    /// it unless you're actually changing the contents.
+    /// it unless you're actually changing the contents. It also removes the label's mnemonic, if
+    /// it has one.
     pub fn contents_mut(&mut self) -> &mut Contents {
         self.widget_tag
             .request_redraw()
-            .request_relayout();
+            .request_relayout()
+            .set_access_key(None);
+        self.mnemonic = None;
 
         &mut self.contents
     }
+
+    /// Whether or not the label's text can be selected and copied.
+    pub fn selectable(&self) -> bool {
+        self.selectable
+    }
+
+    /// Set whether or not the label's text can be selected and copied. Selectable labels take
+    /// keyboard focus when clicked, so that `Ctrl+C` reaches them.
+    ///
+    /// This has no effect on labels displaying an icon.
+    pub fn set_selectable(&mut self, selectable: bool) {
+        if self.selectable != selectable {
+            self.selectable = selectable;
+            self.widget_tag
+                .request_redraw()
+                .request_relayout();
+        }
+    }
+
+    /// How lines that are wider than the label get broken.
+    #[inline]
+    pub fn wrap_mode(&self) -> WrapMode {
+        self.wrap_mode
+    }
+
+    /// Set how lines that are wider than the label get broken. Defaults to `WrapMode::None`, which
+    /// only breaks lines where the text contains line breaks.
+    pub fn set_wrap_mode(&mut self, wrap_mode: WrapMode) {
+        if self.wrap_mode != wrap_mode {
+            self.wrap_mode = wrap_mode;
+            self.widget_tag
+                .request_redraw()
+                .request_relayout();
+        }
+    }
+}
+
+impl Label {
+    /// Retrieves the positions of the label's glyphs from the last time it was laid out, for
+    /// mapping points to string indices and back.
+    ///
+    /// Returns `None` if the label isn't displaying text, hasn't been laid out yet, or if the
+    /// renderer doesn't track glyph positions.
+    pub fn text_layout(&self) -> Option<&TextLayoutResult> {
+        self.edit.text_layout.as_ref()
+    }
+
+    /// How far, in pixels, the cursor has to move while pressed before it starts selecting the
+    /// label's text. `None` means the default threshold is used.
+    pub fn drag_threshold(&self) -> Option<i32> {
+        self.edit.drag_threshold
+    }
+
+    /// Set how far, in pixels along either axis, the cursor has to move while pressed before it
+    /// starts selecting the label's text. Pass `None` to use the default threshold.
+    pub fn set_drag_threshold(&mut self, drag_threshold: Option<i32>) {
+        self.edit.drag_threshold = drag_threshold;
+    }
+
+    /// The mask used to decide which parts of the label can be clicked on.
+    pub fn hit_mask(&self) -> Option<&HitMask> {
+        self.hit_mask.as_ref()
+    }
+
+    /// Only let the cursor hit the parts of the label that are opaque in `hit_mask`, which is
+    /// stretched to fill the label. Clicks elsewhere fall through to the widgets beneath.
+    ///
+    /// This is meant for labels displaying irregularly-shaped icons, with the mask created from
+    /// the icon's image. Pass `None` to make the entire label hittable again, which is the
+    /// default.
+    pub fn set_hit_mask(&mut self, hit_mask: Option<HitMask>) {
+        self.hit_mask = hit_mask;
+    }
 }
 
 impl Widget for Label {
@@ -81,11 +212,73 @@ impl Widget for Label {
         self.size_bounds
     }
 
+    fn on_update_layout(&mut self, old_rect: BoundBox<D2, i32>) {
+        // Wrapped text needs to be re-wrapped at the new width.
+        if self.wrap_mode != WrapMode::None && old_rect.width() != self.bounds.width() {
+            self.widget_tag.request_relayout();
+        }
+    }
+
     #[inline]
-    fn on_widget_event(&mut self, _: WidgetEventSourced, _: InputState) -> EventOps {
+    fn accepts_focus(&self) -> bool {
+        self.selectable
+    }
+
+    fn hit_test(&self, point: Point2<i32>) -> bool {
+        match self.hit_mask {
+            Some(ref hit_mask) => hit_mask.hit(point, DimsBox::new2(self.bounds.width(), self.bounds.height())),
+            None => true
+        }
+    }
+
+    fn accessibility_info(&self) -> Option<AccessibilityNode> {
+        Some(AccessibilityNode {
+            label: self.contents.plain_text(),
+            ..AccessibilityNode::new(AccessibilityRole::Text)
+        })
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, input_state: InputState) -> EventOps {
+        let event = event.unwrap();
+
+        if let WidgetEvent::AccessKey = event {
+            return EventOps {
+                focus: Some(FocusChange::Next),
+                bubble: false,
+                handled: true,
+            };
+        }
+
+        let string = match (self.selectable, &mut self.contents) {
+            (true, Contents::Text(ref mut string)) => string,
+            _ => return EventOps {
+                focus: None,
+                bubble: event.default_bubble(),
+                handled: false,
+            }
+        };
+
+        mem::swap(string, &mut self.edit.string);
+        let TextEditOps {
+            allow_bubble,
+            redraw,
+            cursor_icon,
+            focus,
+            ..
+        } = self.edit.adapt_event(&event, input_state);
+        mem::swap(string, &mut self.edit.string);
+
+        if redraw {
+            self.widget_tag.request_redraw();
+        }
+        if let Some(cursor_icon) = cursor_icon {
+            self.widget_tag.set_cursor_icon(cursor_icon).ok();
+        }
+
         EventOps {
-            focus: None,
-            bubble: true,
+            focus,
+            bubble: allow_bubble && event.default_bubble(),
+            handled: redraw || !allow_bubble,
         }
     }
 }
@@ -95,7 +288,9 @@ impl<R> WidgetRenderable<R> for Label
 {
     type Theme = LabelTheme;
     fn theme(&self) -> LabelTheme {
-        LabelTheme(())
+        LabelTheme {
+            mnemonic: self.mnemonic,
+        }
     }
 
     fn render(&mut self, frame: &mut R::SubFrame) {
@@ -103,12 +298,48 @@ impl<R> WidgetRenderable<R> for Label
     }
 
     fn update_layout(&mut self, layout: &mut R::Layout) {
-        match self.contents {
-            Contents::Text(ref s) => layout.prepare_string(s),
-            Contents::Icon(ref i) => layout.prepare_icon(i),
+        layout.set_text_wrap(self.wrap_mode, self.bounds.width());
+        match (self.selectable, &mut self.contents) {
+            (true, Contents::Text(ref mut s)) => layout.prepare_edit_string(
+                s,
+                &mut self.edit.cursor_data,
+                self.edit.cursor_ops.drain(..),
+            ),
+            (false, Contents::Text(ref s)) => layout.prepare_string(s),
+            (_, Contents::Icon(ref i)) => layout.prepare_icon(i),
+            (_, Contents::Rich(ref r)) => layout.prepare_rich_string(r),
         }
 
         let result = layout.finish();
         self.size_bounds = result.size_bounds;
+        self.edit.text_layout = result.text_layout;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::WindowEvent,
+        test_helpers::{headless_root, send_events},
+    };
+
+    #[test]
+    fn wrap_mode() {
+        let mut label = Label::new(Contents::Text("one two three".to_string()));
+        label.set_wrap_mode(WrapMode::Word);
+        let mut root = headless_root(label, DimsBox::new2(64, 100));
+        // Trailing whitespace hangs off the end of the first line.
+        assert_eq!(DimsBox::new2(56, 32), root.root_widget.size_bounds().min);
+
+        // The text gets re-wrapped when the label's resized.
+        send_events(&mut root, &[WindowEvent::WindowResize(DimsBox::new2(200, 100))]);
+        assert_eq!(DimsBox::new2(104, 16), root.root_widget.size_bounds().min);
+        send_events(&mut root, &[WindowEvent::WindowResize(DimsBox::new2(64, 100))]);
+        assert_eq!(DimsBox::new2(56, 32), root.root_widget.size_bounds().min);
+
+        root.root_widget.set_wrap_mode(WrapMode::None);
+        root.relayout();
+        assert_eq!(DimsBox::new2(104, 16), root.root_widget.size_bounds().min);
     }
 }
@@ -0,0 +1,1100 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use derin_core::{
+    LoopFlow,
+    widget::{Parent, Widget, WidgetInfo, WidgetInfoMut, WidgetIdent, WidgetId, WidgetTag, WidgetRenderable, MessageTarget},
+    render::{Renderer, RendererLayout, SubFrame, WidgetTheme},
+};
+use crate::{
+    event::{EventOps, FocusChange, WidgetEvent, WidgetEventSourced, InputState, Key, ModifierKeys, MouseHoverChange},
+    widgets::{
+        ButtonTheme, GroupTheme,
+        assistants::{
+            ButtonState,
+            mnemonic::{parse_mnemonic, mnemonic_char},
+        },
+    },
+};
+
+use cgmath_geometry::{D2, rect::{BoundBox, DimsBox, GeoBox}};
+use derin_common_types::layout::SizeBounds;
+
+/// A bar of drop-down menus, such as the File/Edit/View menus found at the top of most desktop
+/// applications, placed above a content widget.
+///
+/// Clicking on a menu's title opens the menu, and clicking on one of the menu's items sends the
+/// item's action to the application through `WidgetTag::send_action`.
+///
+/// The bar can also be driven from the keyboard while it has keyboard focus, which it takes when
+/// one of its titles is clicked:
+///
+/// - `Alt` starts and stops keyboard navigation, underlining the menus' mnemonics.
+/// - The left and right arrows move between menus.
+/// - The up and down arrows move between the items of the open menu, and down opens a menu.
+/// - `Enter` opens the selected menu or activates the selected item.
+/// - `Escape` closes the open menu, and stops keyboard navigation if no menu is open.
+/// - Pressing a mnemonic's key (or `Alt` and the key) opens the matching menu or activates the
+///   matching item.
+///
+/// Mnemonics are marked by placing `&` before a character in a title or item's text, such as
+/// `"&File"`. `"&&"` inserts a literal `&`.
+///
+/// Open menus are drawn over the content widget, so the bar should wrap the content of the whole
+/// window.
+#[derive(Debug, Clone)]
+pub struct MenuBar<W, A> {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    size_bounds: SizeBounds,
+    content: W,
+
+    menus: Vec<MenuWidgets<A>>,
+    /// The index of the open menu.
+    open: Option<usize>,
+    /// The index of the menu that's selected by keyboard navigation.
+    highlighted: Option<usize>,
+    /// Whether or not keyboard navigation is active.
+    active: bool,
+}
+
+/// A menu to be placed in a [`MenuBar`].
+///
+/// [`MenuBar`]: ./struct.MenuBar.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Menu<A> {
+    /// The text displayed in the menu bar, with the mnemonic marked by `&`.
+    pub title: String,
+    /// The items displayed when the menu is opened.
+    pub items: Vec<MenuItem<A>>,
+}
+
+/// An item within a [`Menu`].
+///
+/// [`Menu`]: ./struct.Menu.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MenuItem<A> {
+    /// The text displayed for the item, with the mnemonic marked by `&`.
+    pub text: String,
+    /// The action sent to the application when the item is activated.
+    pub action: A,
+}
+
+#[derive(Debug, Clone)]
+struct MenuWidgets<A> {
+    title: MenuTitle,
+    dropdown: MenuDropdown<A>,
+}
+
+#[derive(Debug, Clone)]
+struct MenuTitle {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    size_bounds: SizeBounds,
+    index: usize,
+    text: String,
+    mnemonic: Option<usize>,
+    hover: bool,
+    highlighted: bool,
+    open: bool,
+    show_mnemonic: bool,
+}
+
+#[derive(Debug, Clone)]
+struct MenuDropdown<A> {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    size_bounds: SizeBounds,
+    entries: Vec<MenuEntry<A>>,
+    highlighted: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct MenuEntry<A> {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    size_bounds: SizeBounds,
+    bar_id: WidgetId,
+    index: usize,
+    text: String,
+    mnemonic: Option<usize>,
+    action: A,
+    highlighted: bool,
+    show_mnemonic: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TitleMessage {
+    Pressed(usize),
+    Hovered(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EntryMessage {
+    Activated(usize),
+    Hovered(usize),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MenuBarTheme(());
+
+#[derive(Debug, Clone, Copy)]
+pub struct MenuTitleTheme {
+    pub state: ButtonState,
+    /// Whether or not the title's menu is open.
+    pub open: bool,
+    /// The byte index of the mnemonic character within the title, if the mnemonic should be
+    /// underlined.
+    pub mnemonic: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MenuDropdownTheme(());
+
+#[derive(Debug, Clone, Copy)]
+pub struct MenuItemTheme {
+    /// Whether or not the item is selected, by either the mouse or the keyboard.
+    pub highlighted: bool,
+    /// The byte index of the mnemonic character within the item's text, if the mnemonic should be
+    /// underlined.
+    pub mnemonic: Option<usize>,
+}
+
+impl<A> Menu<A> {
+    /// Creates a new menu with the given title and items.
+    pub fn new(title: String, items: Vec<MenuItem<A>>) -> Menu<A> {
+        Menu{ title, items }
+    }
+}
+
+impl<A> MenuItem<A> {
+    /// Creates a new menu item with the given text, that sends `action` when activated.
+    pub fn new(text: String, action: A) -> MenuItem<A> {
+        MenuItem{ text, action }
+    }
+}
+
+impl<W, A> MenuBar<W, A>
+    where W: Widget,
+          A: 'static + Clone
+{
+    /// Creates a new menu bar containing the given menus, placed above `content`.
+    pub fn new(menus: Vec<Menu<A>>, content: W) -> MenuBar<W, A> {
+        let mut widget_tag = WidgetTag::new();
+        widget_tag.register_message(Self::on_title_message);
+        widget_tag.register_message(Self::on_entry_message);
+        let bar_id = widget_tag.widget_id();
+
+        let menus = menus.into_iter().enumerate().map(|(index, menu)| MenuWidgets {
+            title: MenuTitle::new(index, &menu.title),
+            dropdown: MenuDropdown::new(bar_id, menu.items),
+        }).collect();
+
+        MenuBar {
+            widget_tag,
+            rect: BoundBox::new2(0, 0, 0, 0),
+            size_bounds: SizeBounds::default(),
+            content,
+
+            menus,
+            open: None,
+            highlighted: None,
+            active: false,
+        }
+    }
+
+    /// Retrieves the widget displayed below the menu bar.
+    pub fn content(&self) -> &W {
+        &self.content
+    }
+
+    /// Retrieves the widget displayed below the menu bar, for mutation.
+    ///
+    /// Calling this function forces the menu bar to be re-laid out, so you're discouraged from
+    /// calling it unless you're actually changing the contents.
+    pub fn content_mut(&mut self) -> &mut W {
+        self.widget_tag.request_relayout();
+        &mut self.content
+    }
+
+    /// Retrieves the index of the menu that's currently open, if any.
+    #[inline]
+    pub fn open_menu(&self) -> Option<usize> {
+        self.open
+    }
+
+    /// Close the open menu, if any, and stop keyboard navigation.
+    pub fn close(&mut self) {
+        self.close_menu();
+        self.set_active(false);
+    }
+
+    fn on_title_message(&mut self, message: &TitleMessage) {
+        match *message {
+            TitleMessage::Pressed(index) => match self.open == Some(index) {
+                true => self.close_menu(),
+                false => self.show_menu(index, false),
+            },
+            // Once a menu is open, hovering over the other titles switches between menus.
+            TitleMessage::Hovered(index) => if self.open.is_some() && self.open != Some(index) {
+                self.show_menu(index, false);
+            },
+        }
+    }
+
+    fn on_entry_message(&mut self, message: &EntryMessage) {
+        match *message {
+            EntryMessage::Activated(index) => {
+                self.activate_entry(index);
+            },
+            EntryMessage::Hovered(index) => if let Some(open) = self.open {
+                self.menus[open].dropdown.set_highlighted(Some(index));
+            },
+        }
+    }
+
+    fn set_active(&mut self, active: bool) {
+        self.active = active;
+        self.highlighted = match active {
+            true => self.open.or(match self.menus.len() {
+                0 => None,
+                _ => Some(0)
+            }),
+            false => None
+        };
+        self.update_titles();
+    }
+
+    /// Open the menu at `index`, selecting its first item if `select_first` is `true`.
+    fn show_menu(&mut self, index: usize, select_first: bool) {
+        self.close_menu();
+        if index >= self.menus.len() {
+            return;
+        }
+
+        self.open = Some(index);
+        self.highlighted = Some(index);
+        let dropdown = &mut self.menus[index].dropdown;
+        let highlighted = match (select_first, dropdown.entries.len()) {
+            (true, len) if len > 0 => Some(0),
+            _ => None
+        };
+        dropdown.set_highlighted(highlighted);
+        self.update_titles();
+        self.widget_tag.request_relayout().request_redraw();
+    }
+
+    fn close_menu(&mut self) {
+        if let Some(open) = self.open.take() {
+            self.menus[open].dropdown.set_highlighted(None);
+            if !self.active {
+                self.highlighted = None;
+            }
+            self.update_titles();
+            self.widget_tag.request_relayout().request_redraw();
+        }
+    }
+
+    /// Sync the titles' and items' visual state with the bar's state.
+    fn update_titles(&mut self) {
+        let MenuBar{ ref mut menus, open, highlighted, active, .. } = *self;
+        for (index, menu) in menus.iter_mut().enumerate() {
+            let title = &mut menu.title;
+            let (old_highlighted, old_open, old_show) = (title.highlighted, title.open, title.show_mnemonic);
+            title.highlighted = highlighted == Some(index);
+            title.open = open == Some(index);
+            title.show_mnemonic = active;
+            if (old_highlighted, old_open, old_show) != (title.highlighted, title.open, title.show_mnemonic) {
+                title.widget_tag.request_redraw();
+            }
+
+            for entry in &mut menu.dropdown.entries {
+                if entry.show_mnemonic != active {
+                    entry.show_mnemonic = active;
+                    entry.widget_tag.request_redraw();
+                }
+            }
+        }
+    }
+
+    /// Move the menu selection by `offset` menus, wrapping around the ends of the bar. If a menu
+    /// is open, the newly selected menu gets opened in its place.
+    fn move_menu(&mut self, offset: isize) {
+        let len = self.menus.len() as isize;
+        if len == 0 {
+            return;
+        }
+        let current = self.open.or(self.highlighted).map(|i| i as isize).unwrap_or(-offset.signum());
+        let index = (current + offset).rem_euclid(len) as usize;
+        match self.open {
+            Some(_) => self.show_menu(index, true),
+            None => {
+                self.highlighted = Some(index);
+                self.update_titles();
+            }
+        }
+    }
+
+    /// Move the item selection within the open menu by `offset` items, wrapping around the ends
+    /// of the menu.
+    fn move_entry(&mut self, offset: isize) {
+        if let Some(open) = self.open {
+            let dropdown = &mut self.menus[open].dropdown;
+            let len = dropdown.entries.len() as isize;
+            if len == 0 {
+                return;
+            }
+            let current = dropdown.highlighted.map(|i| i as isize).unwrap_or(-offset.signum());
+            dropdown.set_highlighted(Some((current + offset).rem_euclid(len) as usize));
+        }
+    }
+
+    /// Send the action of the item at `index` in the open menu, and close the menu. Returns
+    /// whether or not an action was sent.
+    fn activate_entry(&mut self, index: usize) -> bool {
+        let action = match self.open.and_then(|open| self.menus[open].dropdown.entries.get(index)) {
+            Some(entry) => entry.action.clone(),
+            None => return false
+        };
+        self.close();
+        self.widget_tag.send_action(action);
+        true
+    }
+
+    /// Open the menu or activate the item whose mnemonic is `c`. Returns whether or not an item
+    /// was activated.
+    fn activate_mnemonic(&mut self, c: char) -> bool {
+        match self.open {
+            Some(open) => {
+                let index = self.menus[open].dropdown.entries.iter()
+                    .position(|e| mnemonic_char(&e.text, e.mnemonic) == Some(c));
+                match index {
+                    Some(index) => self.activate_entry(index),
+                    None => false
+                }
+            },
+            None => {
+                let index = self.menus.iter()
+                    .position(|m| mnemonic_char(&m.title.text, m.title.mnemonic) == Some(c));
+                if let Some(index) = index {
+                    self.active = true;
+                    self.show_menu(index, true);
+                }
+                false
+            }
+        }
+    }
+
+    fn dropdown_index(&self) -> usize {
+        self.menus.len() + 1
+    }
+}
+
+impl MenuTitle {
+    fn new(index: usize, title: &str) -> MenuTitle {
+        let (text, mnemonic) = parse_mnemonic(title);
+        MenuTitle {
+            widget_tag: WidgetTag::new(),
+            rect: BoundBox::new2(0, 0, 0, 0),
+            size_bounds: SizeBounds::default(),
+            index,
+            text,
+            mnemonic,
+            hover: false,
+            highlighted: false,
+            open: false,
+            show_mnemonic: false,
+        }
+    }
+}
+
+impl<A> MenuDropdown<A> {
+    fn new(bar_id: WidgetId, items: Vec<MenuItem<A>>) -> MenuDropdown<A> {
+        // The dropdown lies over the bar's content, and should take the cursor from it.
+        let mut widget_tag = WidgetTag::new();
+        widget_tag.set_popup(true);
+        MenuDropdown {
+            widget_tag,
+            rect: BoundBox::new2(0, 0, 0, 0),
+            size_bounds: SizeBounds::default(),
+            entries: items.into_iter().enumerate()
+                .map(|(index, item)| MenuEntry::new(bar_id, index, item))
+                .collect(),
+            highlighted: None,
+        }
+    }
+
+    fn set_highlighted(&mut self, highlighted: Option<usize>) {
+        if self.highlighted == highlighted {
+            return;
+        }
+        self.highlighted = highlighted;
+        for (index, entry) in self.entries.iter_mut().enumerate() {
+            let entry_highlighted = highlighted == Some(index);
+            if entry.highlighted != entry_highlighted {
+                entry.highlighted = entry_highlighted;
+                entry.widget_tag.request_redraw();
+            }
+        }
+    }
+}
+
+impl<A> MenuEntry<A> {
+    fn new(bar_id: WidgetId, index: usize, item: MenuItem<A>) -> MenuEntry<A> {
+        let (text, mnemonic) = parse_mnemonic(&item.text);
+        MenuEntry {
+            widget_tag: WidgetTag::new(),
+            rect: BoundBox::new2(0, 0, 0, 0),
+            size_bounds: SizeBounds::default(),
+            bar_id,
+            index,
+            text,
+            mnemonic,
+            action: item.action,
+            highlighted: false,
+            show_mnemonic: false,
+        }
+    }
+}
+
+impl<W, A> Widget for MenuBar<W, A>
+    where W: Widget,
+          A: 'static + Clone
+{
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        self.widget_tag.request_relayout();
+        &mut self.rect
+    }
+
+    #[inline]
+    fn size_bounds(&self) -> SizeBounds {
+        self.size_bounds
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        let event = event.unwrap();
+        let mut focus = None;
+        let mut bubble = event.default_bubble();
+        let mut handled = true;
+
+        match event {
+            WidgetEvent::KeyDown(key, modifiers) => {
+                bubble = false;
+                match key {
+                    Key::LAlt | Key::RAlt => match self.active {
+                        false => self.set_active(true),
+                        true => {
+                            self.close();
+                            focus = Some(FocusChange::Remove);
+                        }
+                    },
+                    Key::LArrow => self.move_menu(-1),
+                    Key::RArrow => self.move_menu(1),
+                    Key::UArrow => self.move_entry(-1),
+                    Key::DArrow => match (self.open, self.highlighted) {
+                        (None, Some(highlighted)) => self.show_menu(highlighted, true),
+                        _ => self.move_entry(1)
+                    },
+                    Key::Enter | Key::Space => match (self.open, self.highlighted) {
+                        (Some(open), _) => {
+                            let highlighted = self.menus[open].dropdown.highlighted;
+                            if highlighted.map(|i| self.activate_entry(i)).unwrap_or(false) {
+                                focus = Some(FocusChange::Remove);
+                            }
+                        },
+                        (None, Some(highlighted)) => self.show_menu(highlighted, true),
+                        (None, None) => ()
+                    },
+                    Key::Escape => match self.open {
+                        Some(_) => self.close_menu(),
+                        None => {
+                            self.close();
+                            focus = Some(FocusChange::Remove);
+                        }
+                    },
+                    _ => if self.active || modifiers.contains(ModifierKeys::ALT) {
+                        if let Some(c) = key.to_char() {
+                            if self.activate_mnemonic(c) {
+                                focus = Some(FocusChange::Remove);
+                            }
+                        }
+                    }
+                }
+            },
+            WidgetEvent::LoseFocus => self.close(),
+            _ => handled = false
+        }
+
+        EventOps {
+            focus,
+            bubble,
+            handled,
+        }
+    }
+}
+
+impl Widget for MenuTitle {
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        &mut self.rect
+    }
+
+    #[inline]
+    fn size_bounds(&self) -> SizeBounds {
+        self.size_bounds
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        let event = event.unwrap();
+        let widget_id = self.widget_tag.widget_id();
+        let mut focus = None;
+        let mut handled = true;
+
+        match event {
+            WidgetEvent::MouseMove{hover_change: Some(MouseHoverChange::Enter), ..} => {
+                self.hover = true;
+                self.widget_tag
+                    .request_redraw()
+                    .send_message_to(TitleMessage::Hovered(self.index), MessageTarget::ParentOf(widget_id));
+            },
+            WidgetEvent::MouseMove{hover_change: Some(MouseHoverChange::Exit), ..} => {
+                self.hover = false;
+                self.widget_tag.request_redraw();
+            },
+            WidgetEvent::MouseDown{in_widget: true, ..} => {
+                // Give the bar focus, so that the open menu can be navigated with the keyboard.
+                focus = Some(FocusChange::Parent);
+                self.widget_tag.send_message_to(TitleMessage::Pressed(self.index), MessageTarget::ParentOf(widget_id));
+            },
+            _ => handled = false
+        }
+
+        EventOps {
+            focus,
+            bubble: event.default_bubble(),
+            handled,
+        }
+    }
+}
+
+impl<A: 'static> Widget for MenuDropdown<A> {
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        self.widget_tag.request_relayout();
+        &mut self.rect
+    }
+
+    #[inline]
+    fn size_bounds(&self) -> SizeBounds {
+        self.size_bounds
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        EventOps {
+            focus: None,
+            bubble: event.unwrap().default_bubble(),
+            handled: false,
+        }
+    }
+}
+
+impl<A: 'static> Widget for MenuEntry<A> {
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        &mut self.rect
+    }
+
+    #[inline]
+    fn size_bounds(&self) -> SizeBounds {
+        self.size_bounds
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        let event = event.unwrap();
+        let bar = MessageTarget::Widget(self.bar_id);
+        let mut handled = true;
+
+        match event {
+            WidgetEvent::MouseMove{hover_change: Some(MouseHoverChange::Enter), ..} =>
+                self.widget_tag.send_message_to(EntryMessage::Hovered(self.index), bar),
+            WidgetEvent::MouseUp{in_widget: true, ..} =>
+                self.widget_tag.send_message_to(EntryMessage::Activated(self.index), bar),
+            _ => handled = false
+        }
+
+        EventOps {
+            focus: None,
+            bubble: event.default_bubble(),
+            handled,
+        }
+    }
+}
+
+impl<W, A> Parent for MenuBar<W, A>
+    where W: Widget,
+          A: 'static + Clone
+{
+    fn num_children(&self) -> usize {
+        self.menus.len() + 1 + self.open.is_some() as usize
+    }
+
+    fn framed_child<R: Renderer>(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, R>> {
+        match widget_ident {
+            WidgetIdent::Num(index) => self.framed_child_by_index(index as usize),
+            _ => None
+        }
+    }
+    fn framed_child_mut<R: Renderer>(&mut self, widget_ident: WidgetIdent) -> Option<WidgetInfoMut<'_, R>> {
+        match widget_ident {
+            WidgetIdent::Num(index) => self.framed_child_by_index_mut(index as usize),
+            _ => None
+        }
+    }
+
+    fn framed_children<'a, R, G>(&'a self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfo<'a, R>) -> LoopFlow
+    {
+        for index in 0..self.num_children() {
+            if let LoopFlow::Break = for_each(self.framed_child_by_index(index).unwrap()) {
+                return;
+            }
+        }
+    }
+
+    fn framed_children_mut<'a, R, G>(&'a mut self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfoMut<'a, R>) -> LoopFlow
+    {
+        let content_index = self.menus.len();
+        let dropdown_index = self.dropdown_index();
+        let open = self.open;
+
+        // The open menu comes last, so that it gets drawn over the content.
+        let mut open_dropdown = None;
+        for (index, menu) in self.menus.iter_mut().enumerate() {
+            let MenuWidgets{ ref mut title, ref mut dropdown } = *menu;
+            if Some(index) == open {
+                open_dropdown = Some(dropdown);
+            }
+            if let LoopFlow::Break = for_each(WidgetInfoMut::new(WidgetIdent::Num(index as u32), index, title)) {
+                return;
+            }
+        }
+        if let LoopFlow::Break = for_each(WidgetInfoMut::new(WidgetIdent::Num(content_index as u32), content_index, &mut self.content)) {
+            return;
+        }
+        if let Some(dropdown) = open_dropdown {
+            let _ = for_each(WidgetInfoMut::new(WidgetIdent::Num(dropdown_index as u32), dropdown_index, dropdown));
+        }
+    }
+
+    fn framed_child_by_index<R: Renderer>(&self, index: usize) -> Option<WidgetInfo<'_, R>> {
+        let ident = WidgetIdent::Num(index as u32);
+        match index {
+            _ if index < self.menus.len() => Some(WidgetInfo::new(ident, index, &self.menus[index].title)),
+            _ if index == self.menus.len() => Some(WidgetInfo::new(ident, index, &self.content)),
+            _ if index == self.dropdown_index() => self.open.map(|open| WidgetInfo::new(ident, index, &self.menus[open].dropdown)),
+            _ => None
+        }
+    }
+    fn framed_child_by_index_mut<R: Renderer>(&mut self, index: usize) -> Option<WidgetInfoMut<'_, R>> {
+        let ident = WidgetIdent::Num(index as u32);
+        match index {
+            _ if index < self.menus.len() => Some(WidgetInfoMut::new(ident, index, &mut self.menus[index].title)),
+            _ if index == self.menus.len() => Some(WidgetInfoMut::new(ident, index, &mut self.content)),
+            _ if index == self.dropdown_index() => match self.open {
+                Some(open) => Some(WidgetInfoMut::new(ident, index, &mut self.menus[open].dropdown)),
+                None => None
+            },
+            _ => None
+        }
+    }
+}
+
+impl<A: 'static> Parent for MenuDropdown<A> {
+    fn num_children(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn framed_child<R: Renderer>(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, R>> {
+        match widget_ident {
+            WidgetIdent::Num(index) => self.framed_child_by_index(index as usize),
+            _ => None
+        }
+    }
+    fn framed_child_mut<R: Renderer>(&mut self, widget_ident: WidgetIdent) -> Option<WidgetInfoMut<'_, R>> {
+        match widget_ident {
+            WidgetIdent::Num(index) => self.framed_child_by_index_mut(index as usize),
+            _ => None
+        }
+    }
+
+    fn framed_children<'a, R, G>(&'a self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfo<'a, R>) -> LoopFlow
+    {
+        for (index, entry) in self.entries.iter().enumerate() {
+            if let LoopFlow::Break = for_each(WidgetInfo::new(WidgetIdent::Num(index as u32), index, entry)) {
+                return;
+            }
+        }
+    }
+
+    fn framed_children_mut<'a, R, G>(&'a mut self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfoMut<'a, R>) -> LoopFlow
+    {
+        for (index, entry) in self.entries.iter_mut().enumerate() {
+            if let LoopFlow::Break = for_each(WidgetInfoMut::new(WidgetIdent::Num(index as u32), index, entry)) {
+                return;
+            }
+        }
+    }
+
+    fn framed_child_by_index<R: Renderer>(&self, index: usize) -> Option<WidgetInfo<'_, R>> {
+        self.entries.get(index).map(|e| WidgetInfo::new(WidgetIdent::Num(index as u32), index, e))
+    }
+    fn framed_child_by_index_mut<R: Renderer>(&mut self, index: usize) -> Option<WidgetInfoMut<'_, R>> {
+        self.entries.get_mut(index).map(|e| WidgetInfoMut::new(WidgetIdent::Num(index as u32), index, e))
+    }
+}
+
+impl<R, W, A> WidgetRenderable<R> for MenuBar<W, A>
+    where R: Renderer,
+          W: Widget,
+          A: 'static + Clone
+{
+    type Theme = MenuBarTheme;
+    fn theme(&self) -> MenuBarTheme {
+        MenuBarTheme(())
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, _: &mut R::Layout) {
+        // Line the titles up along the top of the bar, with the content filling the rest.
+        let bar_height = self.menus.iter().map(|m| m.title.size_bounds.min.height()).max().unwrap_or(0);
+        let mut x = 0;
+        for menu in &mut self.menus {
+            let width = menu.title.size_bounds.min.width();
+            menu.title.rect = BoundBox::new2(x, 0, x + width, bar_height);
+            x += width;
+        }
+
+        let content_bounds = self.content.size_bounds();
+        self.size_bounds = SizeBounds {
+            min: DimsBox::new2(
+                x.max(content_bounds.min.width()),
+                bar_height.saturating_add(content_bounds.min.height()),
+            ),
+            max: DimsBox::new2(
+                content_bounds.max.width(),
+                bar_height.saturating_add(content_bounds.max.height()),
+            ),
+        };
+        *self.content.rect_mut() = BoundBox::new2(0, bar_height, self.rect.width(), self.rect.height());
+
+        // Drop the open menu down from its title, keeping it inside the bar's rect where possible.
+        if let Some(open) = self.open {
+            let MenuWidgets{ ref title, ref mut dropdown } = self.menus[open];
+            let dims = dropdown.size_bounds.min;
+            let x = title.rect.min.x.min(self.rect.width() - dims.width()).max(0);
+            dropdown.rect = BoundBox::new2(x, bar_height, x + dims.width(), bar_height + dims.height());
+        }
+    }
+}
+
+impl<R> WidgetRenderable<R> for MenuTitle
+    where R: Renderer
+{
+    type Theme = MenuTitleTheme;
+    fn theme(&self) -> MenuTitleTheme {
+        MenuTitleTheme {
+            state: match (self.open, self.hover || self.highlighted) {
+                (true, _) => ButtonState::Pressed,
+                (false, true) => ButtonState::Hover,
+                (false, false) => ButtonState::Normal,
+            },
+            open: self.open,
+            mnemonic: self.mnemonic.filter(|_| self.show_mnemonic),
+        }
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, layout: &mut R::Layout) {
+        layout.prepare_string(&self.text);
+        let result = layout.finish();
+        self.size_bounds = result.size_bounds;
+    }
+}
+
+impl<R, A> WidgetRenderable<R> for MenuDropdown<A>
+    where R: Renderer,
+          A: 'static
+{
+    type Theme = MenuDropdownTheme;
+    fn theme(&self) -> MenuDropdownTheme {
+        MenuDropdownTheme(())
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, _: &mut R::Layout) {
+        // Stack the items on top of each other, stretching them to the width of the widest item.
+        let width = self.entries.iter().map(|e| e.size_bounds.min.width()).max().unwrap_or(0);
+        let mut y = 0;
+        for entry in &mut self.entries {
+            let height = entry.size_bounds.min.height();
+            entry.rect = BoundBox::new2(0, y, width, y + height);
+            y += height;
+        }
+        self.size_bounds = SizeBounds::new_min(DimsBox::new2(width, y));
+    }
+}
+
+impl<R, A> WidgetRenderable<R> for MenuEntry<A>
+    where R: Renderer,
+          A: 'static
+{
+    type Theme = MenuItemTheme;
+    fn theme(&self) -> MenuItemTheme {
+        MenuItemTheme {
+            highlighted: self.highlighted,
+            mnemonic: self.mnemonic.filter(|_| self.show_mnemonic),
+        }
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, layout: &mut R::Layout) {
+        layout.prepare_string(&self.text);
+        let result = layout.finish();
+        self.size_bounds = result.size_bounds;
+    }
+}
+
+impl WidgetTheme for MenuBarTheme {
+    type Fallback = GroupTheme;
+    fn fallback(self) -> Option<GroupTheme> {
+        Some(GroupTheme::default())
+    }
+}
+
+impl WidgetTheme for MenuTitleTheme {
+    type Fallback = ButtonTheme;
+    fn fallback(self) -> Option<ButtonTheme> {
+        Some(ButtonTheme{ state: self.state, mnemonic: self.mnemonic })
+    }
+}
+
+impl WidgetTheme for MenuDropdownTheme {
+    type Fallback = GroupTheme;
+    fn fallback(self) -> Option<GroupTheme> {
+        Some(GroupTheme::default())
+    }
+}
+
+impl WidgetTheme for MenuItemTheme {
+    type Fallback = ButtonTheme;
+    fn fallback(self) -> Option<ButtonTheme> {
+        let state = match self.highlighted {
+            true => ButtonState::Hover,
+            false => ButtonState::Normal
+        };
+        Some(ButtonTheme{ state, mnemonic: self.mnemonic })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cgmath::Point2,
+        core::{Root, render::headless::HeadlessRenderer},
+        test_helpers::{click, headless_root, press_key},
+        widgets::Spacer,
+    };
+
+    type TestBar = MenuBar<Spacer, &'static str>;
+
+    fn menu_bar() -> TestBar {
+        MenuBar::new(vec![
+            Menu::new("&File".to_string(), vec![
+                MenuItem::new("&New".to_string(), "new"),
+                MenuItem::new("&Open".to_string(), "open"),
+            ]),
+            Menu::new("&Edit".to_string(), vec![
+                MenuItem::new("&Undo".to_string(), "undo"),
+            ]),
+        ], Spacer::flex())
+    }
+
+    /// Click at `pos`, then draw and lay out the tree again so that a newly opened dropdown gets
+    /// added to the tree and laid out.
+    fn click_and_settle(root: &mut Root<TestBar, HeadlessRenderer>, pos: Point2<i32>) {
+        click(root, pos);
+        root.redraw();
+        root.relayout();
+    }
+
+    fn actions(root: &mut Root<TestBar, HeadlessRenderer>) -> Vec<&'static str> {
+        root.take_actions()
+            .map(|action| *action.downcast::<&'static str>().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn layout() {
+        let root = headless_root(menu_bar(), DimsBox::new2(200, 100));
+        let bar = &root.root_widget;
+
+        let title_rects = bar.menus.iter().map(|m| m.title.rect).collect::<Vec<_>>();
+        assert_eq!(vec![BoundBox::new2(0, 0, 32, 16), BoundBox::new2(32, 0, 64, 16)], title_rects);
+        assert_eq!(BoundBox::new2(0, 16, 200, 100), bar.content().rect());
+        assert_eq!(None, bar.open_menu());
+        assert_eq!(4, Parent::num_children(bar));
+    }
+
+    #[test]
+    fn click_title() {
+        let mut root = headless_root(menu_bar(), DimsBox::new2(200, 100));
+        click_and_settle(&mut root, Point2::new(8, 8));
+
+        let bar = &root.root_widget;
+        assert_eq!(Some(0), bar.open_menu());
+        assert_eq!(5, Parent::num_children(bar));
+        // The dropdown hangs below its title, and is as wide as its widest item.
+        let dropdown = &bar.menus[0].dropdown;
+        assert_eq!(BoundBox::new2(0, 16, 32, 48), dropdown.rect);
+        let entry_rects = dropdown.entries.iter().map(|e| e.rect).collect::<Vec<_>>();
+        assert_eq!(vec![BoundBox::new2(0, 0, 32, 16), BoundBox::new2(0, 16, 32, 32)], entry_rects);
+
+        // Clicking the title again closes the menu.
+        click_and_settle(&mut root, Point2::new(8, 8));
+        assert_eq!(None, root.root_widget.open_menu());
+        assert_eq!(0, actions(&mut root).len());
+    }
+
+    #[test]
+    fn click_entry() {
+        let mut root = headless_root(menu_bar(), DimsBox::new2(200, 100));
+        click_and_settle(&mut root, Point2::new(40, 8));
+        assert_eq!(Some(1), root.root_widget.open_menu());
+
+        // The dropdown lies over the content, which comes first in the bar's children, so the
+        // click only reaches the entry if the dropdown takes the cursor from the content.
+        click_and_settle(&mut root, Point2::new(40, 24));
+        assert_eq!(vec!["undo"], actions(&mut root));
+        assert_eq!(None, root.root_widget.open_menu());
+
+        // With the menu closed, clicking the same spot hits the content.
+        click_and_settle(&mut root, Point2::new(40, 24));
+        assert_eq!(0, actions(&mut root).len());
+    }
+
+    #[test]
+    fn keyboard_navigation() {
+        let mut root = headless_root(menu_bar(), DimsBox::new2(200, 100));
+        click_and_settle(&mut root, Point2::new(8, 8));
+        assert_eq!(None, root.root_widget.menus[0].dropdown.highlighted);
+
+        press_key(&mut root, Key::DArrow);
+        assert_eq!(Some(0), root.root_widget.menus[0].dropdown.highlighted);
+        press_key(&mut root, Key::DArrow);
+        assert_eq!(Some(1), root.root_widget.menus[0].dropdown.highlighted);
+        press_key(&mut root, Key::DArrow);
+        assert_eq!(Some(0), root.root_widget.menus[0].dropdown.highlighted);
+
+        // Moving to the next menu opens it with its first item selected.
+        press_key(&mut root, Key::RArrow);
+        assert_eq!(Some(1), root.root_widget.open_menu());
+        assert_eq!(Some(0), root.root_widget.menus[1].dropdown.highlighted);
+        assert_eq!(None, root.root_widget.menus[0].dropdown.highlighted);
+
+        press_key(&mut root, Key::Enter);
+        assert_eq!(vec!["undo"], actions(&mut root));
+        assert_eq!(None, root.root_widget.open_menu());
+    }
+
+    #[test]
+    fn escape() {
+        let mut root = headless_root(menu_bar(), DimsBox::new2(200, 100));
+        click_and_settle(&mut root, Point2::new(8, 8));
+
+        press_key(&mut root, Key::Escape);
+        assert_eq!(None, root.root_widget.open_menu());
+        assert!(root.root_widget.menus.iter().all(|m| !m.title.open));
+        assert_eq!(0, actions(&mut root).len());
+    }
+
+    #[test]
+    fn mnemonics() {
+        let mut root = headless_root(menu_bar(), DimsBox::new2(200, 100));
+        click_and_settle(&mut root, Point2::new(8, 8));
+        press_key(&mut root, Key::Escape);
+
+        // Alt starts keyboard navigation and shows the mnemonics.
+        press_key(&mut root, Key::LAlt);
+        assert!(root.root_widget.menus.iter().all(|m| m.title.show_mnemonic));
+
+        // The mnemonic opens the matching menu, and then activates the matching item.
+        press_key(&mut root, Key::E);
+        assert_eq!(Some(1), root.root_widget.open_menu());
+        press_key(&mut root, Key::U);
+        assert_eq!(vec!["undo"], actions(&mut root));
+        assert_eq!(None, root.root_widget.open_menu());
+        assert!(root.root_widget.menus.iter().all(|m| !m.title.show_mnemonic));
+    }
+}
@@ -0,0 +1,675 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use derin_core::{
+    LoopFlow,
+    timer::{Timer, TimerId},
+    widget::{Parent, Widget, WidgetInfo, WidgetInfoMut, WidgetIdent, WidgetId, WidgetTag, WidgetRenderable, MessageTarget},
+    render::{Renderer, RendererLayout, SubFrame, WidgetTheme},
+};
+use crate::{
+    event::{EventOps, WidgetEvent, WidgetEventSourced, InputState, Key, MouseHoverChange},
+    widgets::{
+        ButtonTheme, LineBoxTheme,
+        assistants::{
+            ButtonState,
+            text_edit::{CharFilter, TextEditAssist, TextEditOps, CursorFlashOp},
+        },
+    },
+};
+
+use cgmath_geometry::{D2, rect::{BoundBox, DimsBox, GeoBox}};
+use derin_common_types::layout::SizeBounds;
+
+use std::{
+    fmt,
+    marker::PhantomData,
+    str::FromStr,
+    time::Duration,
+};
+
+/// A numeric type that can be edited with a [`NumberInput`].
+///
+/// Implemented for all of the primitive integer and floating-point types.
+///
+/// [`NumberInput`]: ./struct.NumberInput.html
+pub trait Num: 'static + Copy + PartialOrd + FromStr + fmt::Display + fmt::Debug {
+    /// Move `self` by `step`, `steps` times. Integers saturate instead of overflowing.
+    fn add_steps(self, step: Self, steps: i32) -> Self;
+    /// Whether or not `c` can appear in the textual representation of the type.
+    fn char_allowed(c: char) -> bool;
+}
+
+macro_rules! impl_num_int {
+    ($($ty:ty),*) => {$(
+        impl Num for $ty {
+            #[inline]
+            fn add_steps(self, step: $ty, steps: i32) -> $ty {
+                // Every primitive integer fits in an `i128`, and so does any step multiplied by an
+                // `i32`, so the only overflow left to handle is converting back.
+                let value = self as i128 + step as i128 * steps as i128;
+                value.max(<$ty>::min_value() as i128).min(<$ty>::max_value() as i128) as $ty
+            }
+
+            #[inline]
+            fn char_allowed(c: char) -> bool {
+                c.is_ascii_digit() || c == '-' || c == '+'
+            }
+        }
+    )*}
+}
+
+macro_rules! impl_num_float {
+    ($($ty:ty),*) => {$(
+        impl Num for $ty {
+            #[inline]
+            fn add_steps(self, step: $ty, steps: i32) -> $ty {
+                self + step * steps as $ty
+            }
+
+            #[inline]
+            fn char_allowed(c: char) -> bool {
+                match c {
+                    '-' | '+' | '.' | 'e' | 'E' => true,
+                    _ => c.is_ascii_digit()
+                }
+            }
+        }
+    )*}
+}
+
+impl_num_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+impl_num_float!(f32, f64);
+
+/// A text box for editing a number, with a pair of buttons for stepping the number up or down.
+///
+/// The value can also be stepped with the up and down arrow keys. Typed text gets parsed and
+/// clamped to the input's range when the input loses focus or `Enter` is pressed; if the text
+/// isn't a valid number, it reverts to the last valid value.
+///
+/// Whenever the value changes, a [`ValueChanged`] message is sent to the input's parent.
+///
+/// [`ValueChanged`]: ./struct.ValueChanged.html
+#[derive(Debug, Clone)]
+pub struct NumberInput<T: Num> {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    size_bounds: SizeBounds,
+    edit: TextEditAssist<NumCharFilter<T>>,
+    flash_timer: Option<TimerId>,
+    /// Set when the text needs to be parsed at the next layout, which is when queued edits
+    /// actually get applied to the string.
+    commit_pending: bool,
+
+    value: T,
+    step: T,
+    min: T,
+    max: T,
+
+    up: StepButton,
+    down: StepButton,
+}
+
+/// Message sent to the parent of a [`NumberInput`] whenever its value changes.
+///
+/// [`NumberInput`]: ./struct.NumberInput.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValueChanged<T> {
+    /// The ID of the input whose value changed.
+    pub widget_id: WidgetId,
+    /// The new value.
+    pub value: T,
+}
+
+#[derive(Debug, Clone)]
+struct StepButton {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    size_bounds: SizeBounds,
+    state: ButtonState,
+    steps: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct StepPressed(i32);
+
+struct NumCharFilter<T>(PhantomData<fn() -> T>);
+
+#[derive(Debug, Clone, Default)]
+pub struct NumberInputTheme(());
+
+#[derive(Debug, Clone, Copy)]
+pub struct NumberInputStepTheme {
+    pub state: ButtonState,
+    /// `true` for the button that steps the value up, `false` for the one that steps it down.
+    pub increment: bool,
+}
+
+impl<T: Num> NumberInput<T> {
+    /// Creates a new number input with the given `value`, `step`, `min`, and `max`.
+    pub fn new(value: T, step: T, min: T, max: T) -> NumberInput<T> {
+        let mut widget_tag = WidgetTag::new();
+        widget_tag.register_message(Self::on_step_pressed);
+
+        let value = clamp(value, min, max);
+        NumberInput {
+            widget_tag,
+            rect: BoundBox::new2(0, 0, 0, 0),
+            size_bounds: SizeBounds::default(),
+            edit: TextEditAssist::new(value.to_string()),
+            flash_timer: None,
+            commit_pending: false,
+
+            value, step, min, max,
+
+            up: StepButton::new(1),
+            down: StepButton::new(-1),
+        }
+    }
+
+    /// Retrieves the value stored in the input.
+    #[inline]
+    pub fn value(&self) -> T {
+        self.value
+    }
+
+    /// Retrieves the amount the value is moved by each step.
+    #[inline]
+    pub fn step(&self) -> T {
+        self.step
+    }
+
+    /// Retrieves the range of possible values the input can contain.
+    #[inline]
+    pub fn range(&self) -> (T, T) {
+        (self.min, self.max)
+    }
+
+    /// Set the value stored in the input, clamping it to the input's range.
+    ///
+    /// This doesn't send a [`ValueChanged`] message.
+    ///
+    /// [`ValueChanged`]: ./struct.ValueChanged.html
+    pub fn set_value(&mut self, value: T) {
+        self.value = clamp(value, self.min, self.max);
+        self.reset_string();
+    }
+
+    /// Set the amount the value is moved by each step.
+    #[inline]
+    pub fn set_step(&mut self, step: T) {
+        self.step = step;
+    }
+
+    /// Set the range of possible values the input can contain, clamping the current value to the
+    /// new range.
+    pub fn set_range(&mut self, min: T, max: T) {
+        self.min = min;
+        self.max = max;
+        self.set_value(self.value);
+    }
+
+    fn on_step_pressed(&mut self, step_pressed: &StepPressed) {
+        self.step_by(step_pressed.0);
+    }
+
+    fn step_by(&mut self, steps: i32) {
+        let value = self.value.add_steps(self.step, steps);
+        self.change_value(value);
+    }
+
+    /// Set the value, notifying the parent if it changed.
+    fn change_value(&mut self, value: T) {
+        let value = clamp(value, self.min, self.max);
+        let changed = value != self.value;
+        self.value = value;
+        self.reset_string();
+
+        if changed {
+            let widget_id = self.widget_tag.widget_id();
+            self.widget_tag.send_message_to(ValueChanged{ widget_id, value }, MessageTarget::ParentOf(widget_id));
+        }
+    }
+
+    /// Parse the typed text, reverting to the last valid value if it doesn't parse.
+    fn commit(&mut self) {
+        match self.edit.string.trim().parse::<T>() {
+            Ok(value) => self.change_value(value),
+            Err(_) => self.reset_string()
+        }
+    }
+
+    /// Replace the string with the textual representation of the current value.
+    fn reset_string(&mut self) {
+        let string = self.value.to_string();
+        if string != self.edit.string {
+            self.edit.string = string;
+            self.widget_tag.request_redraw().request_relayout();
+        }
+    }
+}
+
+impl StepButton {
+    fn new(steps: i32) -> StepButton {
+        StepButton {
+            widget_tag: WidgetTag::new(),
+            rect: BoundBox::new2(0, 0, 0, 0),
+            size_bounds: SizeBounds::default(),
+            state: ButtonState::Normal,
+            steps,
+        }
+    }
+}
+
+impl<T: Num> Widget for NumberInput<T> {
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        self.widget_tag.request_relayout();
+        &mut self.rect
+    }
+
+    #[inline]
+    fn size_bounds(&self) -> SizeBounds {
+        self.size_bounds
+    }
+
+    #[inline]
+    fn accepts_focus(&self) -> bool {
+        true
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, input_state: InputState) -> EventOps {
+        let event = event.unwrap();
+
+        // The arrow keys step the value rather than moving the cursor vertically, and `Enter`
+        // commits the typed text.
+        match event {
+            WidgetEvent::KeyDown(Key::UArrow, _) |
+            WidgetEvent::KeyDown(Key::DArrow, _) |
+            WidgetEvent::KeyDown(Key::Enter, _) => {
+                match event {
+                    WidgetEvent::KeyDown(Key::UArrow, _) => self.step_by(1),
+                    WidgetEvent::KeyDown(Key::DArrow, _) => self.step_by(-1),
+                    _ => {
+                        self.commit_pending = true;
+                        self.widget_tag.request_relayout();
+                    }
+                }
+                return EventOps {
+                    focus: None,
+                    bubble: false,
+                    handled: true,
+                };
+            },
+            _ => ()
+        }
+
+        let TextEditOps {
+            allow_bubble,
+            redraw,
+            cursor_flash,
+            cursor_icon,
+            focus,
+            ..
+        } = self.edit.adapt_event(&event, input_state);
+
+        if let WidgetEvent::LoseFocus = event {
+            self.commit_pending = true;
+            self.widget_tag.request_relayout();
+        }
+
+        match (cursor_flash, self.flash_timer) {
+            (Some(CursorFlashOp::Start), None) => {
+                let timer_id = TimerId::new();
+                self.widget_tag.timers_mut().insert(timer_id, Timer::new(Duration::new(1, 0)/2));
+                self.flash_timer = Some(timer_id);
+            },
+            (Some(CursorFlashOp::End), Some(timer_id)) => {
+                self.widget_tag.timers_mut().remove(&timer_id);
+                self.flash_timer = None;
+            },
+            _ => ()
+        }
+
+        if redraw {
+            self.widget_tag.request_redraw();
+        }
+
+        match event {
+            WidgetEvent::Timer{timer_id, times_triggered, ..} if Some(timer_id) == self.flash_timer => {
+                self.edit.cursor_data.draw_cursor = times_triggered % 2 == 0;
+                self.widget_tag.request_redraw();
+            },
+            _ => ()
+        };
+
+        if let Some(cursor_icon) = cursor_icon {
+            self.widget_tag.set_cursor_icon(cursor_icon).ok();
+        }
+
+        EventOps {
+            focus,
+            bubble: allow_bubble && event.default_bubble(),
+            handled: redraw || !allow_bubble,
+        }
+    }
+}
+
+impl Widget for StepButton {
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        &mut self.rect
+    }
+
+    #[inline]
+    fn size_bounds(&self) -> SizeBounds {
+        self.size_bounds
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        use self::WidgetEvent::*;
+        let event = event.unwrap();
+
+        let mut handled = true;
+        let new_state = match event {
+            MouseMove{hover_change: Some(ref change), ..} => match change {
+                MouseHoverChange::Enter => ButtonState::Hover,
+                MouseHoverChange::Exit => ButtonState::Normal,
+                _ => self.state
+            },
+            MouseDown{..} => ButtonState::Pressed,
+            MouseUp{in_widget: true, pressed_in_widget: true, ..} => ButtonState::Hover,
+            Click{..} => {
+                let widget_id = self.widget_tag.widget_id();
+                self.widget_tag.send_message_to(StepPressed(self.steps), MessageTarget::ParentOf(widget_id));
+                self.state
+            },
+            MouseUp{in_widget: false, ..} => ButtonState::Normal,
+            _ => {
+                handled = false;
+                self.state
+            }
+        };
+
+        if new_state != self.state {
+            self.widget_tag.request_redraw();
+            self.state = new_state;
+        }
+
+        EventOps {
+            focus: None,
+            bubble: event.default_bubble(),
+            handled,
+        }
+    }
+}
+
+impl<T: Num> Parent for NumberInput<T> {
+    fn num_children(&self) -> usize {
+        2
+    }
+
+    fn framed_child<R: Renderer>(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, R>> {
+        match widget_ident {
+            WidgetIdent::Num(0) => Some(WidgetInfo::new(WidgetIdent::Num(0), 0, &self.up)),
+            WidgetIdent::Num(1) => Some(WidgetInfo::new(WidgetIdent::Num(1), 1, &self.down)),
+            _ => None
+        }
+    }
+    fn framed_child_mut<R: Renderer>(&mut self, widget_ident: WidgetIdent) -> Option<WidgetInfoMut<'_, R>> {
+        match widget_ident {
+            WidgetIdent::Num(0) => Some(WidgetInfoMut::new(WidgetIdent::Num(0), 0, &mut self.up)),
+            WidgetIdent::Num(1) => Some(WidgetInfoMut::new(WidgetIdent::Num(1), 1, &mut self.down)),
+            _ => None
+        }
+    }
+
+    fn framed_children<'a, R, G>(&'a self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfo<'a, R>) -> LoopFlow
+    {
+        if let LoopFlow::Continue = for_each(WidgetInfo::new(WidgetIdent::Num(0), 0, &self.up)) {
+            let _ = for_each(WidgetInfo::new(WidgetIdent::Num(1), 1, &self.down));
+        }
+    }
+
+    fn framed_children_mut<'a, R, G>(&'a mut self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfoMut<'a, R>) -> LoopFlow
+    {
+        if let LoopFlow::Continue = for_each(WidgetInfoMut::new(WidgetIdent::Num(0), 0, &mut self.up)) {
+            let _ = for_each(WidgetInfoMut::new(WidgetIdent::Num(1), 1, &mut self.down));
+        }
+    }
+
+    fn framed_child_by_index<R: Renderer>(&self, index: usize) -> Option<WidgetInfo<'_, R>> {
+        match index {
+            0 => Some(WidgetInfo::new(WidgetIdent::Num(0), 0, &self.up)),
+            1 => Some(WidgetInfo::new(WidgetIdent::Num(1), 1, &self.down)),
+            _ => None
+        }
+    }
+    fn framed_child_by_index_mut<R: Renderer>(&mut self, index: usize) -> Option<WidgetInfoMut<'_, R>> {
+        match index {
+            0 => Some(WidgetInfoMut::new(WidgetIdent::Num(0), 0, &mut self.up)),
+            1 => Some(WidgetInfoMut::new(WidgetIdent::Num(1), 1, &mut self.down)),
+            _ => None
+        }
+    }
+}
+
+impl<R, T> WidgetRenderable<R> for NumberInput<T>
+    where R: Renderer,
+          T: Num
+{
+    type Theme = NumberInputTheme;
+    fn theme(&self) -> NumberInputTheme {
+        NumberInputTheme(())
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, layout: &mut R::Layout) {
+        layout.prepare_edit_string(
+            &mut self.edit.string,
+            &mut self.edit.cursor_data,
+            self.edit.cursor_ops.drain(..),
+        );
+
+        let control_height = layout.control_metrics().control_height;
+        let result = layout.finish();
+        self.size_bounds = result.size_bounds.expand_min(DimsBox::new2(0, control_height));
+
+        if self.commit_pending {
+            self.commit_pending = false;
+            self.commit();
+        }
+
+        // Stack the step buttons on the right edge of the input.
+        let button_width = self.up.size_bounds.min.width().max(self.down.size_bounds.min.width());
+        let (width, height) = (self.rect.width(), self.rect.height());
+        self.up.rect = BoundBox::new2(width - button_width, 0, width, height / 2);
+        self.down.rect = BoundBox::new2(width - button_width, height / 2, width, height);
+    }
+}
+
+impl<R> WidgetRenderable<R> for StepButton
+    where R: Renderer
+{
+    type Theme = NumberInputStepTheme;
+    fn theme(&self) -> NumberInputStepTheme {
+        NumberInputStepTheme {
+            state: self.state,
+            increment: self.steps > 0,
+        }
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, layout: &mut R::Layout) {
+        // The buttons don't have any content to measure, so they get their size from the theme.
+        let metrics = layout.control_metrics();
+        let result = layout.finish();
+        self.size_bounds = result.size_bounds.expand_min(DimsBox::new2(metrics.indicator_size, metrics.control_height / 2));
+    }
+}
+
+impl WidgetTheme for NumberInputTheme {
+    type Fallback = LineBoxTheme;
+    fn fallback(self) -> Option<LineBoxTheme> {
+        Some(LineBoxTheme::default())
+    }
+}
+
+impl WidgetTheme for NumberInputStepTheme {
+    type Fallback = ButtonTheme;
+    fn fallback(self) -> Option<ButtonTheme> {
+        Some(ButtonTheme{ state: self.state, mnemonic: None })
+    }
+}
+
+impl<T: Num> CharFilter for NumCharFilter<T> {
+    #[inline(always)]
+    fn char_allowed(&mut self, c: char) -> bool {
+        T::char_allowed(c)
+    }
+}
+
+impl<T> Default for NumCharFilter<T> {
+    fn default() -> NumCharFilter<T> {
+        NumCharFilter(PhantomData)
+    }
+}
+
+impl<T> Clone for NumCharFilter<T> {
+    fn clone(&self) -> NumCharFilter<T> {
+        NumCharFilter(PhantomData)
+    }
+}
+
+impl<T> fmt::Debug for NumCharFilter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_tuple("NumCharFilter").finish()
+    }
+}
+
+fn clamp<T: Num>(value: T, min: T, max: T) -> T {
+    if value < min {
+        min
+    } else if value > max {
+        max
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cgmath::Point2,
+        core::WindowEvent,
+        test_helpers::{click, headless_root, press_key, send_events},
+    };
+
+    #[test]
+    fn add_steps() {
+        assert_eq!(250u8, 10u8.add_steps(30, 8));
+        assert_eq!(255u8, 10u8.add_steps(30, 100));
+        assert_eq!(0u8, 3u8.add_steps(1, -5));
+        assert_eq!(-128i8, 0i8.add_steps(1, -300));
+        assert_eq!(127i8, 0i8.add_steps(-1, -300));
+        // Huge step counts don't take any longer than small ones.
+        assert_eq!(i64::max_value(), 0i64.add_steps(i64::max_value(), i32::max_value()));
+        assert_eq!(u64::min_value(), 5u64.add_steps(u64::max_value(), i32::min_value()));
+        assert_eq!(1.5f32, 0.5f32.add_steps(0.25, 4));
+    }
+
+    #[test]
+    fn clamp_to_range() {
+        let mut input = NumberInput::new(150, 1, 0, 100);
+        assert_eq!(100, input.value());
+
+        input.set_value(-5);
+        assert_eq!(0, input.value());
+
+        input.set_value(50);
+        input.set_range(0, 20);
+        assert_eq!(20, input.value());
+        assert_eq!("20", input.edit.string);
+    }
+
+    #[test]
+    fn step() {
+        let mut root = headless_root(NumberInput::new(5, 2, 0, 10), DimsBox::new2(64, 32));
+        click(&mut root, Point2::new(4, 4));
+
+        press_key(&mut root, Key::UArrow);
+        assert_eq!(7, root.root_widget.value());
+        press_key(&mut root, Key::DArrow);
+        press_key(&mut root, Key::DArrow);
+        assert_eq!(3, root.root_widget.value());
+        for _ in 0..4 {
+            press_key(&mut root, Key::UArrow);
+        }
+        assert_eq!(10, root.root_widget.value());
+        assert_eq!("10", root.root_widget.edit.string);
+
+        // The step buttons are stacked on the right edge of the input.
+        let (up, down) = (root.root_widget.up.rect, root.root_widget.down.rect);
+        assert!(up.width() > 0 && up.height() > 0);
+        assert_eq!(up.max.y, down.min.y);
+        click(&mut root, Point2::new(down.min.x + 1, down.min.y + 1));
+        assert_eq!(8, root.root_widget.value());
+        click(&mut root, Point2::new(up.min.x + 1, up.min.y + 1));
+        assert_eq!(10, root.root_widget.value());
+    }
+
+    #[test]
+    fn commit() {
+        let mut root = headless_root(NumberInput::new(5, 1, 0, 10), DimsBox::new2(64, 32));
+        // Place the cursor after the `5`.
+        click(&mut root, Point2::new(20, 4));
+        press_key(&mut root, Key::Back);
+        send_events(&mut root, &[WindowEvent::Char('4'), WindowEvent::Char('2')]);
+        assert_eq!("42", root.root_widget.edit.string);
+        // The value only changes once the text is committed, and gets clamped to the range.
+        assert_eq!(5, root.root_widget.value());
+        press_key(&mut root, Key::Enter);
+        assert_eq!(10, root.root_widget.value());
+        assert_eq!("10", root.root_widget.edit.string);
+
+        // Text that doesn't parse reverts to the last valid value.
+        press_key(&mut root, Key::Back);
+        press_key(&mut root, Key::Back);
+        send_events(&mut root, &[WindowEvent::Char('-')]);
+        press_key(&mut root, Key::Enter);
+        assert_eq!(10, root.root_widget.value());
+        assert_eq!("10", root.root_widget.edit.string);
+    }
+}
@@ -13,7 +13,7 @@ use crate::{
 };
 
 use crate::cgmath::Point2;
-use cgmath_geometry::{D2, Lerp, rect::BoundBox};
+use cgmath_geometry::{D2, Lerp, rect::{BoundBox, DimsBox}};
 
 
 #[derive(Debug, Clone)]
@@ -114,6 +114,7 @@ impl Widget for ProgressBar {
         EventOps {
             focus: None,
             bubble: true,
+            handled: false,
         }
     }
 }
@@ -139,6 +140,7 @@ impl Widget for ProgressBarFill {
         EventOps {
             focus: None,
             bubble: true,
+            handled: false,
         }
     }
 }
@@ -202,8 +204,9 @@ impl<R> WidgetRenderable<R> for ProgressBar
     }
 
     fn update_layout(&mut self, layout: &mut R::Layout) {
+        let thickness = layout.control_metrics().progress_bar_thickness;
         let result = layout.finish();
-        self.size_bounds = result.size_bounds;
+        self.size_bounds = result.size_bounds.expand_min(DimsBox::new2(0, thickness));
 
         let lerp_factor = self.value / (self.max-self.min);
         self.fill.rect = BoundBox {
@@ -12,7 +12,7 @@ use crate::{
     container::WidgetContainer,
     layout::GridLayout,
     widgets::{
-        Contents,
+        Contents, GroupTheme,
         assistants::toggle_button::{Toggle, ToggleOnClickHandler},
     },
 };
@@ -166,6 +166,11 @@ impl Widget for RadioButton {
         self.toggle.size_bounds()
     }
 
+    #[inline]
+    fn accepts_focus(&self) -> bool {
+        true
+    }
+
     fn on_widget_event(&mut self, event: WidgetEventSourced, state: InputState) -> EventOps {
         self.toggle.on_widget_event(event, state)
     }
@@ -202,6 +207,7 @@ impl<C, L> Widget for RadioButtonList<C, L>
         EventOps {
             focus: None,
             bubble: true,
+            handled: false,
         }
     }
 }
@@ -301,9 +307,13 @@ impl<R, C, L> WidgetRenderable<R> for RadioButtonList<C, L>
                 let widget_size_bounds = summary.widget().size_bounds();
                 let mut layout_hints = self.layout.positions(summary.ident, summary.index, num_children).unwrap_or(WidgetPos::default());
                 layout_hints.size_bounds = SizeBounds {
-                    min: layout_hints.size_bounds.bound_rect(widget_size_bounds.min),
-                    max: layout_hints.size_bounds.bound_rect(widget_size_bounds.max),
+                    min: layout_hints.size_bounds.clamp_dims(widget_size_bounds.min),
+                    max: layout_hints.size_bounds.clamp_dims(widget_size_bounds.max),
                 };
+                let min_intrinsic_size = summary.widget().min_intrinsic_size();
+                if min_intrinsic_size != widget_size_bounds.min {
+                    layout_hints.preferred_size = Some(layout_hints.size_bounds.clamp_dims(min_intrinsic_size));
+                }
                 hints_vec.push(layout_hints);
                 rects_vec.push(Ok(BoundBox::new2(0, 0, 0, 0)));
                 LoopFlow::Continue
@@ -334,6 +344,8 @@ impl WidgetTheme for RadioButtonTheme {
 }
 
 impl WidgetTheme for RadioButtonListTheme {
-    type Fallback = !;
-    fn fallback(self) -> Option<!> {None}
+    type Fallback = GroupTheme;
+    fn fallback(self) -> Option<GroupTheme> {
+        Some(GroupTheme::default())
+    }
 }
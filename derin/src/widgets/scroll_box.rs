@@ -6,10 +6,10 @@ use crate::{
     core::{
         LoopFlow,
         event::{EventOps, WidgetEvent, InputState, WidgetEventSourced},
+        timer::{Timer, TimerId},
         widget::{WidgetIdent, WidgetRenderable, WidgetTag, WidgetInfo, WidgetInfoMut, Widget, Parent},
-        render::Renderer,
+        render::{Renderer, RendererLayout, SubFrame, WidgetTheme},
     },
-    gl_render::{ThemedPrim, PrimFrame, RelPoint, Prim},
     widgets::Clip,
     widgets::assistants::SliderAssist,
 };
@@ -19,12 +19,11 @@ use cgmath_geometry::{D2, rect::{BoundBox, DimsBox, GeoBox}};
 use derin_common_types::layout::SizeBounds;
 use derin_common_types::buttons::MouseButton;
 
-use arrayvec::ArrayVec;
-
 use std::f32;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-const SCROLL_BAR_SIZE: i32 = 16;
+const INERTIA_TICK: Duration = Duration::from_millis(16);
 
 /// A widget that is used to apply scroll bars to a contained widget.
 ///
@@ -36,7 +35,125 @@ pub struct ScrollBox<W> {
     rect: BoundBox<D2, i32>,
     slider_x: Option<SliderAssist>,
     slider_y: Option<SliderAssist>,
-    clip: Clip<W>
+    clip: Clip<W>,
+    inertia: Option<ScrollInertia>,
+    inertia_state: InertiaState,
+    overscroll: Option<ScrollOverscroll>,
+    overscroll_state: OverscrollState,
+    page_repeat: ScrollPageRepeat,
+    trough_press: Option<TroughPress>,
+}
+
+/// The theme of a `ScrollBox`.
+///
+/// The renderer draws the scroll bars, as a track with a thumb on top of it. A bar is `None` if the
+/// content fits in the scroll box along that axis, in which case it shouldn't be drawn.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ScrollBoxTheme {
+    /// The bar along the bottom of the scroll box, which scrolls horizontally.
+    pub bar_x: Option<ScrollBarRects>,
+    /// The bar along the right side of the scroll box, which scrolls vertically.
+    pub bar_y: Option<ScrollBarRects>,
+}
+
+/// Where a `ScrollBox`'s scroll bar gets drawn, relative to the scroll box's origin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScrollBarRects {
+    /// The track the thumb slides along.
+    pub track: BoundBox<D2, i32>,
+    /// The part of the track covered by the thumb.
+    pub thumb: BoundBox<D2, i32>,
+}
+
+/// Configuration for inertial scrolling.
+///
+/// When enabled, releasing a scroll bar that was being dragged quickly keeps scrolling the
+/// content, slowing down until the velocity falls below `min_velocity` or the content hits an
+/// edge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrollInertia {
+    /// The proportion of scroll velocity lost each second, between `0.0` and `1.0`.
+    pub friction: f32,
+    /// The speed, in pixels per second, below which scrolling stops.
+    pub min_velocity: f32,
+}
+
+/// Configuration for rubber-band overscrolling.
+///
+/// When enabled, scrolling past the edge of the content moves the content past the edge with
+/// increasing resistance. Once the scrolling stops, the content springs back to the edge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrollOverscroll {
+    /// The furthest distance, in pixels, the content can be pulled past an edge.
+    pub max_distance: f32,
+    /// How quickly the content springs back to the edge. Each second, the overscroll distance is
+    /// multiplied by `e^-stiffness`.
+    pub stiffness: f32,
+}
+
+/// Configuration for paging through the content by holding the mouse button down in a scroll bar's
+/// trough.
+///
+/// Pressing the trough scrolls by one page immediately. If the button is still held after `delay`,
+/// the scroll box keeps paging every `rate` until the button is released or the bar's head reaches
+/// the mouse cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScrollPageRepeat {
+    /// How long the button must be held before paging starts repeating.
+    pub delay: Duration,
+    /// The time between repeated pages.
+    pub rate: Duration,
+}
+
+#[derive(Debug, Clone)]
+struct InertiaState {
+    /// Scroll velocity, in pixels per second.
+    velocity: Vector2<f32>,
+    last_sample: Option<(Instant, Vector2<f32>)>,
+    timer: Option<TimerId>,
+}
+
+#[derive(Debug, Clone)]
+struct OverscrollState {
+    /// The distance the content is currently pulled past the edge, in pixels.
+    offset: Vector2<f32>,
+    timer: Option<TimerId>,
+}
+
+#[derive(Debug, Clone)]
+struct TroughPress {
+    horizontal: bool,
+    direction: f32,
+    /// The most recent mouse position, used to check whether the cursor is still in the trough.
+    pos: Point2<i32>,
+    timer: TimerId,
+}
+
+impl Default for ScrollInertia {
+    fn default() -> ScrollInertia {
+        ScrollInertia {
+            friction: 0.95,
+            min_velocity: 10.0,
+        }
+    }
+}
+
+impl Default for ScrollOverscroll {
+    fn default() -> ScrollOverscroll {
+        ScrollOverscroll {
+            max_distance: 64.0,
+            stiffness: 12.0,
+        }
+    }
+}
+
+impl Default for ScrollPageRepeat {
+    fn default() -> ScrollPageRepeat {
+        ScrollPageRepeat {
+            delay: Duration::from_millis(400),
+            rate: Duration::from_millis(50),
+        }
+    }
 }
 
 impl<W> ScrollBox<W> {
@@ -47,8 +164,276 @@ impl<W> ScrollBox<W> {
             rect: BoundBox::new2(0, 0, 0, 0),
             clip: Clip::new(widget),
             slider_x: None,
-            slider_y: None
+            slider_y: None,
+            inertia: None,
+            inertia_state: InertiaState {
+                velocity: Vector2::new(0.0, 0.0),
+                last_sample: None,
+                timer: None,
+            },
+            overscroll: None,
+            overscroll_state: OverscrollState {
+                offset: Vector2::new(0.0, 0.0),
+                timer: None,
+            },
+            page_repeat: ScrollPageRepeat::default(),
+            trough_press: None,
+        }
+    }
+
+    /// Retrieves the inertial scrolling configuration. `None` if inertial scrolling is disabled.
+    pub fn inertia(&self) -> Option<ScrollInertia> {
+        self.inertia
+    }
+
+    /// Enables or disables inertial scrolling.
+    pub fn set_inertia(&mut self, inertia: Option<ScrollInertia>) {
+        if inertia.is_none() {
+            self.stop_inertia();
+        }
+        self.inertia = inertia;
+    }
+
+    /// Retrieves the overscroll configuration. `None` if overscrolling is disabled.
+    pub fn overscroll(&self) -> Option<ScrollOverscroll> {
+        self.overscroll
+    }
+
+    /// Enables or disables rubber-band overscrolling.
+    pub fn set_overscroll(&mut self, overscroll: Option<ScrollOverscroll>) {
+        if overscroll.is_none() {
+            self.stop_overscroll();
         }
+        self.overscroll = overscroll;
+    }
+
+    /// Retrieves the trough paging repeat configuration.
+    pub fn page_repeat(&self) -> ScrollPageRepeat {
+        self.page_repeat
+    }
+
+    /// Sets how paging repeats when the mouse button is held down in a scroll bar's trough.
+    pub fn set_page_repeat(&mut self, page_repeat: ScrollPageRepeat) {
+        self.page_repeat = page_repeat;
+    }
+
+    /// Scroll both axes by `delta` pixels. Any scrolling that would move past the content's edge
+    /// gets turned into overscroll, if overscrolling is enabled.
+    fn scroll_by(&mut self, delta: Vector2<f32>) {
+        let overscroll = self.overscroll;
+        let overscroll_offset = &mut self.overscroll_state.offset;
+
+        let scroll_axis = |slider: &mut Option<SliderAssist>, delta: f32, offset: &mut f32| {
+            if let Some(ref mut slider) = slider {
+                let unclamped = slider.value + delta;
+                slider.value = unclamped;
+                slider.round_to_step();
+                if let Some(overscroll) = overscroll {
+                    *offset = rubber_band(*offset, unclamped - slider.value, overscroll.max_distance);
+                }
+            }
+        };
+        scroll_axis(&mut self.slider_x, delta.x, &mut overscroll_offset.x);
+        scroll_axis(&mut self.slider_y, delta.y, &mut overscroll_offset.y);
+
+        self.start_overscroll_return();
+    }
+
+    fn start_overscroll_return(&mut self) {
+        if self.overscroll_state.offset == Vector2::new(0.0, 0.0) {
+            return;
+        }
+        self.widget_tag.request_relayout();
+        if self.overscroll_state.timer.is_none() {
+            let timer_id = TimerId::new();
+            self.widget_tag.timers_mut().insert(timer_id, Timer::new_delayed(INERTIA_TICK, Instant::now() + INERTIA_TICK));
+            self.overscroll_state.timer = Some(timer_id);
+        }
+    }
+
+    fn stop_overscroll(&mut self) {
+        if self.overscroll_state.offset != Vector2::new(0.0, 0.0) {
+            self.overscroll_state.offset = Vector2::new(0.0, 0.0);
+            self.widget_tag.request_relayout();
+        }
+        if let Some(timer_id) = self.overscroll_state.timer.take() {
+            self.widget_tag.timers_mut().remove(&timer_id);
+        }
+    }
+
+    /// Spring the overscrolled content back towards the edge.
+    fn overscroll_tick(&mut self, dt: f32) {
+        let overscroll = match self.overscroll {
+            Some(overscroll) => overscroll,
+            None => return
+        };
+        // Don't pull the content back while inertia is still pushing it out.
+        if self.inertia_state.timer.is_some() {
+            return;
+        }
+
+        let offset = self.overscroll_state.offset * (-overscroll.stiffness * dt).exp();
+        self.widget_tag.request_relayout();
+        match magnitude(offset) < 0.5 {
+            // Snap to the edge so that there isn't any residual offset.
+            true => self.stop_overscroll(),
+            false => self.overscroll_state.offset = offset
+        }
+    }
+
+    /// If `pos` lies in a scroll bar's trough, page that bar towards `pos` and start repeating the
+    /// page while the mouse button is held. Returns whether a trough was pressed.
+    fn press_trough(&mut self, pos: Point2<i32>) -> bool {
+        let trough = self.slider_x.as_ref().and_then(|s| s.trough_direction(pos)).map(|d| (true, d))
+            .or_else(|| self.slider_y.as_ref().and_then(|s| s.trough_direction(pos)).map(|d| (false, d)));
+        let (horizontal, direction) = match trough {
+            Some(trough) => trough,
+            None => return false
+        };
+
+        let timer = TimerId::new();
+        self.widget_tag.timers_mut().insert(timer, Timer::new_delayed(self.page_repeat.rate, Instant::now() + self.page_repeat.delay));
+        self.trough_press = Some(TroughPress{ horizontal, direction, pos, timer });
+        self.page_trough();
+        true
+    }
+
+    fn release_trough(&mut self) {
+        if let Some(trough_press) = self.trough_press.take() {
+            self.widget_tag.timers_mut().remove(&trough_press.timer);
+        }
+    }
+
+    /// Page the pressed scroll bar by the size of the visible area. Paging pauses while the cursor
+    /// is outside the trough, and stops entirely once the bar's head reaches the cursor.
+    fn page_trough(&mut self) {
+        let (horizontal, direction, pos) = match self.trough_press {
+            Some(ref trough_press) => (trough_press.horizontal, trough_press.direction, trough_press.pos),
+            None => return
+        };
+        let slider = match horizontal {
+            true => self.slider_x.as_mut(),
+            false => self.slider_y.as_mut()
+        };
+        let slider = match slider {
+            Some(slider) => slider,
+            None => {
+                self.release_trough();
+                return;
+            }
+        };
+
+        let page = match horizontal {
+            true => slider.bar_rect.width(),
+            false => slider.bar_rect.height()
+        } as f32;
+        let keep_paging = match slider.trough_direction(pos) {
+            Some(d) if d == direction => slider.page(direction, page),
+            _ => !slider.head_rect().contains(pos)
+        };
+        if !keep_paging {
+            self.release_trough();
+        }
+    }
+
+    fn scroll_values(&self) -> Vector2<f32> {
+        Vector2::new(
+            self.slider_x.as_ref().map(|s| s.value).unwrap_or(0.0),
+            self.slider_y.as_ref().map(|s| s.value).unwrap_or(0.0),
+        )
+    }
+
+    fn is_dragging(&self) -> bool {
+        self.slider_x.as_ref().and_then(|s| s.head_click_pos).is_some() ||
+        self.slider_y.as_ref().and_then(|s| s.head_click_pos).is_some()
+    }
+
+    /// Record the current scroll position, updating the tracked scroll velocity.
+    fn sample_velocity(&mut self) {
+        let now = Instant::now();
+        let values = self.scroll_values();
+        if let Some((last_time, last_values)) = self.inertia_state.last_sample {
+            let dt = duration_secs(now - last_time);
+            if dt > 0.0 {
+                // Smooth the velocity so that one jittery sample doesn't dominate the fling.
+                let new_velocity = (values - last_values) / dt;
+                self.inertia_state.velocity = self.inertia_state.velocity * 0.25 + new_velocity * 0.75;
+            }
+        }
+        self.inertia_state.last_sample = Some((now, values));
+    }
+
+    fn start_inertia(&mut self) {
+        let inertia = match self.inertia {
+            Some(inertia) => inertia,
+            None => return
+        };
+
+        // If the drag stopped a while before the mouse was released, there shouldn't be a fling.
+        let stale = self.inertia_state.last_sample
+            .map(|(t, _)| Instant::now() - t > INERTIA_TICK * 4)
+            .unwrap_or(true);
+        self.inertia_state.last_sample = None;
+        if stale || magnitude(self.inertia_state.velocity) < inertia.min_velocity {
+            self.inertia_state.velocity = Vector2::new(0.0, 0.0);
+            return;
+        }
+
+        if self.inertia_state.timer.is_none() {
+            let timer_id = TimerId::new();
+            self.widget_tag.timers_mut().insert(timer_id, Timer::new_delayed(INERTIA_TICK, Instant::now() + INERTIA_TICK));
+            self.inertia_state.timer = Some(timer_id);
+        }
+    }
+
+    fn stop_inertia(&mut self) {
+        self.inertia_state.velocity = Vector2::new(0.0, 0.0);
+        self.inertia_state.last_sample = None;
+        if let Some(timer_id) = self.inertia_state.timer.take() {
+            self.widget_tag.timers_mut().remove(&timer_id);
+        }
+    }
+
+    /// Apply one tick of inertial scrolling.
+    fn inertia_tick(&mut self, dt: f32) {
+        let inertia = match self.inertia {
+            Some(inertia) => inertia,
+            None => return
+        };
+        let velocity = self.inertia_state.velocity;
+        let overscroll = self.overscroll;
+        let overscroll_offset = &mut self.overscroll_state.offset;
+
+        let step_axis = |slider: &mut Option<SliderAssist>, velocity: f32, offset: &mut f32| -> f32 {
+            match slider {
+                Some(ref mut slider) => {
+                    let unclamped = slider.value + velocity * dt;
+                    slider.value = unclamped.min(slider.max).max(slider.min);
+                    if slider.value != unclamped {
+                        // Hitting an edge stops the axis dead, bouncing the content if
+                        // overscrolling is enabled.
+                        if let Some(overscroll) = overscroll {
+                            *offset = rubber_band(*offset, unclamped - slider.value, overscroll.max_distance);
+                        }
+                        0.0
+                    } else {
+                        velocity
+                    }
+                },
+                None => 0.0
+            }
+        };
+        let mut new_velocity = Vector2::new(
+            step_axis(&mut self.slider_x, velocity.x, &mut overscroll_offset.x),
+            step_axis(&mut self.slider_y, velocity.y, &mut overscroll_offset.y),
+        );
+        new_velocity *= (1.0 - inertia.friction).max(0.0).powf(dt);
+
+        self.inertia_state.velocity = new_velocity;
+        if magnitude(new_velocity) < inertia.min_velocity {
+            self.stop_inertia();
+        }
+        self.start_overscroll_return();
     }
 
     /// Retrieves the scrollable widget.
@@ -109,23 +494,37 @@ impl<W> Widget for ScrollBox<W>
         match event {
             WidgetEventSourced::This(ref event) => match event {
                 WidgetEvent::MouseDown{pos, in_widget: true, button: MouseButton::Left} => {
-                    if let Some(ref mut slider_x) = self.slider_x {
-                        slider_x.click_head(*pos);
-                    }
-                    if let Some(ref mut slider_y) = self.slider_y {
-                        slider_y.click_head(*pos);
+                    self.stop_inertia();
+                    self.stop_overscroll();
+                    if !self.press_trough(*pos) {
+                        if let Some(ref mut slider_x) = self.slider_x {
+                            slider_x.click_head(*pos);
+                        }
+                        if let Some(ref mut slider_y) = self.slider_y {
+                            slider_y.click_head(*pos);
+                        }
                     }
                     self.widget_tag.request_redraw();
                 },
                 WidgetEvent::MouseMove{new_pos, ..} => {
+                    if let Some(ref mut trough_press) = self.trough_press {
+                        trough_press.pos = *new_pos;
+                    }
                     if let Some(ref mut slider_x) = self.slider_x {
                         slider_x.move_head(new_pos.x);
                     }
                     if let Some(ref mut slider_y) = self.slider_y {
                         slider_y.move_head(new_pos.y);
                     }
+                    if self.inertia.is_some() && self.is_dragging() {
+                        self.sample_velocity();
+                    }
                 },
                 WidgetEvent::MouseUp{button: MouseButton::Left, ..} => {
+                    if self.is_dragging() {
+                        self.start_inertia();
+                    }
+                    self.release_trough();
                     if let Some(ref mut slider_x) = self.slider_x {
                         slider_x.head_click_pos = None;
                     }
@@ -134,45 +533,63 @@ impl<W> Widget for ScrollBox<W>
                     }
                     self.widget_tag.request_redraw();
                 },
+                WidgetEvent::Timer{timer_id, last_triggered, start_time, ..} if Some(*timer_id) == self.inertia_state.timer => {
+                    let dt = duration_secs(Instant::now() - last_triggered.unwrap_or(*start_time - INERTIA_TICK));
+                    self.inertia_tick(dt);
+                },
+                WidgetEvent::Timer{timer_id, last_triggered, start_time, ..} if Some(*timer_id) == self.overscroll_state.timer => {
+                    let dt = duration_secs(Instant::now() - last_triggered.unwrap_or(*start_time - INERTIA_TICK));
+                    self.overscroll_tick(dt);
+                },
+                WidgetEvent::Timer{timer_id, ..} if Some(*timer_id) == self.trough_press.as_ref().map(|t| t.timer) => {
+                    self.page_trough();
+                },
                 _ => ()
             },
             WidgetEventSourced::Bubble(ref event, _) => match event {
                 WidgetEvent::MouseScrollLines{dir, in_widget: true} => {
+                    self.stop_inertia();
                     allow_bubble = false;
-                    if let Some(ref mut slider_x) = self.slider_x {
-                        slider_x.value -= (24 * dir.x) as f32;
-                        slider_x.round_to_step();
-                    }
-                    if let Some(ref mut slider_y) = self.slider_y {
-                        slider_y.value -= (24 * dir.y) as f32;
-                        slider_y.round_to_step();
-                    }
+                    self.scroll_by(-dir.cast::<f32>().unwrap() * 24.0);
                 },
                 WidgetEvent::MouseScrollPx{dir, in_widget: true} => {
+                    self.stop_inertia();
                     allow_bubble = false;
-                    if let Some(ref mut slider_x) = self.slider_x {
-                        slider_x.value -= dir.x as f32;
-                        slider_x.round_to_step();
-                    }
-                    if let Some(ref mut slider_y) = self.slider_y {
-                        slider_y.value -= dir.y as f32;
-                        slider_y.round_to_step();
-                    }
+                    self.scroll_by(-dir.cast::<f32>().unwrap());
                 },
                 _ => ()
             }
         }
 
-        if values(&self.slider_x, &self.slider_y) != start_values {
+        let scrolled = values(&self.slider_x, &self.slider_y) != start_values;
+        if scrolled {
             self.widget_tag.request_redraw().request_relayout();
         }
         EventOps {
             focus: None,
             bubble: allow_bubble && event.default_bubble(),
+            handled: scrolled || !allow_bubble,
         }
     }
 }
 
+fn duration_secs(duration: Duration) -> f32 {
+    duration.as_secs() as f32 + duration.subsec_nanos() as f32 / 1_000_000_000.0
+}
+
+fn magnitude(v: Vector2<f32>) -> f32 {
+    (v.x * v.x + v.y * v.y).sqrt()
+}
+
+/// Move `offset` by `delta`, with resistance increasing as `offset` approaches `max_distance`.
+fn rubber_band(offset: f32, delta: f32, max_distance: f32) -> f32 {
+    if max_distance <= 0.0 {
+        return 0.0;
+    }
+    let resistance = (1.0 - offset.abs() / max_distance).max(0.0);
+    (offset + delta * resistance).min(max_distance).max(-max_distance)
+}
+
 lazy_static!{
     static ref CLIP_IDENT: WidgetIdent = WidgetIdent::Str(Arc::from("clip"));
 }
@@ -229,76 +646,25 @@ impl<W, R> WidgetRenderable<R> for ScrollBox<W>
     where W: Widget,
           R: Renderer
 {
-    fn render(&mut self, frame: &mut R::SubFrame) {
-        let mut primitives: ArrayVec<[_; 4]> = ArrayVec::new();
-
-        if let Some(slider_x) = self.slider_x.clone() {
-            primitives.push(ThemedPrim {
-                theme_path: "ScrollBackground",
-                min: Point2::new(
-                    RelPoint::new(-1.0, slider_x.bar_rect.min.x),
-                    RelPoint::new(-1.0, slider_x.bar_rect.min.y),
-                ),
-                max: Point2::new(
-                    RelPoint::new(-1.0, slider_x.bar_rect.max.x),
-                    RelPoint::new(-1.0, slider_x.bar_rect.max.y)
-                ),
-                prim: Prim::Image,
-                rect_px_out: None
-            });
-
-            let head_rect = slider_x.head_rect();
-
-            primitives.push(ThemedPrim {
-                theme_path: "ScrollBar",
-                min: Point2::new(
-                    RelPoint::new(-1.0, head_rect.min.x),
-                    RelPoint::new(-1.0, head_rect.min.y),
-                ),
-                max: Point2::new(
-                    RelPoint::new(-1.0, head_rect.max.x),
-                    RelPoint::new(-1.0, head_rect.max.y)
-                ),
-                prim: Prim::Image,
-                rect_px_out: None
-            });
-        }
-        if let Some(slider_y) = self.slider_y.clone() {
-            primitives.push(ThemedPrim {
-                theme_path: "ScrollBackground",
-                min: Point2::new(
-                    RelPoint::new(-1.0, slider_y.bar_rect.min.x),
-                    RelPoint::new(-1.0, slider_y.bar_rect.min.y),
-                ),
-                max: Point2::new(
-                    RelPoint::new(-1.0, slider_y.bar_rect.max.x),
-                    RelPoint::new(-1.0, slider_y.bar_rect.max.y)
-                ),
-                prim: Prim::Image,
-                rect_px_out: None
-            });
-
-            let head_rect = slider_y.head_rect();
-
-            primitives.push(ThemedPrim {
-                theme_path: "ScrollBar",
-                min: Point2::new(
-                    RelPoint::new(-1.0, head_rect.min.x),
-                    RelPoint::new(-1.0, head_rect.min.y),
-                ),
-                max: Point2::new(
-                    RelPoint::new(-1.0, head_rect.max.x),
-                    RelPoint::new(-1.0, head_rect.max.y)
-                ),
-                prim: Prim::Image,
-                rect_px_out: None
-            });
+    type Theme = ScrollBoxTheme;
+
+    fn theme(&self) -> ScrollBoxTheme {
+        let bar = |slider: &Option<SliderAssist>| slider.as_ref().map(|slider| ScrollBarRects {
+            track: slider.bar_rect,
+            thumb: slider.head_rect(),
+        });
+        ScrollBoxTheme {
+            bar_x: bar(&self.slider_x),
+            bar_y: bar(&self.slider_y),
         }
+    }
 
-        frame.upload_primitives(primitives.into_iter());
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
     }
 
-    fn update_layout(&mut self, _: &R::Theme) {
+    fn update_layout(&mut self, layout: &mut R::Layout) {
+        let bar_size = layout.control_metrics().slider_thickness;
         let child_size_bounds = self.clip.widget().size_bounds();
         let mut child_dims: DimsBox<D2, _> = self.rect.dims();
         let mut offset = Vector2 {
@@ -308,8 +674,8 @@ impl<W, R> WidgetRenderable<R> for ScrollBox<W>
 
         let (mut has_x_scroll, mut has_y_scroll) = (false, false);
         for _ in 0..2 {
-            let scroll_dims_x = child_dims.dims.x - SCROLL_BAR_SIZE * has_y_scroll as i32;
-            let scroll_dims_y = child_dims.dims.y - SCROLL_BAR_SIZE * has_x_scroll as i32;
+            let scroll_dims_x = child_dims.dims.x - bar_size * has_y_scroll as i32;
+            let scroll_dims_y = child_dims.dims.y - bar_size * has_x_scroll as i32;
             child_dims.dims.x = scroll_dims_x.max(child_size_bounds.min.width());
             child_dims.dims.y = scroll_dims_y.max(child_size_bounds.min.height());
 
@@ -318,8 +684,8 @@ impl<W, R> WidgetRenderable<R> for ScrollBox<W>
         }
 
         let clip_dims = DimsBox::new2(
-            self.rect.width() - SCROLL_BAR_SIZE * has_y_scroll as i32,
-            self.rect.height() - SCROLL_BAR_SIZE * has_x_scroll as i32,
+            self.rect.width() - bar_size * has_y_scroll as i32,
+            self.rect.height() - bar_size * has_x_scroll as i32,
         );
 
         offset.x = offset.x.min((child_dims.width() as u32).saturating_sub(clip_dims.width() as u32) as i32);
@@ -334,9 +700,9 @@ impl<W, R> WidgetRenderable<R> for ScrollBox<W>
                 min: 0.0,
                 max: (child_dims.width() - clip_dims.width()) as f32,
 
-                head_size: 16.max(clip_dims.width().pow(2) / child_dims.width()), // TODO: PROPER HEIGHT CALCULATION
+                head_size: bar_size.max(clip_dims.width().pow(2) / child_dims.width()),
                 bar_rect: BoundBox::new2(
-                    0, self_dims.height() - SCROLL_BAR_SIZE,
+                    0, self_dims.height() - bar_size,
                     clip_dims.width(), self_dims.height()
                 ),
                 head_click_pos: self.slider_x.as_ref().and_then(|s| s.head_click_pos),
@@ -351,9 +717,9 @@ impl<W, R> WidgetRenderable<R> for ScrollBox<W>
                 min: 0.0,
                 max: (child_dims.height() - clip_dims.height()) as f32,
 
-                head_size: 16.max(clip_dims.height().pow(2) / child_dims.height()),
+                head_size: bar_size.max(clip_dims.height().pow(2) / child_dims.height()),
                 bar_rect: BoundBox::new2(
-                    self_dims.width() - SCROLL_BAR_SIZE, 0,
+                    self_dims.width() - bar_size, 0,
                     self_dims.width(), clip_dims.height()
                 ),
                 head_click_pos: self.slider_y.as_ref().and_then(|s| s.head_click_pos),
@@ -362,6 +728,126 @@ impl<W, R> WidgetRenderable<R> for ScrollBox<W>
         };
 
         *self.clip.rect_mut() = BoundBox::from(clip_dims);
-        *self.clip.widget_mut().rect_mut() = BoundBox::from(child_dims) - offset;
+        let overscroll_offset = self.overscroll_state.offset.cast::<i32>().unwrap_or(Vector2::new(0, 0));
+        *self.clip.widget_mut().rect_mut() = BoundBox::from(child_dims) - offset - overscroll_offset;
+    }
+}
+
+impl WidgetTheme for ScrollBoxTheme {
+    type Fallback = !;
+    fn fallback(self) -> Option<!> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::Spacer;
+    use derin_core::{
+        Root,
+        render::headless::{HeadlessRenderer, TextMetrics},
+    };
+
+    /// A 100x100 scroll box around 400x400 pixels of content, with both scroll bars showing.
+    fn scroll_root() -> Root<ScrollBox<Spacer>, HeadlessRenderer> {
+        let content = Spacer::new(SizeBounds::new_min(DimsBox::new2(400, 400)));
+        let mut root = Root::new(ScrollBox::new(content), (), HeadlessRenderer::new(TextMetrics::default()), DimsBox::new2(100, 100));
+        root.relayout();
+        root
+    }
+
+    #[test]
+    fn layout() {
+        let root = scroll_root();
+        let scroll_box = &root.root_widget;
+        let theme = WidgetRenderable::<HeadlessRenderer>::theme(scroll_box);
+
+        assert_eq!(Some(BoundBox::new2(0, 84, 84, 100)), theme.bar_x.map(|b| b.track));
+        assert_eq!(Some(BoundBox::new2(84, 0, 100, 84)), theme.bar_y.map(|b| b.track));
+        assert_eq!(Some(BoundBox::new2(84, 0, 100, 17)), theme.bar_y.map(|b| b.thumb));
+        assert_eq!(BoundBox::new2(0, 0, 84, 84), scroll_box.clip.rect());
+        assert_eq!(BoundBox::new2(0, 0, 400, 400), scroll_box.widget().rect());
+    }
+
+    #[test]
+    fn rubber_band_resistance() {
+        assert_eq!(32.0, rubber_band(0.0, 32.0, 64.0));
+        assert_eq!(48.0, rubber_band(32.0, 32.0, 64.0));
+        assert_eq!(-48.0, rubber_band(-32.0, -32.0, 64.0));
+        // The offset never goes past the maximum distance.
+        assert_eq!(64.0, rubber_band(60.0, 100.0, 64.0));
+        assert_eq!(0.0, rubber_band(0.0, 32.0, 0.0));
+    }
+
+    #[test]
+    fn inertia_stops_at_edge() {
+        let mut root = scroll_root();
+        {
+            let scroll_box = &mut root.root_widget;
+            scroll_box.set_inertia(Some(ScrollInertia{ friction: 0.5, min_velocity: 10.0 }));
+            scroll_box.inertia_state.velocity = Vector2::new(0.0, 1000.0);
+            scroll_box.inertia_state.timer = Some(TimerId::new());
+
+            scroll_box.inertia_tick(0.1);
+            assert_eq!(100.0, scroll_box.scroll_values().y);
+            assert!(scroll_box.inertia_state.velocity.y < 1000.0);
+            assert!(scroll_box.inertia_state.timer.is_some());
+
+            // Running into the bottom of the content stops scrolling dead, without bouncing.
+            scroll_box.inertia_tick(1.0);
+            assert_eq!(316.0, scroll_box.scroll_values().y);
+            assert_eq!(Vector2::new(0.0, 0.0), scroll_box.inertia_state.velocity);
+            assert_eq!(None, scroll_box.inertia_state.timer);
+            assert_eq!(Vector2::new(0.0, 0.0), scroll_box.overscroll_state.offset);
+        }
+
+        root.relayout();
+        assert_eq!(BoundBox::new2(0, -316, 400, 84), root.root_widget.widget().rect());
+    }
+
+    #[test]
+    fn overscroll_snaps_back() {
+        let mut root = scroll_root();
+        root.root_widget.set_overscroll(Some(ScrollOverscroll::default()));
+        root.root_widget.scroll_by(Vector2::new(0.0, -32.0));
+        assert_eq!(0.0, root.root_widget.scroll_values().y);
+        assert_eq!(Vector2::new(0.0, -32.0), root.root_widget.overscroll_state.offset);
+        assert!(root.root_widget.overscroll_state.timer.is_some());
+
+        // The content gets pulled down past the top edge.
+        root.relayout();
+        assert_eq!(BoundBox::new2(0, 32, 400, 432), root.root_widget.widget().rect());
+
+        // Once the offset is small enough, the content snaps to the edge exactly.
+        root.root_widget.overscroll_tick(1.0);
+        assert_eq!(Vector2::new(0.0, 0.0), root.root_widget.overscroll_state.offset);
+        assert_eq!(None, root.root_widget.overscroll_state.timer);
+        root.relayout();
+        assert_eq!(BoundBox::new2(0, 0, 400, 400), root.root_widget.widget().rect());
+    }
+
+    #[test]
+    fn trough_paging_repeats_until_thumb_reaches_cursor() {
+        let mut root = scroll_root();
+        let scroll_box = &mut root.root_widget;
+
+        // Pressing the trough pages once immediately.
+        assert!(scroll_box.press_trough(Point2::new(92, 60)));
+        assert_eq!(84.0, scroll_box.scroll_values().y);
+        assert!(scroll_box.trough_press.is_some());
+
+        scroll_box.page_trough();
+        assert_eq!(168.0, scroll_box.scroll_values().y);
+        scroll_box.page_trough();
+        assert_eq!(252.0, scroll_box.scroll_values().y);
+
+        // The thumb now covers the cursor, so paging stops.
+        scroll_box.page_trough();
+        assert_eq!(252.0, scroll_box.scroll_values().y);
+        assert!(scroll_box.trough_press.is_none());
+
+        // Presses on the thumb don't page.
+        assert!(!scroll_box.press_trough(Point2::new(92, 60)));
     }
 }
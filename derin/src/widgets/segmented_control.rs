@@ -0,0 +1,536 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use derin_core::{
+    LoopFlow,
+    widget::{Parent, Widget, WidgetInfo, WidgetInfoMut, WidgetIdent, WidgetTag, WidgetRenderable, MessageTarget},
+    render::{Renderer, RendererLayout, SubFrame, WidgetTheme},
+};
+use crate::{
+    event::{EventOps, FocusChange, WidgetEvent, WidgetEventSourced, InputState, Key, MouseHoverChange},
+    widgets::{
+        Contents, GroupTheme, ToggleButtonTheme,
+        assistants::ButtonState,
+    },
+};
+
+use cgmath_geometry::{D2, rect::{BoundBox, DimsBox, GeoBox}};
+use derin_common_types::layout::SizeBounds;
+
+/// A row of connected buttons, exactly one of which is selected at a time.
+///
+/// Each segment is associated with a value. When the user selects a different segment, by either
+/// clicking it or pressing the left and right arrow keys while the control has keyboard focus,
+/// a [`SelectionChanged`] action containing the segment's value is sent to the application
+/// through `WidgetTag::send_action`.
+///
+/// Segments are stretched to the same width, and are drawn with a [`SegmentTheme`] that tells
+/// the theme where the segment lies in the row, so that neighboring segments can share borders
+/// and only the outer corners get rounded.
+///
+/// [`SelectionChanged`]: ./struct.SelectionChanged.html
+/// [`SegmentTheme`]: ./struct.SegmentTheme.html
+#[derive(Debug, Clone)]
+pub struct SegmentedControl<T> {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    size_bounds: SizeBounds,
+    segments: Vec<Segment>,
+    values: Vec<T>,
+    selected: usize,
+}
+
+/// The action sent by a [`SegmentedControl`] when the selected segment changes, containing the
+/// value of the newly-selected segment.
+///
+/// [`SegmentedControl`]: ./struct.SegmentedControl.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SelectionChanged<T>(pub T);
+
+/// Where a segment lies within its [`SegmentedControl`].
+///
+/// [`SegmentedControl`]: ./struct.SegmentedControl.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SegmentPosition {
+    /// The segment is the only one in the control, so all of its corners are outer corners.
+    Only,
+    /// The segment is on the left end of the control.
+    First,
+    /// The segment has neighbors on both sides.
+    Middle,
+    /// The segment is on the right end of the control.
+    Last,
+}
+
+#[derive(Debug, Clone)]
+struct Segment {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    size_bounds: SizeBounds,
+    index: usize,
+    contents: Contents,
+    position: SegmentPosition,
+    selected: bool,
+    state: ButtonState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SegmentClicked(usize);
+
+#[derive(Debug, Clone, Default)]
+pub struct SegmentedControlTheme(());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SegmentTheme {
+    pub state: ButtonState,
+    pub selected: bool,
+    pub position: SegmentPosition,
+}
+
+impl<T> SegmentedControl<T>
+    where T: 'static + Clone
+{
+    /// Creates a new segmented control, with each segment displaying the given contents and
+    /// associated with the given value.
+    ///
+    /// `selected` is clamped to the number of segments.
+    pub fn new(segments: Vec<(Contents, T)>, selected: usize) -> SegmentedControl<T> {
+        let mut widget_tag = WidgetTag::new();
+        widget_tag.register_message(Self::on_segment_clicked);
+
+        let num_segments = segments.len();
+        let selected = selected.min(num_segments.saturating_sub(1));
+        let (contents, values): (Vec<_>, Vec<_>) = segments.into_iter().unzip();
+        let segments = contents.into_iter().enumerate().map(|(index, contents)| Segment {
+            widget_tag: WidgetTag::new(),
+            rect: BoundBox::new2(0, 0, 0, 0),
+            size_bounds: SizeBounds::default(),
+            index,
+            contents,
+            position: match (index, num_segments) {
+                (_, 1) => SegmentPosition::Only,
+                (0, _) => SegmentPosition::First,
+                (i, n) if i == n - 1 => SegmentPosition::Last,
+                _ => SegmentPosition::Middle
+            },
+            selected: index == selected,
+            state: ButtonState::Normal,
+        }).collect();
+
+        SegmentedControl {
+            widget_tag,
+            rect: BoundBox::new2(0, 0, 0, 0),
+            size_bounds: SizeBounds::default(),
+            segments,
+            values,
+            selected,
+        }
+    }
+
+    /// Retrieves the index of the selected segment.
+    #[inline]
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Retrieves the value of the selected segment, or `None` if the control has no segments.
+    pub fn selected_value(&self) -> Option<&T> {
+        self.values.get(self.selected)
+    }
+
+    /// Select the segment at `index`. Unlike selections made by the user, this doesn't send a
+    /// `SelectionChanged` action.
+    ///
+    /// Does nothing if `index` is out of range.
+    pub fn set_selected(&mut self, index: usize) {
+        if index >= self.segments.len() || index == self.selected {
+            return;
+        }
+
+        self.segments[self.selected].set_selected(false);
+        self.segments[index].set_selected(true);
+        self.selected = index;
+    }
+
+    /// Retrieves the contents of the segment at `index`.
+    pub fn contents(&self, index: usize) -> Option<&Contents> {
+        self.segments.get(index).map(|s| &s.contents)
+    }
+
+    /// Retrieves the contents of the segment at `index`, for mutation.
+    ///
+    /// Calling this function forces the control to be re-laid out, so you're discouraged from
+    /// calling it unless you're actually changing the contents.
+    pub fn contents_mut(&mut self, index: usize) -> Option<&mut Contents> {
+        let segment = self.segments.get_mut(index)?;
+        segment.widget_tag.request_redraw().request_relayout();
+        self.widget_tag.request_relayout();
+        Some(&mut segment.contents)
+    }
+
+    /// Select the segment at `index` and tell the application about it.
+    fn select(&mut self, index: usize) {
+        if index < self.segments.len() && index != self.selected {
+            self.set_selected(index);
+            self.widget_tag.send_action(SelectionChanged(self.values[index].clone()));
+        }
+    }
+
+    fn on_segment_clicked(&mut self, message: &SegmentClicked) {
+        self.select(message.0);
+    }
+}
+
+impl Segment {
+    fn set_selected(&mut self, selected: bool) {
+        if self.selected != selected {
+            self.selected = selected;
+            self.widget_tag.request_redraw();
+        }
+    }
+}
+
+impl<T> Widget for SegmentedControl<T>
+    where T: 'static + Clone
+{
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        self.widget_tag.request_relayout();
+        &mut self.rect
+    }
+
+    #[inline]
+    fn size_bounds(&self) -> SizeBounds {
+        self.size_bounds
+    }
+
+    #[inline]
+    fn accepts_focus(&self) -> bool {
+        true
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        let event = event.unwrap();
+        let mut bubble = event.default_bubble();
+
+        if let WidgetEvent::KeyDown(key, _) = event {
+            match key {
+                Key::LArrow => {
+                    if let Some(index) = self.selected.checked_sub(1) {
+                        self.select(index);
+                    }
+                    bubble = false;
+                },
+                Key::RArrow => {
+                    self.select(self.selected + 1);
+                    bubble = false;
+                },
+                _ => ()
+            }
+        }
+
+        EventOps {
+            focus: None,
+            handled: !bubble && event.default_bubble(),
+            bubble,
+        }
+    }
+}
+
+impl Widget for Segment {
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        &mut self.rect
+    }
+
+    #[inline]
+    fn size_bounds(&self) -> SizeBounds {
+        self.size_bounds
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        use self::WidgetEvent::*;
+        let event = event.unwrap();
+        let widget_id = self.widget_tag.widget_id();
+        let mut focus = None;
+
+        let mut handled = true;
+        let new_state = match event {
+            MouseMove{hover_change: Some(ref change), ..} => match change {
+                MouseHoverChange::Enter => ButtonState::Hover,
+                MouseHoverChange::Exit => ButtonState::Normal,
+                _ => self.state
+            },
+            MouseDown{..} => {
+                // Give the control focus, so that the arrow keys move the selection.
+                focus = Some(FocusChange::Parent);
+                ButtonState::Pressed
+            },
+            MouseUp{in_widget: true, pressed_in_widget: true, ..} => ButtonState::Hover,
+            Click{..} => {
+                self.widget_tag.send_message_to(SegmentClicked(self.index), MessageTarget::ParentOf(widget_id));
+                self.state
+            },
+            MouseUp{in_widget: false, ..} => ButtonState::Normal,
+            _ => {
+                handled = false;
+                self.state
+            }
+        };
+
+        if new_state != self.state {
+            self.widget_tag.request_redraw();
+            self.state = new_state;
+        }
+
+        EventOps {
+            focus,
+            bubble: event.default_bubble(),
+            handled,
+        }
+    }
+}
+
+impl<T> Parent for SegmentedControl<T>
+    where T: 'static + Clone
+{
+    fn num_children(&self) -> usize {
+        self.segments.len()
+    }
+
+    fn framed_child<R: Renderer>(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, R>> {
+        match widget_ident {
+            WidgetIdent::Num(index) => self.framed_child_by_index(index as usize),
+            _ => None
+        }
+    }
+    fn framed_child_mut<R: Renderer>(&mut self, widget_ident: WidgetIdent) -> Option<WidgetInfoMut<'_, R>> {
+        match widget_ident {
+            WidgetIdent::Num(index) => self.framed_child_by_index_mut(index as usize),
+            _ => None
+        }
+    }
+
+    fn framed_children<'a, R, G>(&'a self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfo<'a, R>) -> LoopFlow
+    {
+        for (index, segment) in self.segments.iter().enumerate() {
+            if let LoopFlow::Break = for_each(WidgetInfo::new(WidgetIdent::Num(index as u32), index, segment)) {
+                return;
+            }
+        }
+    }
+
+    fn framed_children_mut<'a, R, G>(&'a mut self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfoMut<'a, R>) -> LoopFlow
+    {
+        for (index, segment) in self.segments.iter_mut().enumerate() {
+            if let LoopFlow::Break = for_each(WidgetInfoMut::new(WidgetIdent::Num(index as u32), index, segment)) {
+                return;
+            }
+        }
+    }
+
+    fn framed_child_by_index<R: Renderer>(&self, index: usize) -> Option<WidgetInfo<'_, R>> {
+        self.segments.get(index).map(|s| WidgetInfo::new(WidgetIdent::Num(index as u32), index, s))
+    }
+    fn framed_child_by_index_mut<R: Renderer>(&mut self, index: usize) -> Option<WidgetInfoMut<'_, R>> {
+        self.segments.get_mut(index).map(|s| WidgetInfoMut::new(WidgetIdent::Num(index as u32), index, s))
+    }
+}
+
+impl<R, T> WidgetRenderable<R> for SegmentedControl<T>
+    where R: Renderer,
+          T: 'static + Clone
+{
+    type Theme = SegmentedControlTheme;
+    fn theme(&self) -> SegmentedControlTheme {
+        SegmentedControlTheme(())
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, _: &mut R::Layout) {
+        // Give every segment the same width, which is at least as wide as the widest segment's
+        // contents. The last segment picks up any remainder, so the segments fill the control.
+        let num_segments = self.segments.len() as i32;
+        let min_width = self.segments.iter().map(|s| s.size_bounds.min.width()).max().unwrap_or(0);
+        let min_height = self.segments.iter().map(|s| s.size_bounds.min.height()).max().unwrap_or(0);
+        self.size_bounds = SizeBounds::new_min(DimsBox::new2(min_width * num_segments, min_height));
+
+        let width = match num_segments {
+            0 => 0,
+            _ => i32::max(min_width, self.rect.width() / num_segments)
+        };
+        let height = i32::max(min_height, self.rect.height());
+        for (index, segment) in self.segments.iter_mut().enumerate() {
+            let x = index as i32 * width;
+            let right = match index as i32 == num_segments - 1 {
+                true => i32::max(x + width, self.rect.width()),
+                false => x + width
+            };
+            segment.rect = BoundBox::new2(x, 0, right, height);
+        }
+    }
+}
+
+impl<R> WidgetRenderable<R> for Segment
+    where R: Renderer
+{
+    type Theme = SegmentTheme;
+    fn theme(&self) -> SegmentTheme {
+        SegmentTheme {
+            state: self.state,
+            selected: self.selected,
+            position: self.position,
+        }
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, layout: &mut R::Layout) {
+        match self.contents {
+            Contents::Text(ref s) => layout.prepare_string(s),
+            Contents::Icon(ref i) => layout.prepare_icon(i),
+            Contents::Rich(ref r) => layout.prepare_rich_string(r),
+        }
+
+        let control_height = layout.control_metrics().control_height;
+        let result = layout.finish();
+        self.size_bounds = result.size_bounds.expand_min(DimsBox::new2(0, control_height));
+    }
+}
+
+impl WidgetTheme for SegmentedControlTheme {
+    type Fallback = GroupTheme;
+    fn fallback(self) -> Option<GroupTheme> {
+        Some(GroupTheme::default())
+    }
+}
+
+impl WidgetTheme for SegmentTheme {
+    type Fallback = ToggleButtonTheme;
+    fn fallback(self) -> Option<ToggleButtonTheme> {
+        Some(ToggleButtonTheme{ toggled: self.selected, state: self.state })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cgmath::Point2,
+        core::{Root, render::headless::HeadlessRenderer},
+        test_helpers::{click, headless_root, press_key},
+    };
+
+    type TestControl = SegmentedControl<&'static str>;
+
+    fn control() -> TestControl {
+        SegmentedControl::new(vec![
+            (Contents::Text("One".to_string()), "one"),
+            (Contents::Text("Two".to_string()), "two"),
+            (Contents::Text("Three".to_string()), "three"),
+        ], 0)
+    }
+
+    fn actions(root: &mut Root<TestControl, HeadlessRenderer>) -> Vec<&'static str> {
+        root.take_actions()
+            .map(|action| action.downcast::<SelectionChanged<&'static str>>().unwrap().0)
+            .collect()
+    }
+
+    #[test]
+    fn layout() {
+        let root = headless_root(control(), DimsBox::new2(200, 100));
+        let control = &root.root_widget;
+
+        // Every segment is as wide as the widest one's contents, and the last one fills the rest.
+        assert_eq!(DimsBox::new2(120, 24), control.size_bounds().min);
+        let rects = control.segments.iter().map(|s| s.rect).collect::<Vec<_>>();
+        assert_eq!(vec![
+            BoundBox::new2(0, 0, 66, 100),
+            BoundBox::new2(66, 0, 132, 100),
+            BoundBox::new2(132, 0, 200, 100),
+        ], rects);
+
+        let positions = control.segments.iter().map(|s| s.position).collect::<Vec<_>>();
+        assert_eq!(vec![SegmentPosition::First, SegmentPosition::Middle, SegmentPosition::Last], positions);
+        let only = SegmentedControl::new(vec![(Contents::Text("One".to_string()), ())], 5);
+        assert_eq!(SegmentPosition::Only, only.segments[0].position);
+        assert_eq!(0, only.selected());
+    }
+
+    #[test]
+    fn click_segment() {
+        let mut root = headless_root(control(), DimsBox::new2(200, 100));
+        click(&mut root, Point2::new(100, 50));
+
+        assert_eq!(1, root.root_widget.selected());
+        assert_eq!(Some(&"two"), root.root_widget.selected_value());
+        assert_eq!(vec![false, true, false], root.root_widget.segments.iter().map(|s| s.selected).collect::<Vec<_>>());
+        assert_eq!(vec!["two"], actions(&mut root));
+
+        // Clicking the selected segment again doesn't change anything.
+        click(&mut root, Point2::new(100, 50));
+        assert_eq!(0, actions(&mut root).len());
+    }
+
+    #[test]
+    fn arrow_keys() {
+        let mut root = headless_root(control(), DimsBox::new2(200, 100));
+        // Clicking a segment gives the control focus.
+        click(&mut root, Point2::new(10, 50));
+        assert_eq!(0, actions(&mut root).len());
+
+        press_key(&mut root, Key::RArrow);
+        press_key(&mut root, Key::RArrow);
+        assert_eq!(2, root.root_widget.selected());
+        // The selection doesn't wrap around the ends of the control.
+        press_key(&mut root, Key::RArrow);
+        assert_eq!(2, root.root_widget.selected());
+        press_key(&mut root, Key::LArrow);
+        assert_eq!(1, root.root_widget.selected());
+        assert_eq!(vec!["two", "three", "two"], actions(&mut root));
+    }
+
+    #[test]
+    fn set_selected() {
+        let mut root = headless_root(control(), DimsBox::new2(200, 100));
+        root.root_widget.set_selected(2);
+        assert_eq!(2, root.root_widget.selected());
+        assert!(root.root_widget.segments[2].selected && !root.root_widget.segments[0].selected);
+
+        // Out-of-range indices are ignored, and selecting programmatically doesn't send actions.
+        root.root_widget.set_selected(3);
+        assert_eq!(2, root.root_widget.selected());
+        assert_eq!(0, actions(&mut root).len());
+    }
+}
@@ -4,7 +4,9 @@
 
 use std::ops::RangeInclusive;
 use derin_core::{
-    widget::{WidgetTag, WidgetRenderable, Widget},
+    LoopFlow,
+    accessibility::{AccessibilityNode, AccessibilityRole},
+    widget::{WidgetIdent, WidgetInfo, WidgetInfoMut, WidgetTag, WidgetRenderable, Widget, Parent},
     render::{Renderer, RendererLayout, SubFrame, WidgetTheme},
 };
 use derin_common_types::layout::SizeBounds;
@@ -14,7 +16,7 @@ use crate::{
 
 use cgmath_geometry::{
     Lerp, D2,
-    rect::{BoundBox, GeoBox, OffsetBox}
+    rect::{BoundBox, DimsBox, GeoBox, OffsetBox}
 };
 
 pub trait SliderHandler: 'static {
@@ -31,17 +33,34 @@ pub trait SliderHandler: 'static {
 /// * `min` and `max`: Controls the minimum and maximum values that can be selected by the slider.
 ///
 /// Whenever the slider's head is moved, the provided handler's [`on_move`] function is called.
+/// The head is moved by dragging it.
+///
+/// Scrolling the mouse wheel over the slider moves the value by `step` for each line scrolled. The
+/// slider consumes the scroll event, so ancestors such as a `ScrollBox` don't see it. If the
+/// slider lives in a scrollable container, you may want to disable this with
+/// [`set_wheel_changes_value`] so that the wheel scrolls the container instead.
 ///
 /// [`on_move`]: ./trait.SliderHandler.html#tymethod.on_move
+/// [`set_wheel_changes_value`]: ./struct.Slider.html#method.set_wheel_changes_value
 #[derive(Debug, Clone)]
 pub struct Slider<H: SliderHandler> {
     widget_tag: WidgetTag,
     rect: BoundBox<D2, i32>,
     size_bounds: SizeBounds,
+    wheel_changes_value: bool,
 
     handle: SliderHandle<H>,
 }
 
+/// The direction a slider's track runs in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SliderOrientation {
+    /// The track runs from the minimum value on the left to the maximum value on the right.
+    Horizontal,
+    /// The track runs from the minimum value at the top to the maximum value at the bottom.
+    Vertical,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct SliderTheme(());
 #[derive(Debug, Clone, Default)]
@@ -59,6 +78,7 @@ struct SliderHandle<H: SliderHandler> {
 
     click_pos: Option<i32>,
     pixel_range: RangeInclusive<i32>,
+    orientation: SliderOrientation,
 
     handler: H,
 }
@@ -70,6 +90,7 @@ impl<H: SliderHandler> Slider<H> {
             widget_tag: WidgetTag::new(),
             rect: BoundBox::new2(0, 0, 0, 0),
             size_bounds: SizeBounds::default(),
+            wheel_changes_value: true,
 
             handle: SliderHandle {
                 widget_tag: WidgetTag::new(),
@@ -82,6 +103,7 @@ impl<H: SliderHandler> Slider<H> {
 
                 click_pos: None,
                 pixel_range: 0..=0,
+                orientation: SliderOrientation::Horizontal,
 
                 handler,
             },
@@ -109,6 +131,34 @@ impl<H: SliderHandler> Slider<H> {
         self.handle.step
     }
 
+    /// The direction the slider's track runs in. Defaults to `SliderOrientation::Horizontal`.
+    #[inline]
+    pub fn orientation(&self) -> SliderOrientation {
+        self.handle.orientation
+    }
+
+    /// Sets the direction the slider's track runs in.
+    #[inline]
+    pub fn set_orientation(&mut self, orientation: SliderOrientation) {
+        if self.handle.orientation != orientation {
+            self.handle.orientation = orientation;
+            self.widget_tag.request_redraw().request_relayout();
+        }
+    }
+
+    /// Whether or not scrolling the mouse wheel over the slider changes its value.
+    #[inline]
+    pub fn wheel_changes_value(&self) -> bool {
+        self.wheel_changes_value
+    }
+
+    /// Sets whether or not scrolling the mouse wheel over the slider changes its value. If
+    /// disabled, scroll events are passed on to the slider's ancestors.
+    #[inline]
+    pub fn set_wheel_changes_value(&mut self, wheel_changes_value: bool) {
+        self.wheel_changes_value = wheel_changes_value;
+    }
+
     /// Retrieves the value stored in the slider, for mutation.
     ///
     /// Calling this function forces the slider to be re-drawn, so you're discouraged from calling
@@ -155,14 +205,112 @@ impl<H> Widget for Slider<H>
 
     #[inline]
     fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        // The track and head get sized from the rect.
+        self.widget_tag.request_relayout();
         &mut self.rect
     }
 
     #[inline]
-    fn on_widget_event(&mut self, _: WidgetEventSourced, _: InputState) -> EventOps {
+    fn size_bounds(&self) -> SizeBounds {
+        self.size_bounds
+    }
+
+    #[inline]
+    fn accepts_focus(&self) -> bool {
+        true
+    }
+
+    fn accessibility_info(&self) -> Option<AccessibilityNode> {
+        Some(AccessibilityNode {
+            value: Some(self.value().to_string()),
+            ..AccessibilityNode::new(AccessibilityRole::Slider)
+        })
+    }
+
+    #[inline]
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        // Scroll events over the head get bubbled up to us, so handle both sources.
+        let steps = match (self.wheel_changes_value, event.clone().unwrap()) {
+            (true, WidgetEvent::MouseScrollLines{dir, in_widget: true}) => dir.x + dir.y,
+            (true, WidgetEvent::MouseScrollPx{dir, in_widget: true}) => (dir.x + dir.y).signum(),
+            _ => 0
+        };
+
+        if steps == 0 {
+            return EventOps {
+                focus: None,
+                bubble: event.default_bubble(),
+                handled: false,
+            };
+        }
+
+        let start_value = self.handle.value;
+        self.handle.set_value(start_value + steps as f32 * self.handle.step);
+        if self.handle.value != start_value {
+            if let Some(message) = self.handle.handler.on_move(start_value, self.handle.value) {
+                self.widget_tag.broadcast_message(message);
+            }
+            self.widget_tag.request_redraw();
+        }
+
         EventOps {
             focus: None,
-            bubble: true,
+            bubble: false,
+            handled: true,
+        }
+    }
+}
+
+impl<H> SliderHandle<H>
+    where H: SliderHandler
+{
+    /// Set the value, clamping it to the value range and snapping it to the step, and move the
+    /// head to match.
+    ///
+    /// A step that isn't positive doesn't snap the value, and an empty range pins the head to
+    /// the start of the track.
+    fn set_value(&mut self, value: f32) {
+        let (min, max) = (*self.value_range.start(), *self.value_range.end());
+        self.value = value.min(max).max(min);
+        if self.step > 0.0 {
+            self.value = ((self.value - min) / self.step).round() * self.step + min;
+            self.value = self.value.min(max).max(min);
+        }
+
+        let fraction = if max > min {(self.value - min) / (max - min)} else {0.0};
+        let (_, head_len) = self.head_span();
+        let bar_len = *self.pixel_range.end() - *self.pixel_range.start();
+        self.move_head(
+            (fraction * (bar_len - head_len) as f32) as i32
+            + *self.pixel_range.start()
+        );
+        self.widget_tag.request_redraw();
+    }
+
+    /// The position and length of the head along the track.
+    fn head_span(&self) -> (i32, i32) {
+        let rect = OffsetBox::from(self.rect);
+        self.orientation.along((rect.origin.x, rect.dims.x), (rect.origin.y, rect.dims.y))
+    }
+
+    /// Move the head so that it starts `pos` pixels along the track.
+    fn move_head(&mut self, pos: i32) {
+        let mut rect = OffsetBox::from(self.rect);
+        match self.orientation {
+            SliderOrientation::Horizontal => rect.origin.x = pos,
+            SliderOrientation::Vertical => rect.origin.y = pos,
+        }
+        self.rect = BoundBox::from(rect);
+    }
+}
+
+impl SliderOrientation {
+    /// Pick whichever of `horizontal` and `vertical` lies along the track.
+    #[inline]
+    fn along<T>(self, horizontal: T, vertical: T) -> T {
+        match self {
+            SliderOrientation::Horizontal => horizontal,
+            SliderOrientation::Vertical => vertical,
         }
     }
 }
@@ -187,52 +335,37 @@ impl<H> Widget for SliderHandle<H>
 
     #[inline]
     fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        let mut handled = false;
         if let WidgetEventSourced::This(ref event) = event {
             let start_value = self.value;
+            handled = true;
             match event {
                 WidgetEvent::MouseDown{pos, in_widget: true, button: MouseButton::Left} => {
-                    self.click_pos = Some(pos.x);
+                    self.click_pos = Some(self.orientation.along(pos.x, pos.y));
                     self.widget_tag.request_redraw();
                 },
                 WidgetEvent::MouseMove{new_pos, ..} => {
                     if let Some(click_pos) = self.click_pos {
-                        let mut offset_rect = OffsetBox::from(self.rect);
-                        offset_rect.origin.x += new_pos.x - click_pos;
-
-                        if offset_rect.min().x < *self.pixel_range.start() {
-                            offset_rect.origin.x = *self.pixel_range.start();
-                        }
-                        if offset_rect.max().x > *self.pixel_range.start() {
-                            offset_rect.origin.x = *self.pixel_range.end() - offset_rect.dims.x;
-                        }
-
-                        let bar_len = *self.pixel_range.end() - *self.pixel_range.start();
-
-                        let value_lerp_factor = offset_rect.center().x as f32 / bar_len as f32;
-                        self.value = f32::lerp(*self.value_range.start(), *self.value_range.end(), value_lerp_factor);
-
-                        // Snap the value to the step.
-                        self.value = ((self.value - *self.value_range.start()) / self.step).round() * self.step + *self.value_range.start();
-
-                        // Snap the head to the value
-                        offset_rect.origin.x =
-                            (
-                                (
-                                    (self.value - *self.value_range.start())
-                                    / (*self.value_range.end() - *self.value_range.start())
-                                )
-                                * (bar_len - offset_rect.dims.x) as f32
-                            ) as i32
-                            + *self.pixel_range.start();
-
-                        self.rect = BoundBox::from(offset_rect);
+                        let (start, end) = (*self.pixel_range.start(), *self.pixel_range.end());
+                        let (head_pos, head_len) = self.head_span();
+                        let head_pos = (head_pos + self.orientation.along(new_pos.x, new_pos.y) - click_pos)
+                            .min(end - head_len)
+                            .max(start);
+
+                        let travel = end - start - head_len;
+                        let value_lerp_factor = match travel {
+                            0 => 0.0,
+                            _ => (head_pos - start) as f32 / travel as f32
+                        };
+                        // Snaps the value to the step, and the head to the value.
+                        self.set_value(f32::lerp(*self.value_range.start(), *self.value_range.end(), value_lerp_factor));
                     }
                 },
                 WidgetEvent::MouseUp{button: MouseButton::Left, pressed_in_widget: true, ..} => {
                     self.click_pos = None;
                     self.widget_tag.request_redraw();
                 },
-                _ => ()
+                _ => handled = false
             }
             if self.value != start_value {
                 if let Some(message) = self.handler.on_move(start_value, self.value) {
@@ -244,6 +377,55 @@ impl<H> Widget for SliderHandle<H>
         EventOps {
             focus: None,
             bubble: event.default_bubble(),
+            handled,
+        }
+    }
+}
+
+impl<H> Parent for Slider<H>
+    where H: SliderHandler
+{
+    fn num_children(&self) -> usize {
+        1
+    }
+
+    fn framed_child<R: Renderer>(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, R>> {
+        match widget_ident {
+            WidgetIdent::Num(0) => Some(WidgetInfo::new(WidgetIdent::Num(0), 0, &self.handle)),
+            _ => None
+        }
+    }
+    fn framed_child_mut<R: Renderer>(&mut self, widget_ident: WidgetIdent) -> Option<WidgetInfoMut<'_, R>> {
+        match widget_ident {
+            WidgetIdent::Num(0) => Some(WidgetInfoMut::new(WidgetIdent::Num(0), 0, &mut self.handle)),
+            _ => None
+        }
+    }
+
+    fn framed_children<'a, R, G>(&'a self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfo<'a, R>) -> LoopFlow
+    {
+        let _ = for_each(WidgetInfo::new(WidgetIdent::Num(0), 0, &self.handle));
+    }
+
+    fn framed_children_mut<'a, R, G>(&'a mut self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfoMut<'a, R>) -> LoopFlow
+    {
+        let _ = for_each(WidgetInfoMut::new(WidgetIdent::Num(0), 0, &mut self.handle));
+    }
+
+    fn framed_child_by_index<R: Renderer>(&self, index: usize) -> Option<WidgetInfo<'_, R>> {
+        match index {
+            0 => Some(WidgetInfo::new(WidgetIdent::Num(0), 0, &self.handle)),
+            _ => None
+        }
+    }
+    fn framed_child_by_index_mut<R: Renderer>(&mut self, index: usize) -> Option<WidgetInfoMut<'_, R>> {
+        match index {
+            0 => Some(WidgetInfoMut::new(WidgetIdent::Num(0), 0, &mut self.handle)),
+            _ => None
         }
     }
 }
@@ -263,9 +445,33 @@ impl<R, H> WidgetRenderable<R> for Slider<H>
     }
 
     fn update_layout(&mut self, layout: &mut R::Layout) {
+        let metrics = layout.control_metrics();
         let result = layout.finish();
-        self.size_bounds = result.size_bounds;
-        self.handle.pixel_range = result.content_rect.min.x..=result.content_rect.max.x;
+        let (length, thickness) = (metrics.slider_length, metrics.slider_thickness);
+        let orientation = self.handle.orientation;
+        self.size_bounds = result.size_bounds.expand_min(orientation.along(
+            DimsBox::new2(length, thickness),
+            DimsBox::new2(thickness, length),
+        ));
+        // If the renderer doesn't reserve an area for the track, the track spans the whole slider.
+        let content_rect = match result.content_rect.width() == 0 || result.content_rect.height() == 0 {
+            true => BoundBox::new2(0, 0, self.rect.width(), self.rect.height()),
+            false => result.content_rect
+        };
+        self.handle.pixel_range = orientation.along(
+            content_rect.min.x..=content_rect.max.x,
+            content_rect.min.y..=content_rect.max.y,
+        );
+
+        // The head is as long as the track is thick, and spans the track's thickness.
+        let head_min = self.handle.size_bounds.min;
+        let head_len = orientation.along(head_min.width(), head_min.height()).max(thickness);
+        self.handle.rect = orientation.along(
+            BoundBox::new2(0, content_rect.min.y, head_len, content_rect.max.y),
+            BoundBox::new2(content_rect.min.x, 0, content_rect.max.x, head_len),
+        );
+        let value = self.handle.value;
+        self.handle.set_value(value);
     }
 }
 
@@ -302,3 +508,70 @@ impl WidgetTheme for SliderHandleTheme {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cgmath::Point2,
+        core::WindowEvent,
+        test_helpers::{headless_root, send_events},
+    };
+
+    struct NoAction;
+    impl SliderHandler for NoAction {
+        type Action = ();
+
+        fn on_move(&mut self, _: f32, _: f32) -> Option<()> {
+            None
+        }
+    }
+
+    #[test]
+    fn orientation_size_bounds() {
+        let mut root = headless_root(Slider::new(0.0, 1.0, 0.0..=10.0, NoAction), DimsBox::new2(128, 128));
+        assert_eq!(DimsBox::new2(64, 16), root.root_widget.size_bounds().min);
+
+        // Vertical sliders are as wide as horizontal ones are tall.
+        root.root_widget.set_orientation(SliderOrientation::Vertical);
+        root.relayout();
+        assert_eq!(DimsBox::new2(16, 64), root.root_widget.size_bounds().min);
+    }
+
+    #[test]
+    fn degenerate_range() {
+        let mut root = headless_root(Slider::new(3.0, 1.0, 3.0..=3.0, NoAction), DimsBox::new2(128, 128));
+        let handle = &mut root.root_widget.handle;
+        handle.set_value(7.0);
+        assert_eq!(3.0, handle.value);
+        assert_eq!(*handle.pixel_range.start(), handle.head_span().0);
+
+        // A zero step leaves the value unsnapped.
+        handle.value_range = 0.0..=10.0;
+        handle.step = 0.0;
+        handle.set_value(2.5);
+        assert_eq!(2.5, handle.value);
+    }
+
+    #[test]
+    fn drag_head() {
+        let mut root = headless_root(Slider::new(0.0, 1.0, 0.0..=10.0, NoAction), DimsBox::new2(64, 16));
+        assert_eq!(BoundBox::new2(0, 0, 16, 16), root.root_widget.handle.rect);
+
+        send_events(&mut root, &[
+            WindowEvent::MouseMove(Point2::new(8, 8)),
+            WindowEvent::MouseDown(MouseButton::Left),
+            WindowEvent::MouseMove(Point2::new(32, 8)),
+        ]);
+        assert_eq!(5.0, root.root_widget.value());
+        assert_eq!(BoundBox::new2(24, 0, 40, 16), root.root_widget.handle.rect);
+
+        // Dragging past the end of the track pins the head to the end.
+        send_events(&mut root, &[
+            WindowEvent::MouseMove(Point2::new(100, 8)),
+            WindowEvent::MouseUp(MouseButton::Left),
+        ]);
+        assert_eq!(10.0, root.root_widget.value());
+        assert_eq!(BoundBox::new2(48, 0, 64, 16), root.root_widget.handle.rect);
+    }
+}
@@ -0,0 +1,201 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use derin_core::{
+    event::{EventOps, WidgetEventSourced, InputState},
+    widget::{WidgetTag, WidgetRenderable, Widget},
+    render::{Renderer, WidgetTheme},
+};
+
+use cgmath_geometry::{D2, rect::{BoundBox, DimsBox}};
+use derin_common_types::layout::SizeBounds;
+
+/// An empty widget that takes up space in a layout.
+///
+/// Spacers draw nothing, never accept focus, and are transparent to the pointer, so the widgets
+/// behind them receive mouse events. They're useful for padding out a layout: a fixed-size spacer
+/// adds a gap between two widgets, while a [`flex`](#method.flex) spacer placed in a track sized
+/// with a fraction absorbs the layout's leftover space, pushing its siblings apart.
+#[derive(Debug)]
+pub struct Spacer {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    size_bounds: SizeBounds,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SpacerTheme(());
+
+impl Spacer {
+    /// Creates a new spacer with the given size bounds.
+    pub fn new(size_bounds: SizeBounds) -> Spacer {
+        let mut widget_tag = WidgetTag::new();
+        widget_tag.set_pointer_transparent(true);
+        Spacer {
+            widget_tag,
+            rect: BoundBox::new2(0, 0, 0, 0),
+            size_bounds,
+        }
+    }
+
+    /// Creates a spacer that's always exactly `dims` large.
+    pub fn fixed(dims: DimsBox<D2, i32>) -> Spacer {
+        Spacer::new(SizeBounds::new(dims, dims))
+    }
+
+    /// Creates a spacer with no minimum size and an unbounded maximum size, which grows to fill
+    /// whatever space it's given.
+    pub fn flex() -> Spacer {
+        Spacer::new(SizeBounds::default())
+    }
+
+    /// Retrieves the spacer's size bounds.
+    #[inline]
+    pub fn bounds(&self) -> SizeBounds {
+        self.size_bounds
+    }
+
+    /// Sets the spacer's size bounds.
+    pub fn set_bounds(&mut self, size_bounds: SizeBounds) {
+        if size_bounds != self.size_bounds {
+            self.size_bounds = size_bounds;
+            self.widget_tag.request_relayout();
+        }
+    }
+}
+
+impl Clone for Spacer {
+    fn clone(&self) -> Spacer {
+        // Spacers are always pointer-transparent, whatever the tag being cloned says.
+        Spacer {
+            rect: self.rect,
+            ..Spacer::new(self.size_bounds)
+        }
+    }
+}
+
+impl Widget for Spacer {
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        &mut self.rect
+    }
+
+    #[inline]
+    fn size_bounds(&self) -> SizeBounds {
+        self.size_bounds
+    }
+
+    #[inline]
+    fn on_widget_event(&mut self, _: WidgetEventSourced, _: InputState) -> EventOps {
+        EventOps {
+            focus: None,
+            bubble: true,
+            handled: false,
+        }
+    }
+}
+
+impl<R> WidgetRenderable<R> for Spacer
+    where R: Renderer
+{
+    type Theme = SpacerTheme;
+    fn theme(&self) -> SpacerTheme {
+        SpacerTheme(())
+    }
+
+    fn render(&mut self, _: &mut R::SubFrame) { }
+
+    fn update_layout(&mut self, _: &mut R::Layout) { }
+}
+
+impl WidgetTheme for SpacerTheme {
+    type Fallback = !;
+    fn fallback(self) -> Option<!> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cgmath::Point2,
+        event::Key,
+        layout::{LayoutHorizontal, Margins},
+        test_helpers::{click, headless_root, press_access_key},
+        widgets::{Button, ButtonHandler, Canvas, Contents, Group, Label},
+    };
+    use std::{cell::Cell, rc::Rc};
+
+    struct Counter(Rc<Cell<u32>>);
+
+    impl ButtonHandler for Counter {
+        fn on_click(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    fn button(clicks: &Rc<Cell<u32>>) -> Button<Counter> {
+        Button::new(Contents::Text("Button".to_string()), Counter(clicks.clone()))
+    }
+
+    #[test]
+    fn size_bounds() {
+        let root = headless_root(Spacer::fixed(DimsBox::new2(30, 20)), DimsBox::new2(100, 100));
+        assert_eq!(SizeBounds::new(DimsBox::new2(30, 20), DimsBox::new2(30, 20)), root.root_widget.size_bounds());
+        assert_eq!(BoundBox::new2(0, 0, 30, 20), root.root_widget.rect());
+
+        let root = headless_root(Spacer::flex(), DimsBox::new2(100, 100));
+        assert_eq!(SizeBounds::default(), root.root_widget.size_bounds());
+        assert_eq!(BoundBox::new2(0, 0, 100, 100), root.root_widget.rect());
+    }
+
+    #[test]
+    fn clone_pointer_transparent() {
+        let spacer = Spacer::flex();
+        assert!(spacer.widget_tag().pointer_transparent());
+        assert!(spacer.clone().widget_tag().pointer_transparent());
+    }
+
+    #[test]
+    fn click_through() {
+        // The spacer's the first child, so it would take the cursor if it weren't transparent. It's
+        // cloned to make sure clones stay transparent too.
+        let clicks = Rc::new(Cell::new(0));
+        let canvas = Canvas::new(
+            (Spacer::flex().clone(), button(&clicks)),
+            vec![BoundBox::new2(0, 0, 100, 40), BoundBox::new2(0, 0, 100, 40)],
+        );
+        let mut root = headless_root(canvas, DimsBox::new2(100, 40));
+        click(&mut root, Point2::new(50, 20));
+        assert_eq!(1, clicks.get());
+    }
+
+    #[test]
+    fn focus_skips_spacer() {
+        let clicks = Rc::new(Cell::new(0));
+        let group = Group::new(
+            (Label::with_mnemonic("&Name"), Spacer::fixed(DimsBox::new2(20, 0)), button(&clicks)),
+            LayoutHorizontal::new(Margins::new(0, 0, 0, 0), Default::default()),
+        );
+        let mut root = headless_root(group, DimsBox::new2(200, 24));
+
+        // The label's access key moves focus to the next widget that accepts it.
+        press_access_key(&mut root, Key::N);
+
+        let (_, spacer, button) = root.root_widget.container();
+        assert!(!spacer.widget_tag().has_keyboard_focus());
+        assert!(button.widget_tag().has_keyboard_focus());
+    }
+}
@@ -2,27 +2,43 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-
+use derin_core::{
+    LoopFlow,
+    timer::{Timer, TimerId},
+    widget::{WidgetIdent, WidgetRenderable, WidgetTag, WidgetInfo, WidgetInfoMut, Widget, Parent, MessageTarget},
+    render::{Renderer, RendererLayout, SubFrame, WidgetTheme},
+};
 use crate::{
-    core::{
-        LoopFlow,
-        event::{EventOps, WidgetEvent, WidgetEventSourced, InputState},
-        widget::{WidgetIdent, WidgetRenderable, WidgetTag, WidgetInfo, WidgetInfoMut, Widget, Parent},
-        render::Renderer,
+    cgmath::Vector2,
+    event::{EventOps, WidgetEvent, WidgetEventSourced, InputState, MouseHoverChange},
+    widgets::{
+        GroupTheme, ToggleButtonTheme,
+        assistants::ButtonState,
     },
-    gl_render::{RelPoint, ThemedPrim, Prim, PrimFrame, RenderString},
-    widgets::assistants::ButtonState,
 };
 
-use derin_common_types::layout::{SizeBounds, WidgetPos, GridSize, WidgetSpan, TrackHints};
+use cgmath_geometry::{D2, rect::{BoundBox, DimsBox, GeoBox}};
+use derin_common_types::layout::SizeBounds;
 
-use std::cell::RefCell;
-use crate::cgmath::Point2;
-use cgmath_geometry::{D2, rect::{BoundBox, GeoBox}};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use derin_layout_engine::{GridEngine, UpdateHeapCache, SolveError};
+const TRANSITION_TICK: Duration = Duration::from_millis(16);
 
-use arrayvec::ArrayVec;
+/// How a [`TabList`] animates between pages when a different tab gets opened.
+///
+/// [`TabList`]: ./struct.TabList.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TabTransition {
+    /// Switch to the new page immediately.
+    None,
+    /// Cross-fade between the old and new pages. Both pages occupy the content area for the
+    /// duration of the transition, with the new page in front.
+    Fade,
+    /// Slide the new page in from the side of the old page that its tab lies on, pushing the old
+    /// page out of view.
+    Slide,
+}
 
 /// A page within a greater list of tabs.
 ///
@@ -31,63 +47,213 @@ use arrayvec::ArrayVec;
 /// [`TabList`]: ./struct.TabList.html
 #[derive(Debug, Clone)]
 pub struct TabPage<W> {
-    title: RenderString,
+    header: TabHeader,
     /// The widget that's displayed within the tab page.
     pub page: W,
+}
+
+/// The button at the top of the tab list that opens a page.
+#[derive(Debug, Clone)]
+struct TabHeader {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    size_bounds: SizeBounds,
+    index: usize,
+    title: String,
     open: bool,
-    button_state: ButtonState,
-    rect: BoundBox<D2, i32>
+    state: ButtonState,
 }
 
 /// A list of tabs.
 ///
 /// This widget lets you display a single widget at a time, from a greater selection of widgets.
 /// Users can switch between these widgets by clicking on a list of tabs at the top of the widget.
+///
+/// Switching tabs can be animated with [`set_transition`]. While a switch is being animated, both
+/// the outgoing and incoming pages are laid out and rendered, but the incoming page takes priority
+/// for mouse input.
+///
+/// [`set_transition`]: ./struct.TabList.html#method.set_transition
 #[derive(Debug, Clone)]
 pub struct TabList<W> {
     widget_tag: WidgetTag,
     rect: BoundBox<D2, i32>,
-    layout_engine: GridEngine,
+    size_bounds: SizeBounds,
+
+    tabs: Vec<TabPage<W>>,
+    transition: TabTransition,
+    transition_duration: Duration,
+    transition_state: Option<TransitionState>,
+}
+
+/// A page switch that's currently being animated.
+#[derive(Debug, Clone, Copy)]
+struct TransitionState {
+    /// The index of the page that's being switched away from.
+    outgoing: usize,
+    start: Instant,
+    timer: TimerId,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TabClicked(usize);
+
+#[derive(Debug, Clone, Default)]
+pub struct TabListTheme(());
 
-    tabs: Vec<TabPage<W>>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TabTheme {
+    pub state: ButtonState,
+    pub open: bool,
+}
+
+lazy_static!{
+    static ref TAB_IDENT: Arc<str> = Arc::from("tab");
 }
 
 impl<W> TabPage<W> {
     /// Create a new tab page, with the given title and contained widget.
     pub fn new(title: String, page: W) -> TabPage<W> {
         TabPage {
-            title: RenderString::new(title),
+            header: TabHeader {
+                widget_tag: WidgetTag::new(),
+                rect: BoundBox::new2(0, 0, 0, 0),
+                size_bounds: SizeBounds::default(),
+                index: 0,
+                title,
+                open: true,
+                state: ButtonState::Normal,
+            },
             page,
-            open: true,
-            button_state: ButtonState::Normal,
-            rect: BoundBox::new2(0, 0, 0, 0)
         }
     }
 
-
     /// Retrieves a reference to the tab's title.
     pub fn string(&self) -> &str {
-        self.title.string()
+        &self.header.title
     }
 
     /// Retrieves the tab's title, for mutation.
     pub fn string_mut(&mut self) -> &mut String {
-        self.title.string_mut()
+        self.header.widget_tag.request_redraw().request_relayout();
+        &mut self.header.title
     }
 }
 
-impl<W> TabList<W> {
+impl<W> TabList<W>
+    where W: Widget
+{
     /// Create a new list of tabs.
     pub fn new(tabs: Vec<TabPage<W>>) -> TabList<W> {
+        let mut widget_tag = WidgetTag::new();
+        widget_tag.register_message(Self::on_tab_clicked);
+
         TabList {
-            widget_tag: WidgetTag::new(),
+            widget_tag,
             rect: BoundBox::new2(0, 0, 0, 0),
-            layout_engine: GridEngine::new(),
+            size_bounds: SizeBounds::default(),
 
-            tabs
+            tabs,
+            transition: TabTransition::None,
+            transition_duration: Duration::from_millis(200),
+            transition_state: None,
         }
     }
 
+    /// Retrieves the animation used when switching between tabs, along with its duration.
+    pub fn transition(&self) -> (TabTransition, Duration) {
+        (self.transition, self.transition_duration)
+    }
+
+    /// Sets the animation used when switching between tabs, along with how long it lasts.
+    ///
+    /// Defaults to `TabTransition::None`. Changing this finishes any in-progress transition.
+    pub fn set_transition(&mut self, transition: TabTransition, duration: Duration) {
+        self.transition = transition;
+        self.transition_duration = duration;
+        self.finish_transition();
+    }
+
+    /// How far along the current tab switch is, from `0.0` to `1.0`, or `None` if there isn't a
+    /// switch being animated.
+    pub fn transition_progress(&self) -> Option<f32> {
+        self.transition_state.map(|state| self.progress(state))
+    }
+
+    /// Retrieves the index of the open tab, or `None` if there aren't any tabs.
+    pub fn open_index(&self) -> Option<usize> {
+        self.tabs.iter().position(|t| t.header.open)
+    }
+
+    /// Open the tab at `index`, animating the switch if a transition has been set. Does nothing if
+    /// `index` is out of range.
+    pub fn open_tab(&mut self, index: usize) {
+        let old_open = self.open_index();
+        if index >= self.tabs.len() || Some(index) == old_open {
+            return;
+        }
+
+        for (i, tab) in self.tabs.iter_mut().enumerate() {
+            tab.header.set_open(i == index);
+        }
+        self.widget_tag.request_relayout();
+        if let Some(old_open) = old_open {
+            self.start_transition(old_open);
+        }
+    }
+
+    fn on_tab_clicked(&mut self, message: &TabClicked) {
+        self.open_tab(message.0);
+    }
+
+    fn progress(&self, state: TransitionState) -> f32 {
+        let duration = duration_secs(self.transition_duration);
+        match duration > 0.0 {
+            true => (duration_secs(Instant::now() - state.start) / duration).min(1.0),
+            false => 1.0
+        }
+    }
+
+    fn start_transition(&mut self, outgoing: usize) {
+        self.finish_transition();
+        if self.transition == TabTransition::None || self.transition_duration == Duration::new(0, 0) {
+            return;
+        }
+
+        let timer = TimerId::new();
+        self.widget_tag.timers_mut().insert(timer, Timer::new(TRANSITION_TICK));
+        self.transition_state = Some(TransitionState {
+            outgoing,
+            start: Instant::now(),
+            timer,
+        });
+    }
+
+    /// Stop animating the current tab switch, dropping the outgoing page from the layout.
+    fn finish_transition(&mut self) {
+        if let Some(state) = self.transition_state.take() {
+            self.widget_tag.timers_mut().remove(&state.timer);
+            self.widget_tag.request_relayout().request_redraw();
+        }
+    }
+
+    /// The order in which pages are visited as children. While a transition is running, the
+    /// incoming page comes first so that it receives the mouse events where the two pages
+    /// overlap, and the outgoing page comes last. Otherwise, pages are visited in order.
+    fn child_order(&self) -> impl Iterator<Item=usize> {
+        let len = self.tabs.len();
+        let (open, outgoing) = match self.transition_state {
+            Some(state) => {
+                let open = self.open_index();
+                (open, Some(state.outgoing).filter(|&i| i < len && Some(i) != open))
+            },
+            None => (None, None)
+        };
+        open.into_iter()
+            .chain((0..len).filter(move |&i| Some(i) != open && Some(i) != outgoing))
+            .chain(outgoing)
+    }
+
     /// Retrieves a reference to the tab list.
     pub fn tabs(&self) -> &[TabPage<W>] {
         &self.tabs
@@ -103,6 +269,15 @@ impl<W> TabList<W> {
     }
 }
 
+impl TabHeader {
+    fn set_open(&mut self, open: bool) {
+        if self.open != open {
+            self.open = open;
+            self.widget_tag.request_redraw();
+        }
+    }
+}
+
 impl<W> Widget for TabList<W>
     where W: Widget
 {
@@ -124,105 +299,85 @@ impl<W> Widget for TabList<W>
 
     #[inline]
     fn size_bounds(&self) -> SizeBounds {
-        self.layout_engine.actual_size_bounds()
+        self.size_bounds
+    }
+
+    #[inline]
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        let mut handled = false;
+        if let WidgetEventSourced::This(WidgetEvent::Timer{timer_id, ..}) = event {
+            if Some(timer_id) == self.transition_state.map(|s| s.timer) {
+                handled = true;
+                match self.transition_progress() {
+                    Some(p) if p < 1.0 => {self.widget_tag.request_relayout().request_redraw();},
+                    _ => self.finish_transition()
+                }
+            }
+        }
+
+        EventOps {
+            focus: None,
+            bubble: event.default_bubble(),
+            handled,
+        }
+    }
+}
+
+impl Widget for TabHeader {
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        &mut self.rect
     }
 
     #[inline]
+    fn size_bounds(&self) -> SizeBounds {
+        self.size_bounds
+    }
+
     fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
-        // TODO: PASS FOCUS TO CHILD
-        if let WidgetEventSourced::This(ref event) = event {
-            match event {
-                WidgetEvent::MouseMove{new_pos, in_widget: true, ..} => {
-                    let mut state_changed = false;
-                    for tab in self.tabs.iter_mut() {
-                        let new_state = match (tab.button_state, tab.rect.contains(*new_pos)) {
-                            (ButtonState::Pressed, _) => ButtonState::Pressed,
-                            (_, true) => ButtonState::Hover,
-                            (_, false) => ButtonState::Normal,
-                        };
-                        state_changed |= new_state != tab.button_state;
-                        tab.button_state = new_state;
-                    }
-                    if state_changed {
-                        self.widget_tag.request_redraw();
-                    }
-                },
-                // WidgetEvent::MouseEnterChild{..} => {
-                //     let mut state_changed = false;
-                //     for tab in self.tabs.iter_mut() {
-                //         let new_state = match tab.button_state {
-                //             ButtonState::Pressed => ButtonState::Pressed,
-                //             _ => ButtonState::Normal,
-                //         };
-                //         state_changed |= new_state != tab.button_state;
-                //         tab.button_state = new_state;
-                //     }
-                //     if state_changed {
-                //         self.widget_tag.request_redraw();
-                //     }
-                // }
-                WidgetEvent::MouseDown{pos, in_widget: true, ..} => {
-                    let mut state_changed = false;
-                    for tab in self.tabs.iter_mut() {
-                        let new_state = match tab.rect.contains(*pos) {
-                            true => ButtonState::Pressed,
-                            false => tab.button_state
-                        };
-                        state_changed |= new_state != tab.button_state;
-                        tab.button_state = new_state;
-                    }
-                    if state_changed {
-                        self.widget_tag.request_redraw();
-                    }
-                },
-                WidgetEvent::MouseUp{in_widget: true, pressed_in_widget: true, pos, down_pos, ..} => {
-                    // Change tab selection.
-                    let mut state_changed = false;
-                    let (mut old_open, mut new_open) = (None, None);
-                    for (index, tab) in self.tabs.iter_mut().enumerate() {
-                        let tab_contains = tab.rect.contains(*pos);
-                        let is_open = tab_contains && tab.rect.contains(*down_pos);
-                        let new_state = match tab_contains {
-                            true => ButtonState::Hover,
-                            false => ButtonState::Normal
-                        };
-                        state_changed |= is_open != tab.open || new_state != tab.button_state;
-
-                        if tab.open && old_open == None {
-                            old_open = Some(index);
-                        }
-                        if is_open && new_open == None {
-                            new_open = Some(index);
-                        }
-
-                        tab.open = is_open;
-                        tab.button_state = new_state;
-                    }
-                    if state_changed {
-                        self.widget_tag.request_redraw();
-                    }
-                    if !(old_open == new_open || new_open == None) {
-                        self.widget_tag.request_relayout();
-                    }
-                },
-                WidgetEvent::MouseUp{in_widget: false, pressed_in_widget: true, ..} => {
-                    let mut state_changed = false;
-                    for tab in self.tabs.iter_mut() {
-                        let new_state = ButtonState::Normal;
-                        state_changed |= new_state != tab.button_state;
-                        tab.button_state = new_state;
-                    }
-                    if state_changed {
-                        self.widget_tag.request_redraw();
-                    }
-                },
-                _ => ()
+        use self::WidgetEvent::*;
+        let event = event.unwrap();
+        let widget_id = self.widget_tag.widget_id();
+
+        let mut handled = true;
+        let new_state = match event {
+            MouseMove{hover_change: Some(ref change), ..} => match change {
+                MouseHoverChange::Enter => ButtonState::Hover,
+                MouseHoverChange::Exit => ButtonState::Normal,
+                _ => self.state
+            },
+            MouseDown{..} => ButtonState::Pressed,
+            MouseUp{in_widget: true, pressed_in_widget: true, ..} => ButtonState::Hover,
+            Click{..} => {
+                self.widget_tag.send_message_to(TabClicked(self.index), MessageTarget::ParentOf(widget_id));
+                self.state
+            },
+            MouseUp{in_widget: false, ..} => ButtonState::Normal,
+            _ => {
+                handled = false;
+                self.state
             }
+        };
+
+        if new_state != self.state {
+            self.widget_tag.request_redraw();
+            self.state = new_state;
         }
 
         EventOps {
             focus: None,
-            bubble: event.default_bubble() || event.is_bubble(),
+            bubble: event.default_bubble(),
+            handled,
         }
     }
 }
@@ -230,22 +385,26 @@ impl<W> Widget for TabList<W>
 impl<W> Parent for TabList<W>
     where W: Widget
 {
+    /// The tab headers come first, followed by the pages.
     fn num_children(&self) -> usize {
-        self.tabs.len()
+        self.tabs.len() * 2
     }
 
     fn framed_child<R: Renderer>(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, R>> {
-        if let WidgetIdent::Num(index) = widget_ident {
-            self.tabs.get(index as usize).map(|t| WidgetInfo::new(widget_ident, index as usize, &t.page))
-        } else {
-            None
+        match widget_ident {
+            WidgetIdent::Num(index) => self.framed_child_by_index(self.tabs.len() + index as usize),
+            WidgetIdent::StrCollection(ref s, index) if *s == *TAB_IDENT => self.framed_child_by_index(index as usize),
+            _ => None
         }
     }
     fn framed_child_mut<R: Renderer>(&mut self, widget_ident: WidgetIdent) -> Option<WidgetInfoMut<'_, R>> {
-        if let WidgetIdent::Num(index) = widget_ident {
-            self.tabs.get_mut(index as usize).map(|t| WidgetInfoMut::new(widget_ident, index as usize, &mut t.page))
-        } else {
-            None
+        match widget_ident {
+            WidgetIdent::Num(index) => {
+                let len = self.tabs.len();
+                self.framed_child_by_index_mut(len + index as usize)
+            },
+            WidgetIdent::StrCollection(ref s, index) if *s == *TAB_IDENT => self.framed_child_by_index_mut(index as usize),
+            _ => None
         }
     }
 
@@ -253,10 +412,15 @@ impl<W> Parent for TabList<W>
         where R: Renderer,
               G: FnMut(WidgetInfo<'a, R>) -> LoopFlow
     {
+        let len = self.tabs.len();
         for (index, tab) in self.tabs.iter().enumerate() {
-            match for_each(WidgetInfo::new(WidgetIdent::Num(index as u32), index, &tab.page)) {
-                LoopFlow::Continue => (),
-                LoopFlow::Break => return
+            if let LoopFlow::Break = for_each(WidgetInfo::new(header_ident(index), index, &tab.header)) {
+                return;
+            }
+        }
+        for index in self.child_order() {
+            if let LoopFlow::Break = for_each(WidgetInfo::new(WidgetIdent::Num(index as u32), len + index, &self.tabs[index].page)) {
+                return;
             }
         }
     }
@@ -265,166 +429,266 @@ impl<W> Parent for TabList<W>
         where R: Renderer,
               G: FnMut(WidgetInfoMut<'a, R>) -> LoopFlow
     {
+        // The open and outgoing pages may get moved to the front and back respectively, which
+        // the borrow checker only lets us do by splitting the page list up front.
+        let len = self.tabs.len();
+        let order = self.child_order().collect::<Vec<_>>();
+        let mut pages = Vec::with_capacity(len);
         for (index, tab) in self.tabs.iter_mut().enumerate() {
-            match for_each(WidgetInfoMut::new(WidgetIdent::Num(index as u32), index, &mut tab.page)) {
-                LoopFlow::Continue => (),
-                LoopFlow::Break => return
+            let TabPage{ref mut header, ref mut page} = *tab;
+            if let LoopFlow::Break = for_each(WidgetInfoMut::new(header_ident(index), index, header)) {
+                return;
+            }
+            pages.push(Some(page));
+        }
+        for index in order {
+            let page = pages[index].take().unwrap();
+            if let LoopFlow::Break = for_each(WidgetInfoMut::new(WidgetIdent::Num(index as u32), len + index, page)) {
+                return;
             }
         }
     }
 
     fn framed_child_by_index<R: Renderer>(&self, index: usize) -> Option<WidgetInfo<'_, R>> {
-        self.tabs.get(index).map(|t| WidgetInfo::new(WidgetIdent::Num(index as u32), index, &t.page))
+        let len = self.tabs.len();
+        match index < len {
+            true => Some(WidgetInfo::new(header_ident(index), index, &self.tabs[index].header)),
+            false => self.tabs.get(index - len).map(|t| WidgetInfo::new(WidgetIdent::Num((index - len) as u32), index, &t.page))
+        }
     }
     fn framed_child_by_index_mut<R: Renderer>(&mut self, index: usize) -> Option<WidgetInfoMut<'_, R>> {
-        self.tabs.get_mut(index).map(|t| WidgetInfoMut::new(WidgetIdent::Num(index as u32), index, &mut t.page))
+        let len = self.tabs.len();
+        match index < len {
+            true => Some(WidgetInfoMut::new(header_ident(index), index, &mut self.tabs[index].header)),
+            false => self.tabs.get_mut(index - len).map(|t| WidgetInfoMut::new(WidgetIdent::Num((index - len) as u32), index, &mut t.page))
+        }
     }
 }
 
+fn header_ident(index: usize) -> WidgetIdent {
+    WidgetIdent::StrCollection(TAB_IDENT.clone(), index as u32)
+}
+
 impl<R, W> WidgetRenderable<R> for TabList<W>
     where R: Renderer,
           W: Widget
 {
+    type Theme = TabListTheme;
+    fn theme(&self) -> TabListTheme {
+        TabListTheme(())
+    }
+
     fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, _: &mut R::Layout) {
+        // Exactly one tab is open at a time. If none of them are, open the first one.
+        let active_tab_index = match self.open_index() {
+            Some(index) => index,
+            None if self.tabs.len() > 0 => 0,
+            None => {
+                self.size_bounds = SizeBounds::default();
+                return;
+            }
+        };
+        for (index, tab) in self.tabs.iter_mut().enumerate() {
+            tab.header.index = index;
+            tab.header.set_open(index == active_tab_index);
+        }
+
+        // The headers sit in a row along the top, at their minimum widths, and the page fills
+        // the space below them.
+        let header_height = self.tabs.iter().map(|t| t.header.size_bounds.min.height()).max().unwrap_or(0);
+        let mut x = 0;
         for tab in &mut self.tabs {
-            let theme_path = match tab.button_state {
-                ButtonState::Normal => "Tab::Normal",
-                ButtonState::Hover => "Tab::Hover",
-                ButtonState::Pressed => "Tab::Pressed"
+            let width = tab.header.size_bounds.min.width();
+            tab.header.rect = BoundBox::new2(x, 0, x + width, header_height);
+            x += width;
+        }
+
+        let page_bounds = self.tabs[active_tab_index].page.size_bounds();
+        self.size_bounds = SizeBounds::new_min(DimsBox::new2(
+            i32::max(x, page_bounds.min.width()),
+            header_height + page_bounds.min.height(),
+        ));
+        let content_rect = BoundBox::new2(
+            0, header_height,
+            self.rect.width(), i32::max(header_height, self.rect.height())
+        );
+
+        // While switching tabs, the outgoing page stays in the layout alongside the incoming
+        // page until the transition finishes.
+        let transition = self.transition_state
+            .map(|state| (state.outgoing, self.progress(state)))
+            .filter(|&(outgoing, _)| outgoing < self.tabs.len() && outgoing != active_tab_index);
+        let (incoming_rect, outgoing_rect) = match (self.transition, transition) {
+            (TabTransition::Slide, Some((outgoing, progress))) => {
+                // Pages slide towards the side of the tab that's being opened.
+                let width = content_rect.width() as f32;
+                let dir = match active_tab_index > outgoing {
+                    true => 1.0,
+                    false => -1.0
+                };
+                let incoming_offset = Vector2::new((width * (1.0 - progress) * dir) as i32, 0);
+                let outgoing_offset = Vector2::new((-width * progress * dir) as i32, 0);
+                (content_rect + incoming_offset, Some((outgoing, content_rect + outgoing_offset)))
+            },
+            (TabTransition::Fade, Some((outgoing, _))) => (content_rect, Some((outgoing, content_rect))),
+            _ => (content_rect, None)
+        };
+
+        for (index, tab) in self.tabs.iter_mut().enumerate() {
+            let page_rect = match outgoing_rect {
+                _ if index == active_tab_index => incoming_rect,
+                Some((outgoing, rect)) if index == outgoing => rect,
+                _ => BoundBox::new2(-1, -1, -1, -1)
             };
-            frame.upload_primitives(ArrayVec::from([
-                ThemedPrim {
-                    theme_path,
-                    min: Point2::new(
-                        RelPoint::new(-1.0, tab.rect.min.x),
-                        RelPoint::new(-1.0, tab.rect.min.y),
-                    ),
-                    max: Point2::new(
-                        RelPoint::new(-1.0, tab.rect.max.x),
-                        RelPoint::new(-1.0, tab.rect.max.y)
-                    ),
-                    prim: Prim::Image,
-                    rect_px_out: None
-                },
-                ThemedPrim {
-                    theme_path,
-                    min: Point2::new(
-                        RelPoint::new(-1.0, tab.rect.min.x),
-                        RelPoint::new(-1.0, tab.rect.min.y),
-                    ),
-                    max: Point2::new(
-                        RelPoint::new(-1.0, tab.rect.max.x),
-                        RelPoint::new(-1.0, tab.rect.max.y)
-                    ),
-                    prim: Prim::String(&mut tab.title),
-                    rect_px_out: None
-                },
-            ]));
+            if tab.page.rect() != page_rect {
+                *tab.page.rect_mut() = page_rect;
+            }
         }
     }
+}
 
-    fn update_layout(&mut self, _: &R::Theme) {
-        #[derive(Default)]
-        struct HeapCache {
-            update_heap_cache: UpdateHeapCache,
-            hints_vec: Vec<WidgetPos>,
-            rects_vec: Vec<Result<BoundBox<D2, i32>, SolveError>>
-        }
-        thread_local! {
-            static HEAP_CACHE: RefCell<HeapCache> = RefCell::new(HeapCache::default());
+impl<R> WidgetRenderable<R> for TabHeader
+    where R: Renderer
+{
+    type Theme = TabTheme;
+    fn theme(&self) -> TabTheme {
+        TabTheme {
+            state: self.state,
+            open: self.open,
         }
+    }
 
-        HEAP_CACHE.with(|hc| {
-            let mut hc = hc.borrow_mut();
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
 
-            let HeapCache {
-                ref mut update_heap_cache,
-                ref mut hints_vec,
-                ref mut rects_vec
-            } = *hc;
+    fn update_layout(&mut self, layout: &mut R::Layout) {
+        layout.prepare_string(&self.title);
 
-            let grid_dims = GridSize::new(self.tabs.len() as u32 + 1, 2);
+        let control_height = layout.control_metrics().control_height;
+        let result = layout.finish();
+        self.size_bounds = result.size_bounds.expand_min(DimsBox::new2(0, control_height));
+    }
+}
 
-            self.layout_engine.desired_size = self.rect.dims();
-            self.layout_engine.set_grid_size(grid_dims);
-            self.layout_engine.set_row_hints(
-                0,
-                TrackHints {
-                    fr_size: 0.0,
-                    ..TrackHints::default()
-                }
-            );
-            self.layout_engine.set_col_hints(
-                grid_dims.x - 1,
-                TrackHints {
-                    fr_size: 1.0,
-                    ..TrackHints::default()
-                }
-            );
-
-            let mut active_tab_index_opt = None;
-            for (index, tab) in self.tabs.iter_mut().enumerate() {
-                match (active_tab_index_opt, tab.open) {
-                    (None, true) => active_tab_index_opt = Some(index),
-                    (Some(_), _) => tab.open = false,
-                    _ => ()
-                }
+impl WidgetTheme for TabListTheme {
+    type Fallback = GroupTheme;
+    fn fallback(self) -> Option<GroupTheme> {
+        Some(GroupTheme::default())
+    }
+}
 
-                hints_vec.push(WidgetPos {
-                    size_bounds: SizeBounds::new_min(tab.title.min_size()),
-                    widget_span: WidgetSpan::new(index as u32, 0),
-                    ..WidgetPos::default()
-                });
-                rects_vec.push(Ok(BoundBox::new2(0, 0, 0, 0)));
-                self.layout_engine.set_col_hints(
-                    index as u32,
-                    TrackHints {
-                        fr_size: 0.0,
-                        ..TrackHints::default()
-                    }
-                );
-            }
+impl WidgetTheme for TabTheme {
+    type Fallback = ToggleButtonTheme;
+    fn fallback(self) -> Option<ToggleButtonTheme> {
+        Some(ToggleButtonTheme{ toggled: self.open, state: self.state })
+    }
+}
 
-            let (active_tab, active_tab_index): (&TabPage<W>, usize);
-
-            match (active_tab_index_opt, self.tabs.len()) {
-                (Some(i), _) => {
-                    active_tab = &self.tabs[i];
-                    active_tab_index = i;
-                },
-                (None, 0) => {
-                    hints_vec.clear();
-                    rects_vec.clear();
-                    return;
-                },
-                (None, _) => {
-                    let tab = &mut self.tabs[0];
-                    tab.open = true;
-                    active_tab = tab;
-                    active_tab_index = 0;
-                }
-            }
+fn duration_secs(duration: Duration) -> f32 {
+    duration.as_secs() as f32 + duration.subsec_nanos() as f32 / 1_000_000_000.0
+}
 
-            hints_vec.push(WidgetPos {
-                widget_span: WidgetSpan::new(.., 1),
-                size_bounds: active_tab.page.size_bounds(),
-                ..WidgetPos::default()
-            });
-            rects_vec.push(Ok(BoundBox::new2(0, 0, 0, 0)));
-            self.layout_engine.update_engine(hints_vec, rects_vec, update_heap_cache);
-
-            let mut rects_iter = rects_vec.drain(..);
-            for (tab, rect) in self.tabs.iter_mut().zip(&mut rects_iter) {
-                tab.rect = rect.unwrap_or(BoundBox::new2(-1, -1, -1, -1));
-            }
-            let active_tab_mut = &mut self.tabs[active_tab_index];
-            *active_tab_mut.page.rect_mut() = rects_iter.next().unwrap().unwrap_or(BoundBox::new2(-1, -1, -1, -1));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cgmath::Point2,
+        test_helpers::{click, headless_root},
+        widgets::Spacer,
+    };
+
+    fn tab_list() -> TabList<Spacer> {
+        TabList::new(vec![
+            TabPage::new("One".to_string(), Spacer::flex()),
+            TabPage::new("Two".to_string(), Spacer::flex()),
+            TabPage::new("Three".to_string(), Spacer::flex()),
+        ])
+    }
 
-            for (_, tab) in self.tabs.iter_mut().enumerate().filter(|&(i, _)| i != active_tab_index) {
-                *tab.page.rect_mut() = BoundBox::new2(-1, -1, -1, -1);
-            }
+    /// The rect given to pages that aren't being shown.
+    fn closed() -> BoundBox<D2, i32> {
+        BoundBox::new2(-1, -1, -1, -1)
+    }
+
+    fn page_rects(tabs: &TabList<Spacer>) -> Vec<BoundBox<D2, i32>> {
+        tabs.tabs().iter().map(|t| t.page.rect()).collect()
+    }
+
+    #[test]
+    fn layout() {
+        let root = headless_root(tab_list(), DimsBox::new2(200, 100));
+        let tabs = &root.root_widget;
+
+        let header_rects = tabs.tabs().iter().map(|t| t.header.rect).collect::<Vec<_>>();
+        assert_eq!(vec![
+            BoundBox::new2(0, 0, 24, 24),
+            BoundBox::new2(24, 0, 48, 24),
+            BoundBox::new2(48, 0, 88, 24),
+        ], header_rects);
+
+        // Only the first tab stays open.
+        assert_eq!(Some(0), tabs.open_index());
+        assert_eq!(vec![BoundBox::new2(0, 24, 200, 100), closed(), closed()], page_rects(tabs));
+        assert_eq!(DimsBox::new2(88, 24), tabs.size_bounds().min);
+    }
+
+    #[test]
+    fn click_header() {
+        let mut root = headless_root(tab_list(), DimsBox::new2(200, 100));
+        click(&mut root, Point2::new(30, 10));
+
+        assert_eq!(Some(1), root.root_widget.open_index());
+        assert_eq!(vec![closed(), BoundBox::new2(0, 24, 200, 100), closed()], page_rects(&root.root_widget));
+
+        // Clicking below the headers doesn't change the open tab.
+        click(&mut root, Point2::new(60, 50));
+        assert_eq!(Some(1), root.root_widget.open_index());
+    }
+
+    #[test]
+    fn fade_transition() {
+        let mut root = headless_root(tab_list(), DimsBox::new2(200, 100));
+        root.root_widget.set_transition(TabTransition::Fade, Duration::from_secs(60));
+        root.root_widget.open_tab(2);
+        root.relayout();
+
+        // Both pages fill the content area, with the incoming page visited first so that it
+        // takes the cursor.
+        let tabs = &root.root_widget;
+        assert!(tabs.transition_progress().unwrap() < 1.0);
+        assert_eq!(vec![BoundBox::new2(0, 24, 200, 100), closed(), BoundBox::new2(0, 24, 200, 100)], page_rects(tabs));
+        assert_eq!(vec![2, 1, 0], tabs.child_order().collect::<Vec<_>>());
+
+        // Once the transition finishes, the outgoing page is dropped from the layout.
+        root.root_widget.finish_transition();
+        root.relayout();
+        assert_eq!(None, root.root_widget.transition_progress());
+        assert_eq!(vec![closed(), closed(), BoundBox::new2(0, 24, 200, 100)], page_rects(&root.root_widget));
+        assert_eq!(vec![0, 1, 2], root.root_widget.child_order().collect::<Vec<_>>());
+    }
 
-            hints_vec.clear();
-        })
+    #[test]
+    fn slide_transition() {
+        let mut root = headless_root(tab_list(), DimsBox::new2(200, 100));
+        root.root_widget.set_transition(TabTransition::Slide, Duration::from_secs(60));
+        root.root_widget.open_tab(1);
+        root.relayout();
+
+        // The tab opened is to the right of the old one, so the incoming page starts off to the
+        // right of the content area and the outgoing page starts in it.
+        let rects = page_rects(&root.root_widget);
+        assert!(rects[1].min.x > 190 && rects[1].min.x <= 200);
+        assert!(rects[0].min.x <= 0 && rects[0].min.x > -10);
+        assert_eq!(closed(), rects[2]);
+
+        // Transitions don't run when they're disabled.
+        root.root_widget.set_transition(TabTransition::None, Duration::from_secs(60));
+        root.root_widget.open_tab(0);
+        assert_eq!(None, root.root_widget.transition_progress());
     }
 }
@@ -0,0 +1,805 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use derin_core::{
+    LoopFlow,
+    accessibility::{AccessibilityNode, AccessibilityRole},
+    timer::{Timer, TimerId},
+    widget::{Parent, Widget, WidgetInfo, WidgetInfoMut, WidgetIdent, WidgetTag, WidgetRenderable, MessageTarget},
+    render::{CursorOp, Renderer, RendererLayout, SubFrame, TextLayoutResult, WidgetTheme, WrapMode},
+};
+use crate::{
+    event::{EventOps, Key, WidgetEvent, WidgetEventSourced, InputState, MouseButton},
+    widgets::{
+        EditBoxTheme, EditColors, LabelTheme,
+        assistants::text_edit::{TextEditAssist, TextEditOps, CursorFlashOp, AreaCharFilter},
+    },
+};
+
+use cgmath_geometry::{D2, rect::{BoundBox, DimsBox, GeoBox}};
+use derin_common_types::layout::SizeBounds;
+use std::{
+    ops::Range,
+    time::Duration,
+};
+
+/// Multi-line editable text widget, with a vertical scroll bar.
+///
+/// Pressing `Enter` inserts a line break, and the up and down arrow keys move the caret between
+/// lines while keeping it in the same column. Text that doesn't fit in the area can be scrolled to
+/// with the mouse wheel or the scroll bar, and the area scrolls automatically to keep the caret
+/// visible while typing.
+///
+/// Lines only get broken at explicit line breaks by default. Use [`set_wrap_mode`] to wrap long
+/// lines at the edge of the area instead.
+///
+/// [`set_wrap_mode`]: ./struct.TextArea.html#method.set_wrap_mode
+#[derive(Debug, Clone)]
+pub struct TextArea {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    size_bounds: SizeBounds,
+
+    /// The scroll offset set since the last layout, which gets clamped to the text's height once
+    /// the area gets laid out.
+    pending_scroll: Option<i32>,
+
+    text: TextAreaText,
+    scroll_bar: TextAreaScrollBar,
+}
+
+/// The editable text, which gets moved upwards inside of the area as it scrolls.
+#[derive(Debug, Clone)]
+struct TextAreaText {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    size_bounds: SizeBounds,
+    edit: TextEditAssist<AreaCharFilter>,
+    flash_timer: Option<TimerId>,
+    wrap_mode: WrapMode,
+    colors: EditColors,
+    /// The caret position the last time the text was laid out.
+    last_cursor_pos: Option<usize>,
+    /// Whether the caret has moved since the area last scrolled to it.
+    caret_moved: bool,
+}
+
+#[derive(Debug, Clone)]
+struct TextAreaScrollBar {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    size_bounds: SizeBounds,
+    scroll: i32,
+    content_height: i32,
+    /// The cursor position and scroll offset when the thumb started getting dragged.
+    drag_start: Option<(i32, i32)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ScrollPicked(i32);
+
+/// Sent by the text when it queues cursor operations, which may move the caret.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CaretMoved;
+
+#[derive(Debug, Clone, Default)]
+pub struct TextAreaTheme(());
+
+#[derive(Debug, Clone, Default)]
+pub struct TextAreaTextTheme {
+    pub colors: EditColors,
+}
+
+/// The theme of a `TextArea`'s scroll bar.
+///
+/// The bar should be drawn as a track filling the bar's rect, with a thumb covering the part of
+/// the track returned by `thumb_range`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextAreaScrollBarTheme {
+    /// How far the text has been scrolled down, in pixels.
+    pub scroll: i32,
+    /// The height of the text.
+    pub content_height: i32,
+    /// The height of the part of the text that's visible.
+    pub view_height: i32,
+}
+
+impl TextAreaScrollBarTheme {
+    /// The vertical range the thumb covers in a track `track_length` pixels long.
+    pub fn thumb_range(&self, track_length: i32) -> Range<i32> {
+        if self.content_height <= self.view_height || self.content_height <= 0 {
+            return 0..track_length;
+        }
+
+        let len = |l: i32| (l as i64 * track_length as i64 / self.content_height as i64) as i32;
+        let start = len(self.scroll);
+        start..start + len(self.view_height).max(1)
+    }
+}
+
+impl TextArea {
+    /// Create a new `TextArea`, containing the included `String` by default.
+    pub fn new(string: String) -> TextArea {
+        let mut widget_tag = WidgetTag::new();
+        widget_tag.register_message(Self::on_scroll_picked);
+        widget_tag.register_message(Self::on_caret_moved);
+
+        TextArea {
+            widget_tag,
+            rect: BoundBox::new2(0, 0, 0, 0),
+            size_bounds: SizeBounds::default(),
+
+            pending_scroll: None,
+
+            text: TextAreaText {
+                widget_tag: WidgetTag::new(),
+                rect: BoundBox::new2(0, 0, 0, 0),
+                size_bounds: SizeBounds::default(),
+                edit: TextEditAssist::new(string),
+                flash_timer: None,
+                wrap_mode: WrapMode::None,
+                colors: EditColors::default(),
+                last_cursor_pos: None,
+                caret_moved: false,
+            },
+            scroll_bar: TextAreaScrollBar {
+                widget_tag: WidgetTag::new(),
+                rect: BoundBox::new2(0, 0, 0, 0),
+                size_bounds: SizeBounds::default(),
+                scroll: 0,
+                content_height: 0,
+                drag_start: None,
+            },
+        }
+    }
+
+    /// Retrieves a reference to the string stored within the `TextArea`.
+    pub fn string(&self) -> &str {
+        &self.text.edit.string
+    }
+
+    /// Retrieves the positions of the `TextArea`'s glyphs from the last time it was laid out, for
+    /// mapping points to string indices and back. Positions are relative to the top of the text,
+    /// not the top of the visible area.
+    ///
+    /// Returns `None` if the area hasn't been laid out yet, or if the renderer doesn't track glyph
+    /// positions.
+    pub fn text_layout(&self) -> Option<&TextLayoutResult> {
+        self.text.edit.text_layout.as_ref()
+    }
+
+    /// Retrieves the `String` stored in the `TextArea`, for mutation.
+    ///
+    /// Calling this function forces the area to be re-drawn, so you're discouraged from calling
+    /// it unless you're actually changing the contents.
+    pub fn string_mut(&mut self) -> &mut String {
+        self.text.widget_tag.request_redraw().request_relayout();
+        &mut self.text.edit.string
+    }
+
+    /// How lines that are wider than the area get broken.
+    #[inline]
+    pub fn wrap_mode(&self) -> WrapMode {
+        self.text.wrap_mode
+    }
+
+    /// Set how lines that are wider than the area get broken. Defaults to `WrapMode::None`, which
+    /// only breaks lines where the text contains line breaks.
+    pub fn set_wrap_mode(&mut self, wrap_mode: WrapMode) {
+        if self.text.wrap_mode != wrap_mode {
+            self.text.wrap_mode = wrap_mode;
+            self.text.widget_tag.request_redraw().request_relayout();
+        }
+    }
+
+    /// How far the text has been scrolled down, in pixels.
+    #[inline]
+    pub fn scroll_offset(&self) -> i32 {
+        self.pending_scroll.unwrap_or(-self.text.rect.min.y)
+    }
+
+    /// Scroll the text down by `scroll` pixels from the top. The offset gets clamped so that the
+    /// text doesn't get scrolled past its end.
+    pub fn set_scroll_offset(&mut self, scroll: i32) {
+        self.pending_scroll = Some(scroll);
+        self.widget_tag.request_relayout();
+    }
+
+    /// Whether typed characters replace the text after the cursor instead of being inserted.
+    pub fn overwrite(&self) -> bool {
+        self.text.edit.overwrite
+    }
+
+    /// Switch between overwrite and insert mode. The user can also toggle this by pressing the
+    /// `Insert` key. The caret is drawn as a block in overwrite mode, and as a bar in insert mode.
+    pub fn set_overwrite(&mut self, overwrite: bool) {
+        if self.text.edit.overwrite != overwrite {
+            self.text.edit.set_overwrite(overwrite);
+            self.text.widget_tag.request_redraw();
+        }
+    }
+
+    /// Enable or disable pasting the primary selection with the middle mouse button, and copying
+    /// selected text into it. Enabled by default, but only has an effect on X11-style platforms.
+    pub fn set_primary_selection_enabled(&mut self, enabled: bool) {
+        self.text.edit.disable_primary_selection = !enabled;
+    }
+
+    /// Retrieves the colors the `TextArea`'s caret and selection are drawn with.
+    pub fn colors(&self) -> EditColors {
+        self.text.colors
+    }
+
+    /// Override the theme's colors for the `TextArea`'s caret and selection.
+    pub fn set_colors(&mut self, colors: EditColors) {
+        if self.text.colors != colors {
+            self.text.colors = colors;
+            self.text.widget_tag.request_redraw();
+        }
+    }
+
+    fn on_scroll_picked(&mut self, picked: &ScrollPicked) {
+        self.set_scroll_offset(picked.0);
+    }
+
+    fn on_caret_moved(&mut self, _: &CaretMoved) {
+        self.widget_tag.request_relayout();
+    }
+
+    /// The scroll offset closest to `scroll` that shows the whole line the caret is on, in a view
+    /// `view_height` pixels tall.
+    fn caret_scroll(&self, scroll: i32, view_height: i32) -> i32 {
+        let caret_line = self.text_layout().and_then(|text_layout| {
+            let caret = text_layout.point_at_index(self.text.edit.cursor_data.cursor_pos)?;
+            text_layout.lines().iter().find(|line| line.top == caret.y)
+        });
+
+        match caret_line {
+            Some(line) if view_height > 0 => if line.top < scroll {
+                line.top
+            } else if line.top + line.height > scroll + view_height {
+                line.top + line.height - view_height
+            } else {
+                scroll
+            },
+            _ => scroll
+        }
+    }
+
+    /// The height of a line of text, used to convert scrolled lines to pixels.
+    fn line_height(&self) -> i32 {
+        self.text_layout()
+            .and_then(|l| l.lines().first())
+            .map(|line| line.height)
+            .unwrap_or(16)
+    }
+
+    fn child_info<R: Renderer>(&self, index: usize) -> Option<WidgetInfo<'_, R>> {
+        let ident = WidgetIdent::Num(index as u32);
+        match index {
+            0 => Some(WidgetInfo::new(ident, index, &self.text)),
+            1 => Some(WidgetInfo::new(ident, index, &self.scroll_bar)),
+            _ => None
+        }
+    }
+
+    fn child_info_mut<R: Renderer>(&mut self, index: usize) -> Option<WidgetInfoMut<'_, R>> {
+        let ident = WidgetIdent::Num(index as u32);
+        match index {
+            0 => Some(WidgetInfoMut::new(ident, index, &mut self.text)),
+            1 => Some(WidgetInfoMut::new(ident, index, &mut self.scroll_bar)),
+            _ => None
+        }
+    }
+}
+
+impl Widget for TextArea {
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        self.widget_tag.request_relayout();
+        &mut self.rect
+    }
+
+    #[inline]
+    fn size_bounds(&self) -> SizeBounds {
+        self.size_bounds
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        let event = event.unwrap();
+
+        // Scroll events that the text doesn't handle get bubbled up to here.
+        let scroll_delta = match event {
+            WidgetEvent::MouseScrollLines{dir, in_widget: true} => Some(dir.y * self.line_height()),
+            WidgetEvent::MouseScrollPx{dir, in_widget: true} => Some(dir.y),
+            _ => None
+        };
+        if let Some(delta) = scroll_delta {
+            let scroll = self.scroll_offset() - delta;
+            self.set_scroll_offset(scroll);
+        }
+
+        EventOps {
+            focus: None,
+            bubble: scroll_delta.is_none() && event.default_bubble(),
+            handled: scroll_delta.is_some(),
+        }
+    }
+
+    fn accessibility_info(&self) -> Option<AccessibilityNode> {
+        Some(AccessibilityNode {
+            value: Some(self.string().to_owned()),
+            ..AccessibilityNode::new(AccessibilityRole::TextInput)
+        })
+    }
+}
+
+impl Widget for TextAreaText {
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        &mut self.rect
+    }
+
+    #[inline]
+    fn size_bounds(&self) -> SizeBounds {
+        self.size_bounds
+    }
+
+    #[inline]
+    fn accepts_focus(&self) -> bool {
+        true
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, input_state: InputState) -> EventOps {
+        let event = event.unwrap();
+
+        let TextEditOps {
+            allow_bubble,
+            redraw,
+            text_changed,
+            cursor_flash,
+            cursor_icon,
+            focus,
+        } = match event {
+            WidgetEvent::KeyDown(Key::Enter, _) if !self.edit.read_only => {
+                self.edit.cursor_ops.push(CursorOp::InsertChar('\n'));
+                self.edit.clear_desired_x();
+                TextEditOps {
+                    allow_bubble: false,
+                    redraw: true,
+                    text_changed: true,
+                    focus: None,
+                    cursor_flash: Some(CursorFlashOp::Start),
+                    cursor_icon: None,
+                }
+            },
+            _ => self.edit.adapt_event(&event, input_state)
+        };
+
+        match (cursor_flash, self.flash_timer) {
+            (Some(CursorFlashOp::Start), None) => {
+                let timer_id = TimerId::new();
+                self.widget_tag.timers_mut().insert(timer_id, Timer::new(Duration::new(1, 0)/2));
+                self.flash_timer = Some(timer_id);
+            },
+            (Some(CursorFlashOp::End), Some(timer_id)) => {
+                self.widget_tag.timers_mut().remove(&timer_id);
+                self.flash_timer = None;
+            },
+            _ => ()
+        }
+
+        if redraw {
+            self.widget_tag.request_redraw();
+        }
+        // Queued operations get applied when the text is laid out. The area scrolls to follow the
+        // caret once they have been, so it needs to get laid out too.
+        if !self.edit.cursor_ops.is_empty() {
+            let widget_id = self.widget_tag.widget_id();
+            self.widget_tag
+                .request_relayout()
+                .send_message_to(CaretMoved, MessageTarget::ParentOf(widget_id));
+        }
+
+        match event {
+            WidgetEvent::Timer{timer_id, times_triggered, ..} if Some(timer_id) == self.flash_timer => {
+                self.edit.cursor_data.draw_cursor = times_triggered % 2 == 0;
+                self.widget_tag.request_redraw();
+            },
+            _ => ()
+        };
+
+        if let Some(cursor_icon) = cursor_icon {
+            self.widget_tag.set_cursor_icon(cursor_icon).ok();
+        }
+
+        EventOps {
+            focus,
+            bubble: allow_bubble && event.default_bubble(),
+            handled: redraw || text_changed || !allow_bubble,
+        }
+    }
+}
+
+impl Widget for TextAreaScrollBar {
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        &mut self.rect
+    }
+
+    #[inline]
+    fn size_bounds(&self) -> SizeBounds {
+        self.size_bounds
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        let event = event.unwrap();
+        let view_height = self.rect.height();
+        let thumb = self.theme_parameters().thumb_range(view_height);
+
+        let scroll = match event {
+            // Pressing on the thumb starts dragging it, and pressing on the track moves a page
+            // towards the cursor.
+            WidgetEvent::MouseDown{pos, in_widget: true, button: MouseButton::Left} => match pos.y {
+                y if y < thumb.start => Some(self.scroll - view_height),
+                y if y >= thumb.end => Some(self.scroll + view_height),
+                y => {
+                    self.drag_start = Some((y, self.scroll));
+                    None
+                }
+            },
+            WidgetEvent::MouseMove{new_pos, ..} => self.drag_start.map(|(start_y, start_scroll)| {
+                let drag = (new_pos.y - start_y) as i64 * self.content_height as i64 / view_height.max(1) as i64;
+                start_scroll + drag as i32
+            }),
+            WidgetEvent::MouseUp{button: MouseButton::Left, ..} => {
+                self.drag_start = None;
+                None
+            },
+            _ => None
+        };
+
+        if let Some(scroll) = scroll {
+            let widget_id = self.widget_tag.widget_id();
+            self.widget_tag.send_message_to(ScrollPicked(scroll), MessageTarget::ParentOf(widget_id));
+        }
+
+        EventOps {
+            focus: None,
+            bubble: event.default_bubble(),
+            handled: scroll.is_some() || self.drag_start.is_some(),
+        }
+    }
+}
+
+impl TextAreaScrollBar {
+    fn theme_parameters(&self) -> TextAreaScrollBarTheme {
+        TextAreaScrollBarTheme {
+            scroll: self.scroll,
+            content_height: self.content_height,
+            view_height: self.rect.height(),
+        }
+    }
+}
+
+impl Parent for TextArea {
+    fn num_children(&self) -> usize {
+        2
+    }
+
+    fn framed_child<R: Renderer>(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, R>> {
+        match widget_ident {
+            WidgetIdent::Num(index) => self.child_info(index as usize),
+            _ => None
+        }
+    }
+    fn framed_child_mut<R: Renderer>(&mut self, widget_ident: WidgetIdent) -> Option<WidgetInfoMut<'_, R>> {
+        match widget_ident {
+            WidgetIdent::Num(index) => self.child_info_mut(index as usize),
+            _ => None
+        }
+    }
+
+    fn framed_children<'a, R, G>(&'a self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfo<'a, R>) -> LoopFlow
+    {
+        if let LoopFlow::Break = for_each(WidgetInfo::new(WidgetIdent::Num(0), 0, &self.text)) {
+            return;
+        }
+        let _ = for_each(WidgetInfo::new(WidgetIdent::Num(1), 1, &self.scroll_bar));
+    }
+
+    fn framed_children_mut<'a, R, G>(&'a mut self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfoMut<'a, R>) -> LoopFlow
+    {
+        if let LoopFlow::Break = for_each(WidgetInfoMut::new(WidgetIdent::Num(0), 0, &mut self.text)) {
+            return;
+        }
+        let _ = for_each(WidgetInfoMut::new(WidgetIdent::Num(1), 1, &mut self.scroll_bar));
+    }
+
+    fn framed_child_by_index<R: Renderer>(&self, index: usize) -> Option<WidgetInfo<'_, R>> {
+        self.child_info(index)
+    }
+    fn framed_child_by_index_mut<R: Renderer>(&mut self, index: usize) -> Option<WidgetInfoMut<'_, R>> {
+        self.child_info_mut(index)
+    }
+}
+
+impl<R> WidgetRenderable<R> for TextArea
+    where R: Renderer
+{
+    type Theme = TextAreaTheme;
+    fn theme(&self) -> TextAreaTheme {
+        TextAreaTheme(())
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, layout: &mut R::Layout) {
+        let metrics = layout.control_metrics();
+        let result = layout.finish();
+        self.size_bounds = result.size_bounds.expand_min(DimsBox::new2(
+            metrics.slider_length + metrics.slider_thickness,
+            metrics.control_height,
+        ));
+
+        // The text fills the area to the left of the scroll bar, and is at least as tall as the
+        // area so that clicks below the last line still reach it.
+        let (width, height) = (self.rect.width(), self.rect.height());
+        let bar_width = metrics.slider_thickness;
+        let view_width = (width - bar_width).max(0);
+        let content_height = self.text.size_bounds.min.height().max(height);
+        let max_scroll = content_height - height;
+
+        // The text applies its cursor operations when it gets laid out, which happens after the
+        // area does. Lay the area out again once that's happened, so that it can scroll to the
+        // caret.
+        if !self.text.edit.cursor_ops.is_empty() {
+            self.widget_tag.request_relayout();
+        }
+        if self.text.caret_moved {
+            self.text.caret_moved = false;
+            let scroll = self.caret_scroll(self.scroll_offset(), height);
+            self.pending_scroll = Some(scroll);
+        }
+        let scroll = self.pending_scroll.take()
+            .unwrap_or(-self.text.rect.min.y)
+            .min(max_scroll)
+            .max(0);
+
+        let text_width = match self.text.wrap_mode {
+            WrapMode::None => view_width.max(self.text.size_bounds.min.width()),
+            _ => view_width
+        };
+        let text_rect = BoundBox::new2(0, -scroll, text_width, content_height - scroll);
+        if self.text.rect != text_rect {
+            // Wrapped text needs to be re-wrapped at the new width.
+            if self.text.rect.width() != text_rect.width() && self.text.wrap_mode != WrapMode::None {
+                self.text.widget_tag.request_relayout();
+            }
+            self.text.rect = text_rect;
+        }
+
+        self.scroll_bar.rect = BoundBox::new2(view_width, 0, width, height);
+        if (self.scroll_bar.scroll, self.scroll_bar.content_height) != (scroll, content_height) {
+            self.scroll_bar.scroll = scroll;
+            self.scroll_bar.content_height = content_height;
+            self.scroll_bar.widget_tag.request_redraw();
+        }
+    }
+}
+
+impl<R> WidgetRenderable<R> for TextAreaText
+    where R: Renderer
+{
+    type Theme = TextAreaTextTheme;
+    fn theme(&self) -> TextAreaTextTheme {
+        TextAreaTextTheme {
+            colors: self.colors,
+        }
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, layout: &mut R::Layout) {
+        layout.set_text_wrap(self.wrap_mode, self.rect.width());
+        layout.prepare_edit_string(
+            &mut self.edit.string,
+            &mut self.edit.cursor_data,
+            self.edit.cursor_ops.drain(..),
+        );
+        self.edit.update_primary_selection();
+
+        let result = layout.finish();
+        self.size_bounds = result.size_bounds;
+        self.edit.text_layout = result.text_layout;
+
+        // The area scrolls to the caret, and moves the scroll bar to match, the next time it gets
+        // laid out.
+        let cursor_pos = self.edit.cursor_data.cursor_pos;
+        if self.last_cursor_pos != Some(cursor_pos) {
+            self.last_cursor_pos = Some(cursor_pos);
+            self.caret_moved = true;
+        }
+    }
+}
+
+impl<R> WidgetRenderable<R> for TextAreaScrollBar
+    where R: Renderer
+{
+    type Theme = TextAreaScrollBarTheme;
+    fn theme(&self) -> TextAreaScrollBarTheme {
+        self.theme_parameters()
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, layout: &mut R::Layout) {
+        let result = layout.finish();
+        self.size_bounds = result.size_bounds;
+    }
+}
+
+impl WidgetTheme for TextAreaTheme {
+    type Fallback = EditBoxTheme;
+    fn fallback(self) -> Option<EditBoxTheme> {
+        Some(EditBoxTheme::default())
+    }
+}
+
+impl WidgetTheme for TextAreaTextTheme {
+    type Fallback = LabelTheme;
+    fn fallback(self) -> Option<LabelTheme> {
+        Some(LabelTheme{ mnemonic: None })
+    }
+}
+
+impl WidgetTheme for TextAreaScrollBarTheme {
+    type Fallback = !;
+    fn fallback(self) -> Option<!> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cgmath::Point2,
+        core::{Root, WindowEvent, render::headless::HeadlessRenderer},
+        test_helpers::{click, headless_root, press_key, send_events},
+    };
+
+    /// An area showing two and a half of its ten lines, with the text focused and the caret at
+    /// the start of the text.
+    fn text_area() -> Root<TextArea, HeadlessRenderer> {
+        let string = (0..10).map(|i| i.to_string()).collect::<Vec<_>>().join("\n");
+        let mut root = headless_root(TextArea::new(string), DimsBox::new2(100, 40));
+        click(&mut root, Point2::new(2, 2));
+        root
+    }
+
+    fn scroll(root: &Root<TextArea, HeadlessRenderer>) -> (i32, i32, i32) {
+        let area = &root.root_widget;
+        (area.scroll_offset(), -area.text.rect.min.y, area.scroll_bar.scroll)
+    }
+
+    #[test]
+    fn enter_inserts_newline() {
+        let mut root = headless_root(TextArea::new("ab".to_string()), DimsBox::new2(100, 40));
+        click(&mut root, Point2::new(2, 2));
+        press_key(&mut root, Key::Enter);
+        assert_eq!("\nab", root.root_widget.string());
+
+        // The caret ends up after the line break, so typing continues on the new line.
+        send_events(&mut root, &[WindowEvent::Char('c')]);
+        assert_eq!("\ncab", root.root_widget.string());
+    }
+
+    #[test]
+    fn layout() {
+        let root = text_area();
+        let area = &root.root_widget;
+
+        // The scroll bar sits on the right, and the text takes up the rest of the width.
+        assert_eq!(BoundBox::new2(84, 0, 100, 40), area.scroll_bar.rect);
+        assert_eq!(BoundBox::new2(0, 0, 84, 160), area.text.rect);
+        assert_eq!(160, area.scroll_bar.content_height);
+        assert_eq!((0, 0, 0), scroll(&root));
+    }
+
+    #[test]
+    fn scroll_to_caret() {
+        let mut root = text_area();
+
+        // Moving the caret onto the third line scrolls it into view.
+        press_key(&mut root, Key::DArrow);
+        assert_eq!((0, 0, 0), scroll(&root));
+        press_key(&mut root, Key::DArrow);
+        assert_eq!((8, 8, 8), scroll(&root));
+        for _ in 0..3 {
+            press_key(&mut root, Key::DArrow);
+        }
+        assert_eq!((56, 56, 56), scroll(&root));
+
+        // Moving back up only scrolls once the caret leaves the top of the view.
+        for _ in 0..3 {
+            press_key(&mut root, Key::UArrow);
+        }
+        assert_eq!((32, 32, 32), scroll(&root));
+
+        // Typing a line break also scrolls to follow the caret.
+        root.root_widget.set_scroll_offset(0);
+        root.relayout();
+        assert_eq!((0, 0, 0), scroll(&root));
+        press_key(&mut root, Key::Enter);
+        assert_eq!((24, 24, 24), scroll(&root));
+    }
+
+    #[test]
+    fn drag_thumb() {
+        let mut root = text_area();
+        // Two and a half of the ten lines are visible, so the thumb covers the top quarter of the
+        // bar.
+        assert_eq!(0..10, root.root_widget.scroll_bar.theme_parameters().thumb_range(40));
+
+        send_events(&mut root, &[
+            WindowEvent::MouseMove(Point2::new(90, 5)),
+            WindowEvent::MouseDown(MouseButton::Left),
+            WindowEvent::MouseMove(Point2::new(90, 15)),
+        ]);
+        // Dragging the thumb by a quarter of the bar scrolls by a quarter of the text.
+        assert_eq!((40, 40, 40), scroll(&root));
+
+        // Dragging past the end of the bar stops at the end of the text.
+        send_events(&mut root, &[
+            WindowEvent::MouseMove(Point2::new(90, 60)),
+            WindowEvent::MouseUp(MouseButton::Left),
+        ]);
+        assert_eq!((120, 120, 120), scroll(&root));
+
+        // Once the button's released, moving the mouse doesn't scroll.
+        send_events(&mut root, &[WindowEvent::MouseMove(Point2::new(90, 5))]);
+        assert_eq!((120, 120, 120), scroll(&root));
+    }
+}
@@ -0,0 +1,566 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use derin_core::{
+    LoopFlow,
+    event::{EventOps, WidgetEvent, WidgetEventSourced, InputState, MouseHoverChange},
+    widget::{WidgetIdent, WidgetRenderable, WidgetTag, WidgetInfo, WidgetInfoMut, WidgetId, Widget, Parent, MessageTarget},
+    render::{Renderer, RendererLayout, SubFrame, WidgetTheme},
+};
+use crate::{
+    container::WidgetContainer,
+    event::Key,
+    layout::GridLayout,
+    widgets::{
+        ButtonTheme, Contents, GroupTheme,
+        assistants::ButtonState,
+    },
+};
+
+use cgmath_geometry::{D2, rect::{BoundBox, DimsBox, GeoBox}};
+use derin_common_types::layout::{SizeBounds, WidgetPos};
+use std::cell::RefCell;
+
+use derin_layout_engine::{GridEngine, UpdateHeapCache, SolveError};
+
+/// A push-button that stays pressed while toggled on.
+///
+/// Unlike [`CheckBox`], this is drawn as a regular button, which makes it well-suited for toolbar
+/// state such as bold or italic text. When toggled, calls the [`on_toggle`] function in the
+/// associated handler passed in by the `new` function. The button can be toggled by clicking it,
+/// or by pressing Space or Enter while it has keyboard focus.
+///
+/// Placing toggle buttons inside a [`ToggleButtonGroup`] makes them mutually exclusive. Toggle
+/// buttons in a group can't be untoggled by the user, so that one of them always stays selected.
+///
+/// [`CheckBox`]: ./struct.CheckBox.html
+/// [`on_toggle`]: ./trait.ToggleButtonHandler.html#tymethod.on_toggle
+/// [`ToggleButtonGroup`]: ./struct.ToggleButtonGroup.html
+#[derive(Debug, Clone)]
+pub struct ToggleButton<H> {
+    widget_tag: WidgetTag,
+    bounds: BoundBox<D2, i32>,
+    state: ButtonState,
+    toggled: bool,
+    /// Whether the button's inside a `ToggleButtonGroup`, which keeps it from being untoggled.
+    grouped: bool,
+    pub handler: H,
+    contents: Contents,
+    size_bounds: SizeBounds
+}
+
+/// Determines which action, if any, should be taken in response to a button toggle.
+pub trait ToggleButtonHandler: 'static {
+    fn on_toggle(&mut self, toggled: bool);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ToggleButtonTheme {
+    pub toggled: bool,
+    pub state: ButtonState,
+}
+
+/// A set of toggle buttons, only one of which may be toggled at once.
+///
+/// Works like [`RadioButtonList`], but for [`ToggleButton`]s. Useful for building segmented
+/// controls.
+///
+/// [`RadioButtonList`]: ./struct.RadioButtonList.html
+/// [`ToggleButton`]: ./struct.ToggleButton.html
+#[derive(Debug, Clone)]
+pub struct ToggleButtonGroup<C, L>
+    where L: GridLayout
+{
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+
+    layout_engine: GridEngine,
+    buttons: C,
+    layout: L
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct ToggleButtonGroupTheme(());
+
+/// Sent by a toggle button to its parent when it gets toggled on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ToggleButtonSelected(WidgetId);
+
+/// Sent by a toggle button group to its children, untoggling every button except for the one
+/// specified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct UntoggleExcept(WidgetId);
+
+/// Dispatched by a toggle button group directly to its children whenever it's laid out, letting
+/// them know that they're in a group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct InToggleButtonGroup;
+
+impl<H> ToggleButton<H>
+    where H: ToggleButtonHandler
+{
+    /// Creates a new toggle button with the given toggled state, contents, and [toggle handler].
+    ///
+    /// [toggle handler]: ./trait.ToggleButtonHandler.html
+    pub fn new(toggled: bool, contents: Contents, handler: H) -> ToggleButton<H> {
+        let mut widget_tag = WidgetTag::new();
+        widget_tag.register_message(Self::on_untoggle_except);
+        widget_tag.register_message(Self::on_in_group);
+        ToggleButton {
+            widget_tag,
+            bounds: BoundBox::new2(0, 0, 0, 0),
+            state: ButtonState::Normal,
+            toggled,
+            grouped: false,
+            handler,
+            contents,
+            size_bounds: SizeBounds::default()
+        }
+    }
+
+    /// Retrieves the contents of the toggle button.
+    pub fn contents(&self) -> &Contents {
+        &self.contents
+    }
+
+    /// Retrieves the contents of the toggle button, for mutation.
+    ///
+    /// Calling this function forces the toggle button to be re-drawn, so you're discouraged from
+    /// calling it unless you're actually changing the contents.
+    pub fn contents_mut(&mut self) -> &mut Contents {
+        self.widget_tag
+            .request_redraw()
+            .request_relayout();
+        &mut self.contents
+    }
+
+    /// Retrieves whether or not the toggle button is toggled.
+    pub fn toggled(&self) -> bool {
+        self.toggled
+    }
+
+    /// Retrieves whether or not the toggle button is toggled, for mutation.
+    ///
+    /// Calling this function forces the toggle button to be re-drawn, so you're discouraged from
+    /// calling it unless you're actually changing the contents.
+    pub fn toggled_mut(&mut self) -> &mut bool {
+        self.widget_tag.request_redraw();
+        &mut self.toggled
+    }
+
+    fn toggle(&mut self) {
+        // The selected button in a group stays selected until another one gets toggled.
+        if self.toggled && self.grouped {
+            return;
+        }
+        self.toggled = !self.toggled;
+        self.widget_tag.request_redraw();
+        if self.toggled {
+            let widget_id = self.widget_tag.widget_id();
+            self.widget_tag.send_message_to(ToggleButtonSelected(widget_id), MessageTarget::ParentOf(widget_id));
+        }
+        self.handler.on_toggle(self.toggled);
+    }
+
+    fn on_untoggle_except(&mut self, untoggle: &UntoggleExcept) {
+        if self.widget_tag.widget_id() != untoggle.0 && self.toggled {
+            self.toggled = false;
+            self.widget_tag.request_redraw();
+            self.handler.on_toggle(false);
+        }
+    }
+
+    fn on_in_group(&mut self, _: &InToggleButtonGroup) {
+        self.grouped = true;
+    }
+}
+
+impl<H> Widget for ToggleButton<H>
+    where H: ToggleButtonHandler
+{
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.bounds
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        &mut self.bounds
+    }
+
+    fn size_bounds(&self) -> SizeBounds {
+        self.size_bounds
+    }
+
+    #[inline]
+    fn accepts_focus(&self) -> bool {
+        true
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        use self::WidgetEvent::*;
+        let event = event.unwrap();
+
+        let mut handled = true;
+        let new_state = match event {
+            MouseMove{hover_change: Some(ref change), ..} => match change {
+                MouseHoverChange::Enter => ButtonState::Hover,
+                MouseHoverChange::Exit => ButtonState::Normal,
+                _ => self.state
+            },
+            MouseDown{..} => ButtonState::Pressed,
+            MouseUp{in_widget: true, pressed_in_widget: true, ..} => ButtonState::Hover,
+            Click{..} => {
+                self.toggle();
+                self.state
+            },
+            MouseUp{in_widget: false, ..} => ButtonState::Normal,
+            MouseLeaveWindow if self.state == ButtonState::Hover => ButtonState::Normal,
+            KeyDown(Key::Space, _) |
+            KeyDown(Key::Enter, _) => {
+                self.toggle();
+                self.state
+            },
+            GainFocus(_, _) => ButtonState::Hover,
+            LoseFocus => ButtonState::Normal,
+            _ => {
+                handled = false;
+                self.state
+            }
+        };
+
+        if new_state != self.state {
+            self.widget_tag.request_redraw();
+            self.state = new_state;
+        }
+
+
+        EventOps {
+            focus: None,
+            bubble: event.default_bubble(),
+            handled,
+        }
+    }
+}
+
+impl<R, H> WidgetRenderable<R> for ToggleButton<H>
+    where R: Renderer,
+          H: ToggleButtonHandler
+{
+    type Theme = ToggleButtonTheme;
+
+    fn theme(&self) -> ToggleButtonTheme {
+        ToggleButtonTheme {
+            toggled: self.toggled,
+            state: match self.toggled {
+                true => ButtonState::Pressed,
+                false => self.state
+            },
+        }
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, layout: &mut R::Layout) {
+        match self.contents {
+            Contents::Text(ref s) => layout.prepare_string(s),
+            Contents::Icon(ref i) => layout.prepare_icon(i),
+            Contents::Rich(ref r) => layout.prepare_rich_string(r),
+        }
+
+        let control_height = layout.control_metrics().control_height;
+        let result = layout.finish();
+        self.size_bounds = result.size_bounds.expand_min(DimsBox::new2(0, control_height));
+    }
+}
+
+impl<C, L> ToggleButtonGroup<C, L>
+    where C: WidgetContainer<dyn Widget>,
+          L: GridLayout
+{
+    /// Takes a collection of toggle buttons, as well as the layout in which to place those buttons.
+    pub fn new(buttons: C, layout: L) -> ToggleButtonGroup<C, L> {
+        let mut widget_tag = WidgetTag::new();
+        widget_tag.register_message(Self::on_child_selected);
+        ToggleButtonGroup {
+            widget_tag,
+            rect: BoundBox::new2(0, 0, 0, 0),
+
+            layout_engine: GridEngine::new(),
+            buttons, layout
+        }
+    }
+
+    /// Retrieves the collection of toggle buttons stored within this group.
+    pub fn buttons(&self) -> &C {
+        &self.buttons
+    }
+
+    /// Retrieves the collection of toggle buttons stored within this group, for mutation.
+    pub fn buttons_mut(&mut self) -> &mut C {
+        &mut self.buttons
+    }
+
+    fn on_child_selected(&mut self, child_selected: &ToggleButtonSelected) {
+        let widget_id = self.widget_tag.widget_id();
+        self.widget_tag.send_message_to(UntoggleExcept(child_selected.0), MessageTarget::ChildrenOf(widget_id));
+    }
+}
+
+impl<C, L> Widget for ToggleButtonGroup<C, L>
+    where C: WidgetContainer<dyn Widget>,
+          L: GridLayout
+{
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        self.widget_tag.request_relayout();
+        &mut self.rect
+    }
+
+    fn size_bounds(&self) -> SizeBounds {
+        self.layout_engine.actual_size_bounds()
+    }
+
+    #[inline]
+    fn on_widget_event(&mut self, _: WidgetEventSourced, _: InputState) -> EventOps {
+        // TODO: PASS FOCUS TO CHILD
+
+        EventOps {
+            focus: None,
+            bubble: true,
+            handled: false,
+        }
+    }
+}
+
+impl<C, L> Parent for ToggleButtonGroup<C, L>
+    where C: WidgetContainer<dyn Widget>,
+          L: GridLayout
+{
+    fn num_children(&self) -> usize {
+        self.buttons.num_children()
+    }
+
+    fn framed_child<R: Renderer>(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, R>> {
+        self.buttons.framed_child(widget_ident).map(WidgetInfo::erase_subtype)
+    }
+    fn framed_child_mut<R: Renderer>(&mut self, widget_ident: WidgetIdent) -> Option<WidgetInfoMut<'_, R>> {
+        self.buttons.framed_child_mut(widget_ident).map(WidgetInfoMut::erase_subtype)
+    }
+
+    fn framed_children<'a, R, G>(&'a self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfo<'a, R>) -> LoopFlow
+    {
+        self.buttons.framed_children(|summary| for_each(WidgetInfo::erase_subtype(summary)))
+    }
+
+    fn framed_children_mut<'a, R, G>(&'a mut self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfoMut<'a, R>) -> LoopFlow
+    {
+        self.buttons.framed_children_mut(|summary| for_each(WidgetInfoMut::erase_subtype(summary)))
+    }
+
+    fn framed_child_by_index<R: Renderer>(&self, index: usize) -> Option<WidgetInfo<'_, R>> {
+        self.buttons.framed_child_by_index(index).map(WidgetInfo::erase_subtype)
+    }
+    fn framed_child_by_index_mut<R: Renderer>(&mut self, index: usize) -> Option<WidgetInfoMut<'_, R>> {
+        self.buttons.framed_child_by_index_mut(index).map(WidgetInfoMut::erase_subtype)
+    }
+}
+
+impl<R, C, L> WidgetRenderable<R> for ToggleButtonGroup<C, L>
+    where R: Renderer,
+          C: WidgetContainer<dyn Widget>,
+          L: GridLayout
+{
+    type Theme = ToggleButtonGroupTheme;
+
+    fn theme(&self) -> ToggleButtonGroupTheme {
+        ToggleButtonGroupTheme(())
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, _: &mut R::Layout) {
+        #[derive(Default)]
+        struct HeapCache {
+            update_heap_cache: UpdateHeapCache,
+            hints_vec: Vec<WidgetPos>,
+            rects_vec: Vec<Result<BoundBox<D2, i32>, SolveError>>
+        }
+        thread_local! {
+            static HEAP_CACHE: RefCell<HeapCache> = RefCell::new(HeapCache::default());
+        }
+
+        // The buttons can be of any type, so they get told that they're in a group through a
+        // message. It's dispatched directly rather than sent, since a button can't be clicked
+        // before it's been laid out, but could be clicked before a sent message gets delivered.
+        self.buttons.children_mut::<_>(|mut summary| {
+            summary.widget_mut().dispatch_message(&InToggleButtonGroup);
+            LoopFlow::Continue
+        });
+
+        HEAP_CACHE.with(|hc| {
+            let mut hc = hc.borrow_mut();
+
+            let HeapCache {
+                ref mut update_heap_cache,
+                ref mut hints_vec,
+                ref mut rects_vec
+            } = *hc;
+
+            let num_children = self.num_children();
+            self.buttons.children::<_>(|summary| {
+                let widget_size_bounds = summary.widget().size_bounds();
+                let mut layout_hints = self.layout.positions(summary.ident, summary.index, num_children).unwrap_or(WidgetPos::default());
+                layout_hints.size_bounds = SizeBounds {
+                    min: layout_hints.size_bounds.clamp_dims(widget_size_bounds.min),
+                    max: layout_hints.size_bounds.clamp_dims(widget_size_bounds.max),
+                };
+                let min_intrinsic_size = summary.widget().min_intrinsic_size();
+                if min_intrinsic_size != widget_size_bounds.min {
+                    layout_hints.preferred_size = Some(layout_hints.size_bounds.clamp_dims(min_intrinsic_size));
+                }
+                hints_vec.push(layout_hints);
+                rects_vec.push(Ok(BoundBox::new2(0, 0, 0, 0)));
+                LoopFlow::Continue
+            });
+
+            self.layout_engine.desired_size = self.rect.dims();
+            self.layout_engine.set_grid_size(self.layout.grid_size(num_children));
+            self.layout_engine.update_engine(hints_vec, rects_vec, update_heap_cache);
+
+            let mut rects_iter = rects_vec.drain(..);
+            self.buttons.children_mut::<_>(|mut summary| {
+                match rects_iter.next() {
+                    Some(rect) => *summary.widget_mut().rect_mut() = rect.unwrap_or(BoundBox::new2(0xDEDBEEF, 0xDEDBEEF, 0xDEDBEEF, 0xDEDBEEF)),
+                    None => return LoopFlow::Break
+                }
+
+                LoopFlow::Continue
+            });
+
+            hints_vec.clear();
+        })
+    }
+}
+
+impl WidgetTheme for ToggleButtonTheme {
+    type Fallback = ButtonTheme;
+    fn fallback(self) -> Option<ButtonTheme> {
+        Some(ButtonTheme{ state: self.state, mnemonic: None })
+    }
+}
+
+impl WidgetTheme for ToggleButtonGroupTheme {
+    type Fallback = GroupTheme;
+    fn fallback(self) -> Option<GroupTheme> {
+        Some(GroupTheme::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cgmath::Point2,
+        layout::{LayoutHorizontal, Margins},
+        test_helpers::{click, headless_root, press_access_key, press_key},
+        widgets::{Group, Label},
+    };
+    use std::rc::Rc;
+
+    /// Records every toggle the handler gets told about.
+    struct Recorder(Rc<RefCell<Vec<bool>>>);
+
+    impl ToggleButtonHandler for Recorder {
+        fn on_toggle(&mut self, toggled: bool) {
+            self.0.borrow_mut().push(toggled);
+        }
+    }
+
+    fn toggle_button(toggled: bool, log: &Rc<RefCell<Vec<bool>>>) -> ToggleButton<Recorder> {
+        ToggleButton::new(toggled, Contents::Text("B".to_string()), Recorder(log.clone()))
+    }
+
+    fn horizontal() -> LayoutHorizontal {
+        LayoutHorizontal::new(Margins::new(0, 0, 0, 0), Default::default())
+    }
+
+    fn toggled(group: &ToggleButtonGroup<(ToggleButton<Recorder>, ToggleButton<Recorder>), LayoutHorizontal>) -> (bool, bool) {
+        (group.buttons().0.toggled(), group.buttons().1.toggled())
+    }
+
+    #[test]
+    fn toggle() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut root = headless_root(toggle_button(false, &log), DimsBox::new2(100, 24));
+
+        click(&mut root, Point2::new(10, 10));
+        assert!(root.root_widget.toggled());
+        click(&mut root, Point2::new(10, 10));
+        assert!(!root.root_widget.toggled());
+        assert_eq!(vec![true, false], *log.borrow());
+    }
+
+    #[test]
+    fn group_selection() {
+        let logs = [Rc::new(RefCell::new(Vec::new())), Rc::new(RefCell::new(Vec::new()))];
+        let group = ToggleButtonGroup::new((toggle_button(true, &logs[0]), toggle_button(false, &logs[1])), horizontal());
+        let mut root = headless_root(group, DimsBox::new2(200, 24));
+
+        // Toggling the second button untoggles the first.
+        click(&mut root, Point2::new(150, 12));
+        assert_eq!((false, true), toggled(&root.root_widget));
+        assert_eq!(vec![false], *logs[0].borrow());
+        assert_eq!(vec![true], *logs[1].borrow());
+
+        // Clicking the selected button leaves it selected.
+        click(&mut root, Point2::new(150, 12));
+        assert_eq!((false, true), toggled(&root.root_widget));
+        assert_eq!(vec![true], *logs[1].borrow());
+
+        click(&mut root, Point2::new(50, 12));
+        assert_eq!((true, false), toggled(&root.root_widget));
+        assert_eq!(vec![false, true], *logs[0].borrow());
+        assert_eq!(vec![true, false], *logs[1].borrow());
+    }
+
+    #[test]
+    fn group_keys() {
+        let logs = [Rc::new(RefCell::new(Vec::new())), Rc::new(RefCell::new(Vec::new()))];
+        let buttons = ToggleButtonGroup::new((toggle_button(true, &logs[0]), toggle_button(false, &logs[1])), horizontal());
+        let mut root = headless_root(Group::new((Label::with_mnemonic("&Align"), buttons), horizontal()), DimsBox::new2(240, 24));
+
+        // Focus the first button through the label, then try to untoggle it from the keyboard.
+        press_access_key(&mut root, Key::A);
+        assert!(root.root_widget.container().1.buttons().0.widget_tag().has_keyboard_focus());
+        press_key(&mut root, Key::Space);
+        press_key(&mut root, Key::Enter);
+        assert!(root.root_widget.container().1.buttons().0.toggled());
+        assert!(logs[0].borrow().is_empty());
+        assert!(logs[1].borrow().is_empty());
+    }
+}
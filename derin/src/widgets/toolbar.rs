@@ -0,0 +1,890 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use derin_core::{
+    LoopFlow,
+    widget::{Parent, Widget, WidgetInfo, WidgetInfoMut, WidgetIdent, WidgetTag, WidgetRenderable, MessageTarget},
+    render::{Renderer, RendererLayout, SubFrame, WidgetTheme},
+};
+use crate::{
+    event::{EventOps, FocusChange, WidgetEvent, WidgetEventSourced, InputState, Key, MouseHoverChange},
+    widgets::{
+        ButtonTheme, GroupTheme,
+        assistants::ButtonState,
+    },
+};
+
+use cgmath_geometry::{D2, rect::{BoundBox, DimsBox, GeoBox}};
+use derin_common_types::layout::SizeBounds;
+
+use std::cmp::Reverse;
+
+/// A horizontal bar of items, such as tool buttons, placed above a content widget.
+///
+/// The items are laid out left-to-right at their minimum widths. When the toolbar is too narrow to
+/// fit all of them, the items that don't fit are hidden and a `"»"` button is shown at the right
+/// edge of the bar. Pressing that button opens a popup listing the hidden items. The toolbar
+/// re-measures its items whenever it gets laid out, so items move in and out of the popup as the
+/// toolbar is resized.
+///
+/// Items are kept in the bar in order of their priority, which is `0` by default and can be changed
+/// with [`set_priority`]. Higher-priority items are kept in the bar before lower-priority ones.
+/// Items with equal priority overflow from right to left, so by default the rightmost items are
+/// the first to get moved into the popup. An item that doesn't fit doesn't push the items after
+/// it out of the bar, so a narrow item can stay in the bar after a wider one overflows.
+///
+/// The popup closes when the `"»"` button is pressed again, when `Escape` is pressed, or when the
+/// toolbar loses keyboard focus. It's drawn over the content widget, so the toolbar should wrap
+/// the content of the area it's attached to.
+///
+/// [`set_priority`]: ./struct.Toolbar.html#method.set_priority
+#[derive(Debug, Clone)]
+pub struct Toolbar<W, C> {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    size_bounds: SizeBounds,
+    content: C,
+
+    items: Vec<ToolbarItem>,
+    /// The widgets of the items kept in the bar. Each item's widget is stored either here or in
+    /// the overflow panel, at the item's index.
+    bar_widgets: Vec<Option<W>>,
+    overflow_button: OverflowButton,
+    overflow_panel: OverflowPanel<W>,
+    /// Whether or not the overflow popup is open.
+    open: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ToolbarItem {
+    priority: i32,
+    overflowed: bool,
+}
+
+#[derive(Debug, Clone)]
+struct OverflowButton {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    size_bounds: SizeBounds,
+    hover: bool,
+    open: bool,
+}
+
+/// The popup that holds the overflowed items.
+#[derive(Debug, Clone)]
+struct OverflowPanel<W> {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    /// The widgets of the overflowed items.
+    widgets: Vec<Option<W>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct OverflowPressed;
+
+/// A child of the toolbar, in the order the children are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ToolbarChild {
+    Content,
+    Item(usize),
+    OverflowButton,
+    Panel,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ToolbarTheme(());
+
+#[derive(Debug, Clone, Copy)]
+pub struct ToolbarOverflowButtonTheme {
+    pub state: ButtonState,
+    /// Whether or not the overflow popup is open.
+    pub open: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ToolbarOverflowTheme(());
+
+impl<W, C> Toolbar<W, C>
+    where W: Widget,
+          C: Widget
+{
+    /// Creates a new toolbar containing the given items, placed above `content`.
+    pub fn new(items: Vec<W>, content: C) -> Toolbar<W, C> {
+        let mut widget_tag = WidgetTag::new();
+        widget_tag.register_message(Self::on_overflow_pressed);
+
+        // The popup lies over the content, and should take the cursor from it.
+        let mut panel_tag = WidgetTag::new();
+        panel_tag.set_popup(true);
+
+        Toolbar {
+            widget_tag,
+            rect: BoundBox::new2(0, 0, 0, 0),
+            size_bounds: SizeBounds::default(),
+            content,
+
+            items: items.iter().map(|_| ToolbarItem{ priority: 0, overflowed: false }).collect(),
+            overflow_panel: OverflowPanel {
+                widget_tag: panel_tag,
+                rect: BoundBox::new2(0, 0, 0, 0),
+                widgets: items.iter().map(|_| None).collect(),
+            },
+            bar_widgets: items.into_iter().map(Some).collect(),
+            overflow_button: OverflowButton {
+                widget_tag: WidgetTag::new(),
+                rect: BoundBox::new2(0, 0, 0, 0),
+                size_bounds: SizeBounds::default(),
+                hover: false,
+                open: false,
+            },
+            open: false,
+        }
+    }
+
+    /// Retrieves the widget displayed below the toolbar.
+    pub fn content(&self) -> &C {
+        &self.content
+    }
+
+    /// Retrieves the widget displayed below the toolbar, for mutation.
+    ///
+    /// Calling this function forces the toolbar to be re-laid out, so you're discouraged from
+    /// calling it unless you're actually changing the contents.
+    pub fn content_mut(&mut self) -> &mut C {
+        self.widget_tag.request_relayout();
+        &mut self.content
+    }
+
+    /// Retrieves the number of items in the toolbar.
+    #[inline]
+    pub fn num_items(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Retrieves the item at `index`.
+    pub fn item(&self, index: usize) -> Option<&W> {
+        match self.bar_widgets.get(index)? {
+            Some(widget) => Some(widget),
+            None => self.overflow_panel.widgets[index].as_ref()
+        }
+    }
+
+    /// Retrieves the item at `index`, for mutation.
+    ///
+    /// Calling this function forces the toolbar to be re-laid out, so you're discouraged from
+    /// calling it unless you're actually changing the item.
+    pub fn item_mut(&mut self, index: usize) -> Option<&mut W> {
+        self.widget_tag.request_relayout();
+        match self.bar_widgets.get_mut(index)? {
+            Some(widget) => Some(widget),
+            None => self.overflow_panel.widgets[index].as_mut()
+        }
+    }
+
+    /// Retrieves the overflow priority of the item at `index`.
+    pub fn priority(&self, index: usize) -> Option<i32> {
+        self.items.get(index).map(|i| i.priority)
+    }
+
+    /// Sets the overflow priority of the item at `index`. Higher-priority items stay in the bar
+    /// longer as the toolbar shrinks.
+    pub fn set_priority(&mut self, index: usize, priority: i32) {
+        if let Some(item) = self.items.get_mut(index) {
+            if item.priority != priority {
+                item.priority = priority;
+                self.widget_tag.request_relayout();
+            }
+        }
+    }
+
+    /// Returns whether the item at `index` has been moved into the overflow popup, as of the
+    /// toolbar's last layout.
+    pub fn is_overflowed(&self, index: usize) -> bool {
+        self.items.get(index).map(|i| i.overflowed).unwrap_or(false)
+    }
+
+    /// Returns whether the overflow popup is open.
+    #[inline]
+    pub fn overflow_open(&self) -> bool {
+        self.open
+    }
+
+    /// Close the overflow popup, if it's open.
+    pub fn close_overflow(&mut self) {
+        self.set_open(false);
+    }
+
+    fn on_overflow_pressed(&mut self, _: &OverflowPressed) {
+        let open = !self.open;
+        self.set_open(open);
+    }
+
+    fn set_open(&mut self, open: bool) {
+        if self.open != open {
+            self.open = open;
+            self.overflow_button.open = open;
+            self.overflow_button.widget_tag.request_redraw();
+            self.widget_tag.request_relayout().request_redraw();
+        }
+    }
+
+    fn has_overflow(&self) -> bool {
+        self.items.iter().any(|i| i.overflowed)
+    }
+
+    /// Move the items' widgets between the bar and the overflow panel, so that each widget is
+    /// stored on the side its item's `overflowed` flag calls for.
+    fn sort_widgets(&mut self) {
+        let Toolbar{ ref items, ref mut bar_widgets, ref mut overflow_panel, .. } = *self;
+        for (index, item) in items.iter().enumerate() {
+            let (from, to) = match item.overflowed {
+                true => (&mut bar_widgets[index], &mut overflow_panel.widgets[index]),
+                false => (&mut overflow_panel.widgets[index], &mut bar_widgets[index]),
+            };
+            if let Some(widget) = from.take() {
+                *to = Some(widget);
+            }
+        }
+    }
+
+    /// Retrieves the child at `index`, in drawing order. The content is drawn first and the popup
+    /// last, so that the popup gets drawn over the content.
+    fn child_at(&self, index: usize) -> Option<ToolbarChild> {
+        let num_bar_items = self.num_bar_items();
+        match index {
+            0 => Some(ToolbarChild::Content),
+            _ if index <= num_bar_items => occupied_slots(&self.bar_widgets).nth(index - 1).map(ToolbarChild::Item),
+            _ if index == num_bar_items + 1 => Some(ToolbarChild::OverflowButton),
+            _ if index == num_bar_items + 2 && self.open => Some(ToolbarChild::Panel),
+            _ => None
+        }
+    }
+
+    fn child_index(&self, child: ToolbarChild) -> Option<usize> {
+        let num_bar_items = self.num_bar_items();
+        match child {
+            ToolbarChild::Content => Some(0),
+            ToolbarChild::Item(index) => occupied_slots(&self.bar_widgets).position(|i| i == index).map(|i| 1 + i),
+            ToolbarChild::OverflowButton => Some(1 + num_bar_items),
+            ToolbarChild::Panel if self.open => Some(2 + num_bar_items),
+            ToolbarChild::Panel => None,
+        }
+    }
+
+    fn child_ident(&self, child: ToolbarChild) -> WidgetIdent {
+        let len = self.items.len() as u32;
+        WidgetIdent::Num(match child {
+            ToolbarChild::Item(index) => index as u32,
+            ToolbarChild::OverflowButton => len,
+            ToolbarChild::Content => len + 1,
+            ToolbarChild::Panel => len + 2,
+        })
+    }
+
+    fn child_by_ident(&self, widget_ident: WidgetIdent) -> Option<ToolbarChild> {
+        let len = self.items.len() as u32;
+        match widget_ident {
+            WidgetIdent::Num(index) if index < len => Some(ToolbarChild::Item(index as usize)),
+            WidgetIdent::Num(index) if index == len => Some(ToolbarChild::OverflowButton),
+            WidgetIdent::Num(index) if index == len + 1 => Some(ToolbarChild::Content),
+            WidgetIdent::Num(index) if index == len + 2 => Some(ToolbarChild::Panel),
+            _ => None
+        }
+    }
+
+    fn num_bar_items(&self) -> usize {
+        occupied_slots(&self.bar_widgets).count()
+    }
+
+    fn child_info<R: Renderer>(&self, child: ToolbarChild) -> Option<WidgetInfo<'_, R>> {
+        let ident = self.child_ident(child);
+        let index = self.child_index(child)?;
+        Some(match child {
+            ToolbarChild::Content => WidgetInfo::new(ident, index, &self.content),
+            ToolbarChild::Item(i) => WidgetInfo::new(ident, index, self.bar_widgets[i].as_ref()?),
+            ToolbarChild::OverflowButton => WidgetInfo::new(ident, index, &self.overflow_button),
+            ToolbarChild::Panel => WidgetInfo::new(ident, index, &self.overflow_panel),
+        })
+    }
+
+    fn child_info_mut<R: Renderer>(&mut self, child: ToolbarChild) -> Option<WidgetInfoMut<'_, R>> {
+        let ident = self.child_ident(child);
+        let index = self.child_index(child)?;
+        Some(match child {
+            ToolbarChild::Content => WidgetInfoMut::new(ident, index, &mut self.content),
+            ToolbarChild::Item(i) => WidgetInfoMut::new(ident, index, self.bar_widgets[i].as_mut()?),
+            ToolbarChild::OverflowButton => WidgetInfoMut::new(ident, index, &mut self.overflow_button),
+            ToolbarChild::Panel => WidgetInfoMut::new(ident, index, &mut self.overflow_panel),
+        })
+    }
+}
+
+/// The indices of the slots in `widgets` that hold a widget.
+fn occupied_slots<W>(widgets: &[Option<W>]) -> impl '_ + Iterator<Item=usize> {
+    widgets.iter().enumerate().filter(|(_, w)| w.is_some()).map(|(i, _)| i)
+}
+
+impl<W, C> Widget for Toolbar<W, C>
+    where W: Widget,
+          C: Widget
+{
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        self.widget_tag.request_relayout();
+        &mut self.rect
+    }
+
+    #[inline]
+    fn size_bounds(&self) -> SizeBounds {
+        self.size_bounds
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        let event = event.unwrap();
+        let mut focus = None;
+        let mut bubble = event.default_bubble();
+        let mut handled = true;
+
+        match event {
+            WidgetEvent::KeyDown(Key::Escape, _) if self.open => {
+                bubble = false;
+                self.close_overflow();
+                focus = Some(FocusChange::Remove);
+            },
+            WidgetEvent::LoseFocus => self.close_overflow(),
+            _ => handled = false
+        }
+
+        EventOps {
+            focus,
+            bubble,
+            handled,
+        }
+    }
+}
+
+impl Widget for OverflowButton {
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        &mut self.rect
+    }
+
+    #[inline]
+    fn size_bounds(&self) -> SizeBounds {
+        self.size_bounds
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        let event = event.unwrap();
+        let widget_id = self.widget_tag.widget_id();
+        let mut focus = None;
+        let mut handled = true;
+
+        match event {
+            WidgetEvent::MouseMove{hover_change: Some(MouseHoverChange::Enter), ..} => {
+                self.hover = true;
+                self.widget_tag.request_redraw();
+            },
+            WidgetEvent::MouseMove{hover_change: Some(MouseHoverChange::Exit), ..} => {
+                self.hover = false;
+                self.widget_tag.request_redraw();
+            },
+            WidgetEvent::MouseDown{in_widget: true, ..} => {
+                // Give the toolbar focus, so that the popup closes once focus moves elsewhere.
+                focus = Some(FocusChange::Parent);
+                self.widget_tag.send_message_to(OverflowPressed, MessageTarget::ParentOf(widget_id));
+            },
+            _ => handled = false
+        }
+
+        EventOps {
+            focus,
+            bubble: event.default_bubble(),
+            handled,
+        }
+    }
+}
+
+impl<W: Widget> Widget for OverflowPanel<W> {
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        &mut self.rect
+    }
+
+    #[inline]
+    fn size_bounds(&self) -> SizeBounds {
+        SizeBounds::new_min(self.rect.dims())
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        EventOps {
+            focus: None,
+            bubble: event.unwrap().default_bubble(),
+            handled: false,
+        }
+    }
+}
+
+impl<W, C> Parent for Toolbar<W, C>
+    where W: Widget,
+          C: Widget
+{
+    fn num_children(&self) -> usize {
+        self.num_bar_items() + 2 + self.open as usize
+    }
+
+    fn framed_child<R: Renderer>(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, R>> {
+        self.child_by_ident(widget_ident).and_then(|child| self.child_info(child))
+    }
+    fn framed_child_mut<R: Renderer>(&mut self, widget_ident: WidgetIdent) -> Option<WidgetInfoMut<'_, R>> {
+        match self.child_by_ident(widget_ident) {
+            Some(child) => self.child_info_mut(child),
+            None => None
+        }
+    }
+
+    fn framed_children<'a, R, G>(&'a self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfo<'a, R>) -> LoopFlow
+    {
+        for index in 0..self.num_children() {
+            if let LoopFlow::Break = for_each(self.framed_child_by_index(index).unwrap()) {
+                return;
+            }
+        }
+    }
+
+    fn framed_children_mut<'a, R, G>(&'a mut self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfoMut<'a, R>) -> LoopFlow
+    {
+        let len = self.items.len();
+        let open = self.open;
+        let ident = |num: usize| WidgetIdent::Num(num as u32);
+        let Toolbar{ ref mut content, ref mut bar_widgets, ref mut overflow_button, ref mut overflow_panel, .. } = *self;
+
+        if let LoopFlow::Break = for_each(WidgetInfoMut::new(ident(len + 1), 0, content)) {
+            return;
+        }
+        let mut child_index = 1;
+        for (index, widget) in bar_widgets.iter_mut().enumerate() {
+            if let Some(widget) = widget {
+                if let LoopFlow::Break = for_each(WidgetInfoMut::new(ident(index), child_index, widget)) {
+                    return;
+                }
+                child_index += 1;
+            }
+        }
+        if let LoopFlow::Break = for_each(WidgetInfoMut::new(ident(len), child_index, overflow_button)) {
+            return;
+        }
+        if open {
+            let _ = for_each(WidgetInfoMut::new(ident(len + 2), child_index + 1, overflow_panel));
+        }
+    }
+
+    fn framed_child_by_index<R: Renderer>(&self, index: usize) -> Option<WidgetInfo<'_, R>> {
+        self.child_at(index).and_then(|child| self.child_info(child))
+    }
+    fn framed_child_by_index_mut<R: Renderer>(&mut self, index: usize) -> Option<WidgetInfoMut<'_, R>> {
+        match self.child_at(index) {
+            Some(child) => self.child_info_mut(child),
+            None => None
+        }
+    }
+}
+
+impl<W: Widget> Parent for OverflowPanel<W> {
+    fn num_children(&self) -> usize {
+        occupied_slots(&self.widgets).count()
+    }
+
+    fn framed_child<R: Renderer>(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, R>> {
+        match widget_ident {
+            WidgetIdent::Num(index) => {
+                let index = index as usize;
+                let child_index = occupied_slots(&self.widgets).position(|i| i == index)?;
+                Some(WidgetInfo::new(widget_ident, child_index, self.widgets[index].as_ref()?))
+            },
+            _ => None
+        }
+    }
+    fn framed_child_mut<R: Renderer>(&mut self, widget_ident: WidgetIdent) -> Option<WidgetInfoMut<'_, R>> {
+        match widget_ident {
+            WidgetIdent::Num(index) => {
+                let index = index as usize;
+                let child_index = occupied_slots(&self.widgets).position(|i| i == index)?;
+                Some(WidgetInfoMut::new(widget_ident, child_index, self.widgets[index].as_mut()?))
+            },
+            _ => None
+        }
+    }
+
+    fn framed_children<'a, R, G>(&'a self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfo<'a, R>) -> LoopFlow
+    {
+        let widgets = self.widgets.iter().enumerate().filter_map(|(i, w)| w.as_ref().map(|w| (i, w)));
+        for (child_index, (index, widget)) in widgets.enumerate() {
+            if let LoopFlow::Break = for_each(WidgetInfo::new(WidgetIdent::Num(index as u32), child_index, widget)) {
+                return;
+            }
+        }
+    }
+
+    fn framed_children_mut<'a, R, G>(&'a mut self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfoMut<'a, R>) -> LoopFlow
+    {
+        let widgets = self.widgets.iter_mut().enumerate().filter_map(|(i, w)| w.as_mut().map(|w| (i, w)));
+        for (child_index, (index, widget)) in widgets.enumerate() {
+            if let LoopFlow::Break = for_each(WidgetInfoMut::new(WidgetIdent::Num(index as u32), child_index, widget)) {
+                return;
+            }
+        }
+    }
+
+    fn framed_child_by_index<R: Renderer>(&self, child_index: usize) -> Option<WidgetInfo<'_, R>> {
+        let index = occupied_slots(&self.widgets).nth(child_index)?;
+        Some(WidgetInfo::new(WidgetIdent::Num(index as u32), child_index, self.widgets[index].as_ref()?))
+    }
+    fn framed_child_by_index_mut<R: Renderer>(&mut self, child_index: usize) -> Option<WidgetInfoMut<'_, R>> {
+        let index = occupied_slots(&self.widgets).nth(child_index)?;
+        Some(WidgetInfoMut::new(WidgetIdent::Num(index as u32), child_index, self.widgets[index].as_mut()?))
+    }
+}
+
+impl<R, W, C> WidgetRenderable<R> for Toolbar<W, C>
+    where R: Renderer,
+          W: Widget,
+          C: Widget
+{
+    type Theme = ToolbarTheme;
+    fn theme(&self) -> ToolbarTheme {
+        ToolbarTheme(())
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, _: &mut R::Layout) {
+        let widths = (0..self.items.len())
+            .map(|i| self.item(i).unwrap().size_bounds().min)
+            .collect::<Vec<_>>();
+        let button_dims = self.overflow_button.size_bounds.min;
+        let bar_width = self.rect.width();
+        let bar_height = widths.iter()
+            .map(|d| d.height())
+            .max()
+            .map(|h| h.max(button_dims.height()))
+            .unwrap_or(0);
+
+        // Decide which items overflow. If they don't all fit, room has to be left for the overflow
+        // button, and the items get kept in the bar in priority order as long as they fit.
+        let total_width: i32 = widths.iter().map(|d| d.width()).sum();
+        let fits_all = total_width <= bar_width;
+        let available = match fits_all {
+            true => bar_width,
+            false => bar_width - button_dims.width()
+        };
+        let mut order = (0..self.items.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| (Reverse(self.items[i].priority), i));
+        let mut used = 0;
+        for index in order {
+            let width = widths[index].width();
+            let fits = used + width <= available;
+            self.items[index].overflowed = !fits;
+            if fits {
+                used += width;
+            }
+        }
+        self.sort_widgets();
+
+        // Line the items that fit up along the top of the bar, in their original order.
+        let mut x = 0;
+        for (index, widget) in self.bar_widgets.iter_mut().enumerate() {
+            if let Some(widget) = widget {
+                let width = widths[index].width();
+                *widget.rect_mut() = BoundBox::new2(x, 0, x + width, bar_height);
+                x += width;
+            }
+        }
+
+        if !self.has_overflow() {
+            self.overflow_button.rect = BoundBox::new2(0, 0, 0, 0);
+            if self.open {
+                self.open = false;
+                self.overflow_button.open = false;
+            }
+        } else {
+            let button_x = (bar_width - button_dims.width()).max(0);
+            self.overflow_button.rect = BoundBox::new2(button_x, 0, button_x + button_dims.width(), bar_height);
+        }
+
+        // Stack the overflowed items in the popup, hanging it down from the right edge of the bar.
+        let popup_width = self.items.iter().zip(&widths)
+            .filter(|(i, _)| i.overflowed)
+            .map(|(_, d)| d.width())
+            .max()
+            .unwrap_or(0);
+        let popup_x = (bar_width - popup_width).max(0);
+        let mut y = 0;
+        for (index, widget) in self.overflow_panel.widgets.iter_mut().enumerate() {
+            if let Some(widget) = widget {
+                let height = widths[index].height();
+                *widget.rect_mut() = BoundBox::new2(0, y, popup_width, y + height);
+                y += height;
+            }
+        }
+        self.overflow_panel.rect = BoundBox::new2(popup_x, bar_height, popup_x + popup_width, bar_height + y);
+
+        let content_bounds = self.content.size_bounds();
+        self.size_bounds = SizeBounds {
+            min: DimsBox::new2(
+                total_width.min(button_dims.width()).max(content_bounds.min.width()),
+                bar_height.saturating_add(content_bounds.min.height()),
+            ),
+            max: DimsBox::new2(
+                content_bounds.max.width(),
+                bar_height.saturating_add(content_bounds.max.height()),
+            ),
+        };
+        *self.content.rect_mut() = BoundBox::new2(0, bar_height, self.rect.width(), self.rect.height());
+    }
+}
+
+impl<R> WidgetRenderable<R> for OverflowButton
+    where R: Renderer
+{
+    type Theme = ToolbarOverflowButtonTheme;
+    fn theme(&self) -> ToolbarOverflowButtonTheme {
+        ToolbarOverflowButtonTheme {
+            state: match (self.open, self.hover) {
+                (true, _) => ButtonState::Pressed,
+                (false, true) => ButtonState::Hover,
+                (false, false) => ButtonState::Normal,
+            },
+            open: self.open,
+        }
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, layout: &mut R::Layout) {
+        layout.prepare_string("»");
+        let control_height = layout.control_metrics().control_height;
+        let result = layout.finish();
+        self.size_bounds = result.size_bounds.expand_min(DimsBox::new2(0, control_height));
+    }
+}
+
+impl<R, W> WidgetRenderable<R> for OverflowPanel<W>
+    where R: Renderer,
+          W: Widget
+{
+    type Theme = ToolbarOverflowTheme;
+    fn theme(&self) -> ToolbarOverflowTheme {
+        ToolbarOverflowTheme(())
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, _: &mut R::Layout) {}
+}
+
+impl WidgetTheme for ToolbarTheme {
+    type Fallback = GroupTheme;
+    fn fallback(self) -> Option<GroupTheme> {
+        Some(GroupTheme::default())
+    }
+}
+
+impl WidgetTheme for ToolbarOverflowButtonTheme {
+    type Fallback = ButtonTheme;
+    fn fallback(self) -> Option<ButtonTheme> {
+        Some(ButtonTheme{ state: self.state, mnemonic: None })
+    }
+}
+
+impl WidgetTheme for ToolbarOverflowTheme {
+    type Fallback = GroupTheme;
+    fn fallback(self) -> Option<GroupTheme> {
+        Some(GroupTheme::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cgmath::Point2,
+        core::{Root, WindowEvent, render::headless::HeadlessRenderer},
+        test_helpers::{click, headless_root, press_key, send_events},
+        widgets::{Button, ButtonHandler, Contents, Spacer},
+    };
+    use std::{cell::RefCell, rc::Rc};
+
+    /// Records the index of each button that gets clicked.
+    struct Clicks(usize, Rc<RefCell<Vec<usize>>>);
+
+    impl ButtonHandler for Clicks {
+        fn on_click(&mut self) {
+            self.1.borrow_mut().push(self.0);
+        }
+    }
+
+    type TestToolbar = Toolbar<Button<Clicks>, Spacer>;
+
+    /// A toolbar with items 64, 104, and 24 pixels wide.
+    fn toolbar(clicks: &Rc<RefCell<Vec<usize>>>) -> TestToolbar {
+        let items = ["Copy all", "Paste special", "Cut"].iter().enumerate()
+            .map(|(i, text)| Button::new(Contents::Text(text.to_string()), Clicks(i, clicks.clone())))
+            .collect();
+        Toolbar::new(items, Spacer::flex())
+    }
+
+    fn item_rects(toolbar: &TestToolbar) -> Vec<BoundBox<D2, i32>> {
+        (0..toolbar.num_items()).map(|i| toolbar.item(i).unwrap().rect()).collect()
+    }
+
+    fn settle(root: &mut Root<TestToolbar, HeadlessRenderer>) {
+        root.redraw();
+        root.relayout();
+    }
+
+    #[test]
+    fn fits_all() {
+        let clicks = Rc::new(RefCell::new(Vec::new()));
+        let root = headless_root(toolbar(&clicks), DimsBox::new2(200, 100));
+        let toolbar = &root.root_widget;
+
+        assert!((0..3).all(|i| !toolbar.is_overflowed(i)));
+        assert_eq!(vec![
+            BoundBox::new2(0, 0, 64, 24),
+            BoundBox::new2(64, 0, 168, 24),
+            BoundBox::new2(168, 0, 192, 24),
+        ], item_rects(toolbar));
+        assert_eq!(BoundBox::new2(0, 0, 0, 0), toolbar.overflow_button.rect);
+        assert_eq!(BoundBox::new2(0, 24, 200, 100), toolbar.content().rect());
+    }
+
+    #[test]
+    fn overflow_keeps_later_items_that_fit() {
+        let clicks = Rc::new(RefCell::new(Vec::new()));
+        let root = headless_root(toolbar(&clicks), DimsBox::new2(150, 100));
+        let toolbar = &root.root_widget;
+
+        // The wide middle item doesn't fit next to the overflow button, but the last one still
+        // does.
+        assert_eq!(vec![false, true, false], (0..3).map(|i| toolbar.is_overflowed(i)).collect::<Vec<_>>());
+        let rects = item_rects(toolbar);
+        assert_eq!(BoundBox::new2(0, 0, 64, 24), rects[0]);
+        assert_eq!(BoundBox::new2(64, 0, 88, 24), rects[2]);
+        assert_eq!(BoundBox::new2(142, 0, 150, 24), toolbar.overflow_button.rect);
+        // The overflowed item isn't in the tree while the popup is closed.
+        assert_eq!(4, Parent::num_children(toolbar));
+    }
+
+    #[test]
+    fn priority() {
+        let clicks = Rc::new(RefCell::new(Vec::new()));
+        let mut root = headless_root(toolbar(&clicks), DimsBox::new2(150, 100));
+        root.root_widget.set_priority(1, 1);
+        root.relayout();
+
+        let toolbar = &root.root_widget;
+        assert_eq!(vec![true, false, false], (0..3).map(|i| toolbar.is_overflowed(i)).collect::<Vec<_>>());
+        let rects = item_rects(toolbar);
+        assert_eq!(BoundBox::new2(0, 0, 104, 24), rects[1]);
+        assert_eq!(BoundBox::new2(104, 0, 128, 24), rects[2]);
+    }
+
+    #[test]
+    fn open_overflow() {
+        let clicks = Rc::new(RefCell::new(Vec::new()));
+        let mut root = headless_root(toolbar(&clicks), DimsBox::new2(150, 100));
+        click(&mut root, Point2::new(146, 12));
+        settle(&mut root);
+
+        let toolbar = &root.root_widget;
+        assert!(toolbar.overflow_open());
+        assert!(toolbar.overflow_panel.widget_tag.is_popup());
+        assert_eq!(BoundBox::new2(46, 24, 150, 48), toolbar.overflow_panel.rect);
+        // The overflowed item is placed relative to the popup.
+        assert_eq!(BoundBox::new2(0, 0, 104, 24), toolbar.item(1).unwrap().rect());
+        assert_eq!(1, Parent::num_children(&toolbar.overflow_panel));
+
+        // The popup lies over the content, which comes first in the toolbar's children, so the
+        // click only reaches the item if the popup takes the cursor from the content.
+        click(&mut root, Point2::new(100, 36));
+        assert_eq!(vec![1], *clicks.borrow());
+
+        // Pressing the overflow button again closes the popup.
+        click(&mut root, Point2::new(146, 12));
+        assert!(!root.root_widget.overflow_open());
+    }
+
+    #[test]
+    fn escape_closes_overflow() {
+        let clicks = Rc::new(RefCell::new(Vec::new()));
+        let mut root = headless_root(toolbar(&clicks), DimsBox::new2(150, 100));
+        click(&mut root, Point2::new(146, 12));
+        assert!(root.root_widget.overflow_open());
+
+        press_key(&mut root, Key::Escape);
+        assert!(!root.root_widget.overflow_open());
+    }
+
+    #[test]
+    fn resize() {
+        let clicks = Rc::new(RefCell::new(Vec::new()));
+        let mut root = headless_root(toolbar(&clicks), DimsBox::new2(150, 100));
+        click(&mut root, Point2::new(146, 12));
+        assert!(root.root_widget.overflow_open());
+
+        // Once everything fits, the item moves back into the bar and the popup closes.
+        send_events(&mut root, &[WindowEvent::WindowResize(DimsBox::new2(200, 100))]);
+        settle(&mut root);
+        let toolbar = &root.root_widget;
+        assert!(!toolbar.is_overflowed(1));
+        assert!(!toolbar.overflow_open());
+        assert_eq!(BoundBox::new2(64, 0, 168, 24), toolbar.item(1).unwrap().rect());
+        assert_eq!(5, Parent::num_children(toolbar));
+    }
+}
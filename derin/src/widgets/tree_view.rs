@@ -0,0 +1,854 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use derin_core::{
+    LoopFlow,
+    ui_state::UiStateValue,
+    widget::{Parent, Widget, WidgetInfo, WidgetInfoMut, WidgetIdent, WidgetPathKey, WidgetTag, WidgetRenderable, MessageTarget},
+    render::{Renderer, RendererLayout, SubFrame, WidgetTheme},
+};
+use crate::{
+    event::{EventOps, FocusChange, WidgetEvent, WidgetEventSourced, InputState, Key, MouseHoverChange},
+    widgets::{
+        Contents, GroupTheme, ToggleButtonTheme,
+        assistants::ButtonState,
+    },
+};
+
+use cgmath_geometry::{D2, rect::{BoundBox, DimsBox, GeoBox}};
+use derin_common_types::layout::SizeBounds;
+use std::collections::HashSet;
+
+/// A collapsible hierarchy of nodes, such as a file browser or a document outline.
+///
+/// Each node is displayed on its own row, indented according to its depth in the tree. Nodes that
+/// have children can be expanded to show those children, by clicking the expander at the left of
+/// the row or by pressing the right arrow key. When the user selects a node, by either clicking it
+/// or moving the selection with the arrow keys, a [`NodeSelected`] action containing the node's
+/// path is sent to the application through `WidgetTag::send_action`.
+///
+/// Nodes are addressed by [`WidgetPathKey`]s made out of the idents of the node and each of its
+/// ancestors. Since the paths don't depend on the widgets built for the rows, the set of expanded
+/// nodes can be stored and restored with [`expanded`] and [`set_expanded`], even across rebuilds
+/// of the tree.
+///
+/// Every row has the same height, which lets the view only build widgets for the rows that are
+/// currently visible. Rows that don't fit in the view can be scrolled to with the mouse wheel, and
+/// the view scrolls automatically to keep the selected row visible.
+///
+/// [`NodeSelected`]: ./struct.NodeSelected.html
+/// [`WidgetPathKey`]: ../core/widget/struct.WidgetPathKey.html
+/// [`expanded`]: ./struct.TreeView.html#method.expanded
+/// [`set_expanded`]: ./struct.TreeView.html#method.set_expanded
+#[derive(Debug, Clone)]
+pub struct TreeView {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    size_bounds: SizeBounds,
+    nodes: Vec<TreeNode>,
+    expanded: HashSet<WidgetPathKey>,
+    selected: Option<WidgetPathKey>,
+    row_height: i32,
+    indent: i32,
+
+    /// Every node that isn't hidden by a collapsed ancestor, in display order.
+    flat: Vec<FlatNode>,
+    flat_dirty: bool,
+    /// The index in `flat` of the topmost visible row.
+    first_row: usize,
+    /// The widgets for the visible rows, starting with `first_row`.
+    rows: Vec<TreeRow>,
+}
+
+/// A node in a [`TreeView`], along with its children.
+///
+/// [`TreeView`]: ./struct.TreeView.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeNode {
+    /// The ident identifying the node among its siblings. Siblings must have distinct idents.
+    pub ident: WidgetIdent,
+    pub contents: Contents,
+    pub children: Vec<TreeNode>,
+}
+
+/// The action sent by a [`TreeView`] when the user selects a node, containing the path to the
+/// node.
+///
+/// [`TreeView`]: ./struct.TreeView.html
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NodeSelected(pub WidgetPathKey);
+
+#[derive(Debug, Clone)]
+struct FlatNode {
+    path: WidgetPathKey,
+    depth: usize,
+    has_children: bool,
+}
+
+#[derive(Debug, Clone)]
+struct TreeRow {
+    widget_tag: WidgetTag,
+    rect: BoundBox<D2, i32>,
+    size_bounds: SizeBounds,
+    slot: usize,
+    contents: Contents,
+    depth: usize,
+    expanded: Option<bool>,
+    expander_width: i32,
+    selected: bool,
+    state: ButtonState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RowClicked {
+    slot: usize,
+    on_expander: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TreeViewTheme(());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TreeRowTheme {
+    pub state: ButtonState,
+    pub selected: bool,
+    /// The depth of the row's node in the tree. Root nodes have a depth of `0`.
+    pub depth: usize,
+    /// Whether the row's node is expanded, or `None` if the node doesn't have any children and
+    /// shouldn't get drawn with an expander.
+    pub expanded: Option<bool>,
+}
+
+impl TreeNode {
+    /// Create a node without any children.
+    pub fn new(ident: WidgetIdent, contents: Contents) -> TreeNode {
+        TreeNode {
+            ident,
+            contents,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn with_children(ident: WidgetIdent, contents: Contents, children: Vec<TreeNode>) -> TreeNode {
+        TreeNode {
+            ident,
+            contents,
+            children,
+        }
+    }
+}
+
+impl TreeView {
+    /// Creates a new tree view with the given root nodes. All nodes start out collapsed.
+    pub fn new(nodes: Vec<TreeNode>) -> TreeView {
+        let mut widget_tag = WidgetTag::new();
+        widget_tag.register_message(Self::on_row_clicked);
+
+        let mut tree_view = TreeView {
+            widget_tag,
+            rect: BoundBox::new2(0, 0, 0, 0),
+            size_bounds: SizeBounds::default(),
+            nodes,
+            expanded: HashSet::new(),
+            selected: None,
+            row_height: 20,
+            indent: 16,
+
+            flat: Vec::new(),
+            flat_dirty: true,
+            first_row: 0,
+            rows: Vec::new(),
+        };
+        tree_view.sync_rows();
+        tree_view
+    }
+
+    /// Retrieves the tree's root nodes.
+    #[inline]
+    pub fn nodes(&self) -> &[TreeNode] {
+        &self.nodes
+    }
+
+    /// Retrieves the tree's root nodes, for mutation.
+    ///
+    /// Calling this function forces the view to rebuild its rows, so you're discouraged from
+    /// calling it unless you're actually changing the tree. Expanded and selected nodes stay
+    /// expanded and selected as long as their paths remain in the tree.
+    pub fn nodes_mut(&mut self) -> &mut Vec<TreeNode> {
+        self.widget_tag.request_relayout();
+        self.flat_dirty = true;
+        &mut self.nodes
+    }
+
+    /// Retrieves the node at the given path, or `None` if no node exists there.
+    pub fn node(&self, path: &WidgetPathKey) -> Option<&TreeNode> {
+        Self::find_node(&self.nodes, path)
+    }
+
+    /// Retrieves the path of the selected node.
+    #[inline]
+    pub fn selected(&self) -> Option<&WidgetPathKey> {
+        self.selected.as_ref()
+    }
+
+    /// Select the node at the given path, scrolling it into view. Unlike selections made by the
+    /// user, this doesn't send a `NodeSelected` action.
+    ///
+    /// This doesn't expand the node's ancestors, so the selected node may be hidden.
+    pub fn set_selected(&mut self, selected: Option<WidgetPathKey>) {
+        if selected != self.selected {
+            self.selected = selected;
+            self.scroll_to_selected();
+            self.sync_rows();
+        }
+    }
+
+    /// Whether the node at the given path is expanded.
+    pub fn is_expanded(&self, path: &WidgetPathKey) -> bool {
+        self.expanded.contains(path)
+    }
+
+    /// Retrieves the paths of every expanded node. Nodes that are expanded but hidden by a
+    /// collapsed ancestor are included.
+    pub fn expanded(&self) -> impl '_ + Iterator<Item=&WidgetPathKey> {
+        self.expanded.iter()
+    }
+
+    /// Expand or collapse the node at the given path.
+    ///
+    /// Paths are remembered even if no node currently exists at the path, so that the expansion
+    /// state can be restored before the tree gets populated.
+    pub fn set_expanded(&mut self, path: WidgetPathKey, expanded: bool) {
+        let changed = match expanded {
+            true => self.expanded.insert(path),
+            false => self.expanded.remove(&path),
+        };
+        if changed {
+            self.flat_dirty = true;
+            self.widget_tag.request_relayout();
+            self.sync_rows();
+        }
+    }
+
+    /// Retrieves the height of every row, in pixels.
+    #[inline]
+    pub fn row_height(&self) -> i32 {
+        self.row_height
+    }
+
+    /// Set the height of every row, in pixels. Defaults to `20`.
+    pub fn set_row_height(&mut self, row_height: i32) {
+        self.row_height = i32::max(1, row_height);
+        self.widget_tag.request_relayout();
+        self.sync_rows();
+    }
+
+    /// Retrieves how far each level of the tree gets indented, in pixels.
+    #[inline]
+    pub fn indent(&self) -> i32 {
+        self.indent
+    }
+
+    /// Set how far each level of the tree gets indented, in pixels. This is also the width of the
+    /// expander drawn at the left of each row. Defaults to `16`.
+    pub fn set_indent(&mut self, indent: i32) {
+        self.indent = i32::max(0, indent);
+        self.widget_tag.request_relayout();
+        self.sync_rows();
+    }
+
+    fn rebuild_flat(&mut self) {
+        fn push_nodes(flat: &mut Vec<FlatNode>, expanded: &HashSet<WidgetPathKey>, nodes: &[TreeNode], parent: &WidgetPathKey) {
+            for node in nodes {
+                let path = parent.child(node.ident.clone());
+                let show_children = expanded.contains(&path);
+                flat.push(FlatNode {
+                    path: path.clone(),
+                    depth: parent.len(),
+                    has_children: !node.children.is_empty(),
+                });
+                if show_children {
+                    push_nodes(flat, expanded, &node.children, &path);
+                }
+            }
+        }
+
+        self.flat.clear();
+        push_nodes(&mut self.flat, &self.expanded, &self.nodes, &WidgetPathKey::empty());
+        self.flat_dirty = false;
+    }
+
+    /// The number of rows that fit in the view, including a partially-visible row at the bottom.
+    fn visible_rows(&self) -> usize {
+        ((self.rect.height() + self.row_height - 1) / self.row_height).max(0) as usize
+    }
+
+    fn max_first_row(&self) -> usize {
+        let full_rows = (self.rect.height() / self.row_height).max(0) as usize;
+        self.flat.len().saturating_sub(full_rows)
+    }
+
+    fn selected_row(&self) -> Option<usize> {
+        let selected = self.selected.as_ref()?;
+        self.flat.iter().position(|n| n.path == *selected)
+    }
+
+    fn scroll_to_selected(&mut self) {
+        if self.flat_dirty {
+            self.rebuild_flat();
+        }
+        if let Some(row) = self.selected_row() {
+            let full_rows = usize::max(1, (self.rect.height() / self.row_height).max(0) as usize);
+            if row < self.first_row {
+                self.first_row = row;
+            } else if row >= self.first_row + full_rows {
+                self.first_row = row + 1 - full_rows;
+            }
+        }
+    }
+
+    /// Build the widgets for the visible rows, and update them to display the nodes they're
+    /// currently scrolled to.
+    fn sync_rows(&mut self) {
+        if self.flat_dirty {
+            self.rebuild_flat();
+        }
+        self.first_row = usize::min(self.first_row, self.max_first_row());
+
+        let num_rows = usize::min(self.visible_rows(), self.flat.len() - self.first_row);
+        self.rows.truncate(num_rows);
+        while self.rows.len() < num_rows {
+            self.rows.push(TreeRow {
+                widget_tag: WidgetTag::new(),
+                rect: BoundBox::new2(0, 0, 0, 0),
+                size_bounds: SizeBounds::default(),
+                slot: self.rows.len(),
+                contents: Contents::Text(String::new()),
+                depth: 0,
+                expanded: None,
+                expander_width: 0,
+                selected: false,
+                state: ButtonState::Normal,
+            });
+        }
+
+        for (slot, row) in self.rows.iter_mut().enumerate() {
+            let flat = &self.flat[self.first_row + slot];
+            let contents = Self::find_node(&self.nodes, &flat.path).map(|n| &n.contents);
+            let expanded = match flat.has_children {
+                true => Some(self.expanded.contains(&flat.path)),
+                false => None
+            };
+            let selected = self.selected.as_ref() == Some(&flat.path);
+
+            if contents.map(|c| *c != row.contents).unwrap_or(false) {
+                row.contents = contents.unwrap().clone();
+                row.widget_tag.request_relayout();
+            }
+            if (row.depth, row.expanded, row.selected) != (flat.depth, expanded, selected) {
+                row.depth = flat.depth;
+                row.expanded = expanded;
+                row.selected = selected;
+                row.widget_tag.request_redraw();
+            }
+
+            let top = slot as i32 * self.row_height;
+            let left = flat.depth as i32 * self.indent;
+            row.expander_width = self.indent;
+            row.rect = BoundBox::new2(left, top, i32::max(left, self.rect.width()), top + self.row_height);
+        }
+
+        self.widget_tag.request_redraw();
+    }
+
+    fn find_node<'a>(nodes: &'a [TreeNode], path: &WidgetPathKey) -> Option<&'a TreeNode> {
+        let (last, ancestors) = path.idents().split_last()?;
+        let mut nodes = nodes;
+        for ident in ancestors {
+            nodes = &nodes.iter().find(|n| n.ident == *ident)?.children;
+        }
+        nodes.iter().find(|n| n.ident == *last)
+    }
+
+    /// Select the node at `row` in `flat` and tell the application about it.
+    fn select_row(&mut self, row: usize) {
+        if let Some(path) = self.flat.get(row).map(|n| n.path.clone()) {
+            if self.selected.as_ref() != Some(&path) {
+                self.set_selected(Some(path.clone()));
+                self.widget_tag.send_action(NodeSelected(path));
+            }
+        }
+    }
+
+    fn toggle_row(&mut self, row: usize) {
+        if let Some(node) = self.flat.get(row).filter(|n| n.has_children) {
+            let path = node.path.clone();
+            let expanded = !self.expanded.contains(&path);
+            self.set_expanded(path, expanded);
+        }
+    }
+
+    fn on_row_clicked(&mut self, message: &RowClicked) {
+        let row = self.first_row + message.slot;
+        match message.on_expander {
+            true => self.toggle_row(row),
+            false => self.select_row(row),
+        }
+    }
+
+    fn on_key(&mut self, key: Key) -> bool {
+        let selected_row = self.selected_row();
+        let last_row = self.flat.len().saturating_sub(1);
+
+        match (key, selected_row) {
+            (Key::UArrow, Some(row)) => self.select_row(row.saturating_sub(1)),
+            (Key::DArrow, Some(row)) => self.select_row(usize::min(row + 1, last_row)),
+            (Key::UArrow, None) |
+            (Key::DArrow, None) |
+            (Key::Home, _) => self.select_row(0),
+            (Key::End, _) => self.select_row(last_row),
+            (Key::RArrow, Some(row)) => {
+                let node = &self.flat[row];
+                match (node.has_children, self.expanded.contains(&node.path)) {
+                    (true, false) => self.toggle_row(row),
+                    // The first child is always displayed right after its parent.
+                    (true, true) => self.select_row(row + 1),
+                    (false, _) => ()
+                }
+            },
+            (Key::LArrow, Some(row)) => {
+                let node = &self.flat[row];
+                match self.expanded.contains(&node.path) && node.has_children {
+                    true => self.toggle_row(row),
+                    false => {
+                        let parent = node.path.parent().filter(|p| !p.is_empty());
+                        if let Some(parent_row) = parent.and_then(|p| self.flat.iter().position(|n| n.path == p)) {
+                            self.select_row(parent_row);
+                        }
+                    }
+                }
+            },
+            (Key::Enter, Some(row)) |
+            (Key::Space, Some(row)) => self.toggle_row(row),
+            _ => return false
+        }
+
+        true
+    }
+}
+
+impl Widget for TreeView {
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        self.widget_tag.request_relayout();
+        &mut self.rect
+    }
+
+    #[inline]
+    fn size_bounds(&self) -> SizeBounds {
+        self.size_bounds
+    }
+
+    fn on_update_layout(&mut self, _: BoundBox<D2, i32>) {
+        self.sync_rows();
+    }
+
+    #[inline]
+    fn accepts_focus(&self) -> bool {
+        true
+    }
+
+    /// Saves the expanded nodes, the selected node, and the scroll position, as a list containing
+    /// a list of the expanded paths, the selected path (or `false` if nothing's selected), and the
+    /// index of the topmost visible row.
+    fn save_state(&self) -> Option<UiStateValue> {
+        Some(UiStateValue::List(vec![
+            UiStateValue::List(self.expanded.iter().cloned().map(UiStateValue::Path).collect()),
+            match self.selected {
+                Some(ref selected) => UiStateValue::Path(selected.clone()),
+                None => UiStateValue::Bool(false)
+            },
+            UiStateValue::Int(self.first_row as i64),
+        ]))
+    }
+
+    fn restore_state(&mut self, state: &UiStateValue) {
+        let (expanded, selected, first_row) = match state.as_list() {
+            Some(&[ref expanded, ref selected, ref first_row]) => (expanded, selected, first_row),
+            _ => return
+        };
+
+        if let Some(expanded) = expanded.as_list() {
+            self.expanded = expanded.iter().filter_map(|p| p.as_path()).cloned().collect();
+        }
+        self.selected = selected.as_path().cloned();
+        if let Some(first_row) = first_row.as_int() {
+            self.first_row = first_row.max(0) as usize;
+        }
+
+        // The rows get rebuilt when the view is next laid out.
+        self.flat_dirty = true;
+        self.widget_tag.request_relayout();
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        let event = event.unwrap();
+        let mut bubble = event.default_bubble();
+
+        match event {
+            WidgetEvent::KeyDown(key, _) => {
+                if self.on_key(key) {
+                    bubble = false;
+                }
+            },
+            WidgetEvent::MouseScrollLines{dir, in_widget: true} => {
+                let first_row = self.first_row as isize - dir.y as isize;
+                self.first_row = usize::min(first_row.max(0) as usize, self.max_first_row());
+                self.sync_rows();
+                bubble = false;
+            },
+            _ => ()
+        }
+
+        EventOps {
+            focus: None,
+            handled: !bubble,
+            bubble,
+        }
+    }
+}
+
+impl Widget for TreeRow {
+    #[inline]
+    fn widget_tag(&self) -> &WidgetTag {
+        &self.widget_tag
+    }
+
+    #[inline]
+    fn rect(&self) -> BoundBox<D2, i32> {
+        self.rect
+    }
+
+    #[inline]
+    fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+        &mut self.rect
+    }
+
+    #[inline]
+    fn size_bounds(&self) -> SizeBounds {
+        self.size_bounds
+    }
+
+    fn on_widget_event(&mut self, event: WidgetEventSourced, _: InputState) -> EventOps {
+        use self::WidgetEvent::*;
+        let event = event.unwrap();
+        let widget_id = self.widget_tag.widget_id();
+        let mut focus = None;
+
+        let mut handled = true;
+        let new_state = match event {
+            MouseMove{hover_change: Some(ref change), ..} => match change {
+                MouseHoverChange::Enter => ButtonState::Hover,
+                MouseHoverChange::Exit => ButtonState::Normal,
+                _ => self.state
+            },
+            MouseDown{..} => {
+                // Give the tree view focus, so that the arrow keys move the selection.
+                focus = Some(FocusChange::Parent);
+                ButtonState::Pressed
+            },
+            MouseUp{in_widget: true, pressed_in_widget: true, ..} => ButtonState::Hover,
+            Click{pos, ..} => {
+                let clicked = RowClicked {
+                    slot: self.slot,
+                    on_expander: self.expanded.is_some() && pos.x < self.expander_width,
+                };
+                self.widget_tag.send_message_to(clicked, MessageTarget::ParentOf(widget_id));
+                self.state
+            },
+            MouseUp{in_widget: false, ..} => ButtonState::Normal,
+            _ => {
+                handled = false;
+                self.state
+            }
+        };
+
+        if new_state != self.state {
+            self.widget_tag.request_redraw();
+            self.state = new_state;
+        }
+
+        EventOps {
+            focus,
+            bubble: event.default_bubble(),
+            handled,
+        }
+    }
+}
+
+impl Parent for TreeView {
+    fn num_children(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn framed_child<R: Renderer>(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, R>> {
+        match widget_ident {
+            WidgetIdent::Num(index) => self.framed_child_by_index(index as usize),
+            _ => None
+        }
+    }
+    fn framed_child_mut<R: Renderer>(&mut self, widget_ident: WidgetIdent) -> Option<WidgetInfoMut<'_, R>> {
+        match widget_ident {
+            WidgetIdent::Num(index) => self.framed_child_by_index_mut(index as usize),
+            _ => None
+        }
+    }
+
+    fn framed_children<'a, R, G>(&'a self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfo<'a, R>) -> LoopFlow
+    {
+        for (index, row) in self.rows.iter().enumerate() {
+            if let LoopFlow::Break = for_each(WidgetInfo::new(WidgetIdent::Num(index as u32), index, row)) {
+                return;
+            }
+        }
+    }
+
+    fn framed_children_mut<'a, R, G>(&'a mut self, mut for_each: G)
+        where R: Renderer,
+              G: FnMut(WidgetInfoMut<'a, R>) -> LoopFlow
+    {
+        for (index, row) in self.rows.iter_mut().enumerate() {
+            if let LoopFlow::Break = for_each(WidgetInfoMut::new(WidgetIdent::Num(index as u32), index, row)) {
+                return;
+            }
+        }
+    }
+
+    fn framed_child_by_index<R: Renderer>(&self, index: usize) -> Option<WidgetInfo<'_, R>> {
+        self.rows.get(index).map(|r| WidgetInfo::new(WidgetIdent::Num(index as u32), index, r))
+    }
+    fn framed_child_by_index_mut<R: Renderer>(&mut self, index: usize) -> Option<WidgetInfoMut<'_, R>> {
+        self.rows.get_mut(index).map(|r| WidgetInfoMut::new(WidgetIdent::Num(index as u32), index, r))
+    }
+}
+
+impl<R> WidgetRenderable<R> for TreeView
+    where R: Renderer
+{
+    type Theme = TreeViewTheme;
+    fn theme(&self) -> TreeViewTheme {
+        TreeViewTheme(())
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, _: &mut R::Layout) {
+        self.sync_rows();
+
+        // Only the visible rows have widgets, so the view can only be made as wide as those rows
+        // need. It's expected to be placed in a container that gives it a fixed size.
+        let min_width = self.rows.iter()
+            .map(|r| r.depth as i32 * self.indent + r.size_bounds.min.width())
+            .max()
+            .unwrap_or(0);
+        self.size_bounds = SizeBounds::new_min(DimsBox::new2(min_width, self.row_height));
+    }
+}
+
+impl<R> WidgetRenderable<R> for TreeRow
+    where R: Renderer
+{
+    type Theme = TreeRowTheme;
+    fn theme(&self) -> TreeRowTheme {
+        TreeRowTheme {
+            state: self.state,
+            selected: self.selected,
+            depth: self.depth,
+            expanded: self.expanded,
+        }
+    }
+
+    fn render(&mut self, frame: &mut R::SubFrame) {
+        frame.render_laid_out_content();
+    }
+
+    fn update_layout(&mut self, layout: &mut R::Layout) {
+        match self.contents {
+            Contents::Text(ref s) => layout.prepare_string(s),
+            Contents::Icon(ref i) => layout.prepare_icon(i),
+            Contents::Rich(ref r) => layout.prepare_rich_string(r),
+        }
+
+        let result = layout.finish();
+        self.size_bounds = result.size_bounds;
+    }
+}
+
+impl WidgetTheme for TreeViewTheme {
+    type Fallback = GroupTheme;
+    fn fallback(self) -> Option<GroupTheme> {
+        Some(GroupTheme::default())
+    }
+}
+
+impl WidgetTheme for TreeRowTheme {
+    type Fallback = ToggleButtonTheme;
+    fn fallback(self) -> Option<ToggleButtonTheme> {
+        Some(ToggleButtonTheme{ toggled: self.selected, state: self.state })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cgmath::{Point2, Vector2},
+        core::{Root, WindowEvent, render::headless::HeadlessRenderer},
+        test_helpers::{click, headless_root, press_key, send_events},
+    };
+
+    fn node(ident: &str, children: Vec<TreeNode>) -> TreeNode {
+        TreeNode::with_children(WidgetIdent::new_str(ident), Contents::Text(ident.to_uppercase()), children)
+    }
+
+    fn path(idents: &[&str]) -> WidgetPathKey {
+        idents.iter().fold(WidgetPathKey::empty(), |path, ident| path.child(WidgetIdent::new_str(ident)))
+    }
+
+    /// A view with room for two and a half rows.
+    fn tree_view() -> Root<TreeView, HeadlessRenderer> {
+        let tree_view = TreeView::new(vec![
+            node("a", vec![node("a1", vec![]), node("a2", vec![])]),
+            node("b", vec![]),
+            node("c", vec![]),
+        ]);
+        headless_root(tree_view, DimsBox::new2(100, 50))
+    }
+
+    fn row_text(tree_view: &TreeView) -> Vec<&str> {
+        tree_view.rows.iter().map(|r| r.contents.as_text_ref().unwrap()).collect()
+    }
+
+    fn actions(root: &mut Root<TreeView, HeadlessRenderer>) -> Vec<WidgetPathKey> {
+        root.take_actions()
+            .map(|action| action.downcast::<NodeSelected>().unwrap().0)
+            .collect()
+    }
+
+    #[test]
+    fn layout() {
+        let root = tree_view();
+        let tree_view = &root.root_widget;
+
+        // Only the rows that fit in the view get widgets.
+        assert_eq!(vec!["A", "B", "C"], row_text(tree_view));
+        let rects = tree_view.rows.iter().map(|r| r.rect).collect::<Vec<_>>();
+        assert_eq!(vec![
+            BoundBox::new2(0, 0, 100, 20),
+            BoundBox::new2(0, 20, 100, 40),
+            BoundBox::new2(0, 40, 100, 60),
+        ], rects);
+        let expanded = tree_view.rows.iter().map(|r| r.expanded).collect::<Vec<_>>();
+        assert_eq!(vec![Some(false), None, None], expanded);
+    }
+
+    #[test]
+    fn click_expander() {
+        let mut root = tree_view();
+        click(&mut root, Point2::new(4, 10));
+
+        let tree_view = &root.root_widget;
+        assert!(tree_view.is_expanded(&path(&["a"])));
+        assert_eq!(vec!["A", "A1", "A2"], row_text(tree_view));
+        // Children get indented by a level.
+        assert_eq!(BoundBox::new2(16, 20, 100, 40), tree_view.rows[1].rect);
+        // Clicking the expander doesn't select the node.
+        assert_eq!(None, tree_view.selected());
+        assert_eq!(0, actions(&mut root).len());
+
+        click(&mut root, Point2::new(4, 10));
+        assert!(!root.root_widget.is_expanded(&path(&["a"])));
+        assert_eq!(vec!["A", "B", "C"], row_text(&root.root_widget));
+    }
+
+    #[test]
+    fn click_select() {
+        let mut root = tree_view();
+        click(&mut root, Point2::new(50, 30));
+
+        assert_eq!(Some(&path(&["b"])), root.root_widget.selected());
+        assert!(root.root_widget.rows[1].selected);
+        assert_eq!(vec![path(&["b"])], actions(&mut root));
+
+        // Clicking the row text of an expandable node selects it instead of expanding it.
+        click(&mut root, Point2::new(50, 10));
+        assert_eq!(Some(&path(&["a"])), root.root_widget.selected());
+        assert!(!root.root_widget.is_expanded(&path(&["a"])));
+        assert_eq!(vec![path(&["a"])], actions(&mut root));
+    }
+
+    #[test]
+    fn arrow_keys() {
+        let mut root = tree_view();
+        // Clicking a row gives the view focus.
+        click(&mut root, Point2::new(50, 10));
+
+        press_key(&mut root, Key::RArrow);
+        assert!(root.root_widget.is_expanded(&path(&["a"])));
+        press_key(&mut root, Key::RArrow);
+        assert_eq!(Some(&path(&["a", "a1"])), root.root_widget.selected());
+        press_key(&mut root, Key::DArrow);
+        assert_eq!(Some(&path(&["a", "a2"])), root.root_widget.selected());
+        press_key(&mut root, Key::LArrow);
+        assert_eq!(Some(&path(&["a"])), root.root_widget.selected());
+        press_key(&mut root, Key::LArrow);
+        assert!(!root.root_widget.is_expanded(&path(&["a"])));
+        press_key(&mut root, Key::End);
+        assert_eq!(Some(&path(&["c"])), root.root_widget.selected());
+
+        assert_eq!(vec![
+            path(&["a"]),
+            path(&["a", "a1"]),
+            path(&["a", "a2"]),
+            path(&["a"]),
+            path(&["c"]),
+        ], actions(&mut root));
+    }
+
+    #[test]
+    fn scroll() {
+        let mut root = tree_view();
+        root.root_widget.set_expanded(path(&["a"]), true);
+        root.relayout();
+
+        send_events(&mut root, &[
+            WindowEvent::MouseMove(Point2::new(50, 25)),
+            WindowEvent::MouseScrollLines(Vector2::new(0, -1)),
+        ]);
+        assert_eq!(vec!["A1", "A2", "B"], row_text(&root.root_widget));
+
+        // The view can't scroll past the last full row.
+        send_events(&mut root, &[WindowEvent::MouseScrollLines(Vector2::new(0, -10))]);
+        assert_eq!(vec!["B", "C"], row_text(&root.root_widget));
+
+        // Selecting a node scrolls it into view.
+        root.root_widget.set_selected(Some(path(&["a"])));
+        assert_eq!(vec!["A", "A1", "A2"], row_text(&root.root_widget));
+        assert_eq!(0, actions(&mut root).len());
+    }
+}
@@ -248,6 +248,20 @@ impl From<MouseButton> for u8 {
     }
 }
 
+impl Key {
+    /// The character typed by the key when no modifiers are held, for the letter and number keys.
+    /// Letters are lowercase.
+    pub fn to_char(self) -> Option<char> {
+        if Key::A <= self && self <= Key::Z {
+            Some((b'a' + (self as u8 - Key::A as u8)) as char)
+        } else if Key::Alpha0 <= self && self <= Key::Alpha9 {
+            Some((b'0' + (self as u8 - Key::Alpha0 as u8)) as char)
+        } else {
+            None
+        }
+    }
+}
+
 impl MouseButton {
     #[inline]
     pub fn from_u8(u: u8) -> Option<MouseButton> {
@@ -5,7 +5,7 @@
 use crate::Px;
 use num_traits::Bounded;
 use crate::cgmath::Point2;
-use cgmath_geometry::{D2, rect::{DimsBox, GeoBox}};
+use cgmath_geometry::{D2, rect::{BoundBox, DimsBox, GeoBox}};
 use std::ops::{Add, Range, RangeFrom, RangeFull, RangeTo};
 
 pub type Tr = u32;
@@ -106,9 +106,13 @@ impl Default for Align {
 
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Default, Debug, Clone, Copy)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct WidgetPos {
     pub size_bounds: SizeBounds,
+    /// The size the widget would prefer to have, if there's room for it. The layout engine treats
+    /// this as the widget's minimum size unless that would push the layout past its desired size,
+    /// in which case it falls back to `size_bounds.min`.
+    pub preferred_size: Option<DimsBox<D2, Px>>,
     pub widget_span: WidgetSpan,
     pub place_in_cell: Align2,
     pub margins: Margins<Px>
@@ -118,6 +122,7 @@ impl WidgetPos {
     pub fn new(size_bounds: SizeBounds, widget_span: WidgetSpan, place_in_cell: Align2, margins: Margins<Px>) -> WidgetPos {
         WidgetPos {
             size_bounds: size_bounds,
+            preferred_size: None,
             widget_span: widget_span,
             place_in_cell: place_in_cell,
             margins: margins
@@ -149,6 +154,61 @@ impl Default for TrackHints {
     }
 }
 
+/// A higher-level description of how a row or column gets sized, which is converted into
+/// `TrackHints` during layout.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Track {
+    /// The track is always exactly this many pixels wide.
+    Pixels(Px),
+    /// The track takes up this proportion of the free space left over by the other tracks. See
+    /// `TrackHints::fr_size`.
+    Fraction(Fr),
+    /// The track takes up this percentage (from `0.0` to `100.0`) of the container's size along
+    /// the track's axis.
+    Percent(f32),
+    /// The track is as small as the widgets within it allow.
+    Auto,
+}
+
+impl Track {
+    /// Convert the track into the hints used by the layout engine. `Percent` tracks are resolved
+    /// against `container_size`, which is the container's width for columns and its height for
+    /// rows.
+    pub fn track_hints(self, container_size: Px) -> TrackHints {
+        match self {
+            Track::Pixels(px) => TrackHints {
+                min_size: px,
+                max_size: px,
+                fr_size: 0.0,
+            },
+            Track::Fraction(fr_size) => TrackHints {
+                fr_size,
+                ..TrackHints::default()
+            },
+            Track::Percent(percent) => {
+                let px = (Px::max(container_size, 0) as f32 * percent / 100.0).round() as Px;
+                TrackHints {
+                    min_size: px,
+                    max_size: px,
+                    fr_size: 0.0,
+                }
+            },
+            Track::Auto => TrackHints {
+                fr_size: 0.0,
+                ..TrackHints::default()
+            },
+        }
+    }
+}
+
+/// Splits free space evenly with the other fractional tracks, like the default `TrackHints`.
+impl Default for Track {
+    fn default() -> Track {
+        Track::Fraction(1.0)
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SizeBounds {
@@ -172,21 +232,42 @@ impl SizeBounds {
         SizeBounds{ max, ..SizeBounds::default() }
     }
 
-    /// Bound a rectangle to be within the size bounds.
-    pub fn bound_rect(self, mut desired_size: DimsBox<D2, Px>) -> DimsBox<D2, Px> {
-        if desired_size.width() < self.min.width() {
-            desired_size.dims.x = self.min.width();
-        } else if desired_size.width() > self.max.width() {
-            desired_size.dims.x = self.max.width();
-        }
+    /// Clamp the dimensions to be within the size bounds.
+    ///
+    /// If the bounds are malformed, with `min` greater than `max` along an axis, `min` wins and
+    /// that axis gets set to `min`.
+    pub fn clamp_dims(self, dims: DimsBox<D2, Px>) -> DimsBox<D2, Px> {
+        let clamp = |x: Px, min: Px, max: Px| Px::max(min, Px::min(max, x));
+        DimsBox::new2(
+            clamp(dims.width(), self.min.width(), self.max.width()),
+            clamp(dims.height(), self.min.height(), self.max.height()),
+        )
+    }
 
-        if desired_size.height() < self.min.height() {
-            desired_size.dims.y = self.min.height();
-        } else if desired_size.height() > self.max.height() {
-            desired_size.dims.y = self.max.height();
-        }
+    /// Clamp the rectangle's dimensions to be within the size bounds, keeping its top-left corner
+    /// in place. See `clamp_dims` for how malformed bounds are handled.
+    pub fn clamp_rect(self, rect: BoundBox<D2, Px>) -> BoundBox<D2, Px> {
+        let dims = self.clamp_dims(rect.dims());
+        BoundBox::new2(rect.min.x, rect.min.y, rect.min.x + dims.width(), rect.min.y + dims.height())
+    }
+
+    /// Raise the minimum size to at least `min`, raising the maximum size too if it would
+    /// otherwise be smaller than the new minimum.
+    pub fn expand_min(self, min: DimsBox<D2, Px>) -> SizeBounds {
+        let min = DimsBox::new2(
+            Px::max(self.min.width(), min.width()),
+            Px::max(self.min.height(), min.height()),
+        );
+        let max = DimsBox::new2(
+            Px::max(self.max.width(), min.width()),
+            Px::max(self.max.height(), min.height()),
+        );
+        SizeBounds{ min, max }
+    }
 
-        desired_size
+    #[deprecated(note = "use `clamp_dims` instead")]
+    pub fn bound_rect(self, desired_size: DimsBox<D2, Px>) -> DimsBox<D2, Px> {
+        self.clamp_dims(desired_size)
     }
 }
 
@@ -232,3 +313,52 @@ impl<T> Margins<T>
         self.top + self.bottom
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamp_dims() {
+        let bounds = SizeBounds::new(DimsBox::new2(10, 20), DimsBox::new2(30, 40));
+        assert_eq!(DimsBox::new2(10, 20), bounds.clamp_dims(DimsBox::new2(0, 0)));
+        assert_eq!(DimsBox::new2(15, 25), bounds.clamp_dims(DimsBox::new2(15, 25)));
+        assert_eq!(DimsBox::new2(30, 40), bounds.clamp_dims(DimsBox::new2(100, 100)));
+        assert_eq!(DimsBox::new2(10, 40), bounds.clamp_dims(DimsBox::new2(-5, 41)));
+    }
+
+    #[test]
+    fn clamp_dims_min_over_max() {
+        let bounds = SizeBounds::new(DimsBox::new2(30, 10), DimsBox::new2(20, 40));
+        assert_eq!(DimsBox::new2(30, 10), bounds.clamp_dims(DimsBox::new2(0, 0)));
+        assert_eq!(DimsBox::new2(30, 25), bounds.clamp_dims(DimsBox::new2(25, 25)));
+        assert_eq!(DimsBox::new2(30, 40), bounds.clamp_dims(DimsBox::new2(100, 100)));
+    }
+
+    #[test]
+    fn expand_min() {
+        let bounds = SizeBounds::new(DimsBox::new2(10, 20), DimsBox::new2(30, 40));
+        assert_eq!(bounds, bounds.expand_min(DimsBox::new2(5, 20)));
+        assert_eq!(SizeBounds::new(DimsBox::new2(15, 20), DimsBox::new2(30, 40)), bounds.expand_min(DimsBox::new2(15, 0)));
+        assert_eq!(SizeBounds::new(DimsBox::new2(10, 50), DimsBox::new2(30, 50)), bounds.expand_min(DimsBox::new2(0, 50)));
+    }
+
+    #[test]
+    fn track_hints() {
+        let rigid = |px| TrackHints{ min_size: px, max_size: px, fr_size: 0.0 };
+        assert_eq!(rigid(100), Track::Pixels(100).track_hints(500));
+        assert_eq!(rigid(150), Track::Percent(30.0).track_hints(500));
+        assert_eq!(rigid(33), Track::Percent(33.3).track_hints(100));
+        assert_eq!(rigid(0), Track::Percent(50.0).track_hints(-20));
+        assert_eq!(TrackHints{ fr_size: 2.0, ..TrackHints::default() }, Track::Fraction(2.0).track_hints(500));
+        assert_eq!(TrackHints{ fr_size: 0.0, ..TrackHints::default() }, Track::Auto.track_hints(500));
+        assert_eq!(TrackHints::default(), Track::default().track_hints(500));
+    }
+
+    #[test]
+    fn clamp_rect() {
+        let bounds = SizeBounds::new(DimsBox::new2(10, 10), DimsBox::new2(20, 20));
+        assert_eq!(BoundBox::new2(5, 5, 15, 25), bounds.clamp_rect(BoundBox::new2(5, 5, 6, 100)));
+        assert_eq!(BoundBox::new2(-5, 0, 10, 15), bounds.clamp_rect(BoundBox::new2(-5, 0, 10, 15)));
+    }
+}
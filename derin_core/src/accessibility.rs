@@ -0,0 +1,172 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Semantic descriptions of widgets, for assistive technologies such as screen readers.
+//!
+//! Widgets describe themselves with `Widget::accessibility_info`, and `Root::accessibility_tree`
+//! assembles those descriptions into a tree with the same shape as the widget tree. Platform
+//! bindings can translate that tree into the native accessibility APIs, and tests can inspect it
+//! directly.
+
+use crate::widget::{WidgetId, WidgetIdent};
+use cgmath_geometry::{D2, rect::BoundBox};
+
+/// What kind of control a widget is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccessibilityRole {
+    Button,
+    CheckBox,
+    RadioButton,
+    Slider,
+    ProgressBar,
+    /// Static text, such as a label.
+    Text,
+    /// An editable text field.
+    TextInput,
+    /// A widget that exists only to contain other widgets.
+    Group,
+    Menu,
+    MenuItem,
+}
+
+/// Flags describing a widget's current state.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AccessibilityState {
+    /// Whether the widget is checked, or `None` if it can't be checked.
+    pub checked: Option<bool>,
+    /// Whether the widget has keyboard focus. This is filled in by the framework.
+    pub focused: bool,
+    pub disabled: bool,
+}
+
+/// The description a widget gives of itself.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AccessibilityNode {
+    pub role: AccessibilityRole,
+    /// The text that identifies the widget to the user, such as a button's caption.
+    pub label: Option<String>,
+    /// The widget's current value, such as the text in an edit box or a slider's position.
+    pub value: Option<String>,
+    pub state: AccessibilityState,
+}
+
+/// A widget's entry in the accessibility tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessibilityTreeNode {
+    pub widget_id: WidgetId,
+    pub ident: WidgetIdent,
+    /// The widget's rect, in window coordinates.
+    pub rect: BoundBox<D2, i32>,
+    /// The widget's description, or `None` if the widget doesn't provide one. Widgets without
+    /// descriptions are usually layout containers, which assistive technologies can skip over.
+    pub node: Option<AccessibilityNode>,
+    pub children: Vec<AccessibilityTreeNode>,
+}
+
+/// Assembles a tree out of nodes visited in depth-first order.
+pub(crate) struct TreeBuilder {
+    stack: Vec<AccessibilityTreeNode>,
+}
+
+impl AccessibilityNode {
+    /// Create a node with the given role and no label, value, or state.
+    pub fn new(role: AccessibilityRole) -> AccessibilityNode {
+        AccessibilityNode {
+            role,
+            label: None,
+            value: None,
+            state: AccessibilityState::default(),
+        }
+    }
+}
+
+impl AccessibilityTreeNode {
+    /// Find the node for the given widget among this node and its descendants.
+    pub fn find(&self, widget_id: WidgetId) -> Option<&AccessibilityTreeNode> {
+        if self.widget_id == widget_id {
+            return Some(self);
+        }
+        self.children.iter().filter_map(|c| c.find(widget_id)).next()
+    }
+}
+
+impl TreeBuilder {
+    pub fn new() -> TreeBuilder {
+        TreeBuilder {
+            stack: Vec::new(),
+        }
+    }
+
+    /// Add a node at the given depth, where the root is at depth `1`. The node becomes a child of
+    /// the last node pushed at `depth - 1`.
+    pub fn push(&mut self, depth: usize, node: AccessibilityTreeNode) {
+        self.collapse(depth - 1);
+        self.stack.push(node);
+    }
+
+    /// Get the root of the tree.
+    pub fn finish(mut self) -> Option<AccessibilityTreeNode> {
+        self.collapse(1);
+        self.stack.pop()
+    }
+
+    /// Pop nodes off of the stack and add them to their parents until only `len` nodes remain.
+    fn collapse(&mut self, len: usize) {
+        while self.stack.len() > usize::max(len, 1) {
+            let node = self.stack.pop().unwrap();
+            self.stack.last_mut().unwrap().children.push(node);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(num: u32) -> AccessibilityTreeNode {
+        AccessibilityTreeNode {
+            widget_id: WidgetId::new(),
+            ident: WidgetIdent::Num(num),
+            rect: BoundBox::new2(0, 0, 0, 0),
+            node: None,
+            children: Vec::new(),
+        }
+    }
+
+    fn idents(node: &AccessibilityTreeNode) -> Vec<(u32, usize)> {
+        let mut idents = vec![];
+        fn visit(node: &AccessibilityTreeNode, depth: usize, idents: &mut Vec<(u32, usize)>) {
+            match node.ident {
+                WidgetIdent::Num(n) => idents.push((n, depth)),
+                _ => unreachable!()
+            }
+            for child in &node.children {
+                visit(child, depth + 1, idents);
+            }
+        }
+        visit(node, 1, &mut idents);
+        idents
+    }
+
+    #[test]
+    fn build_tree() {
+        let mut builder = TreeBuilder::new();
+        let nodes = [(0, 1), (1, 2), (2, 3), (3, 3), (4, 2), (5, 3), (6, 4), (7, 2)];
+        for &(num, depth) in &nodes {
+            builder.push(depth, node(num));
+        }
+
+        let root = builder.finish().unwrap();
+        assert_eq!(&nodes[..], &idents(&root)[..]);
+        assert_eq!(3, root.children.len());
+
+        let found = root.children[1].children[0].children[0].widget_id;
+        assert_eq!(Some(found), root.find(found).map(|n| n.widget_id));
+    }
+
+    #[test]
+    fn build_empty() {
+        assert_eq!(None, TreeBuilder::new().finish());
+    }
+}
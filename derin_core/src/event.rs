@@ -23,12 +23,25 @@ pub struct EventOps {
     pub focus: Option<FocusChange>,
     /// Bubble the event to the parent widget.
     pub bubble: bool,
+    /// Whether or not the widget acted on the event.
+    ///
+    /// This is independent of `bubble`. A widget can handle an event and still bubble it so that
+    /// its parents can react as well, and a widget can ignore an event without bubbling it. If any
+    /// widget the event gets delivered to reports handling it, the event is considered handled
+    /// (see `FrameEventProcessor::process_event`), which lets applications tell consumed events
+    /// apart from ones that fell through the widget tree. Defaults to `false`.
+    pub handled: bool,
 }
 
 /// Changes the keyboard focus, removing the focus from another widget if necessary.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum FocusChange {
     /// Give keyboard focus to the widget after the widget sending a focus request.
+    ///
+    /// Siblings are ordered by their tab index (see `WidgetTag::set_tab_index`), and then by their
+    /// position in the tree. Moving past the last child of an active focus scope wraps around to
+    /// the first child (see `WidgetTag::push_focus_scope`). If the next sibling doesn't accept
+    /// focus but has children, focus moves into it (see `Parent::preferred_focus_child`).
     Next,
     /// Give keyboard focus to the widget before the widget sending a focus request.
     Prev,
@@ -50,7 +63,8 @@ pub struct MouseDown {
     /// Which mouse button was pressed.
     pub button: MouseButton,
     /// The position at which the button was pressed, relative to the widget's origin.
-    pub down_pos: Point2<i32>
+    pub down_pos: Point2<i32>,
+    pub(crate) down_time: Instant,
 }
 
 /// The general state of user input devices when an event has occured.
@@ -61,7 +75,12 @@ pub struct InputState<'a> {
     /// The mouse buttons that have been pressed inside of the widget.
     pub mouse_buttons_down_in_widget: &'a [MouseDown],
     /// The position of the mouse, relative to the widget's origin.
+    ///
+    /// This is `None` once the cursor has left the window, unless mouse buttons are still being
+    /// held, in which case the cursor keeps getting tracked outside of the window.
     pub mouse_pos: Option<Point2<i32>>,
+    /// Whether the cursor is inside of the window.
+    pub mouse_in_window: bool,
     /// The modifier keys that have been pressed down.
     pub modifiers: ModifierKeys,
     /// The keys that have been pressed inside of the window.
@@ -82,6 +101,19 @@ pub enum FocusSource {
     },
 }
 
+/// A change in which widget the mouse cursor is hovering over.
+///
+/// When a single cursor movement enters or exits several nested widgets at once, the widgets
+/// receive their hover changes in a fixed order:
+///
+/// - Parents get entered before their children. Each entered widget receives `Enter`, followed by
+///   `EnterChild` if the cursor went on to enter one of its children, after which the child
+///   receives its own events.
+/// - Children get exited before their parents. Each exited widget receives `Exit`, after which its
+///   parent receives `ExitChild`, followed by `Exit` if the cursor left the parent too.
+///
+/// When the cursor moves between siblings, the old sibling is exited before the new one is
+/// entered.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MouseHoverChange {
     /// The mouse cursor has entered the widget.
@@ -99,6 +131,9 @@ pub enum WidgetEventSourced<'a> {
     /// The event was dispatched directly to this widget.
     This(WidgetEvent),
     /// The event was dispatched to the specified child widget, and got bubbled up to this widget.
+    ///
+    /// The slice is the path from this widget to the widget the event was dispatched to, starting
+    /// with the ident of this widget's direct child.
     Bubble(WidgetEvent, &'a [WidgetIdent])
 }
 
@@ -154,6 +189,27 @@ pub enum WidgetEvent {
         /// The button that was released.
         button: MouseButton
     },
+    /// A mouse button has been pressed and released over the widget, without the cursor being
+    /// dragged in between.
+    ///
+    /// This is delivered after the corresponding `MouseUp` event. Widgets that act when they're
+    /// clicked, such as buttons, should respond to this instead of correlating `MouseDown` and
+    /// `MouseUp` events themselves.
+    ///
+    /// The press only counts as a click if the cursor stays within 4 pixels of where the button
+    /// was pressed, along both axes.
+    ///
+    /// # Migrating from `MiddleClick`
+    ///
+    /// This replaces the `MiddleClick` event, which was only delivered for the middle button.
+    /// Code that matched `WidgetEvent::MiddleClick{pos}` should match
+    /// `WidgetEvent::Click{pos, button: MouseButton::Middle}` instead.
+    Click {
+        /// The position of the cursor when the button was released.
+        pos: Point2<i32>,
+        /// The button that was clicked.
+        button: MouseButton
+    },
     MouseScrollLines {
         dir: Vector2<i32>,
         in_widget: bool,
@@ -178,6 +234,25 @@ pub enum WidgetEvent {
     KeyDown(Key, ModifierKeys),
     /// The given key has been released on the keyboard.
     KeyUp(Key, ModifierKeys),
+    /// The widget's access key has been pressed while `Alt` was held. See
+    /// `WidgetTag::set_access_key`.
+    ///
+    /// This is delivered instead of the `KeyDown` event the focused widget would have received.
+    AccessKey,
+    /// The window containing the widget has gained (`true`) or lost (`false`) focus.
+    ///
+    /// This is delivered to the root widget and the widget with keyboard focus. Any mouse
+    /// buttons or keys that were held when the window lost focus get released before this is
+    /// delivered.
+    WindowFocusChanged(bool),
+    /// The cursor has left the window.
+    ///
+    /// This is delivered to the hovered widget and each of its ancestors, deepest first, before
+    /// the `MouseMove` events that move the cursor out of them. Unlike the `MouseHoverChange::Exit`
+    /// a widget gets when the cursor moves onto another widget, this means that no widget is
+    /// hovered anymore, so widgets can use it to reset any hover state they're tracking. The
+    /// cursor position is still reported while mouse buttons are held outside of the window.
+    MouseLeaveWindow,
     /// Enough time has elapsed for a registered timer to be triggered.
     Timer {
         /// The timer's ID.
@@ -196,6 +271,48 @@ pub enum WidgetEvent {
     },
 }
 
+impl MouseDown {
+    /// Creates a new `MouseDown`, recording the current time as the time of the press.
+    pub fn new(button: MouseButton, down_pos: Point2<i32>) -> MouseDown {
+        MouseDown {
+            button,
+            down_pos,
+            down_time: Instant::now(),
+        }
+    }
+
+    /// When the button was pressed.
+    #[inline]
+    pub fn down_time(&self) -> Instant {
+        self.down_time
+    }
+}
+
+impl InputState<'_> {
+    /// Retrieves the press information for `button`, if it's currently held down.
+    pub fn mouse_down(&self, button: MouseButton) -> Option<&MouseDown> {
+        self.mouse_buttons_down.iter().find(|d| d.button == button)
+    }
+
+    /// Whether `button` is currently held down.
+    #[inline]
+    pub fn is_down(&self, button: MouseButton) -> bool {
+        self.mouse_down(button).is_some()
+    }
+
+    /// The position at which `button` was pressed, relative to the widget's origin. `None` if the
+    /// button isn't held down.
+    #[inline]
+    pub fn down_position(&self, button: MouseButton) -> Option<Point2<i32>> {
+        self.mouse_down(button).map(|d| d.down_pos)
+    }
+
+    /// How long `button` has been held down for. `None` if the button isn't held down.
+    pub fn held_duration(&self, button: MouseButton) -> Option<Duration> {
+        self.mouse_down(button).map(|d| Instant::now().duration_since(d.down_time))
+    }
+}
+
 impl WidgetEventSourced<'_> {
     pub fn unwrap(self) -> WidgetEvent {
         match self {
@@ -211,13 +328,38 @@ impl WidgetEventSourced<'_> {
         }
     }
 
+    #[deprecated(note = "use `is_bubbled` instead")]
     pub fn is_bubble(&self) -> bool {
+        self.is_bubbled()
+    }
+
+    /// Whether the event was dispatched to a descendant and bubbled up to this widget, rather
+    /// than being dispatched to this widget directly.
+    pub fn is_bubbled(&self) -> bool {
         match self {
             WidgetEventSourced::This(..) => false,
             WidgetEventSourced::Bubble(..) => true,
         }
     }
 
+    /// The path from this widget to the widget the event was dispatched to. Empty if the event was
+    /// dispatched to this widget.
+    pub fn source_path(&self) -> &[WidgetIdent] {
+        match self {
+            WidgetEventSourced::This(..) => &[],
+            WidgetEventSourced::Bubble(_, path) => path,
+        }
+    }
+
+    /// The event, regardless of where it was dispatched. Any coordinates in the event are
+    /// relative to this widget.
+    pub fn original_event(&self) -> &WidgetEvent {
+        match self {
+            WidgetEventSourced::This(event) |
+            WidgetEventSourced::Bubble(event, _) => event
+        }
+    }
+
     pub fn default_bubble(&self) -> bool {
         match self {
             WidgetEventSourced::This(event) => event.default_bubble(),
@@ -237,9 +379,13 @@ impl WidgetEvent {
 
             WidgetEvent::GainFocus(..) |
             WidgetEvent::LoseFocus |
+            WidgetEvent::WindowFocusChanged(..) |
+            WidgetEvent::MouseLeaveWindow |
             WidgetEvent::MouseMove{..} |
             WidgetEvent::MouseDown{..} |
             WidgetEvent::MouseUp{..} |
+            WidgetEvent::Click{..} |
+            WidgetEvent::AccessKey |
             WidgetEvent::Timer{..} => false
         }
     }
@@ -263,12 +409,20 @@ impl WidgetEvent {
                     down_pos: down_pos + dir,
                     in_widget, pressed_in_widget, button,
                 },
+            WidgetEvent::Click{ pos, button } =>
+                WidgetEvent::Click {
+                    pos: pos + dir,
+                    button,
+                },
             WidgetEvent::Char(..)              |
             WidgetEvent::LoseFocus             |
             WidgetEvent::GainFocus(..)         |
+            WidgetEvent::WindowFocusChanged(..)|
+            WidgetEvent::MouseLeaveWindow      |
             WidgetEvent::Timer{..}             |
             WidgetEvent::KeyUp(..)             |
             WidgetEvent::KeyDown(..)           |
+            WidgetEvent::AccessKey             |
             WidgetEvent::MouseScrollPx{..}     |
             WidgetEvent::MouseScrollLines{..} =>
                 self
@@ -5,17 +5,24 @@
 mod dispatcher;
 
 use crate::{
-    WindowEvent, InputState, LoopFlow,
-    cgmath::{Vector2},
+    WindowEvent, InputState, FocusScope, LoopFlow, EventFilter, FilterAction, CloseAction, CloseHandler,
+    cgmath::{EuclideanSpace, Point2, Vector2},
     event::{EventOps, FocusChange, FocusSource, MouseHoverChange, WidgetEvent, WidgetEventSourced},
     render::Renderer,
-    widget_traverser::{Relation, WidgetTraverser, OffsetWidgetScanPath},
-    update_state::{UpdateStateCell},
+    widget_traverser::{Relation, WidgetTraverser, OffsetWidgetScanPath, tab_order_key},
+    update_state::{FocusScopeOp, UpdateStateCell},
+    widget::{WidgetDyn, WidgetId, WidgetIdent},
     offset_widget::OffsetWidget,
+    mbseq::MouseButtonSequenceTrackPos,
 };
+use derin_common_types::buttons::ModifierKeys;
 use self::dispatcher::{EventDispatcher, EventDestination, DispatchableEvent};
 use cgmath_geometry::rect::{GeoBox, BoundBox};
-use std::rc::Rc;
+use std::{mem, rc::Rc, time::Instant};
+
+/// The furthest, in pixels along either axis, the cursor can move between a mouse button being
+/// pressed and released for the press to still count as a `Click`.
+const CLICK_DRAG_DIST: i32 = 4;
 
 pub(crate) struct EventTranslator
 {
@@ -33,6 +40,14 @@ pub(crate) struct TranslatorActive<'a, 'b, R>
 
 struct TranslatorInner {
     event_dispatcher: EventDispatcher,
+    hover_grace: u32,
+    invert_scroll: bool,
+    event_filter: Option<EventFilter>,
+    close_handler: Option<CloseHandler>,
+    /// Whether a `CloseRequested` event has been translated since the last `take_close_request`.
+    close_requested: bool,
+    /// Whether any widget has reported handling the window event currently being translated.
+    event_handled: bool,
 }
 
 impl EventTranslator
@@ -41,10 +56,78 @@ impl EventTranslator
         EventTranslator {
             inner: TranslatorInner {
                 event_dispatcher: EventDispatcher::new(),
+                hover_grace: 0,
+                invert_scroll: false,
+                event_filter: None,
+                close_handler: None,
+                close_requested: false,
+                event_handled: false,
             },
         }
     }
 
+    /// The number of pixels the cursor can move past the edge of the hovered widget before the
+    /// widget stops being the hover target.
+    #[inline]
+    pub fn hover_grace(&self) -> u32 {
+        self.inner.hover_grace
+    }
+
+    /// Set the hover grace distance. `0` disables the grace entirely.
+    ///
+    /// The grace only applies to movements no larger than the grace distance, so quickly moving
+    /// the cursor across many widgets still produces the usual enter and exit events.
+    #[inline]
+    pub fn set_hover_grace(&mut self, hover_grace: u32) {
+        self.inner.hover_grace = hover_grace;
+    }
+
+    /// Whether or not the direction of scroll events gets flipped before they're delivered.
+    #[inline]
+    pub fn invert_scroll(&self) -> bool {
+        self.inner.invert_scroll
+    }
+
+    /// Set whether or not to flip the direction of scroll events before they're delivered.
+    ///
+    /// Applies to both `MouseScrollLines` and `MouseScrollPx`.
+    #[inline]
+    pub fn set_invert_scroll(&mut self, invert_scroll: bool) {
+        self.inner.invert_scroll = invert_scroll;
+    }
+
+    /// Set the filter every window event gets passed through before being translated, returning
+    /// the previous filter.
+    pub fn set_event_filter(&mut self, event_filter: EventFilter) -> Option<EventFilter> {
+        self.inner.event_filter.replace(event_filter)
+    }
+
+    /// Remove the event filter, returning it.
+    pub fn remove_event_filter(&mut self) -> Option<EventFilter> {
+        self.inner.event_filter.take()
+    }
+
+    pub fn set_close_handler(&mut self, close_handler: CloseHandler) -> Option<CloseHandler> {
+        self.inner.close_handler.replace(close_handler)
+    }
+
+    pub fn remove_close_handler(&mut self) -> Option<CloseHandler> {
+        self.inner.close_handler.take()
+    }
+
+    /// Whether a `CloseRequested` event has been received since this was last called.
+    pub fn take_close_request(&mut self) -> bool {
+        mem::replace(&mut self.inner.close_requested, false)
+    }
+
+    /// Ask the close handler whether a close request should close the window.
+    pub fn confirm_close(&mut self) -> bool {
+        match self.inner.close_handler {
+            Some(ref mut close_handler) => close_handler() == CloseAction::Close,
+            None => true
+        }
+    }
+
     pub fn with_data<'a, 'b, R: Renderer>(
         &'a mut self,
         widget_traverser: &'a mut WidgetTraverser<'b, R>,
@@ -63,7 +146,26 @@ impl EventTranslator
 impl<R> TranslatorActive<'_, '_, R>
     where R: Renderer + 'static
 {
-    pub fn translate_window_event(&mut self, window_event: WindowEvent) {
+    /// Translate the window event and dispatch it to the widget tree, returning whether any widget
+    /// reported handling it.
+    pub fn translate_window_event(&mut self, window_event: WindowEvent) -> bool {
+        let window_event = match self.inner.event_filter {
+            Some(ref mut event_filter) => match event_filter(&window_event) {
+                FilterAction::Pass => window_event,
+                FilterAction::Consume => return false,
+                FilterAction::Replace(event) => event,
+            },
+            None => window_event
+        };
+        self.inner.event_handled = false;
+        self.translate_unfiltered(window_event);
+        self.inner.event_handled
+    }
+
+    /// Translate the event without passing it through the event filter. Events generated by the
+    /// translator itself, such as the button releases caused by the window losing focus, go
+    /// through this so that the filter only sees events that came from the window.
+    fn translate_unfiltered(&mut self, window_event: WindowEvent) {
         use self::WindowEvent::*;
 
         let TranslatorActive {
@@ -74,7 +176,23 @@ impl<R> TranslatorActive<'_, '_, R>
         } = self;
         let TranslatorInner {
             ref mut event_dispatcher,
-        } = inner;
+            hover_grace: max_hover_grace,
+            invert_scroll,
+            event_filter: _,
+            close_handler: _,
+            ref mut close_requested,
+            ref mut event_handled,
+        } = **inner;
+        let scroll_sign = match invert_scroll {
+            false => 1,
+            true => -1,
+        };
+
+        apply_focus_scope_ops(update_state, input_state, event_dispatcher);
+        // The innermost focus scope, along with its path, if focus is being trapped within one.
+        let focus_trap: Option<(WidgetId, Vec<WidgetIdent>)> = input_state.focus_scopes.last()
+            .and_then(|s| widget_traverser.get_widget(s.scope))
+            .map(|wpath| (wpath.widget_id, wpath.path.to_vec()));
 
         let root_id = widget_traverser.root_id();
         let mut root_widget_rect = || widget_traverser.get_widget(root_id).unwrap().widget.rect();
@@ -109,12 +227,27 @@ impl<R> TranslatorActive<'_, '_, R>
                 .map(|d| d.widget_id)
                 .chain(input_state.focused_widget);
 
+        // The distance the hovered widget's rect gets expanded by when checking whether or not the
+        // cursor has left it. Only set for small movements within the window, so that fast
+        // movements and the cursor leaving the window aren't affected.
+        let mut hover_grace = 0;
+
         let _: Option<()> =
         match window_event {
             MouseMove(new_pos) => try {
                 let old_pos = input_state.mouse_pos
                     .unwrap_or_else(|| project_to_outside_root(new_pos));
                 input_state.mouse_pos = Some(new_pos);
+                input_state.mouse_in_window = root_widget_rect().contains(new_pos);
+
+                let move_dist = (new_pos - old_pos).map(|i| i.abs());
+                let root_rect = root_widget_rect();
+                if i32::max(move_dist.x, move_dist.y) <= max_hover_grace as i32 &&
+                    root_rect.contains(old_pos) &&
+                    root_rect.contains(new_pos)
+                {
+                    hover_grace = max_hover_grace as i32;
+                }
 
                 let hover_widget_id = input_state.mouse_hover_widget
                     .unwrap_or(widget_traverser.root_id());
@@ -138,14 +271,28 @@ impl<R> TranslatorActive<'_, '_, R>
                     );
                 }
             },
-            MouseEnter => None,
-            // We convert `MouseExit` events to `MouseMove` events so that we don't have to duplicate
-            // code.
+            MouseEnter => {
+                input_state.mouse_in_window = true;
+                None
+            },
+            // The hovered widget and its ancestors get told that the cursor has left the window,
+            // and then we convert `MouseExit` events to `MouseMove` events so that we don't have to
+            // duplicate the code that moves the cursor out of the hovered widgets.
             MouseExit => {
-                if let Some(old_pos) = input_state.mouse_pos {
-                    let new_pos = project_to_outside_root(old_pos);
+                input_state.mouse_in_window = false;
+                let new_pos = input_state.mouse_pos.map(|old_pos| project_to_outside_root(old_pos));
+
+                if let Some(hover_widget) = input_state.mouse_hover_widget {
+                    event_dispatcher.queue_direct_event(hover_widget, WidgetEvent::MouseLeaveWindow);
+                    for widget_id in widget_traverser.ancestors(hover_widget) {
+                        event_dispatcher.queue_direct_event(widget_id, WidgetEvent::MouseLeaveWindow);
+                    }
+                }
 
-                    self.translate_window_event(WindowEvent::MouseMove(new_pos));
+                if let Some(new_pos) = new_pos {
+                    self.translate_unfiltered(WindowEvent::MouseMove(new_pos));
+                    // The cursor position is still tracked while buttons are held, so that widgets
+                    // being dragged keep getting `MouseMove` events.
                     if self.input_state.mouse_buttons_down.len() == 0 {
                         self.input_state.mouse_pos = None;
                     }
@@ -167,7 +314,7 @@ impl<R> TranslatorActive<'_, '_, R>
                         button: mouse_button
                     },
                 );
-                input_state.mouse_buttons_down.push_button(mouse_button, mouse_pos, hover_widget_id);
+                input_state.mouse_buttons_down.push_button(mouse_button, mouse_pos, hover_widget_id, Instant::now());
 
                 for widget_id in mouse_event_widget_iter.filter(|id| *id != hover_widget_id) {
                     event_dispatcher.queue_direct_event(
@@ -196,6 +343,21 @@ impl<R> TranslatorActive<'_, '_, R>
                         button: mouse_button
                     },
                 );
+
+                let drag = mouse_pos - mouse_down.mouse_down.down_pos;
+                let is_click =
+                    mouse_down.widget_id == hover_widget_id &&
+                    drag.x.abs() <= CLICK_DRAG_DIST &&
+                    drag.y.abs() <= CLICK_DRAG_DIST;
+                if is_click {
+                    event_dispatcher.queue_direct_event(
+                        hover_widget_id,
+                        WidgetEvent::Click {
+                            pos: mouse_pos,
+                            button: mouse_button,
+                        },
+                    );
+                }
                 input_state.mouse_buttons_down.release_button(mouse_button);
 
                 for widget_id in mouse_event_widget_iter.filter(|id| *id != hover_widget_id) {
@@ -212,6 +374,7 @@ impl<R> TranslatorActive<'_, '_, R>
                 }
             },
             MouseScrollLines(dir) => try {
+                let dir = dir * scroll_sign;
                 let hover_widget_id = input_state.mouse_hover_widget?;
                 event_dispatcher.queue_direct_event(
                     hover_widget_id,
@@ -226,6 +389,7 @@ impl<R> TranslatorActive<'_, '_, R>
                 }
             },
             MouseScrollPx(dir) => try {
+                let dir = dir * scroll_sign;
                 let hover_widget_id = input_state.mouse_hover_widget?;
                 event_dispatcher.queue_direct_event(
                     hover_widget_id,
@@ -240,18 +404,58 @@ impl<R> TranslatorActive<'_, '_, R>
                 }
             },
             WindowResize(size) => try {
-                widget_traverser.get_widget(root_id).unwrap().widget.set_rect(BoundBox::new2(0, 0, size.dims.x as i32, size.dims.y as i32));
+                let mut root = widget_traverser.get_widget(root_id).unwrap().widget;
+                let old_rect = root.inner().rect();
+                root.set_rect(BoundBox::new2(0, 0, size.dims.x as i32, size.dims.y as i32));
+                if root.inner().rect() != old_rect {
+                    root.inner_mut().on_update_layout(old_rect);
+                }
                 update_state.borrow_mut().queue_global_update();
             },
+            // Everything's measured in logical pixels, so the widgets themselves don't need to
+            // know about the change. They do need to be relaid out and redrawn, since the
+            // renderer's text metrics change with the scale.
+            ScaleFactorChanged(scale_factor) => try {
+                if input_state.scale_factor != scale_factor {
+                    input_state.scale_factor = scale_factor;
+                    update_state.borrow_mut().queue_global_update();
+                }
+            },
             KeyDown(key) => try {
                 if !input_state.keys_down.contains(&key) {
                     input_state.keys_down.push(key);
-                    match input_state.focused_widget {
-                        Some(widget) => event_dispatcher.queue_direct_event(
+
+                    // `Alt` and a letter or digit activates the widget with the matching access
+                    // key, if there is one. Widgets in disabled subtrees are skipped.
+                    let mut access_key_widget = None;
+                    if let (true, Some(c)) = (input_state.modifiers.contains(ModifierKeys::ALT), key.to_char()) {
+                        let mut disabled_owner_depth = None;
+                        widget_traverser.crawl_widgets(|path| {
+                            let depth = path.path.len();
+                            if disabled_owner_depth.map(|d| d >= depth).unwrap_or(false) {
+                                disabled_owner_depth = None;
+                            }
+                            let widget_tag = path.widget.widget_tag();
+                            if disabled_owner_depth.is_none() && !widget_tag.is_enabled() {
+                                disabled_owner_depth = Some(depth);
+                            }
+
+                            if access_key_widget.is_none() && disabled_owner_depth.is_none() && widget_tag.access_key() == Some(c) {
+                                access_key_widget = Some(path.widget_id);
+                            }
+                        });
+                    }
+
+                    match (access_key_widget, input_state.focused_widget) {
+                        (Some(widget), _) => event_dispatcher.queue_direct_event(
+                            widget,
+                            WidgetEvent::AccessKey,
+                        ),
+                        (None, Some(widget)) => event_dispatcher.queue_direct_event(
                             widget,
                             WidgetEvent::KeyDown(key, input_state.modifiers),
                         ),
-                        None => println!("dispatch to universal fallthrough")
+                        (None, None) => println!("dispatch to universal fallthrough")
                     }
                 }
             },
@@ -275,6 +479,52 @@ impl<R> TranslatorActive<'_, '_, R>
                     None => println!("dispatch to universal fallthrough")
                 }
             },
+            WindowFocus(focused) => {
+                // When the window loses focus, we won't recieve the release events for any buttons
+                // or keys that are currently held. Release them now so widgets don't get stuck in
+                // a pressed state.
+                if !focused && (input_state.mouse_buttons_down.len() != 0 || input_state.keys_down.len() != 0) {
+                    let buttons_down = input_state.mouse_buttons_down
+                        .clone().into_iter()
+                        .map(|d| d.mouse_down.button)
+                        .collect::<Vec<_>>();
+                    let keys_down = input_state.keys_down.clone();
+
+                    for button in buttons_down {
+                        self.translate_unfiltered(WindowEvent::MouseUp(button));
+                    }
+                    for key in keys_down {
+                        self.translate_unfiltered(WindowEvent::KeyUp(key));
+                    }
+
+                    // `MouseUp` doesn't release anything if the cursor isn't in the window, so
+                    // make sure everything gets cleared regardless.
+                    self.input_state.mouse_buttons_down = MouseButtonSequenceTrackPos::new();
+                    self.input_state.keys_down.clear();
+                    self.input_state.modifiers = ModifierKeys::empty();
+
+                    self.translate_unfiltered(WindowEvent::WindowFocus(false));
+                    return;
+                }
+
+                event_dispatcher.queue_direct_event(
+                    root_id,
+                    WidgetEvent::WindowFocusChanged(focused),
+                );
+                if let Some(focused_widget) = input_state.focused_widget.filter(|id| *id != root_id) {
+                    event_dispatcher.queue_direct_event(
+                        focused_widget,
+                        WidgetEvent::WindowFocusChanged(focused),
+                    );
+                }
+
+                None
+            },
+            CloseRequested => {
+                // The close handler gets called in FrameEventProcessor::finish
+                *close_requested = true;
+                None
+            },
             Timer => None, // The timers will be handled in FrameEventProcessor::finish
             Redraw => try {
                 update_state.borrow_mut().queue_global_update();
@@ -283,7 +533,7 @@ impl<R> TranslatorActive<'_, '_, R>
 
         event_dispatcher.dispatch_events(
             widget_traverser,
-            |event_dispatcher, OffsetWidgetScanPath{mut widget, path, widget_id, index}, event| {
+            |event_dispatcher, OffsetWidgetScanPath{mut widget, path, widget_id, index}, enabled, event| {
                 let widget_ident = path.last().unwrap();
 
                 // Helper function that takes the `EventOps` generated by `on_widget_event`, updates
@@ -292,18 +542,20 @@ impl<R> TranslatorActive<'_, '_, R>
                     let EventOps {
                         focus,
                         bubble,
+                        handled,
                     } = ops;
+                    *event_handled |= handled;
                     if let Some(focus) = focus {
                         let of = widget_id;
                         let ident = widget_ident.clone();
                         let destination_source_opt = {
                             match focus.clone() {
                                 FocusChange::Next => Some((
-                                    EventDestination::Relation(of, Relation::Sibling(1)),
+                                    EventDestination::Relation(of, Relation::TabSibling(1)),
                                     FocusSource::Sibling{ident, delta: -1}
                                 )),
                                 FocusChange::Prev => Some((
-                                    EventDestination::Relation(of, Relation::Sibling(-1)),
+                                    EventDestination::Relation(of, Relation::TabSibling(-1)),
                                     FocusSource::Sibling{ident, delta: 1}
                                 )),
                                 FocusChange::Parent => Some((
@@ -345,11 +597,34 @@ impl<R> TranslatorActive<'_, '_, R>
                     // `MouseMove` can trigger other `MouseMove`s if the mouse moves into a child
                     // or parent widget.
                     DispatchableEvent::MouseMove{old_pos, new_pos, exiting_from_child} => {
-                        let widget_rect = match widget.rect_clipped() {
+                        let mut widget_rect = match widget.rect_clipped() {
                             Some(rect) => rect,
                             None => return
                         };
-                        let (contains_new, contains_old) = (widget_rect.contains(new_pos), widget_rect.contains(old_pos));
+                        let is_hover_widget = input_state.mouse_hover_widget.unwrap_or(root_id) == widget_id;
+                        if is_hover_widget && hover_grace > 0 {
+                            widget_rect = BoundBox::new2(
+                                widget_rect.min.x - hover_grace,
+                                widget_rect.min.y - hover_grace,
+                                widget_rect.max.x + hover_grace,
+                                widget_rect.max.y + hover_grace,
+                            );
+                        }
+
+                        // Points within the widget's actual rect also have to pass its hit test.
+                        // The hover grace area around the rect doesn't.
+                        let contains = |pos: Point2<i32>| widget_rect.contains(pos) && (
+                            !widget.rect().contains(pos) ||
+                            widget.inner().hit_test(pos - widget.rect().min.to_vec())
+                        );
+                        let contains_new = contains(new_pos);
+                        // If a parent is sending us the event, we're being entered and the old
+                        // position doesn't matter. That can happen without the old position
+                        // being outside our rect if the parent's hover grace was in effect.
+                        let contains_old = match exiting_from_child.is_none() && !is_hover_widget {
+                            true => false,
+                            false => contains(old_pos)
+                        };
 
                         let mut send_exiting_from_child = |widget: &mut OffsetWidget<'_, R>, in_widget| {
                             if let Some(child_ident) = exiting_from_child.clone() {
@@ -367,13 +642,33 @@ impl<R> TranslatorActive<'_, '_, R>
                         match contains_new {
                             true => {
                                 let mut enter_child_opt = None;
+                                let mut hit_child = false;
                                 widget.children_mut(|child_summary| {
-                                    if child_summary.widget.rect_clipped().map(|r| r.contains(new_pos)).unwrap_or(false) {
-                                        enter_child_opt = Some((child_summary.widget.widget_id(), child_summary.ident));
-                                        LoopFlow::Break
-                                    } else {
-                                        LoopFlow::Continue
+                                    // The first child under the cursor takes it, unless a popup
+                                    // lies under the cursor too. Popups get drawn over their
+                                    // siblings, so the last popup under the cursor takes it
+                                    // instead. Pointer-transparent children, and children that
+                                    // fail their hit test, get skipped, so the cursor falls
+                                    // through to whatever lies beneath them. Disabled children
+                                    // block the cursor without getting entered, so it stays in
+                                    // this widget.
+                                    let child = &child_summary.widget;
+                                    let popup = child.widget_tag().is_popup();
+                                    if hit_child && !popup {
+                                        return LoopFlow::Continue;
+                                    }
+                                    let hit =
+                                        !child.widget_tag().pointer_transparent() &&
+                                        child.rect_clipped().map(|r| r.contains(new_pos)).unwrap_or(false) &&
+                                        child.inner().hit_test(new_pos - child.rect().min.to_vec());
+                                    if hit {
+                                        hit_child = true;
+                                        enter_child_opt = match enabled && child.widget_tag().is_enabled() {
+                                            true => Some((child_summary.widget.widget_id(), child_summary.ident)),
+                                            false => None
+                                        };
                                     }
+                                    LoopFlow::Continue
                                 });
 
                                 send_exiting_from_child(&mut widget, contains_new && enter_child_opt.is_none());
@@ -389,6 +684,9 @@ impl<R> TranslatorActive<'_, '_, R>
                                     ));
                                 }
 
+                                // The child only gets its events once this widget has handled
+                                // its own, so nested widgets get entered outermost-first. See
+                                // `MouseHoverChange` for the full ordering.
                                 match enter_child_opt {
                                     Some((enter_child_id, enter_child_ident)) => {
                                         perform_event_ops(widget.on_widget_event(
@@ -444,6 +742,52 @@ impl<R> TranslatorActive<'_, '_, R>
                         }
                     },
                     DispatchableEvent::GainFocus{source, change} => if input_state.focused_widget != Some(widget_id) {
+                        // Focus can't leave the innermost focus scope. The trap only applies if
+                        // the scope it was computed for hasn't been popped since.
+                        if let Some((trap_id, ref trap_path)) = focus_trap {
+                            let trap_active = input_state.focus_scopes.last().map(|s| s.scope) == Some(trap_id);
+                            if trap_active && !path.starts_with(trap_path) {
+                                return;
+                            }
+                        }
+
+                        // If focus is being moved between siblings and this widget doesn't want
+                        // focus, pass it along to the next sibling in the same direction. Disabled
+                        // widgets pass it along without letting any of their descendants take it.
+                        if let FocusSource::Sibling{ref ident, delta} = source {
+                            if !enabled || !widget.inner().accepts_focus() {
+                                // Focus scopes wrap around, so stop once we've gotten back to
+                                // where we started.
+                                if ident == widget_ident {
+                                    return;
+                                }
+                                // Tabbing into a container moves focus to one of its descendants.
+                                let entry_id = match enabled {
+                                    true => focus_entry(widget.inner(), delta < 0),
+                                    false => None
+                                };
+                                if let Some(entry_id) = entry_id {
+                                    event_dispatcher.queue_event(
+                                        EventDestination::Widget(entry_id),
+                                        DispatchableEvent::GainFocus{source: FocusSource::Parent, change}
+                                    );
+                                    return;
+                                }
+                                let step = -delta.signum();
+                                event_dispatcher.queue_event(
+                                    EventDestination::Relation(widget_id, Relation::TabSibling(step)),
+                                    DispatchableEvent::GainFocus {
+                                        source: FocusSource::Sibling{ident: ident.clone(), delta: delta - step},
+                                        change,
+                                    }
+                                );
+                                return;
+                            }
+                        }
+                        if !enabled {
+                            return;
+                        }
+
                         if let Some(focused_widget_id) = input_state.focused_widget {
                             event_dispatcher.queue_direct_event(
                                 focused_widget_id,
@@ -456,14 +800,24 @@ impl<R> TranslatorActive<'_, '_, R>
                         );
                     },
                     DispatchableEvent::Direct{bubble_source, event} => {
+                        if !enabled && !reaches_disabled(&event) {
+                            return;
+                        }
                         if bubble_source.is_some() {
                             unimplemented!()
                         } else {
                             match event {
-                                WidgetEvent::LoseFocus =>
-                                    input_state.focused_widget = None,
-                                WidgetEvent::GainFocus(..) =>
-                                    input_state.focused_widget= Some(widget_id),
+                                WidgetEvent::LoseFocus => {
+                                    input_state.focused_widget = None;
+                                    widget.widget_tag().set_has_keyboard_focus(false);
+                                    // Redraw so that the focus ring gets moved.
+                                    update_state.borrow_mut().redraw.insert(widget_id);
+                                },
+                                WidgetEvent::GainFocus(..) => {
+                                    input_state.focused_widget= Some(widget_id);
+                                    widget.widget_tag().set_has_keyboard_focus(true);
+                                    update_state.borrow_mut().redraw.insert(widget_id);
+                                },
                                 _ => ()
                             }
                         }
@@ -471,6 +825,9 @@ impl<R> TranslatorActive<'_, '_, R>
                             WidgetEventSourced::This(event),
                             input_state,
                         ));
+                        // Apply scope changes right away, so that focus gets restored as part of
+                        // the event that popped the scope.
+                        apply_focus_scope_ops(update_state, input_state, event_dispatcher);
                     }
                 }
             }
@@ -478,6 +835,89 @@ impl<R> TranslatorActive<'_, '_, R>
     }
 }
 
+/// Whether `event` still gets delivered to a widget that's disabled. Disabled widgets don't get
+/// anything that could start an interaction, but they do get the events that end interactions
+/// already in progress, so that they don't get stuck pressed, hovered, or focused.
+fn reaches_disabled(event: &WidgetEvent) -> bool {
+    match event {
+        WidgetEvent::MouseMove{..} |
+        WidgetEvent::MouseUp{..} |
+        WidgetEvent::MouseLeaveWindow |
+        WidgetEvent::KeyUp(..) |
+        WidgetEvent::LoseFocus |
+        WidgetEvent::WindowFocusChanged(_) => true,
+        _ => false
+    }
+}
+
+/// Find the descendant of `widget` that gets focus when tabbing into it: the widget's preferred
+/// focus child if that has anything focusable, and otherwise the first focusable descendant in tab
+/// order (or the last, if `forward` is `false`). Disabled subtrees get skipped.
+fn focus_entry<R: Renderer>(widget: &dyn WidgetDyn<R>, forward: bool) -> Option<WidgetId> {
+    let enter_child = |child: &dyn WidgetDyn<R>| match (child.widget_tag().is_enabled(), child.accepts_focus()) {
+        (false, _) => None,
+        (true, true) => Some(child.widget_id()),
+        (true, false) => focus_entry(child, forward)
+    };
+
+    if let Some(ident) = widget.preferred_focus_child() {
+        if let Some(entry_id) = widget.child(ident).and_then(|child| enter_child(child.widget)) {
+            return Some(entry_id);
+        }
+    }
+
+    let mut children = Vec::new();
+    widget.children(&mut |summaries| {
+        children.extend(summaries.into_iter().map(|c| (tab_order_key(c.widget.widget_tag().tab_index(), c.index), c.widget)));
+        LoopFlow::Continue
+    });
+    children.sort_by_key(|&(key, _)| key);
+    if !forward {
+        children.reverse();
+    }
+    children.into_iter().filter_map(|(_, child)| enter_child(child)).next()
+}
+
+/// Update the focus scope stack with the pushes and pops widgets have requested, queuing focus
+/// restoration for popped scopes.
+fn apply_focus_scope_ops(update_state: &UpdateStateCell, input_state: &mut InputState, event_dispatcher: &mut EventDispatcher) {
+    let ops = mem::replace(&mut update_state.borrow_mut().focus_scope_ops, Vec::new());
+    for op in ops {
+        match op {
+            FocusScopeOp::Push(scope) => {
+                input_state.focus_scopes.retain(|s| s.scope != scope);
+                input_state.focus_scopes.push(FocusScope {
+                    scope,
+                    restore_focus: input_state.focused_widget,
+                });
+            },
+            FocusScopeOp::Pop(scope) => {
+                let position = match input_state.focus_scopes.iter().position(|s| s.scope == scope) {
+                    Some(position) => position,
+                    None => continue
+                };
+                let restore_focus = input_state.focus_scopes[position].restore_focus;
+                input_state.focus_scopes.truncate(position);
+
+                match (restore_focus, input_state.focused_widget) {
+                    (Some(restore_focus), _) => event_dispatcher.queue_event(
+                        EventDestination::Widget(restore_focus),
+                        DispatchableEvent::GainFocus {
+                            source: FocusSource::This,
+                            change: FocusChange::Take,
+                        }
+                    ),
+                    (None, Some(focused_widget)) => event_dispatcher.queue_direct_event(
+                        focused_widget,
+                        WidgetEvent::LoseFocus
+                    ),
+                    (None, None) => ()
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -505,7 +945,67 @@ mod tests {
                 &mut input_state,
                 update_state
             );
-        }
+        };
+        ($translator:pat, $tree:expr, $root_id:expr, hover_grace: $hover_grace:expr) => {
+            let message_bus = MessageBus::new();
+            let mut traverser_base: WidgetTraverserBase<TestRenderFrame> = WidgetTraverserBase::new($root_id);
+            let update_state = UpdateState::new(&message_bus);
+            let mut traverser = traverser_base.with_root_ref($tree, update_state.clone());
+            let mut input_state = InputState::new();
+
+            let mut translator = EventTranslator::new();
+            translator.set_hover_grace($hover_grace);
+            let $translator = translator.with_data(
+                &mut traverser,
+                &mut input_state,
+                update_state
+            );
+        };
+        ($translator:pat, $tree:expr, $root_id:expr, invert_scroll: $invert_scroll:expr) => {
+            let message_bus = MessageBus::new();
+            let mut traverser_base: WidgetTraverserBase<TestRenderFrame> = WidgetTraverserBase::new($root_id);
+            let update_state = UpdateState::new(&message_bus);
+            let mut traverser = traverser_base.with_root_ref($tree, update_state.clone());
+            let mut input_state = InputState::new();
+
+            let mut translator = EventTranslator::new();
+            translator.set_invert_scroll($invert_scroll);
+            let $translator = translator.with_data(
+                &mut traverser,
+                &mut input_state,
+                update_state
+            );
+        };
+        ($translator:pat, $tree:expr, $root_id:expr, modifiers: $modifiers:expr) => {
+            let message_bus = MessageBus::new();
+            let mut traverser_base: WidgetTraverserBase<TestRenderFrame> = WidgetTraverserBase::new($root_id);
+            let update_state = UpdateState::new(&message_bus);
+            let mut traverser = traverser_base.with_root_ref($tree, update_state.clone());
+            let mut input_state = InputState::new();
+            input_state.modifiers = $modifiers;
+
+            let mut translator = EventTranslator::new();
+            let $translator = translator.with_data(
+                &mut traverser,
+                &mut input_state,
+                update_state
+            );
+        };
+        ($translator:pat, $tree:expr, $root_id:expr, event_filter: $event_filter:expr) => {
+            let message_bus = MessageBus::new();
+            let mut traverser_base: WidgetTraverserBase<TestRenderFrame> = WidgetTraverserBase::new($root_id);
+            let update_state = UpdateState::new(&message_bus);
+            let mut traverser = traverser_base.with_root_ref($tree, update_state.clone());
+            let mut input_state = InputState::new();
+
+            let mut translator = EventTranslator::new();
+            translator.set_event_filter($event_filter);
+            let $translator = translator.with_data(
+                &mut traverser,
+                &mut input_state,
+                update_state
+            );
+        };
     }
 
     #[test]
@@ -650,6 +1150,11 @@ mod tests {
             },
 
             // WindowEvent::MouseExit
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::MouseLeaveWindow,
+            },
             TestEvent {
                 widget: a,
                 source_child: vec![],
@@ -674,113 +1179,289 @@ mod tests {
     }
 
     #[test]
-    fn mouse_move_though_multiple() {
+    fn mouse_enter_exit_order() {
         test_widget_tree!{
             let event_list = crate::test_helpers::EventList::new();
-            let mut tree = root {
-                rect: (0, 0, 60, 20);
-                left {
-                    rect: (10, 1, 30, 19);
-                    left_inner {rect: (5, 1, 15, 18)}
-                },
-                right {
-                    rect: (40, 1, 50, 19)
+            let mut tree = a {
+                rect: (0, 0, 40, 40);
+                b {
+                    rect: (10, 10, 30, 30);
+                    c {
+                        rect: (5, 5, 15, 15);
+                        d {
+                            rect: (2, 2, 8, 8)
+                        }
+                    }
                 }
             };
         }
-        // Rough diagram:
-        // root---------------------------------------------------+
-        // |    left-------------+         right------------+     |
-        // |    |  left_inner-+  |         |                |     |
-        // |    |  |          |  |         |                |     |
-        // |    |  |          |  |         |                |     |
-        // |    |  |          |  |         |                |     |
-        // |    |  +----------+  |         |                |     |
-        // |    +----------------+         +----------------+     |
-        // +------------------------------------------------------+
-
 
         event_list.set_events(vec![
-            // WindowEvent::MouseMove(Point2::new(5, 10))
+            // WindowEvent::MouseMove(Point2::new(1, 5))
             TestEvent {
-                widget: root,
+                widget: a,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(-1, 10),
-                    new_pos: Point2::new(5, 10),
+                    old_pos: Point2::new(-1, 5),
+                    new_pos: Point2::new(1, 5),
                     in_widget: true,
                     hover_change: Some(MouseHoverChange::Enter),
                 }
             },
 
-            // WindowEvent::MouseMove(Point2::new(20, 10))
+            // WindowEvent::MouseMove(Point2::new(20, 20))
+            // Parents get entered before their children.
             TestEvent {
-                widget: root,
+                widget: a,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(5, 10),
-                    new_pos: Point2::new(20, 10),
+                    old_pos: Point2::new(1, 5),
+                    new_pos: Point2::new(20, 20),
                     in_widget: false,
-                    hover_change: Some(MouseHoverChange::EnterChild(WidgetIdent::new_str("left"))),
+                    hover_change: Some(MouseHoverChange::EnterChild(WidgetIdent::new_str("b"))),
                 }
             },
             TestEvent {
-                widget: left,
+                widget: b,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(-5, 9),
-                    new_pos: Point2::new(10, 9),
+                    old_pos: Point2::new(-9, -5),
+                    new_pos: Point2::new(10, 10),
                     in_widget: false,
                     hover_change: Some(MouseHoverChange::Enter),
                 }
             },
             TestEvent {
-                widget: left,
+                widget: b,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(-5, 9),
-                    new_pos: Point2::new(10, 9),
+                    old_pos: Point2::new(-9, -5),
+                    new_pos: Point2::new(10, 10),
                     in_widget: false,
-                    hover_change: Some(MouseHoverChange::EnterChild(WidgetIdent::new_str("left_inner"))),
-                },
+                    hover_change: Some(MouseHoverChange::EnterChild(WidgetIdent::new_str("c"))),
+                }
             },
             TestEvent {
-                widget: left_inner,
+                widget: c,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(-10, 8),
-                    new_pos: Point2::new(5, 8),
-                    in_widget: true,
+                    old_pos: Point2::new(-14, -10),
+                    new_pos: Point2::new(5, 5),
+                    in_widget: false,
                     hover_change: Some(MouseHoverChange::Enter),
-                },
+                }
             },
-
-            // WindowEvent::MouseMove(Point2::new(45, 10)
             TestEvent {
-                widget: left_inner,
+                widget: c,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(5, 8),
-                    new_pos: Point2::new(30, 8),
+                    old_pos: Point2::new(-14, -10),
+                    new_pos: Point2::new(5, 5),
                     in_widget: false,
-                    hover_change: Some(MouseHoverChange::Exit),
-                },
+                    hover_change: Some(MouseHoverChange::EnterChild(WidgetIdent::new_str("d"))),
+                }
             },
             TestEvent {
-                widget: left,
+                widget: d,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(10, 9),
-                    new_pos: Point2::new(35, 9),
-                    in_widget: false,
-                    hover_change: Some(MouseHoverChange::ExitChild(WidgetIdent::new_str("left_inner"))),
-                },
+                    old_pos: Point2::new(-16, -12),
+                    new_pos: Point2::new(3, 3),
+                    in_widget: true,
+                    hover_change: Some(MouseHoverChange::Enter),
+                }
             },
+
+            // WindowEvent::MouseMove(Point2::new(-1, 5))
+            // Children get exited before their parents.
             TestEvent {
-                widget: left,
+                widget: d,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(10, 9),
+                    old_pos: Point2::new(3, 3),
+                    new_pos: Point2::new(-18, -12),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::Exit),
+                }
+            },
+            TestEvent {
+                widget: c,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(5, 5),
+                    new_pos: Point2::new(-16, -10),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::ExitChild(WidgetIdent::new_str("d"))),
+                }
+            },
+            TestEvent {
+                widget: c,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(5, 5),
+                    new_pos: Point2::new(-16, -10),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::Exit),
+                }
+            },
+            TestEvent {
+                widget: b,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(10, 10),
+                    new_pos: Point2::new(-11, -5),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::ExitChild(WidgetIdent::new_str("c"))),
+                }
+            },
+            TestEvent {
+                widget: b,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(10, 10),
+                    new_pos: Point2::new(-11, -5),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::Exit),
+                }
+            },
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(20, 20),
+                    new_pos: Point2::new(-1, 5),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::ExitChild(WidgetIdent::new_str("b"))),
+                }
+            },
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(20, 20),
+                    new_pos: Point2::new(-1, 5),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::Exit),
+                }
+            },
+        ]);
+
+        create_translator!(mut translator, &mut tree, a);
+
+        translator.translate_window_event(WindowEvent::MouseEnter);
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(1, 5)));
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(20, 20)));
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(-1, 5)));
+    }
+
+    #[test]
+    fn mouse_move_though_multiple() {
+        test_widget_tree!{
+            let event_list = crate::test_helpers::EventList::new();
+            let mut tree = root {
+                rect: (0, 0, 60, 20);
+                left {
+                    rect: (10, 1, 30, 19);
+                    left_inner {rect: (5, 1, 15, 18)}
+                },
+                right {
+                    rect: (40, 1, 50, 19)
+                }
+            };
+        }
+        // Rough diagram:
+        // root---------------------------------------------------+
+        // |    left-------------+         right------------+     |
+        // |    |  left_inner-+  |         |                |     |
+        // |    |  |          |  |         |                |     |
+        // |    |  |          |  |         |                |     |
+        // |    |  |          |  |         |                |     |
+        // |    |  +----------+  |         |                |     |
+        // |    +----------------+         +----------------+     |
+        // +------------------------------------------------------+
+
+
+        event_list.set_events(vec![
+            // WindowEvent::MouseMove(Point2::new(5, 10))
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(-1, 10),
+                    new_pos: Point2::new(5, 10),
+                    in_widget: true,
+                    hover_change: Some(MouseHoverChange::Enter),
+                }
+            },
+
+            // WindowEvent::MouseMove(Point2::new(20, 10))
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(5, 10),
+                    new_pos: Point2::new(20, 10),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::EnterChild(WidgetIdent::new_str("left"))),
+                }
+            },
+            TestEvent {
+                widget: left,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(-5, 9),
+                    new_pos: Point2::new(10, 9),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::Enter),
+                }
+            },
+            TestEvent {
+                widget: left,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(-5, 9),
+                    new_pos: Point2::new(10, 9),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::EnterChild(WidgetIdent::new_str("left_inner"))),
+                },
+            },
+            TestEvent {
+                widget: left_inner,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(-10, 8),
+                    new_pos: Point2::new(5, 8),
+                    in_widget: true,
+                    hover_change: Some(MouseHoverChange::Enter),
+                },
+            },
+
+            // WindowEvent::MouseMove(Point2::new(45, 10)
+            TestEvent {
+                widget: left_inner,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(5, 8),
+                    new_pos: Point2::new(30, 8),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::Exit),
+                },
+            },
+            TestEvent {
+                widget: left,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(10, 9),
+                    new_pos: Point2::new(35, 9),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::ExitChild(WidgetIdent::new_str("left_inner"))),
+                },
+            },
+            TestEvent {
+                widget: left,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(10, 9),
                     new_pos: Point2::new(35, 9),
                     in_widget: false,
                     hover_change: Some(MouseHoverChange::Exit),
@@ -849,48 +1530,37 @@ mod tests {
     }
 
     #[test]
-    fn mouse_down() {
+    fn mouse_move_hover_grace() {
         test_widget_tree!{
             let event_list = crate::test_helpers::EventList::new();
             let mut tree = root {
-                rect: (0, 0, 50, 10);
-                a { rect: (10, 0, 20, 10) },
-                b { rect: (30, 0, 40, 10) }
+                rect: (0, 0, 40, 20);
+                a { rect: (0, 0, 20, 20) },
+                b { rect: (20, 0, 40, 20) }
             };
         }
-        // rough diagram:
-        // root----a--------+-------b--------+-------+
-        // |       |        |       |        |       |
-        // |       |        |       |        |       |
-        // | root  |   a    | root  |   b    | root  |
-        // |       |        |       |        |       |
-        // |       |        |       |        |       |
-        // +-------+--------+-------+--------+-------+
 
         let a_ident = WidgetIdent::new_str("a");
         let b_ident = WidgetIdent::new_str("b");
 
         event_list.set_events(vec![
-            // WindowEvent::MouseEnter
-            // WindowEvent::MouseMove(Point2::new(0, 5))
+            // WindowEvent::MouseMove(Point2::new(0, 10))
             TestEvent {
                 widget: root,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(-1, 5),
-                    new_pos: Point2::new(0, 5),
-                    in_widget: true,
+                    old_pos: Point2::new(-1, 10),
+                    new_pos: Point2::new(0, 10),
+                    in_widget: false,
                     hover_change: Some(MouseHoverChange::Enter),
                 },
             },
-
-            // WindowEvent::MouseMove(Point2::new(15, 5))
             TestEvent {
                 widget: root,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(0, 5),
-                    new_pos: Point2::new(15, 5),
+                    old_pos: Point2::new(-1, 10),
+                    new_pos: Point2::new(0, 10),
                     in_widget: false,
                     hover_change: Some(MouseHoverChange::EnterChild(a_ident.clone())),
                 },
@@ -899,31 +1569,45 @@ mod tests {
                 widget: a,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(-10, 5),
-                    new_pos: Point2::new(5, 5),
+                    old_pos: Point2::new(-1, 10),
+                    new_pos: Point2::new(0, 10),
                     in_widget: true,
                     hover_change: Some(MouseHoverChange::Enter),
                 },
             },
 
-            // WindowEvent::MouseDown(MouseButton::Left)
+            // WindowEvent::MouseMove(Point2::new(18, 10))
             TestEvent {
                 widget: a,
                 source_child: vec![],
-                event: WidgetEvent::MouseDown {
-                    pos: Point2::new(5, 5),
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(0, 10),
+                    new_pos: Point2::new(18, 10),
                     in_widget: true,
-                    button: MouseButton::Left,
+                    hover_change: None,
                 },
             },
 
-            // WindowEvent::MouseMove(Point2::new(25, 5))
+            // WindowEvent::MouseMove(Point2::new(21, 10))
+            // The cursor is over `b`, but it hasn't moved far enough past `a` to leave it.
             TestEvent {
                 widget: a,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(5, 5),
-                    new_pos: Point2::new(15, 5),
+                    old_pos: Point2::new(18, 10),
+                    new_pos: Point2::new(21, 10),
+                    in_widget: true,
+                    hover_change: None,
+                },
+            },
+
+            // WindowEvent::MouseMove(Point2::new(24, 10))
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(21, 10),
+                    new_pos: Point2::new(24, 10),
                     in_widget: false,
                     hover_change: Some(MouseHoverChange::Exit),
                 },
@@ -932,498 +1616,482 @@ mod tests {
                 widget: root,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(15, 5),
-                    new_pos: Point2::new(25, 5),
-                    in_widget: true,
+                    old_pos: Point2::new(21, 10),
+                    new_pos: Point2::new(24, 10),
+                    in_widget: false,
                     hover_change: Some(MouseHoverChange::ExitChild(a_ident.clone())),
                 },
             },
-
-            // WindowEvent::MouseMove(Point2::new(26, 5))
             TestEvent {
                 widget: root,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(25, 5),
-                    new_pos: Point2::new(26, 5),
-                    in_widget: true,
-                    hover_change: None,
+                    old_pos: Point2::new(21, 10),
+                    new_pos: Point2::new(24, 10),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::EnterChild(b_ident.clone())),
                 },
             },
             TestEvent {
-                widget: a,
+                widget: b,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(15, 5),
-                    new_pos: Point2::new(16, 5),
-                    in_widget: false,
-                    hover_change: None,
+                    old_pos: Point2::new(1, 10),
+                    new_pos: Point2::new(4, 10),
+                    in_widget: true,
+                    hover_change: Some(MouseHoverChange::Enter),
                 },
             },
+        ]);
 
+        create_translator!(mut translator, &mut tree, root, hover_grace: 3);
 
-            // WindowEvent::MouseScrollLines(Vector2::new(0, 1))
+        translator.translate_window_event(WindowEvent::MouseEnter);
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(0, 10)));
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(18, 10)));
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(21, 10)));
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(24, 10)));
+    }
+
+    #[test]
+    fn mouse_move_pointer_transparent() {
+        test_widget_tree!{
+            let event_list = crate::test_helpers::EventList::new();
+            let mut tree = root {
+                rect: (0, 0, 40, 20);
+                overlay { rect: (0, 0, 40, 20) },
+                b { rect: (0, 0, 20, 20) }
+            };
+        }
+
+        let overlay_ident = WidgetIdent::new_str("overlay");
+        let b_ident = WidgetIdent::new_str("b");
+        tree.children.as_mut().unwrap()
+            .get_mut(&overlay_ident).unwrap()
+            .widget_tag.set_pointer_transparent(true);
+
+        // `overlay` covers the whole root, but the cursor should fall through it to `b` and to
+        // the root itself.
+        event_list.set_events(vec![
+            // WindowEvent::MouseMove(Point2::new(10, 10))
             TestEvent {
                 widget: root,
                 source_child: vec![],
-                event: WidgetEvent::MouseScrollLines {
-                    dir: Vector2::new(0, 1),
-                    in_widget: true,
-                },
-            },
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(-1, 10),
+                    new_pos: Point2::new(10, 10),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::Enter),
+                },
+            },
             TestEvent {
-                widget: a,
+                widget: root,
                 source_child: vec![],
-                event: WidgetEvent::MouseScrollLines {
-                    dir: Vector2::new(0, 1),
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(-1, 10),
+                    new_pos: Point2::new(10, 10),
                     in_widget: false,
+                    hover_change: Some(MouseHoverChange::EnterChild(b_ident.clone())),
                 },
             },
-
-            // WindowEvent::MouseScrollPx(Vector2::new(0, 1))
             TestEvent {
-                widget: root,
+                widget: b,
                 source_child: vec![],
-                event: WidgetEvent::MouseScrollPx {
-                    dir: Vector2::new(0, 1),
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(-1, 10),
+                    new_pos: Point2::new(10, 10),
                     in_widget: true,
+                    hover_change: Some(MouseHoverChange::Enter),
                 },
             },
+
+            // WindowEvent::MouseMove(Point2::new(30, 10))
             TestEvent {
-                widget: a,
+                widget: b,
                 source_child: vec![],
-                event: WidgetEvent::MouseScrollPx {
-                    dir: Vector2::new(0, 1),
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(10, 10),
+                    new_pos: Point2::new(30, 10),
                     in_widget: false,
+                    hover_change: Some(MouseHoverChange::Exit),
                 },
             },
-
-            // WindowEvent::MouseDown(MouseButton::Middle)
             TestEvent {
                 widget: root,
                 source_child: vec![],
-                event: WidgetEvent::MouseDown {
-                    pos: Point2::new(26, 5),
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(10, 10),
+                    new_pos: Point2::new(30, 10),
                     in_widget: true,
-                    button: MouseButton::Middle,
+                    hover_change: Some(MouseHoverChange::ExitChild(b_ident.clone())),
                 },
             },
+        ]);
+
+        create_translator!(mut translator, &mut tree, root);
+
+        translator.translate_window_event(WindowEvent::MouseEnter);
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(10, 10)));
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(30, 10)));
+    }
+
+    #[test]
+    fn mouse_move_popup() {
+        test_widget_tree!{
+            let event_list = crate::test_helpers::EventList::new();
+            let mut tree = root {
+                rect: (0, 0, 40, 20);
+                a { rect: (0, 0, 40, 20) },
+                popup { rect: (10, 0, 30, 20) }
+            };
+        }
+
+        let popup_ident = WidgetIdent::new_str("popup");
+        tree.children.as_mut().unwrap()
+            .get_mut(&popup_ident).unwrap()
+            .widget_tag.set_popup(true);
+
+        // `a` comes first, but `popup` lies over it and takes the cursor.
+        event_list.set_events(vec![
+            // WindowEvent::MouseMove(Point2::new(20, 10))
             TestEvent {
-                widget: a,
+                widget: root,
                 source_child: vec![],
-                event: WidgetEvent::MouseDown {
-                    pos: Point2::new(16, 5),
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(-1, 10),
+                    new_pos: Point2::new(20, 10),
                     in_widget: false,
-                    button: MouseButton::Middle,
+                    hover_change: Some(MouseHoverChange::Enter),
                 },
             },
-
-            // WindowEvent::MouseMove(Point2::new(35, 5))
             TestEvent {
                 widget: root,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(26, 5),
-                    new_pos: Point2::new(35, 5),
+                    old_pos: Point2::new(-1, 10),
+                    new_pos: Point2::new(20, 10),
                     in_widget: false,
-                    hover_change: Some(MouseHoverChange::EnterChild(b_ident.clone())),
+                    hover_change: Some(MouseHoverChange::EnterChild(popup_ident.clone())),
                 },
             },
             TestEvent {
-                widget: a,
+                widget: popup,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(16, 5),
-                    new_pos: Point2::new(25, 5),
-                    in_widget: false,
-                    hover_change: None,
+                    old_pos: Point2::new(-11, 10),
+                    new_pos: Point2::new(10, 10),
+                    in_widget: true,
+                    hover_change: Some(MouseHoverChange::Enter),
                 },
             },
+        ]);
+
+        create_translator!(mut translator, &mut tree, root);
+
+        translator.translate_window_event(WindowEvent::MouseEnter);
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(20, 10)));
+    }
+
+    #[test]
+    fn nested_offset_mouse_down_up() {
+        test_widget_tree!{
+            let event_list = crate::test_helpers::EventList::new();
+            let mut tree = root {
+                rect: (0, 0, 100, 100);
+                child {
+                    rect: (10, 10, 90, 90);
+                    grandchild {rect: (5, 5, 30, 30)}
+                }
+            };
+        }
+
+        let child_ident = WidgetIdent::new_str("child");
+        let grandchild_ident = WidgetIdent::new_str("grandchild");
+
+        // The grandchild's origin sits at (15, 15) in root space, so every position it sees should
+        // be offset by that much - including the press position reported by `MouseUp`, and the
+        // cursor position in the input state.
+        event_list.check_mouse_pos();
+        event_list.set_events(vec![
+            // WindowEvent::MouseMove(Point2::new(0, 20))
             TestEvent {
-                widget: b,
+                widget: root,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(-4, 5),
-                    new_pos: Point2::new(5, 5),
+                    old_pos: Point2::new(-1, 20),
+                    new_pos: Point2::new(0, 20),
                     in_widget: true,
                     hover_change: Some(MouseHoverChange::Enter),
                 },
             },
 
-            // WindowEvent::MouseDown(MouseButton::Right)
+            // WindowEvent::MouseMove(Point2::new(20, 20))
             TestEvent {
-                widget: b,
+                widget: root,
                 source_child: vec![],
-                event: WidgetEvent::MouseDown {
-                    pos: Point2::new(5, 5),
-                    in_widget: true,
-                    button: MouseButton::Right,
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(0, 20),
+                    new_pos: Point2::new(20, 20),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::EnterChild(child_ident.clone())),
                 },
             },
             TestEvent {
-                widget: a,
+                widget: child,
                 source_child: vec![],
-                event: WidgetEvent::MouseDown {
-                    pos: Point2::new(25, 5),
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(-10, 10),
+                    new_pos: Point2::new(10, 10),
                     in_widget: false,
-                    button: MouseButton::Right,
+                    hover_change: Some(MouseHoverChange::Enter),
                 },
             },
             TestEvent {
-                widget: root,
+                widget: child,
                 source_child: vec![],
-                event: WidgetEvent::MouseDown {
-                    pos: Point2::new(35, 5),
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(-10, 10),
+                    new_pos: Point2::new(10, 10),
                     in_widget: false,
-                    button: MouseButton::Right,
+                    hover_change: Some(MouseHoverChange::EnterChild(grandchild_ident.clone())),
                 },
             },
-
-            // WindowEvent::MouseMove(Point2::new(36, 5))
             TestEvent {
-                widget: b,
+                widget: grandchild,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(5, 5),
-                    new_pos: Point2::new(6, 5),
+                    old_pos: Point2::new(-15, 5),
+                    new_pos: Point2::new(5, 5),
                     in_widget: true,
-                    hover_change: None,
+                    hover_change: Some(MouseHoverChange::Enter),
                 },
             },
+
+            // WindowEvent::MouseDown(MouseButton::Left)
             TestEvent {
-                widget: a,
+                widget: grandchild,
                 source_child: vec![],
-                event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(25, 5),
-                    new_pos: Point2::new(26, 5),
-                    in_widget: false,
-                    hover_change: None,
+                event: WidgetEvent::MouseDown {
+                    pos: Point2::new(5, 5),
+                    in_widget: true,
+                    button: MouseButton::Left,
                 },
             },
+
+            // WindowEvent::MouseMove(Point2::new(22, 21))
             TestEvent {
-                widget: root,
+                widget: grandchild,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(35, 5),
-                    new_pos: Point2::new(36, 5),
-                    in_widget: false,
+                    old_pos: Point2::new(5, 5),
+                    new_pos: Point2::new(7, 6),
+                    in_widget: true,
                     hover_change: None,
                 },
             },
 
-            // WindowEvent::MouseUp(MouseButton::Middle)
+            // WindowEvent::MouseUp(MouseButton::Left)
             TestEvent {
-                widget: b,
+                widget: grandchild,
                 source_child: vec![],
                 event: WidgetEvent::MouseUp {
-                    down_pos: Point2::new(-4, 5),
-                    pos: Point2::new(6, 5),
+                    pos: Point2::new(7, 6),
                     in_widget: true,
-                    pressed_in_widget: false,
-                    button: MouseButton::Middle,
+                    pressed_in_widget: true,
+                    down_pos: Point2::new(5, 5),
+                    button: MouseButton::Left,
                 },
             },
             TestEvent {
-                widget: a,
+                widget: grandchild,
                 source_child: vec![],
-                event: WidgetEvent::MouseUp {
-                    down_pos: Point2::new(16, 5),
-                    pos: Point2::new(26, 5),
-                    in_widget: false,
-                    pressed_in_widget: false,
-                    button: MouseButton::Middle,
+                event: WidgetEvent::Click {
+                    pos: Point2::new(7, 6),
+                    button: MouseButton::Left,
                 },
             },
+        ]);
+
+        create_translator!(mut translator, &mut tree, root);
+
+        translator.translate_window_event(WindowEvent::MouseEnter);
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(0, 20)));
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(20, 20)));
+        translator.translate_window_event(WindowEvent::MouseDown(MouseButton::Left));
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(22, 21)));
+        translator.translate_window_event(WindowEvent::MouseUp(MouseButton::Left));
+    }
+
+    #[test]
+    fn mouse_down() {
+        test_widget_tree!{
+            let event_list = crate::test_helpers::EventList::new();
+            let mut tree = root {
+                rect: (0, 0, 50, 10);
+                a { rect: (10, 0, 20, 10) },
+                b { rect: (30, 0, 40, 10) }
+            };
+        }
+        // rough diagram:
+        // root----a--------+-------b--------+-------+
+        // |       |        |       |        |       |
+        // |       |        |       |        |       |
+        // | root  |   a    | root  |   b    | root  |
+        // |       |        |       |        |       |
+        // |       |        |       |        |       |
+        // +-------+--------+-------+--------+-------+
+
+        let a_ident = WidgetIdent::new_str("a");
+        let b_ident = WidgetIdent::new_str("b");
+
+        event_list.set_events(vec![
+            // WindowEvent::MouseEnter
+            // WindowEvent::MouseMove(Point2::new(0, 5))
             TestEvent {
                 widget: root,
                 source_child: vec![],
-                event: WidgetEvent::MouseUp {
-                    down_pos: Point2::new(26, 5),
-                    pos: Point2::new(36, 5),
-                    in_widget: false,
-                    pressed_in_widget: true,
-                    button: MouseButton::Middle,
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(-1, 5),
+                    new_pos: Point2::new(0, 5),
+                    in_widget: true,
+                    hover_change: Some(MouseHoverChange::Enter),
                 },
             },
 
-            // WindowEvent::MouseMove(Point2::new(35, 5))
+            // WindowEvent::MouseMove(Point2::new(15, 5))
             TestEvent {
-                widget: b,
+                widget: root,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(6, 5),
-                    new_pos: Point2::new(5, 5),
-                    in_widget: true,
-                    hover_change: None,
+                    old_pos: Point2::new(0, 5),
+                    new_pos: Point2::new(15, 5),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::EnterChild(a_ident.clone())),
                 },
             },
             TestEvent {
                 widget: a,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(26, 5),
-                    new_pos: Point2::new(25, 5),
-                    in_widget: false,
-                    hover_change: None,
+                    old_pos: Point2::new(-10, 5),
+                    new_pos: Point2::new(5, 5),
+                    in_widget: true,
+                    hover_change: Some(MouseHoverChange::Enter),
                 },
             },
 
-            // WindowEvent::MouseUp(MouseButton::Left)
+            // WindowEvent::MouseDown(MouseButton::Left)
             TestEvent {
-                widget: b,
+                widget: a,
                 source_child: vec![],
-                event: WidgetEvent::MouseUp {
-                    down_pos: Point2::new(-15, 5),
+                event: WidgetEvent::MouseDown {
                     pos: Point2::new(5, 5),
                     in_widget: true,
-                    pressed_in_widget: false,
                     button: MouseButton::Left,
                 },
             },
+
+            // WindowEvent::MouseMove(Point2::new(25, 5))
             TestEvent {
                 widget: a,
                 source_child: vec![],
-                event: WidgetEvent::MouseUp {
-                    down_pos: Point2::new(5, 5),
-                    pos: Point2::new(25, 5),
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(5, 5),
+                    new_pos: Point2::new(15, 5),
                     in_widget: false,
-                    pressed_in_widget: true,
-                    button: MouseButton::Left,
+                    hover_change: Some(MouseHoverChange::Exit),
                 },
             },
-
-            // WindowEvent::MouseUp(MouseButton::Right)
             TestEvent {
-                widget: b,
-                source_child: vec![],
-                event: WidgetEvent::MouseUp {
-                    down_pos: Point2::new(5, 5),
-                    pos: Point2::new(5, 5),
-                    in_widget: true,
-                    pressed_in_widget: true,
-                    button: MouseButton::Right,
-                },
-            },
-
-            // WindowEvent::MouseMove(Point2::new(36, 5))
-            TestEvent {
-                widget: b,
+                widget: root,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(5, 5),
-                    new_pos: Point2::new(6, 5),
-                    in_widget: true,
-                    hover_change: None,
-                },
-            },
-
-            // WindowEvent::MouseScrollLines(Vector2::new(0, 1))
-            TestEvent {
-                widget: b,
-                source_child: vec![],
-                event: WidgetEvent::MouseScrollLines {
-                    dir: Vector2::new(0, 1),
-                    in_widget: true,
-                },
-            },
-
-            // WindowEvent::MouseScrollPx(Vector2::new(0, 1))
-            TestEvent {
-                widget: b,
-                source_child: vec![],
-                event: WidgetEvent::MouseScrollPx {
-                    dir: Vector2::new(0, 1),
+                    old_pos: Point2::new(15, 5),
+                    new_pos: Point2::new(25, 5),
                     in_widget: true,
+                    hover_change: Some(MouseHoverChange::ExitChild(a_ident.clone())),
                 },
             },
-        ]);
-
-        create_translator!(mut translator, &mut tree, root);
-
-        translator.translate_window_event(WindowEvent::MouseEnter);
-        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(0, 5)));
-
-
-        // Move into widget `a` and press the left mouse button. Future mouse moves should send move
-        // events to widget `a`, regardless of whether or not the mouse is over the widget.
-        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(15, 5)));
-        translator.translate_window_event(WindowEvent::MouseDown(MouseButton::Left));
-
-        // Test sending move events to `a`.
-        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(25, 5)));
-        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(26, 5)));
-
-        // Mouse scroll events should be delivered to all mouse widgets.
-        translator.translate_window_event(WindowEvent::MouseScrollLines(Vector2::new(0, 1)));
-        translator.translate_window_event(WindowEvent::MouseScrollPx(Vector2::new(0, 1)));
-
-        // Press the MMB in the root widget.
-        translator.translate_window_event(WindowEvent::MouseDown(MouseButton::Middle));
-
-        // Press the RMB in the right widget.
-        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(35, 5)));
-        translator.translate_window_event(WindowEvent::MouseDown(MouseButton::Right));
-
-        // This should send mouse movement events to all three widgets.
-        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(36, 5)));
-
-        // Sends mouse up events to all widgets.
-        translator.translate_window_event(WindowEvent::MouseUp(MouseButton::Middle));
-        // Root widget should stop tracking mouse movement.
-        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(35, 5)));
-        translator.translate_window_event(WindowEvent::MouseUp(MouseButton::Left));
-        translator.translate_window_event(WindowEvent::MouseUp(MouseButton::Right));
-        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(36, 5)));
-        translator.translate_window_event(WindowEvent::MouseScrollLines(Vector2::new(0, 1)));
-        translator.translate_window_event(WindowEvent::MouseScrollPx(Vector2::new(0, 1)));
-    }
-
-    #[test]
-    fn keyboard_focus() {
-        test_widget_tree!{
-            let event_list = crate::test_helpers::EventList::new();
-            let mut tree = root {
-                rect: (0, 0, 70, 10);
-                a { rect: (10, 0, 20, 10), focus_controls: true },
-                b { rect: (30, 0, 40, 10), focus_controls: true },
-                c { rect: (50, 0, 60, 10) }
-            };
-        }
-        // rough diagram:
-        // root----a--------+-------b--------+-------c--------+-------+
-        // |       |        |       |        |       |        |       |
-        // |       |        |       |        |       |        |       |
-        // | root  |   a    | root  |   b    | root  |   c    | root  |
-        // |       |        |       |        |       |        |       |
-        // |       |        |       |        |       |        |       |
-        // +-------+--------+-------+--------+-------+--------+-------+
 
-        let a_ident = WidgetIdent::new_str("a");
-        let b_ident = WidgetIdent::new_str("b");
-        let c_ident = WidgetIdent::new_str("c");
-
-        event_list.set_events(vec![
-            // WindowEvent::MouseEnter
-            // WindowEvent::MouseMove(Point2::new(0, 5))
+            // WindowEvent::MouseMove(Point2::new(26, 5))
             TestEvent {
                 widget: root,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(-1, 5),
-                    new_pos: Point2::new(0, 5),
+                    old_pos: Point2::new(25, 5),
+                    new_pos: Point2::new(26, 5),
                     in_widget: true,
-                    hover_change: Some(MouseHoverChange::Enter),
-                },
-            },
-
-            // WindowEvent::MouseMove(Point2::new(15, 5))
-            TestEvent {
-                widget: root,
-                source_child: vec![],
-                event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(0, 5),
-                    new_pos: Point2::new(15, 5),
-                    in_widget: false,
-                    hover_change: Some(MouseHoverChange::EnterChild(a_ident.clone())),
+                    hover_change: None,
                 },
             },
             TestEvent {
                 widget: a,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(-10, 5),
-                    new_pos: Point2::new(5, 5),
-                    in_widget: true,
-                    hover_change: Some(MouseHoverChange::Enter),
+                    old_pos: Point2::new(15, 5),
+                    new_pos: Point2::new(16, 5),
+                    in_widget: false,
+                    hover_change: None,
                 },
             },
 
-            // WindowEvent::MouseDown(MouseButton::Left)
+
+            // WindowEvent::MouseScrollLines(Vector2::new(0, 1))
             TestEvent {
-                widget: a,
+                widget: root,
                 source_child: vec![],
-                event: WidgetEvent::MouseDown {
-                    pos: Point2::new(5, 5),
+                event: WidgetEvent::MouseScrollLines {
+                    dir: Vector2::new(0, 1),
                     in_widget: true,
-                    button: MouseButton::Left,
                 },
             },
             TestEvent {
                 widget: a,
                 source_child: vec![],
-                event: WidgetEvent::GainFocus(FocusSource::This, FocusChange::Take),
+                event: WidgetEvent::MouseScrollLines {
+                    dir: Vector2::new(0, 1),
+                    in_widget: false,
+                },
             },
 
-            // WindowEvent::MouseUp(MouseButton::Left)
+            // WindowEvent::MouseScrollPx(Vector2::new(0, 1))
             TestEvent {
-                widget: a,
+                widget: root,
                 source_child: vec![],
-                event: WidgetEvent::MouseUp {
-                    pos: Point2::new(5, 5),
-                    down_pos: Point2::new(5, 5),
+                event: WidgetEvent::MouseScrollPx {
+                    dir: Vector2::new(0, 1),
                     in_widget: true,
-                    pressed_in_widget: true,
-                    button: MouseButton::Left,
                 },
             },
-
-            // WindowEvent::MouseMove(Point2::new(25, 5))
             TestEvent {
                 widget: a,
                 source_child: vec![],
-                event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(5, 5),
-                    new_pos: Point2::new(15, 5),
+                event: WidgetEvent::MouseScrollPx {
+                    dir: Vector2::new(0, 1),
                     in_widget: false,
-                    hover_change: Some(MouseHoverChange::Exit),
-                },
-            },
-            TestEvent {
-                widget: root,
-                source_child: vec![],
-                event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(15, 5),
-                    new_pos: Point2::new(25, 5),
-                    in_widget: true,
-                    hover_change: Some(MouseHoverChange::ExitChild(a_ident.clone())),
                 },
             },
 
-            // WindowEvent::MouseMove(Point2::new(26, 5))
+            // WindowEvent::MouseDown(MouseButton::Middle)
             TestEvent {
                 widget: root,
                 source_child: vec![],
-                event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(25, 5),
-                    new_pos: Point2::new(26, 5),
+                event: WidgetEvent::MouseDown {
+                    pos: Point2::new(26, 5),
                     in_widget: true,
-                    hover_change: None,
+                    button: MouseButton::Middle,
                 },
             },
             TestEvent {
                 widget: a,
                 source_child: vec![],
-                event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(15, 5),
-                    new_pos: Point2::new(16, 5),
+                event: WidgetEvent::MouseDown {
+                    pos: Point2::new(16, 5),
                     in_widget: false,
-                    hover_change: None,
+                    button: MouseButton::Middle,
                 },
             },
 
-            // WindowEvent::KeyDown(Key::A)
-            TestEvent {
-                widget: a,
-                source_child: vec![],
-                event: WidgetEvent::KeyDown(Key::A, ModifierKeys::empty()),
-            },
-            // WindowEvent::KeyUp(Key::A)
-            TestEvent {
-                widget: a,
-                source_child: vec![],
-                event: WidgetEvent::KeyUp(Key::A, ModifierKeys::empty()),
-            },
-
             // WindowEvent::MouseMove(Point2::new(35, 5))
             TestEvent {
                 widget: root,
@@ -1456,14 +2124,14 @@ mod tests {
                 },
             },
 
-            // WindowEvent::MouseDown(MouseButton::Left)
+            // WindowEvent::MouseDown(MouseButton::Right)
             TestEvent {
                 widget: b,
                 source_child: vec![],
                 event: WidgetEvent::MouseDown {
                     pos: Point2::new(5, 5),
                     in_widget: true,
-                    button: MouseButton::Left,
+                    button: MouseButton::Right,
                 },
             },
             TestEvent {
@@ -1472,55 +2140,38 @@ mod tests {
                 event: WidgetEvent::MouseDown {
                     pos: Point2::new(25, 5),
                     in_widget: false,
-                    button: MouseButton::Left,
+                    button: MouseButton::Right,
                 },
             },
             TestEvent {
-                widget: a,
-                source_child: vec![],
-                event: WidgetEvent::LoseFocus,
-            },
-            TestEvent {
-                widget: b,
+                widget: root,
                 source_child: vec![],
-                event: WidgetEvent::GainFocus(FocusSource::This, FocusChange::Take),
+                event: WidgetEvent::MouseDown {
+                    pos: Point2::new(35, 5),
+                    in_widget: false,
+                    button: MouseButton::Right,
+                },
             },
 
-            // WindowEvent::MouseUp(MouseButton::Left)
+            // WindowEvent::MouseMove(Point2::new(36, 5))
             TestEvent {
                 widget: b,
                 source_child: vec![],
-                event: WidgetEvent::MouseUp {
-                    pos: Point2::new(5, 5),
-                    down_pos: Point2::new(5, 5),
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(5, 5),
+                    new_pos: Point2::new(6, 5),
                     in_widget: true,
-                    pressed_in_widget: true,
-                    button: MouseButton::Left,
+                    hover_change: None,
                 },
             },
-
-            // WindowEvent::KeyDown(Key::A)
-            TestEvent {
-                widget: b,
-                source_child: vec![],
-                event: WidgetEvent::KeyDown(Key::A, ModifierKeys::empty()),
-            },
-            // WindowEvent::KeyUp(Key::A)
-            TestEvent {
-                widget: b,
-                source_child: vec![],
-                event: WidgetEvent::KeyUp(Key::A, ModifierKeys::empty()),
-            },
-
-            // WindowEvent::MouseMove(Point2::new(55, 5))
             TestEvent {
-                widget: b,
+                widget: a,
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(5, 5),
-                    new_pos: Point2::new(25, 5),
+                    old_pos: Point2::new(25, 5),
+                    new_pos: Point2::new(26, 5),
                     in_widget: false,
-                    hover_change: Some(MouseHoverChange::Exit),
+                    hover_change: None,
                 },
             },
             TestEvent {
@@ -1528,176 +2179,1579 @@ mod tests {
                 source_child: vec![],
                 event: WidgetEvent::MouseMove {
                     old_pos: Point2::new(35, 5),
-                    new_pos: Point2::new(55, 5),
+                    new_pos: Point2::new(36, 5),
                     in_widget: false,
-                    hover_change: Some(MouseHoverChange::ExitChild(b_ident.clone())),
+                    hover_change: None,
                 },
             },
+
+            // WindowEvent::MouseUp(MouseButton::Middle)
             TestEvent {
-                widget: root,
+                widget: b,
                 source_child: vec![],
-                event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(35, 5),
-                    new_pos: Point2::new(55, 5),
+                event: WidgetEvent::MouseUp {
+                    down_pos: Point2::new(-4, 5),
+                    pos: Point2::new(6, 5),
+                    in_widget: true,
+                    pressed_in_widget: false,
+                    button: MouseButton::Middle,
+                },
+            },
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::MouseUp {
+                    down_pos: Point2::new(16, 5),
+                    pos: Point2::new(26, 5),
                     in_widget: false,
-                    hover_change: Some(MouseHoverChange::EnterChild(c_ident.clone())),
+                    pressed_in_widget: false,
+                    button: MouseButton::Middle,
                 },
             },
             TestEvent {
-                widget: c,
+                widget: root,
                 source_child: vec![],
-                event: WidgetEvent::MouseMove {
-                    old_pos: Point2::new(-15, 5),
-                    new_pos: Point2::new(5, 5),
-                    in_widget: true,
-                    hover_change: Some(MouseHoverChange::Enter),
+                event: WidgetEvent::MouseUp {
+                    down_pos: Point2::new(26, 5),
+                    pos: Point2::new(36, 5),
+                    in_widget: false,
+                    pressed_in_widget: true,
+                    button: MouseButton::Middle,
                 },
             },
 
-            // WindowEvent::MouseDown(MouseButton::Left)
+            // WindowEvent::MouseMove(Point2::new(35, 5))
             TestEvent {
-                widget: c,
+                widget: b,
                 source_child: vec![],
-                event: WidgetEvent::MouseDown {
-                    pos: Point2::new(5, 5),
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(6, 5),
+                    new_pos: Point2::new(5, 5),
                     in_widget: true,
-                    button: MouseButton::Left,
+                    hover_change: None,
                 },
             },
             TestEvent {
-                widget: b,
+                widget: a,
                 source_child: vec![],
-                event: WidgetEvent::MouseDown {
-                    pos: Point2::new(25, 5),
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(26, 5),
+                    new_pos: Point2::new(25, 5),
                     in_widget: false,
-                    button: MouseButton::Left,
+                    hover_change: None,
                 },
             },
 
             // WindowEvent::MouseUp(MouseButton::Left)
             TestEvent {
-                widget: c,
+                widget: b,
                 source_child: vec![],
                 event: WidgetEvent::MouseUp {
+                    down_pos: Point2::new(-15, 5),
                     pos: Point2::new(5, 5),
-                    down_pos: Point2::new(5, 5),
                     in_widget: true,
-                    pressed_in_widget: true,
+                    pressed_in_widget: false,
                     button: MouseButton::Left,
                 },
             },
             TestEvent {
-                widget: b,
+                widget: a,
                 source_child: vec![],
                 event: WidgetEvent::MouseUp {
+                    down_pos: Point2::new(5, 5),
                     pos: Point2::new(25, 5),
-                    down_pos: Point2::new(25, 5),
                     in_widget: false,
-                    pressed_in_widget: false,
+                    pressed_in_widget: true,
                     button: MouseButton::Left,
                 },
             },
 
-            // WindowEvent::KeyDown(Key::LArrow)
+            // WindowEvent::MouseUp(MouseButton::Right)
             TestEvent {
                 widget: b,
                 source_child: vec![],
-                event: WidgetEvent::KeyDown(Key::LArrow, ModifierKeys::empty()),
+                event: WidgetEvent::MouseUp {
+                    down_pos: Point2::new(5, 5),
+                    pos: Point2::new(5, 5),
+                    in_widget: true,
+                    pressed_in_widget: true,
+                    button: MouseButton::Right,
+                },
             },
             TestEvent {
                 widget: b,
                 source_child: vec![],
-                event: WidgetEvent::LoseFocus,
-            },
-            TestEvent {
-                widget: a,
-                source_child: vec![],
-                event: WidgetEvent::GainFocus(
-                    FocusSource::Sibling {
-                        ident: b_ident.clone(),
-                        delta: 1,
-                    },
-                    FocusChange::Prev,
-                ),
+                event: WidgetEvent::Click {
+                    pos: Point2::new(5, 5),
+                    button: MouseButton::Right,
+                },
             },
 
-            // WindowEvent::KeyUp(Key::LArrow)
+            // WindowEvent::MouseMove(Point2::new(36, 5))
             TestEvent {
-                widget: a,
+                widget: b,
                 source_child: vec![],
-                event: WidgetEvent::KeyUp(Key::LArrow, ModifierKeys::empty()),
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(5, 5),
+                    new_pos: Point2::new(6, 5),
+                    in_widget: true,
+                    hover_change: None,
+                },
             },
-            // TODO: ALWAYS DELIVER KEYUP FOR EVERY KEYDOWN
 
-            // WindowEvent::KeyDown(Key::Escape)
+            // WindowEvent::MouseScrollLines(Vector2::new(0, 1))
             TestEvent {
-                widget: a,
+                widget: b,
                 source_child: vec![],
-                event: WidgetEvent::KeyDown(Key::Escape, ModifierKeys::empty()),
+                event: WidgetEvent::MouseScrollLines {
+                    dir: Vector2::new(0, 1),
+                    in_widget: true,
+                },
             },
+
+            // WindowEvent::MouseScrollPx(Vector2::new(0, 1))
             TestEvent {
-                widget: a,
+                widget: b,
                 source_child: vec![],
-                event: WidgetEvent::LoseFocus,
+                event: WidgetEvent::MouseScrollPx {
+                    dir: Vector2::new(0, 1),
+                    in_widget: true,
+                },
             },
-
-            // WindowEvent::KeyUp(Key::Escape)
         ]);
 
         create_translator!(mut translator, &mut tree, root);
 
-        // Because no widget has keyboard focus, these events shouldn't get delivered to a widget.
-        //
-        // There should be *some* mechanism for delivering these events to the user (in the past
-        // we used a universal event fallthrough, which may be worth looking at again). This test
-        // will change when that mechanism gets implemented again.
-        //
-        // TODO: UPDATE TEST FOR UNFOCUSED KEYBOARD EVENTS
-        translator.translate_window_event(WindowEvent::KeyDown(Key::A));
-        translator.translate_window_event(WindowEvent::KeyUp(Key::A));
-
-        // Move the mouse into the window.
         translator.translate_window_event(WindowEvent::MouseEnter);
         translator.translate_window_event(WindowEvent::MouseMove(Point2::new(0, 5)));
 
-        // Move into widget `a` and click the left mouse button, delivering focus to `a`. Future
-        // mouse moves should send move events to widget `a`, regardless of whether or not the
-        // mouse is over the widget.
+
+        // Move into widget `a` and press the left mouse button. Future mouse moves should send move
+        // events to widget `a`, regardless of whether or not the mouse is over the widget.
         translator.translate_window_event(WindowEvent::MouseMove(Point2::new(15, 5)));
         translator.translate_window_event(WindowEvent::MouseDown(MouseButton::Left));
-        translator.translate_window_event(WindowEvent::MouseUp(MouseButton::Left));
 
-        // Test sending mouse move events to `a`.
+        // Test sending move events to `a`.
         translator.translate_window_event(WindowEvent::MouseMove(Point2::new(25, 5)));
         translator.translate_window_event(WindowEvent::MouseMove(Point2::new(26, 5)));
 
-        // Test sending keyboard events to `a`.
-        translator.translate_window_event(WindowEvent::KeyDown(Key::A));
-        translator.translate_window_event(WindowEvent::KeyUp(Key::A));
+        // Mouse scroll events should be delivered to all mouse widgets.
+        translator.translate_window_event(WindowEvent::MouseScrollLines(Vector2::new(0, 1)));
+        translator.translate_window_event(WindowEvent::MouseScrollPx(Vector2::new(0, 1)));
 
+        // Press the MMB in the root widget.
+        translator.translate_window_event(WindowEvent::MouseDown(MouseButton::Middle));
 
-        // This should unfocus `a` and focus `b`.
+        // Press the RMB in the right widget.
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(35, 5)));
+        translator.translate_window_event(WindowEvent::MouseDown(MouseButton::Right));
+
+        // This should send mouse movement events to all three widgets.
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(36, 5)));
+
+        // Sends mouse up events to all widgets.
+        translator.translate_window_event(WindowEvent::MouseUp(MouseButton::Middle));
+        // Root widget should stop tracking mouse movement.
         translator.translate_window_event(WindowEvent::MouseMove(Point2::new(35, 5)));
-        translator.translate_window_event(WindowEvent::MouseDown(MouseButton::Left));
         translator.translate_window_event(WindowEvent::MouseUp(MouseButton::Left));
+        translator.translate_window_event(WindowEvent::MouseUp(MouseButton::Right));
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(36, 5)));
+        translator.translate_window_event(WindowEvent::MouseScrollLines(Vector2::new(0, 1)));
+        translator.translate_window_event(WindowEvent::MouseScrollPx(Vector2::new(0, 1)));
+    }
 
-        // Test sending keyboard events to `b`.
-        translator.translate_window_event(WindowEvent::KeyDown(Key::A));
-        translator.translate_window_event(WindowEvent::KeyUp(Key::A));
+    #[test]
+    fn keyboard_focus() {
+        test_widget_tree!{
+            let event_list = crate::test_helpers::EventList::new();
+            let mut tree = root {
+                rect: (0, 0, 70, 10);
+                a { rect: (10, 0, 20, 10), focus_controls: true },
+                b { rect: (30, 0, 40, 10), focus_controls: true },
+                c { rect: (50, 0, 60, 10) }
+            };
+        }
+        // rough diagram:
+        // root----a--------+-------b--------+-------c--------+-------+
+        // |       |        |       |        |       |        |       |
+        // |       |        |       |        |       |        |       |
+        // | root  |   a    | root  |   b    | root  |   c    | root  |
+        // |       |        |       |        |       |        |       |
+        // |       |        |       |        |       |        |       |
+        // +-------+--------+-------+--------+-------+--------+-------+
 
+        let a_ident = WidgetIdent::new_str("a");
+        let b_ident = WidgetIdent::new_str("b");
+        let c_ident = WidgetIdent::new_str("c");
 
-        // Because `c` doesn't take focus, clicking on it should NOT deliver focus to it.
-        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(55, 5)));
-        translator.translate_window_event(WindowEvent::MouseDown(MouseButton::Left));
-        translator.translate_window_event(WindowEvent::MouseUp(MouseButton::Left));
+        event_list.set_events(vec![
+            // WindowEvent::MouseEnter
+            // WindowEvent::MouseMove(Point2::new(0, 5))
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(-1, 5),
+                    new_pos: Point2::new(0, 5),
+                    in_widget: true,
+                    hover_change: Some(MouseHoverChange::Enter),
+                },
+            },
 
+            // WindowEvent::MouseMove(Point2::new(15, 5))
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(0, 5),
+                    new_pos: Point2::new(15, 5),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::EnterChild(a_ident.clone())),
+                },
+            },
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(-10, 5),
+                    new_pos: Point2::new(5, 5),
+                    in_widget: true,
+                    hover_change: Some(MouseHoverChange::Enter),
+                },
+            },
 
-        // Test focusing sibling widget. Should focus `a`.
-        translator.translate_window_event(WindowEvent::KeyDown(Key::LArrow));
-        translator.translate_window_event(WindowEvent::KeyUp(Key::LArrow));
+            // WindowEvent::MouseDown(MouseButton::Left)
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::MouseDown {
+                    pos: Point2::new(5, 5),
+                    in_widget: true,
+                    button: MouseButton::Left,
+                },
+            },
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::GainFocus(FocusSource::This, FocusChange::Take),
+            },
+
+            // WindowEvent::MouseUp(MouseButton::Left)
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::MouseUp {
+                    pos: Point2::new(5, 5),
+                    down_pos: Point2::new(5, 5),
+                    in_widget: true,
+                    pressed_in_widget: true,
+                    button: MouseButton::Left,
+                },
+            },
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::Click {
+                    pos: Point2::new(5, 5),
+                    button: MouseButton::Left,
+                },
+            },
+
+            // WindowEvent::MouseMove(Point2::new(25, 5))
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(5, 5),
+                    new_pos: Point2::new(15, 5),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::Exit),
+                },
+            },
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(15, 5),
+                    new_pos: Point2::new(25, 5),
+                    in_widget: true,
+                    hover_change: Some(MouseHoverChange::ExitChild(a_ident.clone())),
+                },
+            },
+
+            // WindowEvent::MouseMove(Point2::new(26, 5))
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(25, 5),
+                    new_pos: Point2::new(26, 5),
+                    in_widget: true,
+                    hover_change: None,
+                },
+            },
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(15, 5),
+                    new_pos: Point2::new(16, 5),
+                    in_widget: false,
+                    hover_change: None,
+                },
+            },
+
+            // WindowEvent::KeyDown(Key::A)
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::KeyDown(Key::A, ModifierKeys::empty()),
+            },
+            // WindowEvent::KeyUp(Key::A)
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::KeyUp(Key::A, ModifierKeys::empty()),
+            },
+
+            // WindowEvent::MouseMove(Point2::new(35, 5))
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(26, 5),
+                    new_pos: Point2::new(35, 5),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::EnterChild(b_ident.clone())),
+                },
+            },
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(16, 5),
+                    new_pos: Point2::new(25, 5),
+                    in_widget: false,
+                    hover_change: None,
+                },
+            },
+            TestEvent {
+                widget: b,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(-4, 5),
+                    new_pos: Point2::new(5, 5),
+                    in_widget: true,
+                    hover_change: Some(MouseHoverChange::Enter),
+                },
+            },
+
+            // WindowEvent::MouseDown(MouseButton::Left)
+            TestEvent {
+                widget: b,
+                source_child: vec![],
+                event: WidgetEvent::MouseDown {
+                    pos: Point2::new(5, 5),
+                    in_widget: true,
+                    button: MouseButton::Left,
+                },
+            },
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::MouseDown {
+                    pos: Point2::new(25, 5),
+                    in_widget: false,
+                    button: MouseButton::Left,
+                },
+            },
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::LoseFocus,
+            },
+            TestEvent {
+                widget: b,
+                source_child: vec![],
+                event: WidgetEvent::GainFocus(FocusSource::This, FocusChange::Take),
+            },
+
+            // WindowEvent::MouseUp(MouseButton::Left)
+            TestEvent {
+                widget: b,
+                source_child: vec![],
+                event: WidgetEvent::MouseUp {
+                    pos: Point2::new(5, 5),
+                    down_pos: Point2::new(5, 5),
+                    in_widget: true,
+                    pressed_in_widget: true,
+                    button: MouseButton::Left,
+                },
+            },
+            TestEvent {
+                widget: b,
+                source_child: vec![],
+                event: WidgetEvent::Click {
+                    pos: Point2::new(5, 5),
+                    button: MouseButton::Left,
+                },
+            },
+
+            // WindowEvent::KeyDown(Key::A)
+            TestEvent {
+                widget: b,
+                source_child: vec![],
+                event: WidgetEvent::KeyDown(Key::A, ModifierKeys::empty()),
+            },
+            // WindowEvent::KeyUp(Key::A)
+            TestEvent {
+                widget: b,
+                source_child: vec![],
+                event: WidgetEvent::KeyUp(Key::A, ModifierKeys::empty()),
+            },
+
+            // WindowEvent::MouseMove(Point2::new(55, 5))
+            TestEvent {
+                widget: b,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(5, 5),
+                    new_pos: Point2::new(25, 5),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::Exit),
+                },
+            },
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(35, 5),
+                    new_pos: Point2::new(55, 5),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::ExitChild(b_ident.clone())),
+                },
+            },
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(35, 5),
+                    new_pos: Point2::new(55, 5),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::EnterChild(c_ident.clone())),
+                },
+            },
+            TestEvent {
+                widget: c,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(-15, 5),
+                    new_pos: Point2::new(5, 5),
+                    in_widget: true,
+                    hover_change: Some(MouseHoverChange::Enter),
+                },
+            },
+
+            // WindowEvent::MouseDown(MouseButton::Left)
+            TestEvent {
+                widget: c,
+                source_child: vec![],
+                event: WidgetEvent::MouseDown {
+                    pos: Point2::new(5, 5),
+                    in_widget: true,
+                    button: MouseButton::Left,
+                },
+            },
+            TestEvent {
+                widget: b,
+                source_child: vec![],
+                event: WidgetEvent::MouseDown {
+                    pos: Point2::new(25, 5),
+                    in_widget: false,
+                    button: MouseButton::Left,
+                },
+            },
+
+            // WindowEvent::MouseUp(MouseButton::Left)
+            TestEvent {
+                widget: c,
+                source_child: vec![],
+                event: WidgetEvent::MouseUp {
+                    pos: Point2::new(5, 5),
+                    down_pos: Point2::new(5, 5),
+                    in_widget: true,
+                    pressed_in_widget: true,
+                    button: MouseButton::Left,
+                },
+            },
+            TestEvent {
+                widget: c,
+                source_child: vec![],
+                event: WidgetEvent::Click {
+                    pos: Point2::new(5, 5),
+                    button: MouseButton::Left,
+                },
+            },
+            TestEvent {
+                widget: b,
+                source_child: vec![],
+                event: WidgetEvent::MouseUp {
+                    pos: Point2::new(25, 5),
+                    down_pos: Point2::new(25, 5),
+                    in_widget: false,
+                    pressed_in_widget: false,
+                    button: MouseButton::Left,
+                },
+            },
+
+            // WindowEvent::KeyDown(Key::LArrow)
+            TestEvent {
+                widget: b,
+                source_child: vec![],
+                event: WidgetEvent::KeyDown(Key::LArrow, ModifierKeys::empty()),
+            },
+            TestEvent {
+                widget: b,
+                source_child: vec![],
+                event: WidgetEvent::LoseFocus,
+            },
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::GainFocus(
+                    FocusSource::Sibling {
+                        ident: b_ident.clone(),
+                        delta: 1,
+                    },
+                    FocusChange::Prev,
+                ),
+            },
+
+            // WindowEvent::KeyUp(Key::LArrow)
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::KeyUp(Key::LArrow, ModifierKeys::empty()),
+            },
+            // TODO: ALWAYS DELIVER KEYUP FOR EVERY KEYDOWN
+
+            // WindowEvent::KeyDown(Key::Escape)
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::KeyDown(Key::Escape, ModifierKeys::empty()),
+            },
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::LoseFocus,
+            },
+
+            // WindowEvent::KeyUp(Key::Escape)
+        ]);
+
+        create_translator!(mut translator, &mut tree, root);
+
+        // Because no widget has keyboard focus, these events shouldn't get delivered to a widget.
+        //
+        // There should be *some* mechanism for delivering these events to the user (in the past
+        // we used a universal event fallthrough, which may be worth looking at again). This test
+        // will change when that mechanism gets implemented again.
+        //
+        // TODO: UPDATE TEST FOR UNFOCUSED KEYBOARD EVENTS
+        translator.translate_window_event(WindowEvent::KeyDown(Key::A));
+        translator.translate_window_event(WindowEvent::KeyUp(Key::A));
+
+        // Move the mouse into the window.
+        translator.translate_window_event(WindowEvent::MouseEnter);
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(0, 5)));
+
+        // Move into widget `a` and click the left mouse button, delivering focus to `a`. Future
+        // mouse moves should send move events to widget `a`, regardless of whether or not the
+        // mouse is over the widget.
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(15, 5)));
+        translator.translate_window_event(WindowEvent::MouseDown(MouseButton::Left));
+        translator.translate_window_event(WindowEvent::MouseUp(MouseButton::Left));
+
+        // Test sending mouse move events to `a`.
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(25, 5)));
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(26, 5)));
+
+        // Test sending keyboard events to `a`.
+        translator.translate_window_event(WindowEvent::KeyDown(Key::A));
+        translator.translate_window_event(WindowEvent::KeyUp(Key::A));
+
+
+        // This should unfocus `a` and focus `b`.
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(35, 5)));
+        translator.translate_window_event(WindowEvent::MouseDown(MouseButton::Left));
+        translator.translate_window_event(WindowEvent::MouseUp(MouseButton::Left));
+
+        // Test sending keyboard events to `b`.
+        translator.translate_window_event(WindowEvent::KeyDown(Key::A));
+        translator.translate_window_event(WindowEvent::KeyUp(Key::A));
+
+
+        // Because `c` doesn't take focus, clicking on it should NOT deliver focus to it.
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(55, 5)));
+        translator.translate_window_event(WindowEvent::MouseDown(MouseButton::Left));
+        translator.translate_window_event(WindowEvent::MouseUp(MouseButton::Left));
+
+
+        // Test focusing sibling widget. Should focus `a`.
+        translator.translate_window_event(WindowEvent::KeyDown(Key::LArrow));
+        translator.translate_window_event(WindowEvent::KeyUp(Key::LArrow));
 
         // Test removing keyboard focus. Should unfocus `a`.
         translator.translate_window_event(WindowEvent::KeyDown(Key::Escape));
         translator.translate_window_event(WindowEvent::KeyUp(Key::Escape));
     }
+
+    #[test]
+    fn window_focus() {
+        test_widget_tree!{
+            let event_list = crate::test_helpers::EventList::new();
+            let mut tree = root {
+                rect: (0, 0, 20, 10);
+                a { rect: (10, 0, 20, 10) }
+            };
+        }
+
+        event_list.set_events(vec![
+            // WindowEvent::MouseEnter
+            // WindowEvent::MouseMove(Point2::new(0, 5))
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(-1, 5),
+                    new_pos: Point2::new(0, 5),
+                    in_widget: true,
+                    hover_change: Some(MouseHoverChange::Enter),
+                },
+            },
+
+            // WindowEvent::MouseDown(MouseButton::Left)
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::MouseDown {
+                    pos: Point2::new(0, 5),
+                    in_widget: true,
+                    button: MouseButton::Left,
+                },
+            },
+
+            // WindowEvent::WindowFocus(false)
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::MouseUp {
+                    pos: Point2::new(0, 5),
+                    down_pos: Point2::new(0, 5),
+                    in_widget: true,
+                    pressed_in_widget: true,
+                    button: MouseButton::Left,
+                },
+            },
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::Click {
+                    pos: Point2::new(0, 5),
+                    button: MouseButton::Left,
+                },
+            },
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::WindowFocusChanged(false),
+            },
+
+            // WindowEvent::WindowFocus(true)
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::WindowFocusChanged(true),
+            },
+        ]);
+
+        create_translator!(mut translator, &mut tree, root);
+
+        translator.translate_window_event(WindowEvent::MouseEnter);
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(0, 5)));
+        translator.translate_window_event(WindowEvent::MouseDown(MouseButton::Left));
+        translator.translate_window_event(WindowEvent::KeyDown(Key::A));
+
+        // Losing focus should release the held mouse button and key.
+        translator.translate_window_event(WindowEvent::WindowFocus(false));
+        translator.translate_window_event(WindowEvent::WindowFocus(true));
+
+        // These were released when the window lost focus, so they shouldn't be delivered.
+        translator.translate_window_event(WindowEvent::MouseUp(MouseButton::Left));
+        translator.translate_window_event(WindowEvent::KeyUp(Key::A));
+    }
+
+    #[test]
+    fn click() {
+        test_widget_tree!{
+            let event_list = crate::test_helpers::EventList::new();
+            let mut tree = root {
+                rect: (0, 0, 40, 10);
+                a { rect: (10, 0, 30, 10) }
+            };
+        }
+
+        let a_ident = WidgetIdent::new_str("a");
+
+        event_list.set_events(vec![
+            // WindowEvent::MouseEnter
+            // WindowEvent::MouseMove(Point2::new(0, 5))
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(-1, 5),
+                    new_pos: Point2::new(0, 5),
+                    in_widget: true,
+                    hover_change: Some(MouseHoverChange::Enter),
+                },
+            },
+
+            // WindowEvent::MouseMove(Point2::new(12, 5))
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(0, 5),
+                    new_pos: Point2::new(12, 5),
+                    in_widget: false,
+                    hover_change: Some(MouseHoverChange::EnterChild(a_ident.clone())),
+                },
+            },
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(-10, 5),
+                    new_pos: Point2::new(2, 5),
+                    in_widget: true,
+                    hover_change: Some(MouseHoverChange::Enter),
+                },
+            },
+
+            // WindowEvent::MouseDown(MouseButton::Middle)
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::MouseDown {
+                    pos: Point2::new(2, 5),
+                    in_widget: true,
+                    button: MouseButton::Middle,
+                },
+            },
+
+            // WindowEvent::MouseMove(Point2::new(14, 5))
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(2, 5),
+                    new_pos: Point2::new(4, 5),
+                    in_widget: true,
+                    hover_change: None,
+                },
+            },
+
+            // WindowEvent::MouseUp(MouseButton::Middle)
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::MouseUp {
+                    pos: Point2::new(4, 5),
+                    down_pos: Point2::new(2, 5),
+                    in_widget: true,
+                    pressed_in_widget: true,
+                    button: MouseButton::Middle,
+                },
+            },
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::Click {
+                    pos: Point2::new(4, 5),
+                    button: MouseButton::Middle,
+                },
+            },
+
+            // WindowEvent::MouseDown(MouseButton::Middle)
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::MouseDown {
+                    pos: Point2::new(4, 5),
+                    in_widget: true,
+                    button: MouseButton::Middle,
+                },
+            },
+
+            // WindowEvent::MouseMove(Point2::new(24, 5))
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(4, 5),
+                    new_pos: Point2::new(14, 5),
+                    in_widget: true,
+                    hover_change: None,
+                },
+            },
+
+            // WindowEvent::MouseUp(MouseButton::Middle)
+            // The cursor was dragged too far, so no `Click` gets delivered.
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::MouseUp {
+                    pos: Point2::new(14, 5),
+                    down_pos: Point2::new(4, 5),
+                    in_widget: true,
+                    pressed_in_widget: true,
+                    button: MouseButton::Middle,
+                },
+            },
+        ]);
+
+        create_translator!(mut translator, &mut tree, root);
+
+        translator.translate_window_event(WindowEvent::MouseEnter);
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(0, 5)));
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(12, 5)));
+
+        translator.translate_window_event(WindowEvent::MouseDown(MouseButton::Middle));
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(14, 5)));
+        translator.translate_window_event(WindowEvent::MouseUp(MouseButton::Middle));
+
+        translator.translate_window_event(WindowEvent::MouseDown(MouseButton::Middle));
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(24, 5)));
+        translator.translate_window_event(WindowEvent::MouseUp(MouseButton::Middle));
+    }
+
+    #[test]
+    fn invert_scroll() {
+        test_widget_tree!{
+            let event_list = crate::test_helpers::EventList::new();
+            let mut tree = root {
+                rect: (0, 0, 10, 10)
+            };
+        }
+
+        event_list.set_events(vec![
+            // WindowEvent::MouseEnter
+            // WindowEvent::MouseMove(Point2::new(0, 5))
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(-1, 5),
+                    new_pos: Point2::new(0, 5),
+                    in_widget: true,
+                    hover_change: Some(MouseHoverChange::Enter),
+                },
+            },
+
+            // WindowEvent::MouseScrollLines(Vector2::new(0, 1))
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::MouseScrollLines {
+                    dir: Vector2::new(0, -1),
+                    in_widget: true,
+                },
+            },
+
+            // WindowEvent::MouseScrollPx(Vector2::new(2, -3))
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::MouseScrollPx {
+                    dir: Vector2::new(-2, 3),
+                    in_widget: true,
+                },
+            },
+        ]);
+
+        create_translator!(mut translator, &mut tree, root, invert_scroll: true);
+
+        translator.translate_window_event(WindowEvent::MouseEnter);
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(0, 5)));
+        translator.translate_window_event(WindowEvent::MouseScrollLines(Vector2::new(0, 1)));
+        translator.translate_window_event(WindowEvent::MouseScrollPx(Vector2::new(2, -3)));
+    }
+
+    #[test]
+    fn event_filter() {
+        test_widget_tree!{
+            let event_list = crate::test_helpers::EventList::new();
+            let mut tree = root {
+                rect: (0, 0, 10, 10)
+            };
+        }
+
+        event_list.set_events(vec![
+            // WindowEvent::MouseEnter
+            // WindowEvent::MouseMove(Point2::new(0, 5))
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(-1, 5),
+                    new_pos: Point2::new(0, 5),
+                    in_widget: true,
+                    hover_change: Some(MouseHoverChange::Enter),
+                },
+            },
+
+            // WindowEvent::MouseScrollLines(Vector2::new(0, 1)), replaced by the filter.
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::MouseScrollLines {
+                    dir: Vector2::new(0, 2),
+                    in_widget: true,
+                },
+            },
+
+            // WindowEvent::MouseScrollPx(Vector2::new(0, 1)) gets consumed by the filter.
+        ]);
+
+        let event_filter = Box::new(|event: &WindowEvent| match *event {
+            WindowEvent::MouseScrollLines(dir) => FilterAction::Replace(WindowEvent::MouseScrollLines(dir * 2)),
+            WindowEvent::MouseScrollPx(_) => FilterAction::Consume,
+            _ => FilterAction::Pass
+        });
+        create_translator!(mut translator, &mut tree, root, event_filter: event_filter);
+
+        translator.translate_window_event(WindowEvent::MouseEnter);
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(0, 5)));
+        translator.translate_window_event(WindowEvent::MouseScrollLines(Vector2::new(0, 1)));
+        translator.translate_window_event(WindowEvent::MouseScrollPx(Vector2::new(0, 1)));
+    }
+
+    #[test]
+    fn close_request() {
+        test_widget_tree!{
+            let event_list = crate::test_helpers::EventList::new();
+            let mut tree = root {
+                rect: (0, 0, 10, 10)
+            };
+        }
+        event_list.set_events(vec![]);
+
+        let message_bus = MessageBus::new();
+        let mut traverser_base: WidgetTraverserBase<TestRenderFrame> = WidgetTraverserBase::new(root);
+        let update_state = UpdateState::new(&message_bus);
+        let mut traverser = traverser_base.with_root_ref(&mut tree, update_state.clone());
+        let mut input_state = InputState::new();
+        let mut translator = EventTranslator::new();
+
+        // Without a handler, every request closes the window.
+        assert!(translator.confirm_close());
+
+        // Cancel the first request, and allow the second one.
+        let mut requests = 0;
+        translator.set_close_handler(Box::new(move || {
+            requests += 1;
+            match requests {
+                1 => CloseAction::Cancel,
+                _ => CloseAction::Close,
+            }
+        }));
+
+        assert!(!translator.take_close_request());
+        translator.with_data(&mut traverser, &mut input_state, update_state.clone())
+            .translate_window_event(WindowEvent::CloseRequested);
+        assert!(translator.take_close_request());
+        assert!(!translator.take_close_request());
+
+        assert!(!translator.confirm_close());
+        assert!(translator.confirm_close());
+    }
+
+    #[test]
+    fn access_key() {
+        test_widget_tree!{
+            let event_list = crate::test_helpers::EventList::new();
+            let mut tree = root {
+                rect: (0, 0, 40, 20);
+                a { rect: (0, 0, 20, 20) },
+                b { rect: (20, 0, 40, 20) }
+            };
+        }
+        tree.children.as_mut().unwrap().get_index_mut(1).unwrap().1.widget_tag.set_access_key(Some('S'));
+
+        event_list.set_events(vec![
+            // WindowEvent::KeyDown(Key::S)
+            TestEvent {
+                widget: b,
+                source_child: vec![],
+                event: WidgetEvent::AccessKey,
+            },
+        ]);
+
+        create_translator!(mut translator, &mut tree, root, modifiers: ModifierKeys::ALT);
+
+        translator.translate_window_event(WindowEvent::KeyDown(Key::S));
+        translator.translate_window_event(WindowEvent::KeyUp(Key::S));
+        // No widget has `d` as its access key.
+        translator.translate_window_event(WindowEvent::KeyDown(Key::D));
+    }
+
+    #[test]
+    fn access_key_disabled() {
+        test_widget_tree!{
+            let event_list = crate::test_helpers::EventList::new();
+            let mut tree = root {
+                rect: (0, 0, 60, 20);
+                a {
+                    rect: (0, 0, 20, 20);
+                    aa { rect: (0, 0, 20, 20) }
+                },
+                b { rect: (20, 0, 40, 20) },
+                c { rect: (40, 0, 60, 20) }
+            };
+        }
+        {
+            let children = tree.children.as_mut().unwrap();
+            let a = children.get_mut(&WidgetIdent::new_str("a")).unwrap();
+            a.widget_tag.set_enabled(false);
+            a.children.as_mut().unwrap().get_mut(&WidgetIdent::new_str("aa")).unwrap().widget_tag.set_access_key(Some('S'));
+            children.get_mut(&WidgetIdent::new_str("b")).unwrap().widget_tag.set_enabled(false).set_access_key(Some('S'));
+            children.get_mut(&WidgetIdent::new_str("c")).unwrap().widget_tag.set_access_key(Some('S'));
+        }
+
+        event_list.set_events(vec![
+            // WindowEvent::KeyDown(Key::S)
+            // `aa` is inside a disabled widget and `b` is disabled itself, so `c` gets activated.
+            TestEvent {
+                widget: c,
+                source_child: vec![],
+                event: WidgetEvent::AccessKey,
+            },
+        ]);
+
+        create_translator!(mut translator, &mut tree, root, modifiers: ModifierKeys::ALT);
+
+        translator.translate_window_event(WindowEvent::KeyDown(Key::S));
+    }
+
+    #[test]
+    fn disabled_dispatch() {
+        test_widget_tree!{
+            let event_list = crate::test_helpers::EventList::new();
+            let mut tree = root {
+                rect: (0, 0, 40, 10);
+                a {
+                    rect: (10, 0, 30, 10);
+                    aa { rect: (0, 0, 20, 10) }
+                }
+            };
+        }
+        tree.children.as_mut().unwrap().get_mut(&WidgetIdent::new_str("a")).unwrap().widget_tag.set_enabled(false);
+
+        event_list.set_events(vec![
+            // WindowEvent::MouseMove(Point2::new(0, 5))
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(-1, 5),
+                    new_pos: Point2::new(0, 5),
+                    in_widget: true,
+                    hover_change: Some(MouseHoverChange::Enter),
+                },
+            },
+
+            // WindowEvent::MouseMove(Point2::new(12, 5))
+            // `a` is disabled, so the cursor stays in `root`.
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::MouseMove {
+                    old_pos: Point2::new(0, 5),
+                    new_pos: Point2::new(12, 5),
+                    in_widget: true,
+                    hover_change: None,
+                },
+            },
+
+            // WindowEvent::MouseDown(MouseButton::Left)
+            // `aa` has focus, but doesn't hear about the press since its parent is disabled.
+            TestEvent {
+                widget: root,
+                source_child: vec![],
+                event: WidgetEvent::MouseDown {
+                    pos: Point2::new(12, 5),
+                    in_widget: true,
+                    button: MouseButton::Left,
+                },
+            },
+
+            // WindowEvent::KeyDown(Key::A) doesn't get delivered, but the matching
+            // WindowEvent::KeyUp(Key::A) does, since it ends an interaction rather than starting
+            // one.
+            TestEvent {
+                widget: aa,
+                source_child: vec![],
+                event: WidgetEvent::KeyUp(Key::A, ModifierKeys::empty()),
+            },
+        ]);
+
+        create_translator!(mut translator, &mut tree, root);
+        translator.input_state.focused_widget = Some(aa);
+
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(0, 5)));
+        translator.translate_window_event(WindowEvent::MouseMove(Point2::new(12, 5)));
+        assert_eq!(Some(root), translator.input_state.mouse_hover_widget);
+        translator.translate_window_event(WindowEvent::MouseDown(MouseButton::Left));
+        translator.translate_window_event(WindowEvent::KeyDown(Key::A));
+        translator.translate_window_event(WindowEvent::KeyUp(Key::A));
+    }
+
+    #[test]
+    fn disabled_focus() {
+        test_widget_tree!{
+            let event_list = crate::test_helpers::EventList::new();
+            let mut tree = root {
+                rect: (0, 0, 70, 10);
+                a { rect: (0, 0, 10, 10), focus_controls: true },
+                b {
+                    rect: (20, 0, 40, 10), accepts_focus: false;
+                    bb { rect: (0, 0, 10, 10), focus_controls: true }
+                },
+                c { rect: (50, 0, 60, 10), focus_controls: true }
+            };
+        }
+        tree.children.as_mut().unwrap().get_mut(&WidgetIdent::new_str("b")).unwrap().widget_tag.set_enabled(false);
+        let a_ident = WidgetIdent::new_str("a");
+        let c_ident = WidgetIdent::new_str("c");
+
+        event_list.set_events(vec![
+            // WindowEvent::KeyDown(Key::RArrow)
+            // Focus skips over `b`, without entering it.
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::KeyDown(Key::RArrow, ModifierKeys::empty()),
+            },
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::LoseFocus,
+            },
+            TestEvent {
+                widget: c,
+                source_child: vec![],
+                event: WidgetEvent::GainFocus(
+                    FocusSource::Sibling{ ident: a_ident.clone(), delta: -2 },
+                    FocusChange::Next
+                ),
+            },
+            // WindowEvent::KeyUp(Key::RArrow)
+            TestEvent {
+                widget: c,
+                source_child: vec![],
+                event: WidgetEvent::KeyUp(Key::RArrow, ModifierKeys::empty()),
+            },
+
+            // WindowEvent::KeyDown(Key::LArrow)
+            TestEvent {
+                widget: c,
+                source_child: vec![],
+                event: WidgetEvent::KeyDown(Key::LArrow, ModifierKeys::empty()),
+            },
+            TestEvent {
+                widget: c,
+                source_child: vec![],
+                event: WidgetEvent::LoseFocus,
+            },
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::GainFocus(
+                    FocusSource::Sibling{ ident: c_ident.clone(), delta: 2 },
+                    FocusChange::Prev
+                ),
+            },
+            // WindowEvent::KeyUp(Key::LArrow)
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::KeyUp(Key::LArrow, ModifierKeys::empty()),
+            },
+        ]);
+
+        create_translator!(mut translator, &mut tree, root);
+        translator.input_state.focused_widget = Some(a);
+
+        translator.translate_window_event(WindowEvent::KeyDown(Key::RArrow));
+        translator.translate_window_event(WindowEvent::KeyUp(Key::RArrow));
+        assert_eq!(Some(c), translator.input_state.focused_widget);
+        translator.translate_window_event(WindowEvent::KeyDown(Key::LArrow));
+        translator.translate_window_event(WindowEvent::KeyUp(Key::LArrow));
+        assert_eq!(Some(a), translator.input_state.focused_widget);
+    }
+
+    #[test]
+    fn focus_skips_containers() {
+        test_widget_tree!{
+            let event_list = crate::test_helpers::EventList::new();
+            let mut tree = root {
+                rect: (0, 0, 70, 10);
+                a { rect: (0, 0, 10, 10), focus_controls: true },
+                empty {
+                    rect: (20, 0, 40, 10), accepts_focus: false;
+                    ee { rect: (0, 0, 10, 10), accepts_focus: false }
+                },
+                b {
+                    rect: (50, 0, 70, 10), accepts_focus: false;
+                    bb { rect: (0, 0, 10, 10), focus_controls: true }
+                }
+            };
+        }
+
+        event_list.set_events(vec![
+            // WindowEvent::KeyDown(Key::RArrow)
+            // Neither `empty` nor `b` accept focus. `empty` doesn't have anything focusable in it,
+            // so it gets skipped entirely, while focus goes into `b` and lands on its child.
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::KeyDown(Key::RArrow, ModifierKeys::empty()),
+            },
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::LoseFocus,
+            },
+            TestEvent {
+                widget: bb,
+                source_child: vec![],
+                event: WidgetEvent::GainFocus(FocusSource::Parent, FocusChange::Next),
+            },
+            // WindowEvent::KeyUp(Key::RArrow)
+            TestEvent {
+                widget: bb,
+                source_child: vec![],
+                event: WidgetEvent::KeyUp(Key::RArrow, ModifierKeys::empty()),
+            },
+        ]);
+
+        create_translator!(mut translator, &mut tree, root);
+        translator.input_state.focused_widget = Some(a);
+
+        translator.translate_window_event(WindowEvent::KeyDown(Key::RArrow));
+        translator.translate_window_event(WindowEvent::KeyUp(Key::RArrow));
+        assert_eq!(Some(bb), translator.input_state.focused_widget);
+    }
+
+    #[test]
+    fn preferred_focus_child() {
+        test_widget_tree!{
+            let event_list = crate::test_helpers::EventList::new();
+            let mut tree = root {
+                rect: (0, 0, 90, 10);
+                a { rect: (0, 0, 10, 10), focus_controls: true },
+                b {
+                    rect: (20, 0, 70, 10), accepts_focus: false;
+                    b1 { rect: (0, 0, 10, 10), focus_controls: true },
+                    b2 { rect: (20, 0, 30, 10), focus_controls: true },
+                    b3 { rect: (40, 0, 50, 10), focus_controls: true }
+                },
+                c { rect: (80, 0, 90, 10), focus_controls: true }
+            };
+        }
+        tree.children.as_mut().unwrap().get_mut(&WidgetIdent::new_str("b")).unwrap().preferred_focus_child = Some(WidgetIdent::new_str("b2"));
+
+        event_list.set_events(vec![
+            // WindowEvent::KeyDown(Key::RArrow)
+            // Focus enters `b` at its preferred child, rather than its first child...
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::KeyDown(Key::RArrow, ModifierKeys::empty()),
+            },
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::LoseFocus,
+            },
+            TestEvent {
+                widget: b2,
+                source_child: vec![],
+                event: WidgetEvent::GainFocus(FocusSource::Parent, FocusChange::Next),
+            },
+            // WindowEvent::KeyUp(Key::RArrow)
+            TestEvent {
+                widget: b2,
+                source_child: vec![],
+                event: WidgetEvent::KeyUp(Key::RArrow, ModifierKeys::empty()),
+            },
+
+            // WindowEvent::KeyDown(Key::LArrow)
+            // ...and at its preferred child rather than its last child when moving backwards.
+            TestEvent {
+                widget: c,
+                source_child: vec![],
+                event: WidgetEvent::KeyDown(Key::LArrow, ModifierKeys::empty()),
+            },
+            TestEvent {
+                widget: c,
+                source_child: vec![],
+                event: WidgetEvent::LoseFocus,
+            },
+            TestEvent {
+                widget: b2,
+                source_child: vec![],
+                event: WidgetEvent::GainFocus(FocusSource::Parent, FocusChange::Prev),
+            },
+            // WindowEvent::KeyUp(Key::LArrow)
+            TestEvent {
+                widget: b2,
+                source_child: vec![],
+                event: WidgetEvent::KeyUp(Key::LArrow, ModifierKeys::empty()),
+            },
+        ]);
+
+        create_translator!(mut translator, &mut tree, root);
+        translator.input_state.focused_widget = Some(a);
+
+        translator.translate_window_event(WindowEvent::KeyDown(Key::RArrow));
+        translator.translate_window_event(WindowEvent::KeyUp(Key::RArrow));
+        assert_eq!(Some(b2), translator.input_state.focused_widget);
+
+        translator.input_state.focused_widget = Some(c);
+        translator.translate_window_event(WindowEvent::KeyDown(Key::LArrow));
+        translator.translate_window_event(WindowEvent::KeyUp(Key::LArrow));
+        assert_eq!(Some(b2), translator.input_state.focused_widget);
+    }
+
+    #[test]
+    fn preferred_focus_child_fallback() {
+        test_widget_tree!{
+            let event_list = crate::test_helpers::EventList::new();
+            let mut tree = root {
+                rect: (0, 0, 70, 10);
+                a { rect: (0, 0, 10, 10), focus_controls: true },
+                b {
+                    rect: (20, 0, 70, 10), accepts_focus: false;
+                    b1 { rect: (0, 0, 10, 10), focus_controls: true },
+                    b2 { rect: (20, 0, 30, 10), accepts_focus: false }
+                }
+            };
+        }
+        tree.children.as_mut().unwrap().get_mut(&WidgetIdent::new_str("b")).unwrap().preferred_focus_child = Some(WidgetIdent::new_str("b2"));
+
+        event_list.set_events(vec![
+            // WindowEvent::KeyDown(Key::RArrow)
+            // `b2` can't take focus, so focus goes to `b`'s first focusable child instead.
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::KeyDown(Key::RArrow, ModifierKeys::empty()),
+            },
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::LoseFocus,
+            },
+            TestEvent {
+                widget: b1,
+                source_child: vec![],
+                event: WidgetEvent::GainFocus(FocusSource::Parent, FocusChange::Next),
+            },
+            // WindowEvent::KeyUp(Key::RArrow)
+            TestEvent {
+                widget: b1,
+                source_child: vec![],
+                event: WidgetEvent::KeyUp(Key::RArrow, ModifierKeys::empty()),
+            },
+        ]);
+
+        create_translator!(mut translator, &mut tree, root);
+        translator.input_state.focused_widget = Some(a);
+
+        translator.translate_window_event(WindowEvent::KeyDown(Key::RArrow));
+        translator.translate_window_event(WindowEvent::KeyUp(Key::RArrow));
+        assert_eq!(Some(b1), translator.input_state.focused_widget);
+    }
+
+    #[test]
+    fn focus_scope() {
+        test_widget_tree!{
+            let event_list = crate::test_helpers::EventList::new();
+            let mut tree = root {
+                rect: (0, 0, 70, 10);
+                a { rect: (0, 0, 10, 10), focus_controls: true },
+                scope {
+                    rect: (20, 0, 70, 10), accepts_focus: false;
+                    s1 { rect: (0, 0, 10, 10), focus_controls: true },
+                    s2 { rect: (20, 0, 30, 10), focus_controls: true }
+                }
+            };
+        }
+        let s1_ident = WidgetIdent::new_str("s1");
+        let s2_ident = WidgetIdent::new_str("s2");
+
+        event_list.set_events(vec![
+            // WindowEvent::KeyDown(Key::RArrow)
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::KeyDown(Key::RArrow, ModifierKeys::empty()),
+            },
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::LoseFocus,
+            },
+            TestEvent {
+                widget: s1,
+                source_child: vec![],
+                event: WidgetEvent::GainFocus(FocusSource::Parent, FocusChange::Next),
+            },
+            // WindowEvent::KeyUp(Key::RArrow)
+            TestEvent {
+                widget: s1,
+                source_child: vec![],
+                event: WidgetEvent::KeyUp(Key::RArrow, ModifierKeys::empty()),
+            },
+
+            // WindowEvent::KeyDown(Key::RArrow)
+            TestEvent {
+                widget: s1,
+                source_child: vec![],
+                event: WidgetEvent::KeyDown(Key::RArrow, ModifierKeys::empty()),
+            },
+            TestEvent {
+                widget: s1,
+                source_child: vec![],
+                event: WidgetEvent::LoseFocus,
+            },
+            TestEvent {
+                widget: s2,
+                source_child: vec![],
+                event: WidgetEvent::GainFocus(
+                    FocusSource::Sibling{ ident: s1_ident.clone(), delta: -1 },
+                    FocusChange::Next
+                ),
+            },
+            // WindowEvent::KeyUp(Key::RArrow)
+            TestEvent {
+                widget: s2,
+                source_child: vec![],
+                event: WidgetEvent::KeyUp(Key::RArrow, ModifierKeys::empty()),
+            },
+
+            // WindowEvent::KeyDown(Key::RArrow)
+            TestEvent {
+                widget: s2,
+                source_child: vec![],
+                event: WidgetEvent::KeyDown(Key::RArrow, ModifierKeys::empty()),
+            },
+            TestEvent {
+                widget: s2,
+                source_child: vec![],
+                event: WidgetEvent::LoseFocus,
+            },
+            TestEvent {
+                widget: s1,
+                source_child: vec![],
+                event: WidgetEvent::GainFocus(
+                    FocusSource::Sibling{ ident: s2_ident.clone(), delta: -1 },
+                    FocusChange::Next
+                ),
+            },
+            // WindowEvent::KeyUp(Key::RArrow)
+            TestEvent {
+                widget: s1,
+                source_child: vec![],
+                event: WidgetEvent::KeyUp(Key::RArrow, ModifierKeys::empty()),
+            },
+
+            // WindowEvent::KeyDown(Key::LArrow)
+            TestEvent {
+                widget: s1,
+                source_child: vec![],
+                event: WidgetEvent::KeyDown(Key::LArrow, ModifierKeys::empty()),
+            },
+            TestEvent {
+                widget: s1,
+                source_child: vec![],
+                event: WidgetEvent::LoseFocus,
+            },
+            TestEvent {
+                widget: s2,
+                source_child: vec![],
+                event: WidgetEvent::GainFocus(
+                    FocusSource::Sibling{ ident: s1_ident.clone(), delta: 1 },
+                    FocusChange::Prev
+                ),
+            },
+            // WindowEvent::KeyUp(Key::LArrow)
+            TestEvent {
+                widget: s2,
+                source_child: vec![],
+                event: WidgetEvent::KeyUp(Key::LArrow, ModifierKeys::empty()),
+            },
+
+            // WindowEvent::Timer, after popping the scope
+            TestEvent {
+                widget: s2,
+                source_child: vec![],
+                event: WidgetEvent::LoseFocus,
+            },
+            TestEvent {
+                widget: a,
+                source_child: vec![],
+                event: WidgetEvent::GainFocus(FocusSource::This, FocusChange::Take),
+            },
+        ]);
+
+        let message_bus = MessageBus::new();
+        let update_state = UpdateState::new(&message_bus);
+        {
+            let scope_widget = tree.children.as_mut().unwrap().get_mut(&WidgetIdent::new_str("scope")).unwrap();
+            scope_widget.widget_tag.set_owning_update_state(&update_state);
+            scope_widget.widget_tag.push_focus_scope().unwrap();
+        }
+        let mut traverser_base: WidgetTraverserBase<TestRenderFrame> = WidgetTraverserBase::new(root);
+        let mut traverser = traverser_base.with_root_ref(&mut tree, update_state.clone());
+        let mut input_state = InputState::new();
+        input_state.focused_widget = Some(a);
+
+        let mut event_translator = EventTranslator::new();
+        let mut translator = event_translator.with_data(&mut traverser, &mut input_state, update_state.clone());
+
+        // The scope gets pushed at the start of the next event, remembering that `a` had focus.
+        // Moving focus into the scope enters its first child, since the scope itself doesn't
+        // accept focus.
+        translator.translate_window_event(WindowEvent::KeyDown(Key::RArrow));
+        translator.translate_window_event(WindowEvent::KeyUp(Key::RArrow));
+        assert_eq!(1, translator.input_state.focus_scopes.len());
+
+        // Focus wraps around within the scope in both directions, rather than escaping it.
+        translator.translate_window_event(WindowEvent::KeyDown(Key::RArrow));
+        translator.translate_window_event(WindowEvent::KeyUp(Key::RArrow));
+        translator.translate_window_event(WindowEvent::KeyDown(Key::RArrow));
+        translator.translate_window_event(WindowEvent::KeyUp(Key::RArrow));
+        translator.translate_window_event(WindowEvent::KeyDown(Key::LArrow));
+        translator.translate_window_event(WindowEvent::KeyUp(Key::LArrow));
+
+        // Popping the scope gives focus back to `a`.
+        update_state.borrow_mut().focus_scope_ops.push(FocusScopeOp::Pop(scope));
+        translator.translate_window_event(WindowEvent::Timer);
+        assert_eq!(0, translator.input_state.focus_scopes.len());
+        assert_eq!(Some(a), translator.input_state.focused_widget);
+    }
 }
@@ -59,27 +59,29 @@ impl EventDispatcher {
         )
     }
 
+    /// Deliver every queued event, along with any events queued while delivering them. `f` gets
+    /// told whether the widget an event is being delivered to is enabled.
     pub fn dispatch_events<R>(
         &mut self,
         widget_traverser: &mut WidgetTraverser<R>,
-        mut f: impl FnMut(&mut Self, OffsetWidgetScanPath<R>, DispatchableEvent)
+        mut f: impl FnMut(&mut Self, OffsetWidgetScanPath<R>, bool, DispatchableEvent)
     )
         where R: Renderer
     {
         while let Some((destination, event)) = self.events.pop_front() {
-            let widget_opt = {
+            let widget_id = {
                 use self::EventDestination::*;
                 match destination {
-                    Relation(id, relation) => widget_traverser.get_widget_relation(id, relation),
-                    Widget(id) => widget_traverser.get_widget(id)
+                    Relation(id, relation) => widget_traverser.relation_id(id, relation),
+                    Widget(id) => Some(id)
                 }
             };
 
-            let widget = match widget_opt {
+            let (widget, enabled) = match widget_id.and_then(|id| widget_traverser.get_widget_enabled(id)) {
                 Some(w) => w,
                 None => continue //TODO: LOG WARNING
             };
-            f(self, widget, event);
+            f(self, widget, enabled, event);
         }
     }
 }
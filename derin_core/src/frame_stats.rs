@@ -0,0 +1,44 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Timing and workload statistics for the most recent frame.
+//!
+//! Only available with the `frame-stats` feature. Without it, none of the instrumentation gets
+//! compiled in.
+
+use std::time::Duration;
+
+/// Statistics collected during the last calls to `Root::relayout` and `Root::redraw`.
+///
+/// The layout statistics get replaced on every `relayout`, and the render statistics on every
+/// `redraw`. If `redraw` was called when nothing needed to be drawn, the render statistics are
+/// all zero.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FrameStats {
+    /// The time spent laying out widgets.
+    pub layout_time: Duration,
+    /// The number of widgets that had `update_layout` called on them. Widgets that get
+    /// re-laid out several times in one pass are counted each time.
+    pub widgets_laid_out: usize,
+    /// The time spent drawing widgets, including the renderer's `start_frame` and `finish_frame`.
+    pub render_time: Duration,
+    /// The number of widgets visited while drawing.
+    pub widgets_rendered: usize,
+    /// The number of visited widgets that had their primitives from the previous frame replayed
+    /// instead of being rendered again. See `Root::set_retained_rendering`.
+    pub widgets_replayed: usize,
+    /// The number of widgets that requested a redraw, or `None` if the whole tree was redrawn.
+    pub dirty_widgets: Option<usize>,
+    /// The number of draw calls the renderer issued, if it keeps track of them. See
+    /// `Renderer::draw_call_count`.
+    pub draw_calls: Option<usize>,
+}
+
+impl FrameStats {
+    /// The total time spent laying out and drawing the frame.
+    #[inline]
+    pub fn frame_time(&self) -> Duration {
+        self.layout_time + self.render_time
+    }
+}
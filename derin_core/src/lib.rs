@@ -3,9 +3,13 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 #![feature(range_contains, nll, specialization, try_blocks, never_type)]
+#![cfg_attr(test, feature(test))]
 
 use cgmath_geometry::cgmath;
 extern crate derin_common_types;
+#[cfg(feature = "serde")]
+#[cfg_attr(feature = "serde", macro_use)]
+extern crate serde;
 
 #[macro_use]
 mod macros;
@@ -14,10 +18,14 @@ mod macros;
 #[macro_use]
 pub mod test_helpers;
 
+pub mod accessibility;
+#[cfg(feature = "frame-stats")]
+pub mod frame_stats;
 pub mod timer;
 #[macro_use]
 pub mod event;
 pub mod render;
+pub mod ui_state;
 pub mod widget;
 
 mod mbseq;
@@ -31,6 +39,7 @@ use crate::cgmath::{Point2, Vector2, Bounded, EuclideanSpace};
 use cgmath_geometry::{D2, rect::{DimsBox, BoundBox, GeoBox}};
 
 use crate::{
+    accessibility::{AccessibilityTreeNode, TreeBuilder},
     message_bus::{MessageBus, MessageTarget},
     event::{WidgetEvent, WidgetEventSourced},
     event_translator::EventTranslator,
@@ -39,9 +48,10 @@ use crate::{
         *,
         dynamic::{RenderError, RenderParameters},
     },
-    render::{Renderer},
+    render::{Renderer, ReadPixels, Image, DebugOverlayItem},
+    ui_state::UiState,
     mbseq::MouseButtonSequenceTrackPos,
-    update_state::{UpdateState, UpdateStateCell},
+    update_state::{Deferred, UpdateState, UpdateStateCell},
     widget_traverser::{Relation, WidgetPath, WidgetTraverser, WidgetTraverserBase},
 };
 use derin_common_types::{
@@ -49,7 +59,10 @@ use derin_common_types::{
     cursor::CursorIcon,
     layout::SizeBounds,
 };
+use fnv::{FnvHashMap, FnvHashSet};
 use std::{
+    any::Any,
+    mem,
     rc::Rc,
     time::Instant,
 };
@@ -80,22 +93,66 @@ pub struct Root<N, R>
     message_bus: MessageBus,
     update_state: Rc<UpdateStateCell>,
 
+    /// The widgets the debug overlay last highlighted, or `None` if the overlay is disabled.
+    debug_overlay: Option<OverlayTargets>,
+
+    retained_rendering: bool,
+    /// The parameters every widget was drawn with in the last frame, used to decide whether the
+    /// renderer can replay the widget's primitives instead of rendering it again.
+    retained_renders: FnvHashMap<WidgetId, RetainedRender>,
+
+    /// State passed to `restore_ui_state` that hasn't found its widget yet.
+    pending_ui_state: UiState,
+    /// The tree generation `pending_ui_state` was last restored against.
+    pending_ui_state_generation: u64,
+
+    #[cfg(feature = "frame-stats")]
+    frame_stats: frame_stats::FrameStats,
+
     // User data
     pub root_widget: N,
     pub theme: R::Theme,
     pub renderer: R,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RetainedRender {
+    transform: BoundBox<D2, i32>,
+    clip: BoundBox<D2, i32>,
+    disabled: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct OverlayTargets {
+    hover: Option<WidgetId>,
+    focus: Option<WidgetId>,
+}
+
 struct InputState {
+    /// The last known cursor position. Stays set while the cursor is outside of the window if any
+    /// mouse buttons are held, so that drags can continue.
     mouse_pos: Option<Point2<i32>>,
+    /// Whether the cursor is actually inside of the window.
+    mouse_in_window: bool,
     mouse_buttons_down: MouseButtonSequenceTrackPos,
     modifiers: ModifierKeys,
     keys_down: Vec<Key>,
     mouse_hover_widget: Option<WidgetId>,
-    focused_widget: Option<WidgetId>
+    focused_widget: Option<WidgetId>,
+    /// The active focus scopes, with the innermost scope last.
+    focus_scopes: Vec<FocusScope>,
+    scale_factor: f64,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FocusScope {
+    scope: WidgetId,
+    /// The widget that had focus when the scope was pushed, which gets focus back when the scope
+    /// is popped.
+    restore_focus: Option<WidgetId>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum WindowEvent {
     MouseMove(Point2<i32>),
     MouseEnter,
@@ -108,10 +165,53 @@ pub enum WindowEvent {
     KeyDown(Key),
     KeyUp(Key),
     Char(char),
+    /// The window has gained (`true`) or lost (`false`) focus to another application.
+    WindowFocus(bool),
+    /// The ratio between physical pixels and the logical pixels widgets are laid out in has
+    /// changed, usually because the window moved to a monitor with a different DPI.
+    ///
+    /// The window's logical size should be kept the same, so the window's physical size changes
+    /// along with the scale factor.
+    ScaleFactorChanged(f64),
+    /// The user asked to close the window, such as by clicking its close button.
+    ///
+    /// This doesn't close the window by itself. The request is passed to the close handler set
+    /// with `Root::set_close_handler`, and `EventLoopResult::close` is set if the handler lets the
+    /// window close. Without a handler, the window closes immediately.
+    CloseRequested,
     Timer,
     Redraw
 }
 
+/// What an event filter does with a window event.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FilterAction {
+    /// Deliver the event to the widget tree as usual.
+    Pass,
+    /// Drop the event, so it never reaches the widget tree.
+    Consume,
+    /// Deliver the given event instead of the original.
+    Replace(WindowEvent),
+}
+
+/// A function that sees every window event before it gets translated and delivered to widgets.
+/// See `Root::set_event_filter`.
+pub type EventFilter = Box<FnMut(&WindowEvent) -> FilterAction>;
+
+/// Whether a close request is allowed to close the window. Returned by the close handler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CloseAction {
+    /// Close the window.
+    Close,
+    /// Keep the window open. To close the window later, such as after the user has confirmed
+    /// they want to discard their changes, request the close again.
+    Cancel,
+}
+
+/// A function that decides whether a close request closes the window. See
+/// `Root::set_close_handler`.
+pub type CloseHandler = Box<FnMut() -> CloseAction>;
+
 /// Whether to continue or abort a loop.
 #[must_use]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -140,17 +240,32 @@ pub struct EventLoopResult {
     pub next_timer: Option<Instant>,
     pub set_cursor_pos: Option<Point2<i32>>,
     pub set_cursor_icon: Option<CursorIcon>,
+    /// The title a widget requested the window be given with `WidgetTag::set_window_title`.
+    pub set_window_title: Option<String>,
+    /// Whether the window should be closed, because a close request was made and the close
+    /// handler didn't cancel it.
+    pub close: bool,
 }
 
 impl InputState {
     fn new() -> InputState {
         InputState {
             mouse_pos: None,
+            mouse_in_window: false,
             mouse_buttons_down: MouseButtonSequenceTrackPos::new(),
             modifiers: ModifierKeys::empty(),
             keys_down: Vec::new(),
             mouse_hover_widget: None,
-            focused_widget: None
+            focused_widget: None,
+            focus_scopes: Vec::new(),
+            scale_factor: 1.0,
+        }
+    }
+
+    fn overlay_targets(&self) -> OverlayTargets {
+        OverlayTargets {
+            hover: self.mouse_hover_widget,
+            focus: self.focused_widget,
         }
     }
 }
@@ -175,6 +290,17 @@ impl<N, R> Root<N, R>
             update_state: UpdateState::new(&message_bus),
             message_bus,
 
+            debug_overlay: None,
+
+            retained_rendering: false,
+            retained_renders: FnvHashMap::default(),
+
+            pending_ui_state: UiState::new(),
+            pending_ui_state_generation: 0,
+
+            #[cfg(feature = "frame-stats")]
+            frame_stats: frame_stats::FrameStats::default(),
+
             root_widget, theme, renderer,
         }
     }
@@ -190,15 +316,297 @@ impl<N, R> Root<N, R>
         }
     }
 
+    /// Replace the theme, returning the old one.
+    ///
+    /// Every widget gets notified of the change through `WidgetRenderable::on_theme_changed`, and
+    /// the whole tree gets re-laid out and redrawn. Assigning to the `theme` field directly skips
+    /// the notification, so widgets may keep using data derived from the old theme.
+    pub fn set_theme(&mut self, theme: R::Theme) -> R::Theme {
+        let old_theme = mem::replace(&mut self.theme, theme);
+
+        let Root {
+            ref mut widget_traverser_base,
+            ref mut root_widget,
+            ref update_state,
+            ref theme,
+            ..
+        } = *self;
+        let mut widget_traverser = widget_traverser_base.with_root_ref(root_widget, update_state.clone());
+        widget_traverser.crawl_widgets(|mut path| path.widget.inner_mut().on_theme_changed(theme));
+        update_state.borrow_mut().queue_global_update();
+
+        old_theme
+    }
+
+    /// The number of pixels the cursor can move past the edge of the hovered widget before
+    /// another widget becomes the hover target.
+    #[inline]
+    pub fn hover_grace(&self) -> u32 {
+        self.event_translator.hover_grace()
+    }
+
+    /// Set the hover grace distance, which keeps the hover target from rapidly switching between
+    /// two widgets when the cursor rests on the border between them. Defaults to `0`, which
+    /// disables the grace.
+    #[inline]
+    pub fn set_hover_grace(&mut self, hover_grace: u32) {
+        self.event_translator.set_hover_grace(hover_grace);
+    }
+
+    /// Whether or not scroll directions get inverted before being delivered to widgets.
+    #[inline]
+    pub fn invert_scroll(&self) -> bool {
+        self.event_translator.invert_scroll()
+    }
+
+    /// Invert the direction of every scroll event, for users that prefer "natural" scrolling.
+    ///
+    /// Defaults to `false`. Platforms that have their own natural scrolling setting already apply
+    /// it to the scroll deltas they report, so this should only be enabled to override that.
+    /// Since the inversion happens before the events are dispatched, widgets that convert line
+    /// deltas to pixels see a consistent direction for both kinds of scroll event.
+    #[inline]
+    pub fn set_invert_scroll(&mut self, invert_scroll: bool) {
+        self.event_translator.set_invert_scroll(invert_scroll);
+    }
+
+    /// Set a filter that observes every window event before it's translated into widget events,
+    /// returning the previous filter.
+    ///
+    /// The filter can let the event through, consume it, or replace it with a different event.
+    /// This is useful for input logging, macro recording, and app-wide gestures. Events the
+    /// translator generates internally, like releasing held buttons when the window loses focus,
+    /// don't go through the filter.
+    pub fn set_event_filter(&mut self, event_filter: EventFilter) -> Option<EventFilter> {
+        self.event_translator.set_event_filter(event_filter)
+    }
+
+    /// Remove the event filter, returning it.
+    pub fn remove_event_filter(&mut self) -> Option<EventFilter> {
+        self.event_translator.remove_event_filter()
+    }
+
+    /// Set the function that decides whether requests to close the window are carried out,
+    /// returning the previous handler.
+    ///
+    /// The handler gets called at most once per frame, when `WindowEvent::CloseRequested` has been
+    /// processed or a widget has called `WidgetTag::request_close_window`. It can return
+    /// `CloseAction::Cancel` to keep the window open, for example to ask the user whether to save
+    /// their changes first. Without a handler, every close request closes the window.
+    pub fn set_close_handler(&mut self, close_handler: CloseHandler) -> Option<CloseHandler> {
+        self.event_translator.set_close_handler(close_handler)
+    }
+
+    /// Remove the close handler, returning it.
+    pub fn remove_close_handler(&mut self) -> Option<CloseHandler> {
+        self.event_translator.remove_close_handler()
+    }
+
+    /// Take every action that widgets have queued with `WidgetTag::send_action`, in the order they
+    /// were queued.
+    ///
+    /// Root doesn't do anything with actions itself, so the application's event loop should drain
+    /// them after finishing each frame. Actions that never get taken stay queued indefinitely.
+    /// Actions sent while the tree is being laid out or redrawn get picked up alongside the next
+    /// frame's.
+    pub fn take_actions(&mut self) -> impl Iterator<Item=Box<Any>> {
+        mem::replace(&mut self.update_state.borrow_mut().actions, Vec::new()).into_iter()
+    }
+
+    /// Release memory held by the widget tree's bookkeeping that isn't being used anymore.
+    ///
+    /// Removing widgets only releases the memory at the end of a child list. Compacting also
+    /// removes the gaps in the middle of child lists, which shifts the children after each gap
+    /// down, so lookups by child index may miss until the tree gets rescanned. Applications that
+    /// insert and remove large numbers of widgets may want to call this periodically.
+    pub fn compact_widget_tree(&mut self) {
+        self.widget_traverser_base.compact_tree();
+    }
+
+    /// Stop firing widget timers, without removing them.
+    ///
+    /// Useful for saving resources while the window is minimized. Timers keep however long they
+    /// had left until their next trigger, and pick up from there when `resume_timers` is called.
+    pub fn pause_timers(&mut self) {
+        self.timer_tracker.pause();
+    }
+
+    /// Resume firing widget timers after a call to `pause_timers`.
+    pub fn resume_timers(&mut self) {
+        let mut widget_traverser = self.widget_traverser_base.with_root_ref(&mut self.root_widget, self.update_state.clone());
+        resume_timers(&mut self.timer_tracker, &mut widget_traverser);
+    }
+
+    /// Whether widget timers are currently paused.
+    #[inline]
+    pub fn timers_paused(&self) -> bool {
+        self.timer_tracker.is_paused()
+    }
+
+    /// Whether timers get paused when the window loses focus.
+    #[inline]
+    pub fn pause_timers_when_unfocused(&self) -> bool {
+        self.timer_tracker.pause_when_unfocused()
+    }
+
+    /// Automatically pause timers when a `WindowEvent::WindowFocus(false)` event is processed,
+    /// and resume them on `WindowEvent::WindowFocus(true)`. Defaults to `false`.
+    ///
+    /// Regaining focus resumes timers even if they were paused manually.
+    #[inline]
+    pub fn set_pause_timers_when_unfocused(&mut self, pause_timers_when_unfocused: bool) {
+        self.timer_tracker.set_pause_when_unfocused(pause_timers_when_unfocused);
+    }
+
+    /// Assemble the descriptions given by `Widget::accessibility_info` into a tree that mirrors
+    /// the widget tree.
+    ///
+    /// Every widget gets a node in the tree, even if it doesn't describe itself, so that the tree
+    /// keeps the same shape as the widget tree.
+    pub fn accessibility_tree(&mut self) -> AccessibilityTreeNode {
+        let mut builder = TreeBuilder::new();
+        let mut widget_traverser = self.widget_traverser_base.with_root_ref(&mut self.root_widget, self.update_state.clone());
+        widget_traverser.crawl_widgets(|path| {
+            let widget_tag = path.widget.widget_tag();
+            let mut node = path.widget.inner().accessibility_info();
+            if let Some(ref mut node) = node {
+                node.state.focused = widget_tag.has_keyboard_focus();
+                node.state.disabled |= !widget_tag.is_enabled();
+            }
+
+            builder.push(path.path.len(), AccessibilityTreeNode {
+                widget_id: path.widget_id,
+                ident: path.path.last().cloned().unwrap(),
+                rect: path.widget.rect(),
+                node,
+                children: Vec::new(),
+            });
+        });
+        builder.finish().expect("root widget wasn't crawled")
+    }
+
+    /// Get the path of idents leading from the root widget to the widget with the given ID, or
+    /// `None` if the widget isn't in the tree. The path for the root widget is empty.
+    pub fn widget_path_key(&mut self, widget_id: WidgetId) -> Option<WidgetPathKey> {
+        let mut widget_traverser = self.widget_traverser_base.with_root_ref(&mut self.root_widget, self.update_state.clone());
+        let path = widget_traverser.get_widget(widget_id)?;
+        Some(path.path[1..].into())
+    }
+
+    /// Collect the state saved by every widget's `Widget::save_state` into a `UiState`, keyed by
+    /// the widgets' ident paths.
+    ///
+    /// State passed to `restore_ui_state` that hasn't been restored yet, because no widget has
+    /// appeared at its path, is included as well.
+    pub fn save_ui_state(&mut self) -> UiState {
+        let mut ui_state = self.pending_ui_state.clone();
+        let mut widget_traverser = self.widget_traverser_base.with_root_ref(&mut self.root_widget, self.update_state.clone());
+        widget_traverser.crawl_widgets(|path| {
+            if let Some(value) = path.widget.inner().save_state() {
+                ui_state.insert(path.path[1..].into(), value);
+            }
+        });
+        ui_state
+    }
+
+    /// Hand the state in `ui_state` back to the widgets at the paths it was saved under, through
+    /// `Widget::restore_state`.
+    ///
+    /// Widgets that currently exist get restored immediately. The state for paths without a
+    /// widget is held onto, and gets restored when a widget first appears at the path. Calling
+    /// this again discards any state that's still held onto from the last call.
+    pub fn restore_ui_state(&mut self, ui_state: UiState) {
+        self.pending_ui_state = ui_state;
+        let mut widget_traverser = self.widget_traverser_base.with_root_ref(&mut self.root_widget, self.update_state.clone());
+        restore_ui_state(&mut self.pending_ui_state, &mut widget_traverser);
+        self.pending_ui_state_generation = widget_traverser.tree_generation();
+    }
+
+    /// Whether the debug overlay is being drawn.
+    #[inline]
+    pub fn debug_overlay(&self) -> bool {
+        self.debug_overlay.is_some()
+    }
+
+    /// Draw a debug overlay over each frame, outlining every widget's rect along with its ident and
+    /// ID, and highlighting the widgets with mouse hover and keyboard focus. Defaults to `false`.
+    ///
+    /// This is meant for diagnosing layout and hit-testing problems during development. The
+    /// renderer draws the overlay in `Renderer::render_debug_overlay`. Nothing extra is done while
+    /// the overlay is disabled.
+    pub fn set_debug_overlay(&mut self, debug_overlay: bool) {
+        if debug_overlay != self.debug_overlay.is_some() {
+            self.debug_overlay = match debug_overlay {
+                true => Some(self.input_state.overlay_targets()),
+                false => None
+            };
+            self.update_state.borrow_mut().queue_global_update();
+        }
+    }
+
+    /// Whether widgets that haven't changed get their primitives from the previous frame replayed,
+    /// instead of being rendered again.
+    #[inline]
+    pub fn retained_rendering(&self) -> bool {
+        self.retained_rendering
+    }
+
+    /// Let the renderer replay the primitives of widgets that haven't requested a redraw, instead
+    /// of calling their `render` functions every frame. Defaults to `false`.
+    ///
+    /// Only renderers that implement `Renderer::replay_widget` can replay widgets. Frames where the
+    /// whole tree gets redrawn, such as the first frame and the frame after a theme change, still
+    /// render every widget. Widgets that get laid out again are always rendered, but widgets that
+    /// change their appearance in some other way need to request a redraw for it to show up.
+    pub fn set_retained_rendering(&mut self, retained_rendering: bool) {
+        self.retained_rendering = retained_rendering;
+        if !retained_rendering {
+            self.retained_renders.clear();
+        }
+    }
+
+    /// Timing and workload statistics for the most recent `relayout` and `redraw`.
+    ///
+    /// Only available with the `frame-stats` feature.
+    #[cfg(feature = "frame-stats")]
+    #[inline]
+    pub fn frame_stats(&self) -> &frame_stats::FrameStats {
+        &self.frame_stats
+    }
+
+    /// The window's current scale factor, as last reported by `WindowEvent::ScaleFactorChanged`.
+    #[inline]
+    pub fn scale_factor(&self) -> f64 {
+        self.input_state.scale_factor
+    }
+
     pub fn relayout(&mut self) -> SizeBounds {
+        #[cfg(feature = "frame-stats")]
+        let layout_start = Instant::now();
+        #[cfg(feature = "frame-stats")]
+        let mut widgets_laid_out = 0;
+
+        if self.renderer.scale_factor() != self.input_state.scale_factor {
+            self.renderer.set_scale_factor(self.input_state.scale_factor);
+        }
+
         let mut widget_traverser = self.widget_traverser_base.with_root_ref(&mut self.root_widget, self.update_state.clone());
 
+        // Widgets added since the last relayout may be the ones pending state is waiting for.
+        if !self.pending_ui_state.is_empty() && widget_traverser.tree_generation() != self.pending_ui_state_generation {
+            restore_ui_state(&mut self.pending_ui_state, &mut widget_traverser);
+            self.pending_ui_state_generation = widget_traverser.tree_generation();
+        }
+
         let mut relayout_widgets = Vec::new();
+        let mut old_child_rects = FnvHashMap::default();
 
         let mut iter_num = 0;
         let global_update = self.update_state.borrow().global_update;
 
-        while global_update || self.update_state.borrow().relayout.len() > 0 {
+        // Parents get queued in `relayout_widgets` when their children's layouts change, so keep
+        // going until those have been laid out too.
+        while global_update || relayout_widgets.len() > 0 || self.update_state.borrow().relayout.len() > 0 {
             match global_update {
                 false => relayout_widgets.extend(self.update_state.borrow_mut().relayout.drain()),
                 true => {
@@ -219,13 +627,46 @@ impl<N, R> Root<N, R>
                 };
 
                 let old_widget_rect = widget.rect();
+
+                // Remember where the children were, so we can tell the ones that get moved by
+                // `update_layout` that they've been moved. They're looked up by ID, since
+                // `update_layout` may add, remove, or reorder children.
+                old_child_rects.clear();
+                widget.inner().children(&mut |children| {
+                    old_child_rects.extend(children.into_iter().map(|c| (c.widget.widget_id(), c.widget.rect())));
+                    LoopFlow::Continue
+                });
+
                 self.renderer.layout(widget.widget_id(), |layout| widget.update_layout(layout));
+                // The widget may have prepared different content, so the primitives it drew last
+                // frame can't be replayed.
+                {
+                    let mut update_state = self.update_state.borrow_mut();
+                    update_state.redraw.insert(widget_id);
+                    update_state.redraw_rects.remove(&widget_id);
+                }
+                #[cfg(feature = "frame-stats")]
+                {
+                    widgets_laid_out += 1;
+                }
+
+                widget.inner_mut().children_mut(&mut |children| {
+                    for child in children {
+                        if let Some(&old_rect) = old_child_rects.get(&child.widget.widget_id()) {
+                            if old_rect != child.widget.rect() {
+                                child.widget.on_update_layout(old_rect);
+                            }
+                        }
+                    }
+                    LoopFlow::Continue
+                });
+
                 let size_bounds = widget.size_bounds();
                 let new_widget_rect = widget.rect();
                 let widget_dims = new_widget_rect.dims();
                 widget.cancel_scan();
 
-                let dims_bounded = size_bounds.bound_rect(widget_dims);
+                let dims_bounded = size_bounds.clamp_dims(widget_dims);
 
                 // If we're doing a global update, all widgets are in the relayout list so we don't
                 // need to queue the part for relayout. Otherwise, queue the parent for relayout if
@@ -246,7 +687,11 @@ impl<N, R> Root<N, R>
                         // If there's no parent, we must be on the root widget. So, just resize the
                         // widget to what it expects.
                         let mut widget = widget_traverser.get_widget(widget_id).unwrap().widget;
+                        let old_root_rect = widget.inner().rect();
                         widget.set_rect(dims_bounded.into());
+                        if widget.inner().rect() != old_root_rect {
+                            widget.inner_mut().on_update_layout(old_root_rect);
+                        }
                         widget.cancel_scan();
                     }
                 }
@@ -269,10 +714,40 @@ impl<N, R> Root<N, R>
 
         let root_id = widget_traverser.root_id();
         let root_widget = widget_traverser.get_widget(root_id).unwrap().widget;
-        root_widget.size_bounds()
+        let size_bounds = root_widget.size_bounds();
+
+        #[cfg(feature = "frame-stats")]
+        {
+            self.frame_stats.layout_time = layout_start.elapsed();
+            self.frame_stats.widgets_laid_out = widgets_laid_out;
+        }
+
+        size_bounds
+    }
+
+    /// Whether or not any widget has requested a redraw since the last call to `redraw`.
+    pub fn needs_redraw(&self) -> bool {
+        let update_state = self.update_state.borrow();
+        let overlay_changed = self.debug_overlay
+            .map(|targets| targets != self.input_state.overlay_targets())
+            .unwrap_or(false);
+        update_state.global_update || update_state.redraw.len() > 0 || overlay_changed
     }
 
     pub fn redraw(&mut self) {
+        #[cfg(feature = "frame-stats")]
+        let render_start = Instant::now();
+
+        // Move the debug overlay's highlights if the hover or focus targets have changed.
+        if let Some(ref mut targets) = self.debug_overlay {
+            let new_targets = self.input_state.overlay_targets();
+            if *targets != new_targets {
+                *targets = new_targets;
+                self.update_state.borrow_mut().queue_global_update();
+            }
+        }
+        let debug_overlay = self.debug_overlay;
+
         let root_rect = self.root_widget.rect();
         let new_dims = root_rect.dims().cast::<u32>().unwrap_or(DimsBox::new2(0, 0));
         if new_dims != self.renderer.dims() {
@@ -285,39 +760,239 @@ impl<N, R> Root<N, R>
             ref mut root_widget,
             ref theme,
             ref mut renderer,
+            retained_rendering,
+            ref mut retained_renders,
+            #[cfg(feature = "frame-stats")]
+            ref mut frame_stats,
             ..
         } = *self;
+        #[cfg(feature = "frame-stats")]
+        {
+            frame_stats.render_time = Default::default();
+            frame_stats.widgets_rendered = 0;
+            frame_stats.widgets_replayed = 0;
+            frame_stats.dirty_widgets = Some(0);
+            frame_stats.draw_calls = None;
+        }
 
         let mut update_state_ref = update_state.borrow_mut();
         if update_state_ref.global_update || update_state_ref.redraw.len() > 0 {
-            // We should probably support incremental redraw at some point but not doing that is
-            // soooo much easier.
-            update_state_ref.redraw.clear();
+            // The whole tree still gets crawled every frame, but widgets that haven't changed can
+            // have their primitives from the last frame replayed by the renderer.
+            let global_update = update_state_ref.global_update;
+            let redraw = mem::replace(&mut update_state_ref.redraw, FnvHashSet::default());
+            let mut redraw_rects = mem::replace(&mut update_state_ref.redraw_rects, FnvHashMap::default());
             update_state_ref.reset_global_update();
             drop(update_state_ref);
 
+            #[cfg(feature = "frame-stats")]
+            {
+                frame_stats.dirty_widgets = match global_update {
+                    true => None,
+                    false => Some(redraw.len()),
+                };
+            }
+
+            let mut widget_traverser = widget_traverser_base.with_root_ref(root_widget, update_state.clone());
+
+            // Tell the renderer which part of the window has changed.
+            let redraw_region = match global_update {
+                true => None,
+                false => redraw.iter()
+                    .filter_map(|&widget_id| {
+                        let widget_rect = widget_traverser.get_widget(widget_id)?.widget.rect();
+                        Some(match redraw_rects.remove(&widget_id) {
+                            Some(rect) => rect + widget_rect.min.to_vec(),
+                            None => widget_rect,
+                        })
+                    })
+                    .fold(None, |region, rect| Some(region.map(|r| crate::update_state::union(r, rect)).unwrap_or(rect)))
+            };
+            renderer.set_redraw_region(redraw_region);
+
             renderer.start_frame(theme);
             let window_rect = renderer.dims();
             let window_rect = BoundBox::new2(0, 0, window_rect.width() as i32, window_rect.height() as i32);
 
-            let mut widget_traverser = widget_traverser_base.with_root_ref(root_widget, update_state.clone());
+            // The depth of the widget that pushed each clip mask that's currently active.
+            let mut mask_owner_depths: Vec<usize> = Vec::new();
+            // The rect and clip of the focused widget, if the framework should draw its focus ring.
+            let mut focus_ring = None;
+            let mut debug_items = Vec::new();
+            // The depth of the outermost disabled widget whose subtree we're rendering.
+            let mut disabled_owner_depth: Option<usize> = None;
+            // Widgets that aren't drawn this frame drop out of the map, so that removed widgets
+            // don't stick around in it.
+            let mut last_renders = mem::replace(retained_renders, FnvHashMap::default());
+
             widget_traverser.crawl_widgets(|mut path| {
+                // Pop the masks of any widgets whose subtrees we've finished rendering.
+                let depth = path.path.len();
+                while mask_owner_depths.last().map(|d| *d >= depth).unwrap_or(false) {
+                    mask_owner_depths.pop();
+                    renderer.pop_clip_mask();
+                }
+                let mask_depth = renderer.clip_mask_depth();
+
+                if disabled_owner_depth.map(|d| d >= depth).unwrap_or(false) {
+                    disabled_owner_depth = None;
+                }
+                if disabled_owner_depth.is_none() && !path.widget.widget_tag().is_enabled() {
+                    disabled_owner_depth = Some(depth);
+                }
+
                 let render_parameters = RenderParameters {
                     renderer,
                     widget_id: path.widget.widget_id(),
                     theme,
                     transform: path.widget.rect(),
                     clip: path.widget.clip().unwrap_or(window_rect),
+                    disabled: disabled_owner_depth.is_some(),
                 };
 
-                let result = path.widget.render(render_parameters);
-                match result {
-                    Ok(()) => (),
-                    Err(RenderError::ThemeNotSupported) => println!("WARNING: Attempted to render widget but renderer didn't support theme"),
-                    Err(RenderError::RendererNotSupported) => println!("WARNING: Attempted to render widget but widget didn't support renderer"),
+                let widget_tag = path.widget.widget_tag();
+                if widget_tag.has_keyboard_focus() && !widget_tag.draws_own_focus_ring() {
+                    focus_ring = Some((render_parameters.transform, render_parameters.clip));
+                }
+                if let Some(targets) = debug_overlay {
+                    debug_items.push(DebugOverlayItem {
+                        widget_id: path.widget_id,
+                        ident: path.path.last().cloned().unwrap(),
+                        rect: render_parameters.transform,
+                        clip: render_parameters.clip,
+                        depth,
+                        hovered: targets.hover == Some(path.widget_id),
+                        focused: targets.focus == Some(path.widget_id),
+                    });
+                }
+
+                #[cfg(feature = "frame-stats")]
+                {
+                    frame_stats.widgets_rendered += 1;
+                }
+
+                let widget_id = path.widget_id;
+                let retained_render = RetainedRender {
+                    transform: render_parameters.transform,
+                    clip: render_parameters.clip,
+                    disabled: render_parameters.disabled,
+                };
+                let last_render = last_renders.remove(&widget_id);
+                let replay =
+                    retained_rendering &&
+                    !global_update &&
+                    !redraw.contains(&widget_id) &&
+                    last_render == Some(retained_render);
+
+                let drawn = match replay && render_parameters.renderer.replay_widget(theme, widget_id) {
+                    true => {
+                        #[cfg(feature = "frame-stats")]
+                        {
+                            frame_stats.widgets_replayed += 1;
+                        }
+                        true
+                    },
+                    false => match path.widget.render(render_parameters) {
+                        Ok(()) => true,
+                        Err(RenderError::ThemeNotSupported) => {println!("WARNING: Attempted to render widget but renderer didn't support theme"); false},
+                        Err(RenderError::RendererNotSupported) => {println!("WARNING: Attempted to render widget but widget didn't support renderer"); false},
+                    }
+                };
+                if drawn && retained_rendering {
+                    retained_renders.insert(widget_id, retained_render);
+                }
+
+                for _ in mask_depth..renderer.clip_mask_depth() {
+                    mask_owner_depths.push(depth);
                 }
             });
+            for _ in mask_owner_depths.drain(..) {
+                renderer.pop_clip_mask();
+            }
+            // Draw the ring last so that it isn't covered up by the focused widget's siblings.
+            if let Some((rect, clip)) = focus_ring {
+                renderer.render_focus_ring(theme, rect, clip);
+            }
+            if debug_overlay.is_some() {
+                renderer.render_debug_overlay(theme, &debug_items);
+            }
             renderer.finish_frame(theme);
+
+            #[cfg(feature = "frame-stats")]
+            {
+                frame_stats.render_time = render_start.elapsed();
+                frame_stats.draw_calls = renderer.draw_call_count();
+            }
+        }
+    }
+
+    /// Lay out and draw the entire widget tree, then read the drawn frame back as an image.
+    ///
+    /// This always draws a fresh frame, even if nothing has requested a redraw.
+    pub fn render_to_image(&mut self) -> Image
+        where R: ReadPixels
+    {
+        self.update_state.borrow_mut().queue_global_update();
+        self.relayout();
+        self.redraw();
+        self.renderer.read_pixels()
+    }
+}
+
+/// Render `widget` and its descendants to an image of size `dims`, using an offscreen `renderer`.
+///
+/// The widget is given back once it's been rendered.
+pub fn render_to_image<N, R>(widget: N, theme: R::Theme, renderer: R, dims: DimsBox<D2, u32>) -> (Image, N)
+    where N: Widget,
+          R: ReadPixels
+{
+    let mut root = Root::new(widget, theme, renderer, dims);
+    let image = root.render_to_image();
+    (image, root.root_widget)
+}
+
+/// Run the widget mutations deferred with `WidgetTag::defer`, in the order they were queued.
+fn apply_deferred<R: Renderer>(update_state: &UpdateStateCell, widget_traverser: &mut WidgetTraverser<'_, R>) {
+    // Deferred mutations can defer more mutations, so keep going until the queue's empty. The
+    // iteration limit keeps a widget that always re-defers itself from hanging the event loop.
+    for _ in 0..MAX_FRAME_UPDATE_ITERATIONS {
+        let deferred = update_state.borrow_mut().take_deferred();
+        if deferred.is_empty() {
+            return;
+        }
+
+        for Deferred{widget_id, f} in deferred {
+            if let Some(mut wpath) = widget_traverser.get_widget(widget_id) {
+                wpath.widget.inner_mut().apply_deferred(f);
+            }
+        }
+    }
+}
+
+/// Restore the state in `ui_state` to the widgets at its paths, removing the restored entries.
+fn restore_ui_state<R: Renderer>(ui_state: &mut UiState, widget_traverser: &mut WidgetTraverser<'_, R>) {
+    if ui_state.is_empty() {
+        return;
+    }
+
+    widget_traverser.crawl_widgets(|mut path| {
+        if let Some(value) = ui_state.remove(&path.path[1..].into()) {
+            path.widget.inner_mut().restore_state(&value);
+        }
+    });
+}
+
+/// Resume `timer_tracker`, delaying the widgets' timers to match their queued triggers.
+fn resume_timers<R: Renderer>(timer_tracker: &mut TimerTriggerTracker, widget_traverser: &mut WidgetTraverser<'_, R>) {
+    if let Some(paused_for) = timer_tracker.resume() {
+        for trigger in timer_tracker.queued_triggers() {
+            let widget = match widget_traverser.get_widget(trigger.widget_id) {
+                Some(wpath) => wpath.widget,
+                None => continue
+            };
+            if let Some(timer) = widget.widget_tag().timers.get(&trigger.timer_id) {
+                timer.delay(paused_for);
+            }
         }
     }
 }
@@ -325,26 +1000,45 @@ impl<N, R> Root<N, R>
 impl<R> FrameEventProcessor<'_, R>
     where R: Renderer
 {
+    /// Process a window event, returning whether any widget reported handling it (see
+    /// `EventOps::handled`).
+    ///
+    /// Events consumed by the event filter aren't delivered to any widget, so they're reported as
+    /// unhandled.
     pub fn process_event(
         &mut self,
         event: WindowEvent,
-    ) {
+    ) -> bool {
         let FrameEventProcessor {
             ref mut input_state,
             ref mut event_translator,
             ref update_state,
             ref mut widget_traverser,
-            timer_tracker: _,
+            ref mut timer_tracker,
             message_bus: _,
         } = *self;
 
-        event_translator
+        if let WindowEvent::WindowFocus(focused) = event {
+            if timer_tracker.pause_when_unfocused() {
+                match focused {
+                    true => resume_timers(timer_tracker, widget_traverser),
+                    false => timer_tracker.pause()
+                }
+            }
+        }
+
+        let handled = event_translator
             .with_data(
                 widget_traverser,
                 input_state,
                 update_state.clone(),
             )
             .translate_window_event(event);
+
+        // The dispatcher is done walking the tree, so it's safe to change it now.
+        apply_deferred(update_state, widget_traverser);
+
+        handled
     }
 
     pub fn set_modifiers(&mut self, modifiers: ModifierKeys) {
@@ -356,11 +1050,16 @@ impl<R> FrameEventProcessor<'_, R>
             let mut update_state = self.update_state.borrow_mut();
 
             for remove_id in update_state.remove_from_tree.drain() {
+                self.input_state.focus_scopes.retain(|s| s.scope != remove_id);
                 self.widget_traverser.remove_widget(remove_id);
                 self.message_bus.remove_widget(remove_id);
+                self.timer_tracker.remove_widget(remove_id);
             }
 
             for widget_id in update_state.update_timers.drain() {
+                // Drop the widget's queued triggers, so that timers which have been removed from
+                // the widget don't keep waking up the event loop.
+                self.timer_tracker.remove_widget(widget_id);
                 let widget = match self.widget_traverser.get_widget(widget_id) {
                     Some(wpath) => wpath.widget,
                     None => continue
@@ -441,9 +1140,16 @@ impl<R> FrameEventProcessor<'_, R>
             });
         };}
 
+        // Message handlers and timer events may have deferred mutations too.
+        apply_deferred(&self.update_state, &mut self.widget_traverser);
+
         let mut update_state = self.update_state.borrow_mut();
         let widget_traverser = &mut self.widget_traverser;
         let set_cursor_icon = update_state.set_cursor_icon.take();
+        let set_window_title = update_state.set_window_title.take();
+        let close_requested = mem::replace(&mut update_state.close_requested, false);
+        let close = (self.event_translator.take_close_request() || close_requested) &&
+            self.event_translator.confirm_close();
 
         // The cursor position stored in `UpdateState.set_cursor_pos` is relative to the requesting
         // widget's origin. This translates it into window-space.
@@ -458,6 +1164,8 @@ impl<R> FrameEventProcessor<'_, R>
             next_timer: self.timer_tracker.next_trigger(),
             set_cursor_pos,
             set_cursor_icon,
+            set_window_title,
+            close,
         }
     }
 }
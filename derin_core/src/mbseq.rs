@@ -3,6 +3,7 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use std::iter::{ExactSizeIterator, FromIterator};
+use std::time::Instant;
 
 use derin_common_types::buttons::{MouseButton, MOUSE_INT_MASK, MOUSE_INT_MASK_LEN, NUM_MOUSE_BUTTONS};
 use arrayvec::{ArrayVec, IntoIter};
@@ -12,7 +13,7 @@ use crate::{
     widget::WidgetId,
 };
 
-type PointArray = [(Point2<i32>, WidgetId); MOUSE_INT_MASK_LEN as usize];
+type PointArray = [(Point2<i32>, WidgetId, Instant); MOUSE_INT_MASK_LEN as usize];
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct MouseButtonSequenceTrackPos {
@@ -50,10 +51,10 @@ impl MouseButtonSequenceTrackPos {
         }
     }
 
-    pub fn push_button(&mut self, button: MouseButton, pos: Point2<i32>, widget_id: WidgetId) -> &mut MouseButtonSequenceTrackPos {
+    pub fn push_button(&mut self, button: MouseButton, pos: Point2<i32>, widget_id: WidgetId, down_time: Instant) -> &mut MouseButtonSequenceTrackPos {
         self.release_button(button);
         self.seq.push_button(button);
-        self.down_positions.push((pos, widget_id));
+        self.down_positions.push((pos, widget_id, down_time));
         self
     }
 
@@ -73,7 +74,8 @@ impl MouseButtonSequenceTrackPos {
             .map(|(i, b)| MouseDownWithId {
                 mouse_down: MouseDown {
                     button: b,
-                    down_pos: self.down_positions[i].0
+                    down_pos: self.down_positions[i].0,
+                    down_time: self.down_positions[i].2,
                 },
                 widget_id: self.down_positions[i].1,
             })
@@ -207,11 +209,12 @@ impl Iterator for MouseButtonSeqTrackPosIter {
 
     #[inline]
     fn next(&mut self) -> Option<MouseDownWithId> {
-        let (down_pos, widget_id) = self.pos_iter.next()?;
+        let (down_pos, widget_id, down_time) = self.pos_iter.next()?;
         Some(MouseDownWithId {
             mouse_down: MouseDown {
                 button: self.seq_iter.next()?,
                 down_pos,
+                down_time,
             },
             widget_id,
         })
@@ -257,4 +260,21 @@ mod tests {
         assert_eq!(4, seq.len());
         assert_eq!(&[Left, X1, Middle, Right], &*seq.into_iter().collect::<Vec<_>>());
     }
+
+    #[test]
+    fn track_down_times() {
+        use self::MouseButton::*;
+        use std::time::Duration;
+        let (left_time, right_time) = (Instant::now(), Instant::now() + Duration::from_millis(100));
+        let widget_id = WidgetId::new();
+        let mut seq = MouseButtonSequenceTrackPos::new();
+        seq.push_button(Left, Point2::new(1, 2), widget_id, left_time);
+        seq.push_button(Right, Point2::new(3, 4), widget_id, right_time);
+        seq.release_button(Left);
+
+        assert!(seq.contains(Left).is_none());
+        let right = seq.contains(Right).unwrap().mouse_down;
+        assert_eq!(Point2::new(3, 4), right.down_pos);
+        assert_eq!(right_time, right.down_time());
+    }
 }
@@ -6,10 +6,17 @@ use crate::widget::WidgetId;
 use fnv::{FnvHashMap, FnvHashSet};
 use std::{
     any::{Any, TypeId},
-    sync::mpsc::{self, Sender, Receiver},
+    fmt,
+    marker::PhantomData,
+    sync::{
+        Weak,
+        mpsc::{self, Sender, Receiver},
+    },
 };
 
 pub type Message = Box<Any>;
+/// A message that's been sent from outside of the UI thread. Those messages always have a target.
+pub(crate) type SendMessageTargeted = (Box<Any + Send>, MessageTarget);
 pub type WidgetMessageFn = Box<FnMut(&mut Any, &Any)>;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -48,6 +55,8 @@ pub struct MessageBus {
     type_map: FnvHashMap<TypeId, FnvHashSet<WidgetId>>,
     messages_recv: Receiver<MessageTargeted>,
     messages_send: Sender<MessageTargeted>,
+    send_messages_recv: Receiver<SendMessageTargeted>,
+    send_messages_send: Sender<SendMessageTargeted>,
 }
 
 #[derive(Debug)]
@@ -63,12 +72,89 @@ pub enum MessageTarget {
     ChildrenOf(WidgetId),
 }
 
+/// A handle that lets work running outside of the UI, including on other threads, report progress
+/// back to the widget that created it.
+///
+/// Created with `WidgetTag::progress_sender`. Widgets typically hand one out alongside an action
+/// that starts a long-running operation, and the application moves it to wherever that operation
+/// is performed. Each value sent becomes a message of type `P` targeted at the widget, which the
+/// widget handles with `WidgetTag::register_message`. Messages are delivered the next time the
+/// root processes events.
+///
+/// The handle doesn't keep the widget alive. It's bound to the widget and the root the widget was
+/// in when the handle was created, and sending fails once either of those gets dropped.
+pub struct ProgressSender<P> {
+    sender: Sender<SendMessageTargeted>,
+    widget_id: WidgetId,
+    widget_alive: Weak<()>,
+    _marker: PhantomData<fn(P)>,
+}
+
+/// The error returned when a `ProgressSender` can't deliver progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProgressError {
+    /// The widget that created the sender has been dropped.
+    WidgetDropped,
+    /// The root the widget was in when the sender was created has been dropped.
+    RootDropped,
+}
+
+impl<P> ProgressSender<P>
+    where P: 'static + Send
+{
+    pub(crate) fn new(sender: Sender<SendMessageTargeted>, widget_id: WidgetId, widget_alive: Weak<()>) -> ProgressSender<P> {
+        ProgressSender {
+            sender,
+            widget_id,
+            widget_alive,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The ID of the widget progress gets sent to.
+    #[inline]
+    pub fn widget_id(&self) -> WidgetId {
+        self.widget_id
+    }
+
+    /// Send a progress update to the widget.
+    pub fn send(&self, progress: P) -> Result<(), ProgressError> {
+        if self.widget_alive.upgrade().is_none() {
+            return Err(ProgressError::WidgetDropped);
+        }
+
+        self.sender.send((Box::new(progress), MessageTarget::Widget(self.widget_id)))
+            .map_err(|_| ProgressError::RootDropped)
+    }
+}
+
+impl<P> Clone for ProgressSender<P> {
+    fn clone(&self) -> ProgressSender<P> {
+        ProgressSender {
+            sender: self.sender.clone(),
+            widget_id: self.widget_id,
+            widget_alive: self.widget_alive.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<P> fmt::Debug for ProgressSender<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ProgressSender")
+            .field(&self.widget_id)
+            .finish()
+    }
+}
+
 impl MessageBus {
     pub fn new() -> MessageBus {
         let (messages_send, messages_recv) = mpsc::channel();
+        let (send_messages_send, send_messages_recv) = mpsc::channel();
         MessageBus {
             type_map: FnvHashMap::default(),
             messages_recv, messages_send,
+            send_messages_recv, send_messages_send,
         }
     }
 
@@ -76,8 +162,19 @@ impl MessageBus {
         self.messages_send.clone()
     }
 
+    /// Get a sender for messages that get sent from outside of the UI thread.
+    pub(crate) fn send_sender(&self) -> Sender<SendMessageTargeted> {
+        self.send_messages_send.clone()
+    }
+
     pub fn next_message(&mut self) -> Option<(Message, impl '_ + Iterator<Item=MessageTarget>)> {
-        while let Ok(MessageTargeted{message, target}) = self.messages_recv.try_recv() {
+        // Messages sent from within the UI get delivered before messages sent from elsewhere.
+        let next = self.messages_recv.try_recv().ok()
+            .or_else(|| self.send_messages_recv.try_recv().ok().map(|(message, target)| MessageTargeted {
+                message: message as Message,
+                target: Some(target),
+            }));
+        if let Some(MessageTargeted{message, target}) = next {
             // We have to dereference `message` here because otherwise it would get the TypeId of
             // `Box<Any>`, not the inner `Any`.
             let type_id = (*message).type_id();
@@ -115,6 +212,7 @@ impl MessageBus {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::{sync::Arc, thread};
 
     struct MessageA;
     struct MessageB;
@@ -186,4 +284,34 @@ mod tests {
             MessageTarget::ChildrenOf(a),
         );
     }
+
+    #[test]
+    fn progress_sender() {
+        let a = WidgetId::new();
+        let mut message_bus = MessageBus::new();
+        let widget_alive = Arc::new(());
+
+        let progress = ProgressSender::<u32>::new(message_bus.send_sender(), a, Arc::downgrade(&widget_alive));
+        let progress_clone = progress.clone();
+        thread::spawn(move || progress_clone.send(5)).join().unwrap().unwrap();
+
+        // Messages from the UI thread come first.
+        message_bus.messages_send.send(MessageTargeted {
+            message: Box::new(MessageA),
+            target: Some(MessageTarget::Widget(a))
+        }).unwrap();
+        assert!(message_bus.next_message().unwrap().0.is::<MessageA>());
+
+        {
+            let (message, targets) = message_bus.next_message().unwrap();
+            assert_eq!(vec![MessageTarget::Widget(a)], targets.collect::<Vec<_>>());
+            assert_eq!(Some(&5), message.downcast_ref::<u32>());
+        }
+        assert!(message_bus.next_message().is_none());
+
+        drop(message_bus);
+        assert_eq!(Err(ProgressError::RootDropped), progress.send(6));
+        drop(widget_alive);
+        assert_eq!(Err(ProgressError::WidgetDropped), progress.send(7));
+    }
 }
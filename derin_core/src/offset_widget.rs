@@ -19,6 +19,11 @@ use cgmath_geometry::{D2, rect::{BoundBox, GeoBox}};
 
 use arrayvec::ArrayVec;
 
+/// Coordinate that container widgets assign to children whose layouts couldn't be solved.
+///
+/// A rect containing this should never be used to position other widgets.
+const UNSOLVED_RECT_SENTINEL: i32 = 0xDEDBEEF;
+
 pub(crate) struct OffsetWidget<'a, R: Renderer> {
     widget: &'a mut WidgetDyn<R>,
     offset: Vector2<i32>,
@@ -34,6 +39,7 @@ pub(crate) struct OffsetWidgetInfo<'a, R: Renderer> {
 impl<'a, R: Renderer> OffsetWidget<'a, R> {
     #[inline]
     pub fn new(widget: &'a mut WidgetDyn<R>, offset: Vector2<i32>, clip: Option<BoundBox<D2, i32>>) -> OffsetWidget<'a, R> {
+        widget.widget_tag().window_offset.set(Some(offset));
         OffsetWidget {
             widget,
             offset,
@@ -64,8 +70,17 @@ impl<'a, R: Renderer> OffsetWidget<'a, R> {
     pub fn rect(&self) -> BoundBox<D2, i32> {
         self.widget.rect() + self.offset
     }
+    /// Get the widget's rect, clipped to the visible portion of its parent.
+    ///
+    /// Returns `None` if the widget isn't visible, or if the widget hasn't been given a valid rect
+    /// by its parent yet.
     pub fn rect_clipped(&self) -> Option<BoundBox<D2, i32>> {
-        self.clip.and_then(|clip_rect| clip_rect.intersect_rect(self.rect()))
+        let rect = self.widget.rect();
+        if rect.width() <= 0 || rect.height() <= 0 || is_unsolved_rect(rect) {
+            return None;
+        }
+
+        self.clip.and_then(|clip_rect| clip_rect.intersect_rect(rect + self.offset))
     }
     pub fn set_rect(&mut self, rect: BoundBox<D2, i32>) {
         *self.widget.rect_mut() = rect - self.offset;
@@ -81,6 +96,7 @@ impl<'a, R: Renderer> OffsetWidget<'a, R> {
     {
         let InputState {
             mouse_pos,
+            mouse_in_window,
             mouse_buttons_down,
             keys_down,
             modifiers,
@@ -97,6 +113,7 @@ impl<'a, R: Renderer> OffsetWidget<'a, R> {
 
         let input_state = EventInputState {
             mouse_pos: mouse_pos.map(|p| p - offset),
+            mouse_in_window: *mouse_in_window,
             modifiers: *modifiers,
             mouse_buttons_down: &mbd_array[..],
             mouse_buttons_down_in_widget: &mbd_array[..],
@@ -139,6 +156,14 @@ impl<'a, R: Renderer> OffsetWidget<'a, R> {
     pub fn children_mut<'b, G>(&'b mut self, mut for_each: G)
         where G: FnMut(OffsetWidgetInfo<'b, R>) -> LoopFlow
     {
+        // `rect_clipped` returns `None` if our rect is invalid, so children of an un-laid-out
+        // widget don't get clipped to a garbage rectangle.
+        debug_assert!(
+            !is_unsolved_rect(self.widget.rect()),
+            "widget {:?} has the unsolved layout sentinel rect {:?}",
+            self.widget_id(),
+            self.widget.rect()
+        );
         let child_offset = self.rect().min().to_vec();
         let clip_rect = self.rect_clipped();
 
@@ -159,3 +184,10 @@ impl<'a, R: Renderer> OffsetWidget<'a, R> {
         });
     }
 }
+
+fn is_unsolved_rect(rect: BoundBox<D2, i32>) -> bool {
+    rect.min.x == UNSOLVED_RECT_SENTINEL ||
+    rect.min.y == UNSOLVED_RECT_SENTINEL ||
+    rect.max.x == UNSOLVED_RECT_SENTINEL ||
+    rect.max.y == UNSOLVED_RECT_SENTINEL
+}
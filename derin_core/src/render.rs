@@ -2,11 +2,20 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use crate::widget::WidgetId;
+pub mod headless;
+mod text_layout;
+pub mod text_nav;
+mod wrap;
+
+pub use self::text_layout::{CaretPosition, TextLayoutResult, TextLine};
+pub use self::wrap::{WrapMode, wrap_lines};
+
+use crate::widget::{WidgetId, WidgetIdent};
 use cgmath_geometry::{
     D2,
+    cgmath::Point2,
     line::Segment,
-    rect::{BoundBox, DimsBox},
+    rect::{BoundBox, DimsBox, GeoBox},
 };
 use derin_common_types::layout::SizeBounds;
 use std::ops::Range;
@@ -18,6 +27,13 @@ pub trait Renderer: 'static {
 
     fn resized(&mut self, new_size: DimsBox<D2, u32>);
     fn dims(&self) -> DimsBox<D2, u32>;
+    /// The ratio between physical pixels and the logical pixels widgets are laid out in.
+    fn scale_factor(&self) -> f64 {
+        1.0
+    }
+    /// Change the scale factor. This gets called before the widget tree is relaid out at the new
+    /// scale, so renderers that draw at physical resolution should update their text metrics here.
+    fn set_scale_factor(&mut self, _scale_factor: f64) {}
     fn widget_removed(&mut self, widget_id: WidgetId);
     fn layout(
         &mut self,
@@ -26,6 +42,68 @@ pub trait Renderer: 'static {
     );
     fn start_frame(&mut self, theme: &Self::Theme);
     fn finish_frame(&mut self, theme: &Self::Theme);
+
+    /// Called before `start_frame` with the part of the window that's changed since the last
+    /// frame, in window coordinates, or `None` if the entire window needs to be redrawn.
+    ///
+    /// The whole widget tree is still rendered every frame, so renderers that support partial
+    /// repaints can use this to skip drawing outside of the region. Defaults to ignoring it.
+    fn set_redraw_region(&mut self, _region: Option<BoundBox<D2, i32>>) {}
+
+    /// Draw the focus ring around the widget with keyboard focus, in the style given by the
+    /// theme. This is called after the rest of the widget tree has been drawn, and isn't called
+    /// for widgets that draw their own focus indicators.
+    ///
+    /// `rect` is the focused widget's rect, and `clip` is the region the ring should be clipped
+    /// to. `FocusRingStyle` describes the usual ways a theme can customize the ring.
+    fn render_focus_ring(&mut self, _theme: &Self::Theme, _rect: BoundBox<D2, i32>, _clip: BoundBox<D2, i32>) {}
+
+    /// Draw the debug overlay on top of the finished frame. This is only called while the overlay
+    /// is enabled with `Root::set_debug_overlay`, after the focus ring has been drawn.
+    ///
+    /// `items` contains every widget in the tree, in the order they were drawn. Renderers should
+    /// outline each widget's rect and label it with `DebugOverlayItem::label`, marking the
+    /// hovered and focused widgets so they stand out.
+    fn render_debug_overlay(&mut self, _theme: &Self::Theme, _items: &[DebugOverlayItem]) {}
+
+    /// Called before and after each widget is rendered, with whether the renderer should draw the
+    /// widget in the theme's disabled style. Renderers usually desaturate and dim everything
+    /// drawn while this is set.
+    ///
+    /// This is only set for widgets that are disabled and use `DisabledStyle::Desaturate`.
+    /// Defaults to ignoring it.
+    fn set_disabled(&mut self, _theme: &Self::Theme, _disabled: bool) {}
+
+    /// The number of clip masks that are currently pushed.
+    fn clip_mask_depth(&self) -> usize {
+        0
+    }
+    /// Remove the most recently pushed clip mask.
+    ///
+    /// The widget tree calls this once every descendant of the widget that pushed the mask has
+    /// been rendered.
+    fn pop_clip_mask(&mut self) {}
+
+    /// Draw the primitives the widget with the given ID emitted the last time it was drawn,
+    /// including any clip masks it pushed, without calling into the widget. Returns `false` if
+    /// nothing is cached for the widget, in which case it gets rendered normally.
+    ///
+    /// This only gets called for frames that aren't full redraws, on widgets that haven't
+    /// requested a redraw and that have the same rect, clip, and disabled state as in the previous
+    /// frame. Renderers that support this should hold on to the primitives of every widget drawn
+    /// or replayed during a frame, and can throw away the rest once the next frame starts.
+    /// Defaults to returning `false`, which renders every widget on every frame.
+    fn replay_widget(&mut self, _theme: &Self::Theme, _widget_id: WidgetId) -> bool {
+        false
+    }
+
+    /// The number of draw calls issued since the last `start_frame`, if the renderer counts them.
+    ///
+    /// This is only used to fill in `FrameStats::draw_calls`, and gets called after
+    /// `finish_frame`. Defaults to `None`.
+    fn draw_call_count(&self) -> Option<usize> {
+        None
+    }
 }
 
 pub trait WidgetRenderer<T: WidgetTheme>: Renderer {
@@ -40,8 +118,349 @@ pub trait WidgetRenderer<T: WidgetTheme>: Renderer {
     );
 }
 
+/// A renderer that can read back the frame it most recently drew.
+///
+/// Implementing this lets a widget tree be rendered to an image with `Root::render_to_image`,
+/// independently of any on-screen window.
+pub trait ReadPixels: Renderer {
+    /// Read the contents of the last finished frame.
+    fn read_pixels(&mut self) -> Image;
+}
+
+/// An image with 8-bit RGBA pixels, stored row by row starting from the top.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Image {
+    dims: DimsBox<D2, u32>,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Create a fully transparent image with the given dimensions.
+    pub fn new(dims: DimsBox<D2, u32>) -> Image {
+        Image {
+            dims,
+            pixels: vec![0; dims.width() as usize * dims.height() as usize * 4],
+        }
+    }
+
+    /// Create an image from raw RGBA pixel data.
+    ///
+    /// Panics if `pixels` doesn't have exactly four bytes for every pixel in `dims`.
+    pub fn from_pixels(dims: DimsBox<D2, u32>, pixels: Vec<u8>) -> Image {
+        assert_eq!(dims.width() as usize * dims.height() as usize * 4, pixels.len());
+        Image{ dims, pixels }
+    }
+
+    #[inline]
+    pub fn dims(&self) -> DimsBox<D2, u32> {
+        self.dims
+    }
+
+    #[inline]
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    #[inline]
+    pub fn pixels_mut(&mut self) -> &mut [u8] {
+        &mut self.pixels
+    }
+
+    #[inline]
+    pub fn into_pixels(self) -> Vec<u8> {
+        self.pixels
+    }
+
+    /// Get the RGBA value of the pixel at `(x, y)`, or `None` if it's outside the image.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        let offset = self.pixel_offset(x, y)?;
+        let p = &self.pixels[offset..offset + 4];
+        Some([p[0], p[1], p[2], p[3]])
+    }
+
+    /// Set the RGBA value of the pixel at `(x, y)`. Does nothing if it's outside the image.
+    pub fn set_pixel(&mut self, x: u32, y: u32, rgba: [u8; 4]) {
+        if let Some(offset) = self.pixel_offset(x, y) {
+            self.pixels[offset..offset + 4].copy_from_slice(&rgba);
+        }
+    }
+
+    fn pixel_offset(&self, x: u32, y: u32) -> Option<usize> {
+        match x < self.dims.width() && y < self.dims.height() {
+            true => Some((y as usize * self.dims.width() as usize + x as usize) * 4),
+            false => None
+        }
+    }
+
+    /// Encode the image as a PNG and write it to `w`.
+    #[cfg(feature = "png")]
+    pub fn write_png<W: std::io::Write>(&self, w: W) -> Result<(), png::EncodingError> {
+        let mut encoder = png::Encoder::new(w, self.dims.width(), self.dims.height());
+        encoder.set_color(png::ColorType::RGBA);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(&self.pixels)
+    }
+}
+
+/// Which pixels of an image are opaque enough to be clicked on, for widgets with irregular
+/// shapes. See `Widget::hit_test`.
+///
+/// This only stores a single bit per pixel, but that's still extra memory kept around for every
+/// image that uses one, so masks should only be made for images that need pixel-accurate
+/// hit-testing.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HitMask {
+    dims: DimsBox<D2, u32>,
+    bits: Vec<u64>,
+}
+
+impl HitMask {
+    /// Create a mask from `image`, where pixels with an alpha of at least `alpha_threshold` can
+    /// be hit.
+    pub fn from_image(image: &Image, alpha_threshold: u8) -> HitMask {
+        let dims = image.dims();
+        let num_pixels = dims.width() as usize * dims.height() as usize;
+        let mut bits = vec![0; (num_pixels + 63) / 64];
+        for (i, pixel) in image.pixels().chunks(4).enumerate() {
+            if pixel[3] >= alpha_threshold {
+                bits[i / 64] |= 1 << (i % 64);
+            }
+        }
+
+        HitMask{ dims, bits }
+    }
+
+    #[inline]
+    pub fn dims(&self) -> DimsBox<D2, u32> {
+        self.dims
+    }
+
+    /// Whether `point` lies on an opaque pixel, when the image is stretched to fill `rect_dims`.
+    /// `point` is relative to the top-left corner of the stretched image.
+    pub fn hit(&self, point: Point2<i32>, rect_dims: DimsBox<D2, i32>) -> bool {
+        if point.x < 0 || point.y < 0 || point.x >= rect_dims.width() || point.y >= rect_dims.height() {
+            return false;
+        }
+
+        let x = (point.x as u64 * self.dims.width() as u64 / rect_dims.width() as u64) as usize;
+        let y = (point.y as u64 * self.dims.height() as u64 / rect_dims.height() as u64) as usize;
+        let i = y * self.dims.width() as usize + x;
+        self.bits.get(i / 64).map(|b| b & (1 << (i % 64)) != 0).unwrap_or(false)
+    }
+}
+
+/// Draws a single widget.
+///
+/// A widget is drawn in three layers: the theme's background, the content prepared in
+/// `WidgetRenderable::update_layout` (such as text or an icon), and the theme's foreground (such
+/// as a border drawn over the content). Widgets that only call `render_laid_out_content` get all
+/// three layers drawn in order. Widgets that want to draw their own decorations between the layers
+/// call `render_background` and `render_foreground` explicitly, drawing decorations such as
+/// selection highlights in between:
+///
+/// ```ignore
+/// fn render(&mut self, frame: &mut R::SubFrame) {
+///     frame.render_background();
+///     frame.fill_rect(self.highlight_rect, [0, 120, 215, 255]);
+///     frame.render_laid_out_content();
+///     frame.render_foreground();
+/// }
+/// ```
 pub trait SubFrame {
+    /// Draw the theme's background layer. If this isn't called before `render_laid_out_content`,
+    /// the background gets drawn by `render_laid_out_content` instead.
+    ///
+    /// Renderers that don't separate the layers can ignore this.
+    fn render_background(&mut self) {}
+    /// Draw the content prepared during layout, along with the background if it hasn't been drawn
+    /// yet. The foreground gets drawn once the widget has finished rendering, unless
+    /// `render_foreground` gets called first.
     fn render_laid_out_content(&mut self);
+    /// Draw the theme's foreground layer, on top of everything the widget has drawn so far.
+    ///
+    /// Renderers that don't separate the layers can ignore this.
+    fn render_foreground(&mut self) {}
+
+    /// Fill `rect`, relative to the widget's origin, with a solid RGBA color. This is meant for
+    /// simple decorations drawn between the theme's layers, such as highlights and underlines.
+    ///
+    /// Returns `false` if the renderer doesn't support drawing custom primitives.
+    fn fill_rect(&mut self, _rect: BoundBox<D2, i32>, _color: [u8; 4]) -> bool {
+        false
+    }
+
+    /// Clip everything drawn afterwards by this widget and its descendants to `mask`, on top of
+    /// the normal rectangular clipping.
+    ///
+    /// Returns `false` if the renderer doesn't support the mask, in which case only rectangular
+    /// clipping is performed. Masks get popped automatically after the widget's descendants have
+    /// been rendered.
+    fn push_clip_mask(&mut self, _mask: ClipMask) -> bool {
+        false
+    }
+    /// Remove the most recently pushed clip mask before the widget's descendants are rendered.
+    fn pop_clip_mask(&mut self) {}
+}
+
+/// A non-rectangular region that drawing can be clipped to.
+///
+/// Coordinates are relative to the origin of the widget pushing the mask.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ClipMask {
+    /// A rectangle with its corners rounded by `radius` pixels.
+    ///
+    /// Renderers should anti-alias the mask along the rounded corners, so that curved edges don't
+    /// look jagged.
+    RoundedRect {
+        rect: BoundBox<D2, i32>,
+        radius: i32,
+    },
+    /// A polygon with the given vertices.
+    Polygon(Vec<Point2<i32>>),
+}
+
+/// How a widget gets drawn while it's disabled. Returned by `WidgetRenderable::disabled_style`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DisabledStyle {
+    /// Let the renderer apply the theme's disabled style over everything the widget draws.
+    Desaturate,
+    /// Draw the widget normally. The widget is responsible for looking disabled.
+    Custom,
+}
+
+/// A widget drawn by `Renderer::render_debug_overlay`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DebugOverlayItem {
+    pub widget_id: WidgetId,
+    pub ident: WidgetIdent,
+    /// The widget's rect, in window coordinates.
+    pub rect: BoundBox<D2, i32>,
+    /// The region the widget was clipped to when it was drawn.
+    pub clip: BoundBox<D2, i32>,
+    /// The widget's depth in the tree. The root widget has a depth of `1`.
+    pub depth: usize,
+    /// Whether the widget is under the mouse cursor.
+    pub hovered: bool,
+    /// Whether the widget has keyboard focus.
+    pub focused: bool,
+}
+
+impl DebugOverlayItem {
+    /// A short description of the widget to draw alongside its rect, containing its ident and ID.
+    pub fn label(&self) -> String {
+        format!("{:?} {:?}", self.ident, self.widget_id)
+    }
+}
+
+/// The appearance of the focus ring drawn by `Renderer::render_focus_ring`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FocusRingStyle {
+    /// The RGBA color of the ring.
+    pub color: [u8; 4],
+    /// The thickness of the ring, in pixels.
+    pub width: i32,
+    /// How far outside of the widget's rect the ring's outer edge lies. Negative values draw the
+    /// ring inside of the widget.
+    pub offset: i32,
+}
+
+impl FocusRingStyle {
+    /// Get the outer edge of the ring drawn around `rect`.
+    pub fn ring_rect(&self, rect: BoundBox<D2, i32>) -> BoundBox<D2, i32> {
+        BoundBox::new2(
+            rect.min.x - self.offset,
+            rect.min.y - self.offset,
+            rect.max.x + self.offset,
+            rect.max.y + self.offset,
+        )
+    }
+}
+
+impl Default for FocusRingStyle {
+    fn default() -> FocusRingStyle {
+        FocusRingStyle {
+            color: [0, 120, 215, 255],
+            width: 1,
+            offset: 1,
+        }
+    }
+}
+
+/// The minimum sizes of built-in controls, in logical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ControlMetrics {
+    /// The minimum height of single-line controls, such as buttons and edit boxes.
+    pub control_height: i32,
+    /// The minimum length of a slider's track.
+    pub slider_length: i32,
+    /// The minimum thickness of a slider, perpendicular to its track.
+    pub slider_thickness: i32,
+    /// The minimum thickness of a progress bar.
+    pub progress_bar_thickness: i32,
+    /// The side length of the box drawn next to check boxes and radio buttons.
+    pub indicator_size: i32,
+}
+
+impl ControlMetrics {
+    /// Round every metric up so that it covers a whole number of physical pixels at the given
+    /// scale factor, which keeps the edges of thin controls from getting blurred at fractional
+    /// scales.
+    pub fn snap_to_scale(self, scale_factor: f64) -> ControlMetrics {
+        let snap = |logical: i32| -> i32 {
+            if scale_factor <= 0.0 {
+                return logical;
+            }
+            let physical = (logical as f64 * scale_factor).ceil();
+            let mut snapped = logical;
+            // Find the smallest logical size at or above `logical` that lands on a physical pixel
+            // boundary, giving up after a few pixels since some scale factors never do.
+            for candidate in logical..logical + 4 {
+                let scaled = candidate as f64 * scale_factor;
+                if scaled >= physical && (scaled - scaled.round()).abs() < 1e-6 {
+                    snapped = candidate;
+                    break;
+                }
+            }
+            snapped
+        };
+
+        ControlMetrics {
+            control_height: snap(self.control_height),
+            slider_length: snap(self.slider_length),
+            slider_thickness: snap(self.slider_thickness),
+            progress_bar_thickness: snap(self.progress_bar_thickness),
+            indicator_size: snap(self.indicator_size),
+        }
+    }
+}
+
+impl Default for ControlMetrics {
+    fn default() -> ControlMetrics {
+        ControlMetrics {
+            control_height: 24,
+            slider_length: 64,
+            slider_thickness: 16,
+            progress_bar_thickness: 16,
+            indicator_size: 16,
+        }
+    }
+}
+
+/// A piece of text that's drawn with its own style, as part of a larger paragraph.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TextRun {
+    pub text: String,
+    pub style: TextStyle,
+}
+
+/// Overrides for the theme's text style. Fields that are `None` use the theme's value.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextStyle {
+    /// The RGBA color of the text.
+    pub color: Option<[u8; 4]>,
+    /// The weight of the font, on the usual 100 to 900 scale. `400` is normal and `700` is bold.
+    pub weight: Option<u16>,
+    pub italic: Option<bool>,
 }
 
 #[derive(Debug, Clone)]
@@ -49,6 +468,16 @@ pub struct CursorData {
     pub draw_cursor: bool,
     pub cursor_pos: usize,
     pub highlight_range: Range<usize>,
+    pub caret_shape: CaretShape,
+}
+
+/// How the text caret gets drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CaretShape {
+    /// A thin line between two graphemes, used when typing inserts text.
+    Bar,
+    /// A box covering the grapheme after the cursor, used when typing overwrites text.
+    Block,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -66,6 +495,11 @@ pub enum CursorOp {
     SelectAll,
     UnselectAll,
     InsertChar(char),
+    /// Replace the grapheme cluster after the cursor with the character.
+    ///
+    /// If text is selected, the selection gets replaced instead. If the cursor is at the end of a
+    /// line, the character is inserted without replacing the line break.
+    OverwriteChar(char),
     InsertString(String),
     DeleteChars {
         dist: isize,
@@ -74,15 +508,32 @@ pub enum CursorOp {
     DeleteSelection,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct LayoutResult {
     pub size_bounds: SizeBounds,
     /// The rectangle child content widgets should be put in.
     pub content_rect: BoundBox<D2, i32>,
+    /// The positions of the glyphs in the last string that was prepared, if the renderer tracks
+    /// them. Text widgets use this to place the caret and to map clicks to string indices.
+    pub text_layout: Option<TextLayoutResult>,
 }
 
 pub trait RendererLayout {
+    /// Set how text prepared after this call gets broken into lines once it's wider than
+    /// `max_width`.
+    ///
+    /// Renderers that wrap text should break lines the same way `wrap_lines` does, so that text
+    /// wraps consistently regardless of which renderer draws it. The default implementation
+    /// ignores the wrap mode.
+    fn set_text_wrap(&mut self, _mode: WrapMode, _max_width: i32) {}
     fn prepare_string(&mut self, string: &str);
+    /// Lay out the runs as a single paragraph, with each run drawn in its own style.
+    ///
+    /// The default implementation ignores the styles and lays the runs out as a plain string.
+    fn prepare_rich_string(&mut self, runs: &[TextRun]) {
+        let string = runs.iter().map(|r| &*r.text).collect::<String>();
+        self.prepare_string(&string);
+    }
     /// Layout the render string and perform any queued cursor operations.
     fn prepare_edit_string(
         &mut self,
@@ -91,14 +542,36 @@ pub trait RendererLayout {
         cursor_ops: impl Iterator<Item=CursorOp>,
     );
     fn prepare_icon(&mut self, icon_name: &str);
+    /// The minimum sizes the renderer wants built-in controls to have, snapped to the current
+    /// scale factor.
+    ///
+    /// Controls use these as a floor on the size bounds returned by `finish`, so that they lay out
+    /// at a sensible size even when they have no content to measure. The default implementation
+    /// returns `ControlMetrics::default()`.
+    fn control_metrics(&self) -> ControlMetrics {
+        ControlMetrics::default()
+    }
     /// Finish laying stuff out and retrieve widget-level layout parameters. Calling this more than
     /// once should panic.
     fn finish(&mut self) -> LayoutResult;
 }
 
+/// The parameters a widget passes to the renderer to describe how it should be drawn.
+///
+/// Themes form a fallback chain. When a widget gets rendered, the renderer is first asked to draw
+/// it with the theme returned by `WidgetRenderable::theme`. If the renderer doesn't implement
+/// `WidgetRenderer` for that theme, `fallback` is called and the renderer is asked to draw the
+/// widget with the returned theme instead, and so on down the chain. The first theme in the chain
+/// the renderer supports gets used; if the chain ends (by returning `None`, or by reaching `!`)
+/// before a supported theme is found, the widget isn't drawn.
+///
+/// This lets a specialized theme fall back to a more generic one: a renderer that only knows how
+/// to draw `ButtonTheme` can still draw widgets whose themes eventually fall back to it.
 pub trait WidgetTheme: 'static {
+    /// The next, more generic, theme in the fallback chain.
     type Fallback: WidgetTheme;
 
+    /// Convert this theme into the next theme in the fallback chain, if there is one.
     fn fallback(self) -> Option<Self::Fallback>;
 }
 
@@ -129,6 +602,7 @@ impl Renderer for ! {
 
 impl RendererLayout for ! {
     fn prepare_string(&mut self, _string: &str) {}
+    fn prepare_rich_string(&mut self, _: &[TextRun]) {}
     fn prepare_edit_string(
         &mut self,
         _: &mut String,
@@ -149,6 +623,53 @@ impl Default for CursorData {
             draw_cursor: false,
             cursor_pos: 0,
             highlight_range: 0..0,
+            caret_shape: CaretShape::Bar,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hit_mask() {
+        // A 2x2 image with an opaque top-left pixel and a half-transparent bottom-right pixel.
+        let mut image = Image::new(DimsBox::new2(2, 2));
+        image.set_pixel(0, 0, [0, 0, 0, 255]);
+        image.set_pixel(1, 1, [0, 0, 0, 100]);
+
+        let mask = HitMask::from_image(&image, 128);
+        let dims = DimsBox::new2(2, 2);
+        assert!(mask.hit(Point2::new(0, 0), dims));
+        assert!(!mask.hit(Point2::new(1, 0), dims));
+        assert!(!mask.hit(Point2::new(1, 1), dims));
+        assert!(!mask.hit(Point2::new(-1, 0), dims));
+        assert!(!mask.hit(Point2::new(2, 0), dims));
+        assert!(HitMask::from_image(&image, 100).hit(Point2::new(1, 1), dims));
+
+        // Stretched out to twice the size.
+        let dims = DimsBox::new2(4, 4);
+        assert!(mask.hit(Point2::new(1, 1), dims));
+        assert!(!mask.hit(Point2::new(2, 1), dims));
+    }
+    #[test]
+    fn snap_control_metrics() {
+        let metrics = ControlMetrics {
+            control_height: 24,
+            slider_length: 3,
+            slider_thickness: 16,
+            progress_bar_thickness: 1,
+            indicator_size: 0,
+        };
+        assert_eq!(metrics, metrics.snap_to_scale(1.0));
+        assert_eq!(metrics, metrics.snap_to_scale(2.0));
+
+        let snapped = metrics.snap_to_scale(1.5);
+        assert_eq!(24, snapped.control_height);
+        assert_eq!(4, snapped.slider_length);
+        assert_eq!(16, snapped.slider_thickness);
+        assert_eq!(2, snapped.progress_bar_thickness);
+        assert_eq!(0, snapped.indicator_size);
+    }
+}
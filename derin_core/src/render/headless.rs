@@ -0,0 +1,1376 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A renderer that lays out widgets without drawing anything.
+//!
+//! This is useful for measuring a widget tree's natural size before creating a window for it, and
+//! for testing layout code without a graphics context.
+//!
+//! Since there's no theme to draw with, each frame gets drawn as a wireframe on a transparent
+//! background. Every widget's rectangle is outlined in opaque white, or in grey if the widget is
+//! disabled. Text is drawn in the same color, with every grapheme that isn't whitespace drawn as a
+//! solid block filling its cell, and icons are drawn as outlined boxes. Glyphs get rasterized into
+//! a cache shared by every widget, so each grapheme only gets rasterized once per cell size. The
+//! outline is the widget's foreground layer, so it gets drawn over the widget's content and
+//! anything the widget fills in with `SubFrame::fill_rect` before calling `render_foreground`.
+//! There's no background layer. Reading that back with `ReadPixels` gives a deterministic snapshot
+//! of the tree's layout and of where its text lies.
+//!
+//! The primitives each widget gets drawn with are kept around between frames, so that widgets
+//! which haven't changed can be redrawn with `Renderer::replay_widget`. Frames drawn with a redraw
+//! region only get drawn within that region, with the rest of the last frame left as-is.
+//!
+//! Clip masks are supported, but aren't anti-aliased: a pixel gets drawn if its center lies within
+//! every pushed mask.
+
+use crate::{
+    render::{text_nav, Renderer, ReadPixels, Image, ClipMask, FocusRingStyle, ControlMetrics, WidgetRenderer, SubFrame, RendererLayout, LayoutResult, CursorData, CursorOp, WidgetTheme, WrapMode, wrap_lines, CaretPosition, TextLayoutResult, TextLine, DebugOverlayItem},
+    widget::WidgetId,
+};
+use cgmath_geometry::{
+    D2,
+    cgmath::{EuclideanSpace, Point2, Vector2},
+    rect::{BoundBox, DimsBox, GeoBox},
+};
+use derin_common_types::layout::SizeBounds;
+use fnv::FnvHashMap;
+use std::{mem, rc::Rc};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Fixed-size text metrics used by the headless renderer to measure strings.
+///
+/// Every character is assumed to have the same advance, so this gives an approximation of the
+/// size text would take up in a real renderer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextMetrics {
+    /// The horizontal advance of every character.
+    pub char_width: i32,
+    /// The distance between two lines of text.
+    pub line_height: i32,
+    /// The size of every icon.
+    pub icon_dims: DimsBox<D2, i32>,
+}
+
+/// The number of glyphs `HeadlessRenderer`'s glyph cache holds by default.
+pub const DEFAULT_GLYPH_CACHE_CAPACITY: usize = 1024;
+
+/// A `Renderer` that performs layout and draws widget outlines to an in-memory image.
+#[derive(Debug, Clone)]
+pub struct HeadlessRenderer {
+    pub metrics: TextMetrics,
+    pub focus_ring: FocusRingStyle,
+    /// The control sizes reported to widgets, before being snapped to the scale factor.
+    pub control_metrics: ControlMetrics,
+    dims: DimsBox<D2, u32>,
+    scale_factor: f64,
+    frame: Image,
+    /// The part of the window being drawn this frame, or `None` if the whole window is.
+    redraw_region: Option<BoundBox<D2, i32>>,
+    /// The clip masks that are currently pushed, in window coordinates.
+    clip_masks: Vec<ClipMask>,
+    /// The number of outlines and fills drawn since the frame started.
+    draw_calls: usize,
+    disabled: bool,
+    /// The text and icons each widget prepared the last time it was laid out.
+    contents: FnvHashMap<WidgetId, LaidOutContent>,
+    glyph_cache: GlyphCache,
+    /// The primitives drawn for every widget rendered or replayed this frame.
+    retained: FnvHashMap<WidgetId, RetainedWidget>,
+    /// The primitives drawn last frame, which can be replayed this frame.
+    last_retained: FnvHashMap<WidgetId, RetainedWidget>,
+}
+
+/// Something drawn by the headless renderer, in window coordinates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Primitive {
+    Outline {
+        rect: BoundBox<D2, i32>,
+        color: [u8; 4],
+    },
+    Fill {
+        rect: BoundBox<D2, i32>,
+        color: [u8; 4],
+    },
+    /// A grapheme's glyph, drawn as a block that fills the grapheme's cell.
+    Glyph {
+        cell: BoundBox<D2, i32>,
+        grapheme: String,
+        color: [u8; 4],
+    },
+    PushClipMask(ClipMask),
+    PopClipMask,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RetainedWidget {
+    clip: BoundBox<D2, i32>,
+    primitives: Vec<Primitive>,
+}
+
+/// Rasterized glyphs, shared between every widget the renderer draws.
+///
+/// Glyphs are keyed by grapheme and cell size, so a glyph only gets rasterized the first time it's
+/// drawn at a given size, no matter how many widgets draw it afterwards. Once the cache is full,
+/// the least recently used glyph gets evicted to make room.
+#[derive(Debug, Clone)]
+struct GlyphCache {
+    glyphs: FnvHashMap<DimsBox<D2, i32>, FnvHashMap<String, CachedGlyph>>,
+    len: usize,
+    capacity: usize,
+    /// Incremented on every lookup, to track when each glyph was last used.
+    clock: u64,
+    rasterized: usize,
+}
+
+#[derive(Debug, Clone)]
+struct CachedGlyph {
+    /// Which pixels of the glyph's cell get drawn, row by row.
+    coverage: Rc<[bool]>,
+    last_used: u64,
+}
+
+/// The content a widget prepared during layout, which gets drawn by `render_laid_out_content`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct LaidOutContent {
+    text: Option<(String, TextLayoutResult)>,
+    icon: Option<DimsBox<D2, i32>>,
+}
+
+/// The `RendererLayout` for `HeadlessRenderer`.
+#[derive(Debug, Clone)]
+pub struct HeadlessLayout {
+    metrics: TextMetrics,
+    control_metrics: ControlMetrics,
+    content_dims: DimsBox<D2, i32>,
+    wrap_mode: WrapMode,
+    wrap_width: i32,
+    text_layout: Option<TextLayoutResult>,
+    content: LaidOutContent,
+    finished: bool,
+}
+
+/// The `SubFrame` for `HeadlessRenderer`.
+///
+/// This records the primitives the widget draws, which get drawn to the frame once the widget has
+/// finished rendering.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadlessSubFrame {
+    transform: BoundBox<D2, i32>,
+    disabled: bool,
+    content: Option<LaidOutContent>,
+    primitives: Vec<Primitive>,
+    /// The number of clip masks the widget has pushed and not yet popped.
+    clip_masks_pushed: usize,
+    background_drawn: bool,
+    foreground_drawn: bool,
+}
+
+impl HeadlessRenderer {
+    pub fn new(metrics: TextMetrics) -> HeadlessRenderer {
+        HeadlessRenderer {
+            metrics,
+            focus_ring: FocusRingStyle::default(),
+            control_metrics: ControlMetrics::default(),
+            dims: DimsBox::new2(0, 0),
+            scale_factor: 1.0,
+            frame: Image::new(DimsBox::new2(0, 0)),
+            redraw_region: None,
+            clip_masks: Vec::new(),
+            draw_calls: 0,
+            disabled: false,
+            contents: FnvHashMap::default(),
+            glyph_cache: GlyphCache::new(DEFAULT_GLYPH_CACHE_CAPACITY),
+            retained: FnvHashMap::default(),
+            last_retained: FnvHashMap::default(),
+        }
+    }
+
+    /// The number of glyphs that have been rasterized since the renderer was created.
+    ///
+    /// Glyphs that are still in the glyph cache get reused instead of being rasterized again, so
+    /// this only goes up when a widget draws a grapheme that hasn't been drawn recently.
+    pub fn glyphs_rasterized(&self) -> usize {
+        self.glyph_cache.rasterized
+    }
+
+    /// The maximum number of glyphs kept in the glyph cache.
+    pub fn glyph_cache_capacity(&self) -> usize {
+        self.glyph_cache.capacity
+    }
+
+    /// Set the maximum number of glyphs kept in the glyph cache, evicting the least recently used
+    /// glyphs if the cache holds more than that. Defaults to `DEFAULT_GLYPH_CACHE_CAPACITY`.
+    pub fn set_glyph_cache_capacity(&mut self, capacity: usize) {
+        self.glyph_cache.capacity = capacity;
+        while self.glyph_cache.len > capacity {
+            self.glyph_cache.evict();
+        }
+    }
+
+    /// Outline `rect` in white, or grey if drawing a disabled widget, only drawing the pixels that
+    /// fall within `clip`.
+    #[cfg(test)]
+    fn draw_outline(&mut self, rect: BoundBox<D2, i32>, clip: BoundBox<D2, i32>) {
+        let color = shade(WHITE, self.disabled);
+        self.draw(&Primitive::Outline{ rect, color }, clip);
+    }
+
+    fn draw(&mut self, primitive: &Primitive, clip: BoundBox<D2, i32>) {
+        match *primitive {
+            Primitive::Outline{ rect, color } => self.draw_outline_color(rect, clip, color),
+            Primitive::Fill{ rect, color } => self.fill(rect, clip, color),
+            Primitive::Glyph{ cell, ref grapheme, color } => {
+                self.draw_calls += 1;
+                let coverage = self.glyph_cache.glyph(grapheme, cell.dims());
+                let width = cell.width().max(1) as usize;
+                for (i, _) in coverage.iter().enumerate().filter(|(_, covered)| **covered) {
+                    self.plot(cell.min.x + (i % width) as i32, cell.min.y + (i / width) as i32, clip, color);
+                }
+            },
+            Primitive::PushClipMask(ref mask) => self.clip_masks.push(mask.clone()),
+            Primitive::PopClipMask => {self.clip_masks.pop();},
+        }
+    }
+
+    /// Set the pixel at `(x, y)`, if it's within `clip` and the redraw region.
+    fn plot(&mut self, x: i32, y: i32, clip: BoundBox<D2, i32>, color: [u8; 4]) {
+        let contains = |r: BoundBox<D2, i32>| r.min.x <= x && x < r.max.x && r.min.y <= y && y < r.max.y;
+        let in_region = self.redraw_region.map(contains).unwrap_or(true);
+        let center = Point2::new(x as f64 + 0.5, y as f64 + 0.5);
+        let in_masks = self.clip_masks.iter().all(|mask| mask_contains(mask, center));
+        if 0 <= x && 0 <= y && contains(clip) && in_region && in_masks {
+            self.frame.set_pixel(x as u32, y as u32, color);
+        }
+    }
+
+    fn fill(&mut self, rect: BoundBox<D2, i32>, clip: BoundBox<D2, i32>, color: [u8; 4]) {
+        self.draw_calls += 1;
+        for y in rect.min.y..rect.max.y {
+            for x in rect.min.x..rect.max.x {
+                self.plot(x, y, clip, color);
+            }
+        }
+    }
+
+    fn draw_outline_color(&mut self, rect: BoundBox<D2, i32>, clip: BoundBox<D2, i32>, color: [u8; 4]) {
+        if rect.width() <= 0 || rect.height() <= 0 {
+            return;
+        }
+        self.draw_calls += 1;
+
+        let (left, right) = (rect.min.x, rect.max.x - 1);
+        let (top, bottom) = (rect.min.y, rect.max.y - 1);
+        for x in left..=right {
+            self.plot(x, top, clip, color);
+            self.plot(x, bottom, clip, color);
+        }
+        for y in top..=bottom {
+            self.plot(left, y, clip, color);
+            self.plot(right, y, clip, color);
+        }
+    }
+}
+
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+/// Get the color `color` gets drawn in. Disabled widgets get drawn in grey, at half the color's
+/// brightness.
+fn shade(color: [u8; 4], disabled: bool) -> [u8; 4] {
+    match disabled {
+        false => color,
+        true => {
+            let [r, g, b, a] = color;
+            let grey = ((r as u32 + g as u32 + b as u32) / 3 + 1) / 2;
+            [grey as u8, grey as u8, grey as u8, a]
+        }
+    }
+}
+
+impl GlyphCache {
+    fn new(capacity: usize) -> GlyphCache {
+        GlyphCache {
+            glyphs: FnvHashMap::default(),
+            len: 0,
+            capacity,
+            clock: 0,
+            rasterized: 0,
+        }
+    }
+
+    /// Get the coverage of `grapheme`'s glyph in a cell of size `dims`, rasterizing it if it isn't
+    /// in the cache.
+    fn glyph(&mut self, grapheme: &str, dims: DimsBox<D2, i32>) -> Rc<[bool]> {
+        self.clock += 1;
+        let clock = self.clock;
+        if let Some(glyph) = self.glyphs.get_mut(&dims).and_then(|g| g.get_mut(grapheme)) {
+            glyph.last_used = clock;
+            return glyph.coverage.clone();
+        }
+
+        while self.len > 0 && self.len >= self.capacity {
+            self.evict();
+        }
+        let coverage = GlyphCache::rasterize(dims);
+        self.rasterized += 1;
+        if self.capacity > 0 {
+            self.len += 1;
+            self.glyphs.entry(dims).or_insert_with(FnvHashMap::default)
+                .insert(grapheme.to_string(), CachedGlyph{ coverage: coverage.clone(), last_used: clock });
+        }
+        coverage
+    }
+
+    /// Draw a glyph as a block filling its cell, leaving a pixel of space around the block so that
+    /// neighboring glyphs can be told apart.
+    fn rasterize(dims: DimsBox<D2, i32>) -> Rc<[bool]> {
+        let (width, height) = (dims.width().max(0), dims.height().max(0));
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| 0 < x && x < width - 1 && 0 < y && y < height - 1))
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    /// Remove the least recently used glyph.
+    fn evict(&mut self) {
+        let oldest = self.glyphs.iter()
+            .flat_map(|(dims, glyphs)| glyphs.iter().map(move |(grapheme, glyph)| (glyph.last_used, *dims, grapheme)))
+            .min_by_key(|&(last_used, _, _)| last_used)
+            .map(|(_, dims, grapheme)| (dims, grapheme.clone()));
+
+        if let Some((dims, grapheme)) = oldest {
+            let glyphs = self.glyphs.get_mut(&dims).unwrap();
+            glyphs.remove(&grapheme);
+            if glyphs.is_empty() {
+                self.glyphs.remove(&dims);
+            }
+            self.len -= 1;
+        }
+    }
+}
+
+/// Move `pos` back to the nearest `char` boundary in `string`, if it isn't on one already.
+fn clamp_to_string(string: &str, pos: usize) -> usize {
+    let mut pos = pos.min(string.len());
+    while !string.is_char_boundary(pos) {
+        pos -= 1;
+    }
+    pos
+}
+
+/// Move the cursor to `pos`. If `expand_selection` is `true`, the end of the selection the cursor
+/// isn't at stays put, and the selection grows or shrinks to reach the cursor. Otherwise, the
+/// selection gets cleared.
+fn move_cursor(cursor: &mut CursorData, pos: usize, expand_selection: bool) {
+    let selection = cursor.highlight_range.clone();
+    let anchor = match expand_selection {
+        false => pos,
+        true if selection.len() == 0 => cursor.cursor_pos,
+        true if selection.start == cursor.cursor_pos => selection.end,
+        true => selection.start,
+    };
+    cursor.cursor_pos = pos;
+    cursor.highlight_range = anchor.min(pos)..anchor.max(pos);
+}
+
+/// Remove the selected text, placing the cursor where it was. Returns `false` if nothing was
+/// selected.
+fn delete_selection(string: &mut String, cursor: &mut CursorData) -> bool {
+    let selection = cursor.highlight_range.clone();
+    if selection.len() == 0 {
+        return false;
+    }
+    string.drain(selection.clone());
+    cursor.cursor_pos = selection.start;
+    cursor.highlight_range = selection.start..selection.start;
+    true
+}
+
+/// Replace the selected text with `s`, or insert `s` at the cursor if nothing is selected, placing
+/// the cursor after the new text.
+fn insert_str(string: &mut String, cursor: &mut CursorData, s: &str) {
+    delete_selection(string, cursor);
+    string.insert_str(cursor.cursor_pos, s);
+    cursor.cursor_pos += s.len();
+    cursor.highlight_range = cursor.cursor_pos..cursor.cursor_pos;
+}
+
+/// Whether `point` lies within `mask`.
+fn mask_contains(mask: &ClipMask, point: Point2<f64>) -> bool {
+    match *mask {
+        ClipMask::RoundedRect{ rect, radius } => {
+            let (min_x, min_y) = (rect.min.x as f64, rect.min.y as f64);
+            let (max_x, max_y) = (rect.max.x as f64, rect.max.y as f64);
+            if point.x < min_x || max_x <= point.x || point.y < min_y || max_y <= point.y {
+                return false;
+            }
+
+            // Points within the corners have to be within `radius` of the corner's center.
+            let radius = (radius as f64).min((max_x - min_x) / 2.0).min((max_y - min_y) / 2.0).max(0.0);
+            let corner_center = Point2::new(
+                point.x.max(min_x + radius).min(max_x - radius),
+                point.y.max(min_y + radius).min(max_y - radius),
+            );
+            let (dx, dy) = (point.x - corner_center.x, point.y - corner_center.y);
+            dx * dx + dy * dy <= radius * radius
+        },
+        // Use the even-odd rule, by counting the edges a ray cast to the right of the point crosses.
+        ClipMask::Polygon(ref vertices) => {
+            let mut inside = false;
+            for (i, a) in vertices.iter().enumerate() {
+                let b = vertices[(i + 1) % vertices.len()];
+                let a = Point2::new(a.x as f64, a.y as f64);
+                let b = Point2::new(b.x as f64, b.y as f64);
+                if (a.y > point.y) != (b.y > point.y) {
+                    let crossing_x = a.x + (point.y - a.y) * (b.x - a.x) / (b.y - a.y);
+                    if point.x < crossing_x {
+                        inside = !inside;
+                    }
+                }
+            }
+            inside
+        }
+    }
+}
+
+/// Move `mask` by `offset`.
+fn offset_mask(mask: ClipMask, offset: Vector2<i32>) -> ClipMask {
+    match mask {
+        ClipMask::RoundedRect{ rect, radius } => ClipMask::RoundedRect{ rect: rect + offset, radius },
+        ClipMask::Polygon(vertices) => ClipMask::Polygon(vertices.into_iter().map(|v| v + offset).collect()),
+    }
+}
+
+impl HeadlessLayout {
+    pub fn new(metrics: TextMetrics) -> HeadlessLayout {
+        HeadlessLayout {
+            metrics,
+            control_metrics: ControlMetrics::default(),
+            content_dims: DimsBox::new2(0, 0),
+            wrap_mode: WrapMode::None,
+            wrap_width: 0,
+            text_layout: None,
+            content: LaidOutContent::default(),
+            finished: false,
+        }
+    }
+
+    /// Expand the content to fit the given dimensions.
+    fn fit_dims(&mut self, dims: DimsBox<D2, i32>) {
+        self.content_dims.dims.x = i32::max(self.content_dims.width(), dims.width());
+        self.content_dims.dims.y = i32::max(self.content_dims.height(), dims.height());
+    }
+
+    /// Find the caret positions in the string, assuming every character has the same width.
+    fn lay_out_string(&self, string: &str) -> TextLayoutResult {
+        let TextMetrics{ char_width, line_height, .. } = self.metrics;
+        let lines = wrap_lines(string, self.wrap_mode, self.wrap_width, |g| g.chars().count() as i32 * char_width)
+            .into_iter()
+            .enumerate()
+            .map(|(line_index, range)| {
+                let mut x = 0;
+                let mut carets = Vec::new();
+                for (index, grapheme) in string[range.clone()].grapheme_indices(true) {
+                    carets.push(CaretPosition{ index: range.start + index, x });
+                    x += grapheme.chars().count() as i32 * char_width;
+                }
+                carets.push(CaretPosition{ index: range.end, x });
+
+                TextLine {
+                    range,
+                    top: line_index as i32 * line_height,
+                    height: line_height,
+                    carets,
+                }
+            })
+            .collect();
+        TextLayoutResult::new(lines)
+    }
+
+    /// Perform `op` on `string`, moving the cursor and selection to match.
+    fn apply_cursor_op(&self, string: &mut String, cursor: &mut CursorData, op: CursorOp) {
+        let selection = cursor.highlight_range.clone();
+        match op {
+            CursorOp::MoveHorizontal{ delta, expand_selection, jump_to_word_boundaries } => {
+                let pos = match (selection.len() == 0 || expand_selection, delta.signum()) {
+                    (_, 0) => return,
+                    (true, _) => text_nav::move_by(string, cursor.cursor_pos, delta, jump_to_word_boundaries),
+                    // Moving without expanding the selection collapses it in the direction of travel.
+                    (false, 1) => selection.end,
+                    (false, _) => selection.start,
+                };
+                move_cursor(cursor, pos, expand_selection);
+            },
+            CursorOp::MoveVertical{ delta, expand_selection } => {
+                let text_layout = self.lay_out_string(string);
+                let lines = text_layout.lines();
+                let target = text_layout.point_at_index(cursor.cursor_pos).and_then(|caret| {
+                    let line = lines.iter().position(|line| line.top == caret.y)?;
+                    let target_line = (line as isize + delta).max(0).min(lines.len() as isize - 1) as usize;
+                    text_layout.index_at_point(Point2::new(caret.x, lines[target_line].top))
+                });
+                if let Some(pos) = target {
+                    move_cursor(cursor, pos, expand_selection);
+                }
+            },
+            CursorOp::SelectOnSegment(segment) => {
+                let text_layout = self.lay_out_string(string);
+                let start = text_layout.index_at_point(segment.start).unwrap_or(0);
+                let end = text_layout.index_at_point(segment.end).unwrap_or(0);
+                cursor.highlight_range = start.min(end)..start.max(end);
+                cursor.cursor_pos = end;
+            },
+            CursorOp::SelectAll => {
+                cursor.highlight_range = 0..string.len();
+                cursor.cursor_pos = string.len();
+            },
+            CursorOp::UnselectAll => cursor.highlight_range = cursor.cursor_pos..cursor.cursor_pos,
+            CursorOp::InsertChar(c) => insert_str(string, cursor, c.encode_utf8(&mut [0; 4])),
+            CursorOp::OverwriteChar(c) => {
+                if selection.len() == 0 {
+                    // Line breaks don't get overwritten, so typing at the end of a line extends it.
+                    let pos = cursor.cursor_pos;
+                    let overwritten = string[pos..].graphemes(true).next()
+                        .filter(|g| *g != "\n" && *g != "\r\n")
+                        .map(|g| g.len())
+                        .unwrap_or(0);
+                    string.drain(pos..pos + overwritten);
+                }
+                insert_str(string, cursor, c.encode_utf8(&mut [0; 4]));
+            },
+            CursorOp::InsertString(s) => insert_str(string, cursor, &s),
+            CursorOp::DeleteChars{ dist, jump_to_word_boundaries } => {
+                if !delete_selection(string, cursor) {
+                    let pos = text_nav::move_by(string, cursor.cursor_pos, dist, jump_to_word_boundaries);
+                    let range = pos.min(cursor.cursor_pos)..pos.max(cursor.cursor_pos);
+                    string.drain(range.clone());
+                    cursor.cursor_pos = range.start;
+                    cursor.highlight_range = range.start..range.start;
+                }
+            },
+            CursorOp::DeleteSelection => {delete_selection(string, cursor);},
+        }
+    }
+
+    fn measure_string(&self, string: &str) -> DimsBox<D2, i32> {
+        let char_width = self.metrics.char_width;
+        let measure = |s: &str| s.chars().count() as i32 * char_width;
+
+        let mut num_lines = 0;
+        let mut max_line_width = 0;
+        match self.wrap_mode {
+            WrapMode::None => for line in string.lines() {
+                num_lines += 1;
+                max_line_width = i32::max(max_line_width, measure(line));
+            },
+            mode => for line in wrap_lines(string, mode, self.wrap_width, measure) {
+                num_lines += 1;
+                // Trailing whitespace hangs off the end of wrapped lines.
+                max_line_width = i32::max(max_line_width, measure(string[line].trim_end()));
+            }
+        }
+        // Empty strings still take up a line.
+        let num_lines = i32::max(num_lines, 1);
+
+        DimsBox::new2(max_line_width, num_lines * self.metrics.line_height)
+    }
+}
+
+impl Default for TextMetrics {
+    fn default() -> TextMetrics {
+        TextMetrics {
+            char_width: 8,
+            line_height: 16,
+            icon_dims: DimsBox::new2(16, 16),
+        }
+    }
+}
+
+impl Renderer for HeadlessRenderer {
+    type SubFrame = HeadlessSubFrame;
+    type Theme = ();
+    type Layout = HeadlessLayout;
+
+    fn resized(&mut self, new_size: DimsBox<D2, u32>) {
+        self.dims = new_size;
+    }
+    fn dims(&self) -> DimsBox<D2, u32> {
+        self.dims
+    }
+    fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+    fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
+    }
+    fn widget_removed(&mut self, widget_id: WidgetId) {
+        self.contents.remove(&widget_id);
+        self.retained.remove(&widget_id);
+        self.last_retained.remove(&widget_id);
+    }
+    fn layout(
+        &mut self,
+        widget_id: WidgetId,
+        layout: impl FnOnce(&mut HeadlessLayout)
+    ) {
+        let mut headless_layout = HeadlessLayout::new(self.metrics);
+        headless_layout.control_metrics = self.control_metrics.snap_to_scale(self.scale_factor);
+        layout(&mut headless_layout);
+        self.contents.insert(widget_id, headless_layout.content);
+    }
+    fn set_redraw_region(&mut self, region: Option<BoundBox<D2, i32>>) {
+        self.redraw_region = region;
+    }
+    fn start_frame(&mut self, _: &()) {
+        self.clip_masks.clear();
+        // The rest of the last frame can only be kept if it's the same size as this frame.
+        match self.redraw_region {
+            Some(region) if self.frame.dims() == self.dims => {
+                let window_rect = BoundBox::new2(0, 0, self.dims.width() as i32, self.dims.height() as i32);
+                self.fill(region, window_rect, [0; 4]);
+            },
+            _ => {
+                self.frame = Image::new(self.dims);
+                self.redraw_region = None;
+            }
+        }
+        self.draw_calls = 0;
+        self.last_retained = mem::replace(&mut self.retained, FnvHashMap::default());
+    }
+    fn finish_frame(&mut self, _: &()) {}
+    fn render_focus_ring(&mut self, _: &(), rect: BoundBox<D2, i32>, clip: BoundBox<D2, i32>) {
+        let style = self.focus_ring;
+        let outer = style.ring_rect(rect);
+        for i in 0..style.width {
+            let ring = BoundBox::new2(outer.min.x + i, outer.min.y + i, outer.max.x - i, outer.max.y - i);
+            self.draw_outline_color(ring, clip, style.color);
+        }
+    }
+    fn set_disabled(&mut self, _: &(), disabled: bool) {
+        self.disabled = disabled;
+    }
+    /// Counts every outline and filled rectangle, including each glyph.
+    fn draw_call_count(&self) -> Option<usize> {
+        Some(self.draw_calls)
+    }
+    fn clip_mask_depth(&self) -> usize {
+        self.clip_masks.len()
+    }
+    fn pop_clip_mask(&mut self) {
+        self.clip_masks.pop();
+    }
+    fn replay_widget(&mut self, _: &(), widget_id: WidgetId) -> bool {
+        match self.last_retained.remove(&widget_id) {
+            Some(retained) => {
+                for primitive in &retained.primitives {
+                    self.draw(primitive, retained.clip);
+                }
+                self.retained.insert(widget_id, retained);
+                true
+            },
+            None => false
+        }
+    }
+    /// Outline every widget in yellow, the hovered widget in green, and the focused widget in cyan.
+    ///
+    /// The outlines aren't clipped, so widgets that extend past their parents are fully visible.
+    /// Labels aren't drawn, since they'd be unreadable when drawn with the headless renderer's
+    /// block glyphs.
+    fn render_debug_overlay(&mut self, _: &(), items: &[DebugOverlayItem]) {
+        let window_rect = BoundBox::new2(0, 0, self.dims.width() as i32, self.dims.height() as i32);
+        for item in items {
+            let color = match (item.focused, item.hovered) {
+                (true, _) => [0, 255, 255, 255],
+                (false, true) => [0, 255, 0, 255],
+                (false, false) => [255, 255, 0, 255],
+            };
+            self.draw_outline_color(item.rect, window_rect, color);
+        }
+    }
+}
+
+impl ReadPixels for HeadlessRenderer {
+    fn read_pixels(&mut self) -> Image {
+        self.frame.clone()
+    }
+}
+
+impl<T: WidgetTheme> WidgetRenderer<T> for HeadlessRenderer {
+    fn render_widget(
+        &mut self,
+        widget_id: WidgetId,
+        _: &(),
+        transform: BoundBox<D2, i32>,
+        clip: BoundBox<D2, i32>,
+        _: T,
+        render_widget: impl FnOnce(&mut HeadlessSubFrame),
+    ) {
+        let mut sub_frame = HeadlessSubFrame {
+            transform,
+            disabled: self.disabled,
+            content: self.contents.remove(&widget_id),
+            primitives: Vec::new(),
+            clip_masks_pushed: 0,
+            background_drawn: false,
+            foreground_drawn: false,
+        };
+        render_widget(&mut sub_frame);
+        sub_frame.render_foreground();
+
+        if let Some(content) = sub_frame.content {
+            self.contents.insert(widget_id, content);
+        }
+        for primitive in &sub_frame.primitives {
+            self.draw(primitive, clip);
+        }
+        self.retained.insert(widget_id, RetainedWidget{ clip, primitives: sub_frame.primitives });
+    }
+}
+
+impl RendererLayout for HeadlessLayout {
+    fn set_text_wrap(&mut self, mode: WrapMode, max_width: i32) {
+        self.wrap_mode = mode;
+        self.wrap_width = max_width;
+    }
+
+    fn prepare_string(&mut self, string: &str) {
+        let dims = self.measure_string(string);
+        self.fit_dims(dims);
+        let text_layout = self.lay_out_string(string);
+        self.content.text = Some((string.to_string(), text_layout.clone()));
+        self.text_layout = Some(text_layout);
+    }
+
+    /// Performs the cursor operations on the string, then lays it out. Operations that place the
+    /// cursor at a point use the same fixed-width layout the string gets drawn with.
+    fn prepare_edit_string(
+        &mut self,
+        string: &mut String,
+        cursor_data: &mut CursorData,
+        cursor_ops: impl Iterator<Item=CursorOp>,
+    ) {
+        // The string may have been changed since the cursor was last placed.
+        cursor_data.cursor_pos = clamp_to_string(string, cursor_data.cursor_pos);
+        cursor_data.highlight_range =
+            clamp_to_string(string, cursor_data.highlight_range.start)..clamp_to_string(string, cursor_data.highlight_range.end);
+
+        for op in cursor_ops {
+            self.apply_cursor_op(string, cursor_data, op);
+        }
+        self.prepare_string(string);
+    }
+
+    fn prepare_icon(&mut self, _: &str) {
+        let icon_dims = self.metrics.icon_dims;
+        self.fit_dims(icon_dims);
+        self.content.icon = Some(icon_dims);
+    }
+
+    fn control_metrics(&self) -> ControlMetrics {
+        self.control_metrics
+    }
+
+    fn finish(&mut self) -> LayoutResult {
+        assert!(!self.finished, "`finish` called more than once");
+        self.finished = true;
+
+        LayoutResult {
+            size_bounds: SizeBounds::new_min(self.content_dims),
+            content_rect: BoundBox::from(self.content_dims),
+            text_layout: self.text_layout.take(),
+        }
+    }
+}
+
+impl SubFrame for HeadlessSubFrame {
+    fn render_background(&mut self) {
+        // The headless renderer doesn't draw backgrounds, but this still has to be tracked so
+        // that the layers get drawn in the right order.
+        self.background_drawn = true;
+    }
+
+    fn render_laid_out_content(&mut self) {
+        if !self.background_drawn {
+            self.render_background();
+        }
+        let content = match self.content {
+            Some(ref content) => content,
+            None => return
+        };
+        let (origin, color) = (self.transform.min, shade(WHITE, self.disabled));
+
+        if let Some(icon_dims) = content.icon {
+            self.primitives.push(Primitive::Outline {
+                rect: BoundBox::new2(origin.x, origin.y, origin.x + icon_dims.width(), origin.y + icon_dims.height()),
+                color,
+            });
+        }
+        if let Some((ref string, ref text_layout)) = content.text {
+            for line in text_layout.lines() {
+                for carets in line.carets.windows(2) {
+                    let grapheme = &string[carets[0].index..carets[1].index];
+                    if grapheme.trim().is_empty() {
+                        continue;
+                    }
+                    self.primitives.push(Primitive::Glyph {
+                        cell: BoundBox::new2(
+                            origin.x + carets[0].x,
+                            origin.y + line.top,
+                            origin.x + carets[1].x,
+                            origin.y + line.top + line.height,
+                        ),
+                        grapheme: grapheme.to_string(),
+                        color,
+                    });
+                }
+            }
+        }
+    }
+
+    fn render_foreground(&mut self) {
+        if !self.background_drawn {
+            self.render_background();
+        }
+        if !self.foreground_drawn {
+            self.foreground_drawn = true;
+            self.primitives.push(Primitive::Outline {
+                rect: self.transform,
+                color: shade(WHITE, self.disabled),
+            });
+        }
+    }
+
+    fn fill_rect(&mut self, rect: BoundBox<D2, i32>, color: [u8; 4]) -> bool {
+        self.primitives.push(Primitive::Fill {
+            rect: rect + self.transform.min.to_vec(),
+            color: shade(color, self.disabled),
+        });
+        true
+    }
+
+    fn push_clip_mask(&mut self, mask: ClipMask) -> bool {
+        self.clip_masks_pushed += 1;
+        self.primitives.push(Primitive::PushClipMask(offset_mask(mask, self.transform.min.to_vec())));
+        true
+    }
+
+    fn pop_clip_mask(&mut self) {
+        // Widgets can't pop the masks their ancestors pushed.
+        if self.clip_masks_pushed > 0 {
+            self.clip_masks_pushed -= 1;
+            self.primitives.push(Primitive::PopClipMask);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::{TextRun, TextStyle};
+    use cgmath_geometry::{cgmath::Point2, line::Segment};
+
+    #[test]
+    fn measure_strings() {
+        let metrics = TextMetrics {
+            char_width: 5,
+            line_height: 10,
+            icon_dims: DimsBox::new2(12, 12),
+        };
+
+        let mut layout = HeadlessLayout::new(metrics);
+        layout.prepare_string("");
+        assert_eq!(DimsBox::new2(0, 10), layout.finish().size_bounds.min);
+
+        let mut layout = HeadlessLayout::new(metrics);
+        layout.prepare_string("four\nsixsix\n");
+        let result = layout.finish();
+        assert_eq!(DimsBox::new2(30, 20), result.size_bounds.min);
+        assert_eq!(BoundBox::new2(0, 0, 30, 20), result.content_rect);
+
+        let mut layout = HeadlessLayout::new(metrics);
+        layout.prepare_string("a");
+        layout.prepare_icon("icon");
+        assert_eq!(DimsBox::new2(12, 12), layout.finish().size_bounds.min);
+
+        // Rich text runs get laid out as one paragraph.
+        let mut layout = HeadlessLayout::new(metrics);
+        layout.prepare_rich_string(&[
+            TextRun{ text: "fo".to_string(), style: TextStyle::default() },
+            TextRun{ text: "ur\nsix".to_string(), style: TextStyle{ weight: Some(700), ..TextStyle::default() } },
+            TextRun{ text: "six\n".to_string(), style: TextStyle::default() },
+        ]);
+        assert_eq!(DimsBox::new2(30, 20), layout.finish().size_bounds.min);
+    }
+
+    #[test]
+    fn measure_wrapped_strings() {
+        let metrics = TextMetrics {
+            char_width: 5,
+            line_height: 10,
+            icon_dims: DimsBox::new2(12, 12),
+        };
+
+        let mut layout = HeadlessLayout::new(metrics);
+        layout.set_text_wrap(WrapMode::Word, 40);
+        layout.prepare_string("one two three");
+        assert_eq!(DimsBox::new2(35, 20), layout.finish().size_bounds.min);
+
+        let mut layout = HeadlessLayout::new(metrics);
+        layout.set_text_wrap(WrapMode::Word, 20);
+        layout.prepare_string("ab abcdefgh");
+        assert_eq!(DimsBox::new2(20, 30), layout.finish().size_bounds.min);
+    }
+
+    #[test]
+    fn text_layout() {
+        let metrics = TextMetrics {
+            char_width: 5,
+            line_height: 10,
+            icon_dims: DimsBox::new2(12, 12),
+        };
+
+        let mut layout = HeadlessLayout::new(metrics);
+        layout.prepare_string("ab\nc");
+        let text_layout = layout.finish().text_layout.unwrap();
+        assert_eq!(2, text_layout.lines().len());
+
+        assert_eq!(Some(Point2::new(0, 0)), text_layout.point_at_index(0));
+        assert_eq!(Some(Point2::new(10, 0)), text_layout.point_at_index(2));
+        assert_eq!(Some(Point2::new(5, 10)), text_layout.point_at_index(4));
+        assert_eq!(None, text_layout.point_at_index(5));
+
+        assert_eq!(Some(1), text_layout.index_at_point(Point2::new(6, 3)));
+        // Points past the end of a line go to the end of the line.
+        assert_eq!(Some(2), text_layout.index_at_point(Point2::new(100, 3)));
+        // Points below the last line go to the last line.
+        assert_eq!(Some(3), text_layout.index_at_point(Point2::new(-4, 50)));
+
+        // Icons don't produce a text layout.
+        let mut layout = HeadlessLayout::new(metrics);
+        layout.prepare_icon("icon");
+        assert_eq!(None, layout.finish().text_layout);
+    }
+
+    #[test]
+    fn edit_string() {
+        let metrics = TextMetrics {
+            char_width: 5,
+            line_height: 10,
+            icon_dims: DimsBox::new2(12, 12),
+        };
+        let edit = |string: &mut String, cursor: &mut CursorData, ops: Vec<CursorOp>| {
+            let mut layout = HeadlessLayout::new(metrics);
+            layout.prepare_edit_string(string, cursor, ops.into_iter());
+        };
+        let horizontal = |delta, expand_selection| CursorOp::MoveHorizontal {
+            delta,
+            expand_selection,
+            jump_to_word_boundaries: false,
+        };
+
+        // An "e" with a combining acute accent gets moved over and deleted as a single character.
+        let mut string = "ae\u{301}b".to_string();
+        let mut cursor = CursorData::default();
+        edit(&mut string, &mut cursor, vec![horizontal(1, false), horizontal(1, false)]);
+        assert_eq!(4, cursor.cursor_pos);
+        edit(&mut string, &mut cursor, vec![CursorOp::DeleteChars{ dist: -1, jump_to_word_boundaries: false }]);
+        assert_eq!("ab", string);
+        assert_eq!(1, cursor.cursor_pos);
+
+        // Selections grow from the cursor, and get replaced by typed text.
+        edit(&mut string, &mut cursor, vec![horizontal(-1, true)]);
+        assert_eq!(0..1, cursor.highlight_range);
+        edit(&mut string, &mut cursor, vec![CursorOp::InsertChar('x')]);
+        assert_eq!("xb", string);
+        assert_eq!(1, cursor.cursor_pos);
+        assert_eq!(1..1, cursor.highlight_range);
+
+        // Overwriting replaces whole grapheme clusters, but not line breaks.
+        let mut string = "ae\u{301}\nb".to_string();
+        let mut cursor = CursorData{ cursor_pos: 1, highlight_range: 1..1, ..CursorData::default() };
+        edit(&mut string, &mut cursor, vec![CursorOp::OverwriteChar('x'), CursorOp::OverwriteChar('y')]);
+        assert_eq!("axy\nb", string);
+        assert_eq!(3, cursor.cursor_pos);
+
+        // Vertical movement and mouse selection go through the string's layout.
+        edit(&mut string, &mut cursor, vec![CursorOp::MoveVertical{ delta: 1, expand_selection: false }]);
+        assert_eq!(5, cursor.cursor_pos);
+        edit(&mut string, &mut cursor, vec![CursorOp::SelectOnSegment(Segment::new(Point2::new(4, 2), Point2::new(100, 12)))]);
+        assert_eq!(1..5, cursor.highlight_range);
+        assert_eq!(5, cursor.cursor_pos);
+        edit(&mut string, &mut cursor, vec![CursorOp::SelectAll, CursorOp::DeleteSelection]);
+        assert_eq!("", string);
+        assert_eq!(0, cursor.cursor_pos);
+
+        // Cursors left past the end of a string that's been changed get moved back into it.
+        let mut string = "ab".to_string();
+        let mut cursor = CursorData{ cursor_pos: 10, highlight_range: 1..10, ..CursorData::default() };
+        edit(&mut string, &mut cursor, vec![]);
+        assert_eq!(2, cursor.cursor_pos);
+        assert_eq!(1..2, cursor.highlight_range);
+    }
+
+    #[test]
+    fn outline() {
+        let mut renderer = HeadlessRenderer::new(TextMetrics::default());
+        renderer.resized(DimsBox::new2(8, 8));
+        renderer.start_frame(&());
+        renderer.draw_outline(BoundBox::new2(1, 1, 5, 5), BoundBox::new2(0, 0, 8, 4));
+        renderer.finish_frame(&());
+
+        let image = renderer.read_pixels();
+        assert_eq!(DimsBox::new2(8, 8), image.dims());
+        assert_eq!(Some([255; 4]), image.pixel(1, 1));
+        assert_eq!(Some([255; 4]), image.pixel(4, 3));
+        assert_eq!(Some([0; 4]), image.pixel(2, 2));
+        // Clipped out.
+        assert_eq!(Some([0; 4]), image.pixel(1, 4));
+        assert_eq!(None, image.pixel(8, 0));
+    }
+
+    #[test]
+    fn focus_ring() {
+        let mut renderer = HeadlessRenderer::new(TextMetrics::default());
+        renderer.focus_ring = FocusRingStyle {
+            color: [255, 0, 0, 255],
+            width: 2,
+            offset: 1,
+        };
+        renderer.resized(DimsBox::new2(10, 10));
+        renderer.start_frame(&());
+        renderer.render_focus_ring(&(), BoundBox::new2(3, 3, 7, 7), BoundBox::new2(0, 0, 10, 10));
+        renderer.finish_frame(&());
+
+        let image = renderer.read_pixels();
+        assert_eq!(Some([255, 0, 0, 255]), image.pixel(2, 2));
+        assert_eq!(Some([255, 0, 0, 255]), image.pixel(3, 5));
+        assert_eq!(Some([255, 0, 0, 255]), image.pixel(7, 7));
+        assert_eq!(Some([0; 4]), image.pixel(1, 1));
+        assert_eq!(Some([0; 4]), image.pixel(4, 4));
+    }
+
+    #[test]
+    fn disabled() {
+        struct TestTheme;
+        impl WidgetTheme for TestTheme {
+            type Fallback = !;
+            fn fallback(self) -> Option<!> {
+                None
+            }
+        }
+
+        let mut renderer = HeadlessRenderer::new(TextMetrics::default());
+        renderer.resized(DimsBox::new2(10, 10));
+        renderer.start_frame(&());
+        renderer.set_disabled(&(), true);
+        renderer.render_widget(WidgetId::new(), &(), BoundBox::new2(0, 0, 4, 4), BoundBox::new2(0, 0, 10, 10), TestTheme, |_| ());
+        renderer.set_disabled(&(), false);
+        renderer.render_widget(WidgetId::new(), &(), BoundBox::new2(5, 5, 9, 9), BoundBox::new2(0, 0, 10, 10), TestTheme, |_| ());
+        renderer.finish_frame(&());
+
+        let image = renderer.read_pixels();
+        assert_eq!(Some([128, 128, 128, 255]), image.pixel(0, 0));
+        assert_eq!(Some([255, 255, 255, 255]), image.pixel(5, 5));
+    }
+
+    #[test]
+    fn replay_widget() {
+        struct TestTheme;
+        impl WidgetTheme for TestTheme {
+            type Fallback = !;
+            fn fallback(self) -> Option<!> {
+                None
+            }
+        }
+
+        let (drawn, skipped) = (WidgetId::new(), WidgetId::new());
+        let window_rect = BoundBox::new2(0, 0, 10, 10);
+        let mut renderer = HeadlessRenderer::new(TextMetrics::default());
+        renderer.resized(DimsBox::new2(10, 10));
+        renderer.start_frame(&());
+        assert!(!renderer.replay_widget(&(), drawn));
+        renderer.set_disabled(&(), true);
+        renderer.render_widget(drawn, &(), BoundBox::new2(0, 0, 4, 4), window_rect, TestTheme, |_| ());
+        renderer.set_disabled(&(), false);
+        renderer.render_widget(skipped, &(), BoundBox::new2(5, 5, 9, 9), window_rect, TestTheme, |_| ());
+        renderer.finish_frame(&());
+
+        // Only the widgets drawn in the previous frame can be replayed, in the color they were
+        // originally drawn with.
+        renderer.start_frame(&());
+        assert!(renderer.replay_widget(&(), drawn));
+        renderer.finish_frame(&());
+        let image = renderer.read_pixels();
+        assert_eq!(Some([128, 128, 128, 255]), image.pixel(0, 0));
+        assert_eq!(Some([0; 4]), image.pixel(5, 5));
+
+        renderer.start_frame(&());
+        assert!(renderer.replay_widget(&(), drawn));
+        assert!(!renderer.replay_widget(&(), skipped));
+        renderer.finish_frame(&());
+
+        renderer.widget_removed(drawn);
+        renderer.start_frame(&());
+        assert!(!renderer.replay_widget(&(), drawn));
+        renderer.finish_frame(&());
+    }
+
+    #[test]
+    fn laid_out_content() {
+        struct TestTheme;
+        impl WidgetTheme for TestTheme {
+            type Fallback = !;
+            fn fallback(self) -> Option<!> {
+                None
+            }
+        }
+
+        let metrics = TextMetrics {
+            char_width: 4,
+            line_height: 6,
+            icon_dims: DimsBox::new2(3, 3),
+        };
+        let (text, icon) = (WidgetId::new(), WidgetId::new());
+        let window_rect = BoundBox::new2(0, 0, 20, 20);
+        let mut renderer = HeadlessRenderer::new(metrics);
+        renderer.resized(DimsBox::new2(20, 20));
+        renderer.layout(text, |layout| {
+            layout.prepare_string("a b\nc");
+            let _ = layout.finish();
+        });
+        renderer.layout(icon, |layout| {
+            layout.prepare_icon("icon");
+            let _ = layout.finish();
+        });
+
+        renderer.start_frame(&());
+        renderer.render_widget(text, &(), BoundBox::new2(0, 0, 12, 12), window_rect, TestTheme, |f| f.render_laid_out_content());
+        renderer.render_widget(icon, &(), BoundBox::new2(14, 14, 20, 20), window_rect, TestTheme, |f| f.render_laid_out_content());
+        renderer.finish_frame(&());
+
+        let image = renderer.read_pixels();
+        // `a` fills its cell, apart from a one-pixel border.
+        assert_eq!(Some([255; 4]), image.pixel(1, 1));
+        assert_eq!(Some([255; 4]), image.pixel(2, 4));
+        assert_eq!(Some([0; 4]), image.pixel(3, 2));
+        // Whitespace doesn't get drawn.
+        assert_eq!(Some([0; 4]), image.pixel(5, 2));
+        assert_eq!(Some([255; 4]), image.pixel(9, 2));
+        // `c` is on the second line.
+        assert_eq!(Some([255; 4]), image.pixel(2, 8));
+        assert_eq!(Some([0; 4]), image.pixel(6, 8));
+        // The icon gets outlined, inside of the widget's own outline.
+        assert_eq!(Some([255; 4]), image.pixel(16, 16));
+        assert_eq!(Some([0; 4]), image.pixel(15, 15));
+
+        // Widgets that don't render their content only get outlined.
+        renderer.start_frame(&());
+        renderer.render_widget(text, &(), BoundBox::new2(0, 0, 12, 12), window_rect, TestTheme, |_| ());
+        renderer.finish_frame(&());
+        assert_eq!(Some([0; 4]), renderer.read_pixels().pixel(1, 1));
+    }
+
+    #[test]
+    fn layers() {
+        struct TestTheme;
+        impl WidgetTheme for TestTheme {
+            type Fallback = !;
+            fn fallback(self) -> Option<!> {
+                None
+            }
+        }
+
+        const RED: [u8; 4] = [255, 0, 0, 255];
+        let window_rect = BoundBox::new2(0, 0, 20, 10);
+        let mut renderer = HeadlessRenderer::new(TextMetrics::default());
+        renderer.resized(DimsBox::new2(20, 10));
+        renderer.start_frame(&());
+        // Fills drawn before the foreground end up underneath the outline.
+        renderer.render_widget(WidgetId::new(), &(), BoundBox::new2(0, 0, 10, 10), window_rect, TestTheme, |f| {
+            f.render_background();
+            assert!(f.fill_rect(BoundBox::new2(0, 0, 10, 10), RED));
+            f.render_laid_out_content();
+            f.render_foreground();
+        });
+        // Fills drawn after the foreground cover it up. Fills are relative to the widget's origin.
+        renderer.render_widget(WidgetId::new(), &(), BoundBox::new2(10, 0, 20, 10), window_rect, TestTheme, |f| {
+            f.render_foreground();
+            assert!(f.fill_rect(BoundBox::new2(0, 0, 10, 10), RED));
+        });
+        renderer.set_disabled(&(), true);
+        renderer.render_widget(WidgetId::new(), &(), BoundBox::new2(0, 0, 0, 0), window_rect, TestTheme, |f| {
+            f.fill_rect(BoundBox::new2(5, 5, 6, 6), [0, 0, 255, 255]);
+        });
+        renderer.finish_frame(&());
+
+        let image = renderer.read_pixels();
+        assert_eq!(Some([255; 4]), image.pixel(0, 0));
+        assert_eq!(Some(RED), image.pixel(3, 3));
+        assert_eq!(Some(RED), image.pixel(10, 0));
+        assert_eq!(Some(RED), image.pixel(13, 3));
+        // Disabled widgets get drawn in grey.
+        assert_eq!(Some([43, 43, 43, 255]), image.pixel(5, 5));
+    }
+
+    #[test]
+    fn redraw_region() {
+        let window_rect = BoundBox::new2(0, 0, 10, 10);
+        let mut renderer = HeadlessRenderer::new(TextMetrics::default());
+        renderer.resized(DimsBox::new2(10, 10));
+        renderer.start_frame(&());
+        renderer.draw_outline(BoundBox::new2(0, 0, 10, 10), window_rect);
+        renderer.finish_frame(&());
+
+        // Only the region gets cleared and drawn to.
+        renderer.set_redraw_region(Some(BoundBox::new2(0, 0, 5, 10)));
+        renderer.start_frame(&());
+        renderer.draw_outline(BoundBox::new2(2, 2, 10, 10), window_rect);
+        renderer.finish_frame(&());
+        let image = renderer.read_pixels();
+        assert_eq!(Some([0; 4]), image.pixel(0, 0));
+        assert_eq!(Some([255; 4]), image.pixel(2, 2));
+        assert_eq!(Some([0; 4]), image.pixel(5, 2));
+        assert_eq!(Some([255; 4]), image.pixel(9, 0));
+
+        // The whole frame gets redrawn if the window's been resized.
+        renderer.resized(DimsBox::new2(12, 12));
+        renderer.set_redraw_region(Some(BoundBox::new2(0, 0, 5, 10)));
+        renderer.start_frame(&());
+        renderer.draw_outline(BoundBox::new2(6, 6, 8, 8), BoundBox::new2(0, 0, 12, 12));
+        renderer.finish_frame(&());
+        let image = renderer.read_pixels();
+        assert_eq!(DimsBox::new2(12, 12), image.dims());
+        assert_eq!(Some([255; 4]), image.pixel(6, 6));
+        assert_eq!(Some(1), renderer.draw_call_count());
+    }
+
+    #[test]
+    fn clip_masks() {
+        struct TestTheme;
+        impl WidgetTheme for TestTheme {
+            type Fallback = !;
+            fn fallback(self) -> Option<!> {
+                None
+            }
+        }
+
+        let window_rect = BoundBox::new2(0, 0, 20, 20);
+        let mut renderer = HeadlessRenderer::new(TextMetrics::default());
+        renderer.resized(DimsBox::new2(20, 20));
+        renderer.start_frame(&());
+        // A triangle covering the lower-left half of the widget, which is offset from the window's
+        // origin.
+        renderer.render_widget(WidgetId::new(), &(), BoundBox::new2(10, 10, 20, 20), window_rect, TestTheme, |f| {
+            assert!(f.push_clip_mask(ClipMask::Polygon(vec![Point2::new(0, 0), Point2::new(10, 10), Point2::new(0, 10)])));
+            f.fill_rect(BoundBox::new2(0, 0, 10, 10), [255, 0, 0, 255]);
+            // Popping more masks than were pushed doesn't do anything.
+            f.pop_clip_mask();
+            f.pop_clip_mask();
+            f.fill_rect(BoundBox::new2(8, 1, 9, 2), [0, 0, 255, 255]);
+        });
+        assert_eq!(0, renderer.clip_mask_depth());
+
+        // Masks stay pushed after the widget's done rendering, so that they clip its children.
+        renderer.render_widget(WidgetId::new(), &(), BoundBox::new2(0, 0, 10, 10), window_rect, TestTheme, |f| {
+            f.push_clip_mask(ClipMask::RoundedRect{ rect: BoundBox::new2(0, 0, 10, 10), radius: 4 });
+        });
+        assert_eq!(1, renderer.clip_mask_depth());
+        renderer.render_widget(WidgetId::new(), &(), BoundBox::new2(0, 0, 10, 10), window_rect, TestTheme, |f| {
+            f.fill_rect(BoundBox::new2(0, 0, 10, 10), [0, 255, 0, 255]);
+        });
+        renderer.pop_clip_mask();
+        assert_eq!(0, renderer.clip_mask_depth());
+        renderer.finish_frame(&());
+
+        let image = renderer.read_pixels();
+        assert_eq!(Some([255, 0, 0, 255]), image.pixel(11, 18));
+        assert_eq!(Some([0; 4]), image.pixel(17, 12));
+        assert_eq!(Some([0, 0, 255, 255]), image.pixel(18, 11));
+        // The rounded corners are cut off, but the edges between them aren't.
+        assert_eq!(Some([0; 4]), image.pixel(0, 0));
+        assert_eq!(Some([0; 4]), image.pixel(9, 9));
+        assert_eq!(Some([255; 4]), image.pixel(0, 5));
+        assert_eq!(Some([0, 255, 0, 255]), image.pixel(5, 1));
+        assert_eq!(Some([0, 255, 0, 255]), image.pixel(2, 2));
+    }
+
+    #[test]
+    fn glyph_cache() {
+        struct TestTheme;
+        impl WidgetTheme for TestTheme {
+            type Fallback = !;
+            fn fallback(self) -> Option<!> {
+                None
+            }
+        }
+
+        let (a, b) = (WidgetId::new(), WidgetId::new());
+        let window_rect = BoundBox::new2(0, 0, 64, 64);
+        let mut renderer = HeadlessRenderer::new(TextMetrics::default());
+        renderer.resized(DimsBox::new2(64, 64));
+        renderer.layout(a, |layout| {layout.prepare_string("aaba"); let _ = layout.finish();});
+        renderer.layout(b, |layout| {layout.prepare_string("ba c"); let _ = layout.finish();});
+        let draw = |renderer: &mut HeadlessRenderer| {
+            renderer.start_frame(&());
+            renderer.render_widget(a, &(), BoundBox::new2(0, 0, 64, 16), window_rect, TestTheme, |f| f.render_laid_out_content());
+            renderer.render_widget(b, &(), BoundBox::new2(0, 16, 64, 32), window_rect, TestTheme, |f| f.render_laid_out_content());
+            renderer.finish_frame(&());
+        };
+
+        // Every glyph only gets rasterized once, even across widgets and frames.
+        draw(&mut renderer);
+        assert_eq!(3, renderer.glyphs_rasterized());
+        draw(&mut renderer);
+        assert_eq!(3, renderer.glyphs_rasterized());
+        let image = renderer.read_pixels();
+        assert_eq!(Some([255; 4]), image.pixel(9, 1));
+        assert_eq!(Some([0; 4]), image.pixel(8, 1));
+        assert_eq!(Some([255; 4]), image.pixel(25, 17));
+        assert_eq!(Some([0; 4]), image.pixel(17, 17));
+
+        // Glyphs that get evicted need to be rasterized again.
+        renderer.set_glyph_cache_capacity(1);
+        draw(&mut renderer);
+        assert_eq!(9, renderer.glyphs_rasterized());
+        assert_eq!(image, renderer.read_pixels());
+    }
+
+    #[test]
+    fn debug_overlay() {
+        use crate::widget::WidgetIdent;
+
+        let item = |rect, hovered, focused| DebugOverlayItem {
+            widget_id: WidgetId::new(),
+            ident: WidgetIdent::Num(0),
+            rect,
+            clip: BoundBox::new2(0, 0, 2, 2),
+            depth: 1,
+            hovered,
+            focused,
+        };
+
+        let mut renderer = HeadlessRenderer::new(TextMetrics::default());
+        renderer.resized(DimsBox::new2(10, 10));
+        renderer.start_frame(&());
+        renderer.render_debug_overlay(&(), &[
+            item(BoundBox::new2(0, 0, 10, 10), false, false),
+            item(BoundBox::new2(2, 2, 5, 5), true, false),
+            item(BoundBox::new2(5, 5, 8, 8), true, true),
+        ]);
+        renderer.finish_frame(&());
+
+        let image = renderer.read_pixels();
+        assert_eq!(Some([255, 255, 0, 255]), image.pixel(0, 0));
+        assert_eq!(Some([0, 255, 0, 255]), image.pixel(2, 3));
+        assert_eq!(Some([0, 255, 255, 255]), image.pixel(7, 7));
+        assert_eq!(Some([0; 4]), image.pixel(3, 3));
+    }
+
+    #[test]
+    #[should_panic]
+    fn finish_twice() {
+        let mut layout = HeadlessLayout::new(TextMetrics::default());
+        let _ = layout.finish();
+        let _ = layout.finish();
+    }
+}
@@ -0,0 +1,80 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use cgmath_geometry::cgmath::Point2;
+use std::ops::Range;
+
+/// A position the caret can be placed at, between two grapheme clusters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CaretPosition {
+    /// The byte index in the string the caret is placed before.
+    pub index: usize,
+    /// The horizontal position of the caret.
+    pub x: i32,
+}
+
+/// A single line of laid-out text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TextLine {
+    /// The byte range the line covers in the string, not including the line break that ends it.
+    pub range: Range<usize>,
+    /// The position of the top of the line.
+    pub top: i32,
+    pub height: i32,
+    /// Every position the caret can be placed at on the line, sorted by index. This includes the
+    /// start of every grapheme cluster on the line, as well as the end of the line.
+    pub carets: Vec<CaretPosition>,
+}
+
+/// The positions of laid-out text, for mapping between points and string indices.
+///
+/// This gets returned by renderers in `LayoutResult::text_layout`. All positions are relative
+/// to the origin of the widget the text was laid out in.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct TextLayoutResult {
+    lines: Vec<TextLine>,
+}
+
+impl TextLayoutResult {
+    /// Create a layout out of the given lines, which should be sorted from top to bottom.
+    pub fn new(lines: Vec<TextLine>) -> TextLayoutResult {
+        TextLayoutResult{ lines }
+    }
+
+    #[inline]
+    pub fn lines(&self) -> &[TextLine] {
+        &self.lines
+    }
+
+    /// Find the caret position closest to `point`, returning its index in the string.
+    ///
+    /// Points above or below the text get mapped to the first or last line respectively. Returns
+    /// `None` if there's no text laid out.
+    pub fn index_at_point(&self, point: Point2<i32>) -> Option<usize> {
+        let line = self.lines.iter()
+            .find(|line| point.y < line.top + line.height)
+            .or(self.lines.last())?;
+
+        line.carets.iter()
+            .min_by_key(|caret| (caret.x - point.x).abs())
+            .map(|caret| caret.index)
+    }
+
+    /// Find the position of the caret placed at `index`. The returned point is at the top of the
+    /// caret's line.
+    ///
+    /// If `index` falls on the boundary between two wrapped lines, the position at the start of
+    /// the second line is returned. If `index` falls inside a grapheme cluster, the position before
+    /// the cluster is returned. Returns `None` if `index` is outside of the laid-out text.
+    pub fn point_at_index(&self, index: usize) -> Option<Point2<i32>> {
+        let line = self.lines.iter()
+            .find(|line| line.range.start <= index && index < line.range.end)
+            .or_else(|| self.lines.iter().rev().find(|line| line.range.end == index))?;
+
+        line.carets.iter()
+            .rev()
+            .find(|caret| caret.index <= index)
+            .map(|caret| Point2::new(caret.x, line.top))
+    }
+}
@@ -0,0 +1,134 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Caret movement over strings.
+//!
+//! All positions are byte indices into the string. Movement happens over extended grapheme
+//! clusters rather than `char`s, so the caret never ends up inside something that gets drawn as a
+//! single character (e.g. a letter with combining accents, or an emoji ZWJ sequence). Positions
+//! that don't lie on a cluster boundary are accepted, and get moved to the nearest boundary in the
+//! direction of travel.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Get the position of the first grapheme boundary after `pos`, or `string.len()` if there isn't
+/// one.
+pub fn next_grapheme(string: &str, pos: usize) -> usize {
+    string.grapheme_indices(true)
+        .map(|(i, _)| i)
+        .find(|&i| i > pos)
+        .unwrap_or(string.len())
+}
+
+/// Get the position of the last grapheme boundary before `pos`, or `0` if there isn't one.
+pub fn prev_grapheme(string: &str, pos: usize) -> usize {
+    string.grapheme_indices(true)
+        .map(|(i, _)| i)
+        .take_while(|&i| i < pos)
+        .last()
+        .unwrap_or(0)
+}
+
+/// Get the start of the first word that begins after `pos`, or `string.len()` if there isn't one.
+pub fn next_word(string: &str, pos: usize) -> usize {
+    word_starts(string)
+        .find(|&i| i > pos)
+        .unwrap_or(string.len())
+}
+
+/// Get the start of the last word that begins before `pos`, or `0` if there isn't one.
+pub fn prev_word(string: &str, pos: usize) -> usize {
+    word_starts(string)
+        .take_while(|&i| i < pos)
+        .last()
+        .unwrap_or(0)
+}
+
+/// Move `pos` by `dist` graphemes or, if `words` is `true`, by `dist` words. Negative distances
+/// move towards the start of the string.
+pub fn move_by(string: &str, mut pos: usize, dist: isize, words: bool) -> usize {
+    let step: fn(&str, usize) -> usize = match (dist.signum(), words) {
+        (0, _) => return pos,
+        (1, false) => next_grapheme,
+        (1, true) => next_word,
+        (_, false) => prev_grapheme,
+        (_, true) => prev_word,
+    };
+    for _ in 0..dist.abs() {
+        pos = step(string, pos);
+    }
+    pos
+}
+
+/// The start positions of every word in the string. Whitespace and punctuation aren't words.
+fn word_starts(string: &str) -> impl '_ + Iterator<Item=usize> {
+    string.split_word_bound_indices()
+        .filter(|(_, segment)| segment.chars().any(char::is_alphanumeric))
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combining_marks() {
+        // "e" followed by a combining acute accent and a combining grave accent.
+        let s = "ae\u{301}\u{300}b";
+        assert_eq!(1, next_grapheme(s, 0));
+        assert_eq!(6, next_grapheme(s, 1));
+        assert_eq!(7, next_grapheme(s, 6));
+        assert_eq!(7, next_grapheme(s, 7));
+
+        assert_eq!(6, prev_grapheme(s, 7));
+        assert_eq!(1, prev_grapheme(s, 6));
+        assert_eq!(0, prev_grapheme(s, 1));
+        assert_eq!(0, prev_grapheme(s, 0));
+
+        // Positions inside the cluster move to its edges.
+        assert_eq!(6, next_grapheme(s, 3));
+        assert_eq!(1, prev_grapheme(s, 3));
+    }
+
+    #[test]
+    fn zwj_sequences() {
+        // Man, ZWJ, woman, ZWJ, girl.
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let s = format!("x{}y", family);
+        let family_end = 1 + family.len();
+        assert_eq!(family_end, next_grapheme(&s, 1));
+        assert_eq!(1, prev_grapheme(&s, family_end));
+
+        // Thumbs up with a skin tone modifier.
+        let thumbs = "\u{1F44D}\u{1F3FD}";
+        assert_eq!(thumbs.len(), next_grapheme(thumbs, 0));
+        assert_eq!(0, prev_grapheme(thumbs, thumbs.len()));
+    }
+
+    #[test]
+    fn words() {
+        let s = "hello, wide world";
+        assert_eq!(7, next_word(s, 0));
+        assert_eq!(7, next_word(s, 5));
+        assert_eq!(12, next_word(s, 7));
+        assert_eq!(s.len(), next_word(s, 12));
+
+        assert_eq!(12, prev_word(s, s.len()));
+        assert_eq!(7, prev_word(s, 12));
+        assert_eq!(7, prev_word(s, 9));
+        assert_eq!(0, prev_word(s, 7));
+        assert_eq!(0, prev_word(s, 0));
+    }
+
+    #[test]
+    fn move_by_dist() {
+        let s = "ae\u{301}b cd";
+        assert_eq!(4, move_by(s, 0, 2, false));
+        assert_eq!(1, move_by(s, 4, -1, false));
+        assert_eq!(s.len(), move_by(s, 0, 5, false));
+        assert_eq!(6, move_by(s, 0, 1, true));
+        assert_eq!(0, move_by(s, s.len(), -2, true));
+        assert_eq!(3, move_by(s, 3, 0, true));
+    }
+}
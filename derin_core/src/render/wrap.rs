@@ -0,0 +1,199 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// How text gets broken into lines when it's wider than the space it's laid out in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WrapMode {
+    /// Break lines between words. Words that are too wide to fit on a line by themselves get
+    /// broken between graphemes.
+    Word,
+    /// Break lines between any two graphemes.
+    Char,
+    /// Only break lines at explicit line breaks.
+    None,
+}
+
+impl Default for WrapMode {
+    #[inline]
+    fn default() -> WrapMode {
+        WrapMode::None
+    }
+}
+
+/// Break `text` into lines no wider than `max_width`, returning the byte range of each line.
+///
+/// `grapheme_width` gets called to measure each grapheme cluster. Lines are never broken inside a
+/// grapheme cluster, and a line always contains at least one grapheme, so a single grapheme wider
+/// than `max_width` gets a line to itself.
+///
+/// Explicit line breaks (`'\n'`) always end a line, and aren't included in the returned ranges.
+/// Whitespace at the end of a wrapped line "hangs" past the edge: it's included in the line's
+/// range, but doesn't count towards the line's width, so the next line never starts with the
+/// whitespace that separated it from the previous one.
+pub fn wrap_lines<F>(text: &str, mode: WrapMode, max_width: i32, mut grapheme_width: F) -> Vec<Range<usize>>
+    where F: FnMut(&str) -> i32
+{
+    let mut lines = Vec::new();
+    let mut paragraph_start = 0;
+
+    for paragraph in text.split('\n') {
+        let mut breaker = LineBreaker {
+            lines: &mut lines,
+            offset: paragraph_start,
+            max_width,
+            line_start: 0,
+            line_width: 0,
+            whitespace_width: 0,
+        };
+        if mode != WrapMode::None {
+            breaker.wrap(paragraph, mode, &mut grapheme_width);
+        }
+        breaker.finish(paragraph.len());
+
+        paragraph_start += paragraph.len() + 1;
+    }
+
+    lines
+}
+
+/// Tracks the line currently being filled while wrapping a paragraph.
+struct LineBreaker<'a> {
+    lines: &'a mut Vec<Range<usize>>,
+    /// The paragraph's position in the full string.
+    offset: usize,
+    max_width: i32,
+    line_start: usize,
+    /// The width of the line, up to the end of the last word on the line.
+    line_width: i32,
+    /// The width of the whitespace following the last word on the line.
+    whitespace_width: i32,
+}
+
+impl LineBreaker<'_> {
+    fn wrap<F>(&mut self, paragraph: &str, mode: WrapMode, grapheme_width: &mut F)
+        where F: FnMut(&str) -> i32
+    {
+        let mut word_start = None;
+        let mut word_width = 0;
+
+        for (index, grapheme) in paragraph.grapheme_indices(true) {
+            let width = grapheme_width(grapheme);
+
+            if grapheme.chars().all(char::is_whitespace) {
+                if let Some(start) = word_start.take() {
+                    self.push_word(&paragraph[start..index], start, word_width, grapheme_width);
+                }
+                self.whitespace_width += width;
+            } else if mode == WrapMode::Char {
+                self.push_grapheme(index, width);
+            } else {
+                match word_start {
+                    Some(_) => word_width += width,
+                    None => {
+                        word_start = Some(index);
+                        word_width = width;
+                    }
+                }
+            }
+        }
+
+        if let Some(start) = word_start {
+            self.push_word(&paragraph[start..], start, word_width, grapheme_width);
+        }
+    }
+
+    /// Place a word on the line, moving it to the next line if it doesn't fit. If the word doesn't
+    /// fit on a line by itself, it gets broken between graphemes.
+    fn push_word<F>(&mut self, word: &str, start: usize, width: i32, grapheme_width: &mut F)
+        where F: FnMut(&str) -> i32
+    {
+        if self.line_width > 0 && !self.fits(width) {
+            self.break_before(start);
+        }
+
+        if self.fits(width) {
+            self.line_width += self.whitespace_width + width;
+            self.whitespace_width = 0;
+        } else {
+            for (index, grapheme) in word.grapheme_indices(true) {
+                self.push_grapheme(start + index, grapheme_width(grapheme));
+            }
+        }
+    }
+
+    fn push_grapheme(&mut self, start: usize, width: i32) {
+        if self.line_width > 0 && !self.fits(width) {
+            self.break_before(start);
+        }
+        self.line_width += self.whitespace_width + width;
+        self.whitespace_width = 0;
+    }
+
+    fn fits(&self, width: i32) -> bool {
+        self.line_width + self.whitespace_width + width <= self.max_width
+    }
+
+    fn break_before(&mut self, start: usize) {
+        self.lines.push(self.offset + self.line_start..self.offset + start);
+        self.line_start = start;
+        self.line_width = 0;
+        self.whitespace_width = 0;
+    }
+
+    fn finish(self, paragraph_len: usize) {
+        self.lines.push(self.offset + self.line_start..self.offset + paragraph_len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wrap(text: &str, mode: WrapMode, max_width: i32) -> Vec<&str> {
+        wrap_lines(text, mode, max_width, |g| g.chars().count() as i32)
+            .into_iter()
+            .map(|r| &text[r])
+            .collect()
+    }
+
+    #[test]
+    fn wrap_none() {
+        assert_eq!(vec!["a long line", "b"], wrap("a long line\nb", WrapMode::None, 4));
+        assert_eq!(vec![""], wrap("", WrapMode::None, 4));
+        assert_eq!(vec!["a", ""], wrap("a\n", WrapMode::None, 4));
+    }
+
+    #[test]
+    fn wrap_word() {
+        assert_eq!(vec!["one two ", "three ", "four"], wrap("one two three four", WrapMode::Word, 8));
+        assert_eq!(vec!["one ", "two", "three"], wrap("one two\nthree", WrapMode::Word, 5));
+        // Whitespace hangs off the end of the line, instead of starting the next line.
+        assert_eq!(vec!["ab    ", "cd"], wrap("ab    cd", WrapMode::Word, 4));
+    }
+
+    #[test]
+    fn wrap_word_long_token() {
+        // Only the token that can't fit on a line gets broken between characters.
+        assert_eq!(
+            vec!["ab ", "abcde", "fghij ", "cd ef"],
+            wrap("ab abcdefghij cd ef", WrapMode::Word, 5)
+        );
+    }
+
+    #[test]
+    fn wrap_char() {
+        assert_eq!(vec!["abc", "de ", "fgh"], wrap("abcde fgh", WrapMode::Char, 3));
+    }
+
+    #[test]
+    fn wrap_graphemes() {
+        // "e\u{301}" is a single grapheme cluster, and must not be split.
+        let text = "ae\u{301}e\u{301}b";
+        assert_eq!(vec!["ae\u{301}", "e\u{301}b"], wrap(text, WrapMode::Char, 3));
+        assert_eq!(vec!["ae\u{301}", "e\u{301}b"], wrap(text, WrapMode::Word, 3));
+    }
+}
@@ -18,7 +18,7 @@ use derin_common_types::{
 };
 use indexmap::IndexMap;
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     ops::Drop,
     rc::Rc,
 };
@@ -35,12 +35,17 @@ pub(crate) struct TestWidget {
     /// - Right Arrow Key: Focus Next
     /// - Left Arrow Key: Focus Previous
     pub focus_controls: bool,
+    /// The value returned by `accepts_focus`. Defaults to `true` in `test_widget_tree`.
+    pub accepts_focus: bool,
+    /// The value returned by `preferred_focus_child`. Defaults to `None` in `test_widget_tree`.
+    pub preferred_focus_child: Option<WidgetIdent>,
     pub children: Option<IndexMap<WidgetIdent, TestWidget>>,
 }
 
 #[derive(Clone)]
 pub(crate) struct EventList {
     events: Rc<RefCell<std::vec::IntoIter<TestEvent>>>,
+    check_mouse_pos: Rc<Cell<bool>>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -65,10 +70,17 @@ impl Theme for TestTheme {
 impl EventList {
     pub fn new() -> EventList {
         EventList {
-            events: Rc::new(RefCell::new(Vec::new().into_iter()))
+            events: Rc::new(RefCell::new(Vec::new().into_iter())),
+            check_mouse_pos: Rc::new(Cell::new(false)),
         }
     }
 
+    /// Makes widgets assert that the cursor position in the input state matches the position
+    /// carried by each `MouseMove` event they receive.
+    pub fn check_mouse_pos(&self) {
+        self.check_mouse_pos.set(true);
+    }
+
     pub fn set_events(&self, events: Vec<TestEvent>) {
         *self.events.borrow_mut() = events.into_iter();
     }
@@ -136,6 +148,10 @@ impl Widget for TestWidget {
             }
         }
 
+        if let (WidgetEvent::MouseMove{new_pos, ..}, true) = (&event, self.event_list.check_mouse_pos.get() && source_child.len() == 0) {
+            assert_eq!(Some(*new_pos), input_state.mouse_pos, "input state mouse position doesn't match event position");
+        }
+
         let real_event = TestEvent {
             widget: self.widget_tag.widget_id,
             event,
@@ -153,6 +169,10 @@ impl Widget for TestWidget {
     fn size_bounds(&self) -> SizeBounds {
         self.size_bounds
     }
+
+    fn accepts_focus(&self) -> bool {
+        self.accepts_focus
+    }
 }
 
 impl<F: RenderFrame> WidgetRenderable<F> for TestWidget {
@@ -208,12 +228,17 @@ impl Parent for TestWidget {
             }
         }
     }
+
+    fn preferred_focus_child(&self) -> Option<WidgetIdent> {
+        self.preferred_focus_child.clone()
+    }
 }
 
 macro_rules! extract_widget_tree_idents {
     ($($widget_ident:ident {
         rect: ($x:expr, $y:expr, $w:expr, $h:expr)
         $(, focus_controls: $focus_controls:expr)?
+        $(, accepts_focus: $accepts_focus:expr)?
         $(;$($children:tt)*)?
     }),*) => {$(
         let $widget_ident = crate::widget::WidgetId::new();
@@ -229,6 +254,7 @@ macro_rules! test_widget_tree {
         let $root_pat:pat = $root:ident {
             rect: ($x:expr, $y:expr, $w:expr, $h:expr)
             $(, focus_controls: $focus_controls:expr)?
+            $(, accepts_focus: $accepts_focus:expr)?
             $(;$($rest:tt)*)?
         };
     ) => {
@@ -260,6 +286,8 @@ macro_rules! test_widget_tree {
                     size_bounds: derin_common_types::layout::SizeBounds::default(),
                     event_list: $event_list.clone(),
                     focus_controls: $($focus_controls ||)? false,
+                    accepts_focus: $($accepts_focus &&)? true,
+                    preferred_focus_child: None,
                     children: match children.len() {
                         0 => None,
                         _ => Some(children)
@@ -274,6 +302,7 @@ macro_rules! test_widget_tree {
         $($child:ident {
             rect: ($x:expr, $y:expr, $w:expr, $h:expr)
             $(, focus_controls: $focus_controls:expr)?
+            $(, accepts_focus: $accepts_focus:expr)?
             $(;$($children:tt)*)?
         }),*
     ) => {$({
@@ -294,6 +323,8 @@ macro_rules! test_widget_tree {
             size_bounds: derin_common_types::layout::SizeBounds::default(),
             event_list: $event_list.clone(),
             focus_controls: $($focus_controls ||)? false,
+            accepts_focus: $($accepts_focus &&)? true,
+            preferred_focus_child: None,
             children: match children.len() {
                 0 => None,
                 _ => Some(children)
@@ -13,7 +13,7 @@ id!(pub TimerId);
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Timer {
     pub frequency: Duration,
-    start_time: Instant,
+    start_time: Cell<Instant>,
     pub(crate) last_triggered: Cell<Option<Instant>>,
     pub(crate) times_triggered: Cell<u32>
 }
@@ -27,13 +27,15 @@ pub(crate) struct TimerTrigger {
 
 pub(crate) struct TimerTriggerTracker {
     timers_by_next_trigger: Vec<TimerTrigger>,
+    paused_at: Option<Instant>,
+    pause_when_unfocused: bool,
 }
 
 impl Timer {
     pub fn new(frequency: Duration) -> Timer {
         Timer {
             frequency,
-            start_time: Instant::now(),
+            start_time: Cell::new(Instant::now()),
             last_triggered: Cell::new(None),
             times_triggered: Cell::new(0),
         }
@@ -41,7 +43,8 @@ impl Timer {
 
     pub fn new_delayed(frequency: Duration, start_time: Instant) -> Timer {
         Timer {
-            frequency, start_time,
+            frequency,
+            start_time: Cell::new(start_time),
             last_triggered: Cell::new(None),
             times_triggered: Cell::new(0),
         }
@@ -49,7 +52,7 @@ impl Timer {
 
     #[inline(always)]
     pub fn start_time(&self) -> Instant {
-        self.start_time
+        self.start_time.get()
     }
     #[inline(always)]
     pub fn last_triggered(&self) -> Option<Instant> {
@@ -61,7 +64,13 @@ impl Timer {
     }
 
     pub fn next_trigger(&self) -> Instant {
-        self.start_time + self.frequency * self.times_triggered()
+        self.start_time() + self.frequency * self.times_triggered()
+    }
+
+    /// Push the timer's schedule back by `delay`, as if it had been started that much later.
+    pub(crate) fn delay(&self, delay: Duration) {
+        self.start_time.set(self.start_time.get() + delay);
+        self.last_triggered.set(self.last_triggered.get().map(|t| t + delay));
     }
 }
 
@@ -75,22 +84,29 @@ impl TimerTriggerTracker {
     pub fn new() -> TimerTriggerTracker {
         TimerTriggerTracker {
             timers_by_next_trigger: Vec::new(),
+            paused_at: None,
+            pause_when_unfocused: false,
         }
     }
 
+    /// The next time a timer fires. Paused timers never fire, so this is `None` while paused.
     pub fn next_trigger(&self) -> Option<Instant> {
-        self.timers_by_next_trigger.get(0).map(|t| t.instant)
+        match self.paused_at {
+            Some(_) => None,
+            None => self.timers_by_next_trigger.get(0).map(|t| t.instant)
+        }
     }
 
     pub fn timers_triggered(&mut self) -> impl '_ + Iterator<Item=TimerTrigger> {
         let now = Instant::now();
         let split_location_result = self.timers_by_next_trigger.binary_search_by_key(&now, |t| t.instant);
-        let split_location = match split_location_result {
-            Ok(i) => {
+        let split_location = match (self.paused_at, split_location_result) {
+            (Some(_), _) => 0,
+            (None, Ok(i)) => {
                 // If there are multiple timers triggered at now, find the last timer in that set.
                 i + self.timers_by_next_trigger[i..].iter().take_while(|t| t.instant == now).count()
             }
-            Err(i) => i
+            (None, Err(i)) => i
         };
 
         self.timers_by_next_trigger[..split_location].sort_unstable_by_key(|t| t.widget_id);
@@ -106,4 +122,109 @@ impl TimerTriggerTracker {
 
         self.timers_by_next_trigger.insert(insert_location, timer_trigger);
     }
+
+    /// Every trigger that's waiting to fire, in the order they'll fire.
+    pub fn queued_triggers(&self) -> impl '_ + Iterator<Item=&TimerTrigger> {
+        self.timers_by_next_trigger.iter()
+    }
+
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Stop firing timers until `resume` gets called. Does nothing if already paused.
+    pub fn pause(&mut self) {
+        if self.paused_at.is_none() {
+            self.paused_at = Some(Instant::now());
+        }
+    }
+
+    /// Resume firing timers, pushing every queued trigger back by the time spent paused so that
+    /// each timer has as long left to wait as it did when it was paused.
+    ///
+    /// Returns the time spent paused, or `None` if the tracker wasn't paused. The timers that own
+    /// the queued triggers should be delayed by the same amount.
+    pub fn resume(&mut self) -> Option<Duration> {
+        let paused_for = Instant::now() - self.paused_at.take()?;
+        for trigger in &mut self.timers_by_next_trigger {
+            trigger.instant += paused_for;
+        }
+        Some(paused_for)
+    }
+
+    #[inline]
+    pub fn pause_when_unfocused(&self) -> bool {
+        self.pause_when_unfocused
+    }
+
+    #[inline]
+    pub fn set_pause_when_unfocused(&mut self, pause_when_unfocused: bool) {
+        self.pause_when_unfocused = pause_when_unfocused;
+    }
+
+    /// Remove all queued triggers for the given widget.
+    pub fn remove_widget(&mut self, widget_id: WidgetId) {
+        self.timers_by_next_trigger.retain(|t| t.widget_id != widget_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remove_widget_triggers() {
+        let mut tracker = TimerTriggerTracker::new();
+        let (cleared_id, kept_id) = (WidgetId::new(), WidgetId::new());
+        let now = Instant::now();
+
+        for &widget_id in &[cleared_id, kept_id] {
+            tracker.queue_trigger(TimerTrigger::new(now, TimerId::new(), widget_id));
+            tracker.queue_trigger(TimerTrigger::new(now + Duration::from_secs(60), TimerId::new(), widget_id));
+        }
+
+        tracker.remove_widget(cleared_id);
+        assert!(tracker.timers_triggered().all(|t| t.widget_id == kept_id));
+        assert_eq!(Some(now + Duration::from_secs(60)), tracker.next_trigger());
+
+        tracker.remove_widget(kept_id);
+        assert_eq!(None, tracker.next_trigger());
+    }
+
+    #[test]
+    fn pause_resume() {
+        let mut tracker = TimerTriggerTracker::new();
+        let now = Instant::now();
+        let (due, later) = (now - Duration::from_millis(1), now + Duration::from_secs(60));
+        tracker.queue_trigger(TimerTrigger::new(due, TimerId::new(), WidgetId::new()));
+        tracker.queue_trigger(TimerTrigger::new(later, TimerId::new(), WidgetId::new()));
+
+        tracker.pause();
+        assert!(tracker.is_paused());
+        assert_eq!(None, tracker.next_trigger());
+        assert_eq!(0, tracker.timers_triggered().count());
+
+        let paused_for = tracker.resume().unwrap();
+        assert!(!tracker.is_paused());
+        assert_eq!(None, tracker.resume());
+        assert_eq!(Some(due + paused_for), tracker.next_trigger());
+        assert_eq!(
+            vec![due + paused_for, later + paused_for],
+            tracker.queued_triggers().map(|t| t.instant).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn delay_timer() {
+        let start_time = Instant::now();
+        let timer = Timer::new_delayed(Duration::from_millis(10), start_time);
+        timer.times_triggered.set(2);
+        timer.last_triggered.set(Some(start_time + Duration::from_millis(20)));
+
+        timer.delay(Duration::from_millis(5));
+        assert_eq!(start_time + Duration::from_millis(5), timer.start_time());
+        assert_eq!(Some(start_time + Duration::from_millis(25)), timer.last_triggered());
+        assert_eq!(start_time + Duration::from_millis(25), timer.next_trigger());
+    }
 }
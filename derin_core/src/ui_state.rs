@@ -0,0 +1,157 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Snapshots of transient UI state, for restoring a session where it left off.
+//!
+//! Widgets opt in by implementing `Widget::save_state` and `Widget::restore_state`.
+//! `Root::save_ui_state` collects every widget's state into a `UiState`, keyed by the widget's
+//! ident path, and `Root::restore_ui_state` hands the state back to the widgets at those paths.
+//! State for paths that don't exist yet is held onto and restored once a widget appears there, so
+//! the state of widgets that get built lazily (such as the children of a collapsed tree node)
+//! survives the round trip.
+//!
+//! With the `derin-serde` feature enabled, `UiState` can be serialized and deserialized.
+
+use crate::widget::WidgetPathKey;
+use fnv::FnvHashMap;
+
+/// A piece of state saved by a widget.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum UiStateValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+    /// A path to a widget, relative to the widget that saved the state.
+    Path(WidgetPathKey),
+    List(Vec<UiStateValue>),
+}
+
+/// Saved widget state, keyed by the path from the root widget to the widget the state belongs to.
+///
+/// Paths aren't strings, so formats like JSON can't use them as map keys. `UiState` gets
+/// serialized as a list of `(path, value)` pairs instead of as a map.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct UiState {
+    values: FnvHashMap<WidgetPathKey, UiStateValue>,
+}
+
+impl UiState {
+    #[inline]
+    pub fn new() -> UiState {
+        UiState::default()
+    }
+
+    /// Get the state saved for the widget at `path`.
+    pub fn get(&self, path: &WidgetPathKey) -> Option<&UiStateValue> {
+        self.values.get(path)
+    }
+
+    /// Set the state for the widget at `path`, returning the state that was previously there.
+    pub fn insert(&mut self, path: WidgetPathKey, value: UiStateValue) -> Option<UiStateValue> {
+        self.values.insert(path, value)
+    }
+
+    /// Remove the state for the widget at `path`.
+    pub fn remove(&mut self, path: &WidgetPathKey) -> Option<UiStateValue> {
+        self.values.remove(path)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn iter(&self) -> impl '_ + Iterator<Item=(&WidgetPathKey, &UiStateValue)> {
+        self.values.iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for UiState {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.values.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for UiState {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<UiState, D::Error> {
+        Vec::<(WidgetPathKey, UiStateValue)>::deserialize(deserializer)
+            .map(|values| UiState{ values: values.into_iter().collect() })
+    }
+}
+
+impl UiStateValue {
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            UiStateValue::Bool(b) => Some(b),
+            _ => None
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match *self {
+            UiStateValue::Int(i) => Some(i),
+            _ => None
+        }
+    }
+
+    /// Get the value as a float. `Int` values get converted.
+    pub fn as_float(&self) -> Option<f64> {
+        match *self {
+            UiStateValue::Float(f) => Some(f),
+            UiStateValue::Int(i) => Some(i as f64),
+            _ => None
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match *self {
+            UiStateValue::Text(ref s) => Some(s),
+            _ => None
+        }
+    }
+
+    pub fn as_path(&self) -> Option<&WidgetPathKey> {
+        match *self {
+            UiStateValue::Path(ref p) => Some(p),
+            _ => None
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[UiStateValue]> {
+        match *self {
+            UiStateValue::List(ref l) => Some(l),
+            _ => None
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::widget::WidgetIdent;
+
+    #[test]
+    fn json_round_trip() {
+        let mut ui_state = UiState::new();
+        let path: WidgetPathKey = vec![WidgetIdent::new_str("tree"), WidgetIdent::Num(1)].into_iter().collect();
+        ui_state.insert(path.clone(), UiStateValue::List(vec![
+            UiStateValue::Bool(true),
+            UiStateValue::Text("text".to_string()),
+            UiStateValue::Path(path.child(WidgetIdent::Num(2))),
+        ]));
+        ui_state.insert(WidgetPathKey::empty(), UiStateValue::Float(0.5));
+
+        let json = serde_json::to_string(&ui_state).unwrap();
+        assert_eq!(ui_state, serde_json::from_str::<UiState>(&json).unwrap());
+    }
+}
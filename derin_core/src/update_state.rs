@@ -3,13 +3,16 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use crate::{
-    message_bus::{Message, MessageTarget, MessageTargeted, MessageBus},
+    message_bus::{Message, MessageTarget, MessageTargeted, MessageBus, SendMessageTargeted},
     cgmath::Point2,
     widget::WidgetId,
 };
+use cgmath_geometry::{D2, rect::BoundBox};
 use derin_common_types::cursor::CursorIcon;
-use fnv::FnvHashSet;
+use fnv::{FnvHashMap, FnvHashSet};
 use std::{
+    any::Any,
+    fmt,
     mem,
     rc::{Rc, Weak},
     sync::mpsc::Sender,
@@ -27,26 +30,63 @@ pub(crate) enum UpdateStateShared<R = Weak<UpdateStateCell>> {
 #[derive(Debug, Default)]
 pub(crate) struct UpdateStateVacant {
     buffered_messages: Vec<MessageTargeted>,
+    buffered_actions: Vec<Message>,
 }
 
 pub(crate) type UpdateStateCell = RefCell<UpdateState>;
 
+/// A mutation of a widget that's been put off until event dispatch has finished. Takes the widget
+/// it was deferred on.
+pub(crate) type DeferredFn = Box<FnOnce(&mut Any)>;
+
+pub(crate) struct Deferred {
+    pub widget_id: WidgetId,
+    pub f: DeferredFn,
+}
+
+/// A change to the stack of active focus scopes, requested through `WidgetTag::push_focus_scope`
+/// and `WidgetTag::pop_focus_scope`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FocusScopeOp {
+    Push(WidgetId),
+    Pop(WidgetId),
+}
+
 #[derive(Debug)]
 pub(crate) struct UpdateState {
     pub redraw: FnvHashSet<WidgetId>,
+    /// The parts of the widgets in `redraw` that need to be redrawn, relative to each widget's
+    /// origin. Widgets in `redraw` without an entry here need to be redrawn entirely.
+    pub redraw_rects: FnvHashMap<WidgetId, BoundBox<D2, i32>>,
     pub relayout: FnvHashSet<WidgetId>,
     pub update_timers: FnvHashSet<WidgetId>,
     pub update_messages: FnvHashSet<WidgetId>,
     pub remove_from_tree: FnvHashSet<WidgetId>,
     pub set_cursor_icon: Option<CursorIcon>,
     pub set_cursor_pos: Option<(WidgetId, Point2<i32>)>,
+    pub set_window_title: Option<String>,
+    pub close_requested: bool,
     pub message_sender: Sender<MessageTargeted>,
+    pub send_message_sender: Sender<SendMessageTargeted>,
+    pub actions: Vec<Message>,
+    pub deferred: Vec<Deferred>,
+    pub focus_scope_ops: Vec<FocusScopeOp>,
     pub global_update: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UpdateError {
     NoRootWidget,
+    /// A deferred mutation was given a widget type that doesn't match the widget it was queued on.
+    WrongWidgetType,
+}
+
+impl fmt::Debug for Deferred {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Deferred")
+            .field("widget_id", &self.widget_id)
+            .finish()
+    }
 }
 
 impl UpdateState {
@@ -54,25 +94,54 @@ impl UpdateState {
         Rc::new(
             RefCell::new(UpdateState {
                 redraw: FnvHashSet::default(),
+                redraw_rects: FnvHashMap::default(),
                 relayout: FnvHashSet::default(),
                 update_timers: FnvHashSet::default(),
                 update_messages: FnvHashSet::default(),
                 remove_from_tree: FnvHashSet::default(),
                 set_cursor_icon: None,
                 set_cursor_pos: None,
+                set_window_title: None,
+                close_requested: false,
                 message_sender: message_bus.sender(),
+                send_message_sender: message_bus.send_sender(),
+                actions: Vec::new(),
+                deferred: Vec::new(),
+                focus_scope_ops: Vec::new(),
                 global_update: true,
             })
         )
     }
 
-    fn queue_insert_id(&mut self, id: WidgetId) {
+    /// Queue a widget that just joined the tree to be laid out, drawn, and registered with the timer
+    /// and message systems.
+    pub fn queue_insert_id(&mut self, id: WidgetId) {
+        // The widget may have left the tree earlier in the frame. It's back now, so it shouldn't
+        // get removed.
+        self.remove_from_tree.remove(&id);
         self.redraw.insert(id);
+        self.redraw_rects.remove(&id);
         self.relayout.insert(id);
         self.update_timers.insert(id);
         self.update_messages.insert(id);
     }
 
+    /// Drop any updates queued for a widget that has left the tree, and queue it to be scrubbed from
+    /// the focus, message, and timer bookkeeping at the end of the frame.
+    pub fn queue_remove_id(&mut self, id: WidgetId) {
+        self.redraw.remove(&id);
+        self.redraw_rects.remove(&id);
+        self.relayout.remove(&id);
+        self.update_timers.remove(&id);
+        self.update_messages.remove(&id);
+        self.remove_from_tree.insert(id);
+    }
+
+    /// Take every mutation deferred since the last call, in the order they were queued.
+    pub fn take_deferred(&mut self) -> Vec<Deferred> {
+        mem::replace(&mut self.deferred, Vec::new())
+    }
+
     pub fn queue_global_update(&mut self) {
         self.global_update = true;
     }
@@ -86,6 +155,7 @@ impl UpdateStateShared {
     pub fn new() -> UpdateStateShared {
         UpdateStateShared::Vacant(UpdateStateVacant {
             buffered_messages: Vec::new(),
+            buffered_actions: Vec::new(),
         })
     }
 
@@ -128,6 +198,7 @@ impl UpdateStateShared {
                     for message in vacant.buffered_messages.drain(..) {
                         parent_state.message_sender.send(message).ok();
                     }
+                    parent_state.actions.extend(vacant.buffered_actions.drain(..));
                 }
 
                 *this = UpdateStateShared::Occupied(parent_state.clone())
@@ -137,6 +208,7 @@ impl UpdateStateShared {
                     {
                         let mut old_state = old_state.borrow_mut();
                         old_state.redraw.remove(&id);
+                        old_state.redraw_rects.remove(&id);
                         old_state.relayout.remove(&id);
                         old_state.remove_from_tree.insert(id);
                     }
@@ -153,6 +225,7 @@ impl UpdateStateShared {
             UpdateStateShared::Occupied(update_state) => {
                 let mut update_state = update_state.borrow_mut();
                 update_state.redraw.insert(id);
+                update_state.redraw_rects.remove(&id);
             },
             // All updates are automatically performed on a fresh insert so we don't need to log that
             // an update was requested.
@@ -160,6 +233,23 @@ impl UpdateStateShared {
         });
     }
 
+    pub fn request_redraw_rect(&mut self, id: WidgetId, rect: BoundBox<D2, i32>) {
+        self.upgrade(|this| match this {
+            UpdateStateShared::Occupied(update_state) => {
+                let mut update_state = update_state.borrow_mut();
+                let update_state = &mut *update_state;
+                match (update_state.redraw.insert(id), update_state.redraw_rects.get_mut(&id)) {
+                    // The entire widget's already getting redrawn.
+                    (false, None) => (),
+                    (_, Some(redraw_rect)) => *redraw_rect = union(*redraw_rect, rect),
+                    (true, None) => {update_state.redraw_rects.insert(id, rect);},
+                }
+            },
+            // Ditto.
+            UpdateStateShared::Vacant(_) => ()
+        });
+    }
+
     pub fn request_relayout(&mut self, id: WidgetId) {
         self.upgrade(|this| match this {
             UpdateStateShared::Occupied(update_state) => {
@@ -209,6 +299,19 @@ impl UpdateStateShared {
         });
     }
 
+    pub fn send_action<A: 'static>(&mut self, action: A) {
+        let action = Box::new(action) as Message;
+        self.upgrade(|this| match this {
+            UpdateStateShared::Occupied(update_state) => {
+                let mut update_state = update_state.borrow_mut();
+                update_state.actions.push(action);
+            },
+            UpdateStateShared::Vacant(vacant) => {
+                vacant.buffered_actions.push(action);
+            }
+        });
+    }
+
     pub fn request_set_cursor_pos(&mut self, id: WidgetId, pos: Point2<i32>) -> Result<(), UpdateError> {
         self.upgrade(|this| match this {
             UpdateStateShared::Occupied(update_state) => {
@@ -231,17 +334,108 @@ impl UpdateStateShared {
         })
     }
 
-    pub fn remove_from_tree(&mut self, id: WidgetId) {
+    pub fn request_set_window_title(&mut self, title: String) -> Result<(), UpdateError> {
+        self.upgrade(|this| match this {
+            UpdateStateShared::Occupied(update_state) => {
+                let mut update_state = update_state.borrow_mut();
+                update_state.set_window_title = Some(title);
+                Ok(())
+            },
+            UpdateStateShared::Vacant(_) => Err(UpdateError::NoRootWidget)
+        })
+    }
+
+    pub fn request_close_window(&mut self) -> Result<(), UpdateError> {
+        self.upgrade(|this| match this {
+            UpdateStateShared::Occupied(update_state) => {
+                update_state.borrow_mut().close_requested = true;
+                Ok(())
+            },
+            UpdateStateShared::Vacant(_) => Err(UpdateError::NoRootWidget)
+        })
+    }
+
+    pub fn request_focus_scope_op(&mut self, op: FocusScopeOp) -> Result<(), UpdateError> {
+        self.upgrade(|this| match this {
+            UpdateStateShared::Occupied(update_state) => {
+                update_state.borrow_mut().focus_scope_ops.push(op);
+                Ok(())
+            },
+            UpdateStateShared::Vacant(_) => Err(UpdateError::NoRootWidget)
+        })
+    }
+
+    pub fn defer(&mut self, id: WidgetId, f: DeferredFn) -> Result<(), UpdateError> {
         self.upgrade(|this| match this {
             UpdateStateShared::Occupied(update_state) => {
                 let mut update_state = update_state.borrow_mut();
-                update_state.redraw.remove(&id);
-                update_state.relayout.remove(&id);
-                update_state.update_timers.remove(&id);
-                update_state.update_messages.remove(&id);
-                update_state.remove_from_tree.insert(id);
+                update_state.deferred.push(Deferred{ widget_id: id, f });
+                Ok(())
             },
+            UpdateStateShared::Vacant(_) => Err(UpdateError::NoRootWidget)
+        })
+    }
+
+    pub fn send_message_sender(&mut self) -> Result<Sender<SendMessageTargeted>, UpdateError> {
+        self.upgrade(|this| match this {
+            UpdateStateShared::Occupied(update_state) => Ok(update_state.borrow().send_message_sender.clone()),
+            UpdateStateShared::Vacant(_) => Err(UpdateError::NoRootWidget)
+        })
+    }
+
+    pub fn remove_from_tree(&mut self, id: WidgetId) {
+        self.upgrade(|this| match this {
+            UpdateStateShared::Occupied(update_state) => update_state.borrow_mut().queue_remove_id(id),
             UpdateStateShared::Vacant(_) => ()
         });
     }
 }
+
+/// The smallest rectangle containing both `a` and `b`.
+pub(crate) fn union(a: BoundBox<D2, i32>, b: BoundBox<D2, i32>) -> BoundBox<D2, i32> {
+    BoundBox::new2(
+        i32::min(a.min.x, b.min.x),
+        i32::min(a.min.y, b.min.y),
+        i32::max(a.max.x, b.max.x),
+        i32::max(a.max.y, b.max.y),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widget::WidgetTag;
+
+    #[test]
+    fn coalesce_requests() {
+        let message_bus = MessageBus::new();
+        let update_state = UpdateState::new(&message_bus);
+        let mut widget_tag = WidgetTag::new();
+        widget_tag.set_owning_update_state(&update_state);
+        {
+            let mut update_state = update_state.borrow_mut();
+            update_state.redraw.clear();
+            update_state.relayout.clear();
+        }
+
+        for _ in 0..16 {
+            widget_tag.request_redraw().request_relayout();
+        }
+        {
+            let update_state = update_state.borrow();
+            assert_eq!(1, update_state.redraw.len());
+            assert!(update_state.redraw_rects.is_empty());
+            assert_eq!(1, update_state.relayout.len());
+        }
+
+        // Partial redraws get merged into a single rect.
+        update_state.borrow_mut().redraw.clear();
+        widget_tag.request_redraw_rect(BoundBox::new2(0, 0, 4, 4));
+        widget_tag.request_redraw_rect(BoundBox::new2(8, 2, 12, 6));
+        {
+            let update_state = update_state.borrow();
+            assert_eq!(1, update_state.redraw.len());
+            assert_eq!(Some(&BoundBox::new2(0, 0, 12, 6)), update_state.redraw_rects.get(&widget_tag.widget_id()));
+        }
+    }
+}
@@ -5,17 +5,19 @@
 pub mod dynamic;
 pub(crate) use dynamic::WidgetDyn;
 pub use crate::{
-    message_bus::MessageTarget,
+    message_bus::{MessageTarget, ProgressSender, ProgressError},
     update_state::UpdateError,
 };
 
 use crate::{
     LoopFlow,
+    accessibility::AccessibilityNode,
     event::{WidgetEventSourced, EventOps, InputState},
     message_bus::{WidgetMessageKey, WidgetMessageFn},
-    render::{Renderer, WidgetTheme},
+    render::{DisabledStyle, Renderer, WidgetTheme},
     timer::{TimerId, Timer},
-    update_state::{UpdateStateShared, UpdateStateCell},
+    ui_state::UiStateValue,
+    update_state::{DeferredFn, FocusScopeOp, UpdateStateShared, UpdateStateCell},
 };
 use derin_common_types::{
     cursor::CursorIcon,
@@ -27,18 +29,21 @@ use std::{
     borrow::{Borrow, BorrowMut},
     cell::{Cell, RefCell},
     fmt,
+    hash::{Hash, Hasher},
+    iter::FromIterator,
     ops::Drop,
     rc::Rc,
     sync::Arc,
 };
 use cgmath_geometry::{
-    D2, rect::BoundBox,
-    cgmath::Point2,
+    D2, rect::{BoundBox, DimsBox},
+    cgmath::{Point2, Vector2},
 };
-use fnv::FnvHashMap;
+use fnv::{FnvHashMap, FnvHasher};
 
 
 pub(crate) const ROOT_IDENT: WidgetIdent = WidgetIdent::Num(0);
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum WidgetIdent {
     Str(Arc<str>),
@@ -47,11 +52,53 @@ pub enum WidgetIdent {
     NumCollection(u32, u32)
 }
 
+/// A path of idents addressing a nested widget, for use as a map key.
+///
+/// The idents are stored behind an `Arc` and the path's hash is computed once on construction, so
+/// cloning, hashing, and comparing keys is cheap regardless of how deep the path is. `Str` idents
+/// share their `Arc<str>`s with the idents the key was built from.
+///
+/// This is useful for storing state that should outlive the widgets it belongs to, such as which
+/// nodes in a tree are expanded, since widgets can be rebuilt with new `WidgetId`s but keep
+/// the same idents.
+#[derive(Clone)]
+pub struct WidgetPathKey {
+    idents: Arc<[WidgetIdent]>,
+    hash: u64,
+}
+
+/// The error returned when looking up a descendant widget by its path fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChildPathError {
+    /// The path didn't contain any idents. The widget the lookup was performed on is the target,
+    /// which isn't its own child.
+    EmptyPath,
+    /// No widget existed for the ident at the given index into the path.
+    NotFound(usize),
+}
+
 pub struct WidgetTag {
     update_state: RefCell<UpdateStateShared>,
     registered_messages: FnvHashMap<WidgetMessageKey, Cell<SmallVec<[WidgetMessageFn; 1]>>>,
     pub(crate) widget_id: WidgetId,
     pub(crate) timers: FnvHashMap<TimerId, Timer>,
+    user_data: Option<Box<Any>>,
+    pointer_transparent: bool,
+    popup: bool,
+    access_key: Option<char>,
+    tab_index: Option<i32>,
+    has_keyboard_focus: Cell<bool>,
+    /// The offset from the window's origin to the origin of the space the widget's rect is in.
+    pub(crate) window_offset: Cell<Option<Vector2<i32>>>,
+    /// The type of the widget the tag belongs to, recorded once it's been added to a widget tree.
+    widget_type: Cell<Option<TypeId>>,
+    draws_own_focus_ring: bool,
+    focus_scope: bool,
+    enabled: bool,
+    /// Dropped alongside the tag, so that `ProgressSender`s can tell when the widget is gone.
+    progress_token: Option<Arc<()>>,
+    #[cfg(feature = "message-type-names")]
+    message_type_names: FnvHashMap<TypeId, &'static str>,
 }
 
 impl fmt::Debug for WidgetTag {
@@ -63,10 +110,22 @@ impl fmt::Debug for WidgetTag {
 }
 
 impl Clone for WidgetTag {
-    /// This doesn't actually clone the `WidgetTag` - it just creates a new one and returns it. This
-    /// function is provided primarily to allow widgets to cleanly derive `Clone`.
+    /// Create a new `WidgetTag` with the same configuration as this one. This function is provided
+    /// primarily to allow widgets to cleanly derive `Clone`.
+    ///
+    /// The clone gets a new `WidgetId`, and isn't part of any widget tree. Flags set through the
+    /// tag, such as pointer transparency, the access key, the tab index, and whether the widget is
+    /// enabled, are copied over. Registered messages, timers, user data, and any pushed focus
+    /// scope aren't.
     fn clone(&self) -> WidgetTag {
-        WidgetTag::new()
+        let mut widget_tag = WidgetTag::new();
+        widget_tag.pointer_transparent = self.pointer_transparent;
+        widget_tag.popup = self.popup;
+        widget_tag.access_key = self.access_key;
+        widget_tag.tab_index = self.tab_index;
+        widget_tag.draws_own_focus_ring = self.draws_own_focus_ring;
+        widget_tag.enabled = self.enabled;
+        widget_tag
     }
 }
 
@@ -95,6 +154,83 @@ pub trait Widget: 'static {
         SizeBounds::default()
     }
 
+    /// The size the widget needs to display its contents without compromising them, such as the
+    /// width a label needs to show its text without wrapping.
+    ///
+    /// Layout engines try to give the widget at least this much space, but will shrink the widget
+    /// down to `size_bounds().min` if there isn't room. Defaults to `size_bounds().min`.
+    fn min_intrinsic_size(&self) -> DimsBox<D2, i32> {
+        self.size_bounds().min
+    }
+
+    /// Called by the layout pass when the widget's parent moves or resizes it, with the rect the
+    /// widget had beforehand.
+    ///
+    /// This only gets called if the rect actually changed, and always gets called before the
+    /// widget is next rendered. Unlike `WidgetRenderable::update_layout`, this doesn't depend on
+    /// the renderer, so widgets that derive state from their size (such as which rows of a list
+    /// are visible) can use it without implementing custom rendering.
+    fn on_update_layout(&mut self, _old_rect: BoundBox<D2, i32>) {}
+
+    /// Whether or not the widget can be given keyboard focus by `FocusChange::Next` and
+    /// `FocusChange::Prev`.
+    ///
+    /// Widgets that don't accept focus get skipped over when moving focus between siblings.
+    /// Widgets that can be disabled should return `false` while they're disabled. This doesn't
+    /// stop a widget from taking focus for itself with `FocusChange::Take`.
+    fn accepts_focus(&self) -> bool {
+        false
+    }
+
+    /// Whether `point`, relative to the widget's origin, is part of the widget.
+    ///
+    /// This is only called for points inside of the widget's rect. Points that miss the widget
+    /// fall through to whichever sibling (or the parent) lies beneath it, which lets
+    /// irregularly-shaped widgets ignore clicks on their transparent areas; `render::HitMask` can
+    /// be used to test against an image's alpha. Defaults to `true`.
+    fn hit_test(&self, _point: Point2<i32>) -> bool {
+        true
+    }
+
+    /// Describe the widget to assistive technologies, such as screen readers.
+    ///
+    /// Defaults to `None`, which is appropriate for widgets that only lay out other widgets. The
+    /// `focused` state flag gets filled in by the framework, so it doesn't need to be set here.
+    /// See `Root::accessibility_tree`.
+    fn accessibility_info(&self) -> Option<AccessibilityNode> {
+        None
+    }
+
+    /// Snapshot the widget's transient UI state, such as a scroll offset or which nodes of a tree
+    /// are expanded, so that it can be restored later with `restore_state`.
+    ///
+    /// The state gets stored in a `UiState` under the widget's ident path, so it only makes sense
+    /// for state that should follow the widget's position in the tree. Application data shouldn't
+    /// be saved this way. Defaults to `None`, which saves nothing. See `Root::save_ui_state`.
+    fn save_state(&self) -> Option<UiStateValue> {
+        None
+    }
+
+    /// Restore state previously returned by `save_state`.
+    ///
+    /// This gets called when `Root::restore_ui_state` is called, or when a widget first appears at
+    /// a path that has unrestored state. The state may have been saved by an older version of the
+    /// application, so widgets should ignore values they don't understand rather than panicking.
+    fn restore_state(&mut self, _state: &UiStateValue) {}
+
+    /// Downcast the widget to a `Parent`, if it has children.
+    ///
+    /// This is implemented automatically for every widget that implements `Parent`, and shouldn't
+    /// need to be overridden.
+    fn as_parent(&self) -> Option<&dyn Parent> {
+        dynamic::as_parent(self)
+    }
+
+    /// Mutably downcast the widget to a `Parent`, if it has children.
+    fn as_parent_mut(&mut self) -> Option<&mut dyn Parent> {
+        dynamic::as_parent_mut(self)
+    }
+
     #[doc(hidden)]
     fn dispatch_message(&mut self, message: &Any) {
         let message_key = WidgetMessageKey::from_dyn_message::<Self>(message);
@@ -133,6 +269,23 @@ pub trait WidgetRenderable<R: Renderer>: Widget {
     fn theme(&self) -> Self::Theme;
     fn render(&mut self, frame: &mut R::SubFrame);
     fn update_layout(&mut self, _layout: &mut R::Layout) {}
+
+    /// How the widget gets drawn while it or one of its ancestors is disabled. Defaults to
+    /// `DisabledStyle::Desaturate`.
+    ///
+    /// Widgets that draw their own disabled appearance should return `DisabledStyle::Custom`,
+    /// and check `WidgetTag::is_enabled` when building their theme.
+    fn disabled_style(&self) -> DisabledStyle {
+        DisabledStyle::Desaturate
+    }
+
+    /// Called on every widget when the theme gets replaced with `Root::set_theme`, with the new
+    /// theme.
+    ///
+    /// Widgets that cache anything derived from the theme, such as colors or metrics, should throw
+    /// those caches away here. The whole tree gets re-laid out and redrawn after the theme
+    /// changes, so there's no need to request either.
+    fn on_theme_changed(&mut self, _theme: &R::Theme) {}
 }
 
 impl<W> Widget for Box<W>
@@ -160,6 +313,42 @@ impl<W> Widget for Box<W>
         W::size_bounds(self)
     }
 
+    fn min_intrinsic_size(&self) -> DimsBox<D2, i32> {
+        W::min_intrinsic_size(self)
+    }
+
+    fn on_update_layout(&mut self, old_rect: BoundBox<D2, i32>) {
+        W::on_update_layout(self, old_rect)
+    }
+
+    fn accepts_focus(&self) -> bool {
+        W::accepts_focus(self)
+    }
+
+    fn hit_test(&self, point: Point2<i32>) -> bool {
+        W::hit_test(self, point)
+    }
+
+    fn accessibility_info(&self) -> Option<AccessibilityNode> {
+        W::accessibility_info(self)
+    }
+
+    fn save_state(&self) -> Option<UiStateValue> {
+        W::save_state(self)
+    }
+
+    fn restore_state(&mut self, state: &UiStateValue) {
+        W::restore_state(self, state)
+    }
+
+    fn as_parent(&self) -> Option<&dyn Parent> {
+        W::as_parent(self)
+    }
+
+    fn as_parent_mut(&mut self) -> Option<&mut dyn Parent> {
+        W::as_parent_mut(self)
+    }
+
     fn dispatch_message(&mut self, message: &Any) {
         W::dispatch_message(self, message)
     }
@@ -168,6 +357,9 @@ impl<W> Widget for Box<W>
 pub struct WidgetInfo<'a, R: Renderer, S: ?Sized=Widget> {
     pub ident: WidgetIdent,
     pub index: usize,
+    /// The offset from the window's origin to the parent's origin, if it's known. This gets
+    /// filled in from the child's `WidgetTag::window_offset`. See `window_rect`.
+    pub window_offset: Option<Vector2<i32>>,
     pub(crate) widget: &'a WidgetDyn<R>,
     to_secondary: fn(&'_ WidgetDyn<R>) -> &'_ S,
 }
@@ -175,6 +367,9 @@ pub struct WidgetInfo<'a, R: Renderer, S: ?Sized=Widget> {
 pub struct WidgetInfoMut<'a, R: Renderer, S: ?Sized=Widget> {
     pub ident: WidgetIdent,
     pub index: usize,
+    /// The offset from the window's origin to the parent's origin, if it's known. This gets
+    /// filled in from the child's `WidgetTag::window_offset`. See `window_rect`.
+    pub window_offset: Option<Vector2<i32>>,
     pub(crate) widget: &'a mut WidgetDyn<R>,
     to_secondary: fn(Reference<'_, WidgetDyn<R>>) -> Reference<'_, S>
 }
@@ -215,6 +410,17 @@ pub trait Parent: Widget {
               R: Renderer,
               G: FnMut(WidgetInfoMut<'a, R>) -> LoopFlow;
 
+    /// The child that should receive keyboard focus when `FocusChange::Next` or `FocusChange::Prev`
+    /// moves focus into this widget from one of its siblings, such as a dialog's default button.
+    ///
+    /// If the child doesn't accept focus itself, focus goes to the child's own preferred or first
+    /// focusable descendant. If this returns `None`, or the child has nothing that can take focus,
+    /// focus goes to the first descendant in tab order that accepts focus (or the last, when moving
+    /// backwards). Only consulted if this widget doesn't accept focus itself.
+    fn preferred_focus_child(&self) -> Option<WidgetIdent> {
+        None
+    }
+
     // Ideally all these functions should be callable by `dyn Parent` and automatically implemented
     // with `default impl` (see RFC 1210) but that hasn't been implemented yet in rustc.
     //
@@ -255,6 +461,44 @@ pub trait Parent: Widget {
     {
         self.framed_children_mut::<!, G>(for_each)
     }
+
+    /// Get the descendant at the given path, where each ident in `path` names a child of the
+    /// widget named by the previous ident.
+    fn framed_child_at_path<R: Renderer>(&self, path: &[WidgetIdent]) -> Result<WidgetInfo<'_, R>, ChildPathError>
+        where Self: Sized
+    {
+        let (first, rest) = path.split_first().ok_or(ChildPathError::EmptyPath)?;
+        let mut info = self.framed_child(first.clone()).ok_or(ChildPathError::NotFound(0))?;
+        for (i, ident) in rest.iter().enumerate() {
+            info = info.widget.child(ident.clone()).ok_or(ChildPathError::NotFound(i + 1))?;
+        }
+        Ok(info)
+    }
+
+    /// Mutably get the descendant at the given path, where each ident in `path` names a child of
+    /// the widget named by the previous ident.
+    fn framed_child_at_path_mut<R: Renderer>(&mut self, path: &[WidgetIdent]) -> Result<WidgetInfoMut<'_, R>, ChildPathError>
+        where Self: Sized
+    {
+        let (first, rest) = path.split_first().ok_or(ChildPathError::EmptyPath)?;
+        let mut info = self.framed_child_mut(first.clone()).ok_or(ChildPathError::NotFound(0))?;
+        for (i, ident) in rest.iter().enumerate() {
+            info = info.widget.child_mut(ident.clone()).ok_or(ChildPathError::NotFound(i + 1))?;
+        }
+        Ok(info)
+    }
+
+    fn child_at_path(&self, path: &[WidgetIdent]) -> Result<WidgetInfo<'_, !>, ChildPathError>
+        where Self: Sized
+    {
+        self.framed_child_at_path::<!>(path)
+    }
+
+    fn child_at_path_mut(&mut self, path: &[WidgetIdent]) -> Result<WidgetInfoMut<'_, !>, ChildPathError>
+        where Self: Sized
+    {
+        self.framed_child_at_path_mut::<!>(path)
+    }
 }
 
 pub trait WidgetSubtype<W: Widget + ?Sized> {
@@ -322,6 +566,7 @@ impl<'a, R, S> WidgetInfo<'a, R, S>
         WidgetInfo {
             ident,
             index,
+            window_offset: widget.widget_tag().window_offset(),
             widget: WidgetDyn::new(widget),
             to_secondary: |r| {
                 if r.type_id() == TypeId::of::<W>() {
@@ -337,6 +582,21 @@ impl<'a, R, S> WidgetInfo<'a, R, S>
         self.widget.to_widget()
     }
 
+    /// Set the offset from the window's origin to the parent's origin.
+    ///
+    /// The offset Derin recorded the last time it walked past the child gets used by default.
+    /// Parents that have moved since then, or that build infos for children Derin hasn't visited
+    /// yet, can pass their own origin in window coordinates here.
+    pub fn with_window_offset(mut self, window_offset: Vector2<i32>) -> WidgetInfo<'a, R, S> {
+        self.window_offset = Some(window_offset);
+        self
+    }
+
+    /// The child's rect in window coordinates, or `None` if the window offset isn't known.
+    pub fn window_rect(&self) -> Option<BoundBox<D2, i32>> {
+        self.window_offset.map(|offset| self.widget.rect() + offset)
+    }
+
     pub fn subtype(&self) -> &S {
         self.borrow()
     }
@@ -345,6 +605,7 @@ impl<'a, R, S> WidgetInfo<'a, R, S>
         WidgetInfo {
             ident: self.ident,
             index: self.index,
+            window_offset: self.window_offset,
             widget: self.widget,
             to_secondary: |r| {
                 r.to_widget()
@@ -364,6 +625,7 @@ impl<'a, R, S> WidgetInfoMut<'a, R, S>
         WidgetInfoMut {
             ident,
             index,
+            window_offset: widget.widget_tag().window_offset(),
             widget: WidgetDyn::new_mut(widget),
             to_secondary: |r| {
                 if r.as_ref().type_id() == TypeId::of::<W>() {
@@ -388,6 +650,18 @@ impl<'a, R, S> WidgetInfoMut<'a, R, S>
         self.widget.to_widget_mut()
     }
 
+    /// Set the offset from the window's origin to the parent's origin. See
+    /// `WidgetInfo::with_window_offset`.
+    pub fn with_window_offset(mut self, window_offset: Vector2<i32>) -> WidgetInfoMut<'a, R, S> {
+        self.window_offset = Some(window_offset);
+        self
+    }
+
+    /// The child's rect in window coordinates, or `None` if the window offset isn't known.
+    pub fn window_rect(&self) -> Option<BoundBox<D2, i32>> {
+        self.window_offset.map(|offset| self.widget.rect() + offset)
+    }
+
     pub fn subtype(&self) -> &S {
         self.borrow()
     }
@@ -400,6 +674,7 @@ impl<'a, R, S> WidgetInfoMut<'a, R, S>
         WidgetInfoMut {
             ident: self.ident,
             index: self.index,
+            window_offset: self.window_offset,
             widget: self.widget,
             to_secondary: |r| match r {
                 Reference::Ref(r) => Reference::Ref(r.to_widget()),
@@ -452,6 +727,102 @@ impl WidgetIdent {
     }
 }
 
+impl WidgetPathKey {
+    /// The key for the empty path, which addresses the widget the path is relative to.
+    pub fn empty() -> WidgetPathKey {
+        WidgetPathKey::from_idents(Arc::from(Vec::new()))
+    }
+
+    fn from_idents(idents: Arc<[WidgetIdent]>) -> WidgetPathKey {
+        let mut hasher = FnvHasher::default();
+        idents.hash(&mut hasher);
+        WidgetPathKey {
+            hash: hasher.finish(),
+            idents,
+        }
+    }
+
+    #[inline]
+    pub fn idents(&self) -> &[WidgetIdent] {
+        &self.idents
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.idents.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.idents.is_empty()
+    }
+
+    /// The key for the path to the child of this path's widget with the given ident.
+    pub fn child(&self, ident: WidgetIdent) -> WidgetPathKey {
+        self.idents.iter().cloned().chain(Some(ident)).collect()
+    }
+
+    /// The key for the path to this path's parent widget, or `None` if the path is empty.
+    pub fn parent(&self) -> Option<WidgetPathKey> {
+        let (_, parent) = self.idents.split_last()?;
+        Some(parent.iter().cloned().collect())
+    }
+
+    /// Whether this path starts with every ident in `prefix`.
+    pub fn starts_with(&self, prefix: &WidgetPathKey) -> bool {
+        self.idents.starts_with(&prefix.idents)
+    }
+}
+
+impl FromIterator<WidgetIdent> for WidgetPathKey {
+    fn from_iter<I: IntoIterator<Item=WidgetIdent>>(iter: I) -> WidgetPathKey {
+        WidgetPathKey::from_idents(iter.into_iter().collect::<Vec<_>>().into())
+    }
+}
+
+impl<'a> From<&'a [WidgetIdent]> for WidgetPathKey {
+    fn from(idents: &'a [WidgetIdent]) -> WidgetPathKey {
+        idents.iter().cloned().collect()
+    }
+}
+
+impl PartialEq for WidgetPathKey {
+    fn eq(&self, other: &WidgetPathKey) -> bool {
+        self.hash == other.hash &&
+        (Arc::ptr_eq(&self.idents, &other.idents) || self.idents == other.idents)
+    }
+}
+
+impl Eq for WidgetPathKey {}
+
+impl Hash for WidgetPathKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for WidgetPathKey {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.idents.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for WidgetPathKey {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<WidgetPathKey, D::Error> {
+        Vec::<WidgetIdent>::deserialize(deserializer).map(|idents| WidgetPathKey::from_idents(idents.into()))
+    }
+}
+
+impl fmt::Debug for WidgetPathKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_list()
+            .entries(self.idents.iter())
+            .finish()
+    }
+}
+
 impl WidgetTag {
     #[inline]
     pub fn new() -> WidgetTag {
@@ -460,6 +831,20 @@ impl WidgetTag {
             widget_id: WidgetId::new(),
             registered_messages: FnvHashMap::default(),
             timers: FnvHashMap::default(),
+            user_data: None,
+            pointer_transparent: false,
+            popup: false,
+            access_key: None,
+            tab_index: None,
+            has_keyboard_focus: Cell::new(false),
+            window_offset: Cell::new(None),
+            widget_type: Cell::new(None),
+            draws_own_focus_ring: false,
+            focus_scope: false,
+            enabled: true,
+            progress_token: None,
+            #[cfg(feature = "message-type-names")]
+            message_type_names: FnvHashMap::default(),
         }
     }
 
@@ -468,12 +853,41 @@ impl WidgetTag {
         self.widget_id
     }
 
+    /// The offset from the window's origin to the origin of the widget's parent, which the widget's
+    /// rect is relative to. Adding this to the widget's rect gives the rect in window coordinates.
+    ///
+    /// This is recorded whenever Derin visits the widget while walking the tree, so it's up to date
+    /// during event handling, layout, and rendering, but may be stale if the widget's ancestors
+    /// have been moved since. Returns `None` if Derin hasn't visited the widget yet.
+    #[inline]
+    pub fn window_offset(&self) -> Option<Vector2<i32>> {
+        self.window_offset.get()
+    }
+
+    /// Request that the widget be redrawn in the next frame.
+    ///
+    /// Requests are tracked per widget, so calling this several times before the next frame still
+    /// only redraws the widget once.
     #[inline]
     pub fn request_redraw(&mut self) -> &mut WidgetTag {
         self.update_state.get_mut().request_redraw(self.widget_id);
         self
     }
 
+    /// Request that only part of the widget be redrawn, for widgets that changed a small part of
+    /// themselves. `rect` is relative to the widget's origin.
+    ///
+    /// Multiple requests in the same frame get combined, and a call to `request_redraw` overrides
+    /// them and redraws the whole widget. Renderers can use the combined region to avoid repainting
+    /// the rest of the window; see `Renderer::set_redraw_region`.
+    #[inline]
+    pub fn request_redraw_rect(&mut self, rect: BoundBox<D2, i32>) -> &mut WidgetTag {
+        self.update_state.get_mut().request_redraw_rect(self.widget_id, rect);
+        self
+    }
+
+    /// Request that the widget be laid out again before the next frame. Like `request_redraw`,
+    /// repeated requests get coalesced into a single relayout.
     #[inline]
     pub fn request_relayout(&mut self) -> &mut WidgetTag {
         self.update_state.get_mut().request_relayout(self.widget_id);
@@ -489,6 +903,137 @@ impl WidgetTag {
         &mut self.timers
     }
 
+    /// Cancel all of the widget's timers. The widget won't receive any more `Timer` events until
+    /// new timers are added.
+    pub fn clear_timers(&mut self) {
+        if self.timers.len() > 0 {
+            self.timers_mut().clear();
+        }
+    }
+
+    /// Attach arbitrary data to the widget, returning the data that was previously attached.
+    ///
+    /// The data persists across events and survives the widget being moved within the tree, and
+    /// is dropped along with the widget. It isn't carried over when the tag is cloned.
+    pub fn set_user_data(&mut self, user_data: Box<Any>) -> Option<Box<Any>> {
+        self.user_data.replace(user_data)
+    }
+
+    /// Remove the data attached to the widget.
+    pub fn take_user_data(&mut self) -> Option<Box<Any>> {
+        self.user_data.take()
+    }
+
+    /// Retrieve the data attached to the widget, if it's of type `T`.
+    pub fn user_data<T: 'static>(&self) -> Option<&T> {
+        self.user_data.as_ref().and_then(|d| d.downcast_ref())
+    }
+
+    /// Retrieve the data attached to the widget for mutation, if it's of type `T`.
+    pub fn user_data_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        self.user_data.as_mut().and_then(|d| d.downcast_mut())
+    }
+
+    /// Whether the widget is skipped when finding the widget under the mouse cursor.
+    #[inline]
+    pub fn pointer_transparent(&self) -> bool {
+        self.pointer_transparent
+    }
+
+    /// Make mouse input pass through the widget and its children.
+    ///
+    /// A transparent widget is still laid out and rendered as usual, but it never becomes the
+    /// hover or click target: pointer events go to whichever sibling (or the parent) lies
+    /// underneath it instead. This only affects pointer routing - the widget can still take
+    /// keyboard focus and receives keyboard events while focused.
+    pub fn set_pointer_transparent(&mut self, pointer_transparent: bool) {
+        self.pointer_transparent = pointer_transparent;
+    }
+
+    /// Whether the widget takes the cursor from the siblings it overlaps.
+    #[inline]
+    pub fn is_popup(&self) -> bool {
+        self.popup
+    }
+
+    /// Mark the widget as a popup, such as a drop-down menu, that lies over its siblings.
+    ///
+    /// Normally, the first of several overlapping siblings takes the mouse cursor. Popups take the
+    /// cursor from the siblings they overlap instead, and if popups overlap each other, the last
+    /// one takes it. This only affects pointer routing: popups should be placed after the siblings
+    /// they overlap, so that they get drawn over them.
+    pub fn set_popup(&mut self, popup: bool) {
+        self.popup = popup;
+    }
+
+    /// The character that, pressed alongside `Alt`, activates the widget.
+    #[inline]
+    pub fn access_key(&self) -> Option<char> {
+        self.access_key
+    }
+
+    /// Set the character that, pressed alongside `Alt`, sends the widget a `WidgetEvent::AccessKey`
+    /// event. Only letters and digits can be typed as access keys, and letters are matched
+    /// case-insensitively.
+    ///
+    /// If several widgets share an access key, the first one in the tree gets activated.
+    pub fn set_access_key(&mut self, access_key: Option<char>) {
+        self.access_key = access_key.and_then(|c| c.to_lowercase().next());
+    }
+
+    /// The widget's explicit position in the keyboard focus order, if it has one.
+    #[inline]
+    pub fn tab_index(&self) -> Option<i32> {
+        self.tab_index
+    }
+
+    /// Override where the widget falls when `FocusChange::Next` and `FocusChange::Prev` move focus
+    /// between it and its siblings.
+    ///
+    /// Siblings with explicit tab indices are visited first, in ascending order, with ties broken
+    /// by tree order. Siblings without a tab index follow in tree order. Passing `None` restores
+    /// the default tree ordering.
+    pub fn set_tab_index(&mut self, tab_index: Option<i32>) {
+        self.tab_index = tab_index;
+    }
+
+    /// Whether the widget draws its own focus indicator.
+    #[inline]
+    pub fn draws_own_focus_ring(&self) -> bool {
+        self.draws_own_focus_ring
+    }
+
+    /// Stop the framework from drawing the theme's focus ring around the widget while it has
+    /// keyboard focus. Widgets that opt out should check `has_keyboard_focus` when rendering and
+    /// draw their own indicator.
+    pub fn set_draws_own_focus_ring(&mut self, draws_own_focus_ring: bool) {
+        self.draws_own_focus_ring = draws_own_focus_ring;
+    }
+
+    /// Whether the widget is enabled. Defaults to `true`.
+    ///
+    /// This only reflects the widget's own flag. Widgets are also drawn disabled if any of their
+    /// ancestors are disabled.
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enable or disable the widget. Disabled widgets, along with their descendants, don't get
+    /// input events or keyboard focus, and are drawn in the style given by
+    /// `WidgetRenderable::disabled_style`.
+    pub fn set_enabled(&mut self, enabled: bool) -> &mut WidgetTag {
+        if self.enabled != enabled {
+            self.enabled = enabled;
+            self.request_redraw();
+        }
+        self
+    }
+
+    /// Register a function that gets called when the widget receives a message of type `A`.
+    ///
+    /// `f` can send follow-up messages and actions through the widget's tag. Follow-up messages
+    /// get delivered after every message that was already queued, within the same frame.
     pub fn register_message<W, A>(&mut self, mut f: impl 'static + FnMut(&mut W, &A))
         where W: 'static,
               A: 'static
@@ -505,12 +1050,39 @@ impl WidgetTag {
             .or_insert(Cell::new(SmallVec::new()))
             .get_mut()
             .push(f);
+
+        #[cfg(feature = "message-type-names")]
+        self.message_type_names.insert(TypeId::of::<A>(), std::any::type_name::<A>());
     }
 
     pub fn message_types(&self) -> impl '_ + Iterator<Item=TypeId> {
         self.registered_messages.keys().map(|k| k.message_type())
     }
 
+    /// Whether or not the widget has registered a function for messages of type `A`.
+    pub fn is_subscribed<A: 'static>(&self) -> bool {
+        let message_type = TypeId::of::<A>();
+        self.message_types().any(|t| t == message_type)
+    }
+
+    /// The number of distinct message types the widget has registered functions for.
+    pub fn message_type_count(&self) -> usize {
+        let mut message_types = self.message_types().collect::<SmallVec<[_; 8]>>();
+        message_types.sort();
+        message_types.dedup();
+        message_types.len()
+    }
+
+    /// The names of every message type the widget has registered functions for, alongside their
+    /// `TypeId`s.
+    ///
+    /// Only available with the `message-type-names` feature, which records each type's name when
+    /// its function gets registered.
+    #[cfg(feature = "message-type-names")]
+    pub fn message_type_names(&self) -> impl '_ + Iterator<Item=(TypeId, &'static str)> {
+        self.message_type_names.iter().map(|(&id, &name)| (id, name))
+    }
+
     pub fn broadcast_message<A: 'static>(&mut self, message: A) {
         self.update_state.get_mut().send_message(message, None);
     }
@@ -519,6 +1091,54 @@ impl WidgetTag {
         self.update_state.get_mut().send_message(message, Some(target));
     }
 
+    /// Create a handle that can send progress updates of type `P` to this widget from anywhere,
+    /// including other threads.
+    ///
+    /// See `ProgressSender` for details. Fails if the widget hasn't been inserted into a root yet.
+    pub fn progress_sender<P: 'static + Send>(&mut self) -> Result<ProgressSender<P>, UpdateError> {
+        let sender = self.update_state.get_mut().send_message_sender()?;
+        let progress_token = self.progress_token.get_or_insert_with(|| Arc::new(()));
+        Ok(ProgressSender::new(sender, self.widget_id, Arc::downgrade(progress_token)))
+    }
+
+    /// Queue an action for the application to receive.
+    ///
+    /// This can be called from any place the widget has access to its tag, including message
+    /// handlers and timer events. Actions are delivered to the application in the order they were
+    /// queued. Within a frame, this means actions sent while handling window events come before
+    /// actions sent from message handlers, which come before actions sent from timer events.
+    pub fn send_action<A: 'static>(&mut self, action: A) {
+        self.update_state.get_mut().send_action(action);
+    }
+
+    /// Mutate the widget once the event currently being dispatched has reached every widget it's
+    /// going to.
+    ///
+    /// Adding, removing, or replacing a widget's children while events are being dispatched can
+    /// pull the widget tree out from under the dispatcher. Widgets that need to restructure
+    /// themselves in response to an event should do so in `f`, which gets called with the widget
+    /// this tag belongs to. `W` must be that widget's type.
+    ///
+    /// Deferred mutations run in the order they were queued, including when several target the
+    /// same widget - each one sees the changes made by the ones before it. Mutations deferred by
+    /// another deferred mutation run after every mutation that was already queued. If the widget
+    /// gets removed from the tree before its mutation runs, the mutation is dropped. `f` should
+    /// request a relayout if it changes the widget's children.
+    ///
+    /// Fails if the widget isn't in a widget tree, in which case it can be mutated directly, or if
+    /// `W` isn't the widget's type.
+    pub fn defer<W: 'static>(&mut self, f: impl 'static + FnOnce(&mut W)) -> Result<(), UpdateError> {
+        if self.widget_type.get().map(|t| t != TypeId::of::<W>()).unwrap_or(false) {
+            return Err(UpdateError::WrongWidgetType);
+        }
+        let f: DeferredFn = Box::new(move |widget_any| {
+            if let Some(widget) = widget_any.downcast_mut::<W>() {
+                f(widget);
+            }
+        });
+        self.update_state.get_mut().defer(self.widget_id, f)
+    }
+
     pub fn set_cursor_pos(&mut self, cursor_pos: Point2<i32>) -> Result<(), UpdateError> {
         self.update_state.get_mut().request_set_cursor_pos(self.widget_id, cursor_pos)
     }
@@ -527,15 +1147,72 @@ impl WidgetTag {
         self.update_state.get_mut().request_set_cursor_icon(cursor_icon)
     }
 
+    /// Request that the title of the window containing the widget be changed.
+    ///
+    /// If several widgets request a title in the same frame, the last request wins. Windowing
+    /// backends that don't have titles ignore the request.
+    pub fn set_window_title(&mut self, title: impl Into<String>) -> Result<(), UpdateError> {
+        self.update_state.get_mut().request_set_window_title(title.into())
+    }
+
+    /// Request that the window containing the widget be closed.
+    ///
+    /// This goes through the same path as the user clicking the window's close button, so the
+    /// close handler set with `Root::set_close_handler` can still cancel it.
+    pub fn request_close_window(&mut self) -> Result<(), UpdateError> {
+        self.update_state.get_mut().request_close_window()
+    }
+
+    /// Whether the widget is an active focus scope, pushed with `push_focus_scope`.
+    #[inline]
+    pub fn is_focus_scope(&self) -> bool {
+        self.focus_scope
+    }
+
+    /// Trap keyboard focus within the widget and its descendants, for modals, popups, and the
+    /// like.
+    ///
+    /// While the scope is active, `FocusChange::Next` and `FocusChange::Prev` between the widget's
+    /// children wrap around at the ends of the tab order instead of stopping, and focus can't be
+    /// given to any widget outside of the scope, whether by focus changes or by clicking. Scopes
+    /// nest: the most recently pushed scope is the one that's enforced. The scope takes effect
+    /// starting with the next window event.
+    ///
+    /// This doesn't move focus into the scope; use `FocusChange::ChildIndex` or similar for that.
+    pub fn push_focus_scope(&mut self) -> Result<(), UpdateError> {
+        self.update_state.get_mut().request_focus_scope_op(FocusScopeOp::Push(self.widget_id))?;
+        self.focus_scope = true;
+        Ok(())
+    }
+
+    /// Deactivate the focus scope, along with any scopes pushed after it, and give focus back to
+    /// the widget that had it when the scope was pushed.
+    pub fn pop_focus_scope(&mut self) -> Result<(), UpdateError> {
+        self.update_state.get_mut().request_focus_scope_op(FocusScopeOp::Pop(self.widget_id))?;
+        self.focus_scope = false;
+        Ok(())
+    }
+
+    /// Whether the widget currently has keyboard focus.
     #[inline]
     pub fn has_keyboard_focus(&self) -> bool {
-        unimplemented!()
+        self.has_keyboard_focus.get()
+    }
+
+    #[inline]
+    pub(crate) fn set_has_keyboard_focus(&self, has_keyboard_focus: bool) {
+        self.has_keyboard_focus.set(has_keyboard_focus);
     }
 
     #[inline]
     pub(crate) fn set_owning_update_state(&self, state: &Rc<UpdateStateCell>) {
         self.update_state.borrow_mut().set_owning_update_state(self.widget_id, state);
     }
+
+    #[inline]
+    pub(crate) fn set_widget_type(&self, widget_type: TypeId) {
+        self.widget_type.set(Some(widget_type));
+    }
 }
 
 impl Drop for WidgetTag {
@@ -543,3 +1220,126 @@ impl Drop for WidgetTag {
         self.update_state.get_mut().remove_from_tree(self.widget_id)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accessibility::AccessibilityRole;
+    use std::collections::HashSet;
+
+    /// A widget that overrides every optional `Widget` method, to check that wrappers forward them.
+    struct Described {
+        widget_tag: WidgetTag,
+        rect: BoundBox<D2, i32>,
+        state: i64,
+    }
+
+    impl Widget for Described {
+        fn widget_tag(&self) -> &WidgetTag {
+            &self.widget_tag
+        }
+
+        fn rect(&self) -> BoundBox<D2, i32> {
+            self.rect
+        }
+
+        fn rect_mut(&mut self) -> &mut BoundBox<D2, i32> {
+            &mut self.rect
+        }
+
+        fn on_widget_event(&mut self, _: WidgetEventSourced, _: InputState) -> EventOps {
+            EventOps::default()
+        }
+
+        fn hit_test(&self, point: Point2<i32>) -> bool {
+            point.x < 5
+        }
+
+        fn accessibility_info(&self) -> Option<AccessibilityNode> {
+            Some(AccessibilityNode::new(AccessibilityRole::Button))
+        }
+
+        fn save_state(&self) -> Option<UiStateValue> {
+            Some(UiStateValue::Int(self.state))
+        }
+
+        fn restore_state(&mut self, state: &UiStateValue) {
+            if let Some(state) = state.as_int() {
+                self.state = state;
+            }
+        }
+    }
+
+    #[test]
+    fn box_forwards() {
+        let mut boxed: Box<dyn Widget> = Box::new(Described {
+            widget_tag: WidgetTag::new(),
+            rect: BoundBox::new2(0, 0, 10, 10),
+            state: 0,
+        });
+        assert_eq!(Some(AccessibilityNode::new(AccessibilityRole::Button)), <Box<dyn Widget> as Widget>::accessibility_info(&boxed));
+        assert!(!<Box<dyn Widget> as Widget>::hit_test(&boxed, Point2::new(7, 2)));
+
+        <Box<dyn Widget> as Widget>::restore_state(&mut boxed, &UiStateValue::Int(3));
+        assert_eq!(Some(UiStateValue::Int(3)), <Box<dyn Widget> as Widget>::save_state(&boxed));
+    }
+
+    #[test]
+    fn clone_tag() {
+        let mut tag = WidgetTag::new();
+        tag.set_pointer_transparent(true);
+        tag.set_popup(true);
+        tag.set_access_key(Some('A'));
+        tag.set_tab_index(Some(2));
+        tag.set_draws_own_focus_ring(true);
+        tag.set_enabled(false);
+        tag.set_user_data(Box::new(1u32));
+
+        let clone = tag.clone();
+        assert_ne!(tag.widget_id(), clone.widget_id());
+        assert!(clone.pointer_transparent());
+        assert!(clone.is_popup());
+        assert_eq!(Some('a'), clone.access_key());
+        assert_eq!(Some(2), clone.tab_index());
+        assert!(clone.draws_own_focus_ring());
+        assert!(!clone.is_enabled());
+        assert_eq!(None, clone.user_data::<u32>());
+
+        let clone = WidgetTag::new().clone();
+        assert!(!clone.pointer_transparent());
+        assert!(!clone.is_popup());
+        assert_eq!(None, clone.access_key());
+        assert!(clone.is_enabled());
+    }
+
+    #[test]
+    fn focus_scope_outside_tree() {
+        // Tags that aren't in a tree can't push or pop scopes, so the flag stays as it was.
+        let mut tag = WidgetTag::new();
+        assert!(tag.push_focus_scope().is_err());
+        assert!(!tag.is_focus_scope());
+
+        tag.focus_scope = true;
+        assert!(tag.pop_focus_scope().is_err());
+        assert!(tag.is_focus_scope());
+    }
+
+    #[test]
+    fn path_key() {
+        let a: WidgetPathKey = vec![WidgetIdent::new_str("tree"), WidgetIdent::Num(1)].into_iter().collect();
+        let b = WidgetPathKey::empty().child(WidgetIdent::new_str("tree")).child(WidgetIdent::Num(1));
+        let c = a.child(WidgetIdent::Num(2));
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(Some(a.clone()), c.parent());
+        assert_eq!(None, WidgetPathKey::empty().parent());
+        assert!(c.starts_with(&a));
+        assert!(!a.starts_with(&c));
+
+        let mut set = HashSet::new();
+        set.insert(a);
+        assert!(set.contains(&b));
+        assert!(!set.contains(&c));
+    }
+}
@@ -6,8 +6,11 @@
 
 use crate::{
     LoopFlow,
+    accessibility::AccessibilityNode,
     event::{EventOps, InputState, WidgetEventSourced},
-    render::{Renderer, WidgetRenderer, WidgetTheme},
+    render::{DisabledStyle, Renderer, WidgetRenderer, WidgetTheme},
+    ui_state::UiStateValue,
+    update_state::DeferredFn,
     widget::{Parent, WidgetIdent, Widget, WidgetRenderable, WidgetId, WidgetTag, WidgetInfo, WidgetInfoMut},
 };
 use arrayvec::ArrayVec;
@@ -16,7 +19,8 @@ use std::{
     any::{Any, TypeId},
 };
 use cgmath_geometry::{
-    D2, rect::BoundBox,
+    D2, rect::{BoundBox, DimsBox},
+    cgmath::Point2,
 };
 use derin_common_types::layout::SizeBounds;
 
@@ -38,7 +42,15 @@ pub(crate) trait WidgetDyn<R: Renderer>: 'static {
     ) -> EventOps;
 
     fn size_bounds(&self) -> SizeBounds;
+    fn min_intrinsic_size(&self) -> DimsBox<D2, i32>;
+    fn on_update_layout(&mut self, old_rect: BoundBox<D2, i32>);
+    fn accepts_focus(&self) -> bool;
+    fn hit_test(&self, point: Point2<i32>) -> bool;
+    fn accessibility_info(&self) -> Option<AccessibilityNode>;
+    fn save_state(&self) -> Option<UiStateValue>;
+    fn restore_state(&mut self, state: &UiStateValue);
     fn dispatch_message(&mut self, message: &Any);
+    fn apply_deferred(&mut self, f: DeferredFn);
 
     // Parent methods
     fn num_children(&self) -> usize;
@@ -48,10 +60,12 @@ pub(crate) trait WidgetDyn<R: Renderer>: 'static {
     fn child_by_index_mut(&mut self, index: usize) -> Option<WidgetInfoMut<'_, R>>;
     fn children<'a>(&'a self, for_each: ForEachSummary<WidgetInfo<'a, R>>);
     fn children_mut<'a>(&'a mut self, for_each: ForEachSummary<WidgetInfoMut<'a, R>>);
+    fn preferred_focus_child(&self) -> Option<WidgetIdent>;
 
     // WidgetRenderable methods
     fn render(&mut self, params: RenderParameters<R>) -> Result<(), RenderError>;
     fn update_layout(&mut self, layout: &mut R::Layout);
+    fn on_theme_changed(&mut self, theme: &R::Theme);
 
     fn type_id(&self) -> TypeId;
     fn to_widget(&self) -> &Widget;
@@ -128,15 +142,43 @@ impl<W, R> WidgetDyn<R> for W
     fn size_bounds(&self) -> SizeBounds {
         <Self as Widget>::size_bounds(self)
     }
+    fn min_intrinsic_size(&self) -> DimsBox<D2, i32> {
+        <Self as Widget>::min_intrinsic_size(self)
+    }
+    fn on_update_layout(&mut self, old_rect: BoundBox<D2, i32>) {
+        <Self as Widget>::on_update_layout(self, old_rect)
+    }
+    fn accepts_focus(&self) -> bool {
+        <Self as Widget>::accepts_focus(self)
+    }
+    fn hit_test(&self, point: Point2<i32>) -> bool {
+        <Self as Widget>::hit_test(self, point)
+    }
+    fn accessibility_info(&self) -> Option<AccessibilityNode> {
+        <Self as Widget>::accessibility_info(self)
+    }
+    fn save_state(&self) -> Option<UiStateValue> {
+        <Self as Widget>::save_state(self)
+    }
+    fn restore_state(&mut self, state: &UiStateValue) {
+        <Self as Widget>::restore_state(self, state)
+    }
     fn dispatch_message(&mut self, message: &Any) {
         <Self as Widget>::dispatch_message(self, message)
     }
+    fn apply_deferred(&mut self, f: DeferredFn) {
+        f(self)
+    }
 
     type_match!{
         fn num_children(&self) -> usize {
             default => 0,
             specialized(Parent) => <Self as Parent>::num_children(self)
         }
+        fn preferred_focus_child(&self) -> Option<WidgetIdent> {
+            default => None,
+            specialized(Parent) => <Self as Parent>::preferred_focus_child(self)
+        }
         fn child(&self, widget_ident: WidgetIdent) -> Option<WidgetInfo<'_, R>> {
             default => None,
             specialized(Parent) => <Self as Parent>::framed_child::<R>(self, widget_ident)
@@ -214,6 +256,10 @@ impl<W, R> WidgetDyn<R> for W
             default => (),
             specialized(WidgetRenderable<R>) => self.update_layout(layout)
         }
+        fn on_theme_changed(&mut self, theme: &R::Theme) {
+            default => (),
+            specialized(WidgetRenderable<R>) => <Self as WidgetRenderable<R>>::on_theme_changed(self, theme)
+        }
     }
 
     fn type_id(&self) -> TypeId {
@@ -312,12 +358,51 @@ pub(crate) fn to_any<W>(widget: &mut W, f: impl FnOnce(&mut Any))
     widget.as_widget_sized(f);
 }
 
+trait AsParent {
+    fn to_parent(&self) -> Option<&dyn Parent>;
+    fn to_parent_mut(&mut self) -> Option<&mut dyn Parent>;
+}
+impl<W> AsParent for W
+    where W: Widget + ?Sized
+{
+    default fn to_parent(&self) -> Option<&dyn Parent> {
+        None
+    }
+    default fn to_parent_mut(&mut self) -> Option<&mut dyn Parent> {
+        None
+    }
+}
+impl<W> AsParent for W
+    where W: Parent
+{
+    fn to_parent(&self) -> Option<&dyn Parent> {
+        Some(self)
+    }
+    fn to_parent_mut(&mut self) -> Option<&mut dyn Parent> {
+        Some(self)
+    }
+}
+
+pub(crate) fn as_parent<W>(widget: &W) -> Option<&dyn Parent>
+    where W: Widget + ?Sized
+{
+    <W as AsParent>::to_parent(widget)
+}
+
+pub(crate) fn as_parent_mut<W>(widget: &mut W) -> Option<&mut dyn Parent>
+    where W: Widget + ?Sized
+{
+    <W as AsParent>::to_parent_mut(widget)
+}
+
 pub struct RenderParameters<'a, R: Renderer> {
     pub renderer: &'a mut R,
     pub widget_id: WidgetId,
     pub theme: &'a R::Theme,
     pub transform: BoundBox<D2, i32>,
     pub clip: BoundBox<D2, i32>,
+    /// Whether the widget or one of its ancestors is disabled.
+    pub disabled: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -373,8 +458,13 @@ fn render_with_theme_or_fallback<W, R>(widget: &mut W, render_parameters: Render
                 theme,
                 transform,
                 clip,
+                disabled,
             } = render_parameters;
 
+            let desaturate = disabled && widget.disabled_style() == DisabledStyle::Desaturate;
+            if desaturate {
+                renderer.set_disabled(theme, true);
+            }
             renderer.render_widget(
                 widget_id,
                 theme,
@@ -383,6 +473,9 @@ fn render_with_theme_or_fallback<W, R>(widget: &mut W, render_parameters: Render
                 widget_theme_parameters,
                 |frame| widget.render(frame)
             );
+            if desaturate {
+                renderer.set_disabled(theme, false);
+            }
 
             Ok(())
         }
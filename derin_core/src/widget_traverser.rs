@@ -11,6 +11,7 @@ pub(crate) use self::{
     widget_stack::{WidgetPath, OffsetWidgetPath},
 };
 use crate::{
+    LoopFlow,
     render::Renderer,
     widget::{WidgetDyn, WidgetId, WidgetIdent},
     update_state::UpdateStateCell,
@@ -28,6 +29,8 @@ pub enum Relation {
     Parent,
     /// Sibling with a widget delta. // TODO EXPLAIN MORE
     Sibling(isize),
+    /// Sibling with a delta in the parent's tab order, as set by `WidgetTag::set_tab_index`.
+    TabSibling(isize),
     ChildIdent(WidgetIdent),
     ChildIndex(usize)
 }
@@ -64,12 +67,23 @@ impl<R> WidgetTraverserBase<R>
             update_state
         }
     }
+
+    /// See `VirtualWidgetTree::compact`.
+    pub fn compact_tree(&mut self) {
+        self.virtual_widget_tree.compact();
+    }
 }
 
 impl<R> WidgetTraverser<'_, R>
     where R: Renderer
 {
     pub fn get_widget(&mut self, id: WidgetId) -> Option<OffsetWidgetScanPath<'_, R>> {
+        self.get_widget_enabled(id).map(|(wpath, _)| wpath)
+    }
+
+    /// Get a widget, along with whether it's enabled. Widgets are only enabled if they and all of
+    /// their ancestors have `WidgetTag::is_enabled` set.
+    pub fn get_widget_enabled(&mut self, id: WidgetId) -> Option<(OffsetWidgetScanPath<'_, R>, bool)> {
         // Move the stack top to the desired widget.
         match self.get_widget_with_tree(id) {
             Some(_) => (),
@@ -85,26 +99,59 @@ impl<R> WidgetTraverser<'_, R>
             ref update_state,
         } = self;
 
-        Some(stack.top_mut().map(move |w| OffsetWidgetScan::new(w, virtual_widget_tree, update_state)))
+        let enabled = stack.widgets().all(|wpath| wpath.widget.widget_tag().is_enabled());
+        Some((stack.top_mut().map(move |w| OffsetWidgetScan::new(w, virtual_widget_tree, update_state)), enabled))
     }
 
     pub fn get_widget_relation(&mut self, id: WidgetId, relation: Relation) -> Option<OffsetWidgetScanPath<'_, R>> {
-        let relation_id = match relation {
+        let relation_id = self.relation_id(id, relation)?;
+        self.get_widget(relation_id)
+    }
+
+    /// Find the ID of the widget related to `id` by `relation`.
+    pub fn relation_id(&mut self, id: WidgetId, relation: Relation) -> Option<WidgetId> {
+        match relation {
             Relation::Parent => {
-                self.virtual_widget_tree.parent(id).ok()?
+                self.virtual_widget_tree.parent(id).ok()
             },
             Relation::Sibling(delta) => {
-                self.virtual_widget_tree.sibling(id, delta).ok()?
+                self.virtual_widget_tree.sibling(id, delta).ok()
+            },
+            Relation::TabSibling(delta) => {
+                self.tab_sibling(id, delta)
             },
             Relation::ChildIdent(ident) => {
-                self.virtual_widget_tree.child_ident(id, ident).ok()?
+                self.virtual_widget_tree.child_ident(id, ident).ok()
             },
             Relation::ChildIndex(index) => {
-                self.virtual_widget_tree.child_index(id, index).ok()?
+                self.virtual_widget_tree.child_index(id, index).ok()
             },
-        };
+        }
+    }
 
-        self.get_widget(relation_id)
+    /// Find the sibling `delta` places away from `id` in the parent's tab order. If the parent is
+    /// a focus scope, the tab order wraps around.
+    fn tab_sibling(&mut self, id: WidgetId, delta: isize) -> Option<WidgetId> {
+        let parent_id = self.virtual_widget_tree.parent(id).ok()?;
+
+        let mut siblings = Vec::new();
+        let parent = self.get_widget(parent_id)?;
+        let wrap = parent.widget.widget_tag().is_focus_scope();
+        parent.widget.inner().children(&mut |children| {
+            for child in children {
+                siblings.push((tab_order_key(child.widget.widget_tag().tab_index(), child.index), child.widget.widget_id()));
+            }
+            LoopFlow::Continue
+        });
+        siblings.sort_by_key(|&(key, _)| key);
+
+        let position = siblings.iter().position(|&(_, sibling_id)| sibling_id == id)? as isize + delta;
+        match position {
+            // Focus scopes cycle through their children.
+            p if wrap => siblings.get(p.rem_euclid(siblings.len() as isize) as usize).map(|&(_, sibling_id)| sibling_id),
+            p if p < 0 => None,
+            p => siblings.get(p as usize).map(|&(_, sibling_id)| sibling_id)
+        }
     }
 
     fn get_widget_with_tree(&mut self, id: WidgetId) -> Option<OffsetWidgetPath<'_, R>> {
@@ -217,6 +264,21 @@ impl<R> WidgetTraverser<'_, R>
         self.virtual_widget_tree.root_id()
     }
 
+    /// See `VirtualWidgetTree::ancestors`.
+    pub fn ancestors(&self, id: WidgetId) -> impl '_ + Iterator<Item=WidgetId> {
+        self.virtual_widget_tree.ancestors(id)
+    }
+
+    /// See `VirtualWidgetTree::is_ancestor_of`.
+    pub fn is_ancestor_of(&self, ancestor: WidgetId, descendant: WidgetId) -> bool {
+        self.virtual_widget_tree.is_ancestor_of(ancestor, descendant)
+    }
+
+    /// See `VirtualWidgetTree::generation`.
+    pub fn tree_generation(&self) -> u64 {
+        self.virtual_widget_tree.generation()
+    }
+
     pub fn all_widgets(&self) -> impl '_ + Iterator<Item=WidgetId> {
         self.virtual_widget_tree.all_nodes().map(|(id, _)| id)
     }
@@ -285,6 +347,12 @@ impl<R> WidgetTraverser<'_, R>
     }
 }
 
+/// The key that siblings get sorted by to determine their tab order. Widgets with explicit tab
+/// indices come first, in ascending order, followed by the rest in tree order.
+pub(crate) fn tab_order_key(tab_index: Option<i32>, child_index: usize) -> (bool, i32, usize) {
+    (tab_index.is_none(), tab_index.unwrap_or(0), child_index)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -434,4 +502,110 @@ mod tests {
         test_crawl_children(b, &[ba]);
         test_crawl_children(ba, &[]);
     }
+
+    #[test]
+    fn rescan_scrubs_removed_widgets() {
+        use crate::{test_helpers::TestWidget, widget::WidgetIdent};
+        use indexmap::IndexMap;
+        use std::sync::Arc;
+
+        test_widget_tree!{
+            let event_list = crate::test_helpers::EventList::new();
+            let mut tree = root {
+                rect: (0, 0, 0, 0);
+                a {
+                    rect: (0, 0, 0, 0);
+                    aa { rect: (0, 0, 0, 0) },
+                    ab { rect: (0, 0, 0, 0) }
+                },
+                b { rect: (0, 0, 0, 0) }
+            };
+        }
+
+        let mut traverser_base: WidgetTraverserBase<TestRenderFrame> = WidgetTraverserBase::new(root);
+        let message_bus = MessageBus::new();
+        let update_state = UpdateState::new(&message_bus);
+        let mut rescan = |tree: &mut TestWidget| {
+            traverser_base.with_root_ref(tree, update_state.clone()).crawl_widget_children(root, |_| ());
+            traverser_base.virtual_widget_tree.parent(aa).ok()
+        };
+        fn children<'a>(tree: &'a mut TestWidget, ident: &str) -> &'a mut IndexMap<WidgetIdent, TestWidget> {
+            let child = tree.children.as_mut().unwrap().get_mut(&WidgetIdent::Str(Arc::from(ident))).unwrap();
+            child.children.get_or_insert_with(IndexMap::new)
+        }
+
+        rescan(&mut tree);
+        update_state.borrow_mut().relayout.clear();
+
+        // Detach `ab`, and move `aa` from `a` to `b`. `a` gets scanned before `b`, so `aa` briefly
+        // leaves the tree, but only `ab` should get scrubbed.
+        let (ab_ident, ab_widget) = children(&mut tree, "a").pop().unwrap();
+        let (aa_ident, aa_widget) = children(&mut tree, "a").pop().unwrap();
+        children(&mut tree, "b").insert(aa_ident, aa_widget);
+        assert_eq!(Some(b), rescan(&mut tree));
+        {
+            let update_state = update_state.borrow();
+            assert_eq!(vec![ab], update_state.remove_from_tree.iter().cloned().collect::<Vec<_>>());
+            assert!(update_state.relayout.contains(&aa));
+        }
+
+        // Putting `ab` back before the frame ends cancels its removal.
+        children(&mut tree, "a").insert(ab_ident, ab_widget);
+        rescan(&mut tree);
+        let update_state = update_state.borrow();
+        assert!(update_state.remove_from_tree.is_empty());
+        assert!(update_state.relayout.contains(&ab));
+    }
+
+    #[test]
+    fn window_rect() {
+        use crate::{cgmath::Vector2, widget::Parent};
+
+        test_widget_tree!{
+            let event_list = crate::test_helpers::EventList::new();
+            let mut tree = root {
+                rect: (0, 0, 100, 100);
+                a {
+                    rect: (10, 10, 60, 60);
+                    aa { rect: (5, 5, 20, 20) }
+                },
+                b { rect: (70, 0, 100, 30) }
+            };
+        }
+
+        // Widgets that haven't been visited yet don't know where they are.
+        assert_eq!(None, tree.framed_child_by_index::<TestRenderFrame>(0).unwrap().window_rect());
+
+        let mut traverser_base: WidgetTraverserBase<TestRenderFrame> = WidgetTraverserBase::new(root);
+        let message_bus = MessageBus::new();
+        let update_state = UpdateState::new(&message_bus);
+        traverser_base.with_root_ref(&mut tree, update_state.clone()).crawl_widget_children(root, |_| ());
+
+        let a_info = tree.framed_child_by_index::<TestRenderFrame>(0).unwrap();
+        assert_eq!(Some(BoundBox::new2(10, 10, 60, 60)), a_info.window_rect());
+        let b_info = tree.framed_child_by_index::<TestRenderFrame>(1).unwrap();
+        assert_eq!(Some(BoundBox::new2(70, 0, 100, 30)), b_info.window_rect());
+
+        let a_widget = tree.children.as_ref().unwrap().get_index(0).unwrap().1;
+        let aa_info = a_widget.framed_child_by_index::<TestRenderFrame>(0).unwrap();
+        assert_eq!(Some(BoundBox::new2(15, 15, 30, 30)), aa_info.window_rect());
+        // Parents can override the recorded offset.
+        let aa_info = aa_info.with_window_offset(Vector2::new(0, 0));
+        assert_eq!(Some(BoundBox::new2(5, 5, 20, 20)), aa_info.window_rect());
+    }
+
+    #[test]
+    fn tab_order() {
+        let mut siblings = vec![
+            (None, 0),
+            (Some(2), 1),
+            (None, 2),
+            (Some(-1), 3),
+            (Some(2), 4),
+        ];
+        siblings.sort_by_key(|&(tab_index, index)| tab_order_key(tab_index, index));
+
+        let order = siblings.iter().map(|&(_, index)| index).collect::<Vec<_>>();
+        assert_eq!(vec![3, 1, 4, 0, 2], order);
+    }
 }
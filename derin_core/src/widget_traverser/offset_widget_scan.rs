@@ -13,7 +13,7 @@ use crate::{
     update_state::UpdateStateCell,
     widget::WidgetDyn,
 };
-use super::virtual_widget_tree::VirtualWidgetTree;
+use super::virtual_widget_tree::{ReconcileResult, VirtualWidgetTree};
 
 pub(crate) struct OffsetWidgetScan<'a, R: Renderer> {
     offset_widget: OffsetWidget<'a, R>,
@@ -63,14 +63,46 @@ impl<R: Renderer> OffsetWidgetScan<'_, R> {
 }
 
 pub(crate) fn update_recursive<R: Renderer>(widget: &dyn WidgetDyn<R>, tree: &mut VirtualWidgetTree, update_state: &Rc<UpdateStateCell>) {
+    let mut changes = ReconcileResult::default();
+    reconcile_recursive(widget, tree, update_state, &mut changes);
+
+    let mut update_state = update_state.borrow_mut();
+    for id in changes.added {
+        update_state.queue_insert_id(id);
+    }
+    // A widget that moved to a new parent gets removed from its old parent if the scan reaches
+    // the old parent first, so only scrub the widgets that are still missing after the scan.
+    for id in changes.removed {
+        if tree.get_widget(id).is_none() {
+            update_state.queue_remove_id(id);
+        }
+    }
+}
+
+fn reconcile_recursive<R: Renderer>(
+    widget: &dyn WidgetDyn<R>,
+    tree: &mut VirtualWidgetTree,
+    update_state: &Rc<UpdateStateCell>,
+    changes: &mut ReconcileResult
+) {
     let widget_tag = widget.widget_tag();
     let widget_id = widget_tag.widget_id;
     widget_tag.set_owning_update_state(update_state);
+    widget_tag.set_widget_type(widget.type_id());
+
+    let mut real_children = Vec::new();
+    widget.children(&mut |children| {
+        real_children.extend(children.iter().map(|child| (child.ident.clone(), child.widget.widget_id())));
+        LoopFlow::Continue
+    });
+    let result = tree.reconcile(widget_id, real_children).expect("Widget insert error");
+    changes.added.extend(result.added);
+    changes.removed.extend(result.removed);
+    changes.moved.extend(result.moved);
 
     widget.children(&mut |children| {
         for child in children {
-            tree.insert(widget_id, child.widget.widget_id(), child.index, child.ident).expect("Widget insert error");
-            update_recursive(child.widget, tree, update_state);
+            reconcile_recursive(child.widget, tree, update_state, changes);
         }
         LoopFlow::Continue
     });
@@ -9,10 +9,14 @@ use std::{
         VecDeque,
         hash_map::{HashMap, Entry}
     },
+    iter,
     mem,
 };
 use fnv::FnvBuildHasher;
 
+/// Child lists shorter than this never give back their spare capacity.
+const RECLAIM_MIN_LEN: usize = 32;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub(crate) enum WidgetInsertError {
     ParentNotInTree,
@@ -31,10 +35,20 @@ pub(crate) enum WidgetRelationError {
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct WidgetTreeNode {
     parent_id: WidgetId,
+    children: ChildList,
+    data: WidgetData
+}
+
+/// A widget's children, indexed so that a child's position can be found without scanning the list.
+#[derive(Debug, Clone, Default)]
+struct ChildList {
     // If an entry in the child array is `None`, that means a high-index widget has been inserted
     // before it's lower-index counterparts.
     children: Vec<Option<WidgetId>>,
-    data: WidgetData
+    /// Maps each child to its position in `children`.
+    indices: HashMap<WidgetId, usize, FnvBuildHasher>,
+    /// The number of `None` entries in `children`.
+    num_holes: usize,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -43,12 +57,25 @@ pub struct WidgetData {
     depth: Cell<u32>
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub(crate) struct VirtualWidgetTree {
     root: WidgetId,
     root_data: WidgetData,
-    root_children: Vec<Option<WidgetId>>,
-    tree_data: HashMap<WidgetId, WidgetTreeNode, FnvBuildHasher>
+    root_children: ChildList,
+    tree_data: HashMap<WidgetId, WidgetTreeNode, FnvBuildHasher>,
+    generation: u64,
+}
+
+/// The changes `VirtualWidgetTree::reconcile` made to the tree.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct ReconcileResult {
+    /// Children that weren't in the tree before.
+    pub added: Vec<WidgetId>,
+    /// Widgets that were removed from the tree. This includes the descendants of every removed
+    /// child.
+    pub removed: Vec<WidgetId>,
+    /// Children that were already in the tree, but whose parent, index, or ident changed.
+    pub moved: Vec<WidgetId>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -65,8 +92,9 @@ impl VirtualWidgetTree {
                 ident: ROOT_IDENT,
                 depth: Cell::new(0)
             },
-            root_children: Vec::new(),
-            tree_data: HashMap::default()
+            root_children: ChildList::default(),
+            tree_data: HashMap::default(),
+            generation: 0,
         }
     }
 
@@ -74,6 +102,16 @@ impl VirtualWidgetTree {
         self.root
     }
 
+    /// A counter that advances whenever a widget is inserted into, moved within, or removed from
+    /// the tree.
+    ///
+    /// Data derived from the tree's structure can be cached alongside the generation it was
+    /// computed at, and only needs to be recomputed once the generation changes.
+    #[inline]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
     /// Insert a widget ID into the tree. If the widget in already in the tree, change the widget's
     /// parent to the new parent.
     pub(crate) fn insert(&mut self, parent_id: WidgetId, widget_id: WidgetId, child_index: usize, widget_ident: WidgetIdent) -> Result<(), WidgetInsertError> {
@@ -83,13 +121,10 @@ impl VirtualWidgetTree {
 
         if let Some((parent_data, children)) = self.get_widget_node_mut(parent_id) {
             let parent_depth = parent_data.depth();
+            self.generation += 1;
 
-            crate::vec_remove_element(children, &Some(widget_id));
-            if children.len() <= child_index {
-                children.resize(child_index + 1, None);
-            }
-            let mut removed_widget_id = Some(widget_id);
-            mem::swap(&mut removed_widget_id, &mut children[child_index]);
+            children.remove(widget_id);
+            let removed_widget_id = children.set(child_index, widget_id);
 
             match self.tree_data.entry(widget_id) {
                 Entry::Occupied(mut occ) => {
@@ -100,13 +135,11 @@ impl VirtualWidgetTree {
                     node.data.ident = widget_ident;
 
                     let (_, old_parent_children) = self.get_widget_node_mut(old_parent_id).expect("Bad tree state");
-                    // Remove any trailing `None`s from the parent.
-                    while let Some(None) = old_parent_children.last() {
-                        old_parent_children.pop();
-                    }
+                    old_parent_children.trim();
 
                     if old_parent_id != parent_id {
-                        crate::vec_remove_element(old_parent_children, &Some(widget_id)).unwrap();
+                        old_parent_children.remove(widget_id).unwrap();
+                        old_parent_children.reclaim();
                         self.update_node_depth(parent_depth + 1, &self.tree_data[&widget_id]);
                     }
                 },
@@ -123,6 +156,91 @@ impl VirtualWidgetTree {
         }
     }
 
+    /// Update the children of `parent_id` to match `real_children`, which yields the ident and ID
+    /// of every child the parent currently has, in order.
+    ///
+    /// Unlike inserting the children one at a time, this removes children that are no longer
+    /// present and handles reordered children without disturbing their subtrees. Children that
+    /// were previously under a different parent get moved, along with their descendants.
+    pub(crate) fn reconcile(
+        &mut self,
+        parent_id: WidgetId,
+        real_children: impl IntoIterator<Item=(WidgetIdent, WidgetId)>
+    ) -> Result<ReconcileResult, WidgetInsertError> {
+        let parent_depth = match self.get_widget(parent_id) {
+            Some(parent_data) => parent_data.depth(),
+            None => return Err(WidgetInsertError::ParentNotInTree)
+        };
+        let real_children = real_children.into_iter().collect::<Vec<_>>();
+        if real_children.iter().any(|&(_, id)| id == self.root) {
+            return Err(WidgetInsertError::WidgetIsRoot);
+        }
+
+        let mut result = ReconcileResult::default();
+        let mut old_parents = Vec::new();
+        let mut new_children = ChildList::default();
+        for (index, (ident, widget_id)) in real_children.into_iter().enumerate() {
+            new_children.set(index, widget_id);
+
+            let (old_parent_id, ident_changed) = match self.tree_data.get_mut(&widget_id) {
+                Some(node) => {
+                    let ident_changed = node.data.ident != ident;
+                    node.data.ident = ident;
+                    (mem::replace(&mut node.parent_id, parent_id), ident_changed)
+                },
+                None => {
+                    self.tree_data.insert(widget_id, WidgetTreeNode::new(parent_id, ident, parent_depth + 1));
+                    result.added.push(widget_id);
+                    continue;
+                }
+            };
+
+            let index_changed = match old_parent_id == parent_id {
+                true => self.get_widget_node(parent_id).unwrap().1.index_of(widget_id) != Some(index),
+                false => {
+                    if let Some((_, old_parent_children)) = self.get_widget_node_mut(old_parent_id) {
+                        old_parent_children.remove(widget_id);
+                        old_parents.push(old_parent_id);
+                    }
+                    self.update_node_depth(parent_depth + 1, &self.tree_data[&widget_id]);
+                    true
+                }
+            };
+            if ident_changed || index_changed {
+                result.moved.push(widget_id);
+            }
+        }
+
+        let (_, children) = self.get_widget_node_mut(parent_id).unwrap();
+        let old_children = mem::replace(children, new_children);
+
+        // Remove the children that aren't in the new list, along with their descendants.
+        let mut widgets_to_remove = old_children.iter()
+            .flatten()
+            .filter(|id| self.get_widget_node(parent_id).unwrap().1.index_of(**id).is_none())
+            .cloned()
+            .collect::<VecDeque<_>>();
+        while let Some(remove_id) = widgets_to_remove.pop_front() {
+            let removed_node = self.tree_data.remove(&remove_id).expect("Bad tree state");
+            widgets_to_remove.extend(removed_node.children.children.into_iter().flatten());
+            result.removed.push(remove_id);
+        }
+
+        // Tidy up the lists the moved children came from. This waits until the walk is done so that
+        // the old parents' lists don't change underneath any lookups made in the middle of it.
+        for old_parent_id in old_parents {
+            if let Some((_, old_parent_children)) = self.get_widget_node_mut(old_parent_id) {
+                old_parent_children.trim();
+                old_parent_children.reclaim();
+            }
+        }
+
+        if result != ReconcileResult::default() {
+            self.generation += 1;
+        }
+        Ok(result)
+    }
+
     fn update_node_depth(&self, depth: u32, node: &WidgetTreeNode) {
         node.data.depth.set(depth);
         for child_id in node.children.iter().cloned().flatten() {
@@ -133,16 +251,17 @@ impl VirtualWidgetTree {
     pub(crate) fn remove(&mut self, widget_id: WidgetId) -> Option<WidgetData> {
         if let Entry::Occupied(occ) = self.tree_data.entry(widget_id) {
             let node = occ.remove();
+            self.generation += 1;
 
-            // Remove the widget from the parent's child list and remove any trailing `None`s.
-            let mut parent_children = &mut self.get_widget_node_mut(node.parent_id).unwrap().1;
-            crate::vec_remove_element(parent_children, &Some(widget_id));
-            while let Some(None) = parent_children.last() {
-                parent_children.pop();
-            }
+            // Remove the widget from the parent's child list, remove any trailing `None`s, and
+            // give back any memory the list no longer needs.
+            let parent_children = self.get_widget_node_mut(node.parent_id).unwrap().1;
+            parent_children.remove(widget_id);
+            parent_children.trim();
+            parent_children.reclaim();
 
             // Remove all the child widgets.
-            let mut widgets_to_remove = VecDeque::from(node.children);
+            let mut widgets_to_remove = VecDeque::from(node.children.children);
             while let Some(remove_id) = widgets_to_remove.pop_front() {
                 let remove_id = match remove_id {
                     Some(id) => id,
@@ -152,7 +271,7 @@ impl VirtualWidgetTree {
                     Entry::Occupied(occ) => occ.remove(),
                     Entry::Vacant(_) => panic!("Bad tree state")
                 };
-                widgets_to_remove.extend(removed_node.children);
+                widgets_to_remove.extend(removed_node.children.children);
             }
 
             Some(node.data)
@@ -161,6 +280,24 @@ impl VirtualWidgetTree {
         }
     }
 
+    /// Remove every hole from every child list in the tree, and release any memory the lists
+    /// aren't using.
+    ///
+    /// Holes are usually left behind by high-index children that were inserted before their
+    /// lower-index siblings. Compacting shifts the children after each hole down, so lookups by
+    /// child index may miss until the affected widgets get re-inserted at their real indices.
+    pub(crate) fn compact(&mut self) {
+        let mut changed = self.root_children.compact();
+        for node in self.tree_data.values_mut() {
+            changed |= node.children.compact();
+        }
+        self.tree_data.shrink_to_fit();
+
+        if changed {
+            self.generation += 1;
+        }
+    }
+
     // A recursive remove function existed at one point, but has been removed from the source tree.
     // Check commits from early January 2019 to find it.
 
@@ -174,6 +311,29 @@ impl VirtualWidgetTree {
         }
     }
 
+    /// Iterate over the widget's ancestors, starting with its parent and ending with the root.
+    ///
+    /// The iterator is empty if the widget is the root, or isn't in the tree.
+    pub(crate) fn ancestors(&self, widget_id: WidgetId) -> impl '_ + Iterator<Item=WidgetId> {
+        iter::successors(self.parent(widget_id).ok(), move |&id| self.parent(id).ok())
+    }
+
+    /// Whether `ancestor` is a (possibly indirect) parent of `descendant`. A widget isn't its own
+    /// ancestor.
+    pub(crate) fn is_ancestor_of(&self, ancestor: WidgetId, descendant: WidgetId) -> bool {
+        let (ancestor_depth, descendant_depth) = match (self.get_widget(ancestor), self.get_widget(descendant)) {
+            (Some(a), Some(d)) => (a.depth(), d.depth()),
+            _ => return false
+        };
+        if ancestor_depth >= descendant_depth {
+            return false;
+        }
+
+        // The ancestor can only be one specific distance up the tree, so skip straight to it.
+        let distance = (descendant_depth - ancestor_depth - 1) as usize;
+        self.ancestors(descendant).nth(distance) == Some(ancestor)
+    }
+
     pub(crate) fn sibling(&self, widget_id: WidgetId, offset: isize) -> Result<WidgetId, WidgetRelationError> {
         if widget_id == self.root {
             return if offset == 0 {
@@ -193,8 +353,8 @@ impl VirtualWidgetTree {
 
         let siblings = &self.get_widget_node(node.parent_id).unwrap().1;
 
-        let sibling_index = crate::find_index(&siblings, &Some(widget_id)).unwrap() as isize + offset;
-        siblings.get(sibling_index as usize).cloned().and_then(|id| id).ok_or(WidgetRelationError::RelationNotFound)
+        let sibling_index = siblings.index_of(widget_id).unwrap() as isize + offset;
+        siblings.get(sibling_index).ok_or(WidgetRelationError::RelationNotFound)
     }
 
     pub(crate) fn sibling_wrapping(&self, widget_id: WidgetId, offset: isize) -> Option<WidgetId> {
@@ -225,14 +385,22 @@ impl VirtualWidgetTree {
             }
         };
 
-        let sibling_index = crate::find_index(siblings, &Some(widget_id)).unwrap() as isize + offset;
-        siblings[mod_euc(sibling_index, siblings.len() as isize) as usize]
+        let sibling_index = siblings.index_of(widget_id).unwrap() as isize + offset;
+        siblings.get(mod_euc(sibling_index, siblings.len() as isize))
     }
 
     pub(crate) fn child_index(&self, widget_id: WidgetId, child_index: usize) -> Result<WidgetId, WidgetRelationError> {
         let children = self.get_widget_node(widget_id).ok_or(WidgetRelationError::WidgetNotFound)?.1;
 
-        children.get(child_index).cloned().and_then(|id| id).ok_or(WidgetRelationError::RelationNotFound)
+        children.get(child_index as isize).ok_or(WidgetRelationError::RelationNotFound)
+    }
+
+    /// Gets the position of `child_id` among `parent_id`'s children, skipping over any holes left
+    /// by children that haven't been inserted yet.
+    pub(crate) fn index_of_child(&self, parent_id: WidgetId, child_id: WidgetId) -> Result<usize, WidgetRelationError> {
+        let children = self.get_widget_node(parent_id).ok_or(WidgetRelationError::WidgetNotFound)?.1;
+
+        children.position_of(child_id).ok_or(WidgetRelationError::RelationNotFound)
     }
 
     pub(crate) fn child_ident(&self, widget_id: WidgetId, child_ident: WidgetIdent) -> Result<WidgetId, WidgetRelationError> {
@@ -263,15 +431,15 @@ impl VirtualWidgetTree {
     }
 
     /// Returns `Option<WidgetData, Children>`
-    fn get_widget_node(&self, id: WidgetId) -> Option<(&WidgetData, &[Option<WidgetId>])> {
+    fn get_widget_node(&self, id: WidgetId) -> Option<(&WidgetData, &ChildList)> {
         if self.root == id {
             Some((&self.root_data, &self.root_children))
         } else {
-            self.tree_data.get(&id).map(|n| (&n.data, &n.children[..]))
+            self.tree_data.get(&id).map(|n| (&n.data, &n.children))
         }
     }
 
-    fn get_widget_node_mut(&mut self, id: WidgetId) -> Option<(&mut WidgetData, &mut Vec<Option<WidgetId>>)> {
+    fn get_widget_node_mut(&mut self, id: WidgetId) -> Option<(&mut WidgetData, &mut ChildList)> {
         if self.root == id {
             Some((&mut self.root_data, &mut self.root_children))
         } else {
@@ -339,7 +507,7 @@ impl WidgetTreeNode {
     fn new(parent_id: WidgetId, ident: WidgetIdent, depth: u32) -> WidgetTreeNode {
         WidgetTreeNode {
             parent_id,
-            children: Vec::new(),
+            children: ChildList::default(),
             data: WidgetData {
                 ident,
                 depth: Cell::new(depth)
@@ -348,6 +516,137 @@ impl WidgetTreeNode {
     }
 }
 
+impl PartialEq for VirtualWidgetTree {
+    fn eq(&self, other: &VirtualWidgetTree) -> bool {
+        // Two trees with the same structure are equal, regardless of how they were built.
+        self.root == other.root &&
+        self.root_data == other.root_data &&
+        self.root_children == other.root_children &&
+        self.tree_data == other.tree_data
+    }
+}
+
+impl Eq for VirtualWidgetTree {}
+
+impl ChildList {
+    #[inline]
+    fn len(&self) -> usize {
+        self.children.len()
+    }
+
+    #[inline]
+    fn iter(&self) -> impl Iterator<Item=&'_ Option<WidgetId>> {
+        self.children.iter()
+    }
+
+    /// Gets the child at `index`, returning `None` if the index is out of bounds or there's a hole
+    /// at the index.
+    #[inline]
+    fn get(&self, index: isize) -> Option<WidgetId> {
+        match index < 0 {
+            true => None,
+            false => self.children.get(index as usize).cloned().and_then(|id| id)
+        }
+    }
+
+    /// Gets the index of `child` in the list, including any holes before the child.
+    #[inline]
+    fn index_of(&self, child: WidgetId) -> Option<usize> {
+        self.indices.get(&child).cloned()
+    }
+
+    /// Gets the index of `child` in the list, skipping over any holes before the child.
+    fn position_of(&self, child: WidgetId) -> Option<usize> {
+        let index = self.index_of(child)?;
+        match self.num_holes {
+            0 => Some(index),
+            _ => Some(self.children[..index].iter().filter(|c| c.is_some()).count())
+        }
+    }
+
+    /// Put `child` at `index`, filling any space between the end of the list and `index` with
+    /// holes. Returns the child that was previously at `index`.
+    ///
+    /// `child` must not already be in the list.
+    fn set(&mut self, index: usize, child: WidgetId) -> Option<WidgetId> {
+        if self.children.len() <= index {
+            self.num_holes += index + 1 - self.children.len();
+            self.children.resize(index + 1, None);
+        }
+
+        let old_child = mem::replace(&mut self.children[index], Some(child));
+        match old_child {
+            Some(old_child) => {self.indices.remove(&old_child);},
+            None => self.num_holes -= 1
+        }
+        self.indices.insert(child, index);
+        old_child
+    }
+
+    /// Remove `child` from the list, shifting the children after it down by one. Returns the
+    /// index `child` was at.
+    ///
+    /// Every child after `child` has its index updated, so this takes time proportional to the
+    /// number of children after it. Removing from the end of the list is cheapest.
+    fn remove(&mut self, child: WidgetId) -> Option<usize> {
+        let index = self.indices.remove(&child)?;
+        self.children.remove(index);
+        for (i, c) in self.children.iter().enumerate().skip(index) {
+            if let Some(c) = c {
+                self.indices.insert(*c, i);
+            }
+        }
+        Some(index)
+    }
+
+    /// Remove any trailing holes from the list.
+    fn trim(&mut self) {
+        while let Some(None) = self.children.last() {
+            self.children.pop();
+            self.num_holes -= 1;
+        }
+    }
+
+    /// Remove all holes from the list, shifting the children after each hole down to fill it.
+    /// Returns `true` if any children changed position.
+    fn compact(&mut self) -> bool {
+        let changed = self.num_holes > 0;
+        if changed {
+            self.children.retain(|c| c.is_some());
+            self.indices.clear();
+            for (i, c) in self.children.iter().enumerate() {
+                self.indices.insert(c.unwrap(), i);
+            }
+            self.num_holes = 0;
+        }
+
+        self.children.shrink_to_fit();
+        self.indices.shrink_to_fit();
+        changed
+    }
+
+    /// Release the list's spare capacity if it's using far more memory than it needs.
+    ///
+    /// This never moves any children, so `Relation::ChildIndex` lookups keep working. Holes only
+    /// get removed by `compact`.
+    fn reclaim(&mut self) {
+        if self.children.capacity() / 2 > self.children.len().max(RECLAIM_MIN_LEN) {
+            self.children.shrink_to_fit();
+            self.indices.shrink_to_fit();
+        }
+    }
+}
+
+impl PartialEq for ChildList {
+    fn eq(&self, other: &ChildList) -> bool {
+        // `indices` and `num_holes` are derived from `children`, so there's no need to compare
+        // them.
+        self.children == other.children
+    }
+}
+
+impl Eq for ChildList {}
+
 impl WidgetData {
     #[inline(always)]
     pub fn depth(&self) -> u32 {
@@ -610,6 +909,50 @@ mod tests {
         assert_eq!(Ok(child_1_0), tree.child_index(child_1, 0));
         assert_eq!(Ok(child_1_1), tree.child_index(child_1, 1));
         assert_eq!(Err(WidgetRelationError::RelationNotFound), tree.child_index(root, 3));
+
+        assert_eq!(0, tree.ancestors(root).count());
+        assert_eq!(0, tree.ancestors(WidgetId::new()).count());
+        assert_eq!(vec![root], tree.ancestors(child_1).collect::<Vec<_>>());
+        assert_eq!(vec![child_0_2, child_0, root], tree.ancestors(child_0_2_0).collect::<Vec<_>>());
+
+        assert!(tree.is_ancestor_of(root, child_0_2_0));
+        assert!(tree.is_ancestor_of(child_0, child_0_2_0));
+        assert!(tree.is_ancestor_of(child_0_2, child_0_2_0));
+        assert!(tree.is_ancestor_of(child_1, child_1_1));
+        assert!(!tree.is_ancestor_of(child_0_2_0, child_0_2_0));
+        assert!(!tree.is_ancestor_of(child_0_2_0, child_0));
+        assert!(!tree.is_ancestor_of(child_1, child_0_2_0));
+        assert!(!tree.is_ancestor_of(child_0_1, child_0_2_0));
+        assert!(!tree.is_ancestor_of(WidgetId::new(), child_0));
+        assert!(!tree.is_ancestor_of(root, WidgetId::new()));
+    }
+
+    #[test]
+    fn test_index_of_child() {
+        virtual_widget_tree!{
+            let mut tree = root {
+                child_0 {
+                    child_0_0,
+                    child_0_1
+                },
+                child_1
+            }
+        };
+
+        assert_eq!(Err(WidgetRelationError::WidgetNotFound), tree.index_of_child(WidgetId::new(), child_0));
+        assert_eq!(Ok(0), tree.index_of_child(root, child_0));
+        assert_eq!(Ok(1), tree.index_of_child(root, child_1));
+        assert_eq!(Ok(0), tree.index_of_child(child_0, child_0_0));
+        assert_eq!(Ok(1), tree.index_of_child(child_0, child_0_1));
+        assert_eq!(Err(WidgetRelationError::RelationNotFound), tree.index_of_child(root, child_0_0));
+        assert_eq!(Err(WidgetRelationError::RelationNotFound), tree.index_of_child(child_1, child_0));
+        assert_eq!(Err(WidgetRelationError::RelationNotFound), tree.index_of_child(root, WidgetId::new()));
+
+        // Insert a child past the end of the child list, leaving holes before it.
+        let child_4 = WidgetId::new();
+        tree.insert(root, child_4, 4, WidgetIdent::Num(4)).unwrap();
+        assert_eq!(Ok(2), tree.index_of_child(root, child_4));
+        assert_eq!(Ok(child_4), tree.child_index(root, 4));
     }
 
     #[test]
@@ -795,4 +1138,244 @@ mod tests {
         macro_tree.insert(root, child_1, 1, WidgetIdent::new_str("child_1")).unwrap();
         assert_eq!(macro_tree, reference_tree);
     }
+
+    #[test]
+    fn sibling_after_remove() {
+        virtual_widget_tree!{
+            let mut tree = root {
+                child_0,
+                child_1,
+                child_2,
+                child_3
+            }
+        };
+
+        tree.remove(child_1);
+        assert_eq!(Ok(child_2), tree.sibling(child_0, 1));
+        assert_eq!(Ok(child_3), tree.sibling(child_2, 1));
+        assert_eq!(Ok(2), tree.index_of_child(root, child_3));
+    }
+
+    #[test]
+    fn generation() {
+        virtual_widget_tree!{
+            let mut tree = root {
+                child_0 {
+                    child_0_0
+                },
+                child_1
+            }
+        };
+
+        let generation = tree.generation();
+        tree.insert(child_1, child_0_0, 0, WidgetIdent::new_str("child_0_0")).unwrap();
+        assert!(tree.generation() > generation);
+
+        let generation = tree.generation();
+        tree.remove(child_0);
+        assert!(tree.generation() > generation);
+
+        // Failed operations don't change the tree, so they shouldn't advance the generation.
+        let generation = tree.generation();
+        tree.remove(child_0);
+        assert_eq!(Err(WidgetInsertError::ParentNotInTree), tree.insert(child_0, child_1, 0, WidgetIdent::new_str("child_1")));
+        assert_eq!(generation, tree.generation());
+    }
+
+    #[test]
+    fn reclaim_capacity() {
+        virtual_widget_tree!{
+            let mut tree = root {
+                child_0
+            }
+        };
+
+        // Inserting a widget at a high index fills the space before it with holes. Once it's
+        // removed, the memory backing those holes should be released.
+        let child_1 = WidgetId::new();
+        tree.insert(root, child_1, 10_000, WidgetIdent::new_str("child_1")).unwrap();
+        assert!(tree.root_children.children.capacity() > 10_000);
+        tree.remove(child_1);
+        assert_eq!(1, tree.root_children.len());
+        assert!(tree.root_children.children.capacity() < 10_000);
+
+        // Removing a child from a sparse list doesn't move the children around it, so lookups by
+        // child index keep working. The holes only go away once the tree is explicitly compacted.
+        let child_2 = WidgetId::new();
+        let child_3 = WidgetId::new();
+        tree.insert(root, child_2, 5_000, WidgetIdent::new_str("child_2")).unwrap();
+        tree.insert(root, child_3, 10_000, WidgetIdent::new_str("child_3")).unwrap();
+        tree.remove(child_3);
+        assert_eq!(4_999, tree.root_children.num_holes);
+        assert_eq!(Ok(child_2), tree.child_index(root, 5_000));
+        assert_eq!(Ok(5_000), tree.index_of_child(root, child_2));
+
+        tree.compact();
+        assert_eq!(0, tree.root_children.num_holes);
+        assert_eq!(2, tree.root_children.len());
+        assert!(tree.root_children.children.capacity() < 5_000);
+        assert_eq!(Ok(child_2), tree.sibling(child_0, 1));
+        assert_eq!(Ok(1), tree.index_of_child(root, child_2));
+        assert_eq!(Some(1), tree.root_children.index_of(child_2));
+    }
+
+    #[test]
+    fn compact() {
+        virtual_widget_tree!{
+            let mut tree = root {
+                child_0 {
+                    child_0_0
+                }
+            }
+        };
+        let child_1 = WidgetId::new();
+        let child_0_1 = WidgetId::new();
+        tree.insert(root, child_1, 4, WidgetIdent::new_str("child_1")).unwrap();
+        tree.insert(child_0, child_0_1, 3, WidgetIdent::new_str("child_0_1")).unwrap();
+
+        let generation = tree.generation();
+        tree.compact();
+        assert!(tree.generation() > generation);
+        assert_eq!(Ok(child_1), tree.child_index(root, 1));
+        assert_eq!(Ok(child_0_1), tree.child_index(child_0, 1));
+        assert_eq!(Ok(child_0_1), tree.sibling(child_0_0, 1));
+
+        // Compacting a tree without holes doesn't move anything.
+        let generation = tree.generation();
+        tree.compact();
+        assert_eq!(generation, tree.generation());
+    }
+
+    #[test]
+    fn reconcile() {
+        virtual_widget_tree!{
+            let mut tree = root {
+                child_0 {
+                    child_0_0 {
+                        child_0_0_0
+                    },
+                    child_0_1
+                },
+                child_1,
+                child_2 {
+                    child_2_0
+                }
+            }
+        };
+        let child_3 = WidgetId::new();
+
+        // Reorder `root`'s children, drop `child_2`, add `child_3`, and move `child_0_0` up a
+        // level.
+        let generation = tree.generation();
+        let result = tree.reconcile(root, vec![
+            (WidgetIdent::new_str("child_1"), child_1),
+            (WidgetIdent::new_str("child_0"), child_0),
+            (WidgetIdent::new_str("child_0_0"), child_0_0),
+            (WidgetIdent::new_str("child_3"), child_3),
+        ]).unwrap();
+        assert!(tree.generation() > generation);
+        assert_eq!(vec![child_3], result.added);
+        assert_eq!(vec![child_2, child_2_0], result.removed);
+        assert_eq!(vec![child_1, child_0, child_0_0], result.moved);
+
+        virtual_widget_tree!{
+            let expected_tree = root in old {
+                child_1 in old,
+                child_0 in old {
+                    child_0_1 in old
+                },
+                child_0_0 in old {
+                    child_0_0_0 in old
+                },
+                child_3 in old
+            }
+        };
+        assert_eq!(expected_tree, tree);
+        assert_eq!(1, tree.get_widget(child_0_0).unwrap().depth());
+        assert_eq!(2, tree.get_widget(child_0_0_0).unwrap().depth());
+
+        // Reconciling against an unchanged child list doesn't touch the tree.
+        let generation = tree.generation();
+        let result = tree.reconcile(child_0, vec![(WidgetIdent::new_str("child_0_1"), child_0_1)]).unwrap();
+        assert_eq!(ReconcileResult::default(), result);
+        assert_eq!(generation, tree.generation());
+
+        // Moving a child out of a sparse list doesn't compact the list it came from.
+        let child_1_0 = WidgetId::new();
+        let child_1_1 = WidgetId::new();
+        let child_1_2 = WidgetId::new();
+        tree.insert(child_1, child_1_0, 0, WidgetIdent::new_str("child_1_0")).unwrap();
+        tree.insert(child_1, child_1_1, 1, WidgetIdent::new_str("child_1_1")).unwrap();
+        tree.insert(child_1, child_1_2, 100, WidgetIdent::new_str("child_1_2")).unwrap();
+        let result = tree.reconcile(child_3, vec![(WidgetIdent::new_str("child_1_1"), child_1_1)]).unwrap();
+        assert_eq!(vec![child_1_1], result.moved);
+        assert_eq!(Ok(child_1_0), tree.child_index(child_1, 0));
+        assert_eq!(Ok(child_1_2), tree.child_index(child_1, 99));
+        assert_eq!(Ok(child_1), tree.parent(child_1_1));
+
+        assert_eq!(Err(WidgetInsertError::ParentNotInTree), tree.reconcile(child_2, vec![]));
+        assert_eq!(Err(WidgetInsertError::WidgetIsRoot), tree.reconcile(child_1, vec![(ROOT_IDENT, root)]));
+    }
+
+    extern crate test;
+
+    #[bench]
+    fn insert_remove_10k(b: &mut test::Bencher) {
+        let root = WidgetId::new();
+        let children = (0..10_000).map(|_| WidgetId::new()).collect::<Vec<_>>();
+
+        b.iter(|| {
+            let mut tree = VirtualWidgetTree::new(root);
+            for (i, child) in children.iter().enumerate() {
+                tree.insert(root, *child, i, WidgetIdent::Num(i as u32)).unwrap();
+            }
+            for child in children.iter().step_by(7) {
+                test::black_box(tree.sibling(*child, 1).ok());
+            }
+            for child in children.iter().rev() {
+                tree.remove(*child);
+            }
+            tree
+        });
+    }
+
+    /// Insert `children` under the root, then remove them in the order given by `removal_order`.
+    fn bench_removal(b: &mut test::Bencher, children: &[WidgetId], removal_order: &[WidgetId]) {
+        let root = WidgetId::new();
+
+        b.iter(|| {
+            let mut tree = VirtualWidgetTree::new(root);
+            for (i, child) in children.iter().enumerate() {
+                tree.insert(root, *child, i, WidgetIdent::Num(i as u32)).unwrap();
+            }
+            for child in removal_order {
+                tree.remove(*child);
+            }
+            tree
+        });
+    }
+
+    #[bench]
+    fn remove_back_1k(b: &mut test::Bencher) {
+        let children = (0..1_000).map(|_| WidgetId::new()).collect::<Vec<_>>();
+        let removal_order = children.iter().rev().cloned().collect::<Vec<_>>();
+        bench_removal(b, &children, &removal_order);
+    }
+
+    #[bench]
+    fn remove_front_1k(b: &mut test::Bencher) {
+        let children = (0..1_000).map(|_| WidgetId::new()).collect::<Vec<_>>();
+        bench_removal(b, &children, &children);
+    }
+
+    #[bench]
+    fn remove_middle_1k(b: &mut test::Bencher) {
+        let children = (0..1_000).map(|_| WidgetId::new()).collect::<Vec<_>>();
+        let mut remaining = children.clone();
+        let mut removal_order = Vec::with_capacity(children.len());
+        while !remaining.is_empty() {
+            removal_order.push(remaining.remove(remaining.len() / 2));
+        }
+        bench_removal(b, &children, &removal_order);
+    }
 }
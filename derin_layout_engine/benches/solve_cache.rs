@@ -0,0 +1,56 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#![feature(test)]
+extern crate test;
+
+use cgmath_geometry::rect::{BoundBox, DimsBox};
+use derin_common_types::layout::{GridSize, WidgetPos, WidgetSpan};
+use derin_layout_engine::{GridEngine, UpdateHeapCache};
+use test::Bencher;
+
+const COLS: u32 = 8;
+const ROWS: u32 = 32;
+
+fn gallery_hints() -> Vec<WidgetPos> {
+    (0..ROWS).flat_map(|row| (0..COLS).map(move |col| WidgetPos {
+        widget_span: WidgetSpan::new(col..col + 1, row..row + 1),
+        ..WidgetPos::default()
+    })).collect()
+}
+
+/// Relayout where only the widgets' contents changed, which is skipped by the solve cache.
+#[bench]
+fn relayout_unchanged(b: &mut Bencher) {
+    let mut engine = GridEngine::new();
+    let mut heap_cache = UpdateHeapCache::new();
+    let hints = gallery_hints();
+    let mut rects = vec![Ok(BoundBox::new2(0, 0, 0, 0)); hints.len()];
+
+    engine.desired_size = DimsBox::new2(1024, 768);
+    engine.set_grid_size(GridSize::new(COLS, ROWS));
+    engine.update_engine(&hints, &mut rects, &mut heap_cache);
+
+    b.iter(|| engine.update_engine(&hints, &mut rects, &mut heap_cache));
+    assert!(engine.solves_skipped() > 0);
+}
+
+/// Relayout where the window size changes every iteration, forcing a full solve.
+#[bench]
+fn relayout_resized(b: &mut Bencher) {
+    let mut engine = GridEngine::new();
+    let mut heap_cache = UpdateHeapCache::new();
+    let hints = gallery_hints();
+    let mut rects = vec![Ok(BoundBox::new2(0, 0, 0, 0)); hints.len()];
+
+    engine.set_grid_size(GridSize::new(COLS, ROWS));
+
+    let mut width = 1024;
+    b.iter(|| {
+        width = if width == 1024 {1023} else {1024};
+        engine.desired_size = DimsBox::new2(width, 768);
+        engine.update_engine(&hints, &mut rects, &mut heap_cache)
+    });
+    assert_eq!(0, engine.solves_skipped());
+}
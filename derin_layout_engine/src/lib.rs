@@ -18,7 +18,7 @@ use cgmath_geometry::{D2, rect::{DimsBox, BoundBox, GeoBox}};
 use derin_common_types::layout::{Fr, Tr, Align2, Align, GridSize, WidgetPos, TrackHints, SizeBounds, Margins};
 use crate::grid::{TrackVec, SizeResult};
 
-use std::cmp;
+use std::{cmp, mem};
 
 #[derive(Default)]
 pub struct UpdateHeapCache {
@@ -26,7 +26,8 @@ pub struct UpdateHeapCache {
     potential_frac_tracks: TrackVec<Tr>,
     rigid_tracks_widget: Vec<Tr>,
     frac_tracks_widget: Vec<Tr>,
-    solvable_widgets: Vec<Solvable>
+    solvable_widgets: Vec<Solvable>,
+    preferred_hints: Vec<WidgetPos>,
 }
 
 #[derive(Debug, Clone)]
@@ -42,7 +43,22 @@ pub struct GridEngine {
     /// The size bounds of the engine, accounting for the size bounds of the widgets.
     actual_size_bounds: SizeBounds,
     /// The margins that appear around the outside of the widget grid
-    pub grid_margins: Margins<Px>
+    pub grid_margins: Margins<Px>,
+    /// The inputs and outputs of the last solve, used to skip re-solving when nothing changed.
+    last_solve: Option<LastSolve>,
+    solves_skipped: u64,
+}
+
+/// Everything that went into and came out of a call to `update_engine`, aside from the track
+/// hints. Changing a track's hints clears the cache instead.
+#[derive(Debug, Clone)]
+struct LastSolve {
+    desired_size: DimsBox<D2, Px>,
+    desired_size_bounds: SizeBounds,
+    grid_margins: Margins<Px>,
+    grid_size: GridSize,
+    hints: Vec<WidgetPos>,
+    rects: Vec<Result<BoundBox<D2, Px>, SolveError>>,
 }
 
 impl UpdateHeapCache {
@@ -60,7 +76,9 @@ impl GridEngine {
             actual_size: DimsBox::new2(0, 0),
             desired_size_bounds: SizeBounds::default(),
             actual_size_bounds: SizeBounds::default(),
-            grid_margins: Margins::default()
+            grid_margins: Margins::default(),
+            last_solve: None,
+            solves_skipped: 0,
         }
     }
 
@@ -69,6 +87,9 @@ impl GridEngine {
     }
 
     pub fn set_grid_size(&mut self, size: GridSize) {
+        if size != self.grid_size() {
+            self.last_solve = None;
+        }
         self.grid.set_grid_size(size)
     }
 
@@ -77,6 +98,9 @@ impl GridEngine {
     }
 
     pub fn set_row_hints(&mut self, row: Tr, hints: TrackHints) {
+        if self.row_hints(row) != hints {
+            self.last_solve = None;
+        }
         self.grid.get_row_mut(row).expect(&format!("Row {} out of range", row)).set_hints(hints).ok();
     }
 
@@ -85,6 +109,9 @@ impl GridEngine {
     }
 
     pub fn set_col_hints(&mut self, col: Tr, hints: TrackHints) {
+        if self.col_hints(col) != hints {
+            self.last_solve = None;
+        }
         self.grid.get_col_mut(col).expect(&format!("Col {} out of range", col)).set_hints(hints).ok();
     }
 
@@ -96,6 +123,12 @@ impl GridEngine {
         self.actual_size_bounds
     }
 
+    /// The number of times `update_engine` has reused the previous solution because none of its
+    /// inputs changed.
+    pub fn solves_skipped(&self) -> u64 {
+        self.solves_skipped
+    }
+
     /// This method is the core of the derin layout engine, and is easily the most complex
     /// method it has. This takes a layout engine, iterates over all of the updates performed on that
     /// engine, and performs constraint solving to ensure that all* of the constraints within the engine
@@ -104,6 +137,9 @@ impl GridEngine {
     /// <sup>\* The only situation where some constraints may end up violated would be when the maximum
     /// size is less than the minimum size. In that case, minimum size overrides maximum size, as doing
     /// otherwise could cause rendering issues. </sup>
+    ///
+    /// If the hints, grid, and engine size are identical to those passed in the last call, the
+    /// previous solution is copied into `rects` without re-solving.
     pub fn update_engine(
         &mut self,
         hints: &[WidgetPos],
@@ -112,6 +148,69 @@ impl GridEngine {
     ) {
         assert_eq!(hints.len(), rects.len());
 
+        let grid_size = self.grid_size();
+        if let Some(ref last_solve) = self.last_solve {
+            if last_solve.desired_size == self.desired_size &&
+               last_solve.desired_size_bounds == self.desired_size_bounds &&
+               last_solve.grid_margins == self.grid_margins &&
+               last_solve.grid_size == grid_size &&
+               &last_solve.hints[..] == hints
+            {
+                rects.copy_from_slice(&last_solve.rects);
+                self.solves_skipped += 1;
+                return;
+            }
+        }
+
+        self.solve(hints, rects, heap_cache);
+
+        // If any widget would prefer more space than its minimum, try solving again with the
+        // preferred sizes as minimums. That solve is only kept if it fits within the desired size;
+        // either way, the engine's size bounds come from the real minimums.
+        if hints.iter().any(|h| h.preferred_size.is_some()) {
+            let min_size_bounds = self.actual_size_bounds;
+
+            let mut preferred_hints = mem::replace(&mut heap_cache.preferred_hints, Vec::new());
+            preferred_hints.extend(hints.iter().map(|hint| {
+                let mut hint = *hint;
+                if let Some(preferred_size) = hint.preferred_size {
+                    hint.size_bounds.min = hint.size_bounds.clamp_dims(DimsBox::new2(
+                        cmp::max(hint.size_bounds.min.width(), preferred_size.width()),
+                        cmp::max(hint.size_bounds.min.height(), preferred_size.height()),
+                    ));
+                }
+                hint
+            }));
+            self.solve(&preferred_hints, rects, heap_cache);
+            preferred_hints.clear();
+            heap_cache.preferred_hints = preferred_hints;
+
+            let preferred_min = self.actual_size_bounds.min;
+            if preferred_min.width() <= self.desired_size.width() && preferred_min.height() <= self.desired_size.height() {
+                self.actual_size_bounds = min_size_bounds;
+                self.actual_size = min_size_bounds.clamp_dims(self.desired_size);
+            } else {
+                self.solve(hints, rects, heap_cache);
+            }
+        }
+
+        self.last_solve = Some(LastSolve {
+            desired_size: self.desired_size,
+            desired_size_bounds: self.desired_size_bounds,
+            grid_margins: self.grid_margins,
+            grid_size,
+            hints: hints.to_vec(),
+            rects: rects.to_vec(),
+        });
+    }
+
+    fn solve(
+        &mut self,
+        hints: &[WidgetPos],
+        rects: &mut [Result<BoundBox<D2, Px>, SolveError>],
+        heap_cache: &mut UpdateHeapCache
+    ) {
+
         // We start out by setting the free space to its maximum possible value.
         let mut free_width = sub_px_bound_zero(self.desired_size.width(), self.grid_margins.width());
         let mut fr_total_width = 0.0;
@@ -165,16 +264,16 @@ impl GridEngine {
 
 
         self.actual_size_bounds.max =
-            self.desired_size_bounds.bound_rect(self.actual_size_bounds.max);
+            self.desired_size_bounds.clamp_dims(self.actual_size_bounds.max);
 
         self.actual_size_bounds.min = DimsBox::new2(
             frac_min_size.width() + rigid_min_size.width() + self.grid_margins.width(),
             frac_min_size.height() + rigid_min_size.height() + self.grid_margins.height()
         );
         self.actual_size_bounds.min =
-            self.desired_size_bounds.bound_rect(self.actual_size_bounds.min);
+            self.desired_size_bounds.clamp_dims(self.actual_size_bounds.min);
 
-        self.actual_size = self.actual_size_bounds.bound_rect(self.desired_size);
+        self.actual_size = self.actual_size_bounds.clamp_dims(self.desired_size);
 
         'update: loop {
             /// Macro for solving the track constraints independent of axis. Because each axis is
@@ -629,6 +728,7 @@ mod tests {
     use quickcheck::{Arbitrary, Gen};
     use cgmath_geometry::*;
     use crate::cgmath::Point2;
+    use derin_common_types::layout::WidgetSpan;
     use std::mem;
 
     quickcheck!{
@@ -659,6 +759,69 @@ mod tests {
         }
     }
 
+    #[test]
+    fn skip_unchanged_solve() {
+        let mut engine = GridEngine::new();
+        let mut heap_cache = UpdateHeapCache::new();
+        engine.desired_size = DimsBox::new2(64, 32);
+        engine.set_grid_size(GridSize::new(2, 1));
+
+        let mut hints = [
+            WidgetPos{ widget_span: WidgetSpan::new(0..1, 0..1), ..WidgetPos::default() },
+            WidgetPos{ widget_span: WidgetSpan::new(1..2, 0..1), ..WidgetPos::default() },
+        ];
+        let mut rects = [Ok(BoundBox::new2(0, 0, 0, 0)); 2];
+
+        engine.update_engine(&hints, &mut rects, &mut heap_cache);
+        assert_eq!(0, engine.solves_skipped());
+        let solved_rects = rects;
+
+        rects = [Ok(BoundBox::new2(0, 0, 0, 0)); 2];
+        engine.set_grid_size(GridSize::new(2, 1));
+        engine.update_engine(&hints, &mut rects, &mut heap_cache);
+        assert_eq!(1, engine.solves_skipped());
+        assert_eq!(solved_rects, rects);
+
+        hints[0].size_bounds.min = DimsBox::new2(48, 0);
+        engine.update_engine(&hints, &mut rects, &mut heap_cache);
+        assert_eq!(1, engine.solves_skipped());
+        assert_ne!(solved_rects, rects);
+
+        engine.set_col_hints(0, TrackHints{ fr_size: 2.0, ..TrackHints::default() });
+        engine.update_engine(&hints, &mut rects, &mut heap_cache);
+        assert_eq!(1, engine.solves_skipped());
+    }
+
+    #[test]
+    fn preferred_size() {
+        let mut engine = GridEngine::new();
+        let mut heap_cache = UpdateHeapCache::new();
+        engine.desired_size = DimsBox::new2(100, 10);
+        engine.set_grid_size(GridSize::new(2, 1));
+
+        let hints = [
+            WidgetPos {
+                size_bounds: SizeBounds::new_min(DimsBox::new2(10, 0)),
+                preferred_size: Some(DimsBox::new2(70, 0)),
+                widget_span: WidgetSpan::new(0..1, 0..1),
+                ..WidgetPos::default()
+            },
+            WidgetPos{ widget_span: WidgetSpan::new(1..2, 0..1), ..WidgetPos::default() },
+        ];
+        let mut rects = [Ok(BoundBox::new2(0, 0, 0, 0)); 2];
+
+        // There's room for the preferred size, so it's used.
+        engine.update_engine(&hints, &mut rects, &mut heap_cache);
+        assert_eq!(Ok(BoundBox::new2(0, 0, 70, 10)), rects[0]);
+        assert_eq!(10, engine.actual_size_bounds().min.width());
+
+        // There isn't, so the widget shrinks towards its real minimum.
+        engine.desired_size = DimsBox::new2(50, 10);
+        engine.update_engine(&hints, &mut rects, &mut heap_cache);
+        assert_eq!(Ok(BoundBox::new2(0, 0, 25, 10)), rects[0]);
+        assert_eq!(10, engine.actual_size_bounds().min.width());
+    }
+
     #[derive(Clone)]
     struct A<T>(T);
 